@@ -0,0 +1,84 @@
+//! Benchmarks a full tick of the A320 systems, running natively instead of in the simulator.
+//!
+//! After benchmarking, the percentiles of the duration of every phase of a tick, of the
+//! update of every system and of every element reading from and writing to the simulator
+//! are printed.
+use a320_systems::A320;
+use criterion::{criterion_group, Criterion};
+use std::time::Duration;
+use systems::simulation::{InMemoryReaderWriter, Simulation, VariableRegistry};
+
+const DELTA: Duration = Duration::from_millis(50);
+const PROFILED_TICKS: usize = 1000;
+
+fn cold_and_dark() -> InMemoryReaderWriter {
+    let mut reader_writer = InMemoryReaderWriter::new();
+    reader_writer.write_bool("SIM ON GROUND", true);
+    reader_writer.write_f64("AMBIENT TEMPERATURE", 15.);
+    reader_writer.write_f64("FUEL TANK LEFT MAIN QUANTITY", 3000.);
+    reader_writer.write_f64("FUEL TANK RIGHT MAIN QUANTITY", 3000.);
+    reader_writer.write_bool("OVHD_ELEC_BAT_10_PB_IS_AUTO", true);
+    reader_writer.write_bool("OVHD_ELEC_BAT_11_PB_IS_AUTO", true);
+
+    reader_writer
+}
+
+fn engines_running() -> InMemoryReaderWriter {
+    let mut reader_writer = cold_and_dark();
+    for number in 1..=2 {
+        reader_writer.write_f64(&format!("TURB ENG N1:{}", number), 20.);
+        reader_writer.write_f64(&format!("TURB ENG CORRECTED N2:{}", number), 70.);
+        reader_writer.write_f64(&format!("GENERAL ENG OIL PRESSURE:{}", number), 40.);
+        reader_writer.write_bool(&format!("OVHD_ELEC_ENG_GEN_{}_PB_IS_ON", number), true);
+    }
+
+    reader_writer
+}
+
+fn tick(c: &mut Criterion, name: &str, mut reader_writer: InMemoryReaderWriter) {
+    let mut a320 = A320::new(&mut VariableRegistry::new());
+    let mut simulation = Simulation::new_with_random_seed(&mut a320, &mut reader_writer, 1);
+
+    c.bench_function(name, |b| b.iter(|| simulation.tick(DELTA)));
+}
+
+fn a320_tick(c: &mut Criterion) {
+    tick(c, "cold and dark tick", cold_and_dark());
+    tick(c, "engines running tick", engines_running());
+}
+
+fn a320_tick_with_change_only_writes(c: &mut Criterion) {
+    let mut reader_writer = engines_running();
+    let mut registry = VariableRegistry::new();
+    let mut a320 = A320::new(&mut registry);
+    let mut simulation = Simulation::new_with_random_seed(&mut a320, &mut reader_writer, 1)
+        .with_change_only_writes(Duration::from_secs(1), &registry);
+
+    c.bench_function("engines running tick with change-only writes", |b| {
+        b.iter(|| simulation.tick(DELTA))
+    });
+}
+
+criterion_group!(benches, a320_tick, a320_tick_with_change_only_writes);
+
+fn print_profile() {
+    let mut reader_writer = engines_running();
+    let mut a320 = A320::new(&mut VariableRegistry::new());
+    let mut simulation = Simulation::new_with_random_seed(&mut a320, &mut reader_writer, 1)
+        .with_profiling(PROFILED_TICKS);
+
+    for _ in 0..PROFILED_TICKS {
+        simulation.tick(DELTA);
+    }
+
+    if let Some(report) = simulation.profile_report() {
+        println!("{}", report);
+    }
+}
+
+fn main() {
+    benches();
+    print_profile();
+
+    Criterion::default().configure_from_args().final_summary();
+}
@@ -0,0 +1,379 @@
+use systems::{
+    air_conditioning::{AirConditioningPack, TemperatureZone},
+    electrical::ElectricalBusType,
+    overhead::OnOffFaultPushButton,
+    simulation::{
+        QuantityIdentifier, SimulationElement, SimulationElementVisitor, SimulatorReader,
+        SimulatorWriter, UpdateContext, VariableRegistry, VariableUnit,
+    },
+};
+use uom::si::{f64::*, mass_rate::kilogram_per_second, thermodynamic_temperature::degree_celsius};
+
+/// The packs deliver air at the temperature demanded by the coldest zone. Hot trim air
+/// is added to the air supplied to the other zones to reach their demanded temperatures.
+pub struct A320AirConditioning {
+    pack_1: AirConditioningPack,
+    pack_2: AirConditioningPack,
+    cockpit: TemperatureZone,
+    forward_cabin: TemperatureZone,
+    aft_cabin: TemperatureZone,
+}
+impl A320AirConditioning {
+    const COCKPIT_FLOW_SHARE: f64 = 0.1;
+    const CABIN_FLOW_SHARE: f64 = 0.45;
+    const ZONE_CONTROLLER_GAIN: f64 = 10.;
+    const MINIMUM_DUCT_TEMPERATURE_CELSIUS: f64 = 2.;
+    const MAXIMUM_DUCT_TEMPERATURE_CELSIUS: f64 = 70.;
+
+    pub fn new(registry: &mut VariableRegistry) -> A320AirConditioning {
+        A320AirConditioning {
+            pack_1: AirConditioningPack::new(registry, 1, ElectricalBusType::DirectCurrent(1)),
+            pack_2: AirConditioningPack::new(registry, 2, ElectricalBusType::DirectCurrent(2)),
+            cockpit: TemperatureZone::new(registry, "CKPT", 50000., 10.),
+            forward_cabin: TemperatureZone::new(registry, "FWD", 300000., 50.),
+            aft_cabin: TemperatureZone::new(registry, "AFT", 300000., 50.),
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        context: &UpdateContext,
+        panel: &A320AirConditioningPanel,
+        pack_1_flow: MassRate,
+        pack_2_flow: MassRate,
+    ) {
+        let cockpit_demand =
+            A320AirConditioning::duct_temperature_demand(&self.cockpit, panel.cockpit_selected());
+        let forward_cabin_demand = A320AirConditioning::duct_temperature_demand(
+            &self.forward_cabin,
+            panel.forward_cabin_selected(),
+        );
+        let aft_cabin_demand = A320AirConditioning::duct_temperature_demand(
+            &self.aft_cabin,
+            panel.aft_cabin_selected(),
+        );
+
+        let pack_demand = cockpit_demand
+            .min(forward_cabin_demand)
+            .min(aft_cabin_demand);
+        self.pack_1.update(context, pack_1_flow, pack_demand);
+        self.pack_2.update(context, pack_2_flow, pack_demand);
+
+        let flow = self.pack_flow();
+        let mixed_temperature = self.mixed_temperature(context);
+        let duct_temperature = |demand: ThermodynamicTemperature| {
+            if panel.hot_air_is_on() && flow > MassRate::new::<kilogram_per_second>(0.) {
+                mixed_temperature.max(demand)
+            } else {
+                mixed_temperature
+            }
+        };
+
+        self.cockpit.update(
+            context,
+            flow * A320AirConditioning::COCKPIT_FLOW_SHARE,
+            duct_temperature(cockpit_demand),
+        );
+        self.forward_cabin.update(
+            context,
+            flow * A320AirConditioning::CABIN_FLOW_SHARE,
+            duct_temperature(forward_cabin_demand),
+        );
+        self.aft_cabin.update(
+            context,
+            flow * A320AirConditioning::CABIN_FLOW_SHARE,
+            duct_temperature(aft_cabin_demand),
+        );
+    }
+
+    /// The zone controller demands a duct temperature which brings
+    /// the zone towards the selected temperature.
+    fn duct_temperature_demand(
+        zone: &TemperatureZone,
+        selected: ThermodynamicTemperature,
+    ) -> ThermodynamicTemperature {
+        let selected = selected.get::<degree_celsius>();
+        ThermodynamicTemperature::new::<degree_celsius>(
+            (selected
+                + A320AirConditioning::ZONE_CONTROLLER_GAIN
+                    * (selected - zone.temperature().get::<degree_celsius>()))
+            .clamp(
+                A320AirConditioning::MINIMUM_DUCT_TEMPERATURE_CELSIUS,
+                A320AirConditioning::MAXIMUM_DUCT_TEMPERATURE_CELSIUS,
+            ),
+        )
+    }
+
+    /// The temperature of the air leaving the mixer unit.
+    fn mixed_temperature(&self, context: &UpdateContext) -> ThermodynamicTemperature {
+        let flow = self.pack_flow();
+        if flow > MassRate::new::<kilogram_per_second>(0.) {
+            ThermodynamicTemperature::new::<degree_celsius>(
+                (self.pack_1.outlet_temperature().get::<degree_celsius>()
+                    * self.pack_1.flow().get::<kilogram_per_second>()
+                    + self.pack_2.outlet_temperature().get::<degree_celsius>()
+                        * self.pack_2.flow().get::<kilogram_per_second>())
+                    / flow.get::<kilogram_per_second>(),
+            )
+        } else {
+            context.ambient_temperature()
+        }
+    }
+
+    /// The total flow of conditioned air supplied to the cabin.
+    pub fn pack_flow(&self) -> MassRate {
+        self.pack_1.flow() + self.pack_2.flow()
+    }
+}
+impl SimulationElement for A320AirConditioning {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.pack_1.accept(visitor);
+        self.pack_2.accept(visitor);
+        self.cockpit.accept(visitor);
+        self.forward_cabin.accept(visitor);
+        self.aft_cabin.accept(visitor);
+
+        visitor.visit(self);
+    }
+}
+
+/// The zone temperature selectors and HOT AIR push button on the AIR COND panel.
+/// The PACK push buttons are part of the [`A320PneumaticOverheadPanel`], as they
+/// control the pack flow control valves.
+///
+/// [`A320PneumaticOverheadPanel`]: ../pneumatic/struct.A320PneumaticOverheadPanel.html
+pub struct A320AirConditioningPanel {
+    cockpit_selector_id: QuantityIdentifier<ThermodynamicTemperature>,
+    forward_cabin_selector_id: QuantityIdentifier<ThermodynamicTemperature>,
+    aft_cabin_selector_id: QuantityIdentifier<ThermodynamicTemperature>,
+    cockpit_selected: ThermodynamicTemperature,
+    forward_cabin_selected: ThermodynamicTemperature,
+    aft_cabin_selected: ThermodynamicTemperature,
+    hot_air: OnOffFaultPushButton,
+}
+impl A320AirConditioningPanel {
+    const DEFAULT_SELECTED_TEMPERATURE_CELSIUS: f64 = 24.;
+
+    pub fn new(registry: &mut VariableRegistry) -> A320AirConditioningPanel {
+        let selected = ThermodynamicTemperature::new::<degree_celsius>(
+            A320AirConditioningPanel::DEFAULT_SELECTED_TEMPERATURE_CELSIUS,
+        );
+
+        A320AirConditioningPanel {
+            cockpit_selector_id: registry
+                .register_read_write("OVHD_COND_CKPT_SELECTED_TEMPERATURE", VariableUnit::Celsius),
+            forward_cabin_selector_id: registry
+                .register_read_write("OVHD_COND_FWD_SELECTED_TEMPERATURE", VariableUnit::Celsius),
+            aft_cabin_selector_id: registry
+                .register_read_write("OVHD_COND_AFT_SELECTED_TEMPERATURE", VariableUnit::Celsius),
+            cockpit_selected: selected,
+            forward_cabin_selected: selected,
+            aft_cabin_selected: selected,
+            hot_air: OnOffFaultPushButton::new_on(registry, "COND_HOT_AIR"),
+        }
+    }
+
+    pub fn cockpit_selected(&self) -> ThermodynamicTemperature {
+        self.cockpit_selected
+    }
+
+    pub fn forward_cabin_selected(&self) -> ThermodynamicTemperature {
+        self.forward_cabin_selected
+    }
+
+    pub fn aft_cabin_selected(&self) -> ThermodynamicTemperature {
+        self.aft_cabin_selected
+    }
+
+    pub fn hot_air_is_on(&self) -> bool {
+        self.hot_air.is_on()
+    }
+}
+impl SimulationElement for A320AirConditioningPanel {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.hot_air.accept(visitor);
+
+        visitor.visit(self);
+    }
+
+    fn read(&mut self, reader: &mut SimulatorReader) {
+        self.cockpit_selected = reader.read_temperature(&self.cockpit_selector_id);
+        self.forward_cabin_selected = reader.read_temperature(&self.forward_cabin_selector_id);
+        self.aft_cabin_selected = reader.read_temperature(&self.aft_cabin_selector_id);
+    }
+
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_temperature(&self.cockpit_selector_id, self.cockpit_selected);
+        writer.write_temperature(&self.forward_cabin_selector_id, self.forward_cabin_selected);
+        writer.write_temperature(&self.aft_cabin_selector_id, self.aft_cabin_selected);
+    }
+}
+
+#[cfg(test)]
+mod a320_air_conditioning_tests {
+    use super::*;
+    use std::time::Duration;
+    use systems::{
+        electrical::{consumption::SuppliedPower, Potential, PotentialOrigin},
+        simulation::{test::SimulationTestBed, Aircraft},
+    };
+    use uom::si::electric_potential::volt;
+
+    struct A320AirConditioningTestAircraft {
+        air_conditioning: A320AirConditioning,
+        panel: A320AirConditioningPanel,
+        pack_flow: MassRate,
+    }
+    impl A320AirConditioningTestAircraft {
+        fn new() -> Self {
+            let mut registry = VariableRegistry::new();
+
+            Self {
+                air_conditioning: A320AirConditioning::new(&mut registry),
+                panel: A320AirConditioningPanel::new(&mut registry),
+                pack_flow: MassRate::new::<kilogram_per_second>(0.4),
+            }
+        }
+    }
+    impl Aircraft for A320AirConditioningTestAircraft {
+        fn update_after_power_distribution(&mut self, context: &UpdateContext) {
+            self.air_conditioning
+                .update(context, &self.panel, self.pack_flow, self.pack_flow);
+        }
+
+        fn get_supplied_power(&mut self) -> SuppliedPower {
+            let mut supplied_power = SuppliedPower::new();
+            let dc = Potential::single(
+                PotentialOrigin::Battery(10),
+                ElectricPotential::new::<volt>(28.),
+            );
+            supplied_power.add(ElectricalBusType::DirectCurrent(1), dc);
+            supplied_power.add(ElectricalBusType::DirectCurrent(2), dc);
+
+            supplied_power
+        }
+    }
+    impl SimulationElement for A320AirConditioningTestAircraft {
+        fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            self.air_conditioning.accept(visitor);
+            self.panel.accept(visitor);
+
+            visitor.visit(self);
+        }
+    }
+
+    struct A320AirConditioningTestBed {
+        aircraft: A320AirConditioningTestAircraft,
+        simulation_test_bed: SimulationTestBed,
+    }
+    impl A320AirConditioningTestBed {
+        fn new() -> Self {
+            let mut aircraft = A320AirConditioningTestAircraft::new();
+            let mut simulation_test_bed = SimulationTestBed::seeded_with(&mut aircraft);
+            simulation_test_bed.set_delta(Duration::from_secs(1));
+
+            Self {
+                aircraft,
+                simulation_test_bed,
+            }
+        }
+
+        fn run_for(mut self, seconds: u64) -> Self {
+            for _ in 0..seconds {
+                self.simulation_test_bed.run_aircraft(&mut self.aircraft);
+            }
+
+            self
+        }
+
+        fn packs_off(mut self) -> Self {
+            self.aircraft.pack_flow = MassRate::new::<kilogram_per_second>(0.);
+            self
+        }
+
+        fn hot_air_off(mut self) -> Self {
+            self.simulation_test_bed
+                .write_bool("OVHD_COND_HOT_AIR_PB_IS_ON", false);
+            self
+        }
+
+        fn cockpit_selected(mut self, celsius: f64) -> Self {
+            self.simulation_test_bed
+                .write_f64("OVHD_COND_CKPT_SELECTED_TEMPERATURE", celsius);
+            self
+        }
+
+        fn cockpit_temperature(&self) -> ThermodynamicTemperature {
+            self.aircraft.air_conditioning.cockpit.temperature()
+        }
+
+        fn forward_cabin_temperature(&self) -> ThermodynamicTemperature {
+            self.aircraft.air_conditioning.forward_cabin.temperature()
+        }
+
+        fn aft_cabin_temperature(&self) -> ThermodynamicTemperature {
+            self.aircraft.air_conditioning.aft_cabin.temperature()
+        }
+    }
+
+    fn test_bed() -> A320AirConditioningTestBed {
+        A320AirConditioningTestBed::new()
+    }
+
+    fn is_near(temperature: ThermodynamicTemperature, celsius: f64) -> bool {
+        (temperature.get::<degree_celsius>() - celsius).abs() < 1.
+    }
+
+    #[test]
+    fn zones_reach_selected_temperature() {
+        let test_bed = test_bed().run_for(3600);
+
+        assert!(is_near(test_bed.cockpit_temperature(), 24.));
+        assert!(is_near(test_bed.forward_cabin_temperature(), 24.));
+        assert!(is_near(test_bed.aft_cabin_temperature(), 24.));
+    }
+
+    #[test]
+    fn zones_are_controlled_individually() {
+        let test_bed = test_bed().cockpit_selected(18.).run_for(3600);
+
+        assert!(is_near(test_bed.cockpit_temperature(), 18.));
+        assert!(is_near(test_bed.forward_cabin_temperature(), 24.));
+    }
+
+    #[test]
+    fn without_hot_air_all_zones_are_supplied_at_pack_outlet_temperature() {
+        let test_bed = test_bed().cockpit_selected(18.).hot_air_off().run_for(3600);
+
+        assert!(
+            test_bed.forward_cabin_temperature()
+                < ThermodynamicTemperature::new::<degree_celsius>(23.)
+        );
+        assert_eq!(
+            test_bed
+                .aircraft
+                .air_conditioning
+                .forward_cabin
+                .duct_temperature(),
+            test_bed
+                .aircraft
+                .air_conditioning
+                .cockpit
+                .duct_temperature()
+        );
+    }
+
+    #[test]
+    fn zones_cool_down_without_pack_flow() {
+        let test_bed = test_bed().packs_off().run_for(3600);
+
+        assert!(
+            test_bed.aircraft.air_conditioning.pack_flow()
+                == MassRate::new::<kilogram_per_second>(0.)
+        );
+        assert!(
+            test_bed.forward_cabin_temperature()
+                < ThermodynamicTemperature::new::<degree_celsius>(10.)
+        );
+    }
+}
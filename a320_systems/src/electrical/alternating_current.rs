@@ -10,7 +10,7 @@ use systems::{
         TransformerRectifier,
     },
     shared::DelayedTrueLogicGate,
-    simulation::{SimulationElement, SimulationElementVisitor, UpdateContext},
+    simulation::{SimulationElement, SimulationElementVisitor, UpdateContext, VariableRegistry},
 };
 use uom::si::{f64::*, velocity::knot};
 
@@ -32,23 +32,30 @@ pub(super) struct A320AlternatingCurrentElectrical {
     ac_stat_inv_bus: ElectricalBus,
 }
 impl A320AlternatingCurrentElectrical {
-    pub fn new() -> Self {
+    pub fn new(registry: &mut VariableRegistry) -> Self {
         A320AlternatingCurrentElectrical {
-            main_power_sources: A320MainPowerSources::new(),
-            ac_ess_feed_contactors: A320AcEssFeedContactors::new(),
-            ac_bus_1: ElectricalBus::new(ElectricalBusType::AlternatingCurrent(1)),
-            ac_bus_2: ElectricalBus::new(ElectricalBusType::AlternatingCurrent(2)),
-            ac_ess_bus: ElectricalBus::new(ElectricalBusType::AlternatingCurrentEssential),
-            ac_ess_shed_bus: ElectricalBus::new(ElectricalBusType::AlternatingCurrentEssentialShed),
-            ac_ess_shed_contactor: Contactor::new("8XH"),
-            tr_1: TransformerRectifier::new(1),
-            tr_2: TransformerRectifier::new(2),
-            tr_ess: TransformerRectifier::new(3),
-            ac_ess_to_tr_ess_contactor: Contactor::new("15XE1"),
-            emergency_gen: EmergencyGenerator::new(),
-            emergency_gen_contactor: Contactor::new("2XE"),
-            static_inv_to_ac_ess_bus_contactor: Contactor::new("15XE2"),
+            main_power_sources: A320MainPowerSources::new(registry),
+            ac_ess_feed_contactors: A320AcEssFeedContactors::new(registry),
+            ac_bus_1: ElectricalBus::new(registry, ElectricalBusType::AlternatingCurrent(1)),
+            ac_bus_2: ElectricalBus::new(registry, ElectricalBusType::AlternatingCurrent(2)),
+            ac_ess_bus: ElectricalBus::new(
+                registry,
+                ElectricalBusType::AlternatingCurrentEssential,
+            ),
+            ac_ess_shed_bus: ElectricalBus::new(
+                registry,
+                ElectricalBusType::AlternatingCurrentEssentialShed,
+            ),
+            ac_ess_shed_contactor: Contactor::new(registry, "8XH"),
+            tr_1: TransformerRectifier::new(registry, 1),
+            tr_2: TransformerRectifier::new(registry, 2),
+            tr_ess: TransformerRectifier::new(registry, 3),
+            ac_ess_to_tr_ess_contactor: Contactor::new(registry, "15XE1"),
+            emergency_gen: EmergencyGenerator::new(registry),
+            emergency_gen_contactor: Contactor::new(registry, "2XE"),
+            static_inv_to_ac_ess_bus_contactor: Contactor::new(registry, "15XE2"),
             ac_stat_inv_bus: ElectricalBus::new(
+                registry,
                 ElectricalBusType::AlternatingCurrentStaticInverter,
             ),
         }
@@ -61,11 +68,8 @@ impl A320AlternatingCurrentElectrical {
         overhead: &A320ElectricalOverheadPanel,
         arguments: &mut A320ElectricalUpdateArguments<'a>,
     ) {
-        self.emergency_gen.update(
-            context,
-            arguments.is_blue_hydraulic_circuit_pressurised()
-                && context.indicated_airspeed() > Velocity::new::<knot>(100.),
-        );
+        self.emergency_gen
+            .update(context, arguments.blue_hydraulic_circuit_pressure());
 
         self.main_power_sources
             .update(context, ext_pwr, overhead, arguments);
@@ -132,7 +136,7 @@ impl A320AlternatingCurrentElectrical {
     fn update_shedding(&mut self) {
         let ac_bus_or_emergency_gen_provides_power = self.ac_bus_1.is_powered()
             || self.ac_bus_2.is_powered()
-            || self.emergency_gen.is_powered();
+            || self.emergency_gen_contactor.is_powered();
         self.ac_ess_shed_contactor
             .close_when(ac_bus_or_emergency_gen_provides_power);
 
@@ -229,21 +233,15 @@ impl A320AlternatingCurrentElectrical {
         &self.ac_stat_inv_bus
     }
 
-    #[cfg(test)]
-    pub fn fail_tr_1(&mut self) {
-        self.tr_1.fail();
-    }
-
-    #[cfg(test)]
-    pub fn fail_tr_2(&mut self) {
-        self.tr_2.fail();
-    }
-
     #[cfg(test)]
     pub fn attempt_emergency_gen_start(&mut self) {
         self.emergency_gen.start();
     }
 
+    pub fn emergency_generator_hydraulic_flow_demand(&self) -> VolumeRate {
+        self.emergency_gen.hydraulic_flow_demand()
+    }
+
     pub fn gen_1_contactor_open(&self) -> bool {
         self.main_power_sources.gen_1_contactor_open()
     }
@@ -262,7 +260,7 @@ impl AlternatingCurrentState for A320AlternatingCurrentElectrical {
     }
 
     fn ac_1_and_2_and_emergency_gen_unpowered(&self) -> bool {
-        self.main_ac_buses_unpowered() && self.emergency_gen.is_unpowered()
+        self.main_ac_buses_unpowered() && self.emergency_gen_contactor.is_unpowered()
     }
 
     fn ac_1_and_2_and_emergency_gen_unpowered_and_velocity_equal_to_or_greater_than_50_knots(
@@ -286,7 +284,7 @@ impl AlternatingCurrentState for A320AlternatingCurrentElectrical {
     }
 
     fn emergency_generator_available(&self) -> bool {
-        self.emergency_gen.is_powered()
+        self.emergency_gen_contactor.is_powered()
     }
 }
 impl SimulationElement for A320AlternatingCurrentElectrical {
@@ -324,16 +322,16 @@ struct A320MainPowerSources {
     ext_pwr_contactor: Contactor,
 }
 impl A320MainPowerSources {
-    fn new() -> Self {
+    fn new(registry: &mut VariableRegistry) -> Self {
         A320MainPowerSources {
-            engine_1_gen: EngineGenerator::new(1),
-            engine_1_gen_contactor: Contactor::new("9XU1"),
-            engine_2_gen: EngineGenerator::new(2),
-            engine_2_gen_contactor: Contactor::new("9XU2"),
-            bus_tie_1_contactor: Contactor::new("11XU1"),
-            bus_tie_2_contactor: Contactor::new("11XU2"),
-            apu_gen_contactor: Contactor::new("3XS"),
-            ext_pwr_contactor: Contactor::new("3XG"),
+            engine_1_gen: EngineGenerator::new(registry, 1),
+            engine_1_gen_contactor: Contactor::new(registry, "9XU1"),
+            engine_2_gen: EngineGenerator::new(registry, 2),
+            engine_2_gen_contactor: Contactor::new(registry, "9XU2"),
+            bus_tie_1_contactor: Contactor::new(registry, "11XU1"),
+            bus_tie_2_contactor: Contactor::new(registry, "11XU2"),
+            apu_gen_contactor: Contactor::new(registry, "3XS"),
+            ext_pwr_contactor: Contactor::new(registry, "3XG"),
         }
     }
 
@@ -344,8 +342,10 @@ impl A320MainPowerSources {
         overhead: &A320ElectricalOverheadPanel,
         arguments: &mut A320ElectricalUpdateArguments<'a>,
     ) {
-        self.engine_1_gen.update(context, arguments);
-        self.engine_2_gen.update(context, arguments);
+        self.engine_1_gen
+            .update(context, arguments.engine(1), arguments);
+        self.engine_2_gen
+            .update(context, arguments.engine(2), arguments);
 
         let gen_1_provides_power =
             overhead.generator_1_is_on() && self.engine_1_gen.output_within_normal_parameters();
@@ -447,10 +447,10 @@ pub(super) struct A320AcEssFeedContactors {
 impl A320AcEssFeedContactors {
     pub const AC_ESS_FEED_TO_AC_BUS_2_DELAY_IN_SECONDS: Duration = Duration::from_secs(3);
 
-    fn new() -> Self {
+    fn new(registry: &mut VariableRegistry) -> Self {
         A320AcEssFeedContactors {
-            ac_ess_feed_contactor_1: Contactor::new("3XC1"),
-            ac_ess_feed_contactor_2: Contactor::new("3XC2"),
+            ac_ess_feed_contactor_1: Contactor::new(registry, "3XC1"),
+            ac_ess_feed_contactor_2: Contactor::new(registry, "3XC2"),
             ac_ess_feed_contactor_delay_logic_gate: DelayedTrueLogicGate::new(
                 A320AcEssFeedContactors::AC_ESS_FEED_TO_AC_BUS_2_DELAY_IN_SECONDS,
             ),
@@ -9,7 +9,7 @@ use systems::{
         Battery, BatteryChargeLimiter, BatteryChargeLimiterArguments, Contactor, ElectricalBus,
         ElectricalBusType, PotentialSource, PotentialTarget, StaticInverter,
     },
-    simulation::{SimulationElement, SimulationElementVisitor, UpdateContext},
+    simulation::{SimulationElement, SimulationElementVisitor, UpdateContext, VariableRegistry},
 };
 use uom::si::{f64::*, velocity::knot};
 
@@ -40,32 +40,35 @@ pub(super) struct A320DirectCurrentElectrical {
     apu_start_contactors: Contactor,
 }
 impl A320DirectCurrentElectrical {
-    pub fn new() -> Self {
+    pub fn new(registry: &mut VariableRegistry) -> Self {
         A320DirectCurrentElectrical {
-            dc_bus_1: ElectricalBus::new(ElectricalBusType::DirectCurrent(1)),
-            dc_bus_1_tie_contactor: Contactor::new("1PC1"),
-            dc_bus_2: ElectricalBus::new(ElectricalBusType::DirectCurrent(2)),
-            dc_bus_2_tie_contactor: Contactor::new("1PC2"),
-            dc_bat_bus: ElectricalBus::new(ElectricalBusType::DirectCurrentBattery),
-            dc_ess_bus: ElectricalBus::new(ElectricalBusType::DirectCurrentEssential),
-            dc_bat_bus_to_dc_ess_bus_contactor: Contactor::new("4PC"),
-            dc_ess_shed_bus: ElectricalBus::new(ElectricalBusType::DirectCurrentEssentialShed),
-            dc_ess_shed_contactor: Contactor::new("8PH"),
-            battery_1: Battery::full(10),
-            battery_1_contactor: Contactor::new("6PB1"),
-            battery_1_charge_limiter: BatteryChargeLimiter::new("6PB1"),
-            battery_2: Battery::full(11),
-            battery_2_contactor: Contactor::new("6PB2"),
-            battery_2_charge_limiter: BatteryChargeLimiter::new("6PB2"),
-            hot_bus_2_to_dc_ess_bus_contactor: Contactor::new("2XB2"),
-            hot_bus_1_to_static_inv_contactor: Contactor::new("2XB1"),
-            static_inverter: StaticInverter::new(),
-            hot_bus_1: ElectricalBus::new(ElectricalBusType::DirectCurrentHot(1)),
-            hot_bus_2: ElectricalBus::new(ElectricalBusType::DirectCurrentHot(2)),
-            tr_1_contactor: Contactor::new("5PU1"),
-            tr_2_contactor: Contactor::new("5PU2"),
-            tr_ess_contactor: Contactor::new("3PE"),
-            apu_start_contactors: Contactor::new("10KA_AND_5KA"),
+            dc_bus_1: ElectricalBus::new(registry, ElectricalBusType::DirectCurrent(1)),
+            dc_bus_1_tie_contactor: Contactor::new(registry, "1PC1"),
+            dc_bus_2: ElectricalBus::new(registry, ElectricalBusType::DirectCurrent(2)),
+            dc_bus_2_tie_contactor: Contactor::new(registry, "1PC2"),
+            dc_bat_bus: ElectricalBus::new(registry, ElectricalBusType::DirectCurrentBattery),
+            dc_ess_bus: ElectricalBus::new(registry, ElectricalBusType::DirectCurrentEssential),
+            dc_bat_bus_to_dc_ess_bus_contactor: Contactor::new(registry, "4PC"),
+            dc_ess_shed_bus: ElectricalBus::new(
+                registry,
+                ElectricalBusType::DirectCurrentEssentialShed,
+            ),
+            dc_ess_shed_contactor: Contactor::new(registry, "8PH"),
+            battery_1: Battery::full(registry, 10),
+            battery_1_contactor: Contactor::new(registry, "6PB1"),
+            battery_1_charge_limiter: BatteryChargeLimiter::new(registry, "6PB1"),
+            battery_2: Battery::full(registry, 11),
+            battery_2_contactor: Contactor::new(registry, "6PB2"),
+            battery_2_charge_limiter: BatteryChargeLimiter::new(registry, "6PB2"),
+            hot_bus_2_to_dc_ess_bus_contactor: Contactor::new(registry, "2XB2"),
+            hot_bus_1_to_static_inv_contactor: Contactor::new(registry, "2XB1"),
+            static_inverter: StaticInverter::new(registry),
+            hot_bus_1: ElectricalBus::new(registry, ElectricalBusType::DirectCurrentHot(1)),
+            hot_bus_2: ElectricalBus::new(registry, ElectricalBusType::DirectCurrentHot(2)),
+            tr_1_contactor: Contactor::new(registry, "5PU1"),
+            tr_2_contactor: Contactor::new(registry, "5PU2"),
+            tr_ess_contactor: Contactor::new(registry, "3PE"),
+            apu_start_contactors: Contactor::new(registry, "10KA_AND_5KA"),
         }
     }
 
@@ -292,16 +295,6 @@ impl A320DirectCurrentElectrical {
     pub fn battery_2_input_potential(&self) -> Potential {
         self.battery_2.input_potential()
     }
-
-    #[cfg(test)]
-    pub fn empty_battery_1(&mut self) {
-        self.battery_1 = Battery::empty(1);
-    }
-
-    #[cfg(test)]
-    pub fn empty_battery_2(&mut self) {
-        self.battery_2 = Battery::empty(2);
-    }
 }
 impl DirectCurrentState for A320DirectCurrentElectrical {
     fn static_inverter(&self) -> &StaticInverter {
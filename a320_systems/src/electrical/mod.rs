@@ -13,45 +13,53 @@ use systems::{
         EngineGeneratorUpdateArguments, ExternalPowerSource, Potential, PotentialSource,
         StaticInverter, TransformerRectifier,
     },
+    engine::Engine,
     overhead::{
         AutoOffFaultPushButton, FaultReleasePushButton, NormalAltnFaultPushButton,
         OnOffAvailablePushButton, OnOffFaultPushButton,
     },
     shared::AuxiliaryPowerUnitElectrical,
-    simulation::{SimulationElement, SimulationElementVisitor, SimulatorWriter, UpdateContext},
+    simulation::{
+        QuantityIdentifier, SimulationElement, SimulationElementVisitor, SimulatorWriter,
+        UpdateContext, VariableRegistry, VariableUnit,
+    },
 };
 use uom::si::f64::*;
 
 pub(super) struct A320ElectricalUpdateArguments<'a> {
-    engine_corrected_n2: [Ratio; 2],
+    engines: [&'a Engine; 2],
     idg_push_buttons_released: [bool; 2],
     apu: &'a mut dyn AuxiliaryPowerUnitElectrical,
-    is_blue_hydraulic_circuit_pressurised: bool,
+    blue_hydraulic_circuit_pressure: Pressure,
     apu_master_sw_pb_on: bool,
     apu_start_pb_on: bool,
     landing_gear_is_up_and_locked: bool,
 }
 impl<'a> A320ElectricalUpdateArguments<'a> {
     pub fn new(
-        engine_corrected_n2: [Ratio; 2],
+        engines: [&'a Engine; 2],
         idg_push_buttons_released: [bool; 2],
         apu: &'a mut dyn AuxiliaryPowerUnitElectrical,
-        is_blue_hydraulic_circuit_pressurised: bool,
+        blue_hydraulic_circuit_pressure: Pressure,
         apu_master_sw_pb_on: bool,
         apu_start_pb_on: bool,
         landing_gear_is_up_and_locked: bool,
     ) -> Self {
         Self {
-            engine_corrected_n2,
+            engines,
             idg_push_buttons_released,
             apu,
-            is_blue_hydraulic_circuit_pressurised,
+            blue_hydraulic_circuit_pressure,
             apu_master_sw_pb_on,
             apu_start_pb_on,
             landing_gear_is_up_and_locked,
         }
     }
 
+    fn engine(&self, number: usize) -> &'a Engine {
+        self.engines[number - 1]
+    }
+
     fn apu(&mut self) -> &mut dyn AuxiliaryPowerUnitElectrical {
         self.apu
     }
@@ -76,8 +84,8 @@ impl<'a> A320ElectricalUpdateArguments<'a> {
         self.apu.is_available()
     }
 
-    fn is_blue_hydraulic_circuit_pressurised(&self) -> bool {
-        self.is_blue_hydraulic_circuit_pressurised
+    fn blue_hydraulic_circuit_pressure(&self) -> Pressure {
+        self.blue_hydraulic_circuit_pressure
     }
 
     fn landing_gear_is_up_and_locked(&self) -> bool {
@@ -85,26 +93,24 @@ impl<'a> A320ElectricalUpdateArguments<'a> {
     }
 }
 impl<'a> EngineGeneratorUpdateArguments for A320ElectricalUpdateArguments<'a> {
-    fn engine_corrected_n2(&self, number: usize) -> Ratio {
-        self.engine_corrected_n2[number - 1]
-    }
-
     fn idg_push_button_released(&self, number: usize) -> bool {
         self.idg_push_buttons_released[number - 1]
     }
 }
 
 pub(super) struct A320Electrical {
+    galley_is_shed_id: QuantityIdentifier<bool>,
     alternating_current: A320AlternatingCurrentElectrical,
     direct_current: A320DirectCurrentElectrical,
     main_galley: MainGalley,
     secondary_galley: SecondaryGalley,
 }
 impl A320Electrical {
-    pub fn new() -> A320Electrical {
+    pub fn new(registry: &mut VariableRegistry) -> A320Electrical {
         A320Electrical {
-            alternating_current: A320AlternatingCurrentElectrical::new(),
-            direct_current: A320DirectCurrentElectrical::new(),
+            galley_is_shed_id: registry.register_write("ELEC_GALLEY_IS_SHED", VariableUnit::Bool),
+            alternating_current: A320AlternatingCurrentElectrical::new(registry),
+            direct_current: A320DirectCurrentElectrical::new(registry),
             main_galley: MainGalley::new(),
             secondary_galley: SecondaryGalley::new(),
         }
@@ -195,16 +201,6 @@ impl A320Electrical {
         self.direct_current.debug_assert_invariants();
     }
 
-    #[cfg(test)]
-    fn fail_tr_1(&mut self) {
-        self.alternating_current.fail_tr_1();
-    }
-
-    #[cfg(test)]
-    fn fail_tr_2(&mut self) {
-        self.alternating_current.fail_tr_2();
-    }
-
     #[cfg(test)]
     fn attempt_emergency_gen_start(&mut self) {
         self.alternating_current.attempt_emergency_gen_start();
@@ -235,14 +231,9 @@ impl A320Electrical {
         self.direct_current.battery_2_input_potential()
     }
 
-    #[cfg(test)]
-    pub fn empty_battery_1(&mut self) {
-        self.direct_current.empty_battery_1();
-    }
-
-    #[cfg(test)]
-    pub fn empty_battery_2(&mut self) {
-        self.direct_current.empty_battery_2();
+    pub fn emergency_generator_hydraulic_flow_demand(&self) -> VolumeRate {
+        self.alternating_current
+            .emergency_generator_hydraulic_flow_demand()
     }
 
     pub fn gen_1_contactor_open(&self) -> bool {
@@ -280,7 +271,7 @@ impl SimulationElement for A320Electrical {
     }
 
     fn write(&self, writer: &mut SimulatorWriter) {
-        writer.write_bool("ELEC_GALLEY_IS_SHED", self.galley_is_shed())
+        writer.write_bool(&self.galley_is_shed_id, self.galley_is_shed())
     }
 }
 
@@ -317,20 +308,20 @@ pub(super) struct A320ElectricalOverheadPanel {
     commercial: OnOffFaultPushButton,
 }
 impl A320ElectricalOverheadPanel {
-    pub fn new() -> A320ElectricalOverheadPanel {
+    pub fn new(registry: &mut VariableRegistry) -> A320ElectricalOverheadPanel {
         A320ElectricalOverheadPanel {
-            bat_1: AutoOffFaultPushButton::new_auto("ELEC_BAT_10"),
-            bat_2: AutoOffFaultPushButton::new_auto("ELEC_BAT_11"),
-            idg_1: FaultReleasePushButton::new_in("ELEC_IDG_1"),
-            idg_2: FaultReleasePushButton::new_in("ELEC_IDG_2"),
-            gen_1: OnOffFaultPushButton::new_on("ELEC_ENG_GEN_1"),
-            gen_2: OnOffFaultPushButton::new_on("ELEC_ENG_GEN_2"),
-            apu_gen: OnOffFaultPushButton::new_on("ELEC_APU_GEN"),
-            bus_tie: AutoOffFaultPushButton::new_auto("ELEC_BUS_TIE"),
-            ac_ess_feed: NormalAltnFaultPushButton::new_normal("ELEC_AC_ESS_FEED"),
-            galy_and_cab: AutoOffFaultPushButton::new_auto("ELEC_GALY_AND_CAB"),
-            ext_pwr: OnOffAvailablePushButton::new_off("ELEC_EXT_PWR"),
-            commercial: OnOffFaultPushButton::new_on("ELEC_COMMERCIAL"),
+            bat_1: AutoOffFaultPushButton::new_auto(registry, "ELEC_BAT_10"),
+            bat_2: AutoOffFaultPushButton::new_auto(registry, "ELEC_BAT_11"),
+            idg_1: FaultReleasePushButton::new_in(registry, "ELEC_IDG_1"),
+            idg_2: FaultReleasePushButton::new_in(registry, "ELEC_IDG_2"),
+            gen_1: OnOffFaultPushButton::new_on(registry, "ELEC_ENG_GEN_1"),
+            gen_2: OnOffFaultPushButton::new_on(registry, "ELEC_ENG_GEN_2"),
+            apu_gen: OnOffFaultPushButton::new_on(registry, "ELEC_APU_GEN"),
+            bus_tie: AutoOffFaultPushButton::new_auto(registry, "ELEC_BUS_TIE"),
+            ac_ess_feed: NormalAltnFaultPushButton::new_normal(registry, "ELEC_AC_ESS_FEED"),
+            galy_and_cab: AutoOffFaultPushButton::new_auto(registry, "ELEC_GALY_AND_CAB"),
+            ext_pwr: OnOffAvailablePushButton::new_off(registry, "ELEC_EXT_PWR"),
+            commercial: OnOffFaultPushButton::new_on(registry, "ELEC_COMMERCIAL"),
         }
     }
 
@@ -426,7 +417,7 @@ mod a320_electrical {
 
     #[test]
     fn writes_its_state() {
-        let mut elec = A320Electrical::new();
+        let mut elec = A320Electrical::new(&mut VariableRegistry::new());
         let mut test_bed = SimulationTestBed::new();
         test_bed.run_without_update(&mut elec);
 
@@ -437,7 +428,7 @@ mod a320_electrical {
 #[cfg(test)]
 mod a320_electrical_circuit_tests {
     use std::time::Duration;
-    use uom::si::{electric_potential::volt, ratio::percent};
+    use uom::si::{electric_potential::volt, pressure::psi};
 
     use super::alternating_current::A320AcEssFeedContactors;
     use super::*;
@@ -447,7 +438,7 @@ mod a320_electrical_circuit_tests {
             INTEGRATED_DRIVE_GENERATOR_STABILIZATION_TIME_IN_MILLISECONDS,
         },
         shared::ApuStartContactorsController,
-        simulation::{test::SimulationTestBed, Aircraft},
+        simulation::{test::SimulationTestBed, Aircraft, FailureType, Snapshot},
     };
     use uom::si::{length::foot, velocity::knot};
 
@@ -1434,6 +1425,90 @@ mod a320_electrical_circuit_tests {
         assert!(test_bed.ac_bus_2_output().is_unpowered());
     }
 
+    #[test]
+    fn when_engines_running_but_engine_gen_1_failed_engine_gen_2_powers_ac_bus_1_and_2() {
+        let mut test_bed = test_bed_with()
+            .running_engines()
+            .and()
+            .failed(FailureType::EngineGenerator(1))
+            .run();
+
+        assert!(test_bed
+            .ac_bus_1_output()
+            .is_single(PotentialOrigin::EngineGenerator(2)));
+        assert!(test_bed
+            .ac_bus_2_output()
+            .is_single(PotentialOrigin::EngineGenerator(2)));
+    }
+
+    #[test]
+    fn cleared_engine_gen_failure_restores_normal_configuration() {
+        let mut test_bed = test_bed_with()
+            .running_engines()
+            .and()
+            .failed(FailureType::EngineGenerator(1))
+            .run()
+            .then_continue_with()
+            .cleared(FailureType::EngineGenerator(1))
+            .run_waiting_for(Duration::from_millis(
+                INTEGRATED_DRIVE_GENERATOR_STABILIZATION_TIME_IN_MILLISECONDS,
+            ));
+
+        assert!(test_bed
+            .ac_bus_1_output()
+            .is_single(PotentialOrigin::EngineGenerator(1)));
+    }
+
+    #[test]
+    fn failed_battery_does_not_power_its_hot_bus() {
+        let mut test_bed = test_bed_with()
+            .bat_1_off()
+            .bat_2_off()
+            .and()
+            .failed(FailureType::Battery(10))
+            .airspeed(Velocity::new::<knot>(0.))
+            .run();
+
+        assert!(test_bed.hot_bus_1_output().is_unpowered());
+        assert!(test_bed
+            .hot_bus_2_output()
+            .is_single(PotentialOrigin::Battery(11)));
+    }
+
+    #[test]
+    fn empty_batteries_remain_empty_when_restored_from_snapshot() {
+        let mut test_bed = test_bed_with()
+            .bat_1_off()
+            .empty_battery_1()
+            .bat_2_off()
+            .empty_battery_2()
+            .and()
+            .airspeed(Velocity::new::<knot>(0.))
+            .run()
+            .then_continue_with()
+            .restored_from_snapshot()
+            .run();
+
+        assert!(test_bed.hot_bus_1_output().is_unpowered());
+        assert!(test_bed.hot_bus_2_output().is_unpowered());
+    }
+
+    #[test]
+    fn engine_generators_supply_power_immediately_when_restored_from_snapshot() {
+        let mut test_bed = test_bed_with()
+            .running_engines()
+            .then_continue_with()
+            .restored_from_snapshot()
+            .run_waiting_for(Duration::from_secs(0));
+
+        assert!(test_bed
+            .ac_bus_1_output()
+            .is_single(PotentialOrigin::EngineGenerator(1)));
+        assert!(test_bed
+            .ac_bus_2_output()
+            .is_single(PotentialOrigin::EngineGenerator(2)));
+    }
+
     #[test]
     fn when_only_external_power_connected_but_ext_pwr_push_button_off_nothing_powers_ac_bus_1_and_2(
     ) {
@@ -1884,8 +1959,8 @@ mod a320_electrical_circuit_tests {
     }
 
     struct A320ElectricalTestAircraft {
-        engine_1_running: bool,
-        engine_2_running: bool,
+        engine_1: Engine,
+        engine_2: Engine,
         ext_pwr: ExternalPowerSource,
         elec: A320Electrical,
         overhead: A320ElectricalOverheadPanel,
@@ -1895,27 +1970,20 @@ mod a320_electrical_circuit_tests {
     }
     impl A320ElectricalTestAircraft {
         fn new() -> Self {
-            Self {
-                engine_1_running: false,
-                engine_2_running: false,
+            let mut registry = VariableRegistry::new();
 
-                ext_pwr: ExternalPowerSource::new(),
-                elec: A320Electrical::new(),
-                overhead: A320ElectricalOverheadPanel::new(),
+            Self {
+                engine_1: Engine::new(&mut registry, 1),
+                engine_2: Engine::new(&mut registry, 2),
+                ext_pwr: ExternalPowerSource::new(&mut registry),
+                elec: A320Electrical::new(&mut registry),
+                overhead: A320ElectricalOverheadPanel::new(&mut registry),
                 apu_master_sw_pb_on: false,
                 apu_start_pb_on: false,
                 apu: TestApu::new(),
             }
         }
 
-        fn running_engine_1(&mut self) {
-            self.engine_1_running = true;
-        }
-
-        fn running_engine_2(&mut self) {
-            self.engine_2_running = true;
-        }
-
         fn running_apu(&mut self) {
             self.apu.set_apu_available();
         }
@@ -1936,22 +2004,6 @@ mod a320_electrical_circuit_tests {
             self.apu.start_motor_is_powered()
         }
 
-        fn empty_battery_1(&mut self) {
-            self.elec.empty_battery_1();
-        }
-
-        fn empty_battery_2(&mut self) {
-            self.elec.empty_battery_2();
-        }
-
-        fn failed_tr_1(&mut self) {
-            self.elec.fail_tr_1();
-        }
-
-        fn failed_tr_2(&mut self) {
-            self.elec.fail_tr_2();
-        }
-
         fn running_emergency_generator(&mut self) {
             self.elec.attempt_emergency_gen_start();
         }
@@ -1982,21 +2034,29 @@ mod a320_electrical_circuit_tests {
     }
     impl Aircraft for A320ElectricalTestAircraft {
         fn update_before_power_distribution(&mut self, context: &UpdateContext) {
+            self.engine_1.update(context);
+            self.engine_2.update(context);
+
             self.elec.update(
                 context,
                 &self.ext_pwr,
                 &self.overhead,
                 &mut A320ElectricalUpdateArguments::new(
-                    [
-                        Ratio::new::<percent>(if self.engine_1_running { 80. } else { 0. }),
-                        Ratio::new::<percent>(if self.engine_2_running { 80. } else { 0. }),
-                    ],
+                    [&self.engine_1, &self.engine_2],
                     [
                         self.overhead.idg_1_push_button_released(),
                         self.overhead.idg_2_push_button_released(),
                     ],
                     &mut self.apu,
-                    true,
+                    // The RAT is expected to pressurise the blue circuit as long as
+                    // the airspeed is high enough for its propeller not to stall.
+                    Pressure::new::<psi>(
+                        if context.indicated_airspeed() > Velocity::new::<knot>(100.) {
+                            3000.
+                        } else {
+                            0.
+                        },
+                    ),
                     self.apu_master_sw_pb_on,
                     self.apu_start_pb_on,
                     true,
@@ -2011,6 +2071,8 @@ mod a320_electrical_circuit_tests {
     }
     impl SimulationElement for A320ElectricalTestAircraft {
         fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            self.engine_1.accept(visitor);
+            self.engine_2.accept(visitor);
             self.ext_pwr.accept(visitor);
             self.elec.accept(visitor);
             self.overhead.accept(visitor);
@@ -2033,14 +2095,16 @@ mod a320_electrical_circuit_tests {
         }
 
         fn running_engine_1(mut self) -> Self {
-            self.aircraft.running_engine_1();
+            self.simulation_test_bed
+                .write_f64("TURB ENG CORRECTED N2:1", 80.);
             self.run_waiting_for(Duration::from_millis(
                 INTEGRATED_DRIVE_GENERATOR_STABILIZATION_TIME_IN_MILLISECONDS,
             ))
         }
 
         fn running_engine_2(mut self) -> Self {
-            self.aircraft.running_engine_2();
+            self.simulation_test_bed
+                .write_f64("TURB ENG CORRECTED N2:2", 80.);
             self.run_waiting_for(Duration::from_millis(
                 INTEGRATED_DRIVE_GENERATOR_STABILIZATION_TIME_IN_MILLISECONDS,
             ))
@@ -2061,13 +2125,20 @@ mod a320_electrical_circuit_tests {
             self
         }
 
-        fn empty_battery_1(mut self) -> Self {
-            self.aircraft.empty_battery_1();
-            self
+        fn empty_battery_1(self) -> Self {
+            self.empty_battery(10)
         }
 
-        fn empty_battery_2(mut self) -> Self {
-            self.aircraft.empty_battery_2();
+        fn empty_battery_2(self) -> Self {
+            self.empty_battery(11)
+        }
+
+        /// Empties the battery by restoring it from a snapshot without charge.
+        fn empty_battery(mut self, number: usize) -> Self {
+            let mut snapshot = Snapshot::take(&mut self.aircraft);
+            snapshot.save_f64(&format!("ELEC_BAT_{}_CHARGE", number), 0.);
+            snapshot.restore(&mut self.aircraft);
+
             self
         }
 
@@ -2100,13 +2171,33 @@ mod a320_electrical_circuit_tests {
             self
         }
 
-        fn failed_tr_1(mut self) -> Self {
-            self.aircraft.failed_tr_1();
+        fn failed_tr_1(self) -> Self {
+            self.failed(FailureType::TransformerRectifier(1))
+        }
+
+        fn failed_tr_2(self) -> Self {
+            self.failed(FailureType::TransformerRectifier(2))
+        }
+
+        fn failed(mut self, failure_type: FailureType) -> Self {
+            self.simulation_test_bed.activate_failure(failure_type);
+            self
+        }
+
+        fn cleared(mut self, failure_type: FailureType) -> Self {
+            self.simulation_test_bed.deactivate_failure(failure_type);
             self
         }
 
-        fn failed_tr_2(mut self) -> Self {
-            self.aircraft.failed_tr_2();
+        /// Saves the electrical system's state and restores it
+        /// into a newly created electrical system.
+        fn restored_from_snapshot(mut self) -> Self {
+            let json = Snapshot::take(&mut self.aircraft).to_json();
+            self.aircraft.elec = A320Electrical::new(&mut VariableRegistry::new());
+            Snapshot::from_json(&json)
+                .unwrap()
+                .restore(&mut self.aircraft);
+
             self
         }
 
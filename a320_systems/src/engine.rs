@@ -0,0 +1,146 @@
+use systems::{
+    electrical::ElectricalBusType,
+    engine::{Engine, EngineModeSelectorPosition, EngineStartSystem},
+    shared::LgciuWeightOnWheels,
+    simulation::{
+        QuantityIdentifier, SimulationElement, SimulationElementVisitor, SimulatorReader,
+        SimulatorWriter, UpdateContext, VariableRegistry, VariableUnit,
+    },
+};
+use uom::si::f64::*;
+
+pub struct A320EngineStart {
+    engine_1_start: EngineStartSystem,
+    engine_2_start: EngineStartSystem,
+}
+impl A320EngineStart {
+    pub fn new(registry: &mut VariableRegistry) -> A320EngineStart {
+        A320EngineStart {
+            engine_1_start: A320EngineStart::new_engine_start_system(registry, 1),
+            engine_2_start: A320EngineStart::new_engine_start_system(registry, 2),
+        }
+    }
+
+    fn new_engine_start_system(
+        registry: &mut VariableRegistry,
+        number: usize,
+    ) -> EngineStartSystem {
+        EngineStartSystem::new(
+            registry,
+            number,
+            ElectricalBusType::DirectCurrentEssential,
+            ElectricalBusType::AlternatingCurrentEssential,
+            ElectricalBusType::AlternatingCurrent(1),
+        )
+    }
+
+    /// Engine 1 takes its starter air from the left bleed air duct,
+    /// engine 2 from the right bleed air duct.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update(
+        &mut self,
+        context: &UpdateContext,
+        engine_1: &Engine,
+        engine_2: &Engine,
+        panel: &A320EngineStartPanel,
+        left_duct_pressure: Pressure,
+        right_duct_pressure: Pressure,
+        lgciu: &impl LgciuWeightOnWheels,
+    ) {
+        self.engine_1_start.update(
+            context,
+            panel.mode(),
+            panel.master_1_is_on(),
+            engine_1,
+            left_duct_pressure,
+            lgciu,
+        );
+        self.engine_2_start.update(
+            context,
+            panel.mode(),
+            panel.master_2_is_on(),
+            engine_2,
+            right_duct_pressure,
+            lgciu,
+        );
+    }
+
+    pub fn engine_1_starter_air_demand(&self) -> MassRate {
+        self.engine_1_start.starter_air_demand()
+    }
+
+    pub fn engine_2_starter_air_demand(&self) -> MassRate {
+        self.engine_2_start.starter_air_demand()
+    }
+
+    pub fn any_start_valve_is_open(&self) -> bool {
+        self.engine_1_start.start_valve_is_open() || self.engine_2_start.start_valve_is_open()
+    }
+}
+impl SimulationElement for A320EngineStart {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.engine_1_start.accept(visitor);
+        self.engine_2_start.accept(visitor);
+
+        visitor.visit(self);
+    }
+}
+
+/// The ENG MODE selector and ENG MASTER switches on the pedestal.
+pub struct A320EngineStartPanel {
+    mode_selector_id: QuantityIdentifier<f64>,
+    master_1_id: QuantityIdentifier<bool>,
+    master_2_id: QuantityIdentifier<bool>,
+    mode: EngineModeSelectorPosition,
+    master_1_is_on: bool,
+    master_2_is_on: bool,
+}
+impl A320EngineStartPanel {
+    pub fn new(registry: &mut VariableRegistry) -> A320EngineStartPanel {
+        A320EngineStartPanel {
+            mode_selector_id: registry
+                .register_read_write("ENG_MODE_SELECTOR", VariableUnit::Number),
+            master_1_id: registry.register_read_write("ENG_MASTER_1_IS_ON", VariableUnit::Bool),
+            master_2_id: registry.register_read_write("ENG_MASTER_2_IS_ON", VariableUnit::Bool),
+            mode: EngineModeSelectorPosition::Norm,
+            master_1_is_on: false,
+            master_2_is_on: false,
+        }
+    }
+
+    pub fn mode(&self) -> EngineModeSelectorPosition {
+        self.mode
+    }
+
+    pub fn master_1_is_on(&self) -> bool {
+        self.master_1_is_on
+    }
+
+    pub fn master_2_is_on(&self) -> bool {
+        self.master_2_is_on
+    }
+}
+impl SimulationElement for A320EngineStartPanel {
+    fn read(&mut self, reader: &mut SimulatorReader) {
+        self.mode = match reader.read_f64(&self.mode_selector_id) as u8 {
+            0 => EngineModeSelectorPosition::Crank,
+            2 => EngineModeSelectorPosition::IgnitionStart,
+            _ => EngineModeSelectorPosition::Norm,
+        };
+        self.master_1_is_on = reader.read_bool(&self.master_1_id);
+        self.master_2_is_on = reader.read_bool(&self.master_2_id);
+    }
+
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_f64(
+            &self.mode_selector_id,
+            match self.mode {
+                EngineModeSelectorPosition::Crank => 0.,
+                EngineModeSelectorPosition::Norm => 1.,
+                EngineModeSelectorPosition::IgnitionStart => 2.,
+            },
+        );
+        writer.write_bool(&self.master_1_id, self.master_1_is_on);
+        writer.write_bool(&self.master_2_id, self.master_2_is_on);
+    }
+}
@@ -0,0 +1,581 @@
+use super::{
+    hydraulic::A320Hydraulic, landing_gear::A320LandingGear, pressurization::A320Pressurization,
+};
+use systems::{
+    apu::{ApuGenerator, ApuStartMotor, AuxiliaryPowerUnit},
+    electrical::{consumption::SuppliedPower, ElectricalBusType},
+    engine::Engine,
+    flight_warning::{FlightWarning, FlightWarningComputer, FlightWarningSystem, WarningLevel},
+    shared::{FwcFlightPhase, FwcFlightPhaseProvider, LgciuWeightOnWheels},
+    simulation::{SimulationElement, SimulationElementVisitor, UpdateContext, VariableRegistry},
+};
+use uom::si::{f64::*, length::foot, pressure::psi};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum A320FlightWarning {
+    ElecEmerConfig,
+    LandingGearNotDown,
+    CabinPressureExcessiveAltitude,
+    Engine1OilLowPressure,
+    Engine2OilLowPressure,
+    HydGreenYellowLowPressure,
+    ElecAcEssBusFault,
+    HydGreenLowPressure,
+    HydBlueLowPressure,
+    HydYellowLowPressure,
+    ApuAutoShutdown,
+}
+impl FlightWarning for A320FlightWarning {
+    fn code(&self) -> u32 {
+        match self {
+            A320FlightWarning::ElecEmerConfig => 24001,
+            A320FlightWarning::LandingGearNotDown => 32001,
+            A320FlightWarning::CabinPressureExcessiveAltitude => 21001,
+            A320FlightWarning::Engine1OilLowPressure => 79001,
+            A320FlightWarning::Engine2OilLowPressure => 79002,
+            A320FlightWarning::HydGreenYellowLowPressure => 29001,
+            A320FlightWarning::ElecAcEssBusFault => 24002,
+            A320FlightWarning::HydGreenLowPressure => 29002,
+            A320FlightWarning::HydBlueLowPressure => 29003,
+            A320FlightWarning::HydYellowLowPressure => 29004,
+            A320FlightWarning::ApuAutoShutdown => 49001,
+        }
+    }
+
+    fn message(&self) -> &'static str {
+        match self {
+            A320FlightWarning::ElecEmerConfig => "ELEC EMER CONFIG",
+            A320FlightWarning::LandingGearNotDown => "L/G GEAR NOT DOWN",
+            A320FlightWarning::CabinPressureExcessiveAltitude => "CAB PR EXCESS CAB ALT",
+            A320FlightWarning::Engine1OilLowPressure => "ENG 1 OIL LO PR",
+            A320FlightWarning::Engine2OilLowPressure => "ENG 2 OIL LO PR",
+            A320FlightWarning::HydGreenYellowLowPressure => "HYD G+Y SYS LO PR",
+            A320FlightWarning::ElecAcEssBusFault => "ELEC AC ESS BUS FAULT",
+            A320FlightWarning::HydGreenLowPressure => "HYD G SYS LO PR",
+            A320FlightWarning::HydBlueLowPressure => "HYD B SYS LO PR",
+            A320FlightWarning::HydYellowLowPressure => "HYD Y SYS LO PR",
+            A320FlightWarning::ApuAutoShutdown => "APU AUTO SHUT DOWN",
+        }
+    }
+
+    fn level(&self) -> WarningLevel {
+        match self {
+            A320FlightWarning::ElecEmerConfig
+            | A320FlightWarning::LandingGearNotDown
+            | A320FlightWarning::CabinPressureExcessiveAltitude
+            | A320FlightWarning::Engine1OilLowPressure
+            | A320FlightWarning::Engine2OilLowPressure
+            | A320FlightWarning::HydGreenYellowLowPressure => WarningLevel::Warning,
+            A320FlightWarning::ElecAcEssBusFault
+            | A320FlightWarning::HydGreenLowPressure
+            | A320FlightWarning::HydBlueLowPressure
+            | A320FlightWarning::HydYellowLowPressure
+            | A320FlightWarning::ApuAutoShutdown => WarningLevel::Caution,
+        }
+    }
+
+    /// Warnings are inhibited during the take-off roll, lift-off and touch down.
+    /// Cautions are additionally inhibited from take-off power onwards and during the final approach.
+    fn is_inhibited_in(&self, flight_phase: FwcFlightPhase) -> bool {
+        match self.level() {
+            WarningLevel::Warning => matches!(
+                flight_phase,
+                FwcFlightPhase::AtOrAboveEightyKnots
+                    | FwcFlightPhase::LiftOff
+                    | FwcFlightPhase::TouchDown
+            ),
+            WarningLevel::Caution => matches!(
+                flight_phase,
+                FwcFlightPhase::FirstEngineTakeOffPower
+                    | FwcFlightPhase::AtOrAboveEightyKnots
+                    | FwcFlightPhase::LiftOff
+                    | FwcFlightPhase::AtOrBelow800Feet
+                    | FwcFlightPhase::TouchDown
+            ),
+        }
+    }
+}
+
+/// Detects the failure conditions of the A320's systems and passes them to the
+/// flight warning system in order of priority.
+pub struct A320FlightWarningSystem {
+    fws: FlightWarningSystem<A320FlightWarning>,
+    /// The failures detected in the current update, kept to reuse its allocation.
+    failures: Vec<A320FlightWarning>,
+    ac_bus_1_is_powered: bool,
+    ac_bus_2_is_powered: bool,
+    ac_ess_bus_is_powered: bool,
+}
+impl A320FlightWarningSystem {
+    const ENGINE_OIL_LOW_PRESSURE_PSI: f64 = 13.;
+    const EXCESSIVE_CABIN_ALTITUDE_FEET: f64 = 9550.;
+    const GEAR_NOT_DOWN_RADIO_HEIGHT_FEET: f64 = 750.;
+
+    pub fn new(registry: &mut VariableRegistry) -> A320FlightWarningSystem {
+        A320FlightWarningSystem {
+            fws: FlightWarningSystem::new(registry, ElectricalBusType::DirectCurrentEssential),
+            failures: Vec::new(),
+            ac_bus_1_is_powered: false,
+            ac_bus_2_is_powered: false,
+            ac_ess_bus_is_powered: false,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn update<T: ApuGenerator, U: ApuStartMotor>(
+        &mut self,
+        context: &UpdateContext,
+        fwc: &FlightWarningComputer,
+        engines: [&Engine; 2],
+        apu: &AuxiliaryPowerUnit<T, U>,
+        hydraulic: &A320Hydraulic,
+        pressurization: &A320Pressurization,
+        landing_gear: &A320LandingGear,
+    ) {
+        let failures = &mut self.failures;
+        failures.clear();
+
+        let is_airborne = !landing_gear.left_and_right_gear_compressed();
+        if is_airborne && !self.ac_bus_1_is_powered && !self.ac_bus_2_is_powered {
+            failures.push(A320FlightWarning::ElecEmerConfig);
+        }
+
+        if fwc.flight_phase() == FwcFlightPhase::AtOrBelow800Feet
+            && fwc.radio_height()
+                < Length::new::<foot>(A320FlightWarningSystem::GEAR_NOT_DOWN_RADIO_HEIGHT_FEET)
+            && !landing_gear.is_down_and_locked()
+        {
+            failures.push(A320FlightWarning::LandingGearNotDown);
+        }
+
+        if pressurization.cabin_altitude()
+            > Length::new::<foot>(A320FlightWarningSystem::EXCESSIVE_CABIN_ALTITUDE_FEET)
+        {
+            failures.push(A320FlightWarning::CabinPressureExcessiveAltitude);
+        }
+
+        for (engine, failure) in engines.iter().zip([
+            A320FlightWarning::Engine1OilLowPressure,
+            A320FlightWarning::Engine2OilLowPressure,
+        ]) {
+            if engine.is_running()
+                && engine.oil_pressure()
+                    < Pressure::new::<psi>(A320FlightWarningSystem::ENGINE_OIL_LOW_PRESSURE_PSI)
+            {
+                failures.push(failure);
+            }
+        }
+
+        // The engine driven pumps pressurise green and yellow, the blue electric pump
+        // runs as soon as any engine is running.
+        let green_low_pressure = engines[0].is_running() && !hydraulic.is_green_pressurised();
+        let yellow_low_pressure = engines[1].is_running() && !hydraulic.is_yellow_pressurised();
+        let blue_low_pressure =
+            engines.iter().any(|engine| engine.is_running()) && !hydraulic.is_blue_pressurised();
+        if green_low_pressure && yellow_low_pressure {
+            failures.push(A320FlightWarning::HydGreenYellowLowPressure);
+        }
+
+        if !self.ac_ess_bus_is_powered && (self.ac_bus_1_is_powered || self.ac_bus_2_is_powered) {
+            failures.push(A320FlightWarning::ElecAcEssBusFault);
+        }
+
+        if green_low_pressure && !yellow_low_pressure {
+            failures.push(A320FlightWarning::HydGreenLowPressure);
+        }
+        if blue_low_pressure {
+            failures.push(A320FlightWarning::HydBlueLowPressure);
+        }
+        if yellow_low_pressure && !green_low_pressure {
+            failures.push(A320FlightWarning::HydYellowLowPressure);
+        }
+
+        if apu.is_auto_shutdown() {
+            failures.push(A320FlightWarning::ApuAutoShutdown);
+        }
+
+        self.fws.update(context, fwc, &self.failures);
+    }
+}
+impl SimulationElement for A320FlightWarningSystem {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.fws.accept(visitor);
+
+        visitor.visit(self);
+    }
+
+    fn receive_power(&mut self, supplied_power: &SuppliedPower) {
+        self.ac_bus_1_is_powered =
+            supplied_power.is_powered(&ElectricalBusType::AlternatingCurrent(1));
+        self.ac_bus_2_is_powered =
+            supplied_power.is_powered(&ElectricalBusType::AlternatingCurrent(2));
+        self.ac_ess_bus_is_powered =
+            supplied_power.is_powered(&ElectricalBusType::AlternatingCurrentEssential);
+    }
+}
+
+#[cfg(test)]
+mod a320_flight_warning_tests {
+    use super::*;
+    use crate::landing_gear::A320LandingGearLever;
+    use std::time::Duration;
+    use systems::{
+        apu::{
+            Aps3200ApuGenerator, Aps3200StartMotor, AuxiliaryPowerUnitFactory,
+            AuxiliaryPowerUnitFireOverheadPanel, AuxiliaryPowerUnitOverheadPanel,
+        },
+        electrical::{Potential, PotentialOrigin},
+        simulation::{test::SimulationTestBed, Aircraft},
+    };
+    use uom::si::{electric_potential::volt, velocity::knot};
+
+    struct A320FlightWarningTestAircraft {
+        fws: A320FlightWarningSystem,
+        fwc: FlightWarningComputer,
+        engine_1: Engine,
+        engine_2: Engine,
+        apu: AuxiliaryPowerUnit<Aps3200ApuGenerator, Aps3200StartMotor>,
+        apu_overhead: AuxiliaryPowerUnitOverheadPanel,
+        apu_fire_overhead: AuxiliaryPowerUnitFireOverheadPanel,
+        hydraulic: A320Hydraulic,
+        pressurization: A320Pressurization,
+        landing_gear: A320LandingGear,
+        landing_gear_lever: A320LandingGearLever,
+        green_pressure: Pressure,
+        is_ac_1_powered: bool,
+        is_ac_2_powered: bool,
+        is_ac_ess_powered: bool,
+    }
+    impl A320FlightWarningTestAircraft {
+        fn new() -> Self {
+            let mut registry = VariableRegistry::new();
+
+            Self {
+                fws: A320FlightWarningSystem::new(&mut registry),
+                fwc: FlightWarningComputer::new(&mut registry),
+                engine_1: Engine::new(&mut registry, 1),
+                engine_2: Engine::new(&mut registry, 2),
+                apu: AuxiliaryPowerUnitFactory::new_aps3200(&mut registry, 1),
+                apu_overhead: AuxiliaryPowerUnitOverheadPanel::new(&mut registry),
+                apu_fire_overhead: AuxiliaryPowerUnitFireOverheadPanel::new(&mut registry),
+                hydraulic: A320Hydraulic::new(&mut registry),
+                pressurization: A320Pressurization::new(&mut registry),
+                landing_gear: A320LandingGear::new(&mut registry),
+                landing_gear_lever: A320LandingGearLever::new(&mut registry),
+                green_pressure: Pressure::new::<psi>(3000.),
+                is_ac_1_powered: true,
+                is_ac_2_powered: true,
+                is_ac_ess_powered: true,
+            }
+        }
+    }
+    impl Aircraft for A320FlightWarningTestAircraft {
+        fn update_before_power_distribution(&mut self, context: &UpdateContext) {
+            self.engine_1.update(context);
+            self.engine_2.update(context);
+            self.apu.update_before_electrical(
+                context,
+                &self.apu_overhead,
+                &self.apu_fire_overhead,
+                false,
+                false,
+                Pressure::new::<psi>(30.),
+            );
+        }
+
+        fn update_after_power_distribution(&mut self, context: &UpdateContext) {
+            // The APU's start motor is never powered, such that starting the APU
+            // results in an automatic shutdown.
+            self.apu.update_after_electrical();
+            self.apu_overhead.update_after_apu(&self.apu);
+
+            self.landing_gear
+                .update(context, &self.landing_gear_lever, self.green_pressure);
+            self.fwc.update(
+                context,
+                &[&self.engine_1, &self.engine_2],
+                &self.landing_gear,
+            );
+            self.fws.update(
+                context,
+                &self.fwc,
+                [&self.engine_1, &self.engine_2],
+                &self.apu,
+                &self.hydraulic,
+                &self.pressurization,
+                &self.landing_gear,
+            );
+        }
+
+        fn get_supplied_power(&mut self) -> SuppliedPower {
+            let mut supplied_power = SuppliedPower::new();
+            let dc = Potential::single(
+                PotentialOrigin::Battery(10),
+                ElectricPotential::new::<volt>(28.),
+            );
+            supplied_power.add(ElectricalBusType::DirectCurrentEssential, dc);
+            supplied_power.add(ElectricalBusType::DirectCurrent(2), dc);
+
+            let ac = Potential::single(
+                PotentialOrigin::EngineGenerator(1),
+                ElectricPotential::new::<volt>(115.),
+            );
+            if self.is_ac_1_powered {
+                supplied_power.add(ElectricalBusType::AlternatingCurrent(1), ac);
+            }
+            if self.is_ac_2_powered {
+                supplied_power.add(ElectricalBusType::AlternatingCurrent(2), ac);
+            }
+            if self.is_ac_ess_powered {
+                supplied_power.add(ElectricalBusType::AlternatingCurrentEssential, ac);
+            }
+
+            supplied_power
+        }
+    }
+    impl SimulationElement for A320FlightWarningTestAircraft {
+        fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            self.fws.accept(visitor);
+            self.fwc.accept(visitor);
+            self.engine_1.accept(visitor);
+            self.engine_2.accept(visitor);
+            self.apu.accept(visitor);
+            self.apu_overhead.accept(visitor);
+            self.apu_fire_overhead.accept(visitor);
+            self.landing_gear.accept(visitor);
+            self.landing_gear_lever.accept(visitor);
+
+            visitor.visit(self);
+        }
+    }
+
+    struct A320FlightWarningTestBed {
+        aircraft: A320FlightWarningTestAircraft,
+        simulation_test_bed: SimulationTestBed,
+    }
+    impl A320FlightWarningTestBed {
+        fn new() -> Self {
+            let mut test_bed = Self {
+                aircraft: A320FlightWarningTestAircraft::new(),
+                simulation_test_bed: SimulationTestBed::new_with_delta(Duration::from_secs(1)),
+            };
+            test_bed
+                .simulation_test_bed
+                .set_indicated_airspeed(Velocity::new::<knot>(180.));
+            test_bed
+                .simulation_test_bed
+                .write_bool("GEAR HANDLE POSITION", true);
+
+            test_bed.radio_height(5000.)
+        }
+
+        fn run_for(mut self, seconds: u64) -> Self {
+            for _ in 0..seconds {
+                self.simulation_test_bed.run_aircraft(&mut self.aircraft);
+            }
+
+            self
+        }
+
+        fn on_ground(mut self) -> Self {
+            self.simulation_test_bed.set_on_ground(true);
+            self.simulation_test_bed
+                .write_f64("CONTACT POINT COMPRESSION:1", 50.);
+            self.simulation_test_bed
+                .write_f64("CONTACT POINT COMPRESSION:2", 50.);
+            self.radio_height(0.)
+        }
+
+        fn radio_height(mut self, feet: f64) -> Self {
+            self.simulation_test_bed
+                .set_radio_altitude(Length::new::<foot>(feet));
+            self
+        }
+
+        fn engines_running_with_oil_pressure(mut self, pressure: f64) -> Self {
+            for number in 1..=2 {
+                self.simulation_test_bed
+                    .write_f64(&format!("TURB ENG CORRECTED N2:{}", number), 70.);
+                self.simulation_test_bed
+                    .write_f64(&format!("GENERAL ENG OIL PRESSURE:{}", number), pressure);
+            }
+
+            self
+        }
+
+        fn gear_lever_up(mut self) -> Self {
+            self.simulation_test_bed
+                .write_bool("GEAR HANDLE POSITION", false);
+            self
+        }
+
+        fn apu_auto_shutdown(mut self) -> Self {
+            self.simulation_test_bed
+                .write_bool("OVHD_APU_MASTER_SW_PB_IS_ON", true);
+            self.simulation_test_bed
+                .write_bool("OVHD_APU_START_PB_IS_ON", true);
+            self.run_for(30)
+        }
+
+        fn ac_1_and_2_unpowered(mut self) -> Self {
+            self.aircraft.is_ac_1_powered = false;
+            self.aircraft.is_ac_2_powered = false;
+            self
+        }
+
+        fn ac_ess_unpowered(mut self) -> Self {
+            self.aircraft.is_ac_ess_powered = false;
+            self
+        }
+
+        fn press_master_caution(mut self) -> Self {
+            self.simulation_test_bed
+                .write_bool("ECAM_MASTER_CAUTION_PB_IS_PRESSED", true);
+            self
+        }
+
+        fn master_warning_is_on(&self) -> bool {
+            self.aircraft.fws.fws.master_warning_is_on()
+        }
+
+        fn master_caution_is_on(&self) -> bool {
+            self.aircraft.fws.fws.master_caution_is_on()
+        }
+
+        fn active_warnings(&self) -> Vec<A320FlightWarning> {
+            self.aircraft.fws.fws.active_warnings()
+        }
+
+        fn first_message_code(&mut self) -> f64 {
+            self.simulation_test_bed.read_f64("ECAM_EWD_MESSAGE_1")
+        }
+    }
+
+    fn test_bed() -> A320FlightWarningTestBed {
+        A320FlightWarningTestBed::new()
+    }
+
+    #[test]
+    fn no_warnings_in_normal_conditions() {
+        let test_bed = test_bed().run_for(5);
+
+        assert!(test_bed.active_warnings().is_empty());
+        assert!(!test_bed.master_warning_is_on());
+        assert!(!test_bed.master_caution_is_on());
+    }
+
+    #[test]
+    fn elec_emer_config_when_ac_1_and_2_unpowered_in_flight() {
+        let test_bed = test_bed().ac_1_and_2_unpowered().run_for(1);
+
+        assert_eq!(
+            test_bed.active_warnings(),
+            vec![A320FlightWarning::ElecEmerConfig]
+        );
+        assert!(test_bed.master_warning_is_on());
+    }
+
+    #[test]
+    fn no_elec_emer_config_on_ground() {
+        let test_bed = test_bed().on_ground().ac_1_and_2_unpowered().run_for(1);
+
+        assert!(test_bed.active_warnings().is_empty());
+    }
+
+    #[test]
+    fn ac_ess_bus_fault_lights_master_caution() {
+        let mut test_bed = test_bed().ac_ess_unpowered().run_for(1);
+
+        assert_eq!(
+            test_bed.active_warnings(),
+            vec![A320FlightWarning::ElecAcEssBusFault]
+        );
+        assert!(test_bed.master_caution_is_on());
+        assert_eq!(test_bed.first_message_code(), 24002.);
+    }
+
+    #[test]
+    fn engine_oil_low_pressure_when_running() {
+        let test_bed = test_bed().engines_running_with_oil_pressure(5.).run_for(1);
+
+        let warnings = test_bed.active_warnings();
+        assert!(warnings.contains(&A320FlightWarning::Engine1OilLowPressure));
+        assert!(warnings.contains(&A320FlightWarning::Engine2OilLowPressure));
+    }
+
+    #[test]
+    fn hyd_green_and_yellow_low_pressure_replaces_individual_cautions() {
+        let test_bed = test_bed().engines_running_with_oil_pressure(60.).run_for(1);
+
+        assert_eq!(
+            test_bed.active_warnings(),
+            vec![
+                A320FlightWarning::HydGreenYellowLowPressure,
+                A320FlightWarning::HydBlueLowPressure
+            ]
+        );
+    }
+
+    #[test]
+    fn warnings_are_listed_before_cautions() {
+        let test_bed = test_bed()
+            .apu_auto_shutdown()
+            .ac_1_and_2_unpowered()
+            .run_for(1);
+
+        assert_eq!(
+            test_bed.active_warnings(),
+            vec![
+                A320FlightWarning::ElecEmerConfig,
+                A320FlightWarning::ApuAutoShutdown
+            ]
+        );
+    }
+
+    #[test]
+    fn gear_not_down_below_750_feet_on_approach() {
+        let test_bed = test_bed()
+            .gear_lever_up()
+            .run_for(30)
+            .radio_height(700.)
+            .run_for(1);
+
+        assert_eq!(
+            test_bed.active_warnings(),
+            vec![A320FlightWarning::LandingGearNotDown]
+        );
+    }
+
+    #[test]
+    fn no_gear_not_down_when_gear_down_on_approach() {
+        let test_bed = test_bed().run_for(5).radio_height(700.).run_for(1);
+
+        assert!(test_bed.active_warnings().is_empty());
+    }
+
+    #[test]
+    fn cautions_are_inhibited_on_final_approach() {
+        let test_bed = test_bed()
+            .run_for(1)
+            .radio_height(700.)
+            .apu_auto_shutdown()
+            .run_for(1);
+
+        assert!(test_bed.active_warnings().is_empty());
+    }
+
+    #[test]
+    fn pressing_master_caution_turns_off_the_light() {
+        let test_bed = test_bed()
+            .apu_auto_shutdown()
+            .run_for(1)
+            .press_master_caution()
+            .run_for(1);
+
+        assert!(!test_bed.master_caution_is_on());
+        assert_eq!(
+            test_bed.active_warnings(),
+            vec![A320FlightWarning::ApuAutoShutdown]
+        );
+    }
+}
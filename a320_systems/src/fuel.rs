@@ -1,29 +1,631 @@
-use systems::simulation::{SimulationElement, SimulatorReader};
-use uom::si::{
-    f64::*,
-    mass::{kilogram, pound},
+use systems::{
+    electrical::ElectricalBusType,
+    fuel::{FuelPump, FuelTank, FuelValve},
+    overhead::OnOffFaultPushButton,
+    pneumatic::Valve,
+    shared::LgciuWeightOnWheels,
+    simulation::{
+        QuantityIdentifier, SimulationElement, SimulationElementVisitor, SimulatorReader,
+        UpdateContext, VariableRegistry, VariableUnit,
+    },
 };
+use uom::si::{f64::*, mass::kilogram, pressure::psi};
 
 pub struct A320Fuel {
+    unlimited_fuel_id: QuantityIdentifier<bool>,
     unlimited_fuel: bool,
-    left_inner_tank_fuel_quantity: Mass,
+    left_outer_tank: FuelTank,
+    left_inner_tank: FuelTank,
+    center_tank: FuelTank,
+    right_inner_tank: FuelTank,
+    right_outer_tank: FuelTank,
+    left_tank_pump_1: FuelPump,
+    left_tank_pump_2: FuelPump,
+    center_tank_pump_1: FuelPump,
+    center_tank_pump_2: FuelPump,
+    right_tank_pump_1: FuelPump,
+    right_tank_pump_2: FuelPump,
+    apu_fuel_pump: FuelPump,
+    crossfeed_valve: FuelValve,
+    left_transfer_valve: FuelValve,
+    right_transfer_valve: FuelValve,
 }
 impl A320Fuel {
-    pub fn new() -> Self {
+    /// When the inner tank's quantity drops below this quantity, the transfer
+    /// valves open and the outer tank's fuel drains into the inner tank.
+    const INNER_TANK_LOW_LEVEL_KILOGRAM: f64 = 750.;
+
+    pub fn new(registry: &mut VariableRegistry) -> Self {
         A320Fuel {
+            unlimited_fuel_id: registry.register_read("UNLIMITED FUEL", VariableUnit::Bool),
             unlimited_fuel: false,
-            left_inner_tank_fuel_quantity: Mass::new::<kilogram>(0.),
+            left_outer_tank: FuelTank::new(registry, "FUEL TANK LEFT AUX QUANTITY"),
+            left_inner_tank: FuelTank::new(registry, "FUEL TANK LEFT MAIN QUANTITY"),
+            center_tank: FuelTank::new(registry, "FUEL TANK CENTER QUANTITY"),
+            right_inner_tank: FuelTank::new(registry, "FUEL TANK RIGHT MAIN QUANTITY"),
+            right_outer_tank: FuelTank::new(registry, "FUEL TANK RIGHT AUX QUANTITY"),
+            left_tank_pump_1: FuelPump::new(
+                registry,
+                "L_TK_1",
+                1,
+                ElectricalBusType::AlternatingCurrent(1),
+            ),
+            left_tank_pump_2: FuelPump::new(
+                registry,
+                "L_TK_2",
+                2,
+                ElectricalBusType::AlternatingCurrent(2),
+            ),
+            center_tank_pump_1: FuelPump::new(
+                registry,
+                "CTR_TK_1",
+                3,
+                ElectricalBusType::AlternatingCurrent(1),
+            ),
+            center_tank_pump_2: FuelPump::new(
+                registry,
+                "CTR_TK_2",
+                4,
+                ElectricalBusType::AlternatingCurrent(2),
+            ),
+            right_tank_pump_1: FuelPump::new(
+                registry,
+                "R_TK_1",
+                5,
+                ElectricalBusType::AlternatingCurrent(1),
+            ),
+            right_tank_pump_2: FuelPump::new(
+                registry,
+                "R_TK_2",
+                6,
+                ElectricalBusType::AlternatingCurrent(2),
+            ),
+            // The APU fuel pump is powered by the static inverter, such that
+            // the APU can be started on batteries only.
+            apu_fuel_pump: FuelPump::new(
+                registry,
+                "APU",
+                7,
+                ElectricalBusType::AlternatingCurrentStaticInverter,
+            ),
+            crossfeed_valve: FuelValve::new(
+                registry,
+                "XFEED",
+                ElectricalBusType::DirectCurrentEssential,
+            ),
+            left_transfer_valve: FuelValve::new(
+                registry,
+                "L_XFR",
+                ElectricalBusType::DirectCurrentBattery,
+            ),
+            right_transfer_valve: FuelValve::new(
+                registry,
+                "R_XFR",
+                ElectricalBusType::DirectCurrent(2),
+            ),
         }
     }
 
-    pub fn left_inner_tank_has_fuel_remaining(&self) -> bool {
-        self.unlimited_fuel || self.left_inner_tank_fuel_quantity > Mass::new::<kilogram>(0.)
+    pub fn update(
+        &mut self,
+        context: &UpdateContext,
+        overhead: &A320FuelOverheadPanel,
+        apu_master_is_on: bool,
+        lgciu: &impl LgciuWeightOnWheels,
+    ) {
+        let is_on_ground = lgciu.left_and_right_gear_compressed();
+        let left_inner_has_fuel = self.has_fuel_remaining(&self.left_inner_tank);
+        let center_has_fuel = self.has_fuel_remaining(&self.center_tank);
+        let right_inner_has_fuel = self.has_fuel_remaining(&self.right_inner_tank);
+
+        self.left_tank_pump_1.update(
+            context,
+            overhead.left_tank_pump_1_is_on(),
+            left_inner_has_fuel,
+        );
+        self.left_tank_pump_2.update(
+            context,
+            overhead.left_tank_pump_2_is_on(),
+            left_inner_has_fuel,
+        );
+        self.center_tank_pump_1.update(
+            context,
+            overhead.center_tank_pump_1_is_on(),
+            center_has_fuel,
+        );
+        self.center_tank_pump_2.update(
+            context,
+            overhead.center_tank_pump_2_is_on(),
+            center_has_fuel,
+        );
+        self.right_tank_pump_1.update(
+            context,
+            overhead.right_tank_pump_1_is_on(),
+            right_inner_has_fuel,
+        );
+        self.right_tank_pump_2.update(
+            context,
+            overhead.right_tank_pump_2_is_on(),
+            right_inner_has_fuel,
+        );
+
+        self.crossfeed_valve
+            .update(context, overhead.crossfeed_is_on());
+
+        let left_transfer_valve_should_open = self.transfer_valve_should_open(
+            is_on_ground,
+            &self.left_transfer_valve,
+            &self.left_inner_tank,
+        );
+        self.left_transfer_valve
+            .update(context, left_transfer_valve_should_open);
+        let right_transfer_valve_should_open = self.transfer_valve_should_open(
+            is_on_ground,
+            &self.right_transfer_valve,
+            &self.right_inner_tank,
+        );
+        self.right_transfer_valve
+            .update(context, right_transfer_valve_should_open);
+
+        // The APU fuel pump takes over when none of the tank pumps
+        // pressurise the fuel line feeding the APU.
+        self.apu_fuel_pump.update(
+            context,
+            apu_master_is_on && self.left_feed_line_pressure() <= Pressure::new::<psi>(0.),
+            left_inner_has_fuel || (self.crossfeed_valve.is_open() && right_inner_has_fuel),
+        );
+    }
+
+    fn has_fuel_remaining(&self, tank: &FuelTank) -> bool {
+        self.unlimited_fuel || tank.has_fuel_remaining()
+    }
+
+    /// The transfer valves remain open until the inner tank is refuelled on the ground.
+    fn transfer_valve_should_open(
+        &self,
+        is_on_ground: bool,
+        valve: &FuelValve,
+        inner_tank: &FuelTank,
+    ) -> bool {
+        let inner_tank_is_low = !self.unlimited_fuel
+            && inner_tank.quantity()
+                < Mass::new::<kilogram>(A320Fuel::INNER_TANK_LOW_LEVEL_KILOGRAM);
+
+        inner_tank_is_low || (valve.is_open() && !is_on_ground)
+    }
+
+    /// The pressure in the fuel line feeding the left engine and the APU.
+    fn left_feed_line_pressure(&self) -> Pressure {
+        let pressure = self
+            .left_tank_pump_1
+            .pressure()
+            .max(self.left_tank_pump_2.pressure())
+            .max(self.center_tank_pump_1.pressure());
+
+        if self.crossfeed_valve.is_open() {
+            pressure.max(self.right_feed_line_pressure_excluding_crossfeed())
+        } else {
+            pressure
+        }
+    }
+
+    fn right_feed_line_pressure_excluding_crossfeed(&self) -> Pressure {
+        self.right_tank_pump_1
+            .pressure()
+            .max(self.right_tank_pump_2.pressure())
+            .max(self.center_tank_pump_2.pressure())
+    }
+
+    pub fn apu_fuel_pressure(&self) -> Pressure {
+        self.left_feed_line_pressure()
+            .max(self.apu_fuel_pump.pressure())
     }
 }
 impl SimulationElement for A320Fuel {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.left_outer_tank.accept(visitor);
+        self.left_inner_tank.accept(visitor);
+        self.center_tank.accept(visitor);
+        self.right_inner_tank.accept(visitor);
+        self.right_outer_tank.accept(visitor);
+        self.left_tank_pump_1.accept(visitor);
+        self.left_tank_pump_2.accept(visitor);
+        self.center_tank_pump_1.accept(visitor);
+        self.center_tank_pump_2.accept(visitor);
+        self.right_tank_pump_1.accept(visitor);
+        self.right_tank_pump_2.accept(visitor);
+        self.apu_fuel_pump.accept(visitor);
+        self.crossfeed_valve.accept(visitor);
+        self.left_transfer_valve.accept(visitor);
+        self.right_transfer_valve.accept(visitor);
+
+        visitor.visit(self);
+    }
+
     fn read(&mut self, reader: &mut SimulatorReader) {
-        self.unlimited_fuel = reader.read_bool("UNLIMITED FUEL");
-        self.left_inner_tank_fuel_quantity =
-            Mass::new::<pound>(reader.read_f64("FUEL TANK LEFT MAIN QUANTITY"));
+        self.unlimited_fuel = reader.read_bool(&self.unlimited_fuel_id);
+    }
+}
+
+pub struct A320FuelOverheadPanel {
+    left_tank_pump_1_push_button: OnOffFaultPushButton,
+    left_tank_pump_2_push_button: OnOffFaultPushButton,
+    center_tank_pump_1_push_button: OnOffFaultPushButton,
+    center_tank_pump_2_push_button: OnOffFaultPushButton,
+    right_tank_pump_1_push_button: OnOffFaultPushButton,
+    right_tank_pump_2_push_button: OnOffFaultPushButton,
+    crossfeed_push_button: OnOffFaultPushButton,
+}
+impl A320FuelOverheadPanel {
+    pub fn new(registry: &mut VariableRegistry) -> Self {
+        A320FuelOverheadPanel {
+            left_tank_pump_1_push_button: OnOffFaultPushButton::new_on(
+                registry,
+                "FUEL_L_TK_1_PUMP",
+            ),
+            left_tank_pump_2_push_button: OnOffFaultPushButton::new_on(
+                registry,
+                "FUEL_L_TK_2_PUMP",
+            ),
+            center_tank_pump_1_push_button: OnOffFaultPushButton::new_on(
+                registry,
+                "FUEL_CTR_TK_1_PUMP",
+            ),
+            center_tank_pump_2_push_button: OnOffFaultPushButton::new_on(
+                registry,
+                "FUEL_CTR_TK_2_PUMP",
+            ),
+            right_tank_pump_1_push_button: OnOffFaultPushButton::new_on(
+                registry,
+                "FUEL_R_TK_1_PUMP",
+            ),
+            right_tank_pump_2_push_button: OnOffFaultPushButton::new_on(
+                registry,
+                "FUEL_R_TK_2_PUMP",
+            ),
+            crossfeed_push_button: OnOffFaultPushButton::new_off(registry, "FUEL_XFEED"),
+        }
+    }
+
+    fn left_tank_pump_1_is_on(&self) -> bool {
+        self.left_tank_pump_1_push_button.is_on()
+    }
+
+    fn left_tank_pump_2_is_on(&self) -> bool {
+        self.left_tank_pump_2_push_button.is_on()
+    }
+
+    fn center_tank_pump_1_is_on(&self) -> bool {
+        self.center_tank_pump_1_push_button.is_on()
+    }
+
+    fn center_tank_pump_2_is_on(&self) -> bool {
+        self.center_tank_pump_2_push_button.is_on()
+    }
+
+    fn right_tank_pump_1_is_on(&self) -> bool {
+        self.right_tank_pump_1_push_button.is_on()
+    }
+
+    fn right_tank_pump_2_is_on(&self) -> bool {
+        self.right_tank_pump_2_push_button.is_on()
+    }
+
+    fn crossfeed_is_on(&self) -> bool {
+        self.crossfeed_push_button.is_on()
+    }
+}
+impl SimulationElement for A320FuelOverheadPanel {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.left_tank_pump_1_push_button.accept(visitor);
+        self.left_tank_pump_2_push_button.accept(visitor);
+        self.center_tank_pump_1_push_button.accept(visitor);
+        self.center_tank_pump_2_push_button.accept(visitor);
+        self.right_tank_pump_1_push_button.accept(visitor);
+        self.right_tank_pump_2_push_button.accept(visitor);
+        self.crossfeed_push_button.accept(visitor);
+
+        visitor.visit(self);
+    }
+}
+
+#[cfg(test)]
+mod a320_fuel_tests {
+    use super::*;
+    use systems::{
+        electrical::{consumption::SuppliedPower, Potential, PotentialOrigin},
+        simulation::{test::SimulationTestBed, Aircraft},
+    };
+    use uom::si::{electric_potential::volt, mass::pound};
+
+    struct TestLgciu {
+        compressed: bool,
+    }
+    impl TestLgciu {
+        fn new(compressed: bool) -> Self {
+            Self { compressed }
+        }
+    }
+    impl LgciuWeightOnWheels for TestLgciu {
+        fn left_and_right_gear_compressed(&self) -> bool {
+            self.compressed
+        }
+    }
+
+    struct A320FuelTestAircraft {
+        fuel: A320Fuel,
+        overhead: A320FuelOverheadPanel,
+        apu_master_is_on: bool,
+        is_ac_powered: bool,
+    }
+    impl A320FuelTestAircraft {
+        fn new() -> Self {
+            let mut registry = VariableRegistry::new();
+
+            Self {
+                fuel: A320Fuel::new(&mut registry),
+                overhead: A320FuelOverheadPanel::new(&mut registry),
+                apu_master_is_on: false,
+                is_ac_powered: false,
+            }
+        }
+    }
+    impl Aircraft for A320FuelTestAircraft {
+        fn update_after_power_distribution(&mut self, context: &UpdateContext) {
+            self.fuel.update(
+                context,
+                &self.overhead,
+                self.apu_master_is_on,
+                &TestLgciu::new(context.is_on_ground()),
+            );
+        }
+
+        fn get_supplied_power(&mut self) -> SuppliedPower {
+            let mut supplied_power = SuppliedPower::new();
+            let dc = Potential::single(
+                PotentialOrigin::Battery(10),
+                ElectricPotential::new::<volt>(28.),
+            );
+            supplied_power.add(ElectricalBusType::DirectCurrentBattery, dc);
+            supplied_power.add(ElectricalBusType::DirectCurrentEssential, dc);
+
+            if self.is_ac_powered {
+                let ac = Potential::single(
+                    PotentialOrigin::External,
+                    ElectricPotential::new::<volt>(115.),
+                );
+                supplied_power.add(ElectricalBusType::AlternatingCurrent(1), ac);
+                supplied_power.add(ElectricalBusType::AlternatingCurrent(2), ac);
+                supplied_power.add(ElectricalBusType::DirectCurrent(2), dc);
+            } else {
+                supplied_power.add(
+                    ElectricalBusType::AlternatingCurrentStaticInverter,
+                    Potential::single(
+                        PotentialOrigin::StaticInverter,
+                        ElectricPotential::new::<volt>(115.),
+                    ),
+                );
+            }
+
+            supplied_power
+        }
+    }
+    impl SimulationElement for A320FuelTestAircraft {
+        fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            self.fuel.accept(visitor);
+            self.overhead.accept(visitor);
+
+            visitor.visit(self);
+        }
+    }
+
+    struct A320FuelTestBed {
+        aircraft: A320FuelTestAircraft,
+        simulation_test_bed: SimulationTestBed,
+    }
+    impl A320FuelTestBed {
+        fn new() -> Self {
+            let mut aircraft = A320FuelTestAircraft::new();
+            let mut test_bed = Self {
+                simulation_test_bed: SimulationTestBed::seeded_with(&mut aircraft),
+                aircraft,
+            };
+
+            for tank in &[
+                "FUEL TANK LEFT AUX QUANTITY",
+                "FUEL TANK LEFT MAIN QUANTITY",
+                "FUEL TANK CENTER QUANTITY",
+                "FUEL TANK RIGHT MAIN QUANTITY",
+                "FUEL TANK RIGHT AUX QUANTITY",
+            ] {
+                test_bed.simulation_test_bed.write_f64(tank, 2000.);
+            }
+
+            test_bed
+        }
+
+        fn ac_powered(mut self) -> Self {
+            self.aircraft.is_ac_powered = true;
+            self
+        }
+
+        fn apu_master_on(mut self) -> Self {
+            self.aircraft.apu_master_is_on = true;
+            self
+        }
+
+        fn tank_quantity(mut self, tank: &str, quantity: Mass) -> Self {
+            self.simulation_test_bed
+                .write_f64(tank, quantity.get::<pound>());
+            self
+        }
+
+        fn unlimited_fuel(mut self) -> Self {
+            self.simulation_test_bed.write_bool("UNLIMITED FUEL", true);
+            self
+        }
+
+        fn left_tank_pumps_off(mut self) -> Self {
+            self.simulation_test_bed
+                .write_bool("OVHD_FUEL_L_TK_1_PUMP_PB_IS_ON", false);
+            self.simulation_test_bed
+                .write_bool("OVHD_FUEL_L_TK_2_PUMP_PB_IS_ON", false);
+            self.simulation_test_bed
+                .write_bool("OVHD_FUEL_CTR_TK_1_PUMP_PB_IS_ON", false);
+            self
+        }
+
+        fn crossfeed_on(mut self) -> Self {
+            self.simulation_test_bed
+                .write_bool("OVHD_FUEL_XFEED_PB_IS_ON", true);
+            self
+        }
+
+        fn on_ground(mut self) -> Self {
+            self.simulation_test_bed.set_on_ground(true);
+            self
+        }
+
+        fn run(mut self) -> Self {
+            self.simulation_test_bed.run_aircraft(&mut self.aircraft);
+            self
+        }
+
+        fn apu_fuel_pressure(&self) -> Pressure {
+            self.aircraft.fuel.apu_fuel_pressure()
+        }
+
+        fn is_open(&mut self, valve: &str) -> bool {
+            self.simulation_test_bed
+                .read_bool(&format!("FUEL_{}_VALVE_IS_OPEN", valve))
+        }
+
+        fn is_running(&mut self, pump: &str) -> bool {
+            self.simulation_test_bed
+                .read_bool(&format!("FUEL_{}_PUMP_IS_RUNNING", pump))
+        }
+    }
+
+    fn test_bed() -> A320FuelTestBed {
+        A320FuelTestBed::new()
+    }
+
+    fn no_pressure() -> Pressure {
+        Pressure::new::<psi>(0.)
+    }
+
+    #[test]
+    fn tank_pumps_pressurise_the_apu_feed_when_ac_powered() {
+        let test_bed = test_bed().ac_powered().run();
+
+        assert!(test_bed.apu_fuel_pressure() > Pressure::new::<psi>(17.));
+    }
+
+    #[test]
+    fn no_apu_fuel_pressure_without_ac_power_and_apu_master_off() {
+        let test_bed = test_bed().run();
+
+        assert_eq!(test_bed.apu_fuel_pressure(), no_pressure());
+    }
+
+    #[test]
+    fn apu_fuel_pump_pressurises_the_apu_feed_without_ac_power() {
+        let mut test_bed = test_bed().apu_master_on().run();
+
+        assert!(test_bed.apu_fuel_pressure() > Pressure::new::<psi>(17.));
+        assert!(test_bed.is_running("APU"));
+    }
+
+    #[test]
+    fn apu_fuel_pump_does_not_run_when_tank_pumps_pressurise_the_apu_feed() {
+        let mut test_bed = test_bed().ac_powered().apu_master_on().run();
+
+        assert!(!test_bed.is_running("APU"));
+    }
+
+    #[test]
+    fn no_apu_fuel_pressure_when_left_inner_tank_is_empty() {
+        let test_bed = test_bed()
+            .ac_powered()
+            .apu_master_on()
+            .tank_quantity("FUEL TANK LEFT MAIN QUANTITY", Mass::new::<pound>(0.))
+            .tank_quantity("FUEL TANK CENTER QUANTITY", Mass::new::<pound>(0.))
+            .run();
+
+        assert_eq!(test_bed.apu_fuel_pressure(), no_pressure());
+    }
+
+    #[test]
+    fn apu_fuel_pressure_with_empty_tanks_when_unlimited_fuel() {
+        let test_bed = test_bed()
+            .ac_powered()
+            .tank_quantity("FUEL TANK LEFT MAIN QUANTITY", Mass::new::<pound>(0.))
+            .unlimited_fuel()
+            .run();
+
+        assert!(test_bed.apu_fuel_pressure() > Pressure::new::<psi>(17.));
+    }
+
+    #[test]
+    fn no_apu_fuel_pressure_when_left_pumps_off() {
+        let test_bed = test_bed().ac_powered().left_tank_pumps_off().run();
+
+        assert_eq!(test_bed.apu_fuel_pressure(), no_pressure());
+    }
+
+    #[test]
+    fn crossfeed_allows_right_pumps_to_pressurise_the_apu_feed() {
+        let mut test_bed = test_bed()
+            .ac_powered()
+            .left_tank_pumps_off()
+            .crossfeed_on()
+            .run();
+
+        assert!(test_bed.is_open("XFEED"));
+        assert!(test_bed.apu_fuel_pressure() > Pressure::new::<psi>(17.));
+    }
+
+    #[test]
+    fn transfer_valves_closed_when_inner_tanks_have_sufficient_fuel() {
+        let mut test_bed = test_bed().ac_powered().run();
+
+        assert!(!test_bed.is_open("L_XFR"));
+        assert!(!test_bed.is_open("R_XFR"));
+    }
+
+    #[test]
+    fn transfer_valve_opens_when_inner_tank_is_low() {
+        let mut test_bed = test_bed()
+            .ac_powered()
+            .tank_quantity("FUEL TANK LEFT MAIN QUANTITY", Mass::new::<kilogram>(700.))
+            .run();
+
+        assert!(test_bed.is_open("L_XFR"));
+        assert!(!test_bed.is_open("R_XFR"));
+    }
+
+    #[test]
+    fn transfer_valve_remains_open_in_flight_once_inner_tank_is_no_longer_low() {
+        let mut test_bed = test_bed()
+            .ac_powered()
+            .tank_quantity("FUEL TANK LEFT MAIN QUANTITY", Mass::new::<kilogram>(700.))
+            .run()
+            .tank_quantity("FUEL TANK LEFT MAIN QUANTITY", Mass::new::<kilogram>(1400.))
+            .run();
+
+        assert!(test_bed.is_open("L_XFR"));
+    }
+
+    #[test]
+    fn transfer_valve_closes_on_ground_once_inner_tank_is_refuelled() {
+        let mut test_bed = test_bed()
+            .ac_powered()
+            .on_ground()
+            .tank_quantity("FUEL TANK LEFT MAIN QUANTITY", Mass::new::<kilogram>(700.))
+            .run()
+            .tank_quantity("FUEL TANK LEFT MAIN QUANTITY", Mass::new::<kilogram>(4000.))
+            .run();
+
+        assert!(!test_bed.is_open("L_XFR"));
     }
 }
@@ -1,20 +1,567 @@
-use systems::simulation::{SimulationElement, UpdateContext};
+use systems::{
+    electrical::{consumption::SuppliedPower, ElectricalBusType},
+    engine::Engine,
+    hydraulic::{ElectricPump, EngineDrivenPump, HydraulicLoop, PowerTransferUnit, RamAirTurbine},
+    overhead::{AutoOffFaultPushButton, OnOffFaultPushButton},
+    shared::LgciuWeightOnWheels,
+    simulation::{SimulationElement, SimulationElementVisitor, UpdateContext, VariableRegistry},
+};
+use uom::si::{f64::*, velocity::knot, volume::gallon};
 
 pub struct A320Hydraulic {
-    // Until hydraulic is implemented, we'll fake it with this boolean.
-    blue_pressurised: bool,
+    green_loop: HydraulicLoop,
+    blue_loop: HydraulicLoop,
+    yellow_loop: HydraulicLoop,
+    engine_1_pump: EngineDrivenPump,
+    engine_2_pump: EngineDrivenPump,
+    blue_electric_pump: ElectricPump,
+    yellow_electric_pump: ElectricPump,
+    ram_air_turbine: RamAirTurbine,
+    ptu: PowerTransferUnit,
+    ac_bus_1_is_powered: bool,
+    ac_bus_2_is_powered: bool,
 }
 impl A320Hydraulic {
-    pub fn new() -> A320Hydraulic {
+    const RAT_DEPLOYMENT_MINIMUM_AIRSPEED_KNOTS: f64 = 100.;
+
+    pub fn new(registry: &mut VariableRegistry) -> A320Hydraulic {
         A320Hydraulic {
-            blue_pressurised: true,
+            green_loop: HydraulicLoop::new(
+                registry,
+                "GREEN",
+                Volume::new::<gallon>(3.6),
+                Volume::new::<gallon>(0.264),
+            ),
+            blue_loop: HydraulicLoop::new(
+                registry,
+                "BLUE",
+                Volume::new::<gallon>(1.7),
+                Volume::new::<gallon>(0.264),
+            ),
+            yellow_loop: HydraulicLoop::new(
+                registry,
+                "YELLOW",
+                Volume::new::<gallon>(3.2),
+                Volume::new::<gallon>(0.264),
+            ),
+            engine_1_pump: EngineDrivenPump::new(registry, "ENG_1", 1),
+            engine_2_pump: EngineDrivenPump::new(registry, "ENG_2", 2),
+            blue_electric_pump: ElectricPump::new(
+                registry,
+                "BLUE_ELEC",
+                3,
+                ElectricalBusType::AlternatingCurrent(1),
+            ),
+            yellow_electric_pump: ElectricPump::new(
+                registry,
+                "YELLOW_ELEC",
+                4,
+                ElectricalBusType::AlternatingCurrent(2),
+            ),
+            ram_air_turbine: RamAirTurbine::new(registry),
+            ptu: PowerTransferUnit::new(registry),
+            ac_bus_1_is_powered: false,
+            ac_bus_2_is_powered: false,
         }
     }
 
     pub fn is_blue_pressurised(&self) -> bool {
-        self.blue_pressurised
+        self.blue_loop.is_pressurised()
+    }
+
+    pub fn green_pressure(&self) -> Pressure {
+        self.green_loop.pressure()
+    }
+
+    pub fn blue_pressure(&self) -> Pressure {
+        self.blue_loop.pressure()
+    }
+
+    pub fn update(
+        &mut self,
+        context: &UpdateContext,
+        engine_1: &Engine,
+        engine_2: &Engine,
+        overhead: &A320HydraulicOverheadPanel,
+        lgciu: &impl LgciuWeightOnWheels,
+        emergency_generator_demand: VolumeRate,
+    ) {
+        self.engine_1_pump.update(
+            context,
+            engine_1.corrected_n2(),
+            overhead.engine_1_pump_is_on(),
+        );
+        self.engine_2_pump.update(
+            context,
+            engine_2.corrected_n2(),
+            overhead.engine_2_pump_is_on(),
+        );
+
+        // In AUTO the blue electric pump runs as soon as one of the engines is running.
+        self.blue_electric_pump.update(
+            context,
+            overhead.blue_electric_pump_is_auto()
+                && (engine_1.is_running() || engine_2.is_running()),
+        );
+        self.yellow_electric_pump
+            .update(context, overhead.yellow_electric_pump_is_on());
+
+        // The RAT deploys automatically when AC BUS 1 and 2 are lost in flight.
+        self.ram_air_turbine.update(
+            context,
+            !self.ac_bus_1_is_powered
+                && !self.ac_bus_2_is_powered
+                && !lgciu.left_and_right_gear_compressed()
+                && context.indicated_airspeed()
+                    > Velocity::new::<knot>(A320Hydraulic::RAT_DEPLOYMENT_MINIMUM_AIRSPEED_KNOTS),
+            lgciu,
+        );
+
+        self.ptu.update(
+            context,
+            self.green_loop.pressure(),
+            self.yellow_loop.pressure(),
+            overhead.ptu_is_auto(),
+        );
+        let green_demand = self.ptu.demand_on_left();
+        let yellow_demand = self.ptu.demand_on_right();
+
+        self.green_loop.update(
+            context,
+            &mut [&mut self.engine_1_pump, self.ptu.left_mut()],
+            green_demand,
+        );
+        self.yellow_loop.update(
+            context,
+            &mut [
+                &mut self.engine_2_pump,
+                &mut self.yellow_electric_pump,
+                self.ptu.right_mut(),
+            ],
+            yellow_demand,
+        );
+        self.blue_loop.update(
+            context,
+            &mut [&mut self.blue_electric_pump, &mut self.ram_air_turbine],
+            emergency_generator_demand,
+        );
+    }
+
+    pub fn is_green_pressurised(&self) -> bool {
+        self.green_loop.is_pressurised()
+    }
+
+    pub fn is_yellow_pressurised(&self) -> bool {
+        self.yellow_loop.is_pressurised()
+    }
+}
+impl SimulationElement for A320Hydraulic {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.green_loop.accept(visitor);
+        self.blue_loop.accept(visitor);
+        self.yellow_loop.accept(visitor);
+        self.engine_1_pump.accept(visitor);
+        self.engine_2_pump.accept(visitor);
+        self.blue_electric_pump.accept(visitor);
+        self.yellow_electric_pump.accept(visitor);
+        self.ram_air_turbine.accept(visitor);
+        self.ptu.accept(visitor);
+
+        visitor.visit(self);
+    }
+
+    fn receive_power(&mut self, supplied_power: &SuppliedPower) {
+        self.ac_bus_1_is_powered =
+            supplied_power.is_powered(&ElectricalBusType::AlternatingCurrent(1));
+        self.ac_bus_2_is_powered =
+            supplied_power.is_powered(&ElectricalBusType::AlternatingCurrent(2));
+    }
+}
+
+pub(super) struct A320HydraulicOverheadPanel {
+    engine_1_pump: OnOffFaultPushButton,
+    engine_2_pump: OnOffFaultPushButton,
+    blue_electric_pump: AutoOffFaultPushButton,
+    yellow_electric_pump: OnOffFaultPushButton,
+    ptu: AutoOffFaultPushButton,
+}
+impl A320HydraulicOverheadPanel {
+    pub fn new(registry: &mut VariableRegistry) -> A320HydraulicOverheadPanel {
+        A320HydraulicOverheadPanel {
+            engine_1_pump: OnOffFaultPushButton::new_on(registry, "HYD_ENG_1_PUMP"),
+            engine_2_pump: OnOffFaultPushButton::new_on(registry, "HYD_ENG_2_PUMP"),
+            blue_electric_pump: AutoOffFaultPushButton::new_auto(registry, "HYD_EPUMPB"),
+            yellow_electric_pump: OnOffFaultPushButton::new_off(registry, "HYD_EPUMPY"),
+            ptu: AutoOffFaultPushButton::new_auto(registry, "HYD_PTU"),
+        }
+    }
+
+    pub fn update_after_hydraulic(&mut self, hydraulic: &A320Hydraulic) {
+        self.engine_1_pump
+            .set_fault(hydraulic.engine_1_pump.is_active() && !hydraulic.is_green_pressurised());
+        self.engine_2_pump
+            .set_fault(hydraulic.engine_2_pump.is_active() && !hydraulic.is_yellow_pressurised());
+        self.blue_electric_pump.set_fault(
+            hydraulic.blue_electric_pump.is_active() && !hydraulic.is_blue_pressurised(),
+        );
+        self.yellow_electric_pump.set_fault(
+            hydraulic.yellow_electric_pump.is_active() && !hydraulic.is_yellow_pressurised(),
+        );
+    }
+
+    fn engine_1_pump_is_on(&self) -> bool {
+        self.engine_1_pump.is_on()
+    }
+
+    fn engine_2_pump_is_on(&self) -> bool {
+        self.engine_2_pump.is_on()
+    }
+
+    fn blue_electric_pump_is_auto(&self) -> bool {
+        self.blue_electric_pump.is_auto()
     }
 
-    pub fn update(&mut self, _: &UpdateContext) {}
+    fn yellow_electric_pump_is_on(&self) -> bool {
+        self.yellow_electric_pump.is_on()
+    }
+
+    fn ptu_is_auto(&self) -> bool {
+        self.ptu.is_auto()
+    }
+}
+impl SimulationElement for A320HydraulicOverheadPanel {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.engine_1_pump.accept(visitor);
+        self.engine_2_pump.accept(visitor);
+        self.blue_electric_pump.accept(visitor);
+        self.yellow_electric_pump.accept(visitor);
+        self.ptu.accept(visitor);
+
+        visitor.visit(self);
+    }
+}
+
+#[cfg(test)]
+mod a320_hydraulic_tests {
+    use super::*;
+    use std::time::Duration;
+    use systems::{
+        electrical::{Potential, PotentialOrigin},
+        simulation::{test::SimulationTestBed, Aircraft},
+    };
+    use uom::si::electric_potential::volt;
+
+    struct TestLgciu {
+        compressed: bool,
+    }
+    impl TestLgciu {
+        fn new(compressed: bool) -> Self {
+            Self { compressed }
+        }
+    }
+    impl LgciuWeightOnWheels for TestLgciu {
+        fn left_and_right_gear_compressed(&self) -> bool {
+            self.compressed
+        }
+    }
+
+    struct A320HydraulicTestAircraft {
+        engine_1: Engine,
+        engine_2: Engine,
+        hydraulic: A320Hydraulic,
+        overhead: A320HydraulicOverheadPanel,
+        is_ac_powered: bool,
+    }
+    impl A320HydraulicTestAircraft {
+        fn new() -> Self {
+            let mut registry = VariableRegistry::new();
+
+            Self {
+                engine_1: Engine::new(&mut registry, 1),
+                engine_2: Engine::new(&mut registry, 2),
+                hydraulic: A320Hydraulic::new(&mut registry),
+                overhead: A320HydraulicOverheadPanel::new(&mut registry),
+                is_ac_powered: false,
+            }
+        }
+    }
+    impl Aircraft for A320HydraulicTestAircraft {
+        fn update_before_power_distribution(&mut self, context: &UpdateContext) {
+            self.engine_1.update(context);
+            self.engine_2.update(context);
+        }
+
+        fn update_after_power_distribution(&mut self, context: &UpdateContext) {
+            self.hydraulic.update(
+                context,
+                &self.engine_1,
+                &self.engine_2,
+                &self.overhead,
+                &TestLgciu::new(context.is_on_ground()),
+                VolumeRate::default(),
+            );
+            self.overhead.update_after_hydraulic(&self.hydraulic);
+        }
+
+        fn get_supplied_power(&mut self) -> SuppliedPower {
+            let mut supplied_power = SuppliedPower::new();
+            if self.is_ac_powered {
+                for number in 1..=2 {
+                    supplied_power.add(
+                        ElectricalBusType::AlternatingCurrent(number),
+                        Potential::single(
+                            PotentialOrigin::EngineGenerator(number as usize),
+                            ElectricPotential::new::<volt>(115.),
+                        ),
+                    );
+                }
+            }
+
+            supplied_power
+        }
+    }
+    impl SimulationElement for A320HydraulicTestAircraft {
+        fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            self.engine_1.accept(visitor);
+            self.engine_2.accept(visitor);
+            self.hydraulic.accept(visitor);
+            self.overhead.accept(visitor);
+
+            visitor.visit(self);
+        }
+    }
+
+    struct A320HydraulicTestBed {
+        aircraft: A320HydraulicTestAircraft,
+        simulation_test_bed: SimulationTestBed,
+    }
+    impl A320HydraulicTestBed {
+        fn new() -> Self {
+            let mut aircraft = A320HydraulicTestAircraft::new();
+            Self {
+                simulation_test_bed: SimulationTestBed::seeded_with(&mut aircraft),
+                aircraft,
+            }
+        }
+
+        fn running_engine(mut self, number: usize) -> Self {
+            self.simulation_test_bed
+                .write_f64(&format!("TURB ENG CORRECTED N2:{}", number), 80.);
+            self
+        }
+
+        fn ac_powered(mut self) -> Self {
+            self.aircraft.is_ac_powered = true;
+            self
+        }
+
+        fn pump_off(mut self, name: &str) -> Self {
+            self.simulation_test_bed
+                .write_bool(&format!("OVHD_{}_PB_IS_ON", name), false);
+            self
+        }
+
+        fn yellow_electric_pump_on(mut self) -> Self {
+            self.simulation_test_bed
+                .write_bool("OVHD_HYD_EPUMPY_PB_IS_ON", true);
+            self
+        }
+
+        fn ptu_off(mut self) -> Self {
+            self.simulation_test_bed
+                .write_bool("OVHD_HYD_PTU_PB_IS_AUTO", false);
+            self
+        }
+
+        fn on_ground(mut self) -> Self {
+            self.simulation_test_bed.set_on_ground(true);
+            self
+        }
+
+        fn indicated_airspeed(mut self, knots: f64) -> Self {
+            self.simulation_test_bed
+                .set_indicated_airspeed(Velocity::new::<knot>(knots));
+            self
+        }
+
+        fn run_for(mut self, duration: Duration) -> Self {
+            self.simulation_test_bed
+                .set_delta(Duration::from_millis(100));
+            for _ in 0..(duration.as_millis() / 100) {
+                self.simulation_test_bed.run_aircraft(&mut self.aircraft);
+            }
+            self
+        }
+
+        fn and(self) -> Self {
+            self
+        }
+
+        fn is_green_pressurised(&mut self) -> bool {
+            self.simulation_test_bed
+                .read_bool("HYD_GREEN_IS_PRESSURISED")
+        }
+
+        fn is_blue_pressurised(&self) -> bool {
+            self.aircraft.hydraulic.is_blue_pressurised()
+        }
+
+        fn is_yellow_pressurised(&mut self) -> bool {
+            self.simulation_test_bed
+                .read_bool("HYD_YELLOW_IS_PRESSURISED")
+        }
+
+        fn ptu_is_active(&mut self) -> bool {
+            self.simulation_test_bed.read_bool("HYD_PTU_IS_ACTIVE")
+        }
+
+        fn rat_pump_is_active(&mut self) -> bool {
+            self.simulation_test_bed.read_bool("HYD_RAT_PUMP_IS_ACTIVE")
+        }
+    }
+
+    fn test_bed() -> A320HydraulicTestBed {
+        A320HydraulicTestBed::new()
+    }
+
+    fn test_bed_with() -> A320HydraulicTestBed {
+        test_bed()
+    }
+
+    #[test]
+    fn cold_and_dark_has_no_pressure() {
+        let mut test_bed = test_bed_with()
+            .on_ground()
+            .and()
+            .run_for(Duration::from_secs(5));
+
+        assert!(!test_bed.is_green_pressurised());
+        assert!(!test_bed.is_blue_pressurised());
+        assert!(!test_bed.is_yellow_pressurised());
+    }
+
+    #[test]
+    fn all_circuits_pressurised_with_both_engines_running() {
+        let mut test_bed = test_bed_with()
+            .running_engine(1)
+            .running_engine(2)
+            .ac_powered()
+            .and()
+            .run_for(Duration::from_secs(5));
+
+        assert!(test_bed.is_green_pressurised());
+        assert!(test_bed.is_blue_pressurised());
+        assert!(test_bed.is_yellow_pressurised());
+    }
+
+    #[test]
+    fn blue_not_pressurised_without_ac_power() {
+        let test_bed = test_bed_with()
+            .running_engine(1)
+            .running_engine(2)
+            .on_ground()
+            .and()
+            .run_for(Duration::from_secs(5));
+
+        assert!(!test_bed.is_blue_pressurised());
+    }
+
+    #[test]
+    fn blue_not_pressurised_when_no_engine_running() {
+        let test_bed = test_bed_with()
+            .ac_powered()
+            .and()
+            .run_for(Duration::from_secs(5));
+
+        assert!(!test_bed.is_blue_pressurised());
+    }
+
+    #[test]
+    fn yellow_electric_pump_pressurises_yellow() {
+        let mut test_bed = test_bed_with()
+            .ac_powered()
+            .yellow_electric_pump_on()
+            .and()
+            .run_for(Duration::from_secs(5));
+
+        assert!(test_bed.is_yellow_pressurised());
+    }
+
+    #[test]
+    fn ptu_pressurises_green_from_yellow() {
+        let mut test_bed = test_bed_with()
+            .ac_powered()
+            .yellow_electric_pump_on()
+            .and()
+            .run_for(Duration::from_secs(5));
+
+        assert!(test_bed.ptu_is_active());
+        assert!(test_bed.is_green_pressurised());
+    }
+
+    #[test]
+    fn ptu_pressurises_yellow_from_green_when_engine_2_pump_off() {
+        let mut test_bed = test_bed_with()
+            .running_engine(1)
+            .running_engine(2)
+            .ac_powered()
+            .pump_off("HYD_ENG_2_PUMP")
+            .and()
+            .run_for(Duration::from_secs(5));
+
+        assert!(test_bed.ptu_is_active());
+        assert!(test_bed.is_yellow_pressurised());
+    }
+
+    #[test]
+    fn ptu_does_not_pressurise_green_when_off() {
+        let mut test_bed = test_bed_with()
+            .ac_powered()
+            .yellow_electric_pump_on()
+            .ptu_off()
+            .and()
+            .run_for(Duration::from_secs(5));
+
+        assert!(!test_bed.ptu_is_active());
+        assert!(!test_bed.is_green_pressurised());
+    }
+
+    #[test]
+    fn rat_pressurises_blue_when_ac_buses_lost_in_flight() {
+        let mut test_bed = test_bed_with()
+            .indicated_airspeed(250.)
+            .and()
+            .run_for(Duration::from_secs(5));
+
+        assert!(test_bed.rat_pump_is_active());
+        assert!(test_bed.is_blue_pressurised());
+    }
+
+    #[test]
+    fn rat_does_not_deploy_on_ground() {
+        let mut test_bed = test_bed_with()
+            .on_ground()
+            .indicated_airspeed(120.)
+            .and()
+            .run_for(Duration::from_secs(5));
+
+        assert!(!test_bed.rat_pump_is_active());
+        assert!(!test_bed.is_blue_pressurised());
+    }
+
+    #[test]
+    fn engine_pumps_have_no_fault_when_their_circuits_are_pressurised_or_engine_stopped() {
+        let mut test_bed = test_bed_with()
+            .running_engine(1)
+            .on_ground()
+            .and()
+            .run_for(Duration::from_secs(5));
+
+        assert!(!test_bed
+            .simulation_test_bed
+            .read_bool("OVHD_HYD_ENG_1_PUMP_PB_HAS_FAULT"));
+        assert!(!test_bed
+            .simulation_test_bed
+            .read_bool("OVHD_HYD_ENG_2_PUMP_PB_HAS_FAULT"));
+    }
 }
-impl SimulationElement for A320Hydraulic {}
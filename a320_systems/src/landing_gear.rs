@@ -0,0 +1,418 @@
+use systems::{
+    electrical::ElectricalBusType,
+    landing_gear::{GearLeg, LandingGearControlInterfaceUnit},
+    shared::LgciuWeightOnWheels,
+    simulation::{
+        QuantityIdentifier, SimulationElement, SimulationElementVisitor, SimulatorReader,
+        UpdateContext, VariableRegistry, VariableUnit,
+    },
+};
+use uom::si::{f64::*, pressure::psi, velocity::knot};
+
+/// The landing gear is actuated by the green hydraulic system. LGCIU 1 controls
+/// the gear as long as it is powered and hasn't failed, otherwise LGCIU 2 takes over.
+pub struct A320LandingGear {
+    lgciu_1: LandingGearControlInterfaceUnit,
+    lgciu_2: LandingGearControlInterfaceUnit,
+    nose_gear: GearLeg,
+    left_gear: GearLeg,
+    right_gear: GearLeg,
+}
+impl A320LandingGear {
+    /// Above this airspeed the safety valve cuts the hydraulic supply to the gear.
+    const SAFETY_VALVE_CLOSING_AIRSPEED_KNOTS: f64 = 260.;
+
+    pub fn new(registry: &mut VariableRegistry) -> A320LandingGear {
+        A320LandingGear {
+            lgciu_1: LandingGearControlInterfaceUnit::new(
+                registry,
+                1,
+                ElectricalBusType::DirectCurrentEssential,
+            ),
+            lgciu_2: LandingGearControlInterfaceUnit::new(
+                registry,
+                2,
+                ElectricalBusType::DirectCurrent(2),
+            ),
+            nose_gear: GearLeg::new(registry, "CENTER", 0),
+            left_gear: GearLeg::new(registry, "LEFT", 1),
+            right_gear: GearLeg::new(registry, "RIGHT", 2),
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        context: &UpdateContext,
+        lever: &A320LandingGearLever,
+        green_pressure: Pressure,
+    ) {
+        let legs = [&self.nose_gear, &self.left_gear, &self.right_gear];
+        self.lgciu_1.update(context, lever.is_down(), legs);
+        self.lgciu_2.update(context, lever.is_down(), legs);
+
+        let controlling_lgciu = self.controlling_lgciu();
+        let commands = controlling_lgciu.map(|lgciu| lgciu.commands());
+        let hydraulic_pressure = if commands.is_some()
+            && context.indicated_airspeed()
+                <= Velocity::new::<knot>(A320LandingGear::SAFETY_VALVE_CLOSING_AIRSPEED_KNOTS)
+        {
+            green_pressure
+        } else {
+            Pressure::new::<psi>(0.)
+        };
+
+        for (index, leg) in [
+            &mut self.nose_gear,
+            &mut self.left_gear,
+            &mut self.right_gear,
+        ]
+        .iter_mut()
+        .enumerate()
+        {
+            let (door_should_open, gear_should_extend) = match commands {
+                Some(commands) => (
+                    commands[index].door_should_open(),
+                    commands[index].gear_should_extend(),
+                ),
+                None => (false, true),
+            };
+
+            leg.update(
+                context,
+                door_should_open,
+                gear_should_extend,
+                hydraulic_pressure,
+                lever.gravity_extension_is_on(),
+            );
+        }
+    }
+
+    fn controlling_lgciu(&self) -> Option<&LandingGearControlInterfaceUnit> {
+        if self.lgciu_1.is_operating() {
+            Some(&self.lgciu_1)
+        } else if self.lgciu_2.is_operating() {
+            Some(&self.lgciu_2)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_up_and_locked(&self) -> bool {
+        self.lgciu_1.all_up_and_locked() || self.lgciu_2.all_up_and_locked()
+    }
+
+    pub fn is_down_and_locked(&self) -> bool {
+        self.lgciu_1.all_down_and_locked() || self.lgciu_2.all_down_and_locked()
+    }
+}
+impl LgciuWeightOnWheels for A320LandingGear {
+    fn left_and_right_gear_compressed(&self) -> bool {
+        self.controlling_lgciu()
+            .is_some_and(|lgciu| lgciu.left_and_right_gear_compressed())
+    }
+}
+impl SimulationElement for A320LandingGear {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.lgciu_1.accept(visitor);
+        self.lgciu_2.accept(visitor);
+        self.nose_gear.accept(visitor);
+        self.left_gear.accept(visitor);
+        self.right_gear.accept(visitor);
+
+        visitor.visit(self);
+    }
+}
+
+/// The landing gear lever and the gravity gear extension hand crank.
+pub struct A320LandingGearLever {
+    lever_position_id: QuantityIdentifier<bool>,
+    gravity_extension_id: QuantityIdentifier<bool>,
+    is_down: bool,
+    gravity_extension_is_on: bool,
+}
+impl A320LandingGearLever {
+    pub fn new(registry: &mut VariableRegistry) -> A320LandingGearLever {
+        A320LandingGearLever {
+            lever_position_id: registry.register_read("GEAR HANDLE POSITION", VariableUnit::Bool),
+            gravity_extension_id: registry
+                .register_read("GEAR_GRAVITY_EXTENSION_IS_ON", VariableUnit::Bool),
+            is_down: true,
+            gravity_extension_is_on: false,
+        }
+    }
+
+    pub fn is_down(&self) -> bool {
+        self.is_down
+    }
+
+    pub fn gravity_extension_is_on(&self) -> bool {
+        self.gravity_extension_is_on
+    }
+}
+impl SimulationElement for A320LandingGearLever {
+    fn read(&mut self, reader: &mut SimulatorReader) {
+        self.is_down = reader.read_bool(&self.lever_position_id);
+        self.gravity_extension_is_on = reader.read_bool(&self.gravity_extension_id);
+    }
+}
+
+#[cfg(test)]
+mod a320_landing_gear_tests {
+    use super::*;
+    use std::time::Duration;
+    use systems::{
+        electrical::{consumption::SuppliedPower, Potential, PotentialOrigin},
+        simulation::{test::SimulationTestBed, Aircraft, FailureType},
+    };
+    use uom::si::electric_potential::volt;
+
+    struct A320LandingGearTestAircraft {
+        landing_gear: A320LandingGear,
+        lever: A320LandingGearLever,
+        green_pressure: Pressure,
+        is_dc_ess_powered: bool,
+        is_dc_2_powered: bool,
+    }
+    impl A320LandingGearTestAircraft {
+        fn new() -> Self {
+            let mut registry = VariableRegistry::new();
+
+            Self {
+                landing_gear: A320LandingGear::new(&mut registry),
+                lever: A320LandingGearLever::new(&mut registry),
+                green_pressure: Pressure::new::<psi>(3000.),
+                is_dc_ess_powered: true,
+                is_dc_2_powered: true,
+            }
+        }
+    }
+    impl Aircraft for A320LandingGearTestAircraft {
+        fn update_after_power_distribution(&mut self, context: &UpdateContext) {
+            self.landing_gear
+                .update(context, &self.lever, self.green_pressure);
+        }
+
+        fn get_supplied_power(&mut self) -> SuppliedPower {
+            let mut supplied_power = SuppliedPower::new();
+            let dc = Potential::single(
+                PotentialOrigin::Battery(10),
+                ElectricPotential::new::<volt>(28.),
+            );
+            if self.is_dc_ess_powered {
+                supplied_power.add(ElectricalBusType::DirectCurrentEssential, dc);
+            }
+            if self.is_dc_2_powered {
+                supplied_power.add(ElectricalBusType::DirectCurrent(2), dc);
+            }
+
+            supplied_power
+        }
+    }
+    impl SimulationElement for A320LandingGearTestAircraft {
+        fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            self.landing_gear.accept(visitor);
+            self.lever.accept(visitor);
+
+            visitor.visit(self);
+        }
+    }
+
+    struct A320LandingGearTestBed {
+        aircraft: A320LandingGearTestAircraft,
+        simulation_test_bed: SimulationTestBed,
+    }
+    impl A320LandingGearTestBed {
+        fn new() -> Self {
+            let mut test_bed = Self {
+                aircraft: A320LandingGearTestAircraft::new(),
+                simulation_test_bed: SimulationTestBed::new_with_delta(Duration::from_secs(1)),
+            };
+            test_bed
+                .simulation_test_bed
+                .set_indicated_airspeed(Velocity::new::<knot>(180.));
+
+            test_bed.lever_down()
+        }
+
+        fn run_for(mut self, seconds: u64) -> Self {
+            for _ in 0..seconds {
+                self.simulation_test_bed.run_aircraft(&mut self.aircraft);
+            }
+
+            self
+        }
+
+        fn lever_up(mut self) -> Self {
+            self.simulation_test_bed
+                .write_bool("GEAR HANDLE POSITION", false);
+            self
+        }
+
+        fn lever_down(mut self) -> Self {
+            self.simulation_test_bed
+                .write_bool("GEAR HANDLE POSITION", true);
+            self
+        }
+
+        fn gravity_extension(mut self) -> Self {
+            self.simulation_test_bed
+                .write_bool("GEAR_GRAVITY_EXTENSION_IS_ON", true);
+            self
+        }
+
+        fn airspeed(mut self, knots: f64) -> Self {
+            self.simulation_test_bed
+                .set_indicated_airspeed(Velocity::new::<knot>(knots));
+            self
+        }
+
+        fn green_pressure(mut self, pressure: f64) -> Self {
+            self.aircraft.green_pressure = Pressure::new::<psi>(pressure);
+            self
+        }
+
+        fn dc_ess_unpowered(mut self) -> Self {
+            self.aircraft.is_dc_ess_powered = false;
+            self
+        }
+
+        fn lgciu_1_failed(mut self) -> Self {
+            self.simulation_test_bed
+                .activate_failure(FailureType::Lgciu(1));
+            self
+        }
+
+        fn dc_unpowered(mut self) -> Self {
+            self.aircraft.is_dc_ess_powered = false;
+            self.aircraft.is_dc_2_powered = false;
+            self
+        }
+
+        fn on_ground(mut self) -> Self {
+            for index in 0..=2 {
+                self.simulation_test_bed
+                    .write_f64(&format!("CONTACT POINT COMPRESSION:{}", index), 50.);
+            }
+            self
+        }
+
+        fn is_up_and_locked(&self) -> bool {
+            self.aircraft.landing_gear.is_up_and_locked()
+        }
+
+        fn is_down_and_locked(&self) -> bool {
+            self.aircraft.landing_gear.lgciu_1.all_down_and_locked()
+        }
+
+        fn weight_on_wheels(&self) -> bool {
+            self.aircraft.landing_gear.left_and_right_gear_compressed()
+        }
+    }
+
+    fn test_bed() -> A320LandingGearTestBed {
+        A320LandingGearTestBed::new()
+    }
+
+    #[test]
+    fn gear_down_and_locked_with_lever_down() {
+        let test_bed = test_bed().run_for(1);
+
+        assert!(test_bed.is_down_and_locked());
+        assert!(!test_bed.is_up_and_locked());
+    }
+
+    #[test]
+    fn gear_retracts_with_lever_up() {
+        let test_bed = test_bed().lever_up().run_for(30);
+
+        assert!(test_bed.is_up_and_locked());
+    }
+
+    #[test]
+    fn gear_extends_with_lever_down() {
+        let test_bed = test_bed().lever_up().run_for(30).lever_down().run_for(30);
+
+        assert!(test_bed.is_down_and_locked());
+    }
+
+    #[test]
+    fn lgciu_2_takes_over_when_lgciu_1_unpowered() {
+        let test_bed = test_bed().dc_ess_unpowered().lever_up().run_for(30);
+
+        assert!(test_bed.is_up_and_locked());
+    }
+
+    #[test]
+    fn lgciu_2_takes_over_when_lgciu_1_failed() {
+        let test_bed = test_bed().lgciu_1_failed().lever_up().run_for(30);
+
+        assert!(test_bed.is_up_and_locked());
+    }
+
+    #[test]
+    fn gear_does_not_move_without_green_pressure() {
+        let test_bed = test_bed().green_pressure(0.).lever_up().run_for(30);
+
+        assert!(test_bed.is_down_and_locked());
+    }
+
+    #[test]
+    fn gear_does_not_move_when_both_lgcius_unpowered() {
+        let test_bed = test_bed()
+            .lever_up()
+            .run_for(30)
+            .dc_unpowered()
+            .lever_down()
+            .run_for(30);
+
+        assert!(test_bed.aircraft.landing_gear.nose_gear.is_uplocked());
+        assert!(test_bed.aircraft.landing_gear.left_gear.is_uplocked());
+        assert!(test_bed.aircraft.landing_gear.right_gear.is_uplocked());
+    }
+
+    #[test]
+    fn safety_valve_prevents_extension_above_260_knots() {
+        let test_bed = test_bed()
+            .lever_up()
+            .run_for(30)
+            .airspeed(300.)
+            .lever_down()
+            .run_for(30);
+
+        assert!(test_bed.is_up_and_locked());
+    }
+
+    #[test]
+    fn gravity_extension_extends_gear_without_green_pressure() {
+        let test_bed = test_bed()
+            .lever_up()
+            .run_for(30)
+            .green_pressure(0.)
+            .lever_down()
+            .gravity_extension()
+            .run_for(30);
+
+        assert!(test_bed.is_down_and_locked());
+    }
+
+    #[test]
+    fn provides_weight_on_wheels_on_ground() {
+        let test_bed = test_bed().on_ground().run_for(1);
+
+        assert!(test_bed.weight_on_wheels());
+    }
+
+    #[test]
+    fn provides_no_weight_on_wheels_in_flight() {
+        let test_bed = test_bed().run_for(1);
+
+        assert!(!test_bed.weight_on_wheels());
+    }
+
+    #[test]
+    fn gear_does_not_retract_on_ground() {
+        let test_bed = test_bed().on_ground().lever_up().run_for(30);
+
+        assert!(test_bed.is_down_and_locked());
+    }
+}
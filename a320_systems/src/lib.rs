@@ -1,13 +1,26 @@
+mod air_conditioning;
 mod electrical;
+mod engine;
+mod flight_warning;
 mod fuel;
 mod hydraulic;
+mod landing_gear;
 mod pneumatic;
 mod power_consumption;
+mod pressurization;
 
-use self::{fuel::A320Fuel, pneumatic::A320PneumaticOverheadPanel};
+use self::{
+    air_conditioning::{A320AirConditioning, A320AirConditioningPanel},
+    engine::{A320EngineStart, A320EngineStartPanel},
+    flight_warning::A320FlightWarningSystem,
+    fuel::{A320Fuel, A320FuelOverheadPanel},
+    pneumatic::{A320Pneumatic, A320PneumaticOverheadPanel},
+};
 use electrical::{A320Electrical, A320ElectricalOverheadPanel, A320ElectricalUpdateArguments};
-use hydraulic::A320Hydraulic;
+use hydraulic::{A320Hydraulic, A320HydraulicOverheadPanel};
+use landing_gear::{A320LandingGear, A320LandingGearLever};
 use power_consumption::A320PowerConsumption;
+use pressurization::A320Pressurization;
 use systems::{
     apu::{
         Aps3200ApuGenerator, Aps3200StartMotor, AuxiliaryPowerUnit, AuxiliaryPowerUnitFactory,
@@ -15,93 +28,223 @@ use systems::{
     },
     electrical::{consumption::SuppliedPower, ElectricalSystem, ExternalPowerSource},
     engine::Engine,
-    landing_gear::LandingGear,
-    simulation::{Aircraft, SimulationElement, SimulationElementVisitor, UpdateContext},
+    flight_warning::FlightWarningComputer,
+    simulation::{
+        Aircraft, SimulationElement, SimulationElementVisitor, UpdateContext, VariableRegistry,
+    },
 };
 
 pub struct A320 {
     apu: AuxiliaryPowerUnit<Aps3200ApuGenerator, Aps3200StartMotor>,
     apu_fire_overhead: AuxiliaryPowerUnitFireOverheadPanel,
     apu_overhead: AuxiliaryPowerUnitOverheadPanel,
+    pneumatic: A320Pneumatic,
     pneumatic_overhead: A320PneumaticOverheadPanel,
+    air_conditioning: A320AirConditioning,
+    air_conditioning_panel: A320AirConditioningPanel,
+    pressurization: A320Pressurization,
     electrical_overhead: A320ElectricalOverheadPanel,
     fuel: A320Fuel,
+    fuel_overhead: A320FuelOverheadPanel,
     engine_1: Engine,
     engine_2: Engine,
+    engine_start: A320EngineStart,
+    engine_start_panel: A320EngineStartPanel,
     electrical: A320Electrical,
     power_consumption: A320PowerConsumption,
     ext_pwr: ExternalPowerSource,
     hydraulic: A320Hydraulic,
-    landing_gear: LandingGear,
+    hydraulic_overhead: A320HydraulicOverheadPanel,
+    landing_gear: A320LandingGear,
+    landing_gear_lever: A320LandingGearLever,
+    fwc: FlightWarningComputer,
+    fws: A320FlightWarningSystem,
 }
 impl A320 {
-    pub fn new() -> A320 {
+    pub fn new(registry: &mut VariableRegistry) -> A320 {
         A320 {
-            apu: AuxiliaryPowerUnitFactory::new_aps3200(1),
-            apu_fire_overhead: AuxiliaryPowerUnitFireOverheadPanel::new(),
-            apu_overhead: AuxiliaryPowerUnitOverheadPanel::new(),
-            pneumatic_overhead: A320PneumaticOverheadPanel::new(),
-            electrical_overhead: A320ElectricalOverheadPanel::new(),
-            fuel: A320Fuel::new(),
-            engine_1: Engine::new(1),
-            engine_2: Engine::new(2),
-            electrical: A320Electrical::new(),
+            apu: registry.with_owner("apu", |registry| {
+                AuxiliaryPowerUnitFactory::new_aps3200(registry, 1)
+            }),
+            apu_fire_overhead: registry.with_owner("apu", AuxiliaryPowerUnitFireOverheadPanel::new),
+            apu_overhead: registry.with_owner("apu", AuxiliaryPowerUnitOverheadPanel::new),
+            pneumatic: registry.with_owner("pneumatic", A320Pneumatic::new),
+            pneumatic_overhead: registry.with_owner("pneumatic", A320PneumaticOverheadPanel::new),
+            air_conditioning: registry.with_owner("air_conditioning", A320AirConditioning::new),
+            air_conditioning_panel: registry
+                .with_owner("air_conditioning", A320AirConditioningPanel::new),
+            pressurization: registry.with_owner("pressurization", A320Pressurization::new),
+            electrical_overhead: registry
+                .with_owner("electrical", A320ElectricalOverheadPanel::new),
+            fuel: registry.with_owner("fuel", A320Fuel::new),
+            fuel_overhead: registry.with_owner("fuel", A320FuelOverheadPanel::new),
+            engine_1: registry.with_owner("engine", |registry| Engine::new(registry, 1)),
+            engine_2: registry.with_owner("engine", |registry| Engine::new(registry, 2)),
+            engine_start: registry.with_owner("engine", A320EngineStart::new),
+            engine_start_panel: registry.with_owner("engine", A320EngineStartPanel::new),
+            electrical: registry.with_owner("electrical", A320Electrical::new),
             power_consumption: A320PowerConsumption::new(),
-            ext_pwr: ExternalPowerSource::new(),
-            hydraulic: A320Hydraulic::new(),
-            landing_gear: LandingGear::new(),
+            ext_pwr: registry.with_owner("electrical", ExternalPowerSource::new),
+            hydraulic: registry.with_owner("hydraulic", A320Hydraulic::new),
+            hydraulic_overhead: registry.with_owner("hydraulic", A320HydraulicOverheadPanel::new),
+            landing_gear: registry.with_owner("landing_gear", A320LandingGear::new),
+            landing_gear_lever: registry.with_owner("landing_gear", A320LandingGearLever::new),
+            fwc: registry.with_owner("flight_warning", FlightWarningComputer::new),
+            fws: registry.with_owner("flight_warning", A320FlightWarningSystem::new),
         }
     }
 }
-impl Default for A320 {
-    fn default() -> Self {
-        Self::new()
-    }
-}
 impl Aircraft for A320 {
     fn update_before_power_distribution(&mut self, context: &UpdateContext) {
-        self.apu.update_before_electrical(
-            context,
-            &self.apu_overhead,
-            &self.apu_fire_overhead,
-            self.pneumatic_overhead.apu_bleed_is_on(),
-            // This will be replaced when integrating the whole electrical system.
-            // For now we use the same logic as found in the JavaScript code; ignoring whether or not
-            // the engine generators are supplying electricity.
-            self.electrical_overhead.apu_generator_is_on()
-                && !(self.electrical_overhead.external_power_is_on()
-                    && self.electrical_overhead.external_power_is_available()),
-            self.fuel.left_inner_tank_has_fuel_remaining(),
-        );
+        context.profile("engine", || {
+            self.engine_1.update(context);
+            self.engine_2.update(context);
+        });
 
-        self.electrical.update(
-            context,
-            &self.ext_pwr,
-            &self.electrical_overhead,
-            &mut A320ElectricalUpdateArguments::new(
-                [self.engine_1.corrected_n2(), self.engine_2.corrected_n2()],
-                [
-                    self.electrical_overhead.idg_1_push_button_released(),
-                    self.electrical_overhead.idg_2_push_button_released(),
-                ],
-                &mut self.apu,
-                self.hydraulic.is_blue_pressurised(),
-                self.apu_overhead.master_is_on(),
-                self.apu_overhead.start_is_on(),
-                self.landing_gear.is_up_and_locked(),
-            ),
-        );
+        context.profile("apu", || {
+            self.apu.update_before_electrical(
+                context,
+                &self.apu_overhead,
+                &self.apu_fire_overhead,
+                self.pneumatic_overhead.apu_bleed_is_on(),
+                // This will be replaced when integrating the whole electrical system.
+                // For now we use the same logic as found in the JavaScript code; ignoring whether or not
+                // the engine generators are supplying electricity.
+                self.electrical_overhead.apu_generator_is_on()
+                    && !(self.electrical_overhead.external_power_is_on()
+                        && self.electrical_overhead.external_power_is_available()),
+                self.fuel.apu_fuel_pressure(),
+            );
+        });
 
-        self.apu.update_after_electrical();
+        context.profile("electrical", || {
+            self.electrical.update(
+                context,
+                &self.ext_pwr,
+                &self.electrical_overhead,
+                &mut A320ElectricalUpdateArguments::new(
+                    [&self.engine_1, &self.engine_2],
+                    [
+                        self.electrical_overhead.idg_1_push_button_released(),
+                        self.electrical_overhead.idg_2_push_button_released(),
+                    ],
+                    &mut self.apu,
+                    self.hydraulic.blue_pressure(),
+                    self.apu_overhead.master_is_on(),
+                    self.apu_overhead.start_is_on(),
+                    self.landing_gear.is_up_and_locked(),
+                ),
+            );
+        });
 
-        self.electrical_overhead
-            .update_after_electrical(&self.electrical);
-        self.apu_overhead.update_after_apu(&self.apu);
+        context.profile("apu", || self.apu.update_after_electrical());
+
+        context.profile("electrical", || {
+            self.electrical_overhead
+                .update_after_electrical(&self.electrical);
+        });
+
+        context.profile("apu", || self.apu_overhead.update_after_apu(&self.apu));
     }
 
     fn update_after_power_distribution(&mut self, context: &UpdateContext) {
-        self.hydraulic.update(context);
-        self.power_consumption.update(context);
+        context.profile("landing_gear", || {
+            self.landing_gear.update(
+                context,
+                &self.landing_gear_lever,
+                self.hydraulic.green_pressure(),
+            );
+        });
+
+        context.profile("fuel", || {
+            self.fuel.update(
+                context,
+                &self.fuel_overhead,
+                self.apu_overhead.master_is_on(),
+                &self.landing_gear,
+            );
+        });
+
+        context.profile("engine", || {
+            self.engine_start.update(
+                context,
+                &self.engine_1,
+                &self.engine_2,
+                &self.engine_start_panel,
+                self.pneumatic.left_duct_pressure(),
+                self.pneumatic.right_duct_pressure(),
+                &self.landing_gear,
+            );
+        });
+
+        context.profile("pneumatic", || {
+            self.pneumatic.update(
+                context,
+                &self.engine_1,
+                &self.engine_2,
+                &self.engine_start,
+                &self.pneumatic_overhead,
+                &mut self.apu,
+            );
+        });
+
+        context.profile("engine", || {
+            self.engine_1
+                .set_bleed_air_load(self.pneumatic.engine_1_bleed_air_flow());
+            self.engine_2
+                .set_bleed_air_load(self.pneumatic.engine_2_bleed_air_flow());
+        });
+
+        context.profile("air_conditioning", || {
+            self.air_conditioning.update(
+                context,
+                &self.air_conditioning_panel,
+                self.pneumatic.pack_1_flow(),
+                self.pneumatic.pack_2_flow(),
+            );
+        });
+
+        context.profile("pressurization", || {
+            self.pressurization.update(
+                context,
+                self.air_conditioning.pack_flow(),
+                &self.landing_gear,
+            );
+        });
+
+        context.profile("hydraulic", || {
+            self.hydraulic.update(
+                context,
+                &self.engine_1,
+                &self.engine_2,
+                &self.hydraulic_overhead,
+                &self.landing_gear,
+                self.electrical.emergency_generator_hydraulic_flow_demand(),
+            );
+            self.hydraulic_overhead
+                .update_after_hydraulic(&self.hydraulic);
+        });
+
+        context.profile("flight_warning", || {
+            self.fwc.update(
+                context,
+                &[&self.engine_1, &self.engine_2],
+                &self.landing_gear,
+            );
+            self.fws.update(
+                context,
+                &self.fwc,
+                [&self.engine_1, &self.engine_2],
+                &self.apu,
+                &self.hydraulic,
+                &self.pressurization,
+                &self.landing_gear,
+            );
+        });
+
+        context.profile("power_consumption", || {
+            self.power_consumption.update(context, &self.fwc)
+        });
     }
 
     fn get_supplied_power(&mut self) -> SuppliedPower {
@@ -115,14 +258,216 @@ impl SimulationElement for A320 {
         self.apu_overhead.accept(visitor);
         self.electrical_overhead.accept(visitor);
         self.fuel.accept(visitor);
+        self.fuel_overhead.accept(visitor);
+        self.pneumatic.accept(visitor);
         self.pneumatic_overhead.accept(visitor);
+        self.air_conditioning.accept(visitor);
+        self.air_conditioning_panel.accept(visitor);
+        self.pressurization.accept(visitor);
         self.engine_1.accept(visitor);
         self.engine_2.accept(visitor);
+        self.engine_start.accept(visitor);
+        self.engine_start_panel.accept(visitor);
         self.electrical.accept(visitor);
         self.power_consumption.accept(visitor);
         self.ext_pwr.accept(visitor);
+        self.hydraulic.accept(visitor);
+        self.hydraulic_overhead.accept(visitor);
         self.landing_gear.accept(visitor);
+        self.landing_gear_lever.accept(visitor);
+        self.fwc.accept(visitor);
+        self.fws.accept(visitor);
 
         visitor.visit(self);
     }
 }
+
+#[cfg(test)]
+mod a320_tests {
+    use super::*;
+    use std::{fs, path::Path, time::Duration};
+    use systems::simulation::{
+        test::SimulationTestBed, InMemoryReaderWriter, RecordingSimulatorReaderWriter, Scenario,
+        Simulation, Snapshot, VariableManifest,
+    };
+
+    fn tick(a320: &mut A320, reader_writer: &mut InMemoryReaderWriter, ticks: usize) {
+        let mut simulation = Simulation::new_with_random_seed(a320, reader_writer, 1);
+        for _ in 0..ticks {
+            simulation.tick(Duration::from_millis(100));
+        }
+    }
+
+    #[test]
+    fn replaying_a_recorded_trace_reproduces_the_simulation() {
+        let mut registry = VariableRegistry::new();
+
+        let mut recorder = RecordingSimulatorReaderWriter::new(InMemoryReaderWriter::new());
+        let mut a320 = A320::new(&mut registry);
+        let mut simulation = Simulation::new(&mut a320, &mut recorder);
+        for _ in 0..10 {
+            simulation.tick(Duration::from_millis(100));
+        }
+
+        let trace = recorder.into_trace();
+
+        assert!(trace.replay(&mut A320::new(&mut registry)).is_empty());
+    }
+
+    #[test]
+    fn running_the_yellow_electric_pump_adds_its_demand_to_engine_generator_2_load() {
+        let mut a320 = A320::new(&mut VariableRegistry::new());
+        let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_millis(100));
+        test_bed.set_on_ground(true);
+        for number in 1..=2 {
+            test_bed.write_f64(&format!("TURB ENG N1:{}", number), 20.);
+            test_bed.write_f64(&format!("TURB ENG CORRECTED N2:{}", number), 70.);
+            test_bed.write_f64(&format!("GENERAL ENG OIL PRESSURE:{}", number), 40.);
+            test_bed.write_bool(&format!("OVHD_ELEC_ENG_GEN_{}_PB_IS_ON", number), true);
+        }
+        for _ in 0..10 {
+            test_bed.run_aircraft(&mut a320);
+        }
+        let generator_1_load = test_bed.read_f64("ELEC_ENG_GEN_1_LOAD");
+        let generator_2_load = test_bed.read_f64("ELEC_ENG_GEN_2_LOAD");
+
+        test_bed.write_bool("OVHD_HYD_EPUMPY_PB_IS_ON", true);
+        for _ in 0..20 {
+            test_bed.run_aircraft(&mut a320);
+        }
+
+        // The generator's load is the power it supplies, corrected by a 0.8 power
+        // factor, as a percentage of 90 kW.
+        let added_watts =
+            (test_bed.read_f64("ELEC_ENG_GEN_2_LOAD") - generator_2_load) / 100. * 90000. / 0.8;
+        // Once pressurised, the pump draws its no-load power and the power needed
+        // to make up for the yellow loop's internal leakage.
+        assert!(added_watts > 400.);
+        assert!(added_watts < 2000.);
+        assert!((test_bed.read_f64("ELEC_ENG_GEN_1_LOAD") - generator_1_load).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn restored_a320_continues_like_the_saved_a320() {
+        let mut registry = VariableRegistry::new();
+        let mut a320 = A320::new(&mut registry);
+        let mut reader_writer = InMemoryReaderWriter::new();
+        reader_writer.write_bool("SIM ON GROUND", true);
+        reader_writer.write_f64("FUEL TANK LEFT MAIN QUANTITY", 3000.);
+        reader_writer.write_f64("FUEL TANK RIGHT MAIN QUANTITY", 3000.);
+        reader_writer.write_f64("TURB ENG N1:2", 20.);
+        reader_writer.write_f64("TURB ENG CORRECTED N2:2", 70.);
+        reader_writer.write_f64("GENERAL ENG OIL PRESSURE:2", 40.);
+        reader_writer.write_bool("OVHD_ELEC_ENG_GEN_2_PB_IS_ON", true);
+        tick(&mut a320, &mut reader_writer, 10);
+
+        // Complete a start of engine 1, such that the next start uses the other igniter,
+        // and begin the next start while the yellow electric pump spools up.
+        reader_writer.write_f64("ENG_MODE_SELECTOR", 2.);
+        reader_writer.write_bool("ENG_MASTER_1_IS_ON", true);
+        reader_writer.write_f64("TURB ENG CORRECTED N2:1", 30.);
+        tick(&mut a320, &mut reader_writer, 1);
+        reader_writer.write_f64("TURB ENG CORRECTED N2:1", 60.);
+        tick(&mut a320, &mut reader_writer, 1);
+        reader_writer.write_f64("TURB ENG CORRECTED N2:1", 20.);
+        reader_writer.write_bool("OVHD_HYD_EPUMPY_PB_IS_ON", true);
+        tick(&mut a320, &mut reader_writer, 2);
+
+        let mut restored_a320 = A320::new(&mut VariableRegistry::new());
+        Snapshot::take(&mut a320).restore(&mut restored_a320);
+        let mut restored_reader_writer = reader_writer.clone();
+
+        tick(&mut a320, &mut reader_writer, 2);
+        tick(&mut restored_a320, &mut restored_reader_writer, 2);
+
+        for definition in registry
+            .definitions()
+            .iter()
+            .filter(|definition| definition.direction().is_written())
+        {
+            let value = reader_writer.read_f64(definition.name());
+            let restored_value = restored_reader_writer.read_f64(definition.name());
+            assert!(
+                restored_value == value || (restored_value.is_nan() && value.is_nan()),
+                "{} is {} after restoring instead of {}",
+                definition.name(),
+                restored_value,
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn profiling_measures_the_update_of_every_system() {
+        let mut a320 = A320::new(&mut VariableRegistry::new());
+        let mut reader_writer = InMemoryReaderWriter::new();
+        let mut simulation = Simulation::new(&mut a320, &mut reader_writer).with_profiling(1);
+        simulation.tick(Duration::from_millis(100));
+
+        let report = simulation.profile_report().unwrap();
+        assert_eq!(
+            report
+                .updates()
+                .iter()
+                .map(|update| update.name())
+                .collect::<Vec<_>>(),
+            vec![
+                "air_conditioning",
+                "apu",
+                "electrical",
+                "engine",
+                "flight_warning",
+                "fuel",
+                "hydraulic",
+                "landing_gear",
+                "pneumatic",
+                "power_consumption",
+                "pressurization"
+            ]
+        );
+    }
+
+    #[test]
+    fn every_variable_is_owned_by_a_system() {
+        let mut registry = VariableRegistry::new();
+        A320::new(&mut registry);
+
+        let manifest = VariableManifest::from(&registry);
+        assert!(manifest
+            .entries()
+            .iter()
+            .all(|entry| !entry.owners().contains(&VariableRegistry::DEFAULT_OWNER)));
+        assert_eq!(
+            manifest.find("ELEC_AC_1_BUS_IS_POWERED").unwrap().owners(),
+            ["electrical"]
+        );
+    }
+
+    /// Runs every scenario found in the `scenarios` directory, reporting
+    /// the expectations which weren't met per scenario.
+    #[test]
+    fn scenarios_meet_their_expectations() {
+        let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios");
+        let mut paths: Vec<_> = fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().map(|extension| extension == "txt") == Some(true))
+            .collect();
+        paths.sort();
+        assert!(!paths.is_empty());
+
+        let mut report = String::new();
+        for path in paths {
+            let scenario: Scenario = fs::read_to_string(&path)
+                .unwrap()
+                .parse()
+                .unwrap_or_else(|error| panic!("{}: {}", path.display(), error));
+
+            for failure in scenario.run(&mut A320::new(&mut VariableRegistry::new()), |_, _| {}) {
+                report += &format!("{}: {}\n", path.display(), failure);
+            }
+        }
+
+        assert!(report.is_empty(), "\n{}", report);
+    }
+}
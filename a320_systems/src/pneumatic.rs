@@ -1,26 +1,691 @@
+use crate::engine::A320EngineStart;
 use systems::{
+    electrical::ElectricalBusType,
+    engine::Engine,
     overhead::OnOffFaultPushButton,
-    simulation::{SimulationElement, SimulationElementVisitor},
+    pneumatic::{
+        BleedAirConsumerValve, BleedAirDuct, BleedAirSource, BleedAirValveState, CrossBleedValve,
+        EngineBleedAirSystem, Valve,
+    },
+    simulation::{
+        QuantityIdentifier, SimulationElement, SimulationElementVisitor, SimulatorReader,
+        SimulatorWriter, UpdateContext, VariableRegistry, VariableUnit,
+    },
 };
+use uom::si::{f64::*, mass_rate::kilogram_per_second};
+
+pub struct A320Pneumatic {
+    engine_1_bleed: EngineBleedAirSystem,
+    engine_2_bleed: EngineBleedAirSystem,
+    left_duct: BleedAirDuct,
+    right_duct: BleedAirDuct,
+    crossbleed_valve: CrossBleedValve,
+    pack_1_flow_control_valve: BleedAirConsumerValve,
+    pack_2_flow_control_valve: BleedAirConsumerValve,
+    wing_anti_ice_1_valve: BleedAirConsumerValve,
+    wing_anti_ice_2_valve: BleedAirConsumerValve,
+}
+impl A320Pneumatic {
+    const PACK_NOMINAL_FLOW_KILOGRAM_PER_SECOND: f64 = 0.4;
+    const WING_ANTI_ICE_NOMINAL_FLOW_KILOGRAM_PER_SECOND: f64 = 0.2;
+
+    pub fn new(registry: &mut VariableRegistry) -> A320Pneumatic {
+        A320Pneumatic {
+            engine_1_bleed: EngineBleedAirSystem::new(registry, 1),
+            engine_2_bleed: EngineBleedAirSystem::new(registry, 2),
+            left_duct: BleedAirDuct::new(registry, "LEFT"),
+            right_duct: BleedAirDuct::new(registry, "RIGHT"),
+            crossbleed_valve: CrossBleedValve::new(
+                registry,
+                1,
+                ElectricalBusType::DirectCurrentEssential,
+            ),
+            pack_1_flow_control_valve: BleedAirConsumerValve::new(
+                registry,
+                "PACK_1_FLOW_CONTROL",
+                2,
+                MassRate::new::<kilogram_per_second>(
+                    A320Pneumatic::PACK_NOMINAL_FLOW_KILOGRAM_PER_SECOND,
+                ),
+            ),
+            pack_2_flow_control_valve: BleedAirConsumerValve::new(
+                registry,
+                "PACK_2_FLOW_CONTROL",
+                3,
+                MassRate::new::<kilogram_per_second>(
+                    A320Pneumatic::PACK_NOMINAL_FLOW_KILOGRAM_PER_SECOND,
+                ),
+            ),
+            wing_anti_ice_1_valve: BleedAirConsumerValve::new(
+                registry,
+                "WING_ANTI_ICE_1",
+                4,
+                MassRate::new::<kilogram_per_second>(
+                    A320Pneumatic::WING_ANTI_ICE_NOMINAL_FLOW_KILOGRAM_PER_SECOND,
+                ),
+            ),
+            wing_anti_ice_2_valve: BleedAirConsumerValve::new(
+                registry,
+                "WING_ANTI_ICE_2",
+                5,
+                MassRate::new::<kilogram_per_second>(
+                    A320Pneumatic::WING_ANTI_ICE_NOMINAL_FLOW_KILOGRAM_PER_SECOND,
+                ),
+            ),
+        }
+    }
+
+    pub fn update<T: BleedAirSource + BleedAirValveState>(
+        &mut self,
+        context: &UpdateContext,
+        engine_1: &Engine,
+        engine_2: &Engine,
+        engine_start: &A320EngineStart,
+        overhead: &A320PneumaticOverheadPanel,
+        apu: &mut T,
+    ) {
+        let apu_bleed_air_valve_is_open = apu.bleed_air_valve_is_open();
+
+        self.crossbleed_valve.update(
+            context,
+            match overhead.crossbleed_selector() {
+                CrossBleedValveSelectorMode::Shut => false,
+                CrossBleedValveSelectorMode::Auto => apu_bleed_air_valve_is_open,
+                CrossBleedValveSelectorMode::Open => true,
+            },
+        );
+
+        // The engine bleed valves close when the APU supplies bleed air to their side.
+        self.engine_1_bleed.update(
+            context,
+            engine_1.corrected_n2(),
+            overhead.engine_1_bleed_is_on() && !apu_bleed_air_valve_is_open,
+        );
+        self.engine_2_bleed.update(
+            context,
+            engine_2.corrected_n2(),
+            overhead.engine_2_bleed_is_on()
+                && !(apu_bleed_air_valve_is_open && self.crossbleed_valve.is_open()),
+        );
+
+        // Consumers open based on the duct pressure of the previous update.
+        // The packs close during engine start, leaving all air to the starters.
+        let engine_start_in_progress = engine_start.any_start_valve_is_open();
+        self.pack_1_flow_control_valve.update(
+            context,
+            overhead.pack_1_is_on() && !engine_start_in_progress,
+            &self.left_duct,
+        );
+        self.pack_2_flow_control_valve.update(
+            context,
+            overhead.pack_2_is_on() && !engine_start_in_progress,
+            &self.right_duct,
+        );
+        let wing_anti_ice_is_on = overhead.wing_anti_ice_is_on() && context.is_in_flight();
+        self.wing_anti_ice_1_valve
+            .update(context, wing_anti_ice_is_on, &self.left_duct);
+        self.wing_anti_ice_2_valve
+            .update(context, wing_anti_ice_is_on, &self.right_duct);
+
+        let left_demand = self.pack_1_flow_control_valve.demand()
+            + self.wing_anti_ice_1_valve.demand()
+            + engine_start.engine_1_starter_air_demand();
+        let right_demand = self.pack_2_flow_control_valve.demand()
+            + self.wing_anti_ice_2_valve.demand()
+            + engine_start.engine_2_starter_air_demand();
+
+        if self.crossbleed_valve.is_open() {
+            self.left_duct.update(
+                context,
+                &mut [&mut self.engine_1_bleed, &mut self.engine_2_bleed, apu],
+                left_demand + right_demand,
+            );
+            self.right_duct.equalise_with(&self.left_duct);
+        } else {
+            self.left_duct
+                .update(context, &mut [&mut self.engine_1_bleed, apu], left_demand);
+            self.right_duct
+                .update(context, &mut [&mut self.engine_2_bleed], right_demand);
+        }
+    }
+
+    pub fn left_duct_pressure(&self) -> Pressure {
+        self.left_duct.pressure()
+    }
+
+    pub fn right_duct_pressure(&self) -> Pressure {
+        self.right_duct.pressure()
+    }
+
+    pub fn engine_1_bleed_air_flow(&self) -> MassRate {
+        self.engine_1_bleed.supplied_flow()
+    }
+
+    pub fn engine_2_bleed_air_flow(&self) -> MassRate {
+        self.engine_2_bleed.supplied_flow()
+    }
+
+    pub fn pack_1_flow(&self) -> MassRate {
+        self.pack_1_flow_control_valve.demand()
+    }
+
+    pub fn pack_2_flow(&self) -> MassRate {
+        self.pack_2_flow_control_valve.demand()
+    }
+}
+impl SimulationElement for A320Pneumatic {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.engine_1_bleed.accept(visitor);
+        self.engine_2_bleed.accept(visitor);
+        self.left_duct.accept(visitor);
+        self.right_duct.accept(visitor);
+        self.crossbleed_valve.accept(visitor);
+        self.pack_1_flow_control_valve.accept(visitor);
+        self.pack_2_flow_control_valve.accept(visitor);
+        self.wing_anti_ice_1_valve.accept(visitor);
+        self.wing_anti_ice_2_valve.accept(visitor);
+
+        visitor.visit(self);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CrossBleedValveSelectorMode {
+    Shut,
+    Auto,
+    Open,
+}
 
 pub struct A320PneumaticOverheadPanel {
+    crossbleed_selector_id: QuantityIdentifier<f64>,
     apu_bleed: OnOffFaultPushButton,
+    engine_1_bleed: OnOffFaultPushButton,
+    engine_2_bleed: OnOffFaultPushButton,
+    crossbleed_selector: CrossBleedValveSelectorMode,
+    pack_1: OnOffFaultPushButton,
+    pack_2: OnOffFaultPushButton,
+    wing_anti_ice: OnOffFaultPushButton,
 }
 impl A320PneumaticOverheadPanel {
-    pub fn new() -> Self {
+    pub fn new(registry: &mut VariableRegistry) -> Self {
         A320PneumaticOverheadPanel {
-            apu_bleed: OnOffFaultPushButton::new_on("PNEU_APU_BLEED"),
+            crossbleed_selector_id: registry
+                .register_read_write("OVHD_PNEU_XBLEED_SELECTOR", VariableUnit::Number),
+            apu_bleed: OnOffFaultPushButton::new_on(registry, "PNEU_APU_BLEED"),
+            engine_1_bleed: OnOffFaultPushButton::new_on(registry, "PNEU_ENG_1_BLEED"),
+            engine_2_bleed: OnOffFaultPushButton::new_on(registry, "PNEU_ENG_2_BLEED"),
+            crossbleed_selector: CrossBleedValveSelectorMode::Auto,
+            pack_1: OnOffFaultPushButton::new_on(registry, "COND_PACK_1"),
+            pack_2: OnOffFaultPushButton::new_on(registry, "COND_PACK_2"),
+            wing_anti_ice: OnOffFaultPushButton::new_off(registry, "ANTI_ICE_WING"),
         }
     }
 
     pub fn apu_bleed_is_on(&self) -> bool {
         self.apu_bleed.is_on()
     }
+
+    pub fn engine_1_bleed_is_on(&self) -> bool {
+        self.engine_1_bleed.is_on()
+    }
+
+    pub fn engine_2_bleed_is_on(&self) -> bool {
+        self.engine_2_bleed.is_on()
+    }
+
+    pub fn crossbleed_selector(&self) -> CrossBleedValveSelectorMode {
+        self.crossbleed_selector
+    }
+
+    pub fn pack_1_is_on(&self) -> bool {
+        self.pack_1.is_on()
+    }
+
+    pub fn pack_2_is_on(&self) -> bool {
+        self.pack_2.is_on()
+    }
+
+    pub fn wing_anti_ice_is_on(&self) -> bool {
+        self.wing_anti_ice.is_on()
+    }
 }
 impl SimulationElement for A320PneumaticOverheadPanel {
     fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
         self.apu_bleed.accept(visitor);
+        self.engine_1_bleed.accept(visitor);
+        self.engine_2_bleed.accept(visitor);
+        self.pack_1.accept(visitor);
+        self.pack_2.accept(visitor);
+        self.wing_anti_ice.accept(visitor);
 
         visitor.visit(self);
     }
+
+    fn read(&mut self, reader: &mut SimulatorReader) {
+        self.crossbleed_selector = match reader.read_f64(&self.crossbleed_selector_id) as u8 {
+            0 => CrossBleedValveSelectorMode::Shut,
+            2 => CrossBleedValveSelectorMode::Open,
+            _ => CrossBleedValveSelectorMode::Auto,
+        };
+    }
+
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_f64(
+            &self.crossbleed_selector_id,
+            match self.crossbleed_selector {
+                CrossBleedValveSelectorMode::Shut => 0.,
+                CrossBleedValveSelectorMode::Auto => 1.,
+                CrossBleedValveSelectorMode::Open => 2.,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod a320_pneumatic_tests {
+    use super::*;
+    use crate::engine::A320EngineStartPanel;
+    use std::time::Duration;
+    use systems::{
+        electrical::{consumption::SuppliedPower, Potential, PotentialOrigin},
+        shared::LgciuWeightOnWheels,
+        simulation::{test::SimulationTestBed, Aircraft, Snapshot},
+    };
+    use uom::si::{
+        electric_potential::volt, pressure::psi, thermodynamic_temperature::degree_celsius,
+    };
+
+    struct TestApu {
+        bleed_air_valve_is_open: bool,
+        supplied_flow: MassRate,
+    }
+    impl TestApu {
+        fn new() -> Self {
+            Self {
+                bleed_air_valve_is_open: false,
+                supplied_flow: MassRate::new::<kilogram_per_second>(0.),
+            }
+        }
+    }
+    impl BleedAirValveState for TestApu {
+        fn bleed_air_valve_is_open(&self) -> bool {
+            self.bleed_air_valve_is_open
+        }
+    }
+    impl BleedAirSource for TestApu {
+        fn supply_pressure(&self) -> Pressure {
+            Pressure::new::<psi>(if self.bleed_air_valve_is_open {
+                40.
+            } else {
+                0.
+            })
+        }
+
+        fn supply_temperature(&self) -> ThermodynamicTemperature {
+            ThermodynamicTemperature::new::<degree_celsius>(200.)
+        }
+
+        fn max_flow(&self) -> MassRate {
+            MassRate::new::<kilogram_per_second>(if self.bleed_air_valve_is_open {
+                1.3
+            } else {
+                0.
+            })
+        }
+
+        fn set_supplied_flow(&mut self, flow: MassRate) {
+            self.supplied_flow = flow;
+        }
+    }
+
+    struct TestLgciu {
+        compressed: bool,
+    }
+    impl LgciuWeightOnWheels for TestLgciu {
+        fn left_and_right_gear_compressed(&self) -> bool {
+            self.compressed
+        }
+    }
+
+    struct A320PneumaticTestAircraft {
+        pneumatic: A320Pneumatic,
+        overhead: A320PneumaticOverheadPanel,
+        engine_1: Engine,
+        engine_2: Engine,
+        engine_start: A320EngineStart,
+        engine_start_panel: A320EngineStartPanel,
+        apu: TestApu,
+        lgciu: TestLgciu,
+    }
+    impl A320PneumaticTestAircraft {
+        fn new() -> Self {
+            let mut registry = VariableRegistry::new();
+
+            Self {
+                pneumatic: A320Pneumatic::new(&mut registry),
+                overhead: A320PneumaticOverheadPanel::new(&mut registry),
+                engine_1: Engine::new(&mut registry, 1),
+                engine_2: Engine::new(&mut registry, 2),
+                engine_start: A320EngineStart::new(&mut registry),
+                engine_start_panel: A320EngineStartPanel::new(&mut registry),
+                apu: TestApu::new(),
+                lgciu: TestLgciu { compressed: true },
+            }
+        }
+    }
+    impl Aircraft for A320PneumaticTestAircraft {
+        fn update_before_power_distribution(&mut self, context: &UpdateContext) {
+            self.engine_1.update(context);
+            self.engine_2.update(context);
+        }
+
+        fn update_after_power_distribution(&mut self, context: &UpdateContext) {
+            self.engine_start.update(
+                context,
+                &self.engine_1,
+                &self.engine_2,
+                &self.engine_start_panel,
+                self.pneumatic.left_duct_pressure(),
+                self.pneumatic.right_duct_pressure(),
+                &self.lgciu,
+            );
+            self.pneumatic.update(
+                context,
+                &self.engine_1,
+                &self.engine_2,
+                &self.engine_start,
+                &self.overhead,
+                &mut self.apu,
+            );
+        }
+
+        fn get_supplied_power(&mut self) -> SuppliedPower {
+            let mut supplied_power = SuppliedPower::new();
+            supplied_power.add(
+                ElectricalBusType::DirectCurrentEssential,
+                Potential::single(
+                    PotentialOrigin::Battery(10),
+                    ElectricPotential::new::<volt>(28.),
+                ),
+            );
+
+            supplied_power
+        }
+    }
+    impl SimulationElement for A320PneumaticTestAircraft {
+        fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            self.pneumatic.accept(visitor);
+            self.overhead.accept(visitor);
+            self.engine_1.accept(visitor);
+            self.engine_2.accept(visitor);
+            self.engine_start.accept(visitor);
+            self.engine_start_panel.accept(visitor);
+
+            visitor.visit(self);
+        }
+    }
+
+    struct A320PneumaticTestBed {
+        aircraft: A320PneumaticTestAircraft,
+        simulation_test_bed: SimulationTestBed,
+    }
+    impl A320PneumaticTestBed {
+        fn new() -> Self {
+            let mut aircraft = A320PneumaticTestAircraft::new();
+            Self {
+                simulation_test_bed: SimulationTestBed::seeded_with(&mut aircraft),
+                aircraft,
+            }
+        }
+
+        fn run(mut self) -> Self {
+            self.simulation_test_bed
+                .set_delta(Duration::from_millis(100));
+            for _ in 0..50 {
+                self.simulation_test_bed.run_aircraft(&mut self.aircraft);
+            }
+
+            self
+        }
+
+        fn apu_bleed_air_valve_open(mut self) -> Self {
+            self.aircraft.apu.bleed_air_valve_is_open = true;
+            self
+        }
+
+        fn engines_running(mut self) -> Self {
+            self.simulation_test_bed
+                .write_f64("TURB ENG CORRECTED N2:1", 80.);
+            self.simulation_test_bed
+                .write_f64("TURB ENG CORRECTED N2:2", 80.);
+            self
+        }
+
+        fn crossbleed_selector(mut self, mode: CrossBleedValveSelectorMode) -> Self {
+            self.simulation_test_bed.write_f64(
+                "OVHD_PNEU_XBLEED_SELECTOR",
+                match mode {
+                    CrossBleedValveSelectorMode::Shut => 0.,
+                    CrossBleedValveSelectorMode::Auto => 1.,
+                    CrossBleedValveSelectorMode::Open => 2.,
+                },
+            );
+            self
+        }
+
+        fn packs_off(mut self) -> Self {
+            self.simulation_test_bed
+                .write_bool("OVHD_COND_PACK_1_PB_IS_ON", false);
+            self.simulation_test_bed
+                .write_bool("OVHD_COND_PACK_2_PB_IS_ON", false);
+            self
+        }
+
+        fn engine_bleeds_off(mut self) -> Self {
+            self.simulation_test_bed
+                .write_bool("OVHD_PNEU_ENG_1_BLEED_PB_IS_ON", false);
+            self.simulation_test_bed
+                .write_bool("OVHD_PNEU_ENG_2_BLEED_PB_IS_ON", false);
+            self
+        }
+
+        fn starting_engine_1(mut self) -> Self {
+            self.simulation_test_bed.write_f64("ENG_MODE_SELECTOR", 2.);
+            self.simulation_test_bed
+                .write_bool("ENG_MASTER_1_IS_ON", true);
+            self
+        }
+
+        fn engine_1_start_valve_is_open(&mut self) -> bool {
+            self.simulation_test_bed.read_bool("ENG_1_START_VALVE_OPEN")
+        }
+
+        fn engine_1_starter_is_motoring(&mut self) -> bool {
+            self.simulation_test_bed
+                .read_bool("ENG_1_STARTER_IS_MOTORING")
+        }
+
+        fn left_duct_pressure(&self) -> Pressure {
+            self.aircraft.pneumatic.left_duct_pressure()
+        }
+
+        fn right_duct_pressure(&self) -> Pressure {
+            self.aircraft.pneumatic.right_duct_pressure()
+        }
+
+        fn apu_supplied_flow(&self) -> MassRate {
+            self.aircraft.apu.supplied_flow
+        }
+
+        fn engine_1_supplied_flow(&self) -> MassRate {
+            self.aircraft.pneumatic.engine_1_bleed_air_flow()
+        }
+
+        fn crossbleed_valve_is_open(&self) -> bool {
+            self.aircraft.pneumatic.crossbleed_valve.is_open()
+        }
+
+        fn pack_1_flow_control_valve_is_open(&self) -> bool {
+            self.aircraft.pneumatic.pack_1_flow_control_valve.is_open()
+        }
+    }
+
+    fn test_bed() -> A320PneumaticTestBed {
+        A320PneumaticTestBed::new()
+    }
+
+    #[test]
+    fn ducts_unpressurised_without_sources() {
+        let test_bed = test_bed().run();
+
+        assert_eq!(test_bed.left_duct_pressure(), Pressure::new::<psi>(0.));
+        assert_eq!(test_bed.right_duct_pressure(), Pressure::new::<psi>(0.));
+    }
+
+    #[test]
+    fn apu_bleed_pressurises_both_ducts_when_crossbleed_auto() {
+        let test_bed = test_bed().apu_bleed_air_valve_open().run();
+
+        assert!(test_bed.crossbleed_valve_is_open());
+        assert!(test_bed.left_duct_pressure() > Pressure::new::<psi>(30.));
+        assert!(test_bed.right_duct_pressure() > Pressure::new::<psi>(30.));
+    }
+
+    #[test]
+    fn crossbleed_shut_isolates_right_duct_from_apu() {
+        let test_bed = test_bed()
+            .apu_bleed_air_valve_open()
+            .crossbleed_selector(CrossBleedValveSelectorMode::Shut)
+            .run();
+
+        assert!(!test_bed.crossbleed_valve_is_open());
+        assert!(test_bed.left_duct_pressure() > Pressure::new::<psi>(30.));
+        assert_eq!(test_bed.right_duct_pressure(), Pressure::new::<psi>(0.));
+    }
+
+    #[test]
+    fn ducts_and_crossbleed_valve_are_restored_from_snapshot() {
+        let mut test_bed = test_bed().apu_bleed_air_valve_open().run();
+        let snapshot = Snapshot::take(&mut test_bed.aircraft);
+
+        let mut restored_aircraft = A320PneumaticTestAircraft::new();
+        snapshot.restore(&mut restored_aircraft);
+
+        assert!(restored_aircraft.pneumatic.crossbleed_valve.is_open());
+        assert_eq!(
+            restored_aircraft.pneumatic.left_duct_pressure(),
+            test_bed.left_duct_pressure()
+        );
+        assert_eq!(
+            restored_aircraft.pneumatic.right_duct_pressure(),
+            test_bed.right_duct_pressure()
+        );
+    }
+
+    #[test]
+    fn crossbleed_auto_closed_without_apu_bleed() {
+        let test_bed = test_bed().engines_running().run();
+
+        assert!(!test_bed.crossbleed_valve_is_open());
+    }
+
+    #[test]
+    fn engine_bleeds_pressurise_their_ducts() {
+        let test_bed = test_bed().engines_running().run();
+
+        assert!(test_bed.left_duct_pressure() > Pressure::new::<psi>(30.));
+        assert!(test_bed.right_duct_pressure() > Pressure::new::<psi>(30.));
+    }
+
+    #[test]
+    fn engine_bleeds_off_leave_ducts_unpressurised() {
+        let test_bed = test_bed().engines_running().engine_bleeds_off().run();
+
+        assert_eq!(test_bed.left_duct_pressure(), Pressure::new::<psi>(0.));
+        assert_eq!(test_bed.right_duct_pressure(), Pressure::new::<psi>(0.));
+    }
+
+    #[test]
+    fn apu_bleed_takes_priority_over_engine_bleed() {
+        let test_bed = test_bed()
+            .engines_running()
+            .apu_bleed_air_valve_open()
+            .run();
+
+        assert!(test_bed.apu_supplied_flow() > MassRate::new::<kilogram_per_second>(0.));
+        assert_eq!(
+            test_bed.engine_1_supplied_flow(),
+            MassRate::new::<kilogram_per_second>(0.)
+        );
+    }
+
+    #[test]
+    fn packs_take_air_from_the_apu() {
+        let test_bed = test_bed().apu_bleed_air_valve_open().run();
+
+        assert!(test_bed.pack_1_flow_control_valve_is_open());
+        assert_eq!(
+            test_bed.apu_supplied_flow(),
+            MassRate::new::<kilogram_per_second>(
+                2. * A320Pneumatic::PACK_NOMINAL_FLOW_KILOGRAM_PER_SECOND
+            )
+        );
+    }
+
+    #[test]
+    fn apu_supplies_no_flow_when_packs_off() {
+        let test_bed = test_bed().apu_bleed_air_valve_open().packs_off().run();
+
+        assert_eq!(
+            test_bed.apu_supplied_flow(),
+            MassRate::new::<kilogram_per_second>(0.)
+        );
+    }
+
+    #[test]
+    fn pack_flow_control_valve_closed_without_duct_pressure() {
+        let test_bed = test_bed().run();
+
+        assert!(!test_bed.pack_1_flow_control_valve_is_open());
+    }
+
+    #[test]
+    fn engine_bleed_supplies_flow_to_pack() {
+        let test_bed = test_bed().engines_running().run();
+
+        assert!(test_bed.engine_1_supplied_flow() > MassRate::new::<kilogram_per_second>(0.));
+    }
+
+    #[test]
+    fn apu_bleed_air_starts_engine() {
+        let mut test_bed = test_bed()
+            .apu_bleed_air_valve_open()
+            .starting_engine_1()
+            .run();
+
+        assert!(test_bed.engine_1_start_valve_is_open());
+        assert!(test_bed.engine_1_starter_is_motoring());
+    }
+
+    #[test]
+    fn engine_does_not_start_without_bleed_air() {
+        let mut test_bed = test_bed().starting_engine_1().run();
+
+        assert!(!test_bed.engine_1_start_valve_is_open());
+        assert!(!test_bed.engine_1_starter_is_motoring());
+    }
+
+    #[test]
+    fn packs_close_during_engine_start() {
+        let test_bed = test_bed()
+            .apu_bleed_air_valve_open()
+            .starting_engine_1()
+            .run();
+
+        assert!(!test_bed.pack_1_flow_control_valve_is_open());
+        assert_eq!(
+            test_bed.apu_supplied_flow(),
+            test_bed.aircraft.engine_start.engine_1_starter_air_demand()
+        );
+    }
 }
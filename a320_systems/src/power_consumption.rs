@@ -3,13 +3,18 @@ use systems::{
         consumption::{FlightPhasePowerConsumer, PowerConsumerFlightPhase},
         ElectricalBusType,
     },
-    simulation::{SimulationElement, SimulationElementVisitor, UpdateContext},
+    shared::FwcFlightPhaseProvider,
+    simulation::{SimulationElement, SimulationElementVisitor, Snapshot, UpdateContext},
 };
 use uom::si::{f64::*, power::watt};
 
+const FLIGHT_PHASES: usize = PowerConsumerFlightPhase::TaxiIn as usize + 1;
+
 /// This type provides an aggregated form of power consumption.
-/// We haven't yet implemented all power consumers and thus need something to
-/// consume power, as otherwise electrical load is nearly 0.
+/// It stands in for the consumers we haven't implemented yet, such as the lighting,
+/// avionics, galleys and heating. The consumers which are implemented individually
+/// (hydraulic and fuel pumps, igniters, start valves, pack controllers, LGCIUs and
+/// CPCs) are taken out of the figures, as their demand would otherwise be counted twice.
 pub(super) struct A320PowerConsumption {
     ac_bus_1_consumer: FlightPhasePowerConsumer,
     ac_bus_2_consumer: FlightPhasePowerConsumer,
@@ -25,6 +30,20 @@ pub(super) struct A320PowerConsumption {
     dc_hot_bus_2_consumer: FlightPhasePowerConsumer,
 }
 impl A320PowerConsumption {
+    // The typical demand of the individually implemented consumers per flight phase,
+    // in the order of the phases in the tables below.
+    // The blue electric pump runs in AUTO once an engine runs. The yellow electric
+    // pump is normally off and thus isn't part of the figures.
+    const BLUE_ELECTRIC_PUMP_WATT: [f64; FLIGHT_PHASES] = [0., 1450., 1450., 1450., 1450., 1450.];
+    // Three tank pumps of 800 W each are powered by each AC bus.
+    const TANK_FUEL_PUMPS_WATT: [f64; FLIGHT_PHASES] = [2400.; FLIGHT_PHASES];
+    // Igniters and start valves only draw power while the engines are started.
+    const IGNITER_WATT: [f64; FLIGHT_PHASES] = [50., 0., 0., 0., 0., 0.];
+    const START_VALVES_WATT: [f64; FLIGHT_PHASES] = [40., 0., 0., 0., 0., 0.];
+    const PACK_CONTROLLER_WATT: [f64; FLIGHT_PHASES] = [100.; FLIGHT_PHASES];
+    const LGCIU_WATT: [f64; FLIGHT_PHASES] = [25.; FLIGHT_PHASES];
+    const CPC_WATT: [f64; FLIGHT_PHASES] = [30.; FLIGHT_PHASES];
+
     pub fn new() -> Self {
         // The watts in this function are all provided by komp.
         // They include a 0.8 power factor correction.
@@ -32,78 +51,91 @@ impl A320PowerConsumption {
             ac_bus_1_consumer: FlightPhasePowerConsumer::from(
                 ElectricalBusType::AlternatingCurrent(1),
             )
-            .demand([
-                (
-                    PowerConsumerFlightPhase::BeforeStart,
-                    Power::new::<watt>(26816.3),
-                ),
-                (
-                    PowerConsumerFlightPhase::AfterStart,
-                    Power::new::<watt>(30350.1),
-                ),
-                (
-                    PowerConsumerFlightPhase::Takeoff,
-                    Power::new::<watt>(33797.3),
-                ),
-                (
-                    PowerConsumerFlightPhase::Flight,
-                    Power::new::<watt>(39032.5),
-                ),
-                (
-                    PowerConsumerFlightPhase::Landing,
-                    Power::new::<watt>(30733.3),
-                ),
-                (
-                    PowerConsumerFlightPhase::TaxiIn,
-                    Power::new::<watt>(30243.1),
-                ),
-            ]),
+            .demand(excluding(
+                [
+                    (
+                        PowerConsumerFlightPhase::BeforeStart,
+                        Power::new::<watt>(26816.3),
+                    ),
+                    (
+                        PowerConsumerFlightPhase::AfterStart,
+                        Power::new::<watt>(30350.1),
+                    ),
+                    (
+                        PowerConsumerFlightPhase::Takeoff,
+                        Power::new::<watt>(33797.3),
+                    ),
+                    (
+                        PowerConsumerFlightPhase::Flight,
+                        Power::new::<watt>(39032.5),
+                    ),
+                    (
+                        PowerConsumerFlightPhase::Landing,
+                        Power::new::<watt>(30733.3),
+                    ),
+                    (
+                        PowerConsumerFlightPhase::TaxiIn,
+                        Power::new::<watt>(30243.1),
+                    ),
+                ],
+                &[
+                    A320PowerConsumption::BLUE_ELECTRIC_PUMP_WATT,
+                    A320PowerConsumption::TANK_FUEL_PUMPS_WATT,
+                    A320PowerConsumption::IGNITER_WATT,
+                ],
+            )),
             ac_bus_2_consumer: FlightPhasePowerConsumer::from(
                 ElectricalBusType::AlternatingCurrent(2),
             )
-            .demand([
-                (
-                    PowerConsumerFlightPhase::BeforeStart,
-                    Power::new::<watt>(31678.2),
-                ),
-                (
-                    PowerConsumerFlightPhase::AfterStart,
-                    Power::new::<watt>(25398.8),
-                ),
-                (
-                    PowerConsumerFlightPhase::Takeoff,
-                    Power::new::<watt>(27811.),
-                ),
-                (
-                    PowerConsumerFlightPhase::Flight,
-                    Power::new::<watt>(32161.9),
-                ),
-                (
-                    PowerConsumerFlightPhase::Landing,
-                    Power::new::<watt>(25782.),
-                ),
-                (
-                    PowerConsumerFlightPhase::TaxiIn,
-                    Power::new::<watt>(28138.8),
-                ),
-            ]),
+            .demand(excluding(
+                [
+                    (
+                        PowerConsumerFlightPhase::BeforeStart,
+                        Power::new::<watt>(31678.2),
+                    ),
+                    (
+                        PowerConsumerFlightPhase::AfterStart,
+                        Power::new::<watt>(25398.8),
+                    ),
+                    (
+                        PowerConsumerFlightPhase::Takeoff,
+                        Power::new::<watt>(27811.),
+                    ),
+                    (
+                        PowerConsumerFlightPhase::Flight,
+                        Power::new::<watt>(32161.9),
+                    ),
+                    (
+                        PowerConsumerFlightPhase::Landing,
+                        Power::new::<watt>(25782.),
+                    ),
+                    (
+                        PowerConsumerFlightPhase::TaxiIn,
+                        Power::new::<watt>(28138.8),
+                    ),
+                ],
+                &[A320PowerConsumption::TANK_FUEL_PUMPS_WATT],
+            )),
             ac_ess_bus_consumer: FlightPhasePowerConsumer::from(
                 ElectricalBusType::AlternatingCurrentEssential,
             )
-            .demand([
-                (
-                    PowerConsumerFlightPhase::BeforeStart,
-                    Power::new::<watt>(455.7),
-                ),
-                (
-                    PowerConsumerFlightPhase::AfterStart,
-                    Power::new::<watt>(715.7),
-                ),
-                (PowerConsumerFlightPhase::Takeoff, Power::new::<watt>(875.7)),
-                (PowerConsumerFlightPhase::Flight, Power::new::<watt>(875.7)),
-                (PowerConsumerFlightPhase::Landing, Power::new::<watt>(715.7)),
-                (PowerConsumerFlightPhase::TaxiIn, Power::new::<watt>(715.7)),
-            ]),
+            .demand(excluding(
+                [
+                    (
+                        PowerConsumerFlightPhase::BeforeStart,
+                        Power::new::<watt>(455.7),
+                    ),
+                    (
+                        PowerConsumerFlightPhase::AfterStart,
+                        Power::new::<watt>(715.7),
+                    ),
+                    (PowerConsumerFlightPhase::Takeoff, Power::new::<watt>(875.7)),
+                    (PowerConsumerFlightPhase::Flight, Power::new::<watt>(875.7)),
+                    (PowerConsumerFlightPhase::Landing, Power::new::<watt>(715.7)),
+                    (PowerConsumerFlightPhase::TaxiIn, Power::new::<watt>(715.7)),
+                ],
+                &[A320PowerConsumption::IGNITER_WATT],
+            )),
             ac_ess_shed_bus_consumer: FlightPhasePowerConsumer::from(
                 ElectricalBusType::AlternatingCurrentEssentialShed,
             )
@@ -139,52 +171,69 @@ impl A320PowerConsumption {
                 (PowerConsumerFlightPhase::TaxiIn, Power::new::<watt>(135.)),
             ]),
             dc_bus_1_consumer: FlightPhasePowerConsumer::from(ElectricalBusType::DirectCurrent(1))
-                .demand([
-                    (
-                        PowerConsumerFlightPhase::BeforeStart,
-                        Power::new::<watt>(252.),
-                    ),
-                    (
-                        PowerConsumerFlightPhase::AfterStart,
-                        Power::new::<watt>(308.),
-                    ),
-                    (PowerConsumerFlightPhase::Takeoff, Power::new::<watt>(364.)),
-                    (PowerConsumerFlightPhase::Flight, Power::new::<watt>(280.)),
-                    (PowerConsumerFlightPhase::Landing, Power::new::<watt>(364.)),
-                    (PowerConsumerFlightPhase::TaxiIn, Power::new::<watt>(336.)),
-                ]),
+                .demand(excluding(
+                    [
+                        (
+                            PowerConsumerFlightPhase::BeforeStart,
+                            Power::new::<watt>(252.),
+                        ),
+                        (
+                            PowerConsumerFlightPhase::AfterStart,
+                            Power::new::<watt>(308.),
+                        ),
+                        (PowerConsumerFlightPhase::Takeoff, Power::new::<watt>(364.)),
+                        (PowerConsumerFlightPhase::Flight, Power::new::<watt>(280.)),
+                        (PowerConsumerFlightPhase::Landing, Power::new::<watt>(364.)),
+                        (PowerConsumerFlightPhase::TaxiIn, Power::new::<watt>(336.)),
+                    ],
+                    &[A320PowerConsumption::PACK_CONTROLLER_WATT],
+                )),
             dc_bus_2_consumer: FlightPhasePowerConsumer::from(ElectricalBusType::DirectCurrent(2))
-                .demand([
+                .demand(excluding(
+                    [
+                        (
+                            PowerConsumerFlightPhase::BeforeStart,
+                            Power::new::<watt>(532.),
+                        ),
+                        (
+                            PowerConsumerFlightPhase::AfterStart,
+                            Power::new::<watt>(448.),
+                        ),
+                        (PowerConsumerFlightPhase::Takeoff, Power::new::<watt>(392.)),
+                        (PowerConsumerFlightPhase::Flight, Power::new::<watt>(392.)),
+                        (PowerConsumerFlightPhase::Landing, Power::new::<watt>(392.)),
+                        (PowerConsumerFlightPhase::TaxiIn, Power::new::<watt>(448.)),
+                    ],
+                    &[
+                        A320PowerConsumption::PACK_CONTROLLER_WATT,
+                        A320PowerConsumption::LGCIU_WATT,
+                        A320PowerConsumption::CPC_WATT,
+                    ],
+                )),
+            dc_ess_bus_consumer: FlightPhasePowerConsumer::from(
+                ElectricalBusType::DirectCurrentEssential,
+            )
+            .demand(excluding(
+                [
                     (
                         PowerConsumerFlightPhase::BeforeStart,
-                        Power::new::<watt>(532.),
+                        Power::new::<watt>(168.),
                     ),
                     (
                         PowerConsumerFlightPhase::AfterStart,
-                        Power::new::<watt>(448.),
+                        Power::new::<watt>(140.),
                     ),
-                    (PowerConsumerFlightPhase::Takeoff, Power::new::<watt>(392.)),
-                    (PowerConsumerFlightPhase::Flight, Power::new::<watt>(392.)),
-                    (PowerConsumerFlightPhase::Landing, Power::new::<watt>(392.)),
-                    (PowerConsumerFlightPhase::TaxiIn, Power::new::<watt>(448.)),
-                ]),
-            dc_ess_bus_consumer: FlightPhasePowerConsumer::from(
-                ElectricalBusType::DirectCurrentEssential,
-            )
-            .demand([
-                (
-                    PowerConsumerFlightPhase::BeforeStart,
-                    Power::new::<watt>(168.),
-                ),
-                (
-                    PowerConsumerFlightPhase::AfterStart,
-                    Power::new::<watt>(140.),
-                ),
-                (PowerConsumerFlightPhase::Takeoff, Power::new::<watt>(168.)),
-                (PowerConsumerFlightPhase::Flight, Power::new::<watt>(140.)),
-                (PowerConsumerFlightPhase::Landing, Power::new::<watt>(168.)),
-                (PowerConsumerFlightPhase::TaxiIn, Power::new::<watt>(140.)),
-            ]),
+                    (PowerConsumerFlightPhase::Takeoff, Power::new::<watt>(168.)),
+                    (PowerConsumerFlightPhase::Flight, Power::new::<watt>(140.)),
+                    (PowerConsumerFlightPhase::Landing, Power::new::<watt>(168.)),
+                    (PowerConsumerFlightPhase::TaxiIn, Power::new::<watt>(140.)),
+                ],
+                &[
+                    A320PowerConsumption::START_VALVES_WATT,
+                    A320PowerConsumption::LGCIU_WATT,
+                    A320PowerConsumption::CPC_WATT,
+                ],
+            )),
             dc_ess_shed_bus_consumer: FlightPhasePowerConsumer::from(
                 ElectricalBusType::DirectCurrentEssentialShed,
             )
@@ -256,19 +305,19 @@ impl A320PowerConsumption {
         }
     }
 
-    pub fn update(&mut self, context: &UpdateContext) {
-        self.ac_bus_1_consumer.update(context);
-        self.ac_bus_2_consumer.update(context);
-        self.ac_ess_bus_consumer.update(context);
-        self.ac_ess_shed_bus_consumer.update(context);
-        self.ac_stat_inv_bus_consumer.update(context);
-        self.dc_bus_1_consumer.update(context);
-        self.dc_bus_2_consumer.update(context);
-        self.dc_ess_bus_consumer.update(context);
-        self.dc_ess_shed_bus_consumer.update(context);
-        self.dc_bat_bus_consumer.update(context);
-        self.dc_hot_bus_1_consumer.update(context);
-        self.dc_hot_bus_2_consumer.update(context);
+    pub fn update(&mut self, context: &UpdateContext, fwc: &impl FwcFlightPhaseProvider) {
+        self.ac_bus_1_consumer.update(context, fwc);
+        self.ac_bus_2_consumer.update(context, fwc);
+        self.ac_ess_bus_consumer.update(context, fwc);
+        self.ac_ess_shed_bus_consumer.update(context, fwc);
+        self.ac_stat_inv_bus_consumer.update(context, fwc);
+        self.dc_bus_1_consumer.update(context, fwc);
+        self.dc_bus_2_consumer.update(context, fwc);
+        self.dc_ess_bus_consumer.update(context, fwc);
+        self.dc_ess_shed_bus_consumer.update(context, fwc);
+        self.dc_bat_bus_consumer.update(context, fwc);
+        self.dc_hot_bus_1_consumer.update(context, fwc);
+        self.dc_hot_bus_2_consumer.update(context, fwc);
     }
 }
 impl SimulationElement for A320PowerConsumption {
@@ -288,9 +337,75 @@ impl SimulationElement for A320PowerConsumption {
 
         visitor.visit(self);
     }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        self.ac_bus_1_consumer
+            .save(snapshot, "POWER_CONSUMPTION_AC_BUS_1");
+        self.ac_bus_2_consumer
+            .save(snapshot, "POWER_CONSUMPTION_AC_BUS_2");
+        self.ac_ess_bus_consumer
+            .save(snapshot, "POWER_CONSUMPTION_AC_ESS_BUS");
+        self.ac_ess_shed_bus_consumer
+            .save(snapshot, "POWER_CONSUMPTION_AC_ESS_SHED_BUS");
+        self.ac_stat_inv_bus_consumer
+            .save(snapshot, "POWER_CONSUMPTION_AC_STAT_INV_BUS");
+        self.dc_bus_1_consumer
+            .save(snapshot, "POWER_CONSUMPTION_DC_BUS_1");
+        self.dc_bus_2_consumer
+            .save(snapshot, "POWER_CONSUMPTION_DC_BUS_2");
+        self.dc_ess_bus_consumer
+            .save(snapshot, "POWER_CONSUMPTION_DC_ESS_BUS");
+        self.dc_ess_shed_bus_consumer
+            .save(snapshot, "POWER_CONSUMPTION_DC_ESS_SHED_BUS");
+        self.dc_bat_bus_consumer
+            .save(snapshot, "POWER_CONSUMPTION_DC_BAT_BUS");
+        self.dc_hot_bus_1_consumer
+            .save(snapshot, "POWER_CONSUMPTION_DC_HOT_BUS_1");
+        self.dc_hot_bus_2_consumer
+            .save(snapshot, "POWER_CONSUMPTION_DC_HOT_BUS_2");
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        self.ac_bus_1_consumer
+            .restore(snapshot, "POWER_CONSUMPTION_AC_BUS_1");
+        self.ac_bus_2_consumer
+            .restore(snapshot, "POWER_CONSUMPTION_AC_BUS_2");
+        self.ac_ess_bus_consumer
+            .restore(snapshot, "POWER_CONSUMPTION_AC_ESS_BUS");
+        self.ac_ess_shed_bus_consumer
+            .restore(snapshot, "POWER_CONSUMPTION_AC_ESS_SHED_BUS");
+        self.ac_stat_inv_bus_consumer
+            .restore(snapshot, "POWER_CONSUMPTION_AC_STAT_INV_BUS");
+        self.dc_bus_1_consumer
+            .restore(snapshot, "POWER_CONSUMPTION_DC_BUS_1");
+        self.dc_bus_2_consumer
+            .restore(snapshot, "POWER_CONSUMPTION_DC_BUS_2");
+        self.dc_ess_bus_consumer
+            .restore(snapshot, "POWER_CONSUMPTION_DC_ESS_BUS");
+        self.dc_ess_shed_bus_consumer
+            .restore(snapshot, "POWER_CONSUMPTION_DC_ESS_SHED_BUS");
+        self.dc_bat_bus_consumer
+            .restore(snapshot, "POWER_CONSUMPTION_DC_BAT_BUS");
+        self.dc_hot_bus_1_consumer
+            .restore(snapshot, "POWER_CONSUMPTION_DC_HOT_BUS_1");
+        self.dc_hot_bus_2_consumer
+            .restore(snapshot, "POWER_CONSUMPTION_DC_HOT_BUS_2");
+    }
 }
 impl Default for A320PowerConsumption {
     fn default() -> Self {
         A320PowerConsumption::new()
     }
 }
+
+/// Takes the demand of the individually implemented consumers out of komp's figures.
+fn excluding(
+    mut demand: [(PowerConsumerFlightPhase, Power); FLIGHT_PHASES],
+    implemented: &[[f64; FLIGHT_PHASES]],
+) -> [(PowerConsumerFlightPhase, Power); FLIGHT_PHASES] {
+    for (index, (_, power)) in demand.iter_mut().enumerate() {
+        *power -= Power::new::<watt>(implemented.iter().map(|watts| watts[index]).sum());
+    }
+
+    demand
+}
@@ -0,0 +1,271 @@
+use systems::{
+    electrical::ElectricalBusType,
+    pressurization::{CabinPressureController, OutflowValve, PressurizedCabin},
+    shared::LgciuWeightOnWheels,
+    simulation::{
+        QuantityIdentifier, SimulationElement, SimulationElementVisitor, SimulatorWriter,
+        UpdateContext, VariableRegistry, VariableUnit,
+    },
+};
+use uom::si::f64::*;
+
+/// CPC 1 controls the outflow valve as long as it is powered, otherwise CPC 2 takes over.
+pub struct A320Pressurization {
+    active_system_id: QuantityIdentifier<f64>,
+    cpc_1: CabinPressureController,
+    cpc_2: CabinPressureController,
+    outflow_valve: OutflowValve,
+    cabin: PressurizedCabin,
+}
+impl A320Pressurization {
+    pub fn new(registry: &mut VariableRegistry) -> A320Pressurization {
+        A320Pressurization {
+            active_system_id: registry.register_write("PRESS_ACTIVE_SYSTEM", VariableUnit::Number),
+            cpc_1: CabinPressureController::new(ElectricalBusType::DirectCurrentEssential),
+            cpc_2: CabinPressureController::new(ElectricalBusType::DirectCurrent(2)),
+            outflow_valve: OutflowValve::new(registry),
+            cabin: PressurizedCabin::new(registry),
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        context: &UpdateContext,
+        pack_flow: MassRate,
+        lgciu: &impl LgciuWeightOnWheels,
+    ) {
+        let is_on_ground = lgciu.left_and_right_gear_compressed();
+        self.cpc_1
+            .update(context, &self.cabin, pack_flow, is_on_ground);
+        self.cpc_2
+            .update(context, &self.cabin, pack_flow, is_on_ground);
+
+        self.outflow_valve.update(
+            context,
+            self.cpc_1
+                .outflow_valve_command()
+                .or_else(|| self.cpc_2.outflow_valve_command()),
+        );
+        self.cabin.update(context, pack_flow, &self.outflow_valve);
+    }
+
+    pub fn cabin_altitude(&self) -> Length {
+        self.cabin.altitude()
+    }
+
+    /// The number of the cabin pressure controller in control, or 0 when none is.
+    fn active_system(&self) -> usize {
+        if self.cpc_1.is_powered() {
+            1
+        } else if self.cpc_2.is_powered() {
+            2
+        } else {
+            0
+        }
+    }
+}
+impl SimulationElement for A320Pressurization {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.cpc_1.accept(visitor);
+        self.cpc_2.accept(visitor);
+        self.outflow_valve.accept(visitor);
+        self.cabin.accept(visitor);
+
+        visitor.visit(self);
+    }
+
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_f64(&self.active_system_id, self.active_system() as f64);
+    }
+}
+
+#[cfg(test)]
+mod a320_pressurization_tests {
+    use super::*;
+    use std::time::Duration;
+    use systems::{
+        electrical::{consumption::SuppliedPower, Potential, PotentialOrigin},
+        simulation::{test::SimulationTestBed, Aircraft},
+    };
+    use uom::si::{
+        electric_potential::volt,
+        length::{foot, meter},
+        mass_rate::kilogram_per_second,
+        pressure::pascal,
+        ratio::percent,
+    };
+
+    struct TestLgciu {
+        compressed: bool,
+    }
+    impl TestLgciu {
+        fn new(compressed: bool) -> Self {
+            Self { compressed }
+        }
+    }
+    impl LgciuWeightOnWheels for TestLgciu {
+        fn left_and_right_gear_compressed(&self) -> bool {
+            self.compressed
+        }
+    }
+
+    struct A320PressurizationTestAircraft {
+        pressurization: A320Pressurization,
+        pack_flow: MassRate,
+        is_dc_ess_powered: bool,
+        is_dc_2_powered: bool,
+    }
+    impl A320PressurizationTestAircraft {
+        fn new() -> Self {
+            Self {
+                pressurization: A320Pressurization::new(&mut VariableRegistry::new()),
+                pack_flow: MassRate::new::<kilogram_per_second>(0.8),
+                is_dc_ess_powered: true,
+                is_dc_2_powered: true,
+            }
+        }
+    }
+    impl Aircraft for A320PressurizationTestAircraft {
+        fn update_after_power_distribution(&mut self, context: &UpdateContext) {
+            self.pressurization.update(
+                context,
+                self.pack_flow,
+                &TestLgciu::new(context.is_on_ground()),
+            );
+        }
+
+        fn get_supplied_power(&mut self) -> SuppliedPower {
+            let mut supplied_power = SuppliedPower::new();
+            let dc = Potential::single(
+                PotentialOrigin::Battery(10),
+                ElectricPotential::new::<volt>(28.),
+            );
+            if self.is_dc_ess_powered {
+                supplied_power.add(ElectricalBusType::DirectCurrentEssential, dc);
+            }
+            if self.is_dc_2_powered {
+                supplied_power.add(ElectricalBusType::DirectCurrent(2), dc);
+            }
+
+            supplied_power
+        }
+    }
+    impl SimulationElement for A320PressurizationTestAircraft {
+        fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            self.pressurization.accept(visitor);
+
+            visitor.visit(self);
+        }
+    }
+
+    struct A320PressurizationTestBed {
+        aircraft: A320PressurizationTestAircraft,
+        simulation_test_bed: SimulationTestBed,
+    }
+    impl A320PressurizationTestBed {
+        fn new() -> Self {
+            Self {
+                aircraft: A320PressurizationTestAircraft::new(),
+                simulation_test_bed: SimulationTestBed::new_with_delta(Duration::from_secs(1)),
+            }
+        }
+
+        fn run_for(mut self, seconds: u64) -> Self {
+            for _ in 0..seconds {
+                self.simulation_test_bed.run_aircraft(&mut self.aircraft);
+            }
+
+            self
+        }
+
+        fn on_ground(mut self) -> Self {
+            self.simulation_test_bed.set_on_ground(true);
+            self
+        }
+
+        fn altitude(mut self, feet: f64) -> Self {
+            let altitude = Length::new::<foot>(feet);
+            self.simulation_test_bed.set_indicated_altitude(altitude);
+            // The pressure at the altitude in the International Standard Atmosphere.
+            self.simulation_test_bed
+                .set_ambient_pressure(Pressure::new::<pascal>(
+                    101325. * (1. - 2.25577e-5 * altitude.get::<meter>()).powf(5.25588),
+                ));
+            self
+        }
+
+        fn dc_ess_unpowered(mut self) -> Self {
+            self.aircraft.is_dc_ess_powered = false;
+            self
+        }
+
+        fn dc_unpowered(mut self) -> Self {
+            self.aircraft.is_dc_ess_powered = false;
+            self.aircraft.is_dc_2_powered = false;
+            self
+        }
+
+        fn active_system(&mut self) -> f64 {
+            self.simulation_test_bed.read_f64("PRESS_ACTIVE_SYSTEM")
+        }
+
+        fn cabin_altitude(&mut self) -> f64 {
+            self.simulation_test_bed.read_f64("PRESS_CABIN_ALTITUDE")
+        }
+
+        fn outflow_valve_open_amount(&self) -> Ratio {
+            self.aircraft.pressurization.outflow_valve.open_amount()
+        }
+    }
+
+    fn test_bed() -> A320PressurizationTestBed {
+        A320PressurizationTestBed::new()
+    }
+
+    #[test]
+    fn outflow_valve_fully_open_on_ground() {
+        let test_bed = test_bed().on_ground().altitude(0.).run_for(60);
+
+        assert_eq!(
+            test_bed.outflow_valve_open_amount(),
+            Ratio::new::<percent>(100.)
+        );
+    }
+
+    #[test]
+    fn cabin_is_pressurized_in_cruise() {
+        let mut test_bed = test_bed().altitude(35000.).run_for(1800);
+
+        assert!(test_bed.cabin_altitude() < 8100.);
+        assert!(test_bed.outflow_valve_open_amount() < Ratio::new::<percent>(100.));
+    }
+
+    #[test]
+    fn cpc_1_is_active_when_powered() {
+        let mut test_bed = test_bed().run_for(1);
+
+        assert_eq!(test_bed.active_system(), 1.);
+    }
+
+    #[test]
+    fn cpc_2_takes_over_when_cpc_1_unpowered() {
+        let mut test_bed = test_bed().dc_ess_unpowered().on_ground().run_for(20);
+
+        assert_eq!(test_bed.active_system(), 2.);
+        assert_eq!(
+            test_bed.outflow_valve_open_amount(),
+            Ratio::new::<percent>(100.)
+        );
+    }
+
+    #[test]
+    fn outflow_valve_remains_in_position_when_both_cpcs_unpowered() {
+        let test_bed = test_bed().altitude(35000.).run_for(1800);
+        let open_amount = test_bed.outflow_valve_open_amount();
+
+        let mut test_bed = test_bed.dc_unpowered().on_ground().run_for(20);
+
+        assert_eq!(test_bed.active_system(), 0.);
+        assert_eq!(test_bed.outflow_valve_open_amount(), open_amount);
+    }
+}
@@ -0,0 +1,111 @@
+//! Runs the A320 systems outside of the simulator, using a scripted scenario
+//! as input and writing the requested simulator variables as CSV.
+//!
+//! Usage: `a320_simulate <scenario file> [--output <csv file>]`
+//!
+//! Refer to `systems::simulation::Scenario` for the format of the scenario file.
+//! When any of the scenario's expectations isn't met, the failures are printed
+//! and the process exits with a non-zero exit code.
+//!
+//! Usage: `a320_simulate --variables [--output <json file>]`
+//!
+//! Writes a JSON manifest of every simulator variable the A320 reads and writes,
+//! including each variable's unit, direction and the systems owning it.
+//!
+//! Usage: `a320_simulate --failures [--output <csv file>]`
+//!
+//! Writes the failures which can be activated within the A320 as CSV, including the
+//! simulator variable which activates each failure. Scenarios activate a failure by
+//! setting its variable, e.g. `at 10 1 FAILURE_24001_IS_ACTIVE`.
+use a320_systems::A320;
+use std::{
+    env, fs,
+    io::{self, BufWriter, Write},
+    process,
+};
+use systems::simulation::{
+    InMemoryReaderWriter, Scenario, Simulation, VariableManifest, VariableRegistry,
+};
+
+mod runner;
+
+const USAGE: &str = "usage: a320_simulate <scenario file> [--output <csv file>]\n       \
+                     a320_simulate --variables [--output <json file>]\n       \
+                     a320_simulate --failures [--output <csv file>]";
+
+fn main() {
+    if let Err(error) = try_main(env::args().skip(1).collect()) {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
+}
+
+fn try_main(args: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let (input, output_path) = match args.as_slice() {
+        [input] => (input, None),
+        [input, flag, output] if flag == "--output" => (input, Some(output)),
+        _ => return Err(USAGE.into()),
+    };
+
+    let mut output: BufWriter<Box<dyn Write>> = BufWriter::new(match output_path {
+        Some(path) => Box::new(
+            fs::File::create(path).map_err(|error| format!("cannot create {}: {}", path, error))?,
+        ),
+        None => Box::new(io::stdout()),
+    });
+
+    match input.as_str() {
+        "--variables" => write_variables(&mut output),
+        "--failures" => write_failures(&mut output),
+        _ => simulate(input, &mut output),
+    }
+}
+
+fn write_variables<W: Write>(json: &mut W) -> Result<(), Box<dyn std::error::Error>> {
+    let mut registry = VariableRegistry::new();
+    A320::new(&mut registry);
+
+    json.write_all(VariableManifest::from(&registry).to_json().as_bytes())?;
+    json.flush()?;
+
+    Ok(())
+}
+
+fn write_failures<W: Write>(csv: &mut W) -> Result<(), Box<dyn std::error::Error>> {
+    let mut a320 = A320::new(&mut VariableRegistry::new());
+    let mut reader_writer = InMemoryReaderWriter::new();
+    let failures = Simulation::new(&mut a320, &mut reader_writer).failures();
+
+    writeln!(csv, "id,variable,failure")?;
+    for failure_type in failures.failure_types() {
+        writeln!(
+            csv,
+            "{},{},{:?}",
+            failure_type.id(),
+            failure_type.is_active_id(),
+            failure_type
+        )?;
+    }
+    csv.flush()?;
+
+    Ok(())
+}
+
+fn simulate<W: Write>(scenario_path: &str, csv: &mut W) -> Result<(), Box<dyn std::error::Error>> {
+    let scenario: Scenario = fs::read_to_string(scenario_path)
+        .map_err(|error| format!("cannot read {}: {}", scenario_path, error))?
+        .parse()?;
+
+    let failures = runner::run(&scenario, &mut A320::new(&mut VariableRegistry::new()), csv)?;
+    csv.flush()?;
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        for failure in &failures {
+            eprintln!("{}", failure);
+        }
+
+        Err(format!("{} expectation(s) were not met", failures.len()).into())
+    }
+}
@@ -0,0 +1,139 @@
+use std::io;
+use systems::simulation::{Aircraft, Scenario, ScenarioFailure};
+
+/// Runs the aircraft through the scenario. After every tick a CSV row containing the
+/// elapsed time and the scenario's outputs is written to the given writer.
+/// Returns the scenario's expectations which weren't met.
+pub fn run<T: Aircraft, W: io::Write>(
+    scenario: &Scenario,
+    aircraft: &mut T,
+    csv: &mut W,
+) -> io::Result<Vec<ScenarioFailure>> {
+    write_row(
+        csv,
+        std::iter::once("time".to_owned())
+            .chain(scenario.outputs().iter().map(|name| escape(name))),
+    )?;
+
+    let mut result = Ok(());
+    let failures = scenario.run(aircraft, |elapsed, outputs| {
+        if result.is_ok() {
+            result = write_row(
+                csv,
+                std::iter::once(elapsed.as_secs_f64().to_string())
+                    .chain(outputs.iter().map(|value| value.to_string())),
+            );
+        }
+    });
+
+    result.map(|_| failures)
+}
+
+fn write_row<W: io::Write>(csv: &mut W, fields: impl Iterator<Item = String>) -> io::Result<()> {
+    writeln!(csv, "{}", fields.collect::<Vec<_>>().join(","))
+}
+
+/// Quotes the field when it contains characters which have a meaning in CSV.
+fn escape(field: &str) -> String {
+    if field.contains(&[',', '"', '\n'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use a320_systems::A320;
+    use systems::simulation::{
+        QuantityIdentifier, SimulationElement, SimulatorReader, SimulatorWriter, VariableRegistry,
+        VariableUnit,
+    };
+
+    struct TestAircraft {
+        n_id: QuantityIdentifier<f64>,
+        double_n_id: QuantityIdentifier<f64>,
+        n: f64,
+    }
+    impl TestAircraft {
+        fn new() -> Self {
+            let mut registry = VariableRegistry::new();
+
+            Self {
+                n_id: registry.register_read("TEST N", VariableUnit::Number),
+                double_n_id: registry.register_write("TEST_DOUBLE_N", VariableUnit::Number),
+                n: 0.,
+            }
+        }
+    }
+    impl Aircraft for TestAircraft {}
+    impl SimulationElement for TestAircraft {
+        fn read(&mut self, reader: &mut SimulatorReader) {
+            self.n = reader.read_f64(&self.n_id);
+        }
+
+        fn write(&self, writer: &mut SimulatorWriter) {
+            writer.write_f64(&self.double_n_id, self.n * 2.);
+        }
+    }
+
+    fn run_to_string<T: Aircraft>(scenario: &str, aircraft: &mut T) -> String {
+        let mut csv = Vec::new();
+        assert!(run(&scenario.parse().unwrap(), aircraft, &mut csv)
+            .unwrap()
+            .is_empty());
+
+        String::from_utf8(csv).unwrap()
+    }
+
+    #[test]
+    fn writes_a_row_per_tick() {
+        let csv = run_to_string(
+            "duration 1\ndelta 0.5\noutput TEST_DOUBLE_N",
+            &mut TestAircraft::new(),
+        );
+
+        assert_eq!(csv, "time,TEST_DOUBLE_N\n0.5,0\n1,0\n");
+    }
+
+    #[test]
+    fn quotes_output_names_containing_a_comma() {
+        let csv = run_to_string("output A,B", &mut TestAircraft::new());
+
+        assert_eq!(csv, "time,\"A,B\"\n");
+    }
+
+    #[test]
+    fn a320_starts_with_push_buttons_in_their_default_position() {
+        let csv = run_to_string(
+            "duration 1\ndelta 1\noutput OVHD_ELEC_BUS_TIE_PB_IS_AUTO",
+            &mut A320::new(&mut VariableRegistry::new()),
+        );
+
+        assert_eq!(csv, "time,OVHD_ELEC_BUS_TIE_PB_IS_AUTO\n1,1\n");
+    }
+
+    #[test]
+    fn a320_external_power_powers_ac_bus_when_connected() {
+        let csv = run_to_string(
+            "duration 3\ndelta 1\noutput ELEC_AC_1_BUS_IS_POWERED\n\
+             at 1 1 EXTERNAL POWER AVAILABLE:1\nat 1 1 OVHD_ELEC_EXT_PWR_PB_IS_ON",
+            &mut A320::new(&mut VariableRegistry::new()),
+        );
+
+        // The external power contactor closes one tick after the power becomes available.
+        assert_eq!(csv, "time,ELEC_AC_1_BUS_IS_POWERED\n1,0\n2,0\n3,1\n");
+    }
+
+    #[test]
+    fn runs_with_the_same_seed_produce_the_same_output() {
+        let scenario = "duration 10\nseed 3\noutput ELEC_BAT_10_CURRENT\n\
+                        at 0 1 OVHD_ELEC_BAT_10_PB_IS_AUTO\nat 0 1 OVHD_ELEC_BAT_11_PB_IS_AUTO";
+
+        assert_eq!(
+            run_to_string(scenario, &mut A320::new(&mut VariableRegistry::new())),
+            run_to_string(scenario, &mut A320::new(&mut VariableRegistry::new()))
+        );
+    }
+}
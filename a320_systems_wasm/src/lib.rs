@@ -4,14 +4,42 @@ use msfs::{
     legacy::{AircraftVariable, NamedVariable},
     MSFSEvent,
 };
-use std::collections::HashMap;
-use systems::simulation::{Simulation, SimulatorReaderWriter};
+use std::time::Duration;
+use systems::simulation::{
+    FailureRegistry, RecordingSimulatorReaderWriter, Simulation, SimulatorReaderWriter,
+    VariableIdentifier, VariableRegistry,
+};
+
+/// Frames taking longer than this are simulated in multiple steps, such that
+/// the systems behave the same regardless of the frame rate.
+const MAX_SIMULATION_STEP_MILLIS: u64 = 100;
+
+/// Variables whose value didn't change are written again after this interval,
+/// in case something else in the simulator changed them.
+const WRITE_REFRESH_INTERVAL_MILLIS: u64 = 1000;
+
+/// The simulator's reads and writes are recorded while this variable is set.
+const TRACE_IS_RECORDING_VARIABLE: &str = "A32NX_SYSTEMS_TRACE_IS_RECORDING";
+
+/// The file to which the recorded trace is written once recording stops.
+const TRACE_FILE: &str = "\\work\\systems.trace";
+
+/// Set when the last recorded trace couldn't be written to the [`TRACE_FILE`].
+const TRACE_WRITE_FAILED_VARIABLE: &str = "A32NX_SYSTEMS_TRACE_WRITE_FAILED";
 
 #[msfs::gauge(name=systems)]
 async fn systems(mut gauge: msfs::Gauge) -> Result<(), Box<dyn std::error::Error>> {
-    let mut reader_writer = A320SimulatorReaderWriter::new()?;
-    let mut a320 = A320::new();
-    let mut simulation = Simulation::new(&mut a320, &mut reader_writer);
+    let mut registry = VariableRegistry::new();
+    let mut a320 = A320::new(&mut registry);
+    let mut reader_writer =
+        TraceRecordingReaderWriter::new(A320SimulatorReaderWriter::new(&registry)?);
+    let mut simulation = Simulation::new(&mut a320, &mut reader_writer)
+        .with_max_step(Duration::from_millis(MAX_SIMULATION_STEP_MILLIS))
+        .with_change_only_writes(
+            Duration::from_millis(WRITE_REFRESH_INTERVAL_MILLIS),
+            &registry,
+        );
+    publish_failures(&simulation.failures());
 
     while let Some(event) = gauge.next_event().await {
         if let MSFSEvent::PreDraw(d) = event {
@@ -22,101 +50,158 @@ async fn systems(mut gauge: msfs::Gauge) -> Result<(), Box<dyn std::error::Error
     Ok(())
 }
 
+/// Publishes the failures which can be activated, such that the instructor's tools
+/// can list them. A failure is activated by setting its `A32NX_FAILURE_<id>_IS_ACTIVE`
+/// variable.
+fn publish_failures(failures: &FailureRegistry) {
+    let failure_types = failures.failure_types();
+    NamedVariable::from("A32NX_FAILURE_COUNT").set_value(failure_types.len() as f64);
+    for (index, failure_type) in failure_types.iter().enumerate() {
+        NamedVariable::from(&format!("A32NX_FAILURE_{}_ID", index + 1))
+            .set_value(failure_type.id() as f64);
+    }
+}
+
+/// Simulation variables which are read from an aircraft variable with a different name.
+const AIRCRAFT_VARIABLE_ALIASES: [(&str, &str, usize); 5] = [
+    ("OVHD_ELEC_APU_GEN_PB_IS_ON", "APU GENERATOR SWITCH", 0),
+    (
+        "OVHD_ELEC_EXT_PWR_PB_IS_AVAILABLE",
+        "EXTERNAL POWER AVAILABLE",
+        1,
+    ),
+    ("OVHD_ELEC_EXT_PWR_PB_IS_ON", "EXTERNAL POWER ON", 1),
+    (
+        "OVHD_ELEC_ENG_GEN_1_PB_IS_ON",
+        "GENERAL ENG MASTER ALTERNATOR",
+        1,
+    ),
+    (
+        "OVHD_ELEC_ENG_GEN_2_PB_IS_ON",
+        "GENERAL ENG MASTER ALTERNATOR",
+        2,
+    ),
+];
+
+/// Returns the name and index of the aircraft variable from which the simulation
+/// variable is read. Aircraft variable names contain spaces and are optionally followed
+/// by `:` and an index, e.g. `TURB ENG N1:1`. Other variables are named variables.
+fn aircraft_variable(name: &str) -> Option<(&str, usize)> {
+    if let Some((_, aircraft_name, index)) = AIRCRAFT_VARIABLE_ALIASES
+        .iter()
+        .find(|(alias, _, _)| *alias == name)
+    {
+        return Some((aircraft_name, *index));
+    }
+
+    if !name.contains(' ') {
+        return None;
+    }
+
+    match name.rsplit_once(':') {
+        Some((aircraft_name, index)) => index.parse().ok().map(|index| (aircraft_name, index)),
+        None => Some((name, 0)),
+    }
+}
+
+/// Records the simulator's reads and writes while the [`TRACE_IS_RECORDING_VARIABLE`] is set.
+/// When the variable is reset, the trace is written to the [`TRACE_FILE`], setting the
+/// [`TRACE_WRITE_FAILED_VARIABLE`] when that isn't possible. As the simulation
+/// splits long frames into steps, the trace is replayed using `replay_with_max_step` with
+/// the [`MAX_SIMULATION_STEP_MILLIS`].
+struct TraceRecordingReaderWriter {
+    recorder: RecordingSimulatorReaderWriter<A320SimulatorReaderWriter>,
+    is_recording: NamedVariable,
+    write_failed: NamedVariable,
+}
+impl TraceRecordingReaderWriter {
+    fn new(reader_writer: A320SimulatorReaderWriter) -> Self {
+        Self {
+            recorder: RecordingSimulatorReaderWriter::paused(reader_writer),
+            is_recording: NamedVariable::from(TRACE_IS_RECORDING_VARIABLE),
+            write_failed: NamedVariable::from(TRACE_WRITE_FAILED_VARIABLE),
+        }
+    }
+
+    fn write_trace(&mut self) {
+        let trace = self.recorder.take_trace();
+        let is_written = std::fs::write(TRACE_FILE, trace.to_string()).is_ok();
+        self.write_failed
+            .set_value(if is_written { 0. } else { 1. });
+    }
+}
+impl SimulatorReaderWriter for TraceRecordingReaderWriter {
+    fn begin_tick(&mut self, delta: Duration, random_seed: u64) {
+        let should_record = self.is_recording.get_value() == 1.;
+        if self.recorder.is_recording() && !should_record {
+            self.write_trace();
+        }
+        self.recorder.set_recording(should_record);
+
+        self.recorder.begin_tick(delta, random_seed);
+    }
+
+    fn read(&mut self, identifier: &VariableIdentifier) -> f64 {
+        self.recorder.read(identifier)
+    }
+
+    fn write(&mut self, identifier: &VariableIdentifier, value: f64) {
+        self.recorder.write(identifier, value);
+    }
+
+    fn write_unchanged(&mut self, identifier: &VariableIdentifier, value: f64) {
+        self.recorder.write_unchanged(identifier, value);
+    }
+}
+
 struct A320SimulatorReaderWriter {
-    dynamic_named_variables: HashMap<String, NamedVariable>,
-
-    ambient_temperature: AircraftVariable,
-    apu_generator_pb_on: AircraftVariable,
-    external_power_available: AircraftVariable,
-    external_power_pb_on: AircraftVariable,
-    engine_generator_1_pb_on: AircraftVariable,
-    engine_generator_2_pb_on: AircraftVariable,
-    gear_center_position: AircraftVariable,
-    turb_eng_corrected_n2_1: AircraftVariable,
-    turb_eng_corrected_n2_2: AircraftVariable,
-    airspeed_indicated: AircraftVariable,
-    indicated_altitude: AircraftVariable,
-    fuel_tank_left_main_quantity: AircraftVariable,
-    sim_on_ground: AircraftVariable,
-    unlimited_fuel: AircraftVariable,
+    /// The aircraft variables, indexed by the index of their variable identifier.
+    aircraft_variables: Vec<Option<AircraftVariable>>,
+    /// The named variables, indexed by the index of their variable identifier.
+    named_variables: Vec<Option<NamedVariable>>,
 }
 impl A320SimulatorReaderWriter {
-    fn new() -> Result<Self, Box<dyn std::error::Error>> {
+    /// Creates the aircraft variables read by the simulation, in the unit
+    /// with which they are registered.
+    fn new(registry: &VariableRegistry) -> Result<Self, Box<dyn std::error::Error>> {
+        let aircraft_variables = registry
+            .definitions()
+            .iter()
+            .map(|definition| {
+                aircraft_variable(definition.name())
+                    .filter(|_| definition.direction().is_read())
+                    .map(|(name, index)| {
+                        AircraftVariable::from(name, definition.unit().name(), index)
+                    })
+                    .transpose()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
         Ok(A320SimulatorReaderWriter {
-            dynamic_named_variables: HashMap::new(),
-            ambient_temperature: AircraftVariable::from("AMBIENT TEMPERATURE", "celsius", 0)?,
-            apu_generator_pb_on: AircraftVariable::from("APU GENERATOR SWITCH", "Bool", 0)?,
-            external_power_available: AircraftVariable::from(
-                "EXTERNAL POWER AVAILABLE",
-                "Bool",
-                1,
-            )?,
-            external_power_pb_on: AircraftVariable::from("EXTERNAL POWER ON", "Bool", 1)?,
-            engine_generator_1_pb_on: AircraftVariable::from(
-                "GENERAL ENG MASTER ALTERNATOR",
-                "Bool",
-                1,
-            )?,
-            engine_generator_2_pb_on: AircraftVariable::from(
-                "GENERAL ENG MASTER ALTERNATOR",
-                "Bool",
-                2,
-            )?,
-            gear_center_position: AircraftVariable::from("GEAR CENTER POSITION", "Percent", 0)?,
-            turb_eng_corrected_n2_1: AircraftVariable::from("TURB ENG CORRECTED N2", "Percent", 1)?,
-            turb_eng_corrected_n2_2: AircraftVariable::from("TURB ENG CORRECTED N2", "Percent", 2)?,
-            airspeed_indicated: AircraftVariable::from("AIRSPEED INDICATED", "Knots", 0)?,
-            indicated_altitude: AircraftVariable::from("INDICATED ALTITUDE", "Feet", 0)?,
-            fuel_tank_left_main_quantity: AircraftVariable::from(
-                "FUEL TANK LEFT MAIN QUANTITY",
-                "Pounds",
-                0,
-            )?,
-            sim_on_ground: AircraftVariable::from("SIM ON GROUND", "Bool", 0)?,
-            unlimited_fuel: AircraftVariable::from("UNLIMITED FUEL", "Bool", 0)?,
+            aircraft_variables,
+            named_variables: Vec::new(),
         })
     }
+
+    fn named_variable(&mut self, identifier: &VariableIdentifier) -> &mut NamedVariable {
+        let index = identifier.index();
+        if self.named_variables.len() <= index {
+            self.named_variables.resize_with(index + 1, || None);
+        }
+
+        self.named_variables[index]
+            .get_or_insert_with(|| NamedVariable::from(&format!("A32NX_{}", identifier.name())))
+    }
 }
 impl SimulatorReaderWriter for A320SimulatorReaderWriter {
-    fn read(&mut self, name: &str) -> f64 {
-        match name {
-            "OVHD_ELEC_APU_GEN_PB_IS_ON" => self.apu_generator_pb_on.get(),
-            "OVHD_ELEC_EXT_PWR_PB_IS_AVAILABLE" => self.external_power_available.get(),
-            "OVHD_ELEC_EXT_PWR_PB_IS_ON" => self.external_power_pb_on.get(),
-            "OVHD_ELEC_ENG_GEN_1_PB_IS_ON" => self.engine_generator_1_pb_on.get(),
-            "OVHD_ELEC_ENG_GEN_2_PB_IS_ON" => self.engine_generator_2_pb_on.get(),
-            "AMBIENT TEMPERATURE" => self.ambient_temperature.get(),
-            "EXTERNAL POWER AVAILABLE:1" => self.external_power_available.get(),
-            "GEAR CENTER POSITION" => self.gear_center_position.get(),
-            "TURB ENG CORRECTED N2:1" => self.turb_eng_corrected_n2_1.get(),
-            "TURB ENG CORRECTED N2:2" => self.turb_eng_corrected_n2_2.get(),
-            "FUEL TANK LEFT MAIN QUANTITY" => self.fuel_tank_left_main_quantity.get(),
-            "UNLIMITED FUEL" => self.unlimited_fuel.get(),
-            "AIRSPEED INDICATED" => self.airspeed_indicated.get(),
-            "INDICATED ALTITUDE" => self.indicated_altitude.get(),
-            "SIM ON GROUND" => self.sim_on_ground.get(),
-            _ => {
-                lookup_named_variable(&mut self.dynamic_named_variables, "A32NX_", name).get_value()
-            }
+    fn read(&mut self, identifier: &VariableIdentifier) -> f64 {
+        match self.aircraft_variables.get(identifier.index()) {
+            Some(Some(aircraft_variable)) => aircraft_variable.get(),
+            _ => self.named_variable(identifier).get_value(),
         }
     }
 
-    fn write(&mut self, name: &str, value: f64) {
-        let named_variable =
-            lookup_named_variable(&mut self.dynamic_named_variables, "A32NX_", name);
-
-        named_variable.set_value(value);
+    fn write(&mut self, identifier: &VariableIdentifier, value: f64) {
+        self.named_variable(identifier).set_value(value);
     }
 }
-
-fn lookup_named_variable<'a>(
-    collection: &'a mut HashMap<String, NamedVariable>,
-    key_prefix: &str,
-    key: &str,
-) -> &'a mut NamedVariable {
-    let key = format!("{}{}", key_prefix, key);
-
-    collection
-        .entry(key.clone())
-        .or_insert_with(|| NamedVariable::from(&key))
-}
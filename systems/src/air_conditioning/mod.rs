@@ -0,0 +1,421 @@
+//! Air conditioning packs cool the bleed air supplied to them by the pneumatic system.
+//! The conditioned air is distributed over the temperature zones, where hot trim air
+//! is added to reach each zone's selected temperature.
+use crate::{
+    electrical::{consumption::PowerConsumer, ElectricalBusType},
+    shared::calculate_towards_target_temperature,
+    simulation::{
+        QuantityIdentifier, SimulationElement, SimulationElementVisitor, SimulatorWriter, Snapshot,
+        UpdateContext, VariableRegistry, VariableUnit,
+    },
+};
+use uom::si::{
+    f64::*, mass_rate::kilogram_per_second, power::watt, thermodynamic_temperature::degree_celsius,
+};
+
+/// An air conditioning pack. The pack controller regulates the pack outlet
+/// temperature towards the temperature demanded by the zones. When the pack
+/// controller is unpowered, the pack's bypass valve is pneumatically controlled
+/// to a fixed outlet temperature.
+pub struct AirConditioningPack {
+    outlet_temperature_id: QuantityIdentifier<ThermodynamicTemperature>,
+    is_operating_id: QuantityIdentifier<bool>,
+    outlet_temperature: ThermodynamicTemperature,
+    flow: MassRate,
+    consumer: PowerConsumer,
+}
+impl AirConditioningPack {
+    const MINIMUM_OUTLET_TEMPERATURE_CELSIUS: f64 = 2.;
+    const MAXIMUM_OUTLET_TEMPERATURE_CELSIUS: f64 = 70.;
+    const UNCONTROLLED_OUTLET_TEMPERATURE_CELSIUS: f64 = 15.;
+    const OUTLET_TEMPERATURE_COEFFICIENT: f64 = 1.;
+    const CONTROLLER_POWER_CONSUMPTION_WATT: f64 = 100.;
+
+    pub fn new(
+        registry: &mut VariableRegistry,
+        number: usize,
+        bus_type: ElectricalBusType,
+    ) -> AirConditioningPack {
+        AirConditioningPack {
+            outlet_temperature_id: registry.register_write(
+                &format!("COND_PACK_{}_OUTLET_TEMPERATURE", number),
+                VariableUnit::Celsius,
+            ),
+            is_operating_id: registry.register_write(
+                &format!("COND_PACK_{}_IS_OPERATING", number),
+                VariableUnit::Bool,
+            ),
+            outlet_temperature: ThermodynamicTemperature::new::<degree_celsius>(15.),
+            flow: MassRate::new::<kilogram_per_second>(0.),
+            consumer: PowerConsumer::from(bus_type),
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        context: &UpdateContext,
+        inlet_flow: MassRate,
+        demanded_outlet_temperature: ThermodynamicTemperature,
+    ) {
+        self.flow = inlet_flow;
+
+        let target = if !self.is_operating() {
+            context.ambient_temperature()
+        } else if self.consumer.is_powered() {
+            demanded_outlet_temperature
+                .max(ThermodynamicTemperature::new::<degree_celsius>(
+                    AirConditioningPack::MINIMUM_OUTLET_TEMPERATURE_CELSIUS,
+                ))
+                .min(ThermodynamicTemperature::new::<degree_celsius>(
+                    AirConditioningPack::MAXIMUM_OUTLET_TEMPERATURE_CELSIUS,
+                ))
+        } else {
+            ThermodynamicTemperature::new::<degree_celsius>(
+                AirConditioningPack::UNCONTROLLED_OUTLET_TEMPERATURE_CELSIUS,
+            )
+        };
+
+        self.outlet_temperature = calculate_towards_target_temperature(
+            self.outlet_temperature,
+            target,
+            AirConditioningPack::OUTLET_TEMPERATURE_COEFFICIENT,
+            context.delta(),
+        );
+
+        self.consumer.demand(Power::new::<watt>(
+            AirConditioningPack::CONTROLLER_POWER_CONSUMPTION_WATT,
+        ));
+    }
+
+    pub fn is_operating(&self) -> bool {
+        self.flow > MassRate::new::<kilogram_per_second>(0.)
+    }
+
+    pub fn flow(&self) -> MassRate {
+        self.flow
+    }
+
+    pub fn outlet_temperature(&self) -> ThermodynamicTemperature {
+        self.outlet_temperature
+    }
+}
+impl SimulationElement for AirConditioningPack {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.consumer.accept(visitor);
+
+        visitor.visit(self);
+    }
+
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_temperature(&self.outlet_temperature_id, self.outlet_temperature);
+        writer.write_bool(&self.is_operating_id, self.is_operating());
+    }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        snapshot.save_f64(
+            self.outlet_temperature_id.name(),
+            self.outlet_temperature.get::<degree_celsius>(),
+        );
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        if let Some(temperature) = snapshot.restore_f64(self.outlet_temperature_id.name()) {
+            self.outlet_temperature = ThermodynamicTemperature::new::<degree_celsius>(temperature);
+        }
+    }
+}
+
+/// A temperature zone, such as the cockpit or a cabin section. The zone's air is
+/// heated or cooled by the air supplied through its duct, and exchanges heat
+/// with the outside through the fuselage.
+pub struct TemperatureZone {
+    temperature_id: QuantityIdentifier<ThermodynamicTemperature>,
+    duct_temperature_id: QuantityIdentifier<ThermodynamicTemperature>,
+    temperature: ThermodynamicTemperature,
+    duct_temperature: ThermodynamicTemperature,
+    heat_capacity_joule_per_kelvin: f64,
+    fuselage_conductance_watt_per_kelvin: f64,
+}
+impl TemperatureZone {
+    const AIR_SPECIFIC_HEAT_JOULE_PER_KILOGRAM_KELVIN: f64 = 1005.;
+
+    pub fn new(
+        registry: &mut VariableRegistry,
+        id: &str,
+        heat_capacity_joule_per_kelvin: f64,
+        fuselage_conductance_watt_per_kelvin: f64,
+    ) -> TemperatureZone {
+        TemperatureZone {
+            temperature_id: registry
+                .register_write(&format!("COND_{}_TEMP", id), VariableUnit::Celsius),
+            duct_temperature_id: registry
+                .register_write(&format!("COND_{}_DUCT_TEMP", id), VariableUnit::Celsius),
+            temperature: ThermodynamicTemperature::new::<degree_celsius>(15.),
+            duct_temperature: ThermodynamicTemperature::new::<degree_celsius>(15.),
+            heat_capacity_joule_per_kelvin,
+            fuselage_conductance_watt_per_kelvin,
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        context: &UpdateContext,
+        supply_flow: MassRate,
+        duct_temperature: ThermodynamicTemperature,
+    ) {
+        self.duct_temperature = duct_temperature;
+
+        let zone = self.temperature.get::<degree_celsius>();
+        let supplied_heat = supply_flow.get::<kilogram_per_second>()
+            * TemperatureZone::AIR_SPECIFIC_HEAT_JOULE_PER_KILOGRAM_KELVIN
+            * (duct_temperature.get::<degree_celsius>() - zone);
+        let fuselage_heat = self.fuselage_conductance_watt_per_kelvin
+            * (context.ambient_temperature().get::<degree_celsius>() - zone);
+
+        self.temperature = ThermodynamicTemperature::new::<degree_celsius>(
+            zone + (supplied_heat + fuselage_heat) * context.delta().as_secs_f64()
+                / self.heat_capacity_joule_per_kelvin,
+        );
+    }
+
+    pub fn temperature(&self) -> ThermodynamicTemperature {
+        self.temperature
+    }
+
+    pub fn duct_temperature(&self) -> ThermodynamicTemperature {
+        self.duct_temperature
+    }
+}
+impl SimulationElement for TemperatureZone {
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_temperature(&self.temperature_id, self.temperature);
+        writer.write_temperature(&self.duct_temperature_id, self.duct_temperature);
+    }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        snapshot.save_f64(
+            self.temperature_id.name(),
+            self.temperature.get::<degree_celsius>(),
+        );
+        snapshot.save_f64(
+            self.duct_temperature_id.name(),
+            self.duct_temperature.get::<degree_celsius>(),
+        );
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        if let Some(temperature) = snapshot.restore_f64(self.temperature_id.name()) {
+            self.temperature = ThermodynamicTemperature::new::<degree_celsius>(temperature);
+        }
+        if let Some(duct_temperature) = snapshot.restore_f64(self.duct_temperature_id.name()) {
+            self.duct_temperature =
+                ThermodynamicTemperature::new::<degree_celsius>(duct_temperature);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        electrical::{consumption::SuppliedPower, Potential, PotentialOrigin},
+        simulation::{test::SimulationTestBed, Aircraft},
+    };
+    use std::time::Duration;
+    use uom::si::electric_potential::volt;
+
+    mod air_conditioning_pack_tests {
+        use super::*;
+
+        struct TestAircraft {
+            pack: AirConditioningPack,
+            inlet_flow: MassRate,
+            demanded_outlet_temperature: ThermodynamicTemperature,
+            is_dc_powered: bool,
+        }
+        impl TestAircraft {
+            fn new() -> Self {
+                Self {
+                    pack: AirConditioningPack::new(
+                        &mut VariableRegistry::new(),
+                        1,
+                        ElectricalBusType::DirectCurrent(1),
+                    ),
+                    inlet_flow: MassRate::new::<kilogram_per_second>(0.4),
+                    demanded_outlet_temperature: ThermodynamicTemperature::new::<degree_celsius>(
+                        10.,
+                    ),
+                    is_dc_powered: true,
+                }
+            }
+        }
+        impl Aircraft for TestAircraft {
+            fn update_after_power_distribution(&mut self, context: &UpdateContext) {
+                self.pack
+                    .update(context, self.inlet_flow, self.demanded_outlet_temperature);
+            }
+
+            fn get_supplied_power(&mut self) -> SuppliedPower {
+                let mut supplied_power = SuppliedPower::new();
+                if self.is_dc_powered {
+                    supplied_power.add(
+                        ElectricalBusType::DirectCurrent(1),
+                        Potential::single(
+                            PotentialOrigin::Battery(10),
+                            ElectricPotential::new::<volt>(28.),
+                        ),
+                    );
+                }
+
+                supplied_power
+            }
+        }
+        impl SimulationElement for TestAircraft {
+            fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+                self.pack.accept(visitor);
+
+                visitor.visit(self);
+            }
+        }
+
+        fn run_for(aircraft: &mut TestAircraft, seconds: u64) {
+            let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_secs(1));
+            test_bed.set_ambient_temperature(ThermodynamicTemperature::new::<degree_celsius>(30.));
+            for _ in 0..seconds {
+                test_bed.run_aircraft(aircraft);
+            }
+        }
+
+        #[test]
+        fn regulates_outlet_temperature_towards_demand() {
+            let mut aircraft = TestAircraft::new();
+            run_for(&mut aircraft, 60);
+
+            assert!(aircraft.pack.is_operating());
+            assert_eq!(
+                aircraft.pack.outlet_temperature(),
+                ThermodynamicTemperature::new::<degree_celsius>(10.)
+            );
+        }
+
+        #[test]
+        fn outlet_temperature_is_limited() {
+            let mut aircraft = TestAircraft::new();
+            aircraft.demanded_outlet_temperature =
+                ThermodynamicTemperature::new::<degree_celsius>(-20.);
+            run_for(&mut aircraft, 60);
+
+            assert_eq!(
+                aircraft.pack.outlet_temperature(),
+                ThermodynamicTemperature::new::<degree_celsius>(2.)
+            );
+        }
+
+        #[test]
+        fn unpowered_controller_results_in_fixed_outlet_temperature() {
+            let mut aircraft = TestAircraft::new();
+            aircraft.is_dc_powered = false;
+            run_for(&mut aircraft, 60);
+
+            assert_eq!(
+                aircraft.pack.outlet_temperature(),
+                ThermodynamicTemperature::new::<degree_celsius>(15.)
+            );
+        }
+
+        #[test]
+        fn outlet_temperature_is_restored_from_snapshot() {
+            let mut aircraft = TestAircraft::new();
+            run_for(&mut aircraft, 5);
+
+            let mut restored_aircraft = TestAircraft::new();
+            Snapshot::take(&mut aircraft).restore(&mut restored_aircraft);
+
+            assert_eq!(
+                restored_aircraft.pack.outlet_temperature(),
+                aircraft.pack.outlet_temperature()
+            );
+        }
+
+        #[test]
+        fn without_flow_outlet_temperature_moves_towards_ambient() {
+            let mut aircraft = TestAircraft::new();
+            aircraft.inlet_flow = MassRate::new::<kilogram_per_second>(0.);
+            run_for(&mut aircraft, 60);
+
+            assert!(!aircraft.pack.is_operating());
+            assert_eq!(
+                aircraft.pack.outlet_temperature(),
+                ThermodynamicTemperature::new::<degree_celsius>(30.)
+            );
+        }
+    }
+
+    mod temperature_zone_tests {
+        use super::*;
+
+        fn run_zone(
+            zone: &mut TemperatureZone,
+            seconds: u64,
+            supply_flow: f64,
+            duct_temperature: f64,
+        ) {
+            let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_secs(1));
+            test_bed.set_ambient_temperature(ThermodynamicTemperature::new::<degree_celsius>(-40.));
+            for _ in 0..seconds {
+                test_bed.run(zone, |zone, context| {
+                    zone.update(
+                        context,
+                        MassRate::new::<kilogram_per_second>(supply_flow),
+                        ThermodynamicTemperature::new::<degree_celsius>(duct_temperature),
+                    )
+                });
+            }
+        }
+
+        fn zone() -> TemperatureZone {
+            TemperatureZone::new(&mut VariableRegistry::new(), "FWD", 300000., 50.)
+        }
+
+        #[test]
+        fn supplied_air_warms_the_zone() {
+            let mut zone = zone();
+            run_zone(&mut zone, 1200, 0.4, 40.);
+
+            assert!(zone.temperature() > ThermodynamicTemperature::new::<degree_celsius>(25.));
+        }
+
+        #[test]
+        fn zone_cools_down_without_supplied_air() {
+            let mut zone = zone();
+            run_zone(&mut zone, 600, 0., 40.);
+
+            assert!(zone.temperature() < ThermodynamicTemperature::new::<degree_celsius>(15.));
+        }
+
+        #[test]
+        fn temperatures_are_restored_from_snapshot() {
+            let mut zone = zone();
+            run_zone(&mut zone, 600, 0.4, 40.);
+
+            let mut restored_zone = self::zone();
+            Snapshot::take(&mut zone).restore(&mut restored_zone);
+
+            assert_eq!(restored_zone.temperature(), zone.temperature());
+            assert_eq!(
+                restored_zone.duct_temperature(),
+                ThermodynamicTemperature::new::<degree_celsius>(40.)
+            );
+        }
+
+        #[test]
+        fn writes_its_state() {
+            let mut zone = zone();
+            let mut test_bed = SimulationTestBed::new();
+            test_bed.run_without_update(&mut zone);
+
+            assert!(test_bed.contains_key("COND_FWD_TEMP"));
+            assert!(test_bed.contains_key("COND_FWD_DUCT_TEMP"));
+        }
+    }
+}
@@ -1,50 +1,57 @@
 use super::AirIntakeFlapController;
-use crate::{shared::random_number, simulation::UpdateContext};
+use crate::simulation::{Snapshot, UpdateContext};
 use std::time::Duration;
 use uom::si::{f64::*, ratio::percent};
 
 pub struct AirIntakeFlap {
     open_amount: Ratio,
-    delay: Duration,
+    /// The travel time of the flap. Randomly chosen upon the first update.
+    delay: Option<Duration>,
 }
 impl AirIntakeFlap {
     const MINIMUM_TRAVEL_TIME_SECS: u8 = 6;
     const MAXIMUM_TRAVEL_TIME_SECS: u8 = 12;
 
     pub fn new() -> AirIntakeFlap {
-        let random_above_minimum_mod =
-            AirIntakeFlap::MAXIMUM_TRAVEL_TIME_SECS - AirIntakeFlap::MINIMUM_TRAVEL_TIME_SECS + 1;
-        let delay = Duration::from_secs(
-            (AirIntakeFlap::MINIMUM_TRAVEL_TIME_SECS + (random_number() % random_above_minimum_mod))
-                as u64,
-        );
-
         AirIntakeFlap {
             open_amount: Ratio::new::<percent>(0.),
-            delay,
+            delay: None,
         }
     }
 
+    fn random_delay(context: &UpdateContext) -> Duration {
+        let random_above_minimum_mod =
+            AirIntakeFlap::MAXIMUM_TRAVEL_TIME_SECS - AirIntakeFlap::MINIMUM_TRAVEL_TIME_SECS + 1;
+        Duration::from_secs(
+            (AirIntakeFlap::MINIMUM_TRAVEL_TIME_SECS
+                + (context.random_number() % random_above_minimum_mod)) as u64,
+        )
+    }
+
     pub fn update<T: AirIntakeFlapController>(&mut self, context: &UpdateContext, controller: &T) {
+        let delay = *self
+            .delay
+            .get_or_insert_with(|| AirIntakeFlap::random_delay(context));
+
         if controller.should_open_air_intake_flap()
             && self.open_amount < Ratio::new::<percent>(100.)
         {
             self.open_amount += Ratio::new::<percent>(
-                self.get_flap_change_for_delta(context)
+                AirIntakeFlap::get_flap_change_for_delta(context, delay)
                     .min(100. - self.open_amount.get::<percent>()),
             );
         } else if !controller.should_open_air_intake_flap()
             && self.open_amount > Ratio::new::<percent>(0.)
         {
             self.open_amount -= Ratio::new::<percent>(
-                self.get_flap_change_for_delta(context)
+                AirIntakeFlap::get_flap_change_for_delta(context, delay)
                     .min(self.open_amount.get::<percent>()),
             );
         }
     }
 
-    fn get_flap_change_for_delta(&self, context: &UpdateContext) -> f64 {
-        100. * (context.delta().as_secs_f64() / self.delay.as_secs_f64())
+    fn get_flap_change_for_delta(context: &UpdateContext, delay: Duration) -> f64 {
+        100. * (context.delta().as_secs_f64() / delay.as_secs_f64())
     }
 
     pub fn is_fully_open(&self) -> bool {
@@ -55,9 +62,27 @@ impl AirIntakeFlap {
         self.open_amount
     }
 
+    pub fn save(&self, snapshot: &mut Snapshot) {
+        snapshot.save_f64(
+            "APU_AIR_INTAKE_FLAP_OPEN_AMOUNT",
+            self.open_amount.get::<percent>(),
+        );
+        if let Some(delay) = self.delay {
+            snapshot.save_duration("APU_AIR_INTAKE_FLAP_DELAY", delay);
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        if let Some(open_amount) = snapshot.restore_f64("APU_AIR_INTAKE_FLAP_OPEN_AMOUNT") {
+            self.open_amount = Ratio::new::<percent>(open_amount);
+            // The delay is only saved while the flap is delayed.
+            self.delay = snapshot.restore_duration("APU_AIR_INTAKE_FLAP_DELAY");
+        }
+    }
+
     #[cfg(test)]
     pub fn set_delay(&mut self, delay: Duration) {
-        self.delay = delay;
+        self.delay = Some(delay);
     }
 }
 
@@ -5,8 +5,11 @@ use crate::{
         ElectricalStateWriter, Potential, PotentialOrigin, PotentialSource, PotentialTarget,
         ProvideFrequency, ProvideLoad, ProvidePotential,
     },
-    shared::{calculate_towards_target_temperature, random_number},
-    simulation::{SimulationElement, SimulatorWriter, UpdateContext},
+    shared::calculate_towards_target_temperature,
+    simulation::{
+        Failure, FailureType, SimulationElement, SimulationElementVisitor, SimulatorWriter,
+        Snapshot, UpdateContext, VariableRegistry,
+    },
 };
 use std::time::Duration;
 use uom::si::{
@@ -14,6 +17,54 @@ use uom::si::{
     temperature_interval, thermodynamic_temperature::degree_celsius,
 };
 
+const STATE_ID: &str = "APU_TURBINE_STATE";
+const SINCE_ID: &str = "APU_TURBINE_SINCE";
+const N_ID: &str = "APU_TURBINE_N";
+const EGT_ID: &str = "APU_TURBINE_EGT";
+
+/// Restores the turbine state contained in the snapshot. The given turbine is kept
+/// when the snapshot doesn't contain a complete turbine state.
+fn restore_turbine(turbine: Box<dyn Turbine>, snapshot: &Snapshot) -> Box<dyn Turbine> {
+    let restored: Option<Box<dyn Turbine>> = match snapshot.restore_f64(STATE_ID) {
+        Some(state) if state as u8 == 1 => {
+            Starting::restore(snapshot).map(|starting| Box::new(starting) as Box<dyn Turbine>)
+        }
+        Some(state) if state as u8 == 2 => {
+            Running::restore(snapshot).map(|running| Box::new(running) as Box<dyn Turbine>)
+        }
+        Some(state) if state as u8 == 3 => {
+            Stopping::restore(snapshot).map(|stopping| Box::new(stopping) as Box<dyn Turbine>)
+        }
+        Some(_) => restore_egt(snapshot)
+            .map(|egt| Box::new(ShutdownAps3200Turbine::new_with_egt(egt)) as Box<dyn Turbine>),
+        None => None,
+    };
+
+    restored.unwrap_or(turbine)
+}
+
+fn save_state(snapshot: &mut Snapshot, state: TurbineState) {
+    snapshot.save_f64(
+        STATE_ID,
+        match state {
+            TurbineState::Shutdown => 0.,
+            TurbineState::Starting => 1.,
+            TurbineState::Running => 2.,
+            TurbineState::Stopping => 3.,
+        },
+    );
+}
+
+fn restore_egt(snapshot: &Snapshot) -> Option<ThermodynamicTemperature> {
+    snapshot
+        .restore_f64(EGT_ID)
+        .map(ThermodynamicTemperature::new::<degree_celsius>)
+}
+
+fn restore_n(snapshot: &Snapshot) -> Option<Ratio> {
+    snapshot.restore_f64(N_ID).map(Ratio::new::<percent>)
+}
+
 pub struct ShutdownAps3200Turbine {
     egt: ThermodynamicTemperature,
 }
@@ -56,6 +107,15 @@ impl Turbine for ShutdownAps3200Turbine {
     fn state(&self) -> TurbineState {
         TurbineState::Shutdown
     }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        save_state(snapshot, self.state());
+        snapshot.save_f64(EGT_ID, self.egt.get::<degree_celsius>());
+    }
+
+    fn restore(self: Box<Self>, snapshot: &Snapshot) -> Box<dyn Turbine> {
+        restore_turbine(self, snapshot)
+    }
 }
 
 struct Starting {
@@ -74,6 +134,15 @@ impl Starting {
         }
     }
 
+    fn restore(snapshot: &Snapshot) -> Option<Starting> {
+        Some(Starting {
+            since: snapshot.restore_duration(SINCE_ID)?,
+            n: restore_n(snapshot)?,
+            egt: restore_egt(snapshot)?,
+            ignore_calculated_egt: snapshot.restore_bool("APU_TURBINE_IGNORE_CALCULATED_EGT")?,
+        })
+    }
+
     fn calculate_egt(&mut self, context: &UpdateContext) -> ThermodynamicTemperature {
         // Refer to APS3200.md for details on the values below and source data.
         const APU_N_TEMP_CONST: f64 = -92.3417137705543;
@@ -187,7 +256,7 @@ impl Turbine for Starting {
         if controller.should_stop() {
             Box::new(Stopping::new(self.egt, self.n))
         } else if (self.n.get::<percent>() - 100.).abs() < f64::EPSILON {
-            Box::new(Running::new(self.egt))
+            Box::new(Running::new(context, self.egt))
         } else {
             self
         }
@@ -204,6 +273,21 @@ impl Turbine for Starting {
     fn state(&self) -> TurbineState {
         TurbineState::Starting
     }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        save_state(snapshot, self.state());
+        snapshot.save_duration(SINCE_ID, self.since);
+        snapshot.save_f64(N_ID, self.n.get::<percent>());
+        snapshot.save_f64(EGT_ID, self.egt.get::<degree_celsius>());
+        snapshot.save_bool(
+            "APU_TURBINE_IGNORE_CALCULATED_EGT",
+            self.ignore_calculated_egt,
+        );
+    }
+
+    fn restore(self: Box<Self>, snapshot: &Snapshot) -> Box<dyn Turbine> {
+        restore_turbine(self, snapshot)
+    }
 }
 
 struct BleedAirUsageEgtDelta {
@@ -213,8 +297,8 @@ struct BleedAirUsageEgtDelta {
     min: f64,
 }
 impl BleedAirUsageEgtDelta {
-    fn new() -> Self {
-        let randomisation = 0.95 + ((random_number() % 101) as f64 / 1000.);
+    fn new(context: &UpdateContext) -> Self {
+        let randomisation = 0.95 + ((context.random_number() % 101) as f64 / 1000.);
 
         Self {
             current: 0.,
@@ -242,6 +326,21 @@ impl BleedAirUsageEgtDelta {
         self.current = self.current.max(self.min).min(self.max);
     }
 
+    fn save(&self, snapshot: &mut Snapshot) {
+        snapshot.save_f64("APU_TURBINE_BLEED_AIR_USAGE_CURRENT", self.current);
+        snapshot.save_f64("APU_TURBINE_BLEED_AIR_USAGE_TARGET", self.target);
+        snapshot.save_f64("APU_TURBINE_BLEED_AIR_USAGE_MAX", self.max);
+    }
+
+    fn restore(snapshot: &Snapshot) -> Option<Self> {
+        Some(Self {
+            current: snapshot.restore_f64("APU_TURBINE_BLEED_AIR_USAGE_CURRENT")?,
+            target: snapshot.restore_f64("APU_TURBINE_BLEED_AIR_USAGE_TARGET")?,
+            max: snapshot.restore_f64("APU_TURBINE_BLEED_AIR_USAGE_MAX")?,
+            min: 0.,
+        })
+    }
+
     fn egt_delta(&self) -> TemperatureInterval {
         TemperatureInterval::new::<temperature_interval::degree_celsius>(self.current)
     }
@@ -285,10 +384,10 @@ struct ApuGenUsageEgtDelta {
 impl ApuGenUsageEgtDelta {
     // We just assume it takes 10 seconds to get to our target.
     const SECONDS_TO_REACH_TARGET: u64 = 10;
-    fn new() -> Self {
+    fn new(context: &UpdateContext) -> Self {
         Self {
             time: Duration::from_secs(0),
-            base_egt_delta_per_second: (10. + ((random_number() % 6) as f64))
+            base_egt_delta_per_second: (10. + ((context.random_number() % 6) as f64))
                 / ApuGenUsageEgtDelta::SECONDS_TO_REACH_TARGET as f64,
         }
     }
@@ -305,6 +404,22 @@ impl ApuGenUsageEgtDelta {
         };
     }
 
+    fn save(&self, snapshot: &mut Snapshot) {
+        snapshot.save_duration("APU_TURBINE_GEN_USAGE_TIME", self.time);
+        snapshot.save_f64(
+            "APU_TURBINE_GEN_USAGE_BASE_EGT_DELTA_PER_SECOND",
+            self.base_egt_delta_per_second,
+        );
+    }
+
+    fn restore(snapshot: &Snapshot) -> Option<Self> {
+        Some(Self {
+            time: snapshot.restore_duration("APU_TURBINE_GEN_USAGE_TIME")?,
+            base_egt_delta_per_second: snapshot
+                .restore_f64("APU_TURBINE_GEN_USAGE_BASE_EGT_DELTA_PER_SECOND")?,
+        })
+    }
+
     fn egt_delta(&self) -> TemperatureInterval {
         TemperatureInterval::new::<temperature_interval::degree_celsius>(
             self.time.as_secs_f64() * self.base_egt_delta_per_second,
@@ -320,8 +435,8 @@ struct Running {
     apu_gen_usage: ApuGenUsageEgtDelta,
 }
 impl Running {
-    fn new(egt: ThermodynamicTemperature) -> Running {
-        let base_egt = 340. + ((random_number() % 11) as f64);
+    fn new(context: &UpdateContext, egt: ThermodynamicTemperature) -> Running {
+        let base_egt = 340. + ((context.random_number() % 11) as f64);
         Running {
             egt,
             base_egt: ThermodynamicTemperature::new::<degree_celsius>(base_egt),
@@ -331,11 +446,25 @@ impl Running {
             base_egt_deviation: TemperatureInterval::new::<temperature_interval::degree_celsius>(
                 egt.get::<degree_celsius>() - base_egt,
             ),
-            bleed_air_usage: BleedAirUsageEgtDelta::new(),
-            apu_gen_usage: ApuGenUsageEgtDelta::new(),
+            bleed_air_usage: BleedAirUsageEgtDelta::new(context),
+            apu_gen_usage: ApuGenUsageEgtDelta::new(context),
         }
     }
 
+    fn restore(snapshot: &Snapshot) -> Option<Running> {
+        Some(Running {
+            egt: restore_egt(snapshot)?,
+            base_egt: ThermodynamicTemperature::new::<degree_celsius>(
+                snapshot.restore_f64("APU_TURBINE_BASE_EGT")?,
+            ),
+            base_egt_deviation: TemperatureInterval::new::<temperature_interval::degree_celsius>(
+                snapshot.restore_f64("APU_TURBINE_BASE_EGT_DEVIATION")?,
+            ),
+            bleed_air_usage: BleedAirUsageEgtDelta::restore(snapshot)?,
+            apu_gen_usage: ApuGenUsageEgtDelta::restore(snapshot)?,
+        })
+    }
+
     fn calculate_egt(
         &mut self,
         context: &UpdateContext,
@@ -388,6 +517,26 @@ impl Turbine for Running {
     fn state(&self) -> TurbineState {
         TurbineState::Running
     }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        save_state(snapshot, self.state());
+        snapshot.save_f64(EGT_ID, self.egt.get::<degree_celsius>());
+        snapshot.save_f64(
+            "APU_TURBINE_BASE_EGT",
+            self.base_egt.get::<degree_celsius>(),
+        );
+        snapshot.save_f64(
+            "APU_TURBINE_BASE_EGT_DEVIATION",
+            self.base_egt_deviation
+                .get::<temperature_interval::degree_celsius>(),
+        );
+        self.bleed_air_usage.save(snapshot);
+        self.apu_gen_usage.save(snapshot);
+    }
+
+    fn restore(self: Box<Self>, snapshot: &Snapshot) -> Box<dyn Turbine> {
+        restore_turbine(self, snapshot)
+    }
 }
 
 struct Stopping {
@@ -418,6 +567,21 @@ impl Stopping {
         }
     }
 
+    fn restore(snapshot: &Snapshot) -> Option<Stopping> {
+        Some(Stopping {
+            since: snapshot.restore_duration(SINCE_ID)?,
+            base_temperature: ThermodynamicTemperature::new::<degree_celsius>(
+                snapshot.restore_f64("APU_TURBINE_BASE_TEMPERATURE")?,
+            ),
+            n_factor: snapshot.restore_f64("APU_TURBINE_N_FACTOR")?,
+            egt_delta_at_entry: TemperatureInterval::new::<temperature_interval::degree_celsius>(
+                snapshot.restore_f64("APU_TURBINE_EGT_DELTA_AT_ENTRY")?,
+            ),
+            n: restore_n(snapshot)?,
+            egt: restore_egt(snapshot)?,
+        })
+    }
+
     fn calculate_egt_delta(n: Ratio) -> TemperatureInterval {
         // Refer to APS3200.md for details on the values below and source data.
         const APU_N_TEMP_DELTA_CONST: f64 = -125.73137672208446;
@@ -520,6 +684,27 @@ impl Turbine for Stopping {
     fn state(&self) -> TurbineState {
         TurbineState::Stopping
     }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        save_state(snapshot, self.state());
+        snapshot.save_duration(SINCE_ID, self.since);
+        snapshot.save_f64(
+            "APU_TURBINE_BASE_TEMPERATURE",
+            self.base_temperature.get::<degree_celsius>(),
+        );
+        snapshot.save_f64("APU_TURBINE_N_FACTOR", self.n_factor);
+        snapshot.save_f64(
+            "APU_TURBINE_EGT_DELTA_AT_ENTRY",
+            self.egt_delta_at_entry
+                .get::<temperature_interval::degree_celsius>(),
+        );
+        snapshot.save_f64(N_ID, self.n.get::<percent>());
+        snapshot.save_f64(EGT_ID, self.egt.get::<degree_celsius>());
+    }
+
+    fn restore(self: Box<Self>, snapshot: &Snapshot) -> Box<dyn Turbine> {
+        restore_turbine(self, snapshot)
+    }
 }
 
 fn calculate_towards_ambient_egt(
@@ -544,19 +729,21 @@ pub struct Aps3200ApuGenerator {
     output_potential: ElectricPotential,
     load: Ratio,
     is_emergency_shutdown: bool,
+    failure: Failure,
 }
 impl Aps3200ApuGenerator {
     const APU_GEN_POWERED_N: f64 = 84.;
 
-    pub fn new(number: usize) -> Aps3200ApuGenerator {
+    pub fn new(registry: &mut VariableRegistry, number: usize) -> Aps3200ApuGenerator {
         Aps3200ApuGenerator {
             number,
             n: Ratio::new::<percent>(0.),
-            writer: ElectricalStateWriter::new(&format!("APU_GEN_{}", number)),
+            writer: ElectricalStateWriter::new(registry, &format!("APU_GEN_{}", number)),
             output_potential: ElectricPotential::new::<volt>(0.),
             output_frequency: Frequency::new::<hertz>(0.),
             load: Ratio::new::<percent>(0.),
             is_emergency_shutdown: false,
+            failure: Failure::new(registry, FailureType::ApuGenerator(number)),
         }
     }
 
@@ -617,6 +804,7 @@ impl Aps3200ApuGenerator {
 
     fn should_provide_output(&self) -> bool {
         !self.is_emergency_shutdown
+            && !self.failure.is_active()
             && self.n.get::<percent>() >= Aps3200ApuGenerator::APU_GEN_POWERED_N
     }
 }
@@ -652,6 +840,12 @@ impl PotentialSource for Aps3200ApuGenerator {
     }
 }
 impl SimulationElement for Aps3200ApuGenerator {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.failure.accept(visitor);
+
+        visitor.visit(self);
+    }
+
     fn write(&self, writer: &mut SimulatorWriter) {
         self.writer.write_alternating_with_load(self, writer);
     }
@@ -774,6 +968,17 @@ mod apu_generator_tests {
         assert!(generator.is_unpowered());
     }
 
+    #[test]
+    fn when_apu_running_but_failed_provides_no_output() {
+        let mut generator = apu_generator();
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.activate_failure(FailureType::ApuGenerator(1));
+        update_below_threshold(&mut test_bed, &mut generator);
+        update_above_threshold(&mut test_bed, &mut generator);
+
+        assert!(generator.is_unpowered());
+    }
+
     #[test]
     fn from_n_84_provides_voltage() {
         let mut test_bed = test_bed_with().starting_apu();
@@ -939,7 +1144,7 @@ mod apu_generator_tests {
     }
 
     fn apu_generator() -> Aps3200ApuGenerator {
-        Aps3200ApuGenerator::new(1)
+        Aps3200ApuGenerator::new(&mut VariableRegistry::new(), 1)
     }
 
     fn update_above_threshold(
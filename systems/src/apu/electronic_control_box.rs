@@ -1,12 +1,13 @@
 use super::{
     AirIntakeFlap, AirIntakeFlapController, AuxiliaryPowerUnitFireOverheadPanel,
-    AuxiliaryPowerUnitOverheadPanel, FuelPressureSwitch, Turbine, TurbineController, TurbineState,
+    AuxiliaryPowerUnitOverheadPanel, Turbine, TurbineController, TurbineState,
 };
 use crate::{
     electrical::PotentialSource,
+    fuel::FuelPressureSwitch,
     pneumatic::{BleedAirValveController, Valve},
     shared::ApuStartContactorsController,
-    simulation::UpdateContext,
+    simulation::{Snapshot, UpdateContext},
 };
 use std::time::Duration;
 use uom::si::{f64::*, length::foot, ratio::percent, thermodynamic_temperature::degree_celsius};
@@ -100,6 +101,57 @@ impl ElectronicControlBox {
         }
     }
 
+    pub fn save(&self, snapshot: &mut Snapshot) {
+        snapshot.save_f64(
+            "APU_ECB_FAULT",
+            match self.fault {
+                None => 0.,
+                Some(ApuFault::ApuFire) => 1.,
+                Some(ApuFault::FuelLowPressure) => 2.,
+                Some(ApuFault::DcPowerLoss) => 3.,
+            },
+        );
+        snapshot.save_duration(
+            "APU_ECB_BLEED_AIR_VALVE_LAST_OPEN_TIME_AGO",
+            self.bleed_air_valve_last_open_time_ago,
+        );
+        snapshot.save_duration("APU_ECB_N_ABOVE_95_DURATION", self.n_above_95_duration);
+        snapshot.save_f64(
+            "APU_ECB_EGT_WARNING_TEMPERATURE",
+            self.egt_warning_temperature.get::<degree_celsius>(),
+        );
+    }
+
+    /// Restores the state saved by [`save`], taking the turbine's state
+    /// from the already restored turbine.
+    ///
+    /// [`save`]: #method.save
+    pub fn restore(&mut self, snapshot: &Snapshot, turbine: &dyn Turbine) {
+        if let Some(fault) = snapshot.restore_f64("APU_ECB_FAULT") {
+            self.fault = match fault as u8 {
+                1 => Some(ApuFault::ApuFire),
+                2 => Some(ApuFault::FuelLowPressure),
+                3 => Some(ApuFault::DcPowerLoss),
+                _ => None,
+            };
+        }
+        if let Some(time_ago) =
+            snapshot.restore_duration("APU_ECB_BLEED_AIR_VALVE_LAST_OPEN_TIME_AGO")
+        {
+            self.bleed_air_valve_last_open_time_ago = time_ago;
+        }
+        if let Some(duration) = snapshot.restore_duration("APU_ECB_N_ABOVE_95_DURATION") {
+            self.n_above_95_duration = duration;
+        }
+        if let Some(temperature) = snapshot.restore_f64("APU_ECB_EGT_WARNING_TEMPERATURE") {
+            self.egt_warning_temperature =
+                ThermodynamicTemperature::new::<degree_celsius>(temperature);
+        }
+        self.n = turbine.n();
+        self.egt = turbine.egt();
+        self.turbine_state = turbine.state();
+    }
+
     pub fn update_bleed_air_valve_state<T: Valve>(
         &mut self,
         context: &UpdateContext,
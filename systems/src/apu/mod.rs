@@ -4,14 +4,21 @@ use self::{
 };
 use crate::{
     electrical::{Potential, PotentialSource, PotentialTarget, ProvideFrequency, ProvidePotential},
+    fuel::FuelPressureSwitch,
     overhead::{FirePushButton, OnOffAvailablePushButton, OnOffFaultPushButton},
-    pneumatic::{BleedAirValve, BleedAirValveState, Valve},
+    pneumatic::{BleedAirSource, BleedAirValve, BleedAirValveState, Valve},
     shared::{ApuStartContactorsController, AuxiliaryPowerUnitElectrical},
-    simulation::{SimulationElement, SimulationElementVisitor, SimulatorWriter, UpdateContext},
+    simulation::{
+        QuantityIdentifier, SimulationElement, SimulationElementVisitor, SimulatorWriter, Snapshot,
+        UpdateContext, VariableRegistry, VariableUnit,
+    },
 };
 #[cfg(test)]
 use std::time::Duration;
-use uom::si::{f64::*, ratio::percent, thermodynamic_temperature::degree_celsius};
+use uom::si::{
+    f64::*, mass_rate::kilogram_per_second, pressure::psi,
+    thermodynamic_temperature::degree_celsius,
+};
 
 mod air_intake_flap;
 mod aps3200;
@@ -21,11 +28,14 @@ mod electronic_control_box;
 pub struct AuxiliaryPowerUnitFactory {}
 impl AuxiliaryPowerUnitFactory {
     pub fn new_aps3200(
+        registry: &mut VariableRegistry,
         number: usize,
     ) -> AuxiliaryPowerUnit<Aps3200ApuGenerator, Aps3200StartMotor> {
+        let generator = Aps3200ApuGenerator::new(registry, number);
         AuxiliaryPowerUnit::new(
+            registry,
             Box::new(ShutdownAps3200Turbine::new()),
-            Aps3200ApuGenerator::new(number),
+            generator,
             Aps3200StartMotor::new(),
         )
     }
@@ -33,30 +43,6 @@ impl AuxiliaryPowerUnitFactory {
 
 pub trait ApuStartMotor: PotentialTarget + PotentialSource + SimulationElement {}
 
-/// Komp: There is a pressure switch between the fuel valve and the APU.
-/// It switches from 0 to 1 when the pressure is >=17 PSI and the signal is received by the ECB
-/// And there is a small hysteresis, means it switches back to 0 when <=16 PSI
-/// This type exists because we don't have a full fuel implementation yet. Once we do, we will
-/// probably move this type and the logic to there.
-pub struct FuelPressureSwitch {
-    has_fuel_remaining: bool,
-}
-impl FuelPressureSwitch {
-    fn new() -> Self {
-        FuelPressureSwitch {
-            has_fuel_remaining: false,
-        }
-    }
-
-    fn update(&mut self, has_fuel_remaining: bool) {
-        self.has_fuel_remaining = has_fuel_remaining;
-    }
-
-    fn has_pressure(&self) -> bool {
-        self.has_fuel_remaining
-    }
-}
-
 /// Signals to the APU air intake flap what position it should move towards.
 pub trait AirIntakeFlapController {
     fn should_open_air_intake_flap(&self) -> bool;
@@ -69,23 +55,63 @@ pub trait TurbineController {
 }
 
 pub struct AuxiliaryPowerUnit<T: ApuGenerator, U: ApuStartMotor> {
+    flap_open_percentage_id: QuantityIdentifier<Ratio>,
+    bleed_air_valve_open_id: QuantityIdentifier<bool>,
+    egt_caution_id: QuantityIdentifier<ThermodynamicTemperature>,
+    egt_id: QuantityIdentifier<ThermodynamicTemperature>,
+    inoperable_id: QuantityIdentifier<bool>,
+    is_auto_shutdown_id: QuantityIdentifier<bool>,
+    is_emergency_shutdown_id: QuantityIdentifier<bool>,
+    low_fuel_pressure_fault_id: QuantityIdentifier<bool>,
+    n_id: QuantityIdentifier<Ratio>,
+    egt_warning_id: QuantityIdentifier<ThermodynamicTemperature>,
+
     turbine: Option<Box<dyn Turbine>>,
     generator: T,
     ecb: ElectronicControlBox,
     start_motor: U,
     air_intake_flap: AirIntakeFlap,
     bleed_air_valve: BleedAirValve,
+    bleed_air_supplied_flow: MassRate,
     fuel_pressure_switch: FuelPressureSwitch,
 }
 impl<T: ApuGenerator, U: ApuStartMotor> AuxiliaryPowerUnit<T, U> {
-    pub fn new(turbine: Box<dyn Turbine>, generator: T, start_motor: U) -> Self {
+    const BLEED_AIR_SUPPLY_PRESSURE_PSI: f64 = 40.;
+    const BLEED_AIR_SUPPLY_TEMPERATURE_DEGREE_CELSIUS: f64 = 200.;
+    const BLEED_AIR_MAX_FLOW_KILOGRAM_PER_SECOND: f64 = 1.3;
+    const FUEL_PRESSURE_SWITCH_SNAPSHOT_NAME: &'static str =
+        "APU_FUEL_PRESSURE_SWITCH_HAS_PRESSURE";
+
+    pub fn new(
+        registry: &mut VariableRegistry,
+        turbine: Box<dyn Turbine>,
+        generator: T,
+        start_motor: U,
+    ) -> Self {
         AuxiliaryPowerUnit {
+            flap_open_percentage_id: registry
+                .register_write("APU_FLAP_OPEN_PERCENTAGE", VariableUnit::Percent),
+            bleed_air_valve_open_id: registry
+                .register_write("APU_BLEED_AIR_VALVE_OPEN", VariableUnit::Bool),
+            egt_caution_id: registry.register_write("APU_EGT_CAUTION", VariableUnit::Celsius),
+            egt_id: registry.register_write("APU_EGT", VariableUnit::Celsius),
+            inoperable_id: registry.register_write("ECAM_INOP_SYS_APU", VariableUnit::Bool),
+            is_auto_shutdown_id: registry
+                .register_write("APU_IS_AUTO_SHUTDOWN", VariableUnit::Bool),
+            is_emergency_shutdown_id: registry
+                .register_write("APU_IS_EMERGENCY_SHUTDOWN", VariableUnit::Bool),
+            low_fuel_pressure_fault_id: registry
+                .register_write("APU_LOW_FUEL_PRESSURE_FAULT", VariableUnit::Bool),
+            n_id: registry.register_write("APU_N", VariableUnit::Percent),
+            egt_warning_id: registry.register_write("APU_EGT_WARNING", VariableUnit::Celsius),
+
             turbine: Some(turbine),
             generator,
             ecb: ElectronicControlBox::new(),
             start_motor,
             air_intake_flap: AirIntakeFlap::new(),
             bleed_air_valve: BleedAirValve::new(),
+            bleed_air_supplied_flow: MassRate::new::<kilogram_per_second>(0.),
             fuel_pressure_switch: FuelPressureSwitch::new(),
         }
     }
@@ -97,11 +123,11 @@ impl<T: ApuGenerator, U: ApuStartMotor> AuxiliaryPowerUnit<T, U> {
         fire_overhead: &AuxiliaryPowerUnitFireOverheadPanel,
         apu_bleed_is_on: bool,
         apu_gen_is_used: bool,
-        has_fuel_remaining: bool,
+        fuel_pressure: Pressure,
     ) {
         self.ecb
             .update_overhead_panel_state(overhead, fire_overhead, apu_bleed_is_on);
-        self.fuel_pressure_switch.update(has_fuel_remaining);
+        self.fuel_pressure_switch.update(fuel_pressure);
         self.ecb
             .update_fuel_pressure_switch_state(&self.fuel_pressure_switch);
         self.bleed_air_valve.update(&self.ecb);
@@ -113,7 +139,7 @@ impl<T: ApuGenerator, U: ApuStartMotor> AuxiliaryPowerUnit<T, U> {
         if let Some(turbine) = self.turbine.take() {
             let mut updated_turbine = turbine.update(
                 context,
-                self.bleed_air_valve.is_open(),
+                self.bleed_air_is_used(),
                 apu_gen_is_used,
                 &self.ecb,
             );
@@ -127,6 +153,12 @@ impl<T: ApuGenerator, U: ApuStartMotor> AuxiliaryPowerUnit<T, U> {
             .update(self.n(), self.is_emergency_shutdown());
     }
 
+    /// The bleed air is only used when consumers take air from the APU.
+    fn bleed_air_is_used(&self) -> bool {
+        self.bleed_air_valve.is_open()
+            && self.bleed_air_supplied_flow > MassRate::new::<kilogram_per_second>(0.)
+    }
+
     pub fn update_after_electrical(&mut self) {
         self.ecb.update_start_motor_state(&self.start_motor);
     }
@@ -143,6 +175,11 @@ impl<T: ApuGenerator, U: ApuStartMotor> AuxiliaryPowerUnit<T, U> {
         self.ecb.has_fault()
     }
 
+    /// Indicates if the APU shut down automatically because of a fault other than a fire.
+    pub fn is_auto_shutdown(&self) -> bool {
+        self.ecb.is_auto_shutdown()
+    }
+
     fn is_emergency_shutdown(&self) -> bool {
         self.ecb.is_emergency_shutdown()
     }
@@ -192,28 +229,46 @@ impl<T: ApuGenerator, U: ApuStartMotor> SimulationElement for AuxiliaryPowerUnit
     }
 
     fn write(&self, writer: &mut SimulatorWriter) {
-        writer.write_f64(
-            "APU_FLAP_OPEN_PERCENTAGE",
-            self.air_intake_flap.open_amount().get::<percent>(),
+        writer.write_ratio(
+            &self.flap_open_percentage_id,
+            self.air_intake_flap.open_amount(),
         );
-        writer.write_bool("APU_BLEED_AIR_VALVE_OPEN", self.bleed_air_valve_is_open());
-        writer.write_f64(
-            "APU_EGT_CAUTION",
-            self.ecb.egt_caution_temperature().get::<degree_celsius>(),
+        writer.write_bool(
+            &self.bleed_air_valve_open_id,
+            self.bleed_air_valve_is_open(),
         );
-        writer.write_f64("APU_EGT", self.ecb.egt().get::<degree_celsius>());
-        writer.write_bool("ECAM_INOP_SYS_APU", self.ecb.is_inoperable());
-        writer.write_bool("APU_IS_AUTO_SHUTDOWN", self.ecb.is_auto_shutdown());
-        writer.write_bool("APU_IS_EMERGENCY_SHUTDOWN", self.is_emergency_shutdown());
+        writer.write_temperature(&self.egt_caution_id, self.ecb.egt_caution_temperature());
+        writer.write_temperature(&self.egt_id, self.ecb.egt());
+        writer.write_bool(&self.inoperable_id, self.ecb.is_inoperable());
+        writer.write_bool(&self.is_auto_shutdown_id, self.is_auto_shutdown());
+        writer.write_bool(&self.is_emergency_shutdown_id, self.is_emergency_shutdown());
         writer.write_bool(
-            "APU_LOW_FUEL_PRESSURE_FAULT",
+            &self.low_fuel_pressure_fault_id,
             self.ecb.has_fuel_low_pressure_fault(),
         );
-        writer.write_f64("APU_N", self.n().get::<percent>());
-        writer.write_f64(
-            "APU_EGT_WARNING",
-            self.ecb.egt_warning_temperature().get::<degree_celsius>(),
-        );
+        writer.write_ratio(&self.n_id, self.n());
+        writer.write_temperature(&self.egt_warning_id, self.ecb.egt_warning_temperature());
+    }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        if let Some(turbine) = &self.turbine {
+            turbine.save(snapshot);
+        }
+        self.ecb.save(snapshot);
+        self.air_intake_flap.save(snapshot);
+        self.fuel_pressure_switch
+            .save(snapshot, Self::FUEL_PRESSURE_SWITCH_SNAPSHOT_NAME);
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        if let Some(turbine) = self.turbine.take() {
+            let restored_turbine = turbine.restore(snapshot);
+            self.ecb.restore(snapshot, restored_turbine.as_ref());
+            self.turbine = Some(restored_turbine);
+        }
+        self.air_intake_flap.restore(snapshot);
+        self.fuel_pressure_switch
+            .restore(snapshot, Self::FUEL_PRESSURE_SWITCH_SNAPSHOT_NAME);
     }
 }
 impl<T: ApuGenerator, U: ApuStartMotor> BleedAirValveState for AuxiliaryPowerUnit<T, U> {
@@ -221,6 +276,33 @@ impl<T: ApuGenerator, U: ApuStartMotor> BleedAirValveState for AuxiliaryPowerUni
         self.bleed_air_valve.is_open()
     }
 }
+impl<T: ApuGenerator, U: ApuStartMotor> BleedAirSource for AuxiliaryPowerUnit<T, U> {
+    fn supply_pressure(&self) -> Pressure {
+        Pressure::new::<psi>(if self.bleed_air_valve.is_open() {
+            AuxiliaryPowerUnit::<T, U>::BLEED_AIR_SUPPLY_PRESSURE_PSI
+        } else {
+            0.
+        })
+    }
+
+    fn supply_temperature(&self) -> ThermodynamicTemperature {
+        ThermodynamicTemperature::new::<degree_celsius>(
+            AuxiliaryPowerUnit::<T, U>::BLEED_AIR_SUPPLY_TEMPERATURE_DEGREE_CELSIUS,
+        )
+    }
+
+    fn max_flow(&self) -> MassRate {
+        MassRate::new::<kilogram_per_second>(if self.bleed_air_valve.is_open() {
+            AuxiliaryPowerUnit::<T, U>::BLEED_AIR_MAX_FLOW_KILOGRAM_PER_SECOND
+        } else {
+            0.
+        })
+    }
+
+    fn set_supplied_flow(&mut self, flow: MassRate) {
+        self.bleed_air_supplied_flow = flow;
+    }
+}
 
 pub trait Turbine {
     fn update(
@@ -233,6 +315,8 @@ pub trait Turbine {
     fn n(&self) -> Ratio;
     fn egt(&self) -> ThermodynamicTemperature;
     fn state(&self) -> TurbineState;
+    fn save(&self, snapshot: &mut Snapshot);
+    fn restore(self: Box<Self>, snapshot: &Snapshot) -> Box<dyn Turbine>;
 }
 
 #[derive(PartialEq)]
@@ -254,9 +338,9 @@ pub struct AuxiliaryPowerUnitFireOverheadPanel {
     apu_fire_button: FirePushButton,
 }
 impl AuxiliaryPowerUnitFireOverheadPanel {
-    pub fn new() -> Self {
+    pub fn new(registry: &mut VariableRegistry) -> Self {
         AuxiliaryPowerUnitFireOverheadPanel {
-            apu_fire_button: FirePushButton::new("APU"),
+            apu_fire_button: FirePushButton::new(registry, "APU"),
         }
     }
 
@@ -271,21 +355,16 @@ impl SimulationElement for AuxiliaryPowerUnitFireOverheadPanel {
         visitor.visit(self);
     }
 }
-impl Default for AuxiliaryPowerUnitFireOverheadPanel {
-    fn default() -> Self {
-        Self::new()
-    }
-}
 
 pub struct AuxiliaryPowerUnitOverheadPanel {
     pub master: OnOffFaultPushButton,
     pub start: OnOffAvailablePushButton,
 }
 impl AuxiliaryPowerUnitOverheadPanel {
-    pub fn new() -> AuxiliaryPowerUnitOverheadPanel {
+    pub fn new(registry: &mut VariableRegistry) -> AuxiliaryPowerUnitOverheadPanel {
         AuxiliaryPowerUnitOverheadPanel {
-            master: OnOffFaultPushButton::new_off("APU_MASTER_SW"),
-            start: OnOffAvailablePushButton::new_off("APU_START"),
+            master: OnOffFaultPushButton::new_off(registry, "APU_MASTER_SW"),
+            start: OnOffAvailablePushButton::new_off(registry, "APU_START"),
         }
     }
 
@@ -321,11 +400,6 @@ impl SimulationElement for AuxiliaryPowerUnitOverheadPanel {
         visitor.visit(self);
     }
 }
-impl Default for AuxiliaryPowerUnitOverheadPanel {
-    fn default() -> Self {
-        Self::new()
-    }
-}
 
 #[cfg(test)]
 pub mod tests {
@@ -340,8 +414,8 @@ pub mod tests {
     use super::*;
     use std::time::Duration;
     use uom::si::{
-        electric_potential::volt, frequency::hertz, length::foot, power::watt, ratio::percent,
-        thermodynamic_temperature::degree_celsius,
+        electric_potential::volt, frequency::hertz, length::foot, power::watt, pressure::psi,
+        ratio::percent, thermodynamic_temperature::degree_celsius,
     };
 
     pub fn test_bed_with() -> AuxiliaryPowerUnitTestBed {
@@ -382,6 +456,12 @@ pub mod tests {
         fn state(&self) -> TurbineState {
             TurbineState::Starting
         }
+
+        fn save(&self, _: &mut Snapshot) {}
+
+        fn restore(self: Box<Self>, _: &Snapshot) -> Box<dyn Turbine> {
+            self
+        }
     }
 
     struct AuxiliaryPowerUnitTestAircraft {
@@ -397,11 +477,13 @@ pub mod tests {
     }
     impl AuxiliaryPowerUnitTestAircraft {
         fn new() -> Self {
+            let mut registry = VariableRegistry::new();
+
             Self {
-                apu: AuxiliaryPowerUnitFactory::new_aps3200(1),
-                apu_fire_overhead: AuxiliaryPowerUnitFireOverheadPanel::new(),
-                apu_overhead: AuxiliaryPowerUnitOverheadPanel::new(),
-                apu_bleed: OnOffFaultPushButton::new_on("APU_BLEED"),
+                apu: AuxiliaryPowerUnitFactory::new_aps3200(&mut registry, 1),
+                apu_fire_overhead: AuxiliaryPowerUnitFireOverheadPanel::new(&mut registry),
+                apu_overhead: AuxiliaryPowerUnitOverheadPanel::new(&mut registry),
+                apu_bleed: OnOffFaultPushButton::new_on(&mut registry, "APU_BLEED"),
                 apu_gen_is_used: true,
                 has_fuel_remaining: true,
                 power_consumer: PowerConsumer::from(ElectricalBusType::AlternatingCurrent(1)),
@@ -459,9 +541,13 @@ pub mod tests {
                 &self.apu_fire_overhead,
                 self.apu_bleed.is_on(),
                 self.apu_gen_is_used,
-                self.has_fuel_remaining,
+                Pressure::new::<psi>(if self.has_fuel_remaining { 30. } else { 0. }),
             );
 
+            // Simulate consumers taking all the bleed air the APU can supply.
+            let bleed_air_flow = self.apu.max_flow();
+            self.apu.set_supplied_flow(bleed_air_flow);
+
             self.apu.start_motor_powered_by(
                 if self.apu.should_close_start_contactors() && !self.cut_start_motor_power {
                     Potential::single(
@@ -682,6 +768,14 @@ pub mod tests {
             self
         }
 
+        fn restored_into_new_apu(mut self) -> Self {
+            let snapshot = Snapshot::take(&mut self.aircraft);
+            self.aircraft = AuxiliaryPowerUnitTestAircraft::new();
+            snapshot.restore(&mut self.aircraft);
+
+            self
+        }
+
         pub fn and(self) -> Self {
             self
         }
@@ -1730,5 +1824,48 @@ pub mod tests {
 
             assert!(maximum_power < Power::new::<watt>(10000.));
         }
+
+        #[test]
+        fn running_apu_remains_available_when_restored_from_snapshot() {
+            let mut test_bed = test_bed_with().running_apu();
+            let egt = test_bed.egt();
+
+            test_bed = test_bed
+                .restored_into_new_apu()
+                .run(Duration::from_millis(1));
+
+            assert!(test_bed.apu_is_available());
+            assert_eq!(test_bed.n().get::<percent>(), 100.);
+            assert_about_eq!(
+                test_bed.egt().get::<degree_celsius>(),
+                egt.get::<degree_celsius>(),
+                1.
+            );
+            assert!(test_bed.is_air_intake_flap_fully_open());
+        }
+
+        #[test]
+        fn starting_apu_continues_starting_when_restored_from_snapshot() {
+            let mut test_bed = test_bed_with().starting_apu().run(Duration::from_secs(10));
+            let n = test_bed.n();
+
+            test_bed = test_bed.restored_into_new_apu().run(Duration::from_secs(1));
+
+            assert!(test_bed.n() > n);
+            assert!(!test_bed.apu_is_available());
+        }
+
+        #[test]
+        fn apu_fault_is_restored_from_snapshot() {
+            let mut test_bed = test_bed_with()
+                .starting_apu()
+                .and()
+                .no_fuel_available()
+                .run_until_n_decreases(Duration::from_millis(50))
+                .restored_into_new_apu()
+                .run(Duration::from_millis(1));
+
+            assert!(test_bed.has_fuel_low_pressure_fault());
+        }
     }
 }
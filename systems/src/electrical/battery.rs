@@ -3,7 +3,10 @@ use super::{
     ElectricalStateWriter, Potential, PotentialOrigin, PotentialSource, PotentialTarget,
     ProvideCurrent, ProvidePotential,
 };
-use crate::simulation::{SimulationElement, SimulatorWriter};
+use crate::simulation::{
+    Failure, FailureType, SimulationElement, SimulationElementVisitor, SimulatorWriter, Snapshot,
+    VariableRegistry,
+};
 use uom::si::{
     electric_charge::ampere_hour, electric_current::ampere, electric_potential::volt,
     electrical_resistance::ohm, f64::*, time::second,
@@ -12,40 +15,46 @@ use uom::si::{
 pub struct Battery {
     number: usize,
     writer: ElectricalStateWriter,
+    charge_id: String,
     input_potential: Potential,
     charge: ElectricCharge,
     output_potential: ElectricPotential,
     current: ElectricCurrent,
+    failure: Failure,
 }
 impl Battery {
     const RATED_CAPACITY_AMPERE_HOURS: f64 = 23.;
 
-    pub fn full(number: usize) -> Battery {
+    pub fn full(registry: &mut VariableRegistry, number: usize) -> Battery {
         Battery::new(
+            registry,
             number,
             ElectricCharge::new::<ampere_hour>(Battery::RATED_CAPACITY_AMPERE_HOURS),
         )
     }
 
-    pub fn half(number: usize) -> Battery {
+    pub fn half(registry: &mut VariableRegistry, number: usize) -> Battery {
         Battery::new(
+            registry,
             number,
             ElectricCharge::new::<ampere_hour>(Battery::RATED_CAPACITY_AMPERE_HOURS / 2.),
         )
     }
 
-    pub fn empty(number: usize) -> Battery {
-        Battery::new(number, ElectricCharge::new::<ampere_hour>(0.))
+    pub fn empty(registry: &mut VariableRegistry, number: usize) -> Battery {
+        Battery::new(registry, number, ElectricCharge::new::<ampere_hour>(0.))
     }
 
-    pub fn new(number: usize, charge: ElectricCharge) -> Self {
+    pub fn new(registry: &mut VariableRegistry, number: usize, charge: ElectricCharge) -> Self {
         Self {
             number,
-            writer: ElectricalStateWriter::new(&format!("BAT_{}", number)),
+            writer: ElectricalStateWriter::new(registry, &format!("BAT_{}", number)),
+            charge_id: format!("ELEC_BAT_{}_CHARGE", number),
             input_potential: Potential::none(),
             charge,
             output_potential: Battery::calculate_output_potential_for_charge(charge),
             current: ElectricCurrent::new::<ampere>(0.),
+            failure: Failure::new(registry, FailureType::Battery(number)),
         }
     }
 
@@ -54,7 +63,7 @@ impl Battery {
     }
 
     fn is_powered_by_other_potential(&self) -> bool {
-        self.input_potential.raw() > self.output_potential
+        !self.failure.is_active() && self.input_potential.raw() > self.output_potential
     }
 
     pub fn input_potential(&self) -> Potential {
@@ -119,7 +128,7 @@ impl Battery {
 potential_target!(Battery);
 impl PotentialSource for Battery {
     fn output(&self) -> Potential {
-        if self.output_potential > ElectricPotential::new::<volt>(0.) {
+        if !self.failure.is_active() && self.output_potential > ElectricPotential::new::<volt>(0.) {
             Potential::single(PotentialOrigin::Battery(self.number), self.output_potential)
         } else {
             Potential::none()
@@ -147,6 +156,12 @@ impl ProvidePotential for Battery {
     }
 }
 impl SimulationElement for Battery {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.failure.accept(visitor);
+
+        visitor.visit(self);
+    }
+
     fn write(&self, writer: &mut SimulatorWriter) {
         self.writer.write_direct(self, writer);
     }
@@ -185,6 +200,17 @@ impl SimulationElement for Battery {
 
         self.output_potential = Battery::calculate_output_potential_for_charge(self.charge);
     }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        snapshot.save_f64(&self.charge_id, self.charge.get::<ampere_hour>());
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        if let Some(charge) = snapshot.restore_f64(&self.charge_id) {
+            self.charge = ElectricCharge::new::<ampere_hour>(charge);
+            self.output_potential = Battery::calculate_output_potential_for_charge(self.charge);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -261,12 +287,17 @@ mod tests {
         }
         impl TestAircraft {
             fn new(battery_1: Battery, battery_2: Battery) -> Self {
+                let mut registry = VariableRegistry::new();
+
                 let mut aircraft = Self {
                     battery_1,
                     battery_2,
-                    bat_bus: ElectricalBus::new(ElectricalBusType::DirectCurrentBattery),
-                    battery_1_contactor: Contactor::new("BAT1"),
-                    battery_2_contactor: Contactor::new("BAT2"),
+                    bat_bus: ElectricalBus::new(
+                        &mut registry,
+                        ElectricalBusType::DirectCurrentBattery,
+                    ),
+                    battery_1_contactor: Contactor::new(&mut registry, "BAT1"),
+                    battery_2_contactor: Contactor::new(&mut registry, "BAT2"),
                     consumer: PowerConsumer::from(ElectricalBusType::DirectCurrentBattery),
                     battery_consumption: Power::new::<watt>(0.),
                     supplied_input_potential: Potential::none(),
@@ -278,33 +309,57 @@ mod tests {
             }
 
             fn with_full_batteries() -> Self {
-                Self::new(Battery::full(1), Battery::full(2))
+                let mut registry = VariableRegistry::new();
+
+                Self::new(
+                    Battery::full(&mut registry, 1),
+                    Battery::full(&mut registry, 2),
+                )
             }
 
             fn with_half_charged_batteries() -> Self {
-                Self::new(Battery::half(1), Battery::half(2))
+                let mut registry = VariableRegistry::new();
+
+                Self::new(
+                    Battery::half(&mut registry, 1),
+                    Battery::half(&mut registry, 2),
+                )
             }
 
             fn with_nearly_empty_batteries() -> Self {
+                let mut registry = VariableRegistry::new();
+
                 Self::new(
-                    Battery::new(1, ElectricCharge::new::<ampere_hour>(0.001)),
-                    Battery::new(2, ElectricCharge::new::<ampere_hour>(0.001)),
+                    Battery::new(&mut registry, 1, ElectricCharge::new::<ampere_hour>(0.001)),
+                    Battery::new(&mut registry, 2, ElectricCharge::new::<ampere_hour>(0.001)),
                 )
             }
 
             fn with_nearly_empty_dissimilarly_charged_batteries() -> Self {
+                let mut registry = VariableRegistry::new();
+
                 Self::new(
-                    Battery::new(1, ElectricCharge::new::<ampere_hour>(0.002)),
-                    Battery::new(2, ElectricCharge::new::<ampere_hour>(0.001)),
+                    Battery::new(&mut registry, 1, ElectricCharge::new::<ampere_hour>(0.002)),
+                    Battery::new(&mut registry, 2, ElectricCharge::new::<ampere_hour>(0.001)),
                 )
             }
 
             fn with_empty_batteries() -> Self {
-                Self::new(Battery::empty(1), Battery::empty(2))
+                let mut registry = VariableRegistry::new();
+
+                Self::new(
+                    Battery::empty(&mut registry, 1),
+                    Battery::empty(&mut registry, 2),
+                )
             }
 
             fn with_full_and_empty_battery() -> Self {
-                Self::new(Battery::full(1), Battery::empty(2))
+                let mut registry = VariableRegistry::new();
+
+                Self::new(
+                    Battery::full(&mut registry, 1),
+                    Battery::empty(&mut registry, 2),
+                )
             }
 
             fn supply_input_potential(&mut self, potential: ElectricPotential) {
@@ -643,5 +698,23 @@ mod tests {
                     < ElectricCharge::new::<ampere_hour>(0.001)
             );
         }
+
+        #[test]
+        fn charge_is_restored_from_snapshot() {
+            let mut aircraft = TestAircraft::with_half_charged_batteries();
+            let snapshot = Snapshot::take(&mut aircraft);
+
+            let mut restored_aircraft = TestAircraft::with_full_batteries();
+            snapshot.restore(&mut restored_aircraft);
+
+            assert_eq!(
+                restored_aircraft.battery_1_charge(),
+                aircraft.battery_1_charge()
+            );
+            assert_eq!(
+                restored_aircraft.battery_2_charge(),
+                aircraft.battery_2_charge()
+            );
+        }
     }
 }
@@ -3,7 +3,10 @@ use std::time::Duration;
 use super::{PotentialSource, ProvideCurrent};
 use crate::{
     shared::DelayedTrueLogicGate,
-    simulation::{SimulationElement, SimulatorWriter, UpdateContext},
+    simulation::{
+        QuantityIdentifier, SimulationElement, SimulatorWriter, Snapshot, UpdateContext,
+        VariableRegistry, VariableUnit,
+    },
 };
 use uom::si::{electric_current::ampere, electric_potential::volt, f64::*, velocity::knot};
 
@@ -88,19 +91,21 @@ impl BatteryChargeLimiterArguments {
 }
 
 pub struct BatteryChargeLimiter {
-    should_show_arrow_when_contactor_closed_id: String,
+    should_show_arrow_when_contactor_closed_id: QuantityIdentifier<bool>,
+    snapshot_prefix: String,
     arrow: ArrowBetweenBatteryAndBatBus,
     observer: Option<Box<dyn BatteryStateObserver>>,
 }
 impl BatteryChargeLimiter {
     const CHARGE_DISCHARGE_ARROW_DISPLAYED_AFTER_SECONDS: u64 = 15;
 
-    pub fn new(contactor_id: &str) -> Self {
+    pub fn new(registry: &mut VariableRegistry, contactor_id: &str) -> Self {
         Self {
-            should_show_arrow_when_contactor_closed_id: format!(
-                "ELEC_CONTACTOR_{}_SHOW_ARROW_WHEN_CLOSED",
-                contactor_id
+            should_show_arrow_when_contactor_closed_id: registry.register_write(
+                &format!("ELEC_CONTACTOR_{}_SHOW_ARROW_WHEN_CLOSED", contactor_id),
+                VariableUnit::Bool,
             ),
+            snapshot_prefix: format!("ELEC_BCL_{}", contactor_id),
             arrow: ArrowBetweenBatteryAndBatBus::new(),
             observer: Some(Box::new(OpenContactorObserver::from_off())),
         }
@@ -125,6 +130,21 @@ impl SimulationElement for BatteryChargeLimiter {
             self.arrow.should_show_when_contactor_closed(),
         );
     }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        self.arrow.save(snapshot, &self.snapshot_prefix);
+        self.observer
+            .as_ref()
+            .unwrap()
+            .save(snapshot, &self.snapshot_prefix);
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        self.arrow.restore(snapshot, &self.snapshot_prefix);
+        if let Some(observer) = restore_observer(snapshot, &self.snapshot_prefix) {
+            self.observer = Some(observer);
+        }
+    }
 }
 
 /// Observes the battery, battery contactor and related systems
@@ -136,6 +156,23 @@ trait BatteryStateObserver {
         context: &UpdateContext,
         arguments: &BatteryChargeLimiterArguments,
     ) -> Box<dyn BatteryStateObserver>;
+    fn save(&self, snapshot: &mut Snapshot, prefix: &str);
+}
+
+const OFF_PUSH_BUTTON_OBSERVER: u8 = 0;
+const OPEN_CONTACTOR_OBSERVER: u8 = 1;
+const CLOSED_CONTACTOR_OBSERVER: u8 = 2;
+
+/// Restores the observer contained in the snapshot. Returns `None` when the
+/// snapshot doesn't contain a complete observer.
+fn restore_observer(snapshot: &Snapshot, prefix: &str) -> Option<Box<dyn BatteryStateObserver>> {
+    match snapshot.restore_f64(&format!("{}_OBSERVER", prefix))? as u8 {
+        OFF_PUSH_BUTTON_OBSERVER => Some(Box::new(OffPushButtonObserver::new())),
+        CLOSED_CONTACTOR_OBSERVER => ClosedContactorObserver::restore(snapshot, prefix)
+            .map(|observer| Box::new(observer) as Box<dyn BatteryStateObserver>),
+        _ => OpenContactorObserver::restore(snapshot, prefix)
+            .map(|observer| Box::new(observer) as Box<dyn BatteryStateObserver>),
+    }
 }
 
 /// The BCL is not powered when the BAT push button is in the OFF
@@ -163,6 +200,13 @@ impl BatteryStateObserver for OffPushButtonObserver {
             self
         }
     }
+
+    fn save(&self, snapshot: &mut Snapshot, prefix: &str) {
+        snapshot.save_f64(
+            &format!("{}_OBSERVER", prefix),
+            OFF_PUSH_BUTTON_OBSERVER as f64,
+        );
+    }
 }
 
 /// Observes the open battery contactor and related systems
@@ -211,6 +255,22 @@ impl OpenContactorObserver {
         Self::new(emergency_elec, false, true)
     }
 
+    fn restore(snapshot: &Snapshot, prefix: &str) -> Option<Self> {
+        let mut observer = Self::new(
+            EmergencyElec::restore(snapshot, prefix)?,
+            snapshot.restore_bool(&format!("{}_OPEN_DUE_TO_DISCHARGE_PROTECTION", prefix))?,
+            snapshot.restore_bool(&format!(
+                "{}_OPEN_DUE_TO_EXCEEDING_EMER_ELEC_CLOSING_TIME_ALLOWANCE",
+                prefix
+            ))?,
+        );
+        observer
+            .begin_charging_cycle_delay
+            .restore(snapshot, &format!("{}_BEGIN_CHARGING_CYCLE_DELAY", prefix));
+
+        Some(observer)
+    }
+
     fn update_state(&mut self, context: &UpdateContext, arguments: &BatteryChargeLimiterArguments) {
         self.emergency_elec.update(context, arguments);
         self.update_begin_charging_cycle_delay(context, arguments);
@@ -288,6 +348,27 @@ impl BatteryStateObserver for OpenContactorObserver {
             self
         }
     }
+
+    fn save(&self, snapshot: &mut Snapshot, prefix: &str) {
+        snapshot.save_f64(
+            &format!("{}_OBSERVER", prefix),
+            OPEN_CONTACTOR_OBSERVER as f64,
+        );
+        self.begin_charging_cycle_delay
+            .save(snapshot, &format!("{}_BEGIN_CHARGING_CYCLE_DELAY", prefix));
+        snapshot.save_bool(
+            &format!("{}_OPEN_DUE_TO_DISCHARGE_PROTECTION", prefix),
+            self.open_due_to_discharge_protection,
+        );
+        snapshot.save_bool(
+            &format!(
+                "{}_OPEN_DUE_TO_EXCEEDING_EMER_ELEC_CLOSING_TIME_ALLOWANCE",
+                prefix
+            ),
+            self.open_due_to_exceeding_emergency_elec_closing_time_allowance,
+        );
+        self.emergency_elec.save(snapshot, prefix);
+    }
 }
 
 /// Observes the closed battery contactor and related systems
@@ -317,6 +398,21 @@ impl ClosedContactorObserver {
         }
     }
 
+    fn restore(snapshot: &Snapshot, prefix: &str) -> Option<Self> {
+        Some(Self {
+            below_4_ampere_charging_duration: snapshot
+                .restore_duration(&format!("{}_BELOW_4_AMPERE_CHARGING_DURATION", prefix))?,
+            below_23_volt_duration: snapshot
+                .restore_duration(&format!("{}_BELOW_23_VOLT_DURATION", prefix))?,
+            apu_master_sw_pb_on_duration: snapshot
+                .restore_duration(&format!("{}_APU_MASTER_SW_PB_ON_DURATION", prefix))?,
+            emergency_elec: EmergencyElec::restore(snapshot, prefix)?,
+            had_apu_start: snapshot.restore_bool(&format!("{}_HAD_APU_START", prefix))?,
+            entered_in_emergency_elec: snapshot
+                .restore_bool(&format!("{}_ENTERED_IN_EMER_ELEC", prefix))?,
+        })
+    }
+
     fn update_state(&mut self, context: &UpdateContext, arguments: &BatteryChargeLimiterArguments) {
         self.emergency_elec.update(context, arguments);
 
@@ -435,6 +531,31 @@ impl BatteryStateObserver for ClosedContactorObserver {
             self
         }
     }
+
+    fn save(&self, snapshot: &mut Snapshot, prefix: &str) {
+        snapshot.save_f64(
+            &format!("{}_OBSERVER", prefix),
+            CLOSED_CONTACTOR_OBSERVER as f64,
+        );
+        snapshot.save_duration(
+            &format!("{}_BELOW_4_AMPERE_CHARGING_DURATION", prefix),
+            self.below_4_ampere_charging_duration,
+        );
+        snapshot.save_duration(
+            &format!("{}_BELOW_23_VOLT_DURATION", prefix),
+            self.below_23_volt_duration,
+        );
+        snapshot.save_duration(
+            &format!("{}_APU_MASTER_SW_PB_ON_DURATION", prefix),
+            self.apu_master_sw_pb_on_duration,
+        );
+        snapshot.save_bool(&format!("{}_HAD_APU_START", prefix), self.had_apu_start);
+        snapshot.save_bool(
+            &format!("{}_ENTERED_IN_EMER_ELEC", prefix),
+            self.entered_in_emergency_elec,
+        );
+        self.emergency_elec.save(snapshot, prefix);
+    }
 }
 
 fn on_ground_at_low_speed_with_unpowered_ac_buses(
@@ -468,6 +589,20 @@ impl EmergencyElec {
         }
     }
 
+    fn restore(snapshot: &Snapshot, prefix: &str) -> Option<Self> {
+        Some(Self {
+            is_active_for_duration: snapshot
+                .restore_duration(&format!("{}_EMER_ELEC_IS_ACTIVE_FOR_DURATION", prefix))?,
+        })
+    }
+
+    fn save(&self, snapshot: &mut Snapshot, prefix: &str) {
+        snapshot.save_duration(
+            &format!("{}_EMER_ELEC_IS_ACTIVE_FOR_DURATION", prefix),
+            self.is_active_for_duration,
+        );
+    }
+
     fn is_active(&self) -> bool {
         self.is_active_for_duration > Duration::from_secs(0)
     }
@@ -505,6 +640,28 @@ impl ArrowBetweenBatteryAndBatBus {
         );
     }
 
+    fn save(&self, snapshot: &mut Snapshot, prefix: &str) {
+        self.discharging_above_1_ampere_beyond_time.save(
+            snapshot,
+            &format!("{}_ARROW_DISCHARGING_ABOVE_1_AMPERE", prefix),
+        );
+        self.charging_above_1_ampere_beyond_time.save(
+            snapshot,
+            &format!("{}_ARROW_CHARGING_ABOVE_1_AMPERE", prefix),
+        );
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot, prefix: &str) {
+        self.discharging_above_1_ampere_beyond_time.restore(
+            snapshot,
+            &format!("{}_ARROW_DISCHARGING_ABOVE_1_AMPERE", prefix),
+        );
+        self.charging_above_1_ampere_beyond_time.restore(
+            snapshot,
+            &format!("{}_ARROW_CHARGING_ABOVE_1_AMPERE", prefix),
+        );
+    }
+
     fn should_show_when_contactor_closed(&self) -> bool {
         self.discharging_above_1_ampere_beyond_time.output()
             || self.charging_above_1_ampere_beyond_time.output()
@@ -538,7 +695,7 @@ mod tests {
             fn new() -> Self {
                 Self {
                     test_bed: SimulationTestBed::new(),
-                    aircraft: TestAircraft::new(Battery::half(1)),
+                    aircraft: TestAircraft::new(Battery::half(&mut VariableRegistry::new(), 1)),
                 }
             }
 
@@ -736,6 +893,15 @@ mod tests {
 
                 self
             }
+
+            fn restored_battery_charge_limiter(mut self) -> Self {
+                let snapshot = Snapshot::take(&mut self.aircraft);
+                self.aircraft.battery_charge_limiter =
+                    BatteryChargeLimiter::new(&mut VariableRegistry::new(), "TEST");
+                snapshot.restore(&mut self.aircraft);
+
+                self
+            }
         }
 
         struct TestAircraft {
@@ -754,11 +920,16 @@ mod tests {
         }
         impl TestAircraft {
             fn new(battery: Battery) -> Self {
+                let mut registry = VariableRegistry::new();
+
                 Self {
                     battery,
-                    battery_charge_limiter: BatteryChargeLimiter::new("TEST"),
-                    battery_bus: ElectricalBus::new(ElectricalBusType::DirectCurrentBattery),
-                    battery_contactor: Contactor::new("TEST"),
+                    battery_charge_limiter: BatteryChargeLimiter::new(&mut registry, "TEST"),
+                    battery_bus: ElectricalBus::new(
+                        &mut registry,
+                        ElectricalBusType::DirectCurrentBattery,
+                    ),
+                    battery_contactor: Contactor::new(&mut registry, "TEST"),
                     consumer: PowerConsumer::from(ElectricalBusType::DirectCurrentBattery),
                     both_ac_buses_unpowered: false,
                     apu_master_sw_pb_on: false,
@@ -1556,5 +1727,30 @@ mod tests {
 
             assert!(test_bed.battery_contactor_is_closed(),);
         }
+
+        #[test]
+        fn closed_contactor_remains_closed_when_restored_from_snapshot() {
+            let test_bed = test_bed()
+                .wait_for_closed_contactor(true)
+                .restored_battery_charge_limiter()
+                .run(Duration::from_secs(0));
+
+            assert!(test_bed.battery_contactor_is_closed());
+        }
+
+        #[test]
+        fn arrow_timer_continues_when_restored_from_snapshot() {
+            let mut test_bed = test_bed()
+                .wait_for_closed_contactor(true)
+                .run(
+                    Duration::from_secs(
+                        BatteryChargeLimiter::CHARGE_DISCHARGE_ARROW_DISPLAYED_AFTER_SECONDS,
+                    ) - Duration::from_millis(1),
+                )
+                .restored_battery_charge_limiter()
+                .run(Duration::from_millis(1));
+
+            assert!(test_bed.should_show_arrow_when_contactor_closed())
+        }
     }
 }
@@ -20,10 +20,9 @@ use std::{collections::HashMap, time::Duration};
 
 use super::{ElectricalBus, ElectricalBusType, Potential, PotentialOrigin, PotentialSource};
 use crate::{
-    shared::{random_number, FwcFlightPhase},
-    simulation::{SimulationElement, SimulationElementVisitor, SimulatorReader, UpdateContext},
+    shared::{FwcFlightPhase, FwcFlightPhaseProvider},
+    simulation::{SimulationElement, SimulationElementVisitor, Snapshot, UpdateContext},
 };
-use num_traits::FromPrimitive;
 use uom::si::{f64::*, power::watt};
 
 pub(crate) struct ElectricPower {
@@ -115,7 +114,6 @@ impl PowerConsumer {
         }
     }
 
-    #[cfg(test)]
     /// Determine if the power consumer has potential powering
     /// it during this simulation tick.
     /// If this function is called before power has been supplied to it
@@ -168,12 +166,15 @@ impl FlightPhasePowerConsumer {
         }
     }
 
-    pub fn update(&mut self, context: &UpdateContext) {
+    pub fn update(&mut self, context: &UpdateContext, fwc: &impl FwcFlightPhaseProvider) {
+        self.current_flight_phase = PowerConsumerFlightPhase::from(fwc.flight_phase());
+
         if self.update_after <= context.delta() {
-            self.update_after = Duration::from_secs_f64(5. + ((random_number() % 26) as f64));
+            self.update_after =
+                Duration::from_secs_f64(5. + ((context.random_number() % 26) as f64));
             let base_demand = self.base_demand[self.current_flight_phase as usize].get::<watt>();
             self.consumer.demand(Power::new::<watt>(
-                base_demand * ((90. + ((random_number() % 21) as f64)) / 100.),
+                base_demand * ((90. + ((context.random_number() % 21) as f64)) / 100.),
             ));
         } else {
             self.update_after -= context.delta();
@@ -190,6 +191,23 @@ impl FlightPhasePowerConsumer {
 
         self
     }
+
+    pub fn save(&self, snapshot: &mut Snapshot, name: &str) {
+        snapshot.save_f64(
+            &format!("{}_DEMAND", name),
+            self.consumer.demand.get::<watt>(),
+        );
+        snapshot.save_duration(&format!("{}_UPDATE_AFTER", name), self.update_after);
+    }
+
+    pub fn restore(&mut self, snapshot: &Snapshot, name: &str) {
+        if let Some(demand) = snapshot.restore_f64(&format!("{}_DEMAND", name)) {
+            self.consumer.demand(Power::new::<watt>(demand));
+        }
+        if let Some(update_after) = snapshot.restore_duration(&format!("{}_UPDATE_AFTER", name)) {
+            self.update_after = update_after;
+        }
+    }
 }
 impl SimulationElement for FlightPhasePowerConsumer {
     fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
@@ -197,14 +215,6 @@ impl SimulationElement for FlightPhasePowerConsumer {
 
         visitor.visit(self);
     }
-
-    fn read(&mut self, reader: &mut SimulatorReader) {
-        let flight_phase: Option<FwcFlightPhase> =
-            FromPrimitive::from_f64(reader.read_f64("FWC_FLIGHT_PHASE"));
-        if let Some(phase) = flight_phase {
-            self.current_flight_phase = PowerConsumerFlightPhase::from(phase);
-        }
-    }
 }
 
 #[derive(Copy, Clone)]
@@ -331,7 +341,10 @@ mod tests {
     use uom::si::electric_potential::volt;
 
     use super::*;
-    use crate::electrical::{Potential, PotentialSource};
+    use crate::{
+        electrical::{Potential, PotentialSource},
+        simulation::VariableRegistry,
+    };
 
     struct ApuStub {
         consumed_power: Power,
@@ -370,7 +383,7 @@ mod tests {
         }
 
         fn unpowered_bus(bus_type: ElectricalBusType) -> ElectricalBus {
-            ElectricalBus::new(bus_type)
+            ElectricalBus::new(&mut VariableRegistry::new(), bus_type)
         }
 
         #[test]
@@ -402,7 +415,7 @@ mod tests {
         use crate::electrical::PotentialTarget;
 
         fn powered_bus(bus_type: ElectricalBusType) -> ElectricalBus {
-            let mut bus = ElectricalBus::new(bus_type);
+            let mut bus = ElectricalBus::new(&mut VariableRegistry::new(), bus_type);
             bus.powered_by(&ApuStub::new());
 
             bus
@@ -485,9 +498,19 @@ mod tests {
 
         use super::*;
 
+        struct TestFlightWarningComputer {
+            flight_phase: FwcFlightPhase,
+        }
+        impl FwcFlightPhaseProvider for TestFlightWarningComputer {
+            fn flight_phase(&self) -> FwcFlightPhase {
+                self.flight_phase
+            }
+        }
+
         struct FlightPhasePowerConsumerTestAircraft {
             apu_generator_consumption: Option<Power>,
             consumer: FlightPhasePowerConsumer,
+            fwc: TestFlightWarningComputer,
             powered: bool,
         }
         impl FlightPhasePowerConsumerTestAircraft {
@@ -495,10 +518,17 @@ mod tests {
                 Self {
                     apu_generator_consumption: None,
                     consumer,
+                    fwc: TestFlightWarningComputer {
+                        flight_phase: FwcFlightPhase::ElecPwr,
+                    },
                     powered,
                 }
             }
 
+            fn set_flight_phase(&mut self, phase: FwcFlightPhase) {
+                self.fwc.flight_phase = phase;
+            }
+
             fn consumption_equals(&self, expected: Power) -> bool {
                 match self.apu_generator_consumption {
                     Some(consumption) => consumption == expected,
@@ -514,7 +544,7 @@ mod tests {
             }
 
             fn powered_bus(bus_type: ElectricalBusType) -> ElectricalBus {
-                let mut bus = ElectricalBus::new(bus_type);
+                let mut bus = ElectricalBus::new(&mut VariableRegistry::new(), bus_type);
                 bus.powered_by(&ApuStub::new());
 
                 bus
@@ -522,7 +552,7 @@ mod tests {
         }
         impl Aircraft for FlightPhasePowerConsumerTestAircraft {
             fn update_after_power_distribution(&mut self, context: &UpdateContext) {
-                self.consumer.update(context);
+                self.consumer.update(context, &self.fwc);
             }
 
             fn get_supplied_power(&mut self) -> SuppliedPower {
@@ -550,10 +580,6 @@ mod tests {
             }
         }
 
-        fn apply_flight_phase(test_bed: &mut SimulationTestBed, phase: FwcFlightPhase) {
-            test_bed.write_f64("FWC_FLIGHT_PHASE", phase as i32 as f64);
-        }
-
         #[test]
         fn when_flight_phase_doesnt_have_demand_usage_is_zero() {
             let mut aircraft = FlightPhasePowerConsumerTestAircraft::new(
@@ -572,7 +598,7 @@ mod tests {
             );
 
             let mut test_bed = SimulationTestBed::new();
-            apply_flight_phase(&mut test_bed, FwcFlightPhase::FirstEngineStarted);
+            aircraft.set_flight_phase(FwcFlightPhase::FirstEngineStarted);
 
             test_bed.run_aircraft(&mut aircraft);
 
@@ -598,13 +624,62 @@ mod tests {
             );
 
             let mut test_bed = SimulationTestBed::new();
-            apply_flight_phase(&mut test_bed, FwcFlightPhase::AtOrAbove1500Feet);
+            aircraft.set_flight_phase(FwcFlightPhase::AtOrAbove1500Feet);
 
             test_bed.run_aircraft(&mut aircraft);
 
             assert!(aircraft.consumption_within_range(input * 0.9, input * 1.1));
         }
 
+        #[test]
+        fn usage_is_close_to_demand_for_any_random_seed() {
+            let input = Power::new::<watt>(20000.);
+
+            for seed in 0..100 {
+                let mut aircraft =
+                    FlightPhasePowerConsumerTestAircraft::new(flight_demand_consumer(input), true);
+                let mut test_bed = SimulationTestBed::new();
+                test_bed.set_random_seed(seed);
+                aircraft.set_flight_phase(FwcFlightPhase::AtOrAbove1500Feet);
+
+                test_bed.run_aircraft(&mut aircraft);
+
+                assert!(aircraft.consumption_within_range(input * 0.9, input * 1.1));
+            }
+        }
+
+        #[test]
+        fn usage_is_equal_for_equal_random_seeds() {
+            let input = Power::new::<watt>(20000.);
+            let consumption_with_seed = |seed| {
+                let mut aircraft =
+                    FlightPhasePowerConsumerTestAircraft::new(flight_demand_consumer(input), true);
+                let mut test_bed = SimulationTestBed::new();
+                test_bed.set_random_seed(seed);
+                aircraft.set_flight_phase(FwcFlightPhase::AtOrAbove1500Feet);
+
+                test_bed.run_aircraft(&mut aircraft);
+
+                aircraft.apu_generator_consumption
+            };
+
+            assert_eq!(consumption_with_seed(42), consumption_with_seed(42));
+        }
+
+        fn flight_demand_consumer(demand: Power) -> FlightPhasePowerConsumer {
+            FlightPhasePowerConsumer::from(ElectricalBusType::AlternatingCurrent(1)).demand([
+                (
+                    PowerConsumerFlightPhase::BeforeStart,
+                    Power::new::<watt>(0.),
+                ),
+                (PowerConsumerFlightPhase::AfterStart, Power::new::<watt>(0.)),
+                (PowerConsumerFlightPhase::Takeoff, Power::new::<watt>(0.)),
+                (PowerConsumerFlightPhase::Flight, demand),
+                (PowerConsumerFlightPhase::Landing, Power::new::<watt>(0.)),
+                (PowerConsumerFlightPhase::TaxiIn, Power::new::<watt>(0.)),
+            ])
+        }
+
         #[test]
         fn when_flight_phase_does_have_demand_but_consumer_unpowered_usage_is_zero() {
             let mut aircraft = FlightPhasePowerConsumerTestAircraft::new(
@@ -632,7 +707,7 @@ mod tests {
             );
 
             let mut test_bed = SimulationTestBed::new();
-            apply_flight_phase(&mut test_bed, FwcFlightPhase::FirstEngineStarted);
+            aircraft.set_flight_phase(FwcFlightPhase::FirstEngineStarted);
 
             test_bed.run_aircraft(&mut aircraft);
 
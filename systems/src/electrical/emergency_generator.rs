@@ -1,42 +1,73 @@
-use std::time::Duration;
-
 use super::{
     consumption::PowerConsumptionReport, ElectricalStateWriter, Potential, PotentialOrigin,
     PotentialSource, ProvideFrequency, ProvidePotential,
 };
-use crate::simulation::{SimulationElement, SimulatorWriter, UpdateContext};
-use uom::si::{electric_potential::volt, f64::*, frequency::hertz};
+use crate::simulation::{
+    SimulationElement, SimulatorWriter, Snapshot, UpdateContext, VariableRegistry,
+};
+use uom::si::{
+    angular_velocity::revolution_per_minute, electric_potential::volt, f64::*, frequency::hertz,
+    power::watt, pressure::psi, volume_rate::gallon_per_minute,
+};
 
+/// The emergency generator is driven by a hydraulic motor, which is powered
+/// by the blue hydraulic circuit. Once started, the motor's speed depends on the
+/// hydraulic pressure it receives. The generator's output follows the motor speed.
 pub struct EmergencyGenerator {
     writer: ElectricalStateWriter,
-    supplying: bool,
     output_frequency: Frequency,
     output_potential: ElectricPotential,
-    time_since_start: Duration,
+    hydraulic_pressure: Pressure,
+    motor_speed: AngularVelocity,
+    load: Power,
     starting_or_started: bool,
 }
 impl EmergencyGenerator {
-    pub fn new() -> EmergencyGenerator {
+    const NOMINAL_MOTOR_SPEED_RPM: f64 = 12000.;
+    /// The motor is able to maintain its nominal speed from this pressure onwards.
+    const NOMINAL_SPEED_MINIMUM_PRESSURE_PSI: f64 = 2000.;
+    const MOTOR_SPIN_UP_TIME_SECONDS: f64 = 3.;
+    /// Below this motor speed the generator doesn't provide any output.
+    const MINIMUM_OUTPUT_MOTOR_SPEED_RATIO: f64 = 0.5;
+    const NOMINAL_FREQUENCY_HERTZ: f64 = 400.;
+    const NOMINAL_POTENTIAL_VOLT: f64 = 115.;
+    const MOTOR_NO_LOAD_POWER_WATT: f64 = 500.;
+    const EFFICIENCY: f64 = 0.7;
+
+    pub fn new(registry: &mut VariableRegistry) -> EmergencyGenerator {
         EmergencyGenerator {
-            writer: ElectricalStateWriter::new("EMER_GEN"),
-            supplying: false,
+            writer: ElectricalStateWriter::new(registry, "EMER_GEN"),
             output_frequency: Frequency::new::<hertz>(0.),
             output_potential: ElectricPotential::new::<volt>(0.),
-            time_since_start: Duration::from_secs(0),
+            hydraulic_pressure: Pressure::new::<psi>(0.),
+            motor_speed: AngularVelocity::new::<revolution_per_minute>(0.),
+            load: Power::new::<watt>(0.),
             starting_or_started: false,
         }
     }
 
-    pub fn update(&mut self, context: &UpdateContext, can_supply_when_running: bool) {
-        // TODO: All of this is a very simple implementation.
-        // Once hydraulics is available we should improve it.
-        if self.starting_or_started {
-            self.time_since_start += context.delta();
-        }
+    pub fn update(&mut self, context: &UpdateContext, hydraulic_pressure: Pressure) {
+        self.hydraulic_pressure = hydraulic_pressure;
 
-        self.supplying = can_supply_when_running
-            && self.starting_or_started
-            && self.time_since_start > Duration::from_secs(8);
+        let target_speed = if self.starting_or_started {
+            EmergencyGenerator::NOMINAL_MOTOR_SPEED_RPM
+                * (hydraulic_pressure.get::<psi>()
+                    / EmergencyGenerator::NOMINAL_SPEED_MINIMUM_PRESSURE_PSI)
+                    .clamp(0., 1.)
+        } else {
+            0.
+        };
+
+        let current_speed = self.motor_speed.get::<revolution_per_minute>();
+        let max_change = EmergencyGenerator::NOMINAL_MOTOR_SPEED_RPM
+            / EmergencyGenerator::MOTOR_SPIN_UP_TIME_SECONDS
+            * context.delta().as_secs_f64();
+        self.motor_speed =
+            AngularVelocity::new::<revolution_per_minute>(if target_speed > current_speed {
+                (current_speed + max_change).min(target_speed)
+            } else {
+                (current_speed - max_change).max(target_speed)
+            });
     }
 
     pub fn start(&mut self) {
@@ -50,8 +81,25 @@ impl EmergencyGenerator {
         self.frequency_normal() && self.potential_normal()
     }
 
+    /// The hydraulic flow the generator's motor takes from the hydraulic circuit
+    /// to turn the generator under its current electrical load.
+    pub fn hydraulic_flow_demand(&self) -> VolumeRate {
+        if self.motor_speed_ratio() > 0. && self.hydraulic_pressure > Pressure::new::<psi>(0.) {
+            (Power::new::<watt>(EmergencyGenerator::MOTOR_NO_LOAD_POWER_WATT)
+                + self.load / EmergencyGenerator::EFFICIENCY)
+                / self.hydraulic_pressure
+        } else {
+            VolumeRate::new::<gallon_per_minute>(0.)
+        }
+    }
+
+    fn motor_speed_ratio(&self) -> f64 {
+        self.motor_speed.get::<revolution_per_minute>()
+            / EmergencyGenerator::NOMINAL_MOTOR_SPEED_RPM
+    }
+
     fn should_provide_output(&self) -> bool {
-        self.supplying
+        self.motor_speed_ratio() >= EmergencyGenerator::MINIMUM_OUTPUT_MOTOR_SPEED_RATIO
     }
 }
 impl PotentialSource for EmergencyGenerator {
@@ -66,15 +114,25 @@ impl PotentialSource for EmergencyGenerator {
 provide_frequency!(EmergencyGenerator, (390.0..=410.0));
 provide_potential!(EmergencyGenerator, (110.0..=120.0));
 impl SimulationElement for EmergencyGenerator {
-    fn process_power_consumption_report<T: PowerConsumptionReport>(&mut self, _report: &T) {
+    fn process_power_consumption_report<T: PowerConsumptionReport>(&mut self, report: &T) {
+        self.load = if self.should_provide_output() {
+            report.total_consumption_of(PotentialOrigin::EmergencyGenerator)
+        } else {
+            Power::new::<watt>(0.)
+        };
+
         self.output_frequency = if self.should_provide_output() {
-            Frequency::new::<hertz>(400.)
+            Frequency::new::<hertz>(
+                EmergencyGenerator::NOMINAL_FREQUENCY_HERTZ * self.motor_speed_ratio(),
+            )
         } else {
             Frequency::new::<hertz>(0.)
         };
 
         self.output_potential = if self.should_provide_output() {
-            ElectricPotential::new::<volt>(115.)
+            ElectricPotential::new::<volt>(
+                EmergencyGenerator::NOMINAL_POTENTIAL_VOLT * self.motor_speed_ratio(),
+            )
         } else {
             ElectricPotential::new::<volt>(0.)
         };
@@ -83,10 +141,41 @@ impl SimulationElement for EmergencyGenerator {
     fn write(&self, writer: &mut SimulatorWriter) {
         self.writer.write_alternating(self, writer);
     }
-}
-impl Default for EmergencyGenerator {
-    fn default() -> Self {
-        Self::new()
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        snapshot.save_bool(
+            "ELEC_EMER_GEN_STARTING_OR_STARTED",
+            self.starting_or_started,
+        );
+        snapshot.save_f64(
+            "ELEC_EMER_GEN_MOTOR_SPEED",
+            self.motor_speed.get::<revolution_per_minute>(),
+        );
+        snapshot.save_f64(
+            "ELEC_EMER_GEN_OUTPUT_FREQUENCY",
+            self.output_frequency.get::<hertz>(),
+        );
+        snapshot.save_f64(
+            "ELEC_EMER_GEN_OUTPUT_POTENTIAL",
+            self.output_potential.get::<volt>(),
+        );
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        if let Some(starting_or_started) =
+            snapshot.restore_bool("ELEC_EMER_GEN_STARTING_OR_STARTED")
+        {
+            self.starting_or_started = starting_or_started;
+        }
+        if let Some(motor_speed) = snapshot.restore_f64("ELEC_EMER_GEN_MOTOR_SPEED") {
+            self.motor_speed = AngularVelocity::new::<revolution_per_minute>(motor_speed);
+        }
+        if let Some(frequency) = snapshot.restore_f64("ELEC_EMER_GEN_OUTPUT_FREQUENCY") {
+            self.output_frequency = Frequency::new::<hertz>(frequency);
+        }
+        if let Some(potential) = snapshot.restore_f64("ELEC_EMER_GEN_OUTPUT_POTENTIAL") {
+            self.output_potential = ElectricPotential::new::<volt>(potential);
+        }
     }
 }
 
@@ -96,6 +185,7 @@ mod emergency_generator_tests {
     use crate::simulation::{
         test::SimulationTestBed, Aircraft, SimulationElementVisitor, UpdateContext,
     };
+    use std::time::Duration;
 
     struct EmergencyGeneratorTestBed {
         test_bed: SimulationTestBed,
@@ -123,13 +213,13 @@ mod emergency_generator_tests {
 
     struct TestAircraft {
         emer_gen: EmergencyGenerator,
-        is_blue_pressurised: bool,
+        blue_pressure: Pressure,
     }
     impl TestAircraft {
         fn new() -> Self {
             Self {
-                emer_gen: EmergencyGenerator::new(),
-                is_blue_pressurised: true,
+                emer_gen: EmergencyGenerator::new(&mut VariableRegistry::new()),
+                blue_pressure: Pressure::new::<psi>(3000.),
             }
         }
 
@@ -141,8 +231,8 @@ mod emergency_generator_tests {
             self.emer_gen.start();
         }
 
-        fn set_blue_pressurisation(&mut self, pressurised: bool) {
-            self.is_blue_pressurised = pressurised;
+        fn set_blue_pressure(&mut self, pressure: Pressure) {
+            self.blue_pressure = pressure;
         }
 
         fn generator_output_within_normal_parameters(&self) -> bool {
@@ -151,7 +241,7 @@ mod emergency_generator_tests {
     }
     impl Aircraft for TestAircraft {
         fn update_before_power_distribution(&mut self, context: &UpdateContext) {
-            self.emer_gen.update(context, self.is_blue_pressurised);
+            self.emer_gen.update(context, self.blue_pressure);
         }
     }
     impl SimulationElement for TestAircraft {
@@ -189,12 +279,76 @@ mod emergency_generator_tests {
         let mut test_bed = EmergencyGeneratorTestBed::new();
 
         aircraft.attempt_emer_gen_start();
-        aircraft.set_blue_pressurisation(false);
+        aircraft.set_blue_pressure(Pressure::new::<psi>(0.));
         test_bed.run_aircraft(&mut aircraft, Duration::from_secs(100));
 
         assert!(!aircraft.emer_gen_is_powered());
     }
 
+    #[test]
+    fn when_started_is_not_powered_immediately() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = EmergencyGeneratorTestBed::new();
+
+        aircraft.attempt_emer_gen_start();
+        test_bed.run_aircraft(&mut aircraft, Duration::from_millis(500));
+
+        assert!(!aircraft.emer_gen_is_powered());
+    }
+
+    #[test]
+    fn when_started_with_low_hydraulic_pressure_frequency_not_normal() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = EmergencyGeneratorTestBed::new();
+
+        aircraft.attempt_emer_gen_start();
+        aircraft.set_blue_pressure(Pressure::new::<psi>(1500.));
+        test_bed.run_aircraft(&mut aircraft, Duration::from_secs(100));
+
+        assert!(aircraft.emer_gen_is_powered());
+        assert!(!test_bed.frequency_is_normal());
+    }
+
+    #[test]
+    fn when_hydraulic_pressure_lost_stops_providing_output() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = EmergencyGeneratorTestBed::new();
+
+        aircraft.attempt_emer_gen_start();
+        test_bed.run_aircraft(&mut aircraft, Duration::from_secs(100));
+
+        aircraft.set_blue_pressure(Pressure::new::<psi>(0.));
+        test_bed.run_aircraft(&mut aircraft, Duration::from_secs(5));
+
+        assert!(!aircraft.emer_gen_is_powered());
+    }
+
+    #[test]
+    fn when_shutdown_demands_no_hydraulic_flow() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = EmergencyGeneratorTestBed::new();
+
+        test_bed.run_aircraft(&mut aircraft, Duration::from_secs(100));
+
+        assert_eq!(
+            aircraft.emer_gen.hydraulic_flow_demand(),
+            VolumeRate::new::<gallon_per_minute>(0.)
+        );
+    }
+
+    #[test]
+    fn when_started_demands_hydraulic_flow() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = EmergencyGeneratorTestBed::new();
+
+        aircraft.attempt_emer_gen_start();
+        test_bed.run_aircraft(&mut aircraft, Duration::from_secs(100));
+
+        assert!(
+            aircraft.emer_gen.hydraulic_flow_demand() > VolumeRate::new::<gallon_per_minute>(0.)
+        );
+    }
+
     #[test]
     fn when_shutdown_frequency_not_normal() {
         let mut aircraft = TestAircraft::new();
@@ -258,6 +412,24 @@ mod emergency_generator_tests {
         assert!(aircraft.generator_output_within_normal_parameters());
     }
 
+    #[test]
+    fn running_generator_is_restored_from_snapshot() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = EmergencyGeneratorTestBed::new();
+        aircraft.attempt_emer_gen_start();
+        test_bed.run_aircraft(&mut aircraft, Duration::from_secs(100));
+
+        let mut restored_aircraft = TestAircraft::new();
+        Snapshot::take(&mut aircraft).restore(&mut restored_aircraft);
+
+        assert!(restored_aircraft.emer_gen_is_powered());
+        assert!(restored_aircraft.generator_output_within_normal_parameters());
+        assert_eq!(
+            restored_aircraft.emer_gen.motor_speed,
+            aircraft.emer_gen.motor_speed
+        );
+    }
+
     #[test]
     fn writes_its_state() {
         let mut aircraft = TestAircraft::new();
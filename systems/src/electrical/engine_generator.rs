@@ -3,8 +3,12 @@ use super::{
     PotentialSource, ProvideFrequency, ProvideLoad, ProvidePotential,
 };
 use crate::{
+    engine::Engine,
     shared::calculate_towards_target_temperature,
-    simulation::{SimulationElement, SimulationElementVisitor, SimulatorWriter, UpdateContext},
+    simulation::{
+        Failure, FailureType, QuantityIdentifier, SimulationElement, SimulationElementVisitor,
+        SimulatorWriter, Snapshot, UpdateContext, VariableRegistry, VariableUnit,
+    },
 };
 use std::cmp::min;
 use uom::si::{
@@ -13,7 +17,6 @@ use uom::si::{
 };
 
 pub trait EngineGeneratorUpdateArguments {
-    fn engine_corrected_n2(&self, number: usize) -> Ratio;
     fn idg_push_button_released(&self, number: usize) -> bool;
 }
 
@@ -26,25 +29,28 @@ pub struct EngineGenerator {
     output_frequency: Frequency,
     output_potential: ElectricPotential,
     load: Ratio,
+    failure: Failure,
 }
 impl EngineGenerator {
-    pub fn new(number: usize) -> EngineGenerator {
+    pub fn new(registry: &mut VariableRegistry, number: usize) -> EngineGenerator {
         EngineGenerator {
-            writer: ElectricalStateWriter::new(&format!("ENG_GEN_{}", number)),
+            writer: ElectricalStateWriter::new(registry, &format!("ENG_GEN_{}", number)),
             number,
-            idg: IntegratedDriveGenerator::new(number),
+            idg: IntegratedDriveGenerator::new(registry, number),
             output_frequency: Frequency::new::<hertz>(0.),
             output_potential: ElectricPotential::new::<volt>(0.),
             load: Ratio::new::<percent>(0.),
+            failure: Failure::new(registry, FailureType::EngineGenerator(number)),
         }
     }
 
     pub fn update<T: EngineGeneratorUpdateArguments>(
         &mut self,
         context: &UpdateContext,
+        engine: &Engine,
         arguments: &T,
     ) {
-        self.idg.update(context, arguments);
+        self.idg.update(context, engine, arguments);
     }
 
     /// Indicates if the provided electricity's potential and frequency
@@ -58,7 +64,7 @@ impl EngineGenerator {
     }
 
     fn should_provide_output(&self) -> bool {
-        self.idg.provides_stable_power_output()
+        !self.failure.is_active() && self.idg.provides_stable_power_output()
     }
 }
 impl PotentialSource for EngineGenerator {
@@ -79,6 +85,7 @@ provide_load!(EngineGenerator);
 impl SimulationElement for EngineGenerator {
     fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
         self.idg.accept(visitor);
+        self.failure.accept(visitor);
 
         visitor.visit(self);
     }
@@ -109,32 +116,71 @@ impl SimulationElement for EngineGenerator {
     fn write(&self, writer: &mut SimulatorWriter) {
         self.writer.write_alternating_with_load(self, writer);
     }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        snapshot.save_f64(
+            &format!("ELEC_ENG_GEN_{}_OUTPUT_FREQUENCY", self.number),
+            self.output_frequency.get::<hertz>(),
+        );
+        snapshot.save_f64(
+            &format!("ELEC_ENG_GEN_{}_OUTPUT_POTENTIAL", self.number),
+            self.output_potential.get::<volt>(),
+        );
+        snapshot.save_f64(
+            &format!("ELEC_ENG_GEN_{}_LOAD", self.number),
+            self.load.get::<percent>(),
+        );
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        if let Some(frequency) =
+            snapshot.restore_f64(&format!("ELEC_ENG_GEN_{}_OUTPUT_FREQUENCY", self.number))
+        {
+            self.output_frequency = Frequency::new::<hertz>(frequency);
+        }
+        if let Some(potential) =
+            snapshot.restore_f64(&format!("ELEC_ENG_GEN_{}_OUTPUT_POTENTIAL", self.number))
+        {
+            self.output_potential = ElectricPotential::new::<volt>(potential);
+        }
+        if let Some(load) = snapshot.restore_f64(&format!("ELEC_ENG_GEN_{}_LOAD", self.number)) {
+            self.load = Ratio::new::<percent>(load);
+        }
+    }
 }
 
 struct IntegratedDriveGenerator {
-    oil_outlet_temperature_id: String,
+    oil_outlet_temperature_id: QuantityIdentifier<ThermodynamicTemperature>,
     oil_outlet_temperature: ThermodynamicTemperature,
-    is_connected_id: String,
+    is_connected_id: QuantityIdentifier<bool>,
     connected: bool,
     number: usize,
 
+    time_above_threshold_id: String,
     time_above_threshold_in_milliseconds: u64,
 }
 impl IntegratedDriveGenerator {
     pub const ENGINE_N2_POWER_UP_OUTPUT_THRESHOLD: f64 = 58.;
     pub const ENGINE_N2_POWER_DOWN_OUTPUT_THRESHOLD: f64 = 56.;
 
-    fn new(number: usize) -> IntegratedDriveGenerator {
+    fn new(registry: &mut VariableRegistry, number: usize) -> IntegratedDriveGenerator {
         IntegratedDriveGenerator {
-            oil_outlet_temperature_id: format!(
-                "ELEC_ENG_GEN_{}_IDG_OIL_OUTLET_TEMPERATURE",
-                number
+            oil_outlet_temperature_id: registry.register_write(
+                &format!("ELEC_ENG_GEN_{}_IDG_OIL_OUTLET_TEMPERATURE", number),
+                VariableUnit::Celsius,
             ),
             oil_outlet_temperature: ThermodynamicTemperature::new::<degree_celsius>(0.),
-            is_connected_id: format!("ELEC_ENG_GEN_{}_IDG_IS_CONNECTED", number),
+            is_connected_id: registry.register_write(
+                &format!("ELEC_ENG_GEN_{}_IDG_IS_CONNECTED", number),
+                VariableUnit::Bool,
+            ),
             connected: true,
             number,
 
+            time_above_threshold_id: format!(
+                "ELEC_ENG_GEN_{}_IDG_MILLISECONDS_ABOVE_THRESHOLD",
+                number
+            ),
             time_above_threshold_in_milliseconds: 0,
         }
     }
@@ -142,6 +188,7 @@ impl IntegratedDriveGenerator {
     pub fn update<T: EngineGeneratorUpdateArguments>(
         &mut self,
         context: &UpdateContext,
+        engine: &Engine,
         arguments: &T,
     ) {
         if arguments.idg_push_button_released(self.number) {
@@ -149,10 +196,10 @@ impl IntegratedDriveGenerator {
             self.connected = false;
         }
 
-        self.update_stable_time(context, arguments.engine_corrected_n2(self.number));
+        self.update_stable_time(context, engine);
         self.update_temperature(
             context,
-            self.get_target_temperature(context, arguments.engine_corrected_n2(self.number)),
+            self.get_target_temperature(context, engine.corrected_n2()),
         );
     }
 
@@ -161,15 +208,20 @@ impl IntegratedDriveGenerator {
             == INTEGRATED_DRIVE_GENERATOR_STABILIZATION_TIME_IN_MILLISECONDS
     }
 
-    fn update_stable_time(&mut self, context: &UpdateContext, corrected_n2: Ratio) {
+    fn update_stable_time(&mut self, context: &UpdateContext, engine: &Engine) {
         if !self.connected {
             self.time_above_threshold_in_milliseconds = 0;
             return;
         }
 
+        // The generator only stabilises once the engine completed its start.
+        let corrected_n2 = engine.corrected_n2();
         let mut new_time = self.time_above_threshold_in_milliseconds;
-        if corrected_n2
-            >= Ratio::new::<percent>(IntegratedDriveGenerator::ENGINE_N2_POWER_UP_OUTPUT_THRESHOLD)
+        if engine.is_running()
+            && corrected_n2
+                >= Ratio::new::<percent>(
+                    IntegratedDriveGenerator::ENGINE_N2_POWER_UP_OUTPUT_THRESHOLD,
+                )
             && self.time_above_threshold_in_milliseconds
                 < INTEGRATED_DRIVE_GENERATOR_STABILIZATION_TIME_IN_MILLISECONDS
         {
@@ -231,11 +283,33 @@ impl IntegratedDriveGenerator {
 }
 impl SimulationElement for IntegratedDriveGenerator {
     fn write(&self, writer: &mut SimulatorWriter) {
-        writer.write_f64(
-            &self.oil_outlet_temperature_id,
+        writer.write_temperature(&self.oil_outlet_temperature_id, self.oil_outlet_temperature);
+        writer.write_bool(&self.is_connected_id, self.connected);
+    }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        snapshot.save_f64(
+            self.oil_outlet_temperature_id.name(),
             self.oil_outlet_temperature.get::<degree_celsius>(),
         );
-        writer.write_bool(&self.is_connected_id, self.connected);
+        snapshot.save_bool(self.is_connected_id.name(), self.connected);
+        snapshot.save_f64(
+            &self.time_above_threshold_id,
+            self.time_above_threshold_in_milliseconds as f64,
+        );
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        if let Some(temperature) = snapshot.restore_f64(self.oil_outlet_temperature_id.name()) {
+            self.oil_outlet_temperature =
+                ThermodynamicTemperature::new::<degree_celsius>(temperature);
+        }
+        if let Some(connected) = snapshot.restore_bool(self.is_connected_id.name()) {
+            self.connected = connected;
+        }
+        if let Some(time_above_threshold) = snapshot.restore_f64(&self.time_above_threshold_id) {
+            self.time_above_threshold_in_milliseconds = time_above_threshold as u64;
+        }
     }
 }
 
@@ -255,28 +329,33 @@ fn clamp<T: PartialOrd>(value: T, min: T, max: T) -> T {
 mod tests {
     use super::*;
 
+    use crate::simulation::test::SimulationTestBed;
+
     struct UpdateArguments {
-        engine_corrected_n2: Ratio,
         idg_push_button_released: bool,
     }
     impl UpdateArguments {
-        fn new(engine_corrected_n2: Ratio, idg_push_button_released: bool) -> Self {
+        fn new(idg_push_button_released: bool) -> Self {
             Self {
-                engine_corrected_n2,
                 idg_push_button_released,
             }
         }
     }
     impl EngineGeneratorUpdateArguments for UpdateArguments {
-        fn engine_corrected_n2(&self, _: usize) -> Ratio {
-            self.engine_corrected_n2
-        }
-
         fn idg_push_button_released(&self, _: usize) -> bool {
             self.idg_push_button_released
         }
     }
 
+    fn engine(corrected_n2: f64) -> Engine {
+        let mut engine = Engine::new(&mut VariableRegistry::new(), 1);
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.write_f64("TURB ENG CORRECTED N2:1", corrected_n2);
+        test_bed.run(&mut engine, |element, context| element.update(context));
+
+        engine
+    }
+
     #[cfg(test)]
     mod engine_generator_tests {
         use super::*;
@@ -285,7 +364,7 @@ mod tests {
                 consumption::{PowerConsumer, SuppliedPower},
                 ElectricalBusType,
             },
-            simulation::{test::SimulationTestBed, Aircraft},
+            simulation::Aircraft,
         };
 
         struct EngineGeneratorTestBed {
@@ -328,7 +407,7 @@ mod tests {
         impl TestAircraft {
             fn new(running: bool) -> Self {
                 Self {
-                    engine_gen: EngineGenerator::new(1),
+                    engine_gen: EngineGenerator::new(&mut VariableRegistry::new(), 1),
                     running,
                     idg_push_button_released: false,
                     consumer: PowerConsumer::from(ElectricalBusType::AlternatingCurrent(1)),
@@ -363,10 +442,8 @@ mod tests {
             fn update_before_power_distribution(&mut self, context: &UpdateContext) {
                 self.engine_gen.update(
                     context,
-                    &UpdateArguments::new(
-                        Ratio::new::<percent>(if self.running { 80. } else { 0. }),
-                        self.idg_push_button_released,
-                    ),
+                    &engine(if self.running { 80. } else { 0. }),
+                    &UpdateArguments::new(self.idg_push_button_released),
                 );
             }
 
@@ -567,13 +644,11 @@ mod tests {
 
     #[cfg(test)]
     mod integrated_drive_generator_tests {
-        use crate::simulation::test::SimulationTestBed;
-
         use super::*;
         use std::time::Duration;
 
         fn idg() -> IntegratedDriveGenerator {
-            IntegratedDriveGenerator::new(1)
+            IntegratedDriveGenerator::new(&mut VariableRegistry::new(), 1)
         }
 
         #[test]
@@ -598,10 +673,7 @@ mod tests {
             let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_millis(500));
 
             test_bed.run(&mut idg, |element, context| {
-                element.update(
-                    context,
-                    &UpdateArguments::new(Ratio::new::<percent>(80.), false),
-                )
+                element.update(context, &engine(80.), &UpdateArguments::new(false))
             });
 
             assert_eq!(idg.provides_stable_power_output(), true);
@@ -613,10 +685,7 @@ mod tests {
             let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_millis(499));
 
             test_bed.run(&mut idg, |element, context| {
-                element.update(
-                    context,
-                    &UpdateArguments::new(Ratio::new::<percent>(80.), false),
-                )
+                element.update(context, &engine(80.), &UpdateArguments::new(false))
             });
 
             assert_eq!(idg.provides_stable_power_output(), false);
@@ -627,17 +696,11 @@ mod tests {
             let mut idg = idg();
             let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_millis(500));
             test_bed.run(&mut idg, |element, context| {
-                element.update(
-                    context,
-                    &UpdateArguments::new(Ratio::new::<percent>(80.), true),
-                )
+                element.update(context, &engine(80.), &UpdateArguments::new(true))
             });
 
             test_bed.run(&mut idg, |element, context| {
-                element.update(
-                    context,
-                    &UpdateArguments::new(Ratio::new::<percent>(80.), false),
-                )
+                element.update(context, &engine(80.), &UpdateArguments::new(false))
             });
 
             assert_eq!(idg.provides_stable_power_output(), false);
@@ -650,15 +713,35 @@ mod tests {
             let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_secs(10));
 
             test_bed.run(&mut idg, |element, context| {
-                element.update(
-                    context,
-                    &UpdateArguments::new(Ratio::new::<percent>(80.), false),
-                )
+                element.update(context, &engine(80.), &UpdateArguments::new(false))
             });
 
             assert!(idg.oil_outlet_temperature > starting_temperature);
         }
 
+        #[test]
+        fn idg_warms_up_equally_at_increased_simulation_rate_with_sub_steps() {
+            let warm_up = |simulation_rate: f64, delta: Duration, ticks: usize| {
+                let mut idg = idg();
+                let mut test_bed = SimulationTestBed::new_with_delta(delta);
+                test_bed.set_simulation_rate(simulation_rate);
+                test_bed.set_max_step(Duration::from_secs(1));
+
+                for _ in 0..ticks {
+                    test_bed.run(&mut idg, |element, context| {
+                        element.update(context, &engine(80.), &UpdateArguments::new(false))
+                    });
+                }
+
+                idg.oil_outlet_temperature
+            };
+
+            assert_eq!(
+                warm_up(4., Duration::from_secs(5), 1),
+                warm_up(1., Duration::from_secs(5), 4)
+            );
+        }
+
         #[test]
         fn running_engine_does_not_warm_up_idg_when_disconnected() {
             let mut idg = idg();
@@ -666,10 +749,7 @@ mod tests {
             let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_secs(10));
 
             test_bed.run(&mut idg, |element, context| {
-                element.update(
-                    context,
-                    &UpdateArguments::new(Ratio::new::<percent>(80.), true),
-                )
+                element.update(context, &engine(80.), &UpdateArguments::new(true))
             });
 
             assert_eq!(idg.oil_outlet_temperature, starting_temperature);
@@ -681,22 +761,51 @@ mod tests {
             let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_secs(10));
 
             test_bed.run(&mut idg, |element, context| {
-                element.update(
-                    context,
-                    &UpdateArguments::new(Ratio::new::<percent>(80.), false),
-                )
+                element.update(context, &engine(80.), &UpdateArguments::new(false))
             });
 
             let starting_temperature = idg.oil_outlet_temperature;
 
             test_bed.run(&mut idg, |element, context| {
-                element.update(
-                    context,
-                    &UpdateArguments::new(Ratio::new::<percent>(0.), false),
-                )
+                element.update(context, &engine(0.), &UpdateArguments::new(false))
             });
 
             assert!(idg.oil_outlet_temperature < starting_temperature);
         }
+
+        #[test]
+        fn oil_temperature_and_connection_are_restored_from_snapshot() {
+            let mut idg = idg();
+            let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_secs(10));
+            test_bed.run(&mut idg, |element, context| {
+                element.update(context, &engine(80.), &UpdateArguments::new(false))
+            });
+            test_bed.run(&mut idg, |element, context| {
+                element.update(context, &engine(80.), &UpdateArguments::new(true))
+            });
+
+            let mut restored_idg = self::idg();
+            Snapshot::take(&mut idg).restore(&mut restored_idg);
+
+            assert_eq!(
+                restored_idg.oil_outlet_temperature,
+                idg.oil_outlet_temperature
+            );
+            assert!(!restored_idg.connected);
+        }
+
+        #[test]
+        fn stable_power_output_is_restored_from_snapshot() {
+            let mut idg = idg();
+            let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_millis(500));
+            test_bed.run(&mut idg, |element, context| {
+                element.update(context, &engine(80.), &UpdateArguments::new(false))
+            });
+
+            let mut restored_idg = self::idg();
+            Snapshot::take(&mut idg).restore(&mut restored_idg);
+
+            assert!(restored_idg.provides_stable_power_output());
+        }
     }
 }
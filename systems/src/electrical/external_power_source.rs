@@ -1,4 +1,7 @@
-use crate::simulation::{SimulationElement, SimulatorReader, SimulatorWriter, UpdateContext};
+use crate::simulation::{
+    QuantityIdentifier, SimulationElement, SimulatorReader, SimulatorWriter, UpdateContext,
+    VariableRegistry, VariableUnit,
+};
 use uom::si::{electric_potential::volt, f64::*, frequency::hertz};
 
 use super::{
@@ -8,14 +11,17 @@ use super::{
 
 pub struct ExternalPowerSource {
     writer: ElectricalStateWriter,
+    is_connected_id: QuantityIdentifier<bool>,
     is_connected: bool,
     output_frequency: Frequency,
     output_potential: ElectricPotential,
 }
 impl ExternalPowerSource {
-    pub fn new() -> ExternalPowerSource {
+    pub fn new(registry: &mut VariableRegistry) -> ExternalPowerSource {
         ExternalPowerSource {
-            writer: ElectricalStateWriter::new("EXT_PWR"),
+            writer: ElectricalStateWriter::new(registry, "EXT_PWR"),
+            is_connected_id: registry
+                .register_read("EXTERNAL POWER AVAILABLE:1", VariableUnit::Bool),
             is_connected: false,
             output_frequency: Frequency::new::<hertz>(0.),
             output_potential: ElectricPotential::new::<volt>(0.),
@@ -48,7 +54,7 @@ provide_potential!(ExternalPowerSource, (110.0..=120.0));
 provide_frequency!(ExternalPowerSource, (390.0..=410.0));
 impl SimulationElement for ExternalPowerSource {
     fn read(&mut self, reader: &mut SimulatorReader) {
-        self.is_connected = reader.read_bool("EXTERNAL POWER AVAILABLE:1");
+        self.is_connected = reader.read_bool(&self.is_connected_id);
     }
 
     fn write(&self, writer: &mut SimulatorWriter) {
@@ -72,11 +78,6 @@ impl SimulationElement for ExternalPowerSource {
         };
     }
 }
-impl Default for ExternalPowerSource {
-    fn default() -> Self {
-        Self::new()
-    }
-}
 
 #[cfg(test)]
 mod external_power_source_tests {
@@ -123,7 +124,7 @@ mod external_power_source_tests {
     impl TestAircraft {
         fn new() -> Self {
             Self {
-                ext_pwr: ExternalPowerSource::new(),
+                ext_pwr: ExternalPowerSource::new(&mut VariableRegistry::new()),
             }
         }
 
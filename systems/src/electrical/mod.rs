@@ -22,10 +22,10 @@ use itertools::Itertools;
 pub use static_inverter::StaticInverter;
 pub use transformer_rectifier::TransformerRectifier;
 
-use crate::simulation::{SimulationElement, SimulatorWriter};
-use uom::si::{
-    electric_current::ampere, electric_potential::volt, f64::*, frequency::hertz, ratio::percent,
+use crate::simulation::{
+    QuantityIdentifier, SimulationElement, SimulatorWriter, VariableRegistry, VariableUnit,
 };
+use uom::si::{electric_potential::volt, f64::*};
 
 use self::consumption::SuppliedPower;
 
@@ -242,14 +242,17 @@ pub trait PotentialTarget {
 /// When closed a contactor conducts the potential towards other targets.
 #[derive(Debug)]
 pub struct Contactor {
-    closed_id: String,
+    closed_id: QuantityIdentifier<bool>,
     closed: bool,
     input_potential: Potential,
 }
 impl Contactor {
-    pub fn new(id: &str) -> Contactor {
+    pub fn new(registry: &mut VariableRegistry, id: &str) -> Contactor {
         Contactor {
-            closed_id: format!("ELEC_CONTACTOR_{}_IS_CLOSED", id),
+            closed_id: registry.register_write(
+                &format!("ELEC_CONTACTOR_{}_IS_CLOSED", id),
+                VariableUnit::Bool,
+            ),
             closed: false,
             input_potential: Potential::none(),
         }
@@ -314,16 +317,22 @@ impl Display for ElectricalBusType {
 }
 
 pub struct ElectricalBus {
-    bus_powered_id: String,
-    bus_potential_normal_id: String,
+    bus_powered_id: QuantityIdentifier<bool>,
+    bus_potential_normal_id: QuantityIdentifier<bool>,
     input_potential: Potential,
     bus_type: ElectricalBusType,
 }
 impl ElectricalBus {
-    pub fn new(bus_type: ElectricalBusType) -> ElectricalBus {
+    pub fn new(registry: &mut VariableRegistry, bus_type: ElectricalBusType) -> ElectricalBus {
         ElectricalBus {
-            bus_powered_id: format!("ELEC_{}_BUS_IS_POWERED", bus_type.to_string()),
-            bus_potential_normal_id: format!("ELEC_{}_BUS_POTENTIAL_NORMAL", bus_type.to_string()),
+            bus_powered_id: registry.register_write(
+                &format!("ELEC_{}_BUS_IS_POWERED", bus_type),
+                VariableUnit::Bool,
+            ),
+            bus_potential_normal_id: registry.register_write(
+                &format!("ELEC_{}_BUS_POTENTIAL_NORMAL", bus_type),
+                VariableUnit::Bool,
+            ),
             input_potential: Potential::none(),
             bus_type,
         }
@@ -376,26 +385,30 @@ impl SimulationElement for ElectricalBus {
 }
 
 pub struct ElectricalStateWriter {
-    current_id: String,
-    current_normal_id: String,
-    potential_id: String,
-    potential_normal_id: String,
-    frequency_id: String,
-    frequency_normal_id: String,
-    load_id: String,
-    load_normal_id: String,
+    current_id: QuantityIdentifier<ElectricCurrent>,
+    current_normal_id: QuantityIdentifier<bool>,
+    potential_id: QuantityIdentifier<ElectricPotential>,
+    potential_normal_id: QuantityIdentifier<bool>,
+    frequency_id: QuantityIdentifier<Frequency>,
+    frequency_normal_id: QuantityIdentifier<bool>,
+    load_id: QuantityIdentifier<Ratio>,
+    load_normal_id: QuantityIdentifier<bool>,
 }
 impl ElectricalStateWriter {
-    pub fn new(element_id: &str) -> Self {
+    pub fn new(registry: &mut VariableRegistry, element_id: &str) -> Self {
+        let name = |name: &str| format!("ELEC_{}_{}", element_id, name);
+
         Self {
-            current_id: format!("ELEC_{}_CURRENT", element_id),
-            current_normal_id: format!("ELEC_{}_CURRENT_NORMAL", element_id),
-            potential_id: format!("ELEC_{}_POTENTIAL", element_id),
-            potential_normal_id: format!("ELEC_{}_POTENTIAL_NORMAL", element_id),
-            frequency_id: format!("ELEC_{}_FREQUENCY", element_id),
-            frequency_normal_id: format!("ELEC_{}_FREQUENCY_NORMAL", element_id),
-            load_id: format!("ELEC_{}_LOAD", element_id),
-            load_normal_id: format!("ELEC_{}_LOAD_NORMAL", element_id),
+            current_id: registry.register_write(&name("CURRENT"), VariableUnit::Amperes),
+            current_normal_id: registry.register_write(&name("CURRENT_NORMAL"), VariableUnit::Bool),
+            potential_id: registry.register_write(&name("POTENTIAL"), VariableUnit::Volts),
+            potential_normal_id: registry
+                .register_write(&name("POTENTIAL_NORMAL"), VariableUnit::Bool),
+            frequency_id: registry.register_write(&name("FREQUENCY"), VariableUnit::Hertz),
+            frequency_normal_id: registry
+                .register_write(&name("FREQUENCY_NORMAL"), VariableUnit::Bool),
+            load_id: registry.register_write(&name("LOAD"), VariableUnit::Percent),
+            load_normal_id: registry.register_write(&name("LOAD_NORMAL"), VariableUnit::Bool),
         }
     }
 
@@ -427,22 +440,22 @@ impl ElectricalStateWriter {
     }
 
     fn write_current<T: ProvideCurrent>(&self, source: &T, writer: &mut SimulatorWriter) {
-        writer.write_f64(&self.current_id, source.current().get::<ampere>());
+        writer.write_current(&self.current_id, source.current());
         writer.write_bool(&self.current_normal_id, source.current_normal());
     }
 
     fn write_potential<T: ProvidePotential>(&self, source: &T, writer: &mut SimulatorWriter) {
-        writer.write_f64(&self.potential_id, source.potential().get::<volt>());
+        writer.write_potential(&self.potential_id, source.potential());
         writer.write_bool(&self.potential_normal_id, source.potential_normal());
     }
 
     fn write_frequency<T: ProvideFrequency>(&self, source: &T, writer: &mut SimulatorWriter) {
-        writer.write_f64(&self.frequency_id, source.frequency().get::<hertz>());
+        writer.write_frequency(&self.frequency_id, source.frequency());
         writer.write_bool(&self.frequency_normal_id, source.frequency_normal());
     }
 
     fn write_load<T: ProvideLoad>(&self, source: &T, writer: &mut SimulatorWriter) {
-        writer.write_f64(&self.load_id, source.load().get::<percent>());
+        writer.write_ratio(&self.load_id, source.load());
         writer.write_bool(&self.load_normal_id, source.load_normal());
     }
 }
@@ -469,7 +482,7 @@ pub trait ProvideLoad {
 
 #[cfg(test)]
 mod tests {
-    use uom::si::frequency::hertz;
+    use uom::si::{electric_current::ampere, frequency::hertz, ratio::percent};
 
     use super::*;
     struct Powerless {}
@@ -920,7 +933,7 @@ mod tests {
         impl ElectricalBusTestAircraft {
             fn new(bus_type: ElectricalBusType) -> Self {
                 Self {
-                    bus: ElectricalBus::new(bus_type),
+                    bus: ElectricalBus::new(&mut VariableRegistry::new(), bus_type),
                 }
             }
 
@@ -941,6 +954,8 @@ mod tests {
 
         #[test]
         fn or_powered_by_both_batteries_results_in_both() {
+            let mut registry = VariableRegistry::new();
+
             let potential = ElectricPotential::new::<volt>(28.);
             let bat_1 =
                 BatteryStub::new(Potential::single(PotentialOrigin::Battery(10), potential));
@@ -949,11 +964,11 @@ mod tests {
 
             let mut bus = electrical_bus();
 
-            let mut contactor_1 = Contactor::new("BAT1");
+            let mut contactor_1 = Contactor::new(&mut registry, "BAT1");
             contactor_1.powered_by(&bat_1);
             contactor_1.close_when(true);
 
-            let mut contactor_2 = Contactor::new("BAT2");
+            let mut contactor_2 = Contactor::new(&mut registry, "BAT2");
             contactor_2.powered_by(&bat_2);
             contactor_2.close_when(true);
 
@@ -1055,7 +1070,10 @@ mod tests {
 
         #[test]
         fn writes_potential_normal_when_bat_bus() {
-            let mut bus = ElectricalBus::new(ElectricalBusType::DirectCurrentBattery);
+            let mut bus = ElectricalBus::new(
+                &mut VariableRegistry::new(),
+                ElectricalBusType::DirectCurrentBattery,
+            );
 
             let mut test_bed = SimulationTestBed::new();
             test_bed.run_without_update(&mut bus);
@@ -1065,7 +1083,10 @@ mod tests {
 
         #[test]
         fn does_not_write_potential_normal_when_not_bat_bus() {
-            let mut bus = ElectricalBus::new(ElectricalBusType::AlternatingCurrentEssential);
+            let mut bus = ElectricalBus::new(
+                &mut VariableRegistry::new(),
+                ElectricalBusType::AlternatingCurrentEssential,
+            );
 
             let mut test_bed = SimulationTestBed::new();
             test_bed.run_without_update(&mut bus);
@@ -1078,11 +1099,11 @@ mod tests {
             bat_1: BatteryStub,
             bat_2: BatteryStub,
         ) {
-            let mut contactor_1 = Contactor::new("BAT1");
+            let mut contactor_1 = Contactor::new(&mut VariableRegistry::new(), "BAT1");
             contactor_1.powered_by(&bat_1);
             contactor_1.close_when(true);
 
-            let mut contactor_2 = Contactor::new("BAT2");
+            let mut contactor_2 = Contactor::new(&mut VariableRegistry::new(), "BAT2");
             contactor_2.powered_by(&bat_2);
             contactor_2.close_when(true);
 
@@ -1090,7 +1111,10 @@ mod tests {
         }
 
         fn electrical_bus() -> ElectricalBus {
-            ElectricalBus::new(ElectricalBusType::AlternatingCurrent(2))
+            ElectricalBus::new(
+                &mut VariableRegistry::new(),
+                ElectricalBusType::AlternatingCurrent(2),
+            )
         }
     }
 
@@ -1197,7 +1221,7 @@ mod tests {
         }
 
         fn contactor() -> Contactor {
-            Contactor::new("TEST")
+            Contactor::new(&mut VariableRegistry::new(), "TEST")
         }
 
         fn open_contactor() -> Contactor {
@@ -1234,7 +1258,7 @@ mod tests {
             fn new(write_type: WriteType) -> Self {
                 Self {
                     write_type,
-                    writer: ElectricalStateWriter::new("TEST"),
+                    writer: ElectricalStateWriter::new(&mut VariableRegistry::new(), "TEST"),
                 }
             }
         }
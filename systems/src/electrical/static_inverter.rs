@@ -3,7 +3,7 @@ use super::{
     ElectricalStateWriter, Potential, PotentialOrigin, PotentialSource, PotentialTarget,
     ProvideFrequency, ProvidePotential,
 };
-use crate::simulation::{SimulationElement, SimulatorWriter};
+use crate::simulation::{SimulationElement, SimulatorWriter, VariableRegistry};
 use uom::si::{electric_potential::volt, f64::*, frequency::hertz};
 
 pub struct StaticInverter {
@@ -13,9 +13,9 @@ pub struct StaticInverter {
     output_frequency: Frequency,
 }
 impl StaticInverter {
-    pub fn new() -> StaticInverter {
+    pub fn new(registry: &mut VariableRegistry) -> StaticInverter {
         StaticInverter {
-            writer: ElectricalStateWriter::new("STAT_INV"),
+            writer: ElectricalStateWriter::new(registry, "STAT_INV"),
             input_potential: Potential::none(),
             output_potential: ElectricPotential::new::<volt>(0.),
             output_frequency: Frequency::new::<hertz>(0.),
@@ -71,11 +71,6 @@ impl SimulationElement for StaticInverter {
         };
     }
 }
-impl Default for StaticInverter {
-    fn default() -> Self {
-        Self::new()
-    }
-}
 
 #[cfg(test)]
 mod static_inverter_tests {
@@ -138,7 +133,7 @@ mod static_inverter_tests {
     impl TestAircraft {
         fn new() -> Self {
             Self {
-                static_inverter: StaticInverter::new(),
+                static_inverter: StaticInverter::new(&mut VariableRegistry::new()),
                 consumer: PowerConsumer::from(ElectricalBusType::AlternatingCurrentEssential),
                 static_inverter_consumption: Power::new::<watt>(0.),
             }
@@ -3,39 +3,38 @@ use super::{
     ElectricalStateWriter, Potential, PotentialOrigin, PotentialSource, PotentialTarget,
     ProvideCurrent, ProvidePotential,
 };
-use crate::simulation::{SimulationElement, SimulatorWriter};
+use crate::simulation::{
+    Failure, FailureType, SimulationElement, SimulationElementVisitor, SimulatorWriter,
+    VariableRegistry,
+};
 use uom::si::{electric_current::ampere, electric_potential::volt, f64::*};
 
 pub struct TransformerRectifier {
     writer: ElectricalStateWriter,
     number: usize,
     input_potential: Potential,
-    failed: bool,
+    failure: Failure,
     output_potential: ElectricPotential,
     output_current: ElectricCurrent,
 }
 impl TransformerRectifier {
-    pub fn new(number: usize) -> TransformerRectifier {
+    pub fn new(registry: &mut VariableRegistry, number: usize) -> TransformerRectifier {
         TransformerRectifier {
-            writer: ElectricalStateWriter::new(&format!("TR_{}", number)),
+            writer: ElectricalStateWriter::new(registry, &format!("TR_{}", number)),
             number,
             input_potential: Potential::none(),
-            failed: false,
+            failure: Failure::new(registry, FailureType::TransformerRectifier(number)),
             output_potential: ElectricPotential::new::<volt>(0.),
             output_current: ElectricCurrent::new::<ampere>(0.),
         }
     }
 
-    pub fn fail(&mut self) {
-        self.failed = true;
-    }
-
     pub fn input_potential(&self) -> Potential {
         self.input_potential
     }
 
     fn should_provide_output(&self) -> bool {
-        !self.failed && self.input_potential.is_powered()
+        !self.failure.is_active() && self.input_potential.is_powered()
     }
 }
 potential_target!(TransformerRectifier);
@@ -62,6 +61,12 @@ impl ProvideCurrent for TransformerRectifier {
 }
 provide_potential!(TransformerRectifier, (25.0..=31.0));
 impl SimulationElement for TransformerRectifier {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.failure.accept(visitor);
+
+        visitor.visit(self);
+    }
+
     fn write(&self, writer: &mut SimulatorWriter) {
         self.writer.write_direct(self, writer);
     }
@@ -125,6 +130,11 @@ mod transformer_rectifier_tests {
             self.test_bed.read_bool("ELEC_TR_1_POTENTIAL_NORMAL")
         }
 
+        fn fail_transformer_rectifier(&mut self) {
+            self.test_bed
+                .activate_failure(FailureType::TransformerRectifier(1));
+        }
+
         fn current(&mut self) -> ElectricCurrent {
             ElectricCurrent::new::<ampere>(self.test_bed.read_f64("ELEC_TR_1_CURRENT"))
         }
@@ -138,7 +148,7 @@ mod transformer_rectifier_tests {
     impl TestAircraft {
         fn new() -> Self {
             Self {
-                transformer_rectifier: TransformerRectifier::new(1),
+                transformer_rectifier: TransformerRectifier::new(&mut VariableRegistry::new(), 1),
                 consumer: PowerConsumer::from(ElectricalBusType::DirectCurrent(1)),
                 transformer_rectifier_consumption: Power::new::<watt>(0.),
             }
@@ -154,10 +164,6 @@ mod transformer_rectifier_tests {
             self
         }
 
-        fn fail_transformer_rectifier(&mut self) {
-            self.transformer_rectifier.fail();
-        }
-
         fn transformer_rectifier_is_powered(&self) -> bool {
             self.transformer_rectifier.is_powered()
         }
@@ -242,7 +248,7 @@ mod transformer_rectifier_tests {
         let mut aircraft = TestAircraft::new().with_powered_transformer_rectifier();
         let mut test_bed = TransformerRectifierTestBed::new();
 
-        aircraft.fail_transformer_rectifier();
+        test_bed.fail_transformer_rectifier();
         test_bed.run_aircraft(&mut aircraft);
 
         assert!(!aircraft.transformer_rectifier_is_powered());
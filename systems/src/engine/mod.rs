@@ -1,27 +1,464 @@
-use uom::si::{f64::*, ratio::percent};
+use uom::si::{
+    f64::*,
+    mass_rate::{kilogram_per_second, pound_per_hour},
+    power::kilowatt,
+    pressure::psi,
+    ratio::percent,
+    thermodynamic_temperature::degree_celsius,
+};
 
-use crate::simulation::{SimulationElement, SimulatorReader, UpdateContext};
+use crate::{
+    electrical::{consumption::PowerConsumptionReport, PotentialOrigin},
+    simulation::{
+        QuantityIdentifier, SimulationElement, SimulatorReader, SimulatorWriter, Snapshot,
+        UpdateContext, VariableRegistry, VariableUnit,
+    },
+};
 
+mod start;
+pub use start::{EngineModeSelectorPosition, EngineStartSystem, Igniter, PneumaticStarter};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EngineState {
+    Off,
+    Starting,
+    Running,
+    ShuttingDown,
+}
+
+/// An engine. The simulator runs the engine itself, this type reads the engine's state
+/// from the simulator and adds the effect of the loads placed upon it by the aircraft's
+/// systems: the bleed air it supplies and the power its generator supplies.
 pub struct Engine {
-    corrected_n2_id: String,
+    number: usize,
+    n1_id: QuantityIdentifier<Ratio>,
+    corrected_n2_id: QuantityIdentifier<Ratio>,
+    egt_id: QuantityIdentifier<ThermodynamicTemperature>,
+    fuel_flow_id: QuantityIdentifier<MassRate>,
+    oil_pressure_id: QuantityIdentifier<Pressure>,
+    oil_temperature_id: QuantityIdentifier<ThermodynamicTemperature>,
+    state_id: QuantityIdentifier<f64>,
+    loaded_egt_id: QuantityIdentifier<ThermodynamicTemperature>,
+    loaded_fuel_flow_id: QuantityIdentifier<MassRate>,
+    n1: Ratio,
     corrected_n2: Ratio,
+    previous_corrected_n2: Ratio,
+    egt: ThermodynamicTemperature,
+    fuel_flow: MassRate,
+    oil_pressure: Pressure,
+    oil_temperature: ThermodynamicTemperature,
+    state: EngineState,
+    bleed_air_load: MassRate,
+    generator_load: Power,
 }
 impl Engine {
-    pub fn new(number: usize) -> Engine {
+    const IDLE_N2_PERCENT: f64 = 58.;
+    /// Below this N2 the engine is considered to be at a standstill.
+    const ROTATING_N2_PERCENT: f64 = 1.;
+    const EGT_INCREASE_PER_BLEED_AIR_KILOGRAM_PER_SECOND: f64 = 25.;
+    const EGT_INCREASE_PER_GENERATOR_KILOWATT: f64 = 0.1;
+    const FUEL_FLOW_INCREASE_PER_BLEED_AIR_KILOGRAM_PER_SECOND: f64 = 180.;
+    const FUEL_FLOW_INCREASE_PER_GENERATOR_KILOWATT: f64 = 1.;
+
+    pub fn new(registry: &mut VariableRegistry, number: usize) -> Engine {
         Engine {
-            corrected_n2_id: format!("TURB ENG CORRECTED N2:{}", number),
+            number,
+            n1_id: registry
+                .register_read(&format!("TURB ENG N1:{}", number), VariableUnit::Percent),
+            corrected_n2_id: registry.register_read(
+                &format!("TURB ENG CORRECTED N2:{}", number),
+                VariableUnit::Percent,
+            ),
+            egt_id: registry.register_read(
+                &format!("GENERAL ENG EXHAUST GAS TEMPERATURE:{}", number),
+                VariableUnit::Celsius,
+            ),
+            fuel_flow_id: registry.register_read(
+                &format!("TURB ENG FUEL FLOW PPH:{}", number),
+                VariableUnit::PoundsPerHour,
+            ),
+            oil_pressure_id: registry.register_read(
+                &format!("GENERAL ENG OIL PRESSURE:{}", number),
+                VariableUnit::Psi,
+            ),
+            oil_temperature_id: registry.register_read(
+                &format!("GENERAL ENG OIL TEMPERATURE:{}", number),
+                VariableUnit::Celsius,
+            ),
+            state_id: registry
+                .register_write(&format!("ENG_{}_STATE", number), VariableUnit::Number),
+            loaded_egt_id: registry
+                .register_write(&format!("ENG_{}_EGT", number), VariableUnit::Celsius),
+            loaded_fuel_flow_id: registry.register_write(
+                &format!("ENG_{}_FUEL_FLOW", number),
+                VariableUnit::PoundsPerHour,
+            ),
+            n1: Ratio::new::<percent>(0.),
             corrected_n2: Ratio::new::<percent>(0.),
+            previous_corrected_n2: Ratio::new::<percent>(0.),
+            egt: ThermodynamicTemperature::new::<degree_celsius>(0.),
+            fuel_flow: MassRate::new::<pound_per_hour>(0.),
+            oil_pressure: Pressure::new::<psi>(0.),
+            oil_temperature: ThermodynamicTemperature::new::<degree_celsius>(0.),
+            state: EngineState::Off,
+            bleed_air_load: MassRate::new::<kilogram_per_second>(0.),
+            generator_load: Power::new::<kilowatt>(0.),
         }
     }
 
+    pub fn update(&mut self, _: &UpdateContext) {
+        self.state = if self.corrected_n2 >= Ratio::new::<percent>(Engine::IDLE_N2_PERCENT) {
+            EngineState::Running
+        } else if self.corrected_n2 < Ratio::new::<percent>(Engine::ROTATING_N2_PERCENT) {
+            EngineState::Off
+        } else if self.state == EngineState::Running
+            || self.corrected_n2 < self.previous_corrected_n2
+        {
+            EngineState::ShuttingDown
+        } else if self.corrected_n2 > self.previous_corrected_n2 {
+            EngineState::Starting
+        } else {
+            self.state
+        };
+
+        self.previous_corrected_n2 = self.corrected_n2;
+    }
+
+    pub fn n1(&self) -> Ratio {
+        self.n1
+    }
+
     pub fn corrected_n2(&self) -> Ratio {
         self.corrected_n2
     }
 
-    pub fn update(&mut self, _: &UpdateContext) {}
+    /// The exhaust gas temperature, including the effect of the loads on the engine.
+    pub fn egt(&self) -> ThermodynamicTemperature {
+        if self.is_rotating() {
+            ThermodynamicTemperature::new::<degree_celsius>(
+                self.egt.get::<degree_celsius>()
+                    + self.bleed_air_load.get::<kilogram_per_second>()
+                        * Engine::EGT_INCREASE_PER_BLEED_AIR_KILOGRAM_PER_SECOND
+                    + self.generator_load.get::<kilowatt>()
+                        * Engine::EGT_INCREASE_PER_GENERATOR_KILOWATT,
+            )
+        } else {
+            self.egt
+        }
+    }
+
+    /// The fuel flow, including the effect of the loads on the engine.
+    pub fn fuel_flow(&self) -> MassRate {
+        if self.is_rotating() {
+            MassRate::new::<pound_per_hour>(
+                self.fuel_flow.get::<pound_per_hour>()
+                    + self.bleed_air_load.get::<kilogram_per_second>()
+                        * Engine::FUEL_FLOW_INCREASE_PER_BLEED_AIR_KILOGRAM_PER_SECOND
+                    + self.generator_load.get::<kilowatt>()
+                        * Engine::FUEL_FLOW_INCREASE_PER_GENERATOR_KILOWATT,
+            )
+        } else {
+            self.fuel_flow
+        }
+    }
+
+    pub fn oil_pressure(&self) -> Pressure {
+        self.oil_pressure
+    }
+
+    pub fn oil_temperature(&self) -> ThermodynamicTemperature {
+        self.oil_temperature
+    }
+
+    pub fn state(&self) -> EngineState {
+        self.state
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.state == EngineState::Running
+    }
+
+    /// Sets the bleed air flow taken from the engine.
+    pub fn set_bleed_air_load(&mut self, flow: MassRate) {
+        self.bleed_air_load = flow;
+    }
+
+    fn is_rotating(&self) -> bool {
+        self.state != EngineState::Off
+    }
+
+    fn state_value(&self) -> f64 {
+        match self.state {
+            EngineState::Off => 0.,
+            EngineState::Starting => 1.,
+            EngineState::Running => 2.,
+            EngineState::ShuttingDown => 3.,
+        }
+    }
 }
 impl SimulationElement for Engine {
     fn read(&mut self, reader: &mut SimulatorReader) {
-        self.corrected_n2 = Ratio::new::<percent>(reader.read_f64(&self.corrected_n2_id));
+        self.n1 = reader.read_ratio(&self.n1_id);
+        self.corrected_n2 = reader.read_ratio(&self.corrected_n2_id);
+        self.egt = reader.read_temperature(&self.egt_id);
+        self.fuel_flow = reader.read_mass_rate(&self.fuel_flow_id);
+        self.oil_pressure = reader.read_pressure(&self.oil_pressure_id);
+        self.oil_temperature = reader.read_temperature(&self.oil_temperature_id);
+    }
+
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_f64(&self.state_id, self.state_value());
+        writer.write_temperature(&self.loaded_egt_id, self.egt());
+        writer.write_mass_rate(&self.loaded_fuel_flow_id, self.fuel_flow());
+    }
+
+    fn process_power_consumption_report<T: PowerConsumptionReport>(&mut self, report: &T) {
+        self.generator_load =
+            report.total_consumption_of(PotentialOrigin::EngineGenerator(self.number));
+    }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        snapshot.save_f64(self.state_id.name(), self.state_value());
+        snapshot.save_f64(
+            &format!("ENG_{}_PREVIOUS_CORRECTED_N2", self.number),
+            self.previous_corrected_n2.get::<percent>(),
+        );
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        if let Some(state) = snapshot.restore_f64(self.state_id.name()) {
+            self.state = match state as u8 {
+                1 => EngineState::Starting,
+                2 => EngineState::Running,
+                3 => EngineState::ShuttingDown,
+                _ => EngineState::Off,
+            };
+        }
+        if let Some(previous_corrected_n2) =
+            snapshot.restore_f64(&format!("ENG_{}_PREVIOUS_CORRECTED_N2", self.number))
+        {
+            self.previous_corrected_n2 = Ratio::new::<percent>(previous_corrected_n2);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        electrical::{
+            consumption::{PowerConsumer, SuppliedPower},
+            ElectricalBusType, Potential,
+        },
+        simulation::{test::SimulationTestBed, Aircraft, SimulationElementVisitor},
+    };
+    use uom::si::{electric_potential::volt, power::watt};
+
+    struct TestAircraft {
+        engine: Engine,
+        consumer: PowerConsumer,
+    }
+    impl TestAircraft {
+        fn new() -> Self {
+            Self {
+                engine: Engine::new(&mut VariableRegistry::new(), 1),
+                consumer: PowerConsumer::from(ElectricalBusType::AlternatingCurrent(1)),
+            }
+        }
+
+        fn with_generator_load(mut self, load: Power) -> Self {
+            self.consumer.demand(load);
+            self
+        }
+    }
+    impl Aircraft for TestAircraft {
+        fn update_before_power_distribution(&mut self, context: &UpdateContext) {
+            self.engine.update(context);
+        }
+
+        fn get_supplied_power(&mut self) -> SuppliedPower {
+            let mut supplied_power = SuppliedPower::new();
+            supplied_power.add(
+                ElectricalBusType::AlternatingCurrent(1),
+                Potential::single(
+                    PotentialOrigin::EngineGenerator(1),
+                    ElectricPotential::new::<volt>(115.),
+                ),
+            );
+
+            supplied_power
+        }
+    }
+    impl SimulationElement for TestAircraft {
+        fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            self.engine.accept(visitor);
+            self.consumer.accept(visitor);
+
+            visitor.visit(self);
+        }
+    }
+
+    fn run_with_n2(
+        aircraft: &mut TestAircraft,
+        test_bed: &mut SimulationTestBed,
+        corrected_n2: f64,
+    ) {
+        test_bed.write_f64("TURB ENG CORRECTED N2:1", corrected_n2);
+        test_bed.run_aircraft(aircraft);
+    }
+
+    #[test]
+    fn reads_its_state_from_the_simulator() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.write_f64("TURB ENG N1:1", 20.);
+        test_bed.write_f64("TURB ENG CORRECTED N2:1", 60.);
+        test_bed.write_f64("GENERAL ENG OIL PRESSURE:1", 40.);
+        test_bed.write_f64("GENERAL ENG OIL TEMPERATURE:1", 80.);
+
+        test_bed.run_aircraft(&mut aircraft);
+
+        assert_eq!(aircraft.engine.n1(), Ratio::new::<percent>(20.));
+        assert_eq!(aircraft.engine.corrected_n2(), Ratio::new::<percent>(60.));
+        assert_eq!(aircraft.engine.oil_pressure(), Pressure::new::<psi>(40.));
+        assert_eq!(
+            aircraft.engine.oil_temperature(),
+            ThermodynamicTemperature::new::<degree_celsius>(80.)
+        );
+    }
+
+    #[test]
+    fn is_off_when_not_rotating() {
+        let mut aircraft = TestAircraft::new();
+        run_with_n2(&mut aircraft, &mut SimulationTestBed::new(), 0.);
+
+        assert_eq!(aircraft.engine.state(), EngineState::Off);
+        assert!(!aircraft.engine.is_running());
+    }
+
+    #[test]
+    fn is_starting_while_n2_increases_below_idle() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+        run_with_n2(&mut aircraft, &mut test_bed, 10.);
+        run_with_n2(&mut aircraft, &mut test_bed, 20.);
+
+        assert_eq!(aircraft.engine.state(), EngineState::Starting);
+    }
+
+    #[test]
+    fn is_running_from_idle() {
+        let mut aircraft = TestAircraft::new();
+        run_with_n2(&mut aircraft, &mut SimulationTestBed::new(), 58.);
+
+        assert_eq!(aircraft.engine.state(), EngineState::Running);
+        assert!(aircraft.engine.is_running());
+    }
+
+    #[test]
+    fn is_shutting_down_when_n2_drops_below_idle_after_running() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+        run_with_n2(&mut aircraft, &mut test_bed, 60.);
+        run_with_n2(&mut aircraft, &mut test_bed, 50.);
+
+        assert_eq!(aircraft.engine.state(), EngineState::ShuttingDown);
+    }
+
+    #[test]
+    fn is_shutting_down_when_start_is_aborted() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+        run_with_n2(&mut aircraft, &mut test_bed, 20.);
+        run_with_n2(&mut aircraft, &mut test_bed, 30.);
+        run_with_n2(&mut aircraft, &mut test_bed, 25.);
+
+        assert_eq!(aircraft.engine.state(), EngineState::ShuttingDown);
+    }
+
+    #[test]
+    fn bleed_air_load_increases_egt_and_fuel_flow() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.write_f64("GENERAL ENG EXHAUST GAS TEMPERATURE:1", 400.);
+        test_bed.write_f64("TURB ENG FUEL FLOW PPH:1", 600.);
+        run_with_n2(&mut aircraft, &mut test_bed, 60.);
+
+        aircraft
+            .engine
+            .set_bleed_air_load(MassRate::new::<kilogram_per_second>(1.));
+
+        assert!(aircraft.engine.egt() > ThermodynamicTemperature::new::<degree_celsius>(400.));
+        assert!(aircraft.engine.fuel_flow() > MassRate::new::<pound_per_hour>(600.));
+    }
+
+    #[test]
+    fn generator_load_increases_egt_and_fuel_flow() {
+        let mut aircraft = TestAircraft::new().with_generator_load(Power::new::<watt>(50000.));
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.write_f64("GENERAL ENG EXHAUST GAS TEMPERATURE:1", 400.);
+        test_bed.write_f64("TURB ENG FUEL FLOW PPH:1", 600.);
+        run_with_n2(&mut aircraft, &mut test_bed, 60.);
+
+        assert!(aircraft.engine.egt() > ThermodynamicTemperature::new::<degree_celsius>(400.));
+        assert!(aircraft.engine.fuel_flow() > MassRate::new::<pound_per_hour>(600.));
+    }
+
+    #[test]
+    fn loads_have_no_effect_when_off() {
+        let mut aircraft = TestAircraft::new().with_generator_load(Power::new::<watt>(50000.));
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.write_f64("GENERAL ENG EXHAUST GAS TEMPERATURE:1", 15.);
+        run_with_n2(&mut aircraft, &mut test_bed, 0.);
+
+        aircraft
+            .engine
+            .set_bleed_air_load(MassRate::new::<kilogram_per_second>(1.));
+
+        assert_eq!(
+            aircraft.engine.egt(),
+            ThermodynamicTemperature::new::<degree_celsius>(15.)
+        );
+    }
+
+    #[test]
+    fn state_and_previous_corrected_n2_are_restored_from_snapshot() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+        run_with_n2(&mut aircraft, &mut test_bed, 30.);
+
+        let snapshot = Snapshot::take(&mut aircraft);
+        let mut restored_aircraft = TestAircraft::new();
+        snapshot.restore(&mut restored_aircraft);
+        assert_eq!(restored_aircraft.engine.state(), EngineState::Starting);
+
+        run_with_n2(&mut restored_aircraft, &mut test_bed, 25.);
+        assert_eq!(restored_aircraft.engine.state(), EngineState::ShuttingDown);
+    }
+
+    #[test]
+    fn values_read_from_the_simulator_are_not_part_of_the_snapshot() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+        run_with_n2(&mut aircraft, &mut test_bed, 60.);
+
+        let snapshot = Snapshot::take(&mut aircraft);
+
+        assert!(!snapshot.contains_key("TURB ENG N1:1"));
+        assert!(!snapshot.contains_key("TURB ENG CORRECTED N2:1"));
+        assert!(!snapshot.contains_key("GENERAL ENG EXHAUST GAS TEMPERATURE:1"));
+        assert!(!snapshot.contains_key("GENERAL ENG OIL PRESSURE:1"));
+    }
+
+    #[test]
+    fn writes_its_state() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+
+        test_bed.run_aircraft(&mut aircraft);
+
+        assert!(test_bed.contains_key("ENG_1_STATE"));
+        assert!(test_bed.contains_key("ENG_1_EGT"));
+        assert!(test_bed.contains_key("ENG_1_FUEL_FLOW"));
     }
 }
@@ -0,0 +1,613 @@
+use super::Engine;
+use crate::{
+    electrical::{consumption::PowerConsumer, ElectricalBusType},
+    pneumatic::Valve,
+    shared::LgciuWeightOnWheels,
+    simulation::{
+        QuantityIdentifier, SimulationElement, SimulationElementVisitor, SimulatorWriter, Snapshot,
+        UpdateContext, VariableRegistry, VariableUnit,
+    },
+};
+use uom::si::{f64::*, mass_rate::kilogram_per_second, power::watt, pressure::psi, ratio::percent};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EngineModeSelectorPosition {
+    Crank,
+    Norm,
+    IgnitionStart,
+}
+
+/// The pneumatic starter of an engine. The start valve is electrically controlled
+/// and pneumatically operated: it only opens when its solenoid is powered and the
+/// bleed air duct supplies sufficient pressure. Air flowing through the open valve
+/// drives the starter, which motors the engine when the pressure is sufficient.
+pub struct PneumaticStarter {
+    start_valve_open_id: QuantityIdentifier<bool>,
+    is_motoring_id: QuantityIdentifier<bool>,
+    start_valve_is_open: bool,
+    is_motoring: bool,
+    consumer: PowerConsumer,
+}
+impl PneumaticStarter {
+    const START_VALVE_MINIMUM_OPENING_PRESSURE_PSI: f64 = 8.;
+    const MINIMUM_MOTORING_PRESSURE_PSI: f64 = 25.;
+    const AIR_DEMAND_KILOGRAM_PER_SECOND: f64 = 0.6;
+    const START_VALVE_POWER_CONSUMPTION_WATT: f64 = 20.;
+
+    pub fn new(
+        registry: &mut VariableRegistry,
+        number: usize,
+        bus_type: ElectricalBusType,
+    ) -> PneumaticStarter {
+        PneumaticStarter {
+            start_valve_open_id: registry.register_write(
+                &format!("ENG_{}_START_VALVE_OPEN", number),
+                VariableUnit::Bool,
+            ),
+            is_motoring_id: registry.register_write(
+                &format!("ENG_{}_STARTER_IS_MOTORING", number),
+                VariableUnit::Bool,
+            ),
+            start_valve_is_open: false,
+            is_motoring: false,
+            consumer: PowerConsumer::from(bus_type),
+        }
+    }
+
+    pub fn update(&mut self, _: &UpdateContext, should_open: bool, duct_pressure: Pressure) {
+        let solenoid_is_energised = should_open && self.consumer.is_powered();
+        self.start_valve_is_open = solenoid_is_energised
+            && duct_pressure
+                >= Pressure::new::<psi>(PneumaticStarter::START_VALVE_MINIMUM_OPENING_PRESSURE_PSI);
+        self.is_motoring = self.start_valve_is_open
+            && duct_pressure
+                >= Pressure::new::<psi>(PneumaticStarter::MINIMUM_MOTORING_PRESSURE_PSI);
+
+        self.consumer.demand(if solenoid_is_energised {
+            Power::new::<watt>(PneumaticStarter::START_VALVE_POWER_CONSUMPTION_WATT)
+        } else {
+            Power::new::<watt>(0.)
+        });
+    }
+
+    pub fn is_motoring(&self) -> bool {
+        self.is_motoring
+    }
+
+    /// The bleed air flow the starter takes from the duct.
+    pub fn air_demand(&self) -> MassRate {
+        MassRate::new::<kilogram_per_second>(if self.start_valve_is_open {
+            PneumaticStarter::AIR_DEMAND_KILOGRAM_PER_SECOND
+        } else {
+            0.
+        })
+    }
+}
+impl Valve for PneumaticStarter {
+    fn is_open(&self) -> bool {
+        self.start_valve_is_open
+    }
+}
+impl SimulationElement for PneumaticStarter {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.consumer.accept(visitor);
+
+        visitor.visit(self);
+    }
+
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_bool(&self.start_valve_open_id, self.start_valve_is_open);
+        writer.write_bool(&self.is_motoring_id, self.is_motoring);
+    }
+}
+
+/// An igniter, supplied by its own ignition exciter.
+pub struct Igniter {
+    is_on_id: QuantityIdentifier<bool>,
+    is_on: bool,
+    consumer: PowerConsumer,
+}
+impl Igniter {
+    const POWER_CONSUMPTION_WATT: f64 = 50.;
+
+    pub fn new(
+        registry: &mut VariableRegistry,
+        number: usize,
+        id: &str,
+        bus_type: ElectricalBusType,
+    ) -> Igniter {
+        Igniter {
+            is_on_id: registry.register_write(
+                &format!("ENG_{}_IGNITER_{}_IS_ON", number, id),
+                VariableUnit::Bool,
+            ),
+            is_on: false,
+            consumer: PowerConsumer::from(bus_type),
+        }
+    }
+
+    pub fn update(&mut self, _: &UpdateContext, should_ignite: bool) {
+        self.is_on = should_ignite && self.consumer.is_powered();
+
+        self.consumer.demand(if self.is_on {
+            Power::new::<watt>(Igniter::POWER_CONSUMPTION_WATT)
+        } else {
+            Power::new::<watt>(0.)
+        });
+    }
+
+    pub fn is_on(&self) -> bool {
+        self.is_on
+    }
+}
+impl SimulationElement for Igniter {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.consumer.accept(visitor);
+
+        visitor.visit(self);
+    }
+
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_bool(&self.is_on_id, self.is_on);
+    }
+}
+
+/// The engine start system as controlled by the FADEC. With the mode selector in
+/// IGN/START and the master switch on, the FADEC runs the automatic start sequence:
+/// the start valve opens, ignition and fuel follow as N2 increases, and the start
+/// valve and ignition cut out once the engine is self-sustaining.
+/// With the mode selector in CRANK and the master switch off, the start valve opens
+/// to dry crank the engine: the starter motors it without ignition and fuel.
+/// On ground the FADEC alternates between the igniters on every start,
+/// in flight both igniters are used.
+pub struct EngineStartSystem {
+    number: usize,
+    fuel_valve_open_id: QuantityIdentifier<bool>,
+    starter: PneumaticStarter,
+    igniter_a: Igniter,
+    igniter_b: Igniter,
+    start_sequence_is_active: bool,
+    use_igniter_b: bool,
+    fuel_valve_is_open: bool,
+}
+impl EngineStartSystem {
+    const IGNITION_N2_PERCENT: f64 = 16.;
+    const FUEL_N2_PERCENT: f64 = 22.;
+    const STARTER_CUT_OUT_N2_PERCENT: f64 = 50.;
+
+    pub fn new(
+        registry: &mut VariableRegistry,
+        number: usize,
+        start_valve_bus_type: ElectricalBusType,
+        igniter_a_bus_type: ElectricalBusType,
+        igniter_b_bus_type: ElectricalBusType,
+    ) -> EngineStartSystem {
+        EngineStartSystem {
+            number,
+            fuel_valve_open_id: registry.register_write(
+                &format!("ENG_{}_FUEL_VALVE_OPEN", number),
+                VariableUnit::Bool,
+            ),
+            starter: PneumaticStarter::new(registry, number, start_valve_bus_type),
+            igniter_a: Igniter::new(registry, number, "A", igniter_a_bus_type),
+            igniter_b: Igniter::new(registry, number, "B", igniter_b_bus_type),
+            start_sequence_is_active: false,
+            use_igniter_b: false,
+            fuel_valve_is_open: false,
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        context: &UpdateContext,
+        mode: EngineModeSelectorPosition,
+        master_is_on: bool,
+        engine: &Engine,
+        duct_pressure: Pressure,
+        lgciu: &impl LgciuWeightOnWheels,
+    ) {
+        let is_on_ground = lgciu.left_and_right_gear_compressed();
+        let n2 = engine.corrected_n2();
+        let self_sustaining =
+            n2 >= Ratio::new::<percent>(EngineStartSystem::STARTER_CUT_OUT_N2_PERCENT);
+
+        let was_active = self.start_sequence_is_active;
+        self.start_sequence_is_active =
+            mode == EngineModeSelectorPosition::IgnitionStart && master_is_on && !self_sustaining;
+        if was_active && !self.start_sequence_is_active && is_on_ground {
+            self.use_igniter_b = !self.use_igniter_b;
+        }
+
+        let is_dry_cranking = mode == EngineModeSelectorPosition::Crank && !master_is_on;
+        self.starter.update(
+            context,
+            self.start_sequence_is_active || is_dry_cranking,
+            duct_pressure,
+        );
+
+        // In flight, IGN/START provides continuous ignition to running engines.
+        let should_ignite = (self.start_sequence_is_active
+            && n2 >= Ratio::new::<percent>(EngineStartSystem::IGNITION_N2_PERCENT))
+            || (mode == EngineModeSelectorPosition::IgnitionStart
+                && master_is_on
+                && engine.is_running()
+                && !is_on_ground);
+        let use_both_igniters = !is_on_ground;
+        self.igniter_a.update(
+            context,
+            should_ignite && (use_both_igniters || !self.use_igniter_b),
+        );
+        self.igniter_b.update(
+            context,
+            should_ignite && (use_both_igniters || self.use_igniter_b),
+        );
+
+        self.fuel_valve_is_open = master_is_on
+            && (!self.start_sequence_is_active
+                || n2 >= Ratio::new::<percent>(EngineStartSystem::FUEL_N2_PERCENT));
+    }
+
+    pub fn start_valve_is_open(&self) -> bool {
+        self.starter.is_open()
+    }
+
+    pub fn starter_is_motoring(&self) -> bool {
+        self.starter.is_motoring()
+    }
+
+    pub fn starter_air_demand(&self) -> MassRate {
+        self.starter.air_demand()
+    }
+
+    pub fn igniter_a_is_on(&self) -> bool {
+        self.igniter_a.is_on()
+    }
+
+    pub fn igniter_b_is_on(&self) -> bool {
+        self.igniter_b.is_on()
+    }
+
+    pub fn fuel_valve_is_open(&self) -> bool {
+        self.fuel_valve_is_open
+    }
+}
+impl SimulationElement for EngineStartSystem {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.starter.accept(visitor);
+        self.igniter_a.accept(visitor);
+        self.igniter_b.accept(visitor);
+
+        visitor.visit(self);
+    }
+
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_bool(&self.fuel_valve_open_id, self.fuel_valve_is_open);
+    }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        snapshot.save_bool(
+            &format!("ENG_{}_START_SEQUENCE_IS_ACTIVE", self.number),
+            self.start_sequence_is_active,
+        );
+        snapshot.save_bool(
+            &format!("ENG_{}_USE_IGNITER_B", self.number),
+            self.use_igniter_b,
+        );
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        if let Some(start_sequence_is_active) =
+            snapshot.restore_bool(&format!("ENG_{}_START_SEQUENCE_IS_ACTIVE", self.number))
+        {
+            self.start_sequence_is_active = start_sequence_is_active;
+        }
+        if let Some(use_igniter_b) =
+            snapshot.restore_bool(&format!("ENG_{}_USE_IGNITER_B", self.number))
+        {
+            self.use_igniter_b = use_igniter_b;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        electrical::{consumption::SuppliedPower, Potential, PotentialOrigin},
+        simulation::{test::SimulationTestBed, Aircraft},
+    };
+    use uom::si::electric_potential::volt;
+
+    struct TestLgciu {
+        compressed: bool,
+    }
+    impl LgciuWeightOnWheels for TestLgciu {
+        fn left_and_right_gear_compressed(&self) -> bool {
+            self.compressed
+        }
+    }
+
+    struct TestAircraft {
+        engine: Engine,
+        start: EngineStartSystem,
+        mode: EngineModeSelectorPosition,
+        master_is_on: bool,
+        duct_pressure: Pressure,
+        is_dc_powered: bool,
+        lgciu: TestLgciu,
+    }
+    impl TestAircraft {
+        fn new() -> Self {
+            let mut registry = VariableRegistry::new();
+
+            Self {
+                engine: Engine::new(&mut registry, 1),
+                start: EngineStartSystem::new(
+                    &mut registry,
+                    1,
+                    ElectricalBusType::DirectCurrentEssential,
+                    ElectricalBusType::AlternatingCurrentEssential,
+                    ElectricalBusType::AlternatingCurrent(1),
+                ),
+                mode: EngineModeSelectorPosition::Norm,
+                master_is_on: false,
+                duct_pressure: Pressure::new::<psi>(40.),
+                is_dc_powered: true,
+                lgciu: TestLgciu { compressed: true },
+            }
+        }
+
+        fn starting(mut self) -> Self {
+            self.mode = EngineModeSelectorPosition::IgnitionStart;
+            self.master_is_on = true;
+            self
+        }
+
+        fn with_mode(mut self, mode: EngineModeSelectorPosition) -> Self {
+            self.mode = mode;
+            self
+        }
+
+        fn with_duct_pressure(mut self, pressure: f64) -> Self {
+            self.duct_pressure = Pressure::new::<psi>(pressure);
+            self
+        }
+
+        fn without_dc_power(mut self) -> Self {
+            self.is_dc_powered = false;
+            self
+        }
+
+        fn in_flight(mut self) -> Self {
+            self.lgciu.compressed = false;
+            self
+        }
+    }
+    impl Aircraft for TestAircraft {
+        fn update_before_power_distribution(&mut self, context: &UpdateContext) {
+            self.engine.update(context);
+        }
+
+        fn update_after_power_distribution(&mut self, context: &UpdateContext) {
+            self.start.update(
+                context,
+                self.mode,
+                self.master_is_on,
+                &self.engine,
+                self.duct_pressure,
+                &self.lgciu,
+            );
+        }
+
+        fn get_supplied_power(&mut self) -> SuppliedPower {
+            let mut supplied_power = SuppliedPower::new();
+            let ac = Potential::single(
+                PotentialOrigin::ApuGenerator(1),
+                ElectricPotential::new::<volt>(115.),
+            );
+            supplied_power.add(ElectricalBusType::AlternatingCurrentEssential, ac);
+            supplied_power.add(ElectricalBusType::AlternatingCurrent(1), ac);
+            if self.is_dc_powered {
+                supplied_power.add(
+                    ElectricalBusType::DirectCurrentEssential,
+                    Potential::single(
+                        PotentialOrigin::Battery(10),
+                        ElectricPotential::new::<volt>(28.),
+                    ),
+                );
+            }
+
+            supplied_power
+        }
+    }
+    impl SimulationElement for TestAircraft {
+        fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            self.engine.accept(visitor);
+            self.start.accept(visitor);
+
+            visitor.visit(self);
+        }
+    }
+
+    fn run_with_n2(aircraft: &mut TestAircraft, test_bed: &mut SimulationTestBed, n2: f64) {
+        test_bed.write_f64("TURB ENG CORRECTED N2:1", n2);
+        test_bed.run_aircraft(aircraft);
+    }
+
+    #[test]
+    fn start_valve_opens_when_starting() {
+        let mut aircraft = TestAircraft::new().starting();
+        run_with_n2(&mut aircraft, &mut SimulationTestBed::new(), 0.);
+
+        assert!(aircraft.start.start_valve_is_open());
+        assert!(aircraft.start.starter_is_motoring());
+        assert!(aircraft.start.starter_air_demand() > MassRate::new::<kilogram_per_second>(0.));
+    }
+
+    #[test]
+    fn start_valve_remains_closed_in_norm() {
+        let mut aircraft = TestAircraft::new();
+        aircraft.master_is_on = true;
+        run_with_n2(&mut aircraft, &mut SimulationTestBed::new(), 0.);
+
+        assert!(!aircraft.start.start_valve_is_open());
+    }
+
+    #[test]
+    fn start_valve_remains_closed_without_duct_pressure() {
+        let mut aircraft = TestAircraft::new().starting().with_duct_pressure(0.);
+        run_with_n2(&mut aircraft, &mut SimulationTestBed::new(), 0.);
+
+        assert!(!aircraft.start.start_valve_is_open());
+        assert!(!aircraft.start.starter_is_motoring());
+    }
+
+    #[test]
+    fn start_valve_remains_closed_without_dc_power() {
+        let mut aircraft = TestAircraft::new().starting().without_dc_power();
+        run_with_n2(&mut aircraft, &mut SimulationTestBed::new(), 0.);
+
+        assert!(!aircraft.start.start_valve_is_open());
+    }
+
+    #[test]
+    fn starter_does_not_motor_with_insufficient_duct_pressure() {
+        let mut aircraft = TestAircraft::new().starting().with_duct_pressure(15.);
+        run_with_n2(&mut aircraft, &mut SimulationTestBed::new(), 0.);
+
+        assert!(aircraft.start.start_valve_is_open());
+        assert!(!aircraft.start.starter_is_motoring());
+    }
+
+    #[test]
+    fn no_ignition_and_fuel_at_low_n2() {
+        let mut aircraft = TestAircraft::new().starting();
+        run_with_n2(&mut aircraft, &mut SimulationTestBed::new(), 10.);
+
+        assert!(!aircraft.start.igniter_a_is_on());
+        assert!(!aircraft.start.igniter_b_is_on());
+        assert!(!aircraft.start.fuel_valve_is_open());
+    }
+
+    #[test]
+    fn single_igniter_on_ground_from_ignition_n2() {
+        let mut aircraft = TestAircraft::new().starting();
+        run_with_n2(&mut aircraft, &mut SimulationTestBed::new(), 18.);
+
+        assert!(aircraft.start.igniter_a_is_on());
+        assert!(!aircraft.start.igniter_b_is_on());
+        assert!(!aircraft.start.fuel_valve_is_open());
+    }
+
+    #[test]
+    fn both_igniters_in_flight() {
+        let mut aircraft = TestAircraft::new().starting().in_flight();
+        run_with_n2(&mut aircraft, &mut SimulationTestBed::new(), 18.);
+
+        assert!(aircraft.start.igniter_a_is_on());
+        assert!(aircraft.start.igniter_b_is_on());
+    }
+
+    #[test]
+    fn fuel_from_fuel_n2() {
+        let mut aircraft = TestAircraft::new().starting();
+        run_with_n2(&mut aircraft, &mut SimulationTestBed::new(), 25.);
+
+        assert!(aircraft.start.fuel_valve_is_open());
+    }
+
+    #[test]
+    fn start_valve_and_ignition_cut_out_when_self_sustaining() {
+        let mut aircraft = TestAircraft::new().starting();
+        let mut test_bed = SimulationTestBed::new();
+        run_with_n2(&mut aircraft, &mut test_bed, 40.);
+        run_with_n2(&mut aircraft, &mut test_bed, 60.);
+
+        assert!(!aircraft.start.start_valve_is_open());
+        assert!(!aircraft.start.igniter_a_is_on());
+        assert!(!aircraft.start.igniter_b_is_on());
+        assert!(aircraft.start.fuel_valve_is_open());
+    }
+
+    #[test]
+    fn alternates_igniters_between_starts_on_ground() {
+        let mut aircraft = TestAircraft::new().starting();
+        let mut test_bed = SimulationTestBed::new();
+        run_with_n2(&mut aircraft, &mut test_bed, 40.);
+        run_with_n2(&mut aircraft, &mut test_bed, 60.);
+
+        aircraft.master_is_on = false;
+        run_with_n2(&mut aircraft, &mut test_bed, 0.);
+        aircraft.master_is_on = true;
+        run_with_n2(&mut aircraft, &mut test_bed, 20.);
+
+        assert!(!aircraft.start.igniter_a_is_on());
+        assert!(aircraft.start.igniter_b_is_on());
+    }
+
+    #[test]
+    fn no_ignition_in_crank() {
+        let mut aircraft = TestAircraft::new().with_mode(EngineModeSelectorPosition::Crank);
+        aircraft.master_is_on = true;
+        run_with_n2(&mut aircraft, &mut SimulationTestBed::new(), 20.);
+
+        assert!(!aircraft.start.start_valve_is_open());
+        assert!(!aircraft.start.igniter_a_is_on());
+        assert!(!aircraft.start.igniter_b_is_on());
+    }
+
+    #[test]
+    fn dry_crank_opens_start_valve_without_ignition_and_fuel() {
+        let mut aircraft = TestAircraft::new().with_mode(EngineModeSelectorPosition::Crank);
+        run_with_n2(&mut aircraft, &mut SimulationTestBed::new(), 20.);
+
+        assert!(aircraft.start.start_valve_is_open());
+        assert!(aircraft.start.starter_is_motoring());
+        assert!(!aircraft.start.igniter_a_is_on());
+        assert!(!aircraft.start.igniter_b_is_on());
+        assert!(!aircraft.start.fuel_valve_is_open());
+    }
+
+    #[test]
+    fn dry_crank_ends_when_leaving_crank() {
+        let mut aircraft = TestAircraft::new().with_mode(EngineModeSelectorPosition::Crank);
+        let mut test_bed = SimulationTestBed::new();
+        run_with_n2(&mut aircraft, &mut test_bed, 20.);
+
+        aircraft.mode = EngineModeSelectorPosition::Norm;
+        run_with_n2(&mut aircraft, &mut test_bed, 15.);
+
+        assert!(!aircraft.start.start_valve_is_open());
+    }
+
+    #[test]
+    fn continuous_ignition_in_flight_with_engine_running() {
+        let mut aircraft = TestAircraft::new().starting().in_flight();
+        run_with_n2(&mut aircraft, &mut SimulationTestBed::new(), 80.);
+
+        assert!(aircraft.start.igniter_a_is_on());
+        assert!(aircraft.start.igniter_b_is_on());
+    }
+
+    #[test]
+    fn fuel_valve_closes_when_master_off() {
+        let mut aircraft = TestAircraft::new();
+        run_with_n2(&mut aircraft, &mut SimulationTestBed::new(), 80.);
+
+        assert!(!aircraft.start.fuel_valve_is_open());
+    }
+
+    #[test]
+    fn writes_its_state() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+
+        test_bed.run_aircraft(&mut aircraft);
+
+        assert!(test_bed.contains_key("ENG_1_START_VALVE_OPEN"));
+        assert!(test_bed.contains_key("ENG_1_STARTER_IS_MOTORING"));
+        assert!(test_bed.contains_key("ENG_1_IGNITER_A_IS_ON"));
+        assert!(test_bed.contains_key("ENG_1_IGNITER_B_IS_ON"));
+        assert!(test_bed.contains_key("ENG_1_FUEL_VALVE_OPEN"));
+    }
+}
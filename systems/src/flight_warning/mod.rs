@@ -0,0 +1,556 @@
+//! The Flight Warning Computer (FWC) monitors the aircraft's systems. It divides the
+//! flight into phases, which are used to inhibit warnings and cautions that are not
+//! relevant at that moment.
+use crate::{
+    engine::Engine,
+    shared::{FwcFlightPhase, FwcFlightPhaseProvider, LgciuWeightOnWheels},
+    simulation::{
+        QuantityIdentifier, SimulationElement, SimulatorWriter, Snapshot, UpdateContext,
+        VariableRegistry, VariableUnit,
+    },
+};
+use std::time::Duration;
+use uom::si::{f64::*, length::foot, ratio::percent, velocity::knot};
+
+mod warning;
+pub use warning::{FlightWarning, FlightWarningSystem, WarningLevel};
+
+pub struct FlightWarningComputer {
+    flight_phase_id: QuantityIdentifier<f64>,
+    flight_phase: Option<FwcFlightPhase>,
+    time_in_flight_phase: Duration,
+    radio_height: Length,
+}
+impl FlightWarningComputer {
+    const TAKE_OFF_POWER_N1_PERCENT: f64 = 75.;
+    const EIGHTY_KNOTS: f64 = 80.;
+    const LIFT_OFF_PHASE_END_FEET: f64 = 1500.;
+    const APPROACH_PHASE_START_FEET: f64 = 800.;
+    const LIFT_OFF_PHASE_MAXIMUM_DURATION: Duration = Duration::from_secs(120);
+    const ENGINES_SHUTDOWN_PHASE_DURATION: Duration = Duration::from_secs(300);
+
+    pub fn new(registry: &mut VariableRegistry) -> FlightWarningComputer {
+        FlightWarningComputer {
+            flight_phase_id: registry.register_write("FWC_FLIGHT_PHASE", VariableUnit::Number),
+            flight_phase: None,
+            time_in_flight_phase: Duration::from_secs(0),
+            radio_height: Length::new::<foot>(0.),
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        context: &UpdateContext,
+        engines: &[&Engine],
+        lgciu: &impl LgciuWeightOnWheels,
+    ) {
+        self.radio_height = context.radio_altitude();
+
+        let conditions = FlightPhaseConditions {
+            is_on_ground: lgciu.left_and_right_gear_compressed(),
+            any_engine_running: engines.iter().any(|engine| engine.is_running()),
+            take_off_power: engines.iter().any(|engine| {
+                engine.n1()
+                    >= Ratio::new::<percent>(FlightWarningComputer::TAKE_OFF_POWER_N1_PERCENT)
+            }),
+            at_or_above_eighty_knots: context.indicated_airspeed()
+                >= Velocity::new::<knot>(FlightWarningComputer::EIGHTY_KNOTS),
+            radio_height: self.radio_height,
+        };
+
+        self.time_in_flight_phase += context.delta();
+        let next_flight_phase = match self.flight_phase {
+            Some(phase) => self.next_flight_phase(phase, &conditions),
+            None => FlightWarningComputer::initial_flight_phase(&conditions),
+        };
+
+        if self.flight_phase != Some(next_flight_phase) {
+            self.flight_phase = Some(next_flight_phase);
+            self.time_in_flight_phase = Duration::from_secs(0);
+        }
+    }
+
+    /// The flight phase when the computer starts in the given conditions.
+    fn initial_flight_phase(conditions: &FlightPhaseConditions) -> FwcFlightPhase {
+        if !conditions.is_on_ground {
+            if conditions.radio_height
+                >= Length::new::<foot>(FlightWarningComputer::APPROACH_PHASE_START_FEET)
+            {
+                FwcFlightPhase::AtOrAbove1500Feet
+            } else {
+                FwcFlightPhase::AtOrBelow800Feet
+            }
+        } else if conditions.any_engine_running {
+            FwcFlightPhase::FirstEngineStarted
+        } else {
+            FwcFlightPhase::ElecPwr
+        }
+    }
+
+    fn next_flight_phase(
+        &self,
+        phase: FwcFlightPhase,
+        conditions: &FlightPhaseConditions,
+    ) -> FwcFlightPhase {
+        match phase {
+            FwcFlightPhase::ElecPwr if conditions.any_engine_running => {
+                FwcFlightPhase::FirstEngineStarted
+            }
+            FwcFlightPhase::FirstEngineStarted if !conditions.any_engine_running => {
+                FwcFlightPhase::ElecPwr
+            }
+            FwcFlightPhase::FirstEngineStarted | FwcFlightPhase::AtOrBelowEightyKnots
+                if conditions.take_off_power =>
+            {
+                FwcFlightPhase::FirstEngineTakeOffPower
+            }
+            FwcFlightPhase::FirstEngineTakeOffPower if conditions.at_or_above_eighty_knots => {
+                FwcFlightPhase::AtOrAboveEightyKnots
+            }
+            FwcFlightPhase::FirstEngineTakeOffPower if !conditions.take_off_power => {
+                FwcFlightPhase::FirstEngineStarted
+            }
+            FwcFlightPhase::AtOrAboveEightyKnots if !conditions.is_on_ground => {
+                FwcFlightPhase::LiftOff
+            }
+            // A rejected take off.
+            FwcFlightPhase::AtOrAboveEightyKnots
+                if !conditions.take_off_power && !conditions.at_or_above_eighty_knots =>
+            {
+                FwcFlightPhase::AtOrBelowEightyKnots
+            }
+            FwcFlightPhase::LiftOff
+            | FwcFlightPhase::AtOrAbove1500Feet
+            | FwcFlightPhase::AtOrBelow800Feet
+                if conditions.is_on_ground =>
+            {
+                FwcFlightPhase::TouchDown
+            }
+            FwcFlightPhase::LiftOff
+                if conditions.radio_height
+                    >= Length::new::<foot>(FlightWarningComputer::LIFT_OFF_PHASE_END_FEET)
+                    || self.time_in_flight_phase
+                        >= FlightWarningComputer::LIFT_OFF_PHASE_MAXIMUM_DURATION =>
+            {
+                FwcFlightPhase::AtOrAbove1500Feet
+            }
+            FwcFlightPhase::AtOrAbove1500Feet
+                if conditions.radio_height
+                    < Length::new::<foot>(FlightWarningComputer::APPROACH_PHASE_START_FEET) =>
+            {
+                FwcFlightPhase::AtOrBelow800Feet
+            }
+            // A go around.
+            FwcFlightPhase::AtOrBelow800Feet
+                if conditions.radio_height
+                    >= Length::new::<foot>(FlightWarningComputer::LIFT_OFF_PHASE_END_FEET) =>
+            {
+                FwcFlightPhase::AtOrAbove1500Feet
+            }
+            // A touch and go.
+            FwcFlightPhase::TouchDown if !conditions.is_on_ground => FwcFlightPhase::LiftOff,
+            FwcFlightPhase::TouchDown if !conditions.at_or_above_eighty_knots => {
+                FwcFlightPhase::AtOrBelowEightyKnots
+            }
+            FwcFlightPhase::AtOrBelowEightyKnots if !conditions.any_engine_running => {
+                FwcFlightPhase::EnginesShutdown
+            }
+            FwcFlightPhase::EnginesShutdown if conditions.any_engine_running => {
+                FwcFlightPhase::FirstEngineStarted
+            }
+            FwcFlightPhase::EnginesShutdown
+                if self.time_in_flight_phase
+                    >= FlightWarningComputer::ENGINES_SHUTDOWN_PHASE_DURATION =>
+            {
+                FwcFlightPhase::ElecPwr
+            }
+            _ => phase,
+        }
+    }
+
+    pub fn radio_height(&self) -> Length {
+        self.radio_height
+    }
+
+    fn flight_phase_from(value: u8) -> Option<FwcFlightPhase> {
+        match value {
+            1 => Some(FwcFlightPhase::ElecPwr),
+            2 => Some(FwcFlightPhase::FirstEngineStarted),
+            3 => Some(FwcFlightPhase::FirstEngineTakeOffPower),
+            4 => Some(FwcFlightPhase::AtOrAboveEightyKnots),
+            5 => Some(FwcFlightPhase::LiftOff),
+            6 => Some(FwcFlightPhase::AtOrAbove1500Feet),
+            7 => Some(FwcFlightPhase::AtOrBelow800Feet),
+            8 => Some(FwcFlightPhase::TouchDown),
+            9 => Some(FwcFlightPhase::AtOrBelowEightyKnots),
+            10 => Some(FwcFlightPhase::EnginesShutdown),
+            _ => None,
+        }
+    }
+}
+impl FwcFlightPhaseProvider for FlightWarningComputer {
+    fn flight_phase(&self) -> FwcFlightPhase {
+        self.flight_phase.unwrap_or(FwcFlightPhase::ElecPwr)
+    }
+}
+impl SimulationElement for FlightWarningComputer {
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_f64(&self.flight_phase_id, self.flight_phase() as u8 as f64);
+    }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        if let Some(flight_phase) = self.flight_phase {
+            snapshot.save_f64(self.flight_phase_id.name(), flight_phase as u8 as f64);
+            snapshot.save_duration("FWC_TIME_IN_FLIGHT_PHASE", self.time_in_flight_phase);
+        }
+        snapshot.save_f64("FWC_RADIO_HEIGHT", self.radio_height.get::<foot>());
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        if let Some(flight_phase) = snapshot
+            .restore_f64(self.flight_phase_id.name())
+            .and_then(|value| FlightWarningComputer::flight_phase_from(value as u8))
+        {
+            self.flight_phase = Some(flight_phase);
+        }
+        if let Some(time_in_flight_phase) = snapshot.restore_duration("FWC_TIME_IN_FLIGHT_PHASE") {
+            self.time_in_flight_phase = time_in_flight_phase;
+        }
+        if let Some(radio_height) = snapshot.restore_f64("FWC_RADIO_HEIGHT") {
+            self.radio_height = Length::new::<foot>(radio_height);
+        }
+    }
+}
+
+struct FlightPhaseConditions {
+    is_on_ground: bool,
+    any_engine_running: bool,
+    take_off_power: bool,
+    at_or_above_eighty_knots: bool,
+    radio_height: Length,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{test::SimulationTestBed, Aircraft, SimulationElementVisitor};
+
+    struct TestLgciu {
+        compressed: bool,
+    }
+    impl LgciuWeightOnWheels for TestLgciu {
+        fn left_and_right_gear_compressed(&self) -> bool {
+            self.compressed
+        }
+    }
+
+    struct TestAircraft {
+        fwc: FlightWarningComputer,
+        engine_1: Engine,
+        engine_2: Engine,
+    }
+    impl TestAircraft {
+        fn new() -> Self {
+            let mut registry = VariableRegistry::new();
+
+            Self {
+                fwc: FlightWarningComputer::new(&mut registry),
+                engine_1: Engine::new(&mut registry, 1),
+                engine_2: Engine::new(&mut registry, 2),
+            }
+        }
+    }
+    impl Aircraft for TestAircraft {
+        fn update_before_power_distribution(&mut self, context: &UpdateContext) {
+            self.engine_1.update(context);
+            self.engine_2.update(context);
+        }
+
+        fn update_after_power_distribution(&mut self, context: &UpdateContext) {
+            self.fwc.update(
+                context,
+                &[&self.engine_1, &self.engine_2],
+                &TestLgciu {
+                    compressed: context.is_on_ground(),
+                },
+            );
+        }
+    }
+    impl SimulationElement for TestAircraft {
+        fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            self.fwc.accept(visitor);
+            self.engine_1.accept(visitor);
+            self.engine_2.accept(visitor);
+
+            visitor.visit(self);
+        }
+    }
+
+    struct FlightWarningComputerTestBed {
+        aircraft: TestAircraft,
+        simulation_test_bed: SimulationTestBed,
+    }
+    impl FlightWarningComputerTestBed {
+        fn new() -> Self {
+            let mut test_bed = Self {
+                aircraft: TestAircraft::new(),
+                simulation_test_bed: SimulationTestBed::new(),
+            };
+            test_bed.simulation_test_bed.set_on_ground(true);
+            test_bed
+                .simulation_test_bed
+                .set_radio_altitude(Length::new::<foot>(0.));
+            test_bed
+                .simulation_test_bed
+                .set_indicated_airspeed(Velocity::new::<knot>(0.));
+
+            test_bed
+        }
+
+        fn run_for(mut self, seconds: u64) -> Self {
+            for _ in 0..seconds {
+                self.simulation_test_bed.run_aircraft(&mut self.aircraft);
+            }
+
+            self
+        }
+
+        fn engines(mut self, n1: f64, n2: f64) -> Self {
+            for number in 1..=2 {
+                self.simulation_test_bed
+                    .write_f64(&format!("TURB ENG N1:{}", number), n1);
+                self.simulation_test_bed
+                    .write_f64(&format!("TURB ENG CORRECTED N2:{}", number), n2);
+            }
+            self
+        }
+
+        fn engines_running(self) -> Self {
+            self.engines(20., 60.)
+        }
+
+        fn engines_off(self) -> Self {
+            self.engines(0., 0.)
+        }
+
+        fn take_off_power(self) -> Self {
+            self.engines(85., 95.)
+        }
+
+        fn airspeed(mut self, knots: f64) -> Self {
+            self.simulation_test_bed
+                .set_indicated_airspeed(Velocity::new::<knot>(knots));
+            self
+        }
+
+        fn airborne_at(mut self, feet: f64) -> Self {
+            self.simulation_test_bed.set_on_ground(false);
+            self.simulation_test_bed
+                .set_radio_altitude(Length::new::<foot>(feet));
+            self
+        }
+
+        fn on_ground(mut self) -> Self {
+            self.simulation_test_bed.set_on_ground(true);
+            self.simulation_test_bed
+                .set_radio_altitude(Length::new::<foot>(0.));
+            self
+        }
+
+        fn take_off(self) -> Self {
+            self.engines_running()
+                .run_for(1)
+                .take_off_power()
+                .run_for(1)
+                .airspeed(140.)
+                .run_for(1)
+                .airborne_at(50.)
+                .run_for(1)
+        }
+
+        fn flight_phase(&self) -> FwcFlightPhase {
+            self.aircraft.fwc.flight_phase()
+        }
+    }
+
+    fn test_bed() -> FlightWarningComputerTestBed {
+        FlightWarningComputerTestBed::new()
+    }
+
+    #[test]
+    fn elec_pwr_on_ground_with_engines_off() {
+        let test_bed = test_bed().run_for(1);
+
+        assert_eq!(test_bed.flight_phase(), FwcFlightPhase::ElecPwr);
+    }
+
+    #[test]
+    fn first_engine_started_when_engine_running() {
+        let test_bed = test_bed().run_for(1).engines_running().run_for(1);
+
+        assert_eq!(test_bed.flight_phase(), FwcFlightPhase::FirstEngineStarted);
+    }
+
+    #[test]
+    fn first_engine_take_off_power_when_take_off_power_set() {
+        let test_bed = test_bed()
+            .engines_running()
+            .run_for(1)
+            .take_off_power()
+            .run_for(1);
+
+        assert_eq!(
+            test_bed.flight_phase(),
+            FwcFlightPhase::FirstEngineTakeOffPower
+        );
+    }
+
+    #[test]
+    fn at_or_above_eighty_knots_during_take_off_roll() {
+        let test_bed = test_bed()
+            .engines_running()
+            .run_for(1)
+            .take_off_power()
+            .run_for(1)
+            .airspeed(85.)
+            .run_for(1);
+
+        assert_eq!(
+            test_bed.flight_phase(),
+            FwcFlightPhase::AtOrAboveEightyKnots
+        );
+    }
+
+    #[test]
+    fn lift_off_when_airborne() {
+        let test_bed = test_bed().take_off();
+
+        assert_eq!(test_bed.flight_phase(), FwcFlightPhase::LiftOff);
+    }
+
+    #[test]
+    fn at_or_above_1500_feet_after_lift_off() {
+        let test_bed = test_bed().take_off().airborne_at(1600.).run_for(1);
+
+        assert_eq!(test_bed.flight_phase(), FwcFlightPhase::AtOrAbove1500Feet);
+    }
+
+    #[test]
+    fn at_or_above_1500_feet_two_minutes_after_lift_off() {
+        let test_bed = test_bed().take_off().airborne_at(1000.).run_for(121);
+
+        assert_eq!(test_bed.flight_phase(), FwcFlightPhase::AtOrAbove1500Feet);
+    }
+
+    #[test]
+    fn at_or_below_800_feet_during_approach() {
+        let test_bed = test_bed()
+            .take_off()
+            .airborne_at(5000.)
+            .run_for(1)
+            .airborne_at(700.)
+            .run_for(1);
+
+        assert_eq!(test_bed.flight_phase(), FwcFlightPhase::AtOrBelow800Feet);
+    }
+
+    #[test]
+    fn touch_down_when_landing() {
+        let test_bed = test_bed()
+            .take_off()
+            .airborne_at(5000.)
+            .run_for(1)
+            .on_ground()
+            .run_for(1);
+
+        assert_eq!(test_bed.flight_phase(), FwcFlightPhase::TouchDown);
+    }
+
+    #[test]
+    fn at_or_below_eighty_knots_after_landing() {
+        let test_bed = test_bed()
+            .take_off()
+            .airborne_at(5000.)
+            .run_for(1)
+            .on_ground()
+            .run_for(1)
+            .airspeed(30.)
+            .run_for(1);
+
+        assert_eq!(
+            test_bed.flight_phase(),
+            FwcFlightPhase::AtOrBelowEightyKnots
+        );
+    }
+
+    #[test]
+    fn engines_shutdown_after_landing_returns_to_elec_pwr_after_five_minutes() {
+        let test_bed = test_bed()
+            .take_off()
+            .airborne_at(5000.)
+            .run_for(1)
+            .on_ground()
+            .run_for(1)
+            .airspeed(0.)
+            .run_for(1)
+            .engines_off()
+            .run_for(1);
+
+        assert_eq!(test_bed.flight_phase(), FwcFlightPhase::EnginesShutdown);
+
+        let test_bed = test_bed.run_for(300);
+
+        assert_eq!(test_bed.flight_phase(), FwcFlightPhase::ElecPwr);
+    }
+
+    #[test]
+    fn rejected_take_off_goes_to_at_or_below_eighty_knots() {
+        let test_bed = test_bed()
+            .engines_running()
+            .run_for(1)
+            .take_off_power()
+            .run_for(1)
+            .airspeed(100.)
+            .run_for(1)
+            .engines_running()
+            .airspeed(40.)
+            .run_for(1);
+
+        assert_eq!(
+            test_bed.flight_phase(),
+            FwcFlightPhase::AtOrBelowEightyKnots
+        );
+    }
+
+    #[test]
+    fn starts_in_cruise_when_initialised_in_flight() {
+        let test_bed = test_bed().airspeed(250.).airborne_at(10000.).run_for(1);
+
+        assert_eq!(test_bed.flight_phase(), FwcFlightPhase::AtOrAbove1500Feet);
+    }
+
+    #[test]
+    fn flight_phase_and_time_in_flight_phase_are_restored_from_snapshot() {
+        let mut test_bed = test_bed().take_off().airborne_at(1000.).run_for(100);
+
+        let mut restored_test_bed = self::test_bed().engines_running().airborne_at(1000.);
+        Snapshot::take(&mut test_bed.aircraft).restore(&mut restored_test_bed.aircraft);
+        assert_eq!(restored_test_bed.flight_phase(), FwcFlightPhase::LiftOff);
+
+        let restored_test_bed = restored_test_bed.run_for(21);
+        assert_eq!(
+            restored_test_bed.flight_phase(),
+            FwcFlightPhase::AtOrAbove1500Feet
+        );
+    }
+
+    #[test]
+    fn writes_flight_phase() {
+        let mut test_bed = test_bed().engines_running().run_for(1);
+
+        assert_eq!(
+            test_bed.simulation_test_bed.read_f64("FWC_FLIGHT_PHASE"),
+            FwcFlightPhase::FirstEngineStarted as u8 as f64
+        );
+    }
+}
@@ -0,0 +1,442 @@
+use crate::{
+    electrical::{consumption::PowerConsumer, ElectricalBusType},
+    shared::{FwcFlightPhase, FwcFlightPhaseProvider},
+    simulation::{
+        QuantityIdentifier, SimulationElement, SimulationElementVisitor, SimulatorReader,
+        SimulatorWriter, Snapshot, UpdateContext, VariableRegistry, VariableUnit,
+    },
+};
+use std::cmp::Reverse;
+
+/// The level of a flight warning. Warnings take priority over cautions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WarningLevel {
+    /// Requires crew awareness, but not immediate action. Lights the MASTER CAUT light.
+    Caution,
+    /// Requires immediate crew action. Lights the MASTER WARN light.
+    Warning,
+}
+
+/// A failure condition the flight warning system alerts the crew to.
+pub trait FlightWarning: Copy + PartialEq {
+    /// The code identifying the message displayed on the E/WD.
+    fn code(&self) -> u32;
+
+    /// The message displayed on the E/WD.
+    fn message(&self) -> &'static str;
+
+    fn level(&self) -> WarningLevel;
+
+    /// Indicates if the warning is inhibited during the given flight phase.
+    fn is_inhibited_in(&self, flight_phase: FwcFlightPhase) -> bool;
+}
+
+struct ActiveFlightWarning<T: FlightWarning> {
+    warning: T,
+    is_acknowledged: bool,
+}
+
+/// Evaluates the failure conditions detected by the aircraft against the inhibitions of
+/// the current flight phase. Active warnings light the MASTER WARN or MASTER CAUT light
+/// until the crew acknowledges them by pressing the light. The E/WD shows the active
+/// warnings first, followed by the cautions, each in the order of priority in which
+/// the aircraft detected them.
+pub struct FlightWarningSystem<T: FlightWarning> {
+    master_warning_id: QuantityIdentifier<bool>,
+    master_caution_id: QuantityIdentifier<bool>,
+    master_warning_pressed_id: QuantityIdentifier<bool>,
+    master_caution_pressed_id: QuantityIdentifier<bool>,
+    message_count_id: QuantityIdentifier<f64>,
+    message_ids: Vec<QuantityIdentifier<f64>>,
+    active: Vec<ActiveFlightWarning<T>>,
+    /// The codes of the active warnings which the crew acknowledged.
+    acknowledged_codes: Vec<u32>,
+    master_warning_pressed: bool,
+    master_caution_pressed: bool,
+    consumer: PowerConsumer,
+}
+impl<T: FlightWarning> FlightWarningSystem<T> {
+    const DISPLAYED_MESSAGE_LINES: usize = 7;
+
+    pub fn new(
+        registry: &mut VariableRegistry,
+        bus_type: ElectricalBusType,
+    ) -> FlightWarningSystem<T> {
+        FlightWarningSystem {
+            master_warning_id: registry.register_write("ECAM_MASTER_WARNING", VariableUnit::Bool),
+            master_caution_id: registry.register_write("ECAM_MASTER_CAUTION", VariableUnit::Bool),
+            master_warning_pressed_id: registry
+                .register_read("ECAM_MASTER_WARNING_PB_IS_PRESSED", VariableUnit::Bool),
+            master_caution_pressed_id: registry
+                .register_read("ECAM_MASTER_CAUTION_PB_IS_PRESSED", VariableUnit::Bool),
+            message_count_id: registry
+                .register_write("ECAM_EWD_MESSAGE_COUNT", VariableUnit::Number),
+            message_ids: (1..=FlightWarningSystem::<T>::DISPLAYED_MESSAGE_LINES)
+                .map(|line| {
+                    registry
+                        .register_write(&format!("ECAM_EWD_MESSAGE_{}", line), VariableUnit::Number)
+                })
+                .collect(),
+            active: Vec::new(),
+            acknowledged_codes: Vec::new(),
+            master_warning_pressed: false,
+            master_caution_pressed: false,
+            consumer: PowerConsumer::from(bus_type),
+        }
+    }
+
+    /// Updates the active warnings based on the failures detected by the aircraft,
+    /// ordered by priority.
+    pub fn update(&mut self, _: &UpdateContext, fwc: &impl FwcFlightPhaseProvider, failures: &[T]) {
+        self.active.clear();
+        if !self.consumer.is_powered() {
+            self.acknowledged_codes.clear();
+            return;
+        }
+
+        let flight_phase = fwc.flight_phase();
+        let acknowledged_codes = &self.acknowledged_codes;
+        self.active.extend(
+            failures
+                .iter()
+                .filter(|failure| !failure.is_inhibited_in(flight_phase))
+                .map(|&failure| ActiveFlightWarning {
+                    warning: failure,
+                    is_acknowledged: acknowledged_codes.contains(&failure.code()),
+                }),
+        );
+        self.active
+            .sort_by_key(|active| Reverse(active.warning.level()));
+
+        for active in self.active.iter_mut() {
+            active.is_acknowledged |= match active.warning.level() {
+                WarningLevel::Warning => self.master_warning_pressed,
+                WarningLevel::Caution => self.master_caution_pressed,
+            };
+        }
+
+        self.acknowledged_codes.clear();
+        self.acknowledged_codes.extend(
+            self.active
+                .iter()
+                .filter(|active| active.is_acknowledged)
+                .map(|active| active.warning.code()),
+        );
+    }
+
+    fn has_unacknowledged(&self, level: WarningLevel) -> bool {
+        self.active
+            .iter()
+            .any(|active| active.warning.level() == level && !active.is_acknowledged)
+    }
+
+    pub fn master_warning_is_on(&self) -> bool {
+        self.has_unacknowledged(WarningLevel::Warning)
+    }
+
+    pub fn master_caution_is_on(&self) -> bool {
+        self.has_unacknowledged(WarningLevel::Caution)
+    }
+
+    /// The active warnings in the order in which they are displayed on the E/WD.
+    pub fn active_warnings(&self) -> Vec<T> {
+        self.active.iter().map(|active| active.warning).collect()
+    }
+
+    /// The messages in the order in which they are displayed on the E/WD.
+    pub fn messages(&self) -> Vec<&'static str> {
+        self.active
+            .iter()
+            .map(|active| active.warning.message())
+            .collect()
+    }
+}
+impl<T: FlightWarning> SimulationElement for FlightWarningSystem<T> {
+    fn accept<U: SimulationElementVisitor>(&mut self, visitor: &mut U) {
+        self.consumer.accept(visitor);
+
+        visitor.visit(self);
+    }
+
+    fn read(&mut self, reader: &mut SimulatorReader) {
+        self.master_warning_pressed = reader.read_bool(&self.master_warning_pressed_id);
+        self.master_caution_pressed = reader.read_bool(&self.master_caution_pressed_id);
+    }
+
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_bool(&self.master_warning_id, self.master_warning_is_on());
+        writer.write_bool(&self.master_caution_id, self.master_caution_is_on());
+        writer.write_f64(&self.message_count_id, self.active.len() as f64);
+        for (index, id) in self.message_ids.iter().enumerate() {
+            writer.write_f64(
+                id,
+                self.active
+                    .get(index)
+                    .map_or(0., |active| active.warning.code() as f64),
+            );
+        }
+    }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        snapshot.save_f64(
+            "ECAM_ACKNOWLEDGED_WARNING_COUNT",
+            self.acknowledged_codes.len() as f64,
+        );
+        for (index, code) in self.acknowledged_codes.iter().enumerate() {
+            snapshot.save_f64(
+                &format!("ECAM_ACKNOWLEDGED_WARNING_{}", index + 1),
+                *code as f64,
+            );
+        }
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        if let Some(count) = snapshot.restore_f64("ECAM_ACKNOWLEDGED_WARNING_COUNT") {
+            self.acknowledged_codes.clear();
+            self.acknowledged_codes
+                .extend((1..=count as usize).filter_map(|number| {
+                    snapshot
+                        .restore_f64(&format!("ECAM_ACKNOWLEDGED_WARNING_{}", number))
+                        .map(|code| code as u32)
+                }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        electrical::{consumption::SuppliedPower, Potential, PotentialOrigin},
+        simulation::{test::SimulationTestBed, Aircraft},
+    };
+    use uom::si::{electric_potential::volt, f64::*};
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum TestWarning {
+        Fire,
+        Overheat,
+        PumpFault,
+        ValveFault,
+    }
+    impl FlightWarning for TestWarning {
+        fn code(&self) -> u32 {
+            match self {
+                TestWarning::Fire => 1,
+                TestWarning::Overheat => 2,
+                TestWarning::PumpFault => 3,
+                TestWarning::ValveFault => 4,
+            }
+        }
+
+        fn message(&self) -> &'static str {
+            match self {
+                TestWarning::Fire => "FIRE",
+                TestWarning::Overheat => "OVHT",
+                TestWarning::PumpFault => "PUMP FAULT",
+                TestWarning::ValveFault => "VALVE FAULT",
+            }
+        }
+
+        fn level(&self) -> WarningLevel {
+            match self {
+                TestWarning::Fire | TestWarning::Overheat => WarningLevel::Warning,
+                TestWarning::PumpFault | TestWarning::ValveFault => WarningLevel::Caution,
+            }
+        }
+
+        fn is_inhibited_in(&self, flight_phase: FwcFlightPhase) -> bool {
+            *self == TestWarning::ValveFault && flight_phase == FwcFlightPhase::LiftOff
+        }
+    }
+
+    struct TestFlightWarningComputer {
+        flight_phase: FwcFlightPhase,
+    }
+    impl FwcFlightPhaseProvider for TestFlightWarningComputer {
+        fn flight_phase(&self) -> FwcFlightPhase {
+            self.flight_phase
+        }
+    }
+
+    struct TestAircraft {
+        fws: FlightWarningSystem<TestWarning>,
+        fwc: TestFlightWarningComputer,
+        failures: Vec<TestWarning>,
+        is_ac_ess_powered: bool,
+    }
+    impl TestAircraft {
+        fn new() -> Self {
+            Self {
+                fws: FlightWarningSystem::new(
+                    &mut VariableRegistry::new(),
+                    ElectricalBusType::AlternatingCurrentEssential,
+                ),
+                fwc: TestFlightWarningComputer {
+                    flight_phase: FwcFlightPhase::AtOrAbove1500Feet,
+                },
+                failures: vec![],
+                is_ac_ess_powered: true,
+            }
+        }
+    }
+    impl Aircraft for TestAircraft {
+        fn update_after_power_distribution(&mut self, context: &UpdateContext) {
+            self.fws.update(context, &self.fwc, &self.failures);
+        }
+
+        fn get_supplied_power(&mut self) -> SuppliedPower {
+            let mut supplied_power = SuppliedPower::new();
+            if self.is_ac_ess_powered {
+                supplied_power.add(
+                    ElectricalBusType::AlternatingCurrentEssential,
+                    Potential::single(
+                        PotentialOrigin::StaticInverter,
+                        ElectricPotential::new::<volt>(115.),
+                    ),
+                );
+            }
+
+            supplied_power
+        }
+    }
+    impl SimulationElement for TestAircraft {
+        fn accept<U: SimulationElementVisitor>(&mut self, visitor: &mut U) {
+            self.fws.accept(visitor);
+
+            visitor.visit(self);
+        }
+    }
+
+    fn run_with(failures: &[TestWarning]) -> (TestAircraft, SimulationTestBed) {
+        let mut aircraft = TestAircraft::new();
+        aircraft.failures = failures.to_vec();
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.run_aircraft(&mut aircraft);
+
+        (aircraft, test_bed)
+    }
+
+    #[test]
+    fn no_failures_results_in_no_master_lights() {
+        let (aircraft, _) = run_with(&[]);
+
+        assert!(!aircraft.fws.master_warning_is_on());
+        assert!(!aircraft.fws.master_caution_is_on());
+        assert!(aircraft.fws.messages().is_empty());
+    }
+
+    #[test]
+    fn warning_lights_master_warning() {
+        let (aircraft, _) = run_with(&[TestWarning::Fire]);
+
+        assert!(aircraft.fws.master_warning_is_on());
+        assert!(!aircraft.fws.master_caution_is_on());
+    }
+
+    #[test]
+    fn caution_lights_master_caution() {
+        let (aircraft, _) = run_with(&[TestWarning::PumpFault]);
+
+        assert!(!aircraft.fws.master_warning_is_on());
+        assert!(aircraft.fws.master_caution_is_on());
+    }
+
+    #[test]
+    fn warnings_are_displayed_before_cautions_in_order_of_priority() {
+        let (aircraft, _) = run_with(&[
+            TestWarning::ValveFault,
+            TestWarning::Overheat,
+            TestWarning::PumpFault,
+            TestWarning::Fire,
+        ]);
+
+        assert_eq!(
+            aircraft.fws.messages(),
+            vec!["OVHT", "FIRE", "VALVE FAULT", "PUMP FAULT"]
+        );
+    }
+
+    #[test]
+    fn inhibited_failure_is_not_displayed() {
+        let mut aircraft = TestAircraft::new();
+        aircraft.fwc.flight_phase = FwcFlightPhase::LiftOff;
+        aircraft.failures = vec![TestWarning::ValveFault, TestWarning::PumpFault];
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert_eq!(aircraft.fws.active_warnings(), vec![TestWarning::PumpFault]);
+    }
+
+    #[test]
+    fn pressing_master_warning_acknowledges_warnings() {
+        let (mut aircraft, mut test_bed) = run_with(&[TestWarning::Fire, TestWarning::PumpFault]);
+        test_bed.write_bool("ECAM_MASTER_WARNING_PB_IS_PRESSED", true);
+        test_bed.run_aircraft(&mut aircraft);
+        test_bed.write_bool("ECAM_MASTER_WARNING_PB_IS_PRESSED", false);
+        test_bed.run_aircraft(&mut aircraft);
+
+        assert!(!aircraft.fws.master_warning_is_on());
+        assert!(aircraft.fws.master_caution_is_on());
+        assert_eq!(aircraft.fws.messages(), vec!["FIRE", "PUMP FAULT"]);
+    }
+
+    #[test]
+    fn new_warning_lights_master_warning_after_acknowledgement() {
+        let (mut aircraft, mut test_bed) = run_with(&[TestWarning::Fire]);
+        test_bed.write_bool("ECAM_MASTER_WARNING_PB_IS_PRESSED", true);
+        test_bed.run_aircraft(&mut aircraft);
+        test_bed.write_bool("ECAM_MASTER_WARNING_PB_IS_PRESSED", false);
+        aircraft.failures = vec![TestWarning::Fire, TestWarning::Overheat];
+        test_bed.run_aircraft(&mut aircraft);
+
+        assert!(aircraft.fws.master_warning_is_on());
+    }
+
+    #[test]
+    fn acknowledgements_are_restored_from_snapshot() {
+        let (mut aircraft, mut test_bed) = run_with(&[TestWarning::Fire, TestWarning::PumpFault]);
+        test_bed.write_bool("ECAM_MASTER_WARNING_PB_IS_PRESSED", true);
+        test_bed.run_aircraft(&mut aircraft);
+
+        let mut restored_aircraft = TestAircraft::new();
+        restored_aircraft.failures = aircraft.failures.clone();
+        Snapshot::take(&mut aircraft).restore(&mut restored_aircraft);
+        test_bed.write_bool("ECAM_MASTER_WARNING_PB_IS_PRESSED", false);
+        test_bed.run_aircraft(&mut restored_aircraft);
+
+        assert!(!restored_aircraft.fws.master_warning_is_on());
+        assert!(restored_aircraft.fws.master_caution_is_on());
+    }
+
+    #[test]
+    fn master_caution_goes_out_when_failure_clears() {
+        let (mut aircraft, mut test_bed) = run_with(&[TestWarning::PumpFault]);
+        aircraft.failures = vec![];
+        test_bed.run_aircraft(&mut aircraft);
+
+        assert!(!aircraft.fws.master_caution_is_on());
+    }
+
+    #[test]
+    fn unpowered_system_shows_nothing() {
+        let mut aircraft = TestAircraft::new();
+        aircraft.is_ac_ess_powered = false;
+        aircraft.failures = vec![TestWarning::Fire];
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert!(!aircraft.fws.master_warning_is_on());
+        assert!(aircraft.fws.messages().is_empty());
+    }
+
+    #[test]
+    fn writes_message_codes_in_display_order() {
+        let (_, mut test_bed) = run_with(&[TestWarning::PumpFault, TestWarning::Fire]);
+
+        assert_eq!(test_bed.read_f64("ECAM_EWD_MESSAGE_COUNT"), 2.);
+        assert_eq!(test_bed.read_f64("ECAM_EWD_MESSAGE_1"), 1.);
+        assert_eq!(test_bed.read_f64("ECAM_EWD_MESSAGE_2"), 3.);
+        assert_eq!(test_bed.read_f64("ECAM_EWD_MESSAGE_3"), 0.);
+        assert!(test_bed.read_bool("ECAM_MASTER_WARNING"));
+        assert!(test_bed.read_bool("ECAM_MASTER_CAUTION"));
+    }
+}
@@ -0,0 +1,355 @@
+//! The fuel quantity within the tanks is owned by the simulator, as the simulator burns
+//! the fuel consumed by the engines. This module models the things that move the fuel
+//! around: pumps, valves and the pressure they build up in the fuel lines.
+use crate::{
+    electrical::{consumption::PowerConsumer, ElectricalBusType},
+    pneumatic::Valve,
+    simulation::{
+        QuantityIdentifier, SimulationElement, SimulationElementVisitor, SimulatorReader,
+        SimulatorWriter, Snapshot, UpdateContext, VariableRegistry, VariableUnit,
+    },
+};
+use uom::si::{f64::*, mass::kilogram, pressure::psi};
+
+mod pump;
+pub use pump::FuelPump;
+
+/// A fuel tank. Its quantity is read from the simulator.
+pub struct FuelTank {
+    quantity_id: QuantityIdentifier<Mass>,
+    quantity: Mass,
+}
+impl FuelTank {
+    pub fn new(registry: &mut VariableRegistry, quantity_id: &str) -> FuelTank {
+        FuelTank {
+            quantity_id: registry.register_read(quantity_id, VariableUnit::Pounds),
+            quantity: Mass::new::<kilogram>(0.),
+        }
+    }
+
+    pub fn quantity(&self) -> Mass {
+        self.quantity
+    }
+
+    pub fn has_fuel_remaining(&self) -> bool {
+        self.quantity > Mass::new::<kilogram>(0.)
+    }
+}
+impl SimulationElement for FuelTank {
+    fn read(&mut self, reader: &mut SimulatorReader) {
+        self.quantity = reader.read_mass(&self.quantity_id);
+    }
+}
+
+/// A motor operated fuel valve. The motor moves the valve to the commanded position
+/// as long as it is powered. When unpowered, the valve remains in its last position.
+pub struct FuelValve {
+    is_open_id: QuantityIdentifier<bool>,
+    is_open: bool,
+    consumer: PowerConsumer,
+}
+impl FuelValve {
+    pub fn new(
+        registry: &mut VariableRegistry,
+        id: &str,
+        bus_type: ElectricalBusType,
+    ) -> FuelValve {
+        FuelValve {
+            is_open_id: registry
+                .register_write(&format!("FUEL_{}_VALVE_IS_OPEN", id), VariableUnit::Bool),
+            is_open: false,
+            consumer: PowerConsumer::from(bus_type),
+        }
+    }
+
+    pub fn update(&mut self, _: &UpdateContext, should_open: bool) {
+        if self.consumer.is_powered() {
+            self.is_open = should_open;
+        }
+    }
+}
+impl Valve for FuelValve {
+    fn is_open(&self) -> bool {
+        self.is_open
+    }
+}
+impl SimulationElement for FuelValve {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.consumer.accept(visitor);
+
+        visitor.visit(self);
+    }
+
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_bool(&self.is_open_id, self.is_open);
+    }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        snapshot.save_bool(self.is_open_id.name(), self.is_open);
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        if let Some(is_open) = snapshot.restore_bool(self.is_open_id.name()) {
+            self.is_open = is_open;
+        }
+    }
+}
+
+/// Komp: There is a pressure switch between the fuel valve and the APU.
+/// It switches from 0 to 1 when the pressure is >=17 PSI and the signal is received by the ECB
+/// And there is a small hysteresis, means it switches back to 0 when <=16 PSI
+pub struct FuelPressureSwitch {
+    has_pressure: bool,
+}
+impl FuelPressureSwitch {
+    const SWITCH_ON_PRESSURE_PSI: f64 = 17.;
+    const SWITCH_OFF_PRESSURE_PSI: f64 = 16.;
+
+    pub fn new() -> Self {
+        FuelPressureSwitch {
+            has_pressure: false,
+        }
+    }
+
+    pub fn update(&mut self, pressure: Pressure) {
+        if pressure >= Pressure::new::<psi>(FuelPressureSwitch::SWITCH_ON_PRESSURE_PSI) {
+            self.has_pressure = true;
+        } else if pressure <= Pressure::new::<psi>(FuelPressureSwitch::SWITCH_OFF_PRESSURE_PSI) {
+            self.has_pressure = false;
+        }
+    }
+
+    pub fn has_pressure(&self) -> bool {
+        self.has_pressure
+    }
+
+    pub fn save(&self, snapshot: &mut Snapshot, name: &str) {
+        snapshot.save_bool(name, self.has_pressure);
+    }
+
+    pub fn restore(&mut self, snapshot: &Snapshot, name: &str) {
+        if let Some(has_pressure) = snapshot.restore_bool(name) {
+            self.has_pressure = has_pressure;
+        }
+    }
+}
+impl Default for FuelPressureSwitch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(test)]
+    mod fuel_tank_tests {
+        use super::*;
+        use crate::simulation::test::SimulationTestBed;
+        use uom::si::mass::pound;
+
+        #[test]
+        fn reads_its_quantity_from_the_simulator() {
+            let mut tank =
+                FuelTank::new(&mut VariableRegistry::new(), "FUEL TANK LEFT MAIN QUANTITY");
+            let mut test_bed = SimulationTestBed::new();
+            test_bed.write_f64("FUEL TANK LEFT MAIN QUANTITY", 1000.);
+
+            test_bed.run_without_update(&mut tank);
+
+            assert_eq!(tank.quantity(), Mass::new::<pound>(1000.));
+            assert!(tank.has_fuel_remaining());
+        }
+
+        #[test]
+        fn has_no_fuel_remaining_when_empty() {
+            let mut tank =
+                FuelTank::new(&mut VariableRegistry::new(), "FUEL TANK LEFT MAIN QUANTITY");
+            let mut test_bed = SimulationTestBed::new();
+            test_bed.write_f64("FUEL TANK LEFT MAIN QUANTITY", 0.);
+
+            test_bed.run_without_update(&mut tank);
+
+            assert!(!tank.has_fuel_remaining());
+        }
+    }
+
+    #[cfg(test)]
+    mod fuel_valve_tests {
+        use super::*;
+        use crate::{
+            electrical::{consumption::SuppliedPower, Potential, PotentialOrigin},
+            simulation::{test::SimulationTestBed, Aircraft},
+        };
+        use uom::si::electric_potential::volt;
+
+        struct TestAircraft {
+            valve: FuelValve,
+            should_open: bool,
+            is_dc_ess_powered: bool,
+        }
+        impl TestAircraft {
+            fn new() -> Self {
+                Self {
+                    valve: FuelValve::new(
+                        &mut VariableRegistry::new(),
+                        "XFEED",
+                        ElectricalBusType::DirectCurrentEssential,
+                    ),
+                    should_open: false,
+                    is_dc_ess_powered: true,
+                }
+            }
+
+            fn command_open(&mut self, should_open: bool) {
+                self.should_open = should_open;
+            }
+
+            fn set_dc_ess_powered(&mut self, is_powered: bool) {
+                self.is_dc_ess_powered = is_powered;
+            }
+        }
+        impl Aircraft for TestAircraft {
+            fn update_after_power_distribution(&mut self, context: &UpdateContext) {
+                self.valve.update(context, self.should_open);
+            }
+
+            fn get_supplied_power(&mut self) -> SuppliedPower {
+                let mut supplied_power = SuppliedPower::new();
+                if self.is_dc_ess_powered {
+                    supplied_power.add(
+                        ElectricalBusType::DirectCurrentEssential,
+                        Potential::single(
+                            PotentialOrigin::Battery(10),
+                            ElectricPotential::new::<volt>(28.),
+                        ),
+                    );
+                }
+
+                supplied_power
+            }
+        }
+        impl SimulationElement for TestAircraft {
+            fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+                self.valve.accept(visitor);
+
+                visitor.visit(self);
+            }
+        }
+
+        #[test]
+        fn starts_closed() {
+            let mut aircraft = TestAircraft::new();
+            SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+            assert!(!aircraft.valve.is_open());
+        }
+
+        #[test]
+        fn opens_when_powered_and_commanded_open() {
+            let mut aircraft = TestAircraft::new();
+            aircraft.command_open(true);
+            SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+            assert!(aircraft.valve.is_open());
+        }
+
+        #[test]
+        fn remains_in_position_when_unpowered() {
+            let mut aircraft = TestAircraft::new();
+            let mut test_bed = SimulationTestBed::new();
+            aircraft.command_open(true);
+            test_bed.run_aircraft(&mut aircraft);
+
+            aircraft.set_dc_ess_powered(false);
+            aircraft.command_open(false);
+            test_bed.run_aircraft(&mut aircraft);
+
+            assert!(aircraft.valve.is_open());
+        }
+
+        #[test]
+        fn writes_its_state() {
+            let mut aircraft = TestAircraft::new();
+            let mut test_bed = SimulationTestBed::new();
+
+            test_bed.run_aircraft(&mut aircraft);
+
+            assert!(test_bed.contains_key("FUEL_XFEED_VALVE_IS_OPEN"));
+        }
+
+        #[test]
+        fn position_is_restored_from_snapshot() {
+            let mut aircraft = TestAircraft::new();
+            aircraft.command_open(true);
+            SimulationTestBed::new().run_aircraft(&mut aircraft);
+            let snapshot = Snapshot::take(&mut aircraft);
+
+            let mut restored_aircraft = TestAircraft::new();
+            snapshot.restore(&mut restored_aircraft);
+
+            assert!(restored_aircraft.valve.is_open());
+        }
+    }
+
+    #[cfg(test)]
+    mod fuel_pressure_switch_tests {
+        use super::*;
+
+        #[test]
+        fn has_no_pressure_when_created() {
+            let switch = FuelPressureSwitch::new();
+
+            assert!(!switch.has_pressure());
+        }
+
+        #[test]
+        fn has_pressure_from_17_psi() {
+            let mut switch = FuelPressureSwitch::new();
+            switch.update(Pressure::new::<psi>(17.));
+
+            assert!(switch.has_pressure());
+        }
+
+        #[test]
+        fn has_no_pressure_below_17_psi_when_previously_without_pressure() {
+            let mut switch = FuelPressureSwitch::new();
+            switch.update(Pressure::new::<psi>(16.9));
+
+            assert!(!switch.has_pressure());
+        }
+
+        #[test]
+        fn keeps_pressure_between_16_and_17_psi_when_previously_with_pressure() {
+            let mut switch = FuelPressureSwitch::new();
+            switch.update(Pressure::new::<psi>(30.));
+            switch.update(Pressure::new::<psi>(16.5));
+
+            assert!(switch.has_pressure());
+        }
+
+        #[test]
+        fn keeps_pressure_restored_from_snapshot() {
+            let mut switch = FuelPressureSwitch::new();
+            switch.update(Pressure::new::<psi>(30.));
+            let mut snapshot = Snapshot::new();
+            switch.save(&mut snapshot, "SWITCH");
+
+            let mut restored_switch = FuelPressureSwitch::new();
+            restored_switch.restore(&snapshot, "SWITCH");
+            restored_switch.update(Pressure::new::<psi>(16.5));
+
+            assert!(restored_switch.has_pressure());
+        }
+
+        #[test]
+        fn loses_pressure_at_16_psi() {
+            let mut switch = FuelPressureSwitch::new();
+            switch.update(Pressure::new::<psi>(30.));
+            switch.update(Pressure::new::<psi>(16.));
+
+            assert!(!switch.has_pressure());
+        }
+    }
+}
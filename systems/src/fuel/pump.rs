@@ -0,0 +1,237 @@
+use crate::{
+    electrical::{consumption::PowerConsumer, ElectricalBusType},
+    simulation::{
+        Failure, FailureType, QuantityIdentifier, SimulationElement, SimulationElementVisitor,
+        SimulatorWriter, UpdateContext, VariableRegistry, VariableUnit,
+    },
+};
+use uom::si::{f64::*, power::watt, pressure::psi};
+
+/// A booster pump driven by an AC electrical motor, immersed in the tank it
+/// pumps fuel from. The pump runs when it is switched on, its motor is powered and
+/// it hasn't failed.
+/// It only delivers pressure into the fuel line while its tank has fuel remaining.
+pub struct FuelPump {
+    is_running_id: QuantityIdentifier<bool>,
+    is_running: bool,
+    tank_has_fuel_remaining: bool,
+    consumer: PowerConsumer,
+    failure: Failure,
+}
+impl FuelPump {
+    const DELIVERY_PRESSURE_PSI: f64 = 30.;
+    const POWER_CONSUMPTION_WATT: f64 = 800.;
+
+    pub fn new(
+        registry: &mut VariableRegistry,
+        id: &str,
+        number: usize,
+        bus_type: ElectricalBusType,
+    ) -> FuelPump {
+        FuelPump {
+            is_running_id: registry
+                .register_write(&format!("FUEL_{}_PUMP_IS_RUNNING", id), VariableUnit::Bool),
+            is_running: false,
+            tank_has_fuel_remaining: false,
+            consumer: PowerConsumer::from(bus_type),
+            failure: Failure::new(registry, FailureType::FuelPump(number)),
+        }
+    }
+
+    pub fn update(&mut self, _: &UpdateContext, should_run: bool, tank_has_fuel_remaining: bool) {
+        self.is_running = should_run && self.consumer.is_powered() && !self.failure.is_active();
+        self.tank_has_fuel_remaining = tank_has_fuel_remaining;
+
+        self.consumer.demand(if self.is_running {
+            Power::new::<watt>(FuelPump::POWER_CONSUMPTION_WATT)
+        } else {
+            Power::new::<watt>(0.)
+        });
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.is_running
+    }
+
+    /// The pressure the pump delivers into the fuel line.
+    pub fn pressure(&self) -> Pressure {
+        if self.is_running && self.tank_has_fuel_remaining {
+            Pressure::new::<psi>(FuelPump::DELIVERY_PRESSURE_PSI)
+        } else {
+            Pressure::new::<psi>(0.)
+        }
+    }
+}
+impl SimulationElement for FuelPump {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.consumer.accept(visitor);
+        self.failure.accept(visitor);
+
+        visitor.visit(self);
+    }
+
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_bool(&self.is_running_id, self.is_running);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        electrical::{
+            consumption::{PowerConsumptionReport, SuppliedPower},
+            Potential, PotentialOrigin,
+        },
+        simulation::{test::SimulationTestBed, Aircraft},
+    };
+    use uom::si::electric_potential::volt;
+
+    struct TestAircraft {
+        pump: FuelPump,
+        should_run: bool,
+        tank_has_fuel_remaining: bool,
+        is_ac_1_powered: bool,
+        consumed_power: Power,
+    }
+    impl TestAircraft {
+        fn new() -> Self {
+            Self {
+                pump: FuelPump::new(
+                    &mut VariableRegistry::new(),
+                    "L_TK_1",
+                    1,
+                    ElectricalBusType::AlternatingCurrent(1),
+                ),
+                should_run: true,
+                tank_has_fuel_remaining: true,
+                is_ac_1_powered: false,
+                consumed_power: Power::new::<watt>(0.),
+            }
+        }
+
+        fn powered(mut self) -> Self {
+            self.is_ac_1_powered = true;
+            self
+        }
+
+        fn switched_off(mut self) -> Self {
+            self.should_run = false;
+            self
+        }
+
+        fn with_empty_tank(mut self) -> Self {
+            self.tank_has_fuel_remaining = false;
+            self
+        }
+    }
+    impl Aircraft for TestAircraft {
+        fn update_after_power_distribution(&mut self, context: &UpdateContext) {
+            self.pump
+                .update(context, self.should_run, self.tank_has_fuel_remaining);
+        }
+
+        fn get_supplied_power(&mut self) -> SuppliedPower {
+            let mut supplied_power = SuppliedPower::new();
+            if self.is_ac_1_powered {
+                supplied_power.add(
+                    ElectricalBusType::AlternatingCurrent(1),
+                    Potential::single(
+                        PotentialOrigin::EngineGenerator(1),
+                        ElectricPotential::new::<volt>(115.),
+                    ),
+                );
+            }
+
+            supplied_power
+        }
+    }
+    impl SimulationElement for TestAircraft {
+        fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            self.pump.accept(visitor);
+
+            visitor.visit(self);
+        }
+
+        fn process_power_consumption_report<T: PowerConsumptionReport>(&mut self, report: &T) {
+            self.consumed_power = report.total_consumption_of(PotentialOrigin::EngineGenerator(1));
+        }
+    }
+
+    #[test]
+    fn does_not_run_when_unpowered() {
+        let mut aircraft = TestAircraft::new();
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert!(!aircraft.pump.is_running());
+        assert_eq!(aircraft.pump.pressure(), Pressure::new::<psi>(0.));
+    }
+
+    #[test]
+    fn delivers_pressure_when_powered() {
+        let mut aircraft = TestAircraft::new().powered();
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert!(aircraft.pump.is_running());
+        assert!(aircraft.pump.pressure() > Pressure::new::<psi>(20.));
+    }
+
+    #[test]
+    fn does_not_run_when_switched_off() {
+        let mut aircraft = TestAircraft::new().powered().switched_off();
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert!(!aircraft.pump.is_running());
+        assert_eq!(aircraft.pump.pressure(), Pressure::new::<psi>(0.));
+    }
+
+    #[test]
+    fn does_not_run_when_failed() {
+        let mut aircraft = TestAircraft::new().powered();
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.activate_failure(FailureType::FuelPump(1));
+
+        test_bed.run_aircraft(&mut aircraft);
+
+        assert!(!aircraft.pump.is_running());
+        assert_eq!(aircraft.pump.pressure(), Pressure::new::<psi>(0.));
+    }
+
+    #[test]
+    fn delivers_no_pressure_when_tank_is_empty() {
+        let mut aircraft = TestAircraft::new().powered().with_empty_tank();
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert!(aircraft.pump.is_running());
+        assert_eq!(aircraft.pump.pressure(), Pressure::new::<psi>(0.));
+    }
+
+    #[test]
+    fn consumes_power_when_running() {
+        let mut aircraft = TestAircraft::new().powered();
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert_eq!(
+            aircraft.consumed_power,
+            Power::new::<watt>(FuelPump::POWER_CONSUMPTION_WATT)
+        );
+    }
+
+    #[test]
+    fn consumes_no_power_when_switched_off() {
+        let mut aircraft = TestAircraft::new().powered().switched_off();
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert_eq!(aircraft.consumed_power, Power::new::<watt>(0.));
+    }
+
+    #[test]
+    fn writes_its_state() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+
+        test_bed.run_aircraft(&mut aircraft);
+
+        assert!(test_bed.contains_key("FUEL_L_TK_1_PUMP_IS_RUNNING"));
+    }
+}
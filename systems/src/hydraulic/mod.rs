@@ -1 +1,500 @@
+use crate::simulation::{
+    QuantityIdentifier, SimulationElement, SimulatorWriter, Snapshot, UpdateContext,
+    VariableRegistry, VariableUnit,
+};
+use uom::si::{
+    f64::*,
+    pressure::psi,
+    volume::gallon,
+    volume_rate::{gallon_per_minute, gallon_per_second},
+};
 
+mod power_transfer_unit;
+mod pump;
+mod ram_air_turbine;
+pub use power_transfer_unit::{PowerTransferUnit, PowerTransferUnitPort};
+pub use pump::{ElectricPump, EngineDrivenPump, RatPump};
+pub use ram_air_turbine::RamAirTurbine;
+
+/// A source of hydraulic fluid flow into a [`HydraulicLoop`].
+pub trait PressureSource {
+    /// The maximum flow the source can deliver into the loop at this moment.
+    fn max_flow(&self) -> VolumeRate;
+
+    /// The pressure up to which the source is able to pressurise the loop.
+    fn regulated_pressure(&self) -> Pressure;
+
+    /// Informs the source of the flow it actually delivered into the loop,
+    /// and the pressure it delivered the flow at.
+    fn set_delivered_flow(&mut self, flow: VolumeRate, pressure: Pressure);
+}
+
+/// Represents a single hydraulic circuit: its reservoir, the lines and
+/// an accumulator. Pressure is built up by the pressure sources connected
+/// to the loop, and lost through consumers and internal leakage.
+pub struct HydraulicLoop {
+    pressure_id: QuantityIdentifier<Pressure>,
+    reservoir_volume_id: QuantityIdentifier<Volume>,
+    is_pressurised_id: QuantityIdentifier<bool>,
+    snapshot_prefix: String,
+    pressure: Pressure,
+    fluid_volume: Volume,
+    reservoir_volume: Volume,
+    accumulator: Accumulator,
+}
+impl HydraulicLoop {
+    /// Below this pressure the loop is considered to have a low pressure.
+    const PRESSURISED_THRESHOLD_PSI: f64 = 1450.;
+    /// The pressure lost by pressure sources when delivering their maximum flow.
+    const PRESSURE_DROOP_AT_MAX_FLOW_PSI: f64 = 100.;
+    /// The pressure rise caused by a gallon of fluid entering the lines,
+    /// ignoring the accumulator.
+    const LINE_STIFFNESS_PSI_PER_GALLON: f64 = 60000.;
+    const INTERNAL_LEAKAGE_AT_3000_PSI_GALLON_PER_MINUTE: f64 = 0.6;
+
+    pub fn new(
+        registry: &mut VariableRegistry,
+        id: &str,
+        fluid_volume: Volume,
+        accumulator_volume: Volume,
+    ) -> HydraulicLoop {
+        HydraulicLoop {
+            pressure_id: registry
+                .register_write(&format!("HYD_{}_PRESSURE", id), VariableUnit::Psi),
+            reservoir_volume_id: registry
+                .register_write(&format!("HYD_{}_RESERVOIR", id), VariableUnit::Gallons),
+            is_pressurised_id: registry
+                .register_write(&format!("HYD_{}_IS_PRESSURISED", id), VariableUnit::Bool),
+            snapshot_prefix: format!("HYD_{}", id),
+            pressure: Pressure::new::<psi>(0.),
+            fluid_volume,
+            reservoir_volume: fluid_volume,
+            accumulator: Accumulator::new(accumulator_volume),
+        }
+    }
+
+    /// Updates the loop's pressure based on the flow the given sources can deliver
+    /// and the flow demanded by consumers connected to the loop.
+    pub fn update(
+        &mut self,
+        context: &UpdateContext,
+        sources: &mut [&mut dyn PressureSource],
+        demand: VolumeRate,
+    ) {
+        let max_flow = if self.reservoir_volume > Volume::new::<gallon>(0.) {
+            sources
+                .iter()
+                .map(|source| source.max_flow().get::<gallon_per_second>())
+                .sum()
+        } else {
+            0.
+        };
+
+        let regulated_pressure = sources
+            .iter()
+            .filter(|source| source.max_flow() > VolumeRate::new::<gallon_per_second>(0.))
+            .map(|source| source.regulated_pressure().get::<psi>())
+            .fold(0., f64::max);
+
+        let current_pressure = self.pressure.get::<psi>();
+        let demand = demand.get::<gallon_per_second>() + self.internal_leakage();
+        let target_pressure = Self::equilibrium_pressure(regulated_pressure, max_flow, demand);
+        let stiffness = self.stiffness();
+        let delta = context.delta().as_secs_f64();
+
+        let new_pressure = if target_pressure > current_pressure {
+            (current_pressure + (max_flow - demand).max(0.) * stiffness * delta)
+                .min(target_pressure)
+        } else {
+            (current_pressure - demand * stiffness * delta).max(target_pressure)
+        };
+
+        let delivered_flow = if delta > 0. && max_flow > 0. {
+            (demand + (new_pressure - current_pressure) / stiffness / delta).clamp(0., max_flow)
+        } else {
+            0.
+        };
+
+        for source in sources.iter_mut() {
+            let share = if max_flow > 0. {
+                source.max_flow().get::<gallon_per_second>() / max_flow
+            } else {
+                0.
+            };
+
+            source.set_delivered_flow(
+                VolumeRate::new::<gallon_per_second>(delivered_flow * share),
+                Pressure::new::<psi>(new_pressure),
+            );
+        }
+
+        self.pressure = Pressure::new::<psi>(new_pressure);
+        self.accumulator.update(self.pressure);
+        self.reservoir_volume = (self.fluid_volume
+            - self.accumulator.fluid_volume()
+            - Volume::new::<gallon>(new_pressure / Self::LINE_STIFFNESS_PSI_PER_GALLON))
+        .max(Volume::new::<gallon>(0.));
+    }
+
+    fn equilibrium_pressure(regulated_pressure: f64, max_flow: f64, demand: f64) -> f64 {
+        if max_flow <= 0. {
+            0.
+        } else {
+            let demand_ratio = demand / max_flow;
+            let pressure_at_max_flow =
+                (regulated_pressure - HydraulicLoop::PRESSURE_DROOP_AT_MAX_FLOW_PSI).max(0.);
+
+            if demand_ratio <= 1. {
+                regulated_pressure - (regulated_pressure - pressure_at_max_flow) * demand_ratio
+            } else {
+                pressure_at_max_flow / demand_ratio
+            }
+        }
+    }
+
+    fn internal_leakage(&self) -> f64 {
+        VolumeRate::new::<gallon_per_minute>(
+            HydraulicLoop::INTERNAL_LEAKAGE_AT_3000_PSI_GALLON_PER_MINUTE,
+        )
+        .get::<gallon_per_second>()
+            * (self.pressure.get::<psi>() / 3000.)
+    }
+
+    /// The pressure change caused by a gallon of fluid entering or leaving the loop.
+    fn stiffness(&self) -> f64 {
+        1. / (1. / HydraulicLoop::LINE_STIFFNESS_PSI_PER_GALLON
+            + self.accumulator.compliance(self.pressure))
+    }
+
+    pub fn pressure(&self) -> Pressure {
+        self.pressure
+    }
+
+    pub fn reservoir_volume(&self) -> Volume {
+        self.reservoir_volume
+    }
+
+    pub fn is_pressurised(&self) -> bool {
+        self.pressure.get::<psi>() > HydraulicLoop::PRESSURISED_THRESHOLD_PSI
+    }
+}
+impl SimulationElement for HydraulicLoop {
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_pressure(&self.pressure_id, self.pressure);
+        writer.write_volume(&self.reservoir_volume_id, self.reservoir_volume);
+        writer.write_bool(&self.is_pressurised_id, self.is_pressurised());
+    }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        snapshot.save_f64(self.pressure_id.name(), self.pressure.get::<psi>());
+        snapshot.save_f64(
+            self.reservoir_volume_id.name(),
+            self.reservoir_volume.get::<gallon>(),
+        );
+        self.accumulator.save(snapshot, &self.snapshot_prefix);
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        if let Some(pressure) = snapshot.restore_f64(self.pressure_id.name()) {
+            self.pressure = Pressure::new::<psi>(pressure);
+        }
+        if let Some(reservoir_volume) = snapshot.restore_f64(self.reservoir_volume_id.name()) {
+            self.reservoir_volume = Volume::new::<gallon>(reservoir_volume);
+        }
+        self.accumulator.restore(snapshot, &self.snapshot_prefix);
+    }
+}
+
+/// A gas charged accumulator, which stores fluid under pressure.
+/// The accumulator dampens pressure changes in the loop and keeps the loop
+/// pressurised for a while after all pressure sources stop.
+struct Accumulator {
+    total_volume: Volume,
+    fluid_volume: Volume,
+}
+impl Accumulator {
+    const GAS_PRECHARGE_PSI: f64 = 1885.;
+
+    fn new(total_volume: Volume) -> Self {
+        Self {
+            total_volume,
+            fluid_volume: Volume::new::<gallon>(0.),
+        }
+    }
+
+    fn update(&mut self, pressure: Pressure) {
+        // The gas is compressed isothermally once the loop pressure exceeds the precharge.
+        self.fluid_volume = if pressure.get::<psi>() > Accumulator::GAS_PRECHARGE_PSI {
+            self.total_volume * (1. - Accumulator::GAS_PRECHARGE_PSI / pressure.get::<psi>())
+        } else {
+            Volume::new::<gallon>(0.)
+        };
+    }
+
+    /// The volume in gallons the accumulator takes in per psi of pressure increase.
+    fn compliance(&self, pressure: Pressure) -> f64 {
+        if pressure.get::<psi>() > Accumulator::GAS_PRECHARGE_PSI {
+            self.total_volume.get::<gallon>() * Accumulator::GAS_PRECHARGE_PSI
+                / pressure.get::<psi>().powi(2)
+        } else {
+            0.
+        }
+    }
+
+    fn fluid_volume(&self) -> Volume {
+        self.fluid_volume
+    }
+
+    fn save(&self, snapshot: &mut Snapshot, prefix: &str) {
+        snapshot.save_f64(
+            &format!("{}_ACCUMULATOR_FLUID_VOLUME", prefix),
+            self.fluid_volume.get::<gallon>(),
+        );
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot, prefix: &str) {
+        if let Some(fluid_volume) =
+            snapshot.restore_f64(&format!("{}_ACCUMULATOR_FLUID_VOLUME", prefix))
+        {
+            self.fluid_volume = Volume::new::<gallon>(fluid_volume);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{
+        test::SimulationTestBed, Aircraft, SimulationElementVisitor, UpdateContext,
+    };
+    use std::time::Duration;
+
+    struct TestPressureSource {
+        max_flow: VolumeRate,
+        delivered_flow: VolumeRate,
+    }
+    impl TestPressureSource {
+        fn new() -> Self {
+            Self {
+                max_flow: VolumeRate::new::<gallon_per_minute>(0.),
+                delivered_flow: VolumeRate::new::<gallon_per_minute>(0.),
+            }
+        }
+
+        fn set_max_flow(&mut self, flow: VolumeRate) {
+            self.max_flow = flow;
+        }
+    }
+    impl PressureSource for TestPressureSource {
+        fn max_flow(&self) -> VolumeRate {
+            self.max_flow
+        }
+
+        fn regulated_pressure(&self) -> Pressure {
+            Pressure::new::<psi>(3000.)
+        }
+
+        fn set_delivered_flow(&mut self, flow: VolumeRate, _: Pressure) {
+            self.delivered_flow = flow;
+        }
+    }
+
+    struct TestAircraft {
+        hydraulic_loop: HydraulicLoop,
+        source: TestPressureSource,
+        demand: VolumeRate,
+    }
+    impl TestAircraft {
+        fn new() -> Self {
+            Self {
+                hydraulic_loop: HydraulicLoop::new(
+                    &mut VariableRegistry::new(),
+                    "GREEN",
+                    Volume::new::<gallon>(3.6),
+                    Volume::new::<gallon>(0.264),
+                ),
+                source: TestPressureSource::new(),
+                demand: VolumeRate::new::<gallon_per_minute>(0.),
+            }
+        }
+
+        fn pump_at(&mut self, flow: VolumeRate) {
+            self.source.set_max_flow(flow);
+        }
+
+        fn demand(&mut self, flow: VolumeRate) {
+            self.demand = flow;
+        }
+
+        fn pressure(&self) -> Pressure {
+            self.hydraulic_loop.pressure()
+        }
+
+        fn reservoir_volume(&self) -> Volume {
+            self.hydraulic_loop.reservoir_volume()
+        }
+
+        fn is_pressurised(&self) -> bool {
+            self.hydraulic_loop.is_pressurised()
+        }
+
+        fn delivered_flow(&self) -> VolumeRate {
+            self.source.delivered_flow
+        }
+    }
+    impl Aircraft for TestAircraft {
+        fn update_after_power_distribution(&mut self, context: &UpdateContext) {
+            self.hydraulic_loop
+                .update(context, &mut [&mut self.source], self.demand);
+        }
+    }
+    impl SimulationElement for TestAircraft {
+        fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            self.hydraulic_loop.accept(visitor);
+
+            visitor.visit(self);
+        }
+    }
+
+    fn run_for(test_bed: &mut SimulationTestBed, aircraft: &mut TestAircraft, delta: Duration) {
+        test_bed.set_delta(delta);
+        test_bed.run_aircraft(aircraft);
+    }
+
+    fn pressurised_aircraft(test_bed: &mut SimulationTestBed) -> TestAircraft {
+        let mut aircraft = TestAircraft::new();
+        aircraft.pump_at(VolumeRate::new::<gallon_per_minute>(37.));
+
+        for _ in 0..10 {
+            run_for(test_bed, &mut aircraft, Duration::from_millis(100));
+        }
+
+        aircraft
+    }
+
+    #[test]
+    fn starts_depressurised() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+
+        test_bed.run_aircraft(&mut aircraft);
+
+        assert_eq!(aircraft.pressure(), Pressure::new::<psi>(0.));
+        assert!(!aircraft.is_pressurised());
+    }
+
+    #[test]
+    fn pressurises_to_regulated_pressure_when_pumped() {
+        let mut test_bed = SimulationTestBed::new();
+        let aircraft = pressurised_aircraft(&mut test_bed);
+
+        assert!(aircraft.is_pressurised());
+        assert!(aircraft.pressure() > Pressure::new::<psi>(2900.));
+        assert!(aircraft.pressure() <= Pressure::new::<psi>(3000.));
+    }
+
+    #[test]
+    fn pressure_does_not_overshoot_with_large_delta() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+
+        aircraft.pump_at(VolumeRate::new::<gallon_per_minute>(37.));
+        run_for(&mut test_bed, &mut aircraft, Duration::from_secs(100));
+
+        assert!(aircraft.pressure() <= Pressure::new::<psi>(3000.));
+        assert!(aircraft.is_pressurised());
+    }
+
+    #[test]
+    fn accumulator_keeps_loop_pressurised_for_a_while_after_pumping_stops() {
+        let mut test_bed = SimulationTestBed::new();
+        let mut aircraft = pressurised_aircraft(&mut test_bed);
+
+        aircraft.pump_at(VolumeRate::new::<gallon_per_minute>(0.));
+        run_for(&mut test_bed, &mut aircraft, Duration::from_secs(1));
+
+        assert!(aircraft.is_pressurised());
+    }
+
+    #[test]
+    fn loses_pressure_after_pumping_stops() {
+        let mut test_bed = SimulationTestBed::new();
+        let mut aircraft = pressurised_aircraft(&mut test_bed);
+
+        aircraft.pump_at(VolumeRate::new::<gallon_per_minute>(0.));
+        for _ in 0..60 {
+            run_for(&mut test_bed, &mut aircraft, Duration::from_secs(1));
+        }
+
+        assert!(!aircraft.is_pressurised());
+    }
+
+    #[test]
+    fn demand_exceeding_pump_capacity_lowers_pressure() {
+        let mut test_bed = SimulationTestBed::new();
+        let mut aircraft = pressurised_aircraft(&mut test_bed);
+
+        aircraft.demand(VolumeRate::new::<gallon_per_minute>(74.));
+        for _ in 0..10 {
+            run_for(&mut test_bed, &mut aircraft, Duration::from_millis(100));
+        }
+
+        assert!(aircraft.pressure() < Pressure::new::<psi>(1500.));
+    }
+
+    #[test]
+    fn delivers_flow_demanded_by_consumers() {
+        let mut test_bed = SimulationTestBed::new();
+        let mut aircraft = pressurised_aircraft(&mut test_bed);
+
+        aircraft.demand(VolumeRate::new::<gallon_per_minute>(10.));
+        for _ in 0..10 {
+            run_for(&mut test_bed, &mut aircraft, Duration::from_millis(100));
+        }
+
+        assert!(aircraft.delivered_flow() > VolumeRate::new::<gallon_per_minute>(10.));
+        assert!(aircraft.delivered_flow() < VolumeRate::new::<gallon_per_minute>(11.));
+    }
+
+    #[test]
+    fn reservoir_volume_decreases_when_pressurised() {
+        let mut test_bed = SimulationTestBed::new();
+        let aircraft = pressurised_aircraft(&mut test_bed);
+
+        assert!(aircraft.reservoir_volume() < Volume::new::<gallon>(3.6));
+    }
+
+    #[test]
+    fn pressure_and_fluid_volumes_are_restored_from_snapshot() {
+        let mut test_bed = SimulationTestBed::new();
+        let mut aircraft = pressurised_aircraft(&mut test_bed);
+
+        let mut restored_aircraft = TestAircraft::new();
+        Snapshot::take(&mut aircraft).restore(&mut restored_aircraft);
+
+        assert_eq!(restored_aircraft.pressure(), aircraft.pressure());
+        assert_eq!(
+            restored_aircraft.reservoir_volume(),
+            aircraft.reservoir_volume()
+        );
+        assert_eq!(
+            restored_aircraft.hydraulic_loop.accumulator.fluid_volume(),
+            aircraft.hydraulic_loop.accumulator.fluid_volume()
+        );
+        assert!(
+            restored_aircraft.hydraulic_loop.accumulator.fluid_volume() > Volume::new::<gallon>(0.)
+        );
+    }
+
+    #[test]
+    fn writes_its_state() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+
+        test_bed.run_aircraft(&mut aircraft);
+
+        assert!(test_bed.contains_key("HYD_GREEN_PRESSURE"));
+        assert!(test_bed.contains_key("HYD_GREEN_RESERVOIR"));
+        assert!(test_bed.contains_key("HYD_GREEN_IS_PRESSURISED"));
+    }
+}
@@ -0,0 +1,308 @@
+use super::PressureSource;
+use crate::simulation::{
+    QuantityIdentifier, SimulationElement, SimulatorWriter, UpdateContext, VariableRegistry,
+    VariableUnit,
+};
+use uom::si::{f64::*, pressure::psi, volume_rate::gallon_per_minute};
+
+/// One side of a [`PowerTransferUnit`]. When the side is being driven by
+/// the other side's loop, it acts as a pressure source for its own loop.
+pub struct PowerTransferUnitPort {
+    max_flow: VolumeRate,
+    regulated_pressure: Pressure,
+    delivered_flow: VolumeRate,
+}
+impl PowerTransferUnitPort {
+    fn new() -> Self {
+        Self {
+            max_flow: VolumeRate::new::<gallon_per_minute>(0.),
+            regulated_pressure: Pressure::new::<psi>(0.),
+            delivered_flow: VolumeRate::new::<gallon_per_minute>(0.),
+        }
+    }
+
+    fn drive(&mut self, driving_pressure: Pressure, driven_pressure: Pressure) {
+        // The unit's flow increases with the pressure difference between both loops.
+        let flow_ratio = ((driving_pressure - driven_pressure).get::<psi>()
+            - PowerTransferUnit::PRESSURE_DIFFERENCE_WHEN_DRIVING_PSI)
+            / (PowerTransferUnit::PRESSURE_DIFFERENCE_AT_MAX_FLOW_PSI
+                - PowerTransferUnit::PRESSURE_DIFFERENCE_WHEN_DRIVING_PSI);
+
+        self.max_flow = VolumeRate::new::<gallon_per_minute>(
+            PowerTransferUnit::MAX_FLOW_GPM * flow_ratio.clamp(0., 1.),
+        );
+        self.regulated_pressure = driving_pressure
+            - Pressure::new::<psi>(PowerTransferUnit::PRESSURE_DIFFERENCE_WHEN_DRIVING_PSI);
+    }
+
+    fn stop(&mut self) {
+        self.max_flow = VolumeRate::new::<gallon_per_minute>(0.);
+        self.regulated_pressure = Pressure::new::<psi>(0.);
+    }
+}
+impl PressureSource for PowerTransferUnitPort {
+    fn max_flow(&self) -> VolumeRate {
+        self.max_flow
+    }
+
+    fn regulated_pressure(&self) -> Pressure {
+        self.regulated_pressure
+    }
+
+    fn set_delivered_flow(&mut self, flow: VolumeRate, _: Pressure) {
+        self.delivered_flow = flow;
+    }
+}
+
+/// A hydraulic motor and pump on a common shaft which connects two hydraulic loops.
+/// When the pressure difference between the loops becomes too large, the loop with
+/// the highest pressure drives the loop with the lowest pressure. The unit cannot
+/// transfer fluid between the loops.
+pub struct PowerTransferUnit {
+    is_active_id: QuantityIdentifier<bool>,
+    is_active: bool,
+    left_drives_right: bool,
+    left: PowerTransferUnitPort,
+    right: PowerTransferUnitPort,
+}
+impl PowerTransferUnit {
+    const ACTIVATION_PRESSURE_DIFFERENCE_PSI: f64 = 500.;
+    const DEACTIVATION_PRESSURE_DIFFERENCE_PSI: f64 = 150.;
+    const PRESSURE_DIFFERENCE_WHEN_DRIVING_PSI: f64 = 200.;
+    const PRESSURE_DIFFERENCE_AT_MAX_FLOW_PSI: f64 = 1500.;
+    const MAX_FLOW_GPM: f64 = 22.;
+    const EFFICIENCY: f64 = 0.8;
+
+    pub fn new(registry: &mut VariableRegistry) -> PowerTransferUnit {
+        PowerTransferUnit {
+            is_active_id: registry.register_write("HYD_PTU_IS_ACTIVE", VariableUnit::Bool),
+            is_active: false,
+            left_drives_right: false,
+            left: PowerTransferUnitPort::new(),
+            right: PowerTransferUnitPort::new(),
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        _: &UpdateContext,
+        left_pressure: Pressure,
+        right_pressure: Pressure,
+        is_enabled: bool,
+    ) {
+        if !self.is_active {
+            self.left_drives_right = left_pressure > right_pressure;
+        }
+
+        // Once active, the unit keeps turning in the same direction until
+        // the driven loop catches up with the driving loop.
+        let (driving_pressure, driven_pressure) = if self.left_drives_right {
+            (left_pressure, right_pressure)
+        } else {
+            (right_pressure, left_pressure)
+        };
+        let pressure_difference = (driving_pressure - driven_pressure).get::<psi>();
+
+        self.is_active = is_enabled
+            && if self.is_active {
+                pressure_difference > PowerTransferUnit::DEACTIVATION_PRESSURE_DIFFERENCE_PSI
+            } else {
+                pressure_difference > PowerTransferUnit::ACTIVATION_PRESSURE_DIFFERENCE_PSI
+            };
+
+        if !self.is_active {
+            self.left.stop();
+            self.right.stop();
+        } else if self.left_drives_right {
+            self.left.stop();
+            self.right.drive(left_pressure, right_pressure);
+        } else {
+            self.left.drive(right_pressure, left_pressure);
+            self.right.stop();
+        }
+    }
+
+    pub fn left_mut(&mut self) -> &mut PowerTransferUnitPort {
+        &mut self.left
+    }
+
+    pub fn right_mut(&mut self) -> &mut PowerTransferUnitPort {
+        &mut self.right
+    }
+
+    /// The flow the unit takes from the left loop to drive the right loop.
+    pub fn demand_on_left(&self) -> VolumeRate {
+        self.right.delivered_flow / PowerTransferUnit::EFFICIENCY
+    }
+
+    /// The flow the unit takes from the right loop to drive the left loop.
+    pub fn demand_on_right(&self) -> VolumeRate {
+        self.left.delivered_flow / PowerTransferUnit::EFFICIENCY
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.is_active
+    }
+}
+impl SimulationElement for PowerTransferUnit {
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_bool(&self.is_active_id, self.is_active);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{test::SimulationTestBed, Aircraft, SimulationElementVisitor};
+
+    struct TestAircraft {
+        ptu: PowerTransferUnit,
+        left_pressure: Pressure,
+        right_pressure: Pressure,
+        is_enabled: bool,
+    }
+    impl TestAircraft {
+        fn new() -> Self {
+            Self {
+                ptu: PowerTransferUnit::new(&mut VariableRegistry::new()),
+                left_pressure: Pressure::new::<psi>(0.),
+                right_pressure: Pressure::new::<psi>(0.),
+                is_enabled: true,
+            }
+        }
+
+        fn set_pressures(&mut self, left: f64, right: f64) {
+            self.left_pressure = Pressure::new::<psi>(left);
+            self.right_pressure = Pressure::new::<psi>(right);
+        }
+
+        fn disable(&mut self) {
+            self.is_enabled = false;
+        }
+    }
+    impl Aircraft for TestAircraft {
+        fn update_after_power_distribution(&mut self, context: &UpdateContext) {
+            self.ptu.update(
+                context,
+                self.left_pressure,
+                self.right_pressure,
+                self.is_enabled,
+            );
+        }
+    }
+    impl SimulationElement for TestAircraft {
+        fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            self.ptu.accept(visitor);
+
+            visitor.visit(self);
+        }
+    }
+
+    fn no_flow() -> VolumeRate {
+        VolumeRate::new::<gallon_per_minute>(0.)
+    }
+
+    #[test]
+    fn inactive_when_pressures_are_equal() {
+        let mut aircraft = TestAircraft::new();
+        aircraft.set_pressures(3000., 3000.);
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert!(!aircraft.ptu.is_active());
+        assert_eq!(aircraft.ptu.left_mut().max_flow(), no_flow());
+        assert_eq!(aircraft.ptu.right_mut().max_flow(), no_flow());
+    }
+
+    #[test]
+    fn left_drives_right_when_right_pressure_is_low() {
+        let mut aircraft = TestAircraft::new();
+        aircraft.set_pressures(3000., 0.);
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert!(aircraft.ptu.is_active());
+        assert_eq!(aircraft.ptu.left_mut().max_flow(), no_flow());
+        assert!(aircraft.ptu.right_mut().max_flow() > no_flow());
+    }
+
+    #[test]
+    fn right_drives_left_when_left_pressure_is_low() {
+        let mut aircraft = TestAircraft::new();
+        aircraft.set_pressures(0., 3000.);
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert!(aircraft.ptu.is_active());
+        assert!(aircraft.ptu.left_mut().max_flow() > no_flow());
+        assert_eq!(aircraft.ptu.right_mut().max_flow(), no_flow());
+    }
+
+    #[test]
+    fn does_not_activate_below_activation_pressure_difference() {
+        let mut aircraft = TestAircraft::new();
+        aircraft.set_pressures(3000., 2600.);
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert!(!aircraft.ptu.is_active());
+    }
+
+    #[test]
+    fn remains_active_until_pressure_difference_is_small() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+        aircraft.set_pressures(3000., 0.);
+        test_bed.run_aircraft(&mut aircraft);
+
+        aircraft.set_pressures(3000., 2800.);
+        test_bed.run_aircraft(&mut aircraft);
+        assert!(aircraft.ptu.is_active());
+
+        aircraft.set_pressures(3000., 2900.);
+        test_bed.run_aircraft(&mut aircraft);
+        assert!(!aircraft.ptu.is_active());
+    }
+
+    #[test]
+    fn does_not_reverse_direction_while_active() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+        aircraft.set_pressures(3000., 0.);
+        test_bed.run_aircraft(&mut aircraft);
+
+        aircraft.set_pressures(2000., 3000.);
+        test_bed.run_aircraft(&mut aircraft);
+
+        assert!(!aircraft.ptu.is_active());
+        assert_eq!(aircraft.ptu.left_mut().max_flow(), no_flow());
+    }
+
+    #[test]
+    fn inactive_when_disabled() {
+        let mut aircraft = TestAircraft::new();
+        aircraft.set_pressures(3000., 0.);
+        aircraft.disable();
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert!(!aircraft.ptu.is_active());
+    }
+
+    #[test]
+    fn demands_more_flow_from_driving_side_than_it_delivers() {
+        let mut ptu = PowerTransferUnit::new(&mut VariableRegistry::new());
+        ptu.right_mut().set_delivered_flow(
+            VolumeRate::new::<gallon_per_minute>(8.),
+            Pressure::new::<psi>(2800.),
+        );
+
+        assert!(ptu.demand_on_left() > VolumeRate::new::<gallon_per_minute>(8.));
+        assert_eq!(ptu.demand_on_right(), no_flow());
+    }
+
+    #[test]
+    fn writes_its_state() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+
+        test_bed.run_aircraft(&mut aircraft);
+
+        assert!(test_bed.contains_key("HYD_PTU_IS_ACTIVE"));
+    }
+}
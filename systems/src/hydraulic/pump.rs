@@ -0,0 +1,569 @@
+use super::PressureSource;
+use crate::{
+    electrical::{consumption::PowerConsumer, ElectricalBusType},
+    simulation::{
+        Failure, FailureType, QuantityIdentifier, SimulationElement, SimulationElementVisitor,
+        SimulatorWriter, Snapshot, UpdateContext, VariableRegistry, VariableUnit,
+    },
+};
+use uom::si::{
+    angular_velocity::revolution_per_minute,
+    f64::*,
+    power::watt,
+    pressure::psi,
+    ratio::percent,
+    volume::cubic_inch,
+    volume_rate::{cubic_inch_per_minute, gallon_per_minute},
+};
+
+/// A variable displacement pump. The pump delivers flow proportional
+/// to its speed, as long as it isn't depressurised.
+struct Pump {
+    displacement: Volume,
+    speed: AngularVelocity,
+    is_pressurising: bool,
+    delivered_flow: VolumeRate,
+    delivered_pressure: Pressure,
+}
+impl Pump {
+    const REGULATED_PRESSURE_PSI: f64 = 3000.;
+
+    fn new(displacement: Volume) -> Self {
+        Self {
+            displacement,
+            speed: AngularVelocity::new::<revolution_per_minute>(0.),
+            is_pressurising: false,
+            delivered_flow: VolumeRate::new::<gallon_per_minute>(0.),
+            delivered_pressure: Pressure::new::<psi>(0.),
+        }
+    }
+
+    fn update(&mut self, speed: AngularVelocity, is_pressurising: bool) {
+        self.speed = speed;
+        self.is_pressurising = is_pressurising;
+    }
+
+    fn speed(&self) -> AngularVelocity {
+        self.speed
+    }
+
+    fn is_active(&self) -> bool {
+        self.is_pressurising && self.speed.get::<revolution_per_minute>() > 0.
+    }
+
+    fn flow(&self) -> VolumeRate {
+        self.delivered_flow
+    }
+
+    /// The hydraulic power the pump delivered into the loop.
+    fn hydraulic_power(&self) -> Power {
+        self.delivered_pressure * self.delivered_flow
+    }
+}
+impl PressureSource for Pump {
+    fn max_flow(&self) -> VolumeRate {
+        if self.is_pressurising {
+            VolumeRate::new::<cubic_inch_per_minute>(
+                self.displacement.get::<cubic_inch>() * self.speed.get::<revolution_per_minute>(),
+            )
+        } else {
+            VolumeRate::new::<gallon_per_minute>(0.)
+        }
+    }
+
+    fn regulated_pressure(&self) -> Pressure {
+        Pressure::new::<psi>(Pump::REGULATED_PRESSURE_PSI)
+    }
+
+    fn set_delivered_flow(&mut self, flow: VolumeRate, pressure: Pressure) {
+        self.delivered_flow = flow;
+        self.delivered_pressure = pressure;
+    }
+}
+
+macro_rules! pressure_source_via_pump {
+    ($t: ty) => {
+        impl PressureSource for $t {
+            fn max_flow(&self) -> VolumeRate {
+                self.pump.max_flow()
+            }
+
+            fn regulated_pressure(&self) -> Pressure {
+                self.pump.regulated_pressure()
+            }
+
+            fn set_delivered_flow(&mut self, flow: VolumeRate, pressure: Pressure) {
+                self.pump.set_delivered_flow(flow, pressure);
+            }
+        }
+    };
+}
+
+/// A pump driven by the engine's accessory gearbox.
+/// It turns whenever the engine turns, and only delivers flow
+/// when it isn't depressurised through its push button and hasn't failed.
+pub struct EngineDrivenPump {
+    is_active_id: QuantityIdentifier<bool>,
+    pump: Pump,
+    failure: Failure,
+}
+impl EngineDrivenPump {
+    const DISPLACEMENT_CUBIC_INCH: f64 = 2.4;
+    const PUMP_RPM_PER_PERCENT_N2: f64 = 40.;
+
+    pub fn new(registry: &mut VariableRegistry, id: &str, number: usize) -> EngineDrivenPump {
+        EngineDrivenPump {
+            is_active_id: registry
+                .register_write(&format!("HYD_{}_PUMP_IS_ACTIVE", id), VariableUnit::Bool),
+            pump: Pump::new(Volume::new::<cubic_inch>(
+                EngineDrivenPump::DISPLACEMENT_CUBIC_INCH,
+            )),
+            failure: Failure::new(registry, FailureType::HydraulicPump(number)),
+        }
+    }
+
+    pub fn update(&mut self, _: &UpdateContext, engine_n2: Ratio, should_pressurise: bool) {
+        self.pump.update(
+            AngularVelocity::new::<revolution_per_minute>(
+                engine_n2.get::<percent>() * EngineDrivenPump::PUMP_RPM_PER_PERCENT_N2,
+            ),
+            should_pressurise && !self.failure.is_active(),
+        );
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.pump.is_active()
+    }
+
+    pub fn flow(&self) -> VolumeRate {
+        self.pump.flow()
+    }
+}
+pressure_source_via_pump!(EngineDrivenPump);
+impl SimulationElement for EngineDrivenPump {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.failure.accept(visitor);
+
+        visitor.visit(self);
+    }
+
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_bool(&self.is_active_id, self.is_active());
+    }
+}
+
+/// A pump driven by an AC electrical motor. The motor spools up when the pump
+/// should pressurise, its bus is powered and it hasn't failed, and spools down otherwise.
+/// The power drawn from the bus depends on the hydraulic power the pump delivers.
+pub struct ElectricPump {
+    is_active_id: QuantityIdentifier<bool>,
+    speed_snapshot_key: String,
+    pump: Pump,
+    consumer: PowerConsumer,
+    failure: Failure,
+}
+impl ElectricPump {
+    const DISPLACEMENT_CUBIC_INCH: f64 = 0.263;
+    const NOMINAL_SPEED_RPM: f64 = 7600.;
+    const SPOOL_TIME_SECONDS: f64 = 0.5;
+    const MOTOR_NO_LOAD_POWER_WATT: f64 = 400.;
+    const MOTOR_EFFICIENCY: f64 = 0.75;
+
+    pub fn new(
+        registry: &mut VariableRegistry,
+        id: &str,
+        number: usize,
+        bus_type: ElectricalBusType,
+    ) -> ElectricPump {
+        ElectricPump {
+            is_active_id: registry
+                .register_write(&format!("HYD_{}_PUMP_IS_ACTIVE", id), VariableUnit::Bool),
+            speed_snapshot_key: format!("HYD_{}_PUMP_SPEED", id),
+            pump: Pump::new(Volume::new::<cubic_inch>(
+                ElectricPump::DISPLACEMENT_CUBIC_INCH,
+            )),
+            consumer: PowerConsumer::from(bus_type),
+            failure: Failure::new(registry, FailureType::HydraulicPump(number)),
+        }
+    }
+
+    pub fn update(&mut self, context: &UpdateContext, should_pressurise: bool) {
+        let should_run =
+            should_pressurise && self.consumer.is_powered() && !self.failure.is_active();
+        let target_speed = if should_run {
+            ElectricPump::NOMINAL_SPEED_RPM
+        } else {
+            0.
+        };
+
+        let current_speed = self.pump.speed().get::<revolution_per_minute>();
+        let max_change = ElectricPump::NOMINAL_SPEED_RPM / ElectricPump::SPOOL_TIME_SECONDS
+            * context.delta().as_secs_f64();
+        let speed = if target_speed > current_speed {
+            (current_speed + max_change).min(target_speed)
+        } else {
+            (current_speed - max_change).max(target_speed)
+        };
+
+        self.pump.update(
+            AngularVelocity::new::<revolution_per_minute>(speed),
+            should_run,
+        );
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.pump.is_active()
+    }
+
+    pub fn flow(&self) -> VolumeRate {
+        self.pump.flow()
+    }
+}
+impl PressureSource for ElectricPump {
+    fn max_flow(&self) -> VolumeRate {
+        self.pump.max_flow()
+    }
+
+    fn regulated_pressure(&self) -> Pressure {
+        self.pump.regulated_pressure()
+    }
+
+    fn set_delivered_flow(&mut self, flow: VolumeRate, pressure: Pressure) {
+        self.pump.set_delivered_flow(flow, pressure);
+
+        let demand = if self.pump.speed().get::<revolution_per_minute>() > 0. {
+            Power::new::<watt>(ElectricPump::MOTOR_NO_LOAD_POWER_WATT)
+                + self.pump.hydraulic_power() / ElectricPump::MOTOR_EFFICIENCY
+        } else {
+            Power::new::<watt>(0.)
+        };
+        self.consumer.demand(demand);
+    }
+}
+impl SimulationElement for ElectricPump {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.consumer.accept(visitor);
+        self.failure.accept(visitor);
+
+        visitor.visit(self);
+    }
+
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_bool(&self.is_active_id, self.is_active());
+    }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        snapshot.save_f64(
+            &self.speed_snapshot_key,
+            self.pump.speed().get::<revolution_per_minute>(),
+        );
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        if let Some(speed) = snapshot.restore_f64(&self.speed_snapshot_key) {
+            self.pump.speed = AngularVelocity::new::<revolution_per_minute>(speed);
+        }
+    }
+}
+
+/// The pump driven by the ram air turbine's propeller.
+pub struct RatPump {
+    is_active_id: QuantityIdentifier<bool>,
+    pump: Pump,
+}
+impl RatPump {
+    const DISPLACEMENT_CUBIC_INCH: f64 = 0.22;
+
+    pub fn new(registry: &mut VariableRegistry) -> RatPump {
+        RatPump {
+            is_active_id: registry.register_write("HYD_RAT_PUMP_IS_ACTIVE", VariableUnit::Bool),
+            pump: Pump::new(Volume::new::<cubic_inch>(RatPump::DISPLACEMENT_CUBIC_INCH)),
+        }
+    }
+
+    pub fn update(&mut self, _: &UpdateContext, propeller_speed: AngularVelocity) {
+        self.pump.update(propeller_speed, true);
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.pump.is_active()
+    }
+
+    pub fn flow(&self) -> VolumeRate {
+        self.pump.flow()
+    }
+}
+pressure_source_via_pump!(RatPump);
+impl SimulationElement for RatPump {
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_bool(&self.is_active_id, self.is_active());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        electrical::{
+            consumption::{PowerConsumptionReport, SuppliedPower},
+            Potential, PotentialOrigin,
+        },
+        simulation::{test::SimulationTestBed, Aircraft},
+    };
+    use std::time::Duration;
+    use uom::si::electric_potential::volt;
+
+    struct TestAircraft {
+        engine_driven_pump: EngineDrivenPump,
+        electric_pump: ElectricPump,
+        rat_pump: RatPump,
+        engine_n2: Ratio,
+        rat_speed: AngularVelocity,
+        should_pressurise: bool,
+        is_ac_1_powered: bool,
+        electric_pump_flow: VolumeRate,
+        consumed_power: Power,
+    }
+    impl TestAircraft {
+        fn new() -> Self {
+            let mut registry = VariableRegistry::new();
+
+            Self {
+                engine_driven_pump: EngineDrivenPump::new(&mut registry, "ENG_1", 1),
+                electric_pump: ElectricPump::new(
+                    &mut registry,
+                    "BLUE_ELEC",
+                    3,
+                    ElectricalBusType::AlternatingCurrent(1),
+                ),
+                rat_pump: RatPump::new(&mut registry),
+                engine_n2: Ratio::new::<percent>(0.),
+                rat_speed: AngularVelocity::new::<revolution_per_minute>(0.),
+                should_pressurise: true,
+                is_ac_1_powered: false,
+                electric_pump_flow: VolumeRate::new::<gallon_per_minute>(0.),
+                consumed_power: Power::new::<watt>(0.),
+            }
+        }
+
+        fn with_electric_pump_delivering(mut self, flow: VolumeRate) -> Self {
+            self.electric_pump_flow = flow;
+            self
+        }
+
+        fn powered(mut self) -> Self {
+            self.is_ac_1_powered = true;
+            self
+        }
+
+        fn with_engine_running(mut self) -> Self {
+            self.engine_n2 = Ratio::new::<percent>(80.);
+            self
+        }
+
+        fn with_rat_turning(mut self) -> Self {
+            self.rat_speed = AngularVelocity::new::<revolution_per_minute>(6000.);
+            self
+        }
+
+        fn depressurised(mut self) -> Self {
+            self.should_pressurise = false;
+            self
+        }
+    }
+    impl Aircraft for TestAircraft {
+        fn update_after_power_distribution(&mut self, context: &UpdateContext) {
+            self.engine_driven_pump
+                .update(context, self.engine_n2, self.should_pressurise);
+            self.electric_pump.update(context, self.should_pressurise);
+            self.electric_pump
+                .set_delivered_flow(self.electric_pump_flow, Pressure::new::<psi>(3000.));
+            self.rat_pump.update(context, self.rat_speed);
+        }
+
+        fn get_supplied_power(&mut self) -> SuppliedPower {
+            let mut supplied_power = SuppliedPower::new();
+            if self.is_ac_1_powered {
+                supplied_power.add(
+                    ElectricalBusType::AlternatingCurrent(1),
+                    Potential::single(
+                        PotentialOrigin::EngineGenerator(1),
+                        ElectricPotential::new::<volt>(115.),
+                    ),
+                );
+            }
+
+            supplied_power
+        }
+    }
+    impl SimulationElement for TestAircraft {
+        fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            self.engine_driven_pump.accept(visitor);
+            self.electric_pump.accept(visitor);
+            self.rat_pump.accept(visitor);
+
+            visitor.visit(self);
+        }
+
+        fn process_power_consumption_report<T: PowerConsumptionReport>(&mut self, report: &T) {
+            self.consumed_power = report.total_consumption_of(PotentialOrigin::EngineGenerator(1));
+        }
+    }
+
+    #[test]
+    fn engine_driven_pump_delivers_no_flow_when_engine_stopped() {
+        let mut aircraft = TestAircraft::new();
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert_eq!(
+            aircraft.engine_driven_pump.max_flow(),
+            VolumeRate::new::<gallon_per_minute>(0.)
+        );
+        assert!(!aircraft.engine_driven_pump.is_active());
+    }
+
+    #[test]
+    fn engine_driven_pump_delivers_flow_when_engine_running() {
+        let mut aircraft = TestAircraft::new().with_engine_running();
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert!(aircraft.engine_driven_pump.max_flow() > VolumeRate::new::<gallon_per_minute>(30.));
+        assert!(aircraft.engine_driven_pump.is_active());
+    }
+
+    #[test]
+    fn engine_driven_pump_delivers_no_flow_when_depressurised() {
+        let mut aircraft = TestAircraft::new().with_engine_running().depressurised();
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert_eq!(
+            aircraft.engine_driven_pump.max_flow(),
+            VolumeRate::new::<gallon_per_minute>(0.)
+        );
+    }
+
+    #[test]
+    fn failed_engine_driven_pump_delivers_no_flow() {
+        let mut aircraft = TestAircraft::new().with_engine_running();
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.activate_failure(FailureType::HydraulicPump(1));
+
+        test_bed.run_aircraft(&mut aircraft);
+
+        assert_eq!(
+            aircraft.engine_driven_pump.max_flow(),
+            VolumeRate::new::<gallon_per_minute>(0.)
+        );
+        assert!(!aircraft.engine_driven_pump.is_active());
+    }
+
+    #[test]
+    fn electric_pump_delivers_no_flow_when_unpowered() {
+        let mut aircraft = TestAircraft::new();
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert_eq!(
+            aircraft.electric_pump.max_flow(),
+            VolumeRate::new::<gallon_per_minute>(0.)
+        );
+        assert!(!aircraft.electric_pump.is_active());
+    }
+
+    #[test]
+    fn electric_pump_spools_up_when_powered() {
+        let mut aircraft = TestAircraft::new().powered();
+        let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_millis(100));
+
+        test_bed.run_aircraft(&mut aircraft);
+        let partial_flow = aircraft.electric_pump.max_flow();
+
+        for _ in 0..10 {
+            test_bed.run_aircraft(&mut aircraft);
+        }
+
+        assert!(partial_flow < aircraft.electric_pump.max_flow());
+        assert!(aircraft.electric_pump.max_flow() > VolumeRate::new::<gallon_per_minute>(8.));
+        assert!(aircraft.electric_pump.is_active());
+    }
+
+    #[test]
+    fn failed_electric_pump_does_not_spool_up() {
+        let mut aircraft = TestAircraft::new().powered();
+        let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_millis(100));
+        test_bed.activate_failure(FailureType::HydraulicPump(3));
+
+        for _ in 0..10 {
+            test_bed.run_aircraft(&mut aircraft);
+        }
+
+        assert_eq!(
+            aircraft.electric_pump.max_flow(),
+            VolumeRate::new::<gallon_per_minute>(0.)
+        );
+        assert!(!aircraft.electric_pump.is_active());
+    }
+
+    #[test]
+    fn electric_pump_delivers_no_flow_when_powered_but_depressurised() {
+        let mut aircraft = TestAircraft::new().powered().depressurised();
+        let mut test_bed = SimulationTestBed::new();
+
+        test_bed.run_aircraft(&mut aircraft);
+
+        assert_eq!(
+            aircraft.electric_pump.max_flow(),
+            VolumeRate::new::<gallon_per_minute>(0.)
+        );
+    }
+
+    #[test]
+    fn electric_pump_consumes_no_power_when_unpowered() {
+        let mut aircraft = TestAircraft::new();
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert_eq!(aircraft.consumed_power, Power::new::<watt>(0.));
+    }
+
+    #[test]
+    fn electric_pump_consumes_power_when_running() {
+        let mut aircraft = TestAircraft::new().powered();
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert!(aircraft.consumed_power > Power::new::<watt>(0.));
+    }
+
+    #[test]
+    fn electric_pump_consumes_more_power_when_delivering_more_flow() {
+        let mut idle_aircraft = TestAircraft::new().powered();
+        SimulationTestBed::new().run_aircraft(&mut idle_aircraft);
+
+        let mut busy_aircraft = TestAircraft::new()
+            .powered()
+            .with_electric_pump_delivering(VolumeRate::new::<gallon_per_minute>(6.));
+        SimulationTestBed::new().run_aircraft(&mut busy_aircraft);
+
+        assert!(busy_aircraft.consumed_power > idle_aircraft.consumed_power);
+        assert!(busy_aircraft.consumed_power > Power::new::<watt>(10000.));
+    }
+
+    #[test]
+    fn rat_pump_delivers_flow_when_turning() {
+        let mut aircraft = TestAircraft::new().with_rat_turning();
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert!(aircraft.rat_pump.max_flow() > VolumeRate::new::<gallon_per_minute>(5.));
+        assert!(aircraft.rat_pump.is_active());
+    }
+
+    #[test]
+    fn writes_its_state() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+
+        test_bed.run_aircraft(&mut aircraft);
+
+        assert!(test_bed.contains_key("HYD_ENG_1_PUMP_IS_ACTIVE"));
+        assert!(test_bed.contains_key("HYD_BLUE_ELEC_PUMP_IS_ACTIVE"));
+        assert!(test_bed.contains_key("HYD_RAT_PUMP_IS_ACTIVE"));
+    }
+}
@@ -0,0 +1,371 @@
+use super::{PressureSource, RatPump};
+use crate::{
+    shared::LgciuWeightOnWheels,
+    simulation::{
+        QuantityIdentifier, SimulationElement, SimulationElementVisitor, SimulatorReader,
+        SimulatorWriter, Snapshot, UpdateContext, VariableRegistry, VariableUnit,
+    },
+};
+use uom::si::{angular_velocity::revolution_per_minute, f64::*, ratio::percent, velocity::knot};
+
+/// The ram air turbine (RAT). Once deployed, the airflow turns its propeller which
+/// drives a hydraulic pump. The RAT remains deployed until it is stowed on the ground.
+pub struct RamAirTurbine {
+    position_id: QuantityIdentifier<Ratio>,
+    propeller_speed_id: QuantityIdentifier<AngularVelocity>,
+    stow_id: QuantityIdentifier<bool>,
+    is_deployment_commanded: bool,
+    is_stow_requested: bool,
+    position: Ratio,
+    propeller_speed: AngularVelocity,
+    pump: RatPump,
+}
+impl RamAirTurbine {
+    const DEPLOYMENT_TIME_SECONDS: f64 = 2.;
+    const PROPELLER_SPIN_UP_TIME_SECONDS: f64 = 1.5;
+    /// Below this airspeed the propeller stalls.
+    const STALL_AIRSPEED_KNOTS: f64 = 100.;
+    /// From this airspeed onwards the propeller's speed is governed.
+    const GOVERNED_AIRSPEED_KNOTS: f64 = 140.;
+    const GOVERNED_SPEED_RPM: f64 = 6000.;
+
+    pub fn new(registry: &mut VariableRegistry) -> RamAirTurbine {
+        RamAirTurbine {
+            position_id: registry.register_write("HYD_RAT_POSITION", VariableUnit::Percent),
+            propeller_speed_id: registry.register_write("HYD_RAT_RPM", VariableUnit::Rpm),
+            stow_id: registry.register_read("HYD_RAT_STOW", VariableUnit::Bool),
+            is_deployment_commanded: false,
+            is_stow_requested: false,
+            position: Ratio::new::<percent>(0.),
+            propeller_speed: AngularVelocity::new::<revolution_per_minute>(0.),
+            pump: RatPump::new(registry),
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        context: &UpdateContext,
+        should_deploy: bool,
+        lgciu: &impl LgciuWeightOnWheels,
+    ) {
+        if should_deploy {
+            self.is_deployment_commanded = true;
+        } else if self.is_stow_requested && lgciu.left_and_right_gear_compressed() {
+            self.is_deployment_commanded = false;
+            self.position = Ratio::new::<percent>(0.);
+        }
+
+        if self.is_deployment_commanded {
+            self.position = (self.position
+                + Ratio::new::<percent>(
+                    100. * context.delta().as_secs_f64() / RamAirTurbine::DEPLOYMENT_TIME_SECONDS,
+                ))
+            .min(Ratio::new::<percent>(100.));
+        }
+
+        let target_speed = self.target_propeller_speed(context);
+        let current_speed = self.propeller_speed.get::<revolution_per_minute>();
+        let max_change = RamAirTurbine::GOVERNED_SPEED_RPM
+            / RamAirTurbine::PROPELLER_SPIN_UP_TIME_SECONDS
+            * context.delta().as_secs_f64();
+        self.propeller_speed =
+            AngularVelocity::new::<revolution_per_minute>(if target_speed > current_speed {
+                (current_speed + max_change).min(target_speed)
+            } else {
+                (current_speed - max_change).max(target_speed)
+            });
+
+        self.pump.update(context, self.propeller_speed);
+    }
+
+    fn target_propeller_speed(&self, context: &UpdateContext) -> f64 {
+        let airspeed = context.indicated_airspeed().get::<knot>();
+
+        if self.is_deployed() && airspeed >= RamAirTurbine::STALL_AIRSPEED_KNOTS {
+            RamAirTurbine::GOVERNED_SPEED_RPM
+                * (airspeed / RamAirTurbine::GOVERNED_AIRSPEED_KNOTS).min(1.)
+        } else {
+            0.
+        }
+    }
+
+    pub fn is_deployed(&self) -> bool {
+        (self.position.get::<percent>() - 100.).abs() < f64::EPSILON
+    }
+
+    pub fn is_stowed(&self) -> bool {
+        self.position.get::<percent>() < f64::EPSILON
+    }
+
+    pub fn propeller_speed(&self) -> AngularVelocity {
+        self.propeller_speed
+    }
+}
+impl PressureSource for RamAirTurbine {
+    fn max_flow(&self) -> VolumeRate {
+        self.pump.max_flow()
+    }
+
+    fn regulated_pressure(&self) -> Pressure {
+        self.pump.regulated_pressure()
+    }
+
+    fn set_delivered_flow(&mut self, flow: VolumeRate, pressure: Pressure) {
+        self.pump.set_delivered_flow(flow, pressure);
+    }
+}
+impl SimulationElement for RamAirTurbine {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.pump.accept(visitor);
+
+        visitor.visit(self);
+    }
+
+    fn read(&mut self, reader: &mut SimulatorReader) {
+        self.is_stow_requested = reader.read_bool(&self.stow_id);
+    }
+
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_ratio(&self.position_id, self.position);
+        writer.write_angular_velocity(&self.propeller_speed_id, self.propeller_speed);
+    }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        snapshot.save_bool(
+            "HYD_RAT_IS_DEPLOYMENT_COMMANDED",
+            self.is_deployment_commanded,
+        );
+        snapshot.save_f64(self.position_id.name(), self.position.get::<percent>());
+        snapshot.save_f64(
+            self.propeller_speed_id.name(),
+            self.propeller_speed.get::<revolution_per_minute>(),
+        );
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        if let Some(is_deployment_commanded) =
+            snapshot.restore_bool("HYD_RAT_IS_DEPLOYMENT_COMMANDED")
+        {
+            self.is_deployment_commanded = is_deployment_commanded;
+        }
+        if let Some(position) = snapshot.restore_f64(self.position_id.name()) {
+            self.position = Ratio::new::<percent>(position);
+        }
+        if let Some(propeller_speed) = snapshot.restore_f64(self.propeller_speed_id.name()) {
+            self.propeller_speed = AngularVelocity::new::<revolution_per_minute>(propeller_speed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{test::SimulationTestBed, Aircraft};
+    use std::time::Duration;
+    use uom::si::volume_rate::gallon_per_minute;
+
+    struct TestLgciu {
+        compressed: bool,
+    }
+    impl LgciuWeightOnWheels for TestLgciu {
+        fn left_and_right_gear_compressed(&self) -> bool {
+            self.compressed
+        }
+    }
+
+    struct TestAircraft {
+        rat: RamAirTurbine,
+        should_deploy: bool,
+        lgciu: TestLgciu,
+    }
+    impl TestAircraft {
+        fn new() -> Self {
+            Self {
+                rat: RamAirTurbine::new(&mut VariableRegistry::new()),
+                should_deploy: false,
+                lgciu: TestLgciu { compressed: false },
+            }
+        }
+
+        fn command_deployment(&mut self) {
+            self.should_deploy = true;
+        }
+
+        fn stop_commanding_deployment(&mut self) {
+            self.should_deploy = false;
+        }
+
+        fn land(&mut self) {
+            self.lgciu.compressed = true;
+        }
+    }
+    impl Aircraft for TestAircraft {
+        fn update_after_power_distribution(&mut self, context: &UpdateContext) {
+            self.rat.update(context, self.should_deploy, &self.lgciu);
+        }
+    }
+    impl SimulationElement for TestAircraft {
+        fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            self.rat.accept(visitor);
+
+            visitor.visit(self);
+        }
+    }
+
+    fn run_for(test_bed: &mut SimulationTestBed, aircraft: &mut TestAircraft, duration: Duration) {
+        test_bed.set_delta(Duration::from_millis(100));
+        for _ in 0..(duration.as_millis() / 100) {
+            test_bed.run_aircraft(aircraft);
+        }
+    }
+
+    fn deployed_aircraft(test_bed: &mut SimulationTestBed) -> TestAircraft {
+        let mut aircraft = TestAircraft::new();
+        aircraft.command_deployment();
+        run_for(test_bed, &mut aircraft, Duration::from_secs(5));
+
+        aircraft
+    }
+
+    #[test]
+    fn starts_stowed() {
+        let mut aircraft = TestAircraft::new();
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert!(aircraft.rat.is_stowed());
+        assert_eq!(
+            aircraft.rat.max_flow(),
+            VolumeRate::new::<gallon_per_minute>(0.)
+        );
+    }
+
+    #[test]
+    fn is_not_deployed_immediately() {
+        let mut test_bed = SimulationTestBed::new();
+        let mut aircraft = TestAircraft::new();
+        aircraft.command_deployment();
+        run_for(&mut test_bed, &mut aircraft, Duration::from_secs(1));
+
+        assert!(!aircraft.rat.is_stowed());
+        assert!(!aircraft.rat.is_deployed());
+    }
+
+    #[test]
+    fn deploys_and_delivers_flow() {
+        let mut test_bed = SimulationTestBed::new();
+        let aircraft = deployed_aircraft(&mut test_bed);
+
+        assert!(aircraft.rat.is_deployed());
+        assert!(aircraft.rat.max_flow() > VolumeRate::new::<gallon_per_minute>(5.));
+    }
+
+    #[test]
+    fn remains_deployed_when_no_longer_commanded() {
+        let mut test_bed = SimulationTestBed::new();
+        let mut aircraft = deployed_aircraft(&mut test_bed);
+
+        aircraft.stop_commanding_deployment();
+        run_for(&mut test_bed, &mut aircraft, Duration::from_secs(5));
+
+        assert!(aircraft.rat.is_deployed());
+    }
+
+    #[test]
+    fn propeller_turns_slower_at_lower_airspeed() {
+        let mut test_bed = SimulationTestBed::new();
+        let aircraft = deployed_aircraft(&mut test_bed);
+        let governed_speed = aircraft.rat.propeller_speed();
+
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.set_indicated_airspeed(Velocity::new::<knot>(110.));
+        let aircraft = deployed_aircraft(&mut test_bed);
+
+        assert!(aircraft.rat.propeller_speed() < governed_speed);
+        assert!(aircraft.rat.propeller_speed() > AngularVelocity::new::<revolution_per_minute>(0.));
+    }
+
+    #[test]
+    fn propeller_stalls_at_low_airspeed() {
+        let mut test_bed = SimulationTestBed::new();
+        let mut aircraft = deployed_aircraft(&mut test_bed);
+
+        test_bed.set_indicated_airspeed(Velocity::new::<knot>(90.));
+        run_for(&mut test_bed, &mut aircraft, Duration::from_secs(5));
+
+        assert_eq!(
+            aircraft.rat.propeller_speed(),
+            AngularVelocity::new::<revolution_per_minute>(0.)
+        );
+    }
+
+    #[test]
+    fn cannot_be_stowed_in_flight() {
+        let mut test_bed = SimulationTestBed::new();
+        let mut aircraft = deployed_aircraft(&mut test_bed);
+
+        aircraft.stop_commanding_deployment();
+        test_bed.write_bool("HYD_RAT_STOW", true);
+        run_for(&mut test_bed, &mut aircraft, Duration::from_secs(1));
+
+        assert!(aircraft.rat.is_deployed());
+    }
+
+    #[test]
+    fn can_be_stowed_on_ground() {
+        let mut test_bed = SimulationTestBed::new();
+        let mut aircraft = deployed_aircraft(&mut test_bed);
+
+        aircraft.stop_commanding_deployment();
+        aircraft.land();
+        test_bed.write_bool("HYD_RAT_STOW", true);
+        run_for(&mut test_bed, &mut aircraft, Duration::from_secs(1));
+
+        assert!(aircraft.rat.is_stowed());
+    }
+
+    #[test]
+    fn cannot_be_stowed_while_the_gear_is_not_compressed() {
+        let mut test_bed = SimulationTestBed::new();
+        let mut aircraft = deployed_aircraft(&mut test_bed);
+
+        aircraft.stop_commanding_deployment();
+        test_bed.set_on_ground(true);
+        test_bed.write_bool("HYD_RAT_STOW", true);
+        run_for(&mut test_bed, &mut aircraft, Duration::from_secs(1));
+
+        assert!(aircraft.rat.is_deployed());
+    }
+
+    #[test]
+    fn deployment_is_restored_from_snapshot() {
+        let mut test_bed = SimulationTestBed::new();
+        let mut aircraft = deployed_aircraft(&mut test_bed);
+        aircraft.stop_commanding_deployment();
+
+        let mut restored_aircraft = TestAircraft::new();
+        Snapshot::take(&mut aircraft).restore(&mut restored_aircraft);
+        run_for(
+            &mut test_bed,
+            &mut restored_aircraft,
+            Duration::from_secs(1),
+        );
+
+        assert!(restored_aircraft.rat.is_deployed());
+        assert_eq!(
+            restored_aircraft.rat.propeller_speed(),
+            aircraft.rat.propeller_speed()
+        );
+    }
+
+    #[test]
+    fn writes_its_state() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+
+        test_bed.run_aircraft(&mut aircraft);
+
+        assert!(test_bed.contains_key("HYD_RAT_POSITION"));
+        assert!(test_bed.contains_key("HYD_RAT_RPM"));
+        assert!(test_bed.contains_key("HYD_RAT_PUMP_IS_ACTIVE"));
+    }
+}
@@ -1,98 +1,622 @@
-use crate::simulation::{SimulationElement, SimulatorReader};
-use uom::si::{f64::*, ratio::percent};
-
-/// Represents a landing gear on Airbus aircraft.
-/// Note that this type somewhat hides the gear's position.
-/// The real aircraft also can only check whether or not the gear is up and
-/// locked or down and locked. No in between state.
-pub struct LandingGear {
+//! The landing gear of Airbus aircraft is hydraulically actuated. Each gear leg has a
+//! door which opens before the gear moves and closes once the gear is locked. The
+//! Landing Gear Control and Interface Units (LGCIU) sequence the doors and gear based
+//! on the gear lever position and the proximity sensors on each leg. They also provide
+//! the weight-on-wheels signals used by other systems.
+//! When the gear cannot be extended hydraulically, the gravity extension releases the
+//! uplocks and the gear free falls into its downlocks.
+use crate::{
+    electrical::{consumption::PowerConsumer, ElectricalBusType},
+    shared::LgciuWeightOnWheels,
+    simulation::{
+        Failure, FailureType, QuantityIdentifier, SimulationElement, SimulationElementVisitor,
+        SimulatorReader, SimulatorWriter, Snapshot, UpdateContext, VariableRegistry, VariableUnit,
+    },
+};
+use uom::si::{f64::*, power::watt, pressure::psi, ratio::percent};
+
+/// A gear leg with its door. The position of the gear and door are expressed as a
+/// ratio, where 0% is retracted or closed and 100% is extended or open.
+pub struct GearLeg {
+    position_id: QuantityIdentifier<Ratio>,
+    door_position_id: QuantityIdentifier<Ratio>,
+    compression_id: QuantityIdentifier<Ratio>,
     position: Ratio,
+    door_position: Ratio,
+    is_compressed: bool,
 }
-impl LandingGear {
-    const GEAR_CENTER_POSITION: &'static str = "GEAR CENTER POSITION";
+impl GearLeg {
+    const MINIMUM_ACTUATION_PRESSURE_PSI: f64 = 1500.;
+    const GEAR_TRAVEL_TIME_SECONDS: f64 = 8.;
+    const DOOR_TRAVEL_TIME_SECONDS: f64 = 4.;
+    const FREE_FALL_GEAR_TRAVEL_TIME_SECONDS: f64 = 12.;
+    const FREE_FALL_DOOR_TRAVEL_TIME_SECONDS: f64 = 3.;
+
+    pub fn new(registry: &mut VariableRegistry, id: &str, contact_point_index: usize) -> GearLeg {
+        GearLeg {
+            position_id: registry
+                .register_write(&format!("GEAR_{}_POSITION", id), VariableUnit::Percent),
+            door_position_id: registry
+                .register_write(&format!("GEAR_DOOR_{}_POSITION", id), VariableUnit::Percent),
+            compression_id: registry.register_read(
+                &format!("CONTACT POINT COMPRESSION:{}", contact_point_index),
+                VariableUnit::Percent,
+            ),
+            position: Ratio::new::<percent>(100.),
+            door_position: Ratio::new::<percent>(0.),
+            is_compressed: false,
+        }
+    }
 
-    pub fn new() -> Self {
-        Self {
-            position: Ratio::new::<percent>(0.),
+    /// Moves the door and gear as commanded, as long as the hydraulic pressure is sufficient.
+    /// The gear only moves when its door is fully open. During a free fall the uplocks are
+    /// released and both the door and the gear fall open under their own weight.
+    pub fn update(
+        &mut self,
+        context: &UpdateContext,
+        door_should_open: bool,
+        gear_should_extend: bool,
+        hydraulic_pressure: Pressure,
+        free_fall: bool,
+    ) {
+        if free_fall {
+            self.door_position = GearLeg::travel(
+                context,
+                self.door_position,
+                true,
+                GearLeg::FREE_FALL_DOOR_TRAVEL_TIME_SECONDS,
+            );
+            self.position = GearLeg::travel(
+                context,
+                self.position,
+                true,
+                GearLeg::FREE_FALL_GEAR_TRAVEL_TIME_SECONDS,
+            );
+        } else if hydraulic_pressure
+            >= Pressure::new::<psi>(GearLeg::MINIMUM_ACTUATION_PRESSURE_PSI)
+        {
+            let gear_is_in_commanded_position = if gear_should_extend {
+                self.is_downlocked()
+            } else {
+                self.is_uplocked()
+            };
+
+            if self.door_is_fully_open() && !gear_is_in_commanded_position {
+                self.position = GearLeg::travel(
+                    context,
+                    self.position,
+                    gear_should_extend,
+                    GearLeg::GEAR_TRAVEL_TIME_SECONDS,
+                );
+            } else if self.is_uplocked() || self.is_downlocked() {
+                self.door_position = GearLeg::travel(
+                    context,
+                    self.door_position,
+                    door_should_open,
+                    GearLeg::DOOR_TRAVEL_TIME_SECONDS,
+                );
+            }
         }
     }
 
-    pub fn is_up_and_locked(&self) -> bool {
-        (self.position.get::<percent>() - 0.).abs() < f64::EPSILON
+    fn travel(
+        context: &UpdateContext,
+        position: Ratio,
+        towards_open: bool,
+        travel_time_seconds: f64,
+    ) -> Ratio {
+        let change = 100. * context.delta().as_secs_f64() / travel_time_seconds;
+        let position = position.get::<percent>();
+
+        Ratio::new::<percent>(if towards_open {
+            (position + change).min(100.)
+        } else {
+            (position - change).max(0.)
+        })
+    }
+
+    fn position_is(&self, percentage: f64) -> bool {
+        (self.position.get::<percent>() - percentage).abs() < f64::EPSILON
+    }
+
+    /// Proximity sensor indicating the gear is locked in the retracted position.
+    pub fn is_uplocked(&self) -> bool {
+        self.position_is(0.)
+    }
+
+    /// Proximity sensor indicating the gear is locked in the extended position.
+    pub fn is_downlocked(&self) -> bool {
+        self.position_is(100.)
+    }
+
+    pub fn door_is_fully_open(&self) -> bool {
+        (self.door_position.get::<percent>() - 100.).abs() < f64::EPSILON
+    }
+
+    pub fn door_is_closed(&self) -> bool {
+        self.door_position.get::<percent>() < f64::EPSILON
     }
 
-    pub fn is_down_and_locked(&self) -> bool {
-        (self.position.get::<percent>() - 100.).abs() < f64::EPSILON
+    /// Proximity sensor indicating the shock absorber is compressed.
+    pub fn is_compressed(&self) -> bool {
+        self.is_compressed
     }
 }
-impl SimulationElement for LandingGear {
+impl SimulationElement for GearLeg {
     fn read(&mut self, reader: &mut SimulatorReader) {
-        self.position = Ratio::new::<percent>(reader.read_f64(LandingGear::GEAR_CENTER_POSITION));
+        self.is_compressed = reader.read_ratio(&self.compression_id) > Ratio::new::<percent>(0.);
     }
-}
-impl Default for LandingGear {
-    fn default() -> Self {
-        Self::new()
+
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_ratio(&self.position_id, self.position);
+        writer.write_ratio(&self.door_position_id, self.door_position);
+    }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        snapshot.save_f64(self.position_id.name(), self.position.get::<percent>());
+        snapshot.save_f64(
+            self.door_position_id.name(),
+            self.door_position.get::<percent>(),
+        );
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        if let Some(position) = snapshot.restore_f64(self.position_id.name()) {
+            self.position = Ratio::new::<percent>(position);
+        }
+        if let Some(door_position) = snapshot.restore_f64(self.door_position_id.name()) {
+            self.door_position = Ratio::new::<percent>(door_position);
+        }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::simulation::test::SimulationTestBed;
+/// The commands a LGCIU gives to a single gear leg.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GearLegCommand {
+    door_should_open: bool,
+    gear_should_extend: bool,
+}
+impl GearLegCommand {
+    pub fn door_should_open(&self) -> bool {
+        self.door_should_open
+    }
+
+    pub fn gear_should_extend(&self) -> bool {
+        self.gear_should_extend
+    }
+}
 
-    #[test]
-    fn is_up_and_locked_returns_false_when_fully_down() {
-        let mut landing_gear = LandingGear::new();
-        run_test_bed_on(&mut landing_gear, 100.);
+/// A Landing Gear Control and Interface Unit. It receives the proximity sensor signals
+/// of the nose, left and right gear legs and sequences the doors and gear according to
+/// the gear lever position. Retraction is inhibited while the main gear is compressed.
+/// When unpowered or failed, the LGCIU provides no signals and commands nothing.
+pub struct LandingGearControlInterfaceUnit {
+    nose_gear_compressed_id: QuantityIdentifier<bool>,
+    left_gear_compressed_id: QuantityIdentifier<bool>,
+    right_gear_compressed_id: QuantityIdentifier<bool>,
+    down_and_locked_id: QuantityIdentifier<bool>,
+    consumer: PowerConsumer,
+    failure: Failure,
+    is_operating: bool,
+    nose_gear_compressed: bool,
+    left_gear_compressed: bool,
+    right_gear_compressed: bool,
+    all_down_and_locked: bool,
+    all_up_and_locked: bool,
+    commands: [GearLegCommand; 3],
+}
+impl LandingGearControlInterfaceUnit {
+    const POWER_CONSUMPTION_WATT: f64 = 25.;
 
-        assert!(!landing_gear.is_up_and_locked());
+    pub fn new(
+        registry: &mut VariableRegistry,
+        number: usize,
+        bus_type: ElectricalBusType,
+    ) -> LandingGearControlInterfaceUnit {
+        LandingGearControlInterfaceUnit {
+            nose_gear_compressed_id: registry.register_write(
+                &format!("LGCIU_{}_NOSE_GEAR_COMPRESSED", number),
+                VariableUnit::Bool,
+            ),
+            left_gear_compressed_id: registry.register_write(
+                &format!("LGCIU_{}_LEFT_GEAR_COMPRESSED", number),
+                VariableUnit::Bool,
+            ),
+            right_gear_compressed_id: registry.register_write(
+                &format!("LGCIU_{}_RIGHT_GEAR_COMPRESSED", number),
+                VariableUnit::Bool,
+            ),
+            down_and_locked_id: registry.register_write(
+                &format!("LGCIU_{}_GEAR_DOWN_AND_LOCKED", number),
+                VariableUnit::Bool,
+            ),
+            consumer: PowerConsumer::from(bus_type),
+            failure: Failure::new(registry, FailureType::Lgciu(number)),
+            is_operating: false,
+            nose_gear_compressed: false,
+            left_gear_compressed: false,
+            right_gear_compressed: false,
+            all_down_and_locked: false,
+            all_up_and_locked: false,
+            commands: [GearLegCommand {
+                door_should_open: false,
+                gear_should_extend: true,
+            }; 3],
+        }
     }
 
-    #[test]
-    fn is_up_and_locked_returns_false_when_somewhat_down() {
-        let mut landing_gear = LandingGear::new();
-        run_test_bed_on(&mut landing_gear, 1.);
+    /// The gear legs are given in the order nose, left and right.
+    pub fn update(&mut self, _: &UpdateContext, lever_is_down: bool, legs: [&GearLeg; 3]) {
+        self.consumer.demand(Power::new::<watt>(
+            LandingGearControlInterfaceUnit::POWER_CONSUMPTION_WATT,
+        ));
+
+        self.is_operating = self.consumer.is_powered() && !self.failure.is_active();
+        if !self.is_operating {
+            self.nose_gear_compressed = false;
+            self.left_gear_compressed = false;
+            self.right_gear_compressed = false;
+            self.all_down_and_locked = false;
+            self.all_up_and_locked = false;
+            return;
+        }
+
+        self.nose_gear_compressed = legs[0].is_compressed();
+        self.left_gear_compressed = legs[1].is_compressed();
+        self.right_gear_compressed = legs[2].is_compressed();
+        self.all_down_and_locked = legs.iter().all(|leg| leg.is_downlocked());
+        self.all_up_and_locked = legs.iter().all(|leg| leg.is_uplocked());
+
+        let gear_should_extend = lever_is_down || self.left_and_right_gear_compressed();
+        for (command, leg) in self.commands.iter_mut().zip(legs.iter()) {
+            let gear_is_in_commanded_position = if gear_should_extend {
+                leg.is_downlocked()
+            } else {
+                leg.is_uplocked()
+            };
 
-        assert!(!landing_gear.is_up_and_locked());
+            *command = GearLegCommand {
+                door_should_open: !gear_is_in_commanded_position,
+                gear_should_extend,
+            };
+        }
     }
 
-    #[test]
-    fn is_up_and_locked_returns_true_when_fully_up() {
-        let mut landing_gear = LandingGear::new();
-        run_test_bed_on(&mut landing_gear, 0.);
+    pub fn is_operating(&self) -> bool {
+        self.is_operating
+    }
 
-        assert!(landing_gear.is_up_and_locked());
+    /// The commands for the nose, left and right gear legs.
+    pub fn commands(&self) -> [GearLegCommand; 3] {
+        self.commands
     }
 
-    #[test]
-    fn is_down_and_locked_returns_false_when_fully_up() {
-        let mut landing_gear = LandingGear::new();
-        run_test_bed_on(&mut landing_gear, 0.);
+    pub fn nose_gear_compressed(&self) -> bool {
+        self.nose_gear_compressed
+    }
 
-        assert!(!landing_gear.is_down_and_locked());
+    pub fn all_down_and_locked(&self) -> bool {
+        self.all_down_and_locked
+    }
+
+    pub fn all_up_and_locked(&self) -> bool {
+        self.all_up_and_locked
+    }
+}
+impl LgciuWeightOnWheels for LandingGearControlInterfaceUnit {
+    fn left_and_right_gear_compressed(&self) -> bool {
+        self.left_gear_compressed && self.right_gear_compressed
     }
+}
+impl SimulationElement for LandingGearControlInterfaceUnit {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.consumer.accept(visitor);
+        self.failure.accept(visitor);
 
-    #[test]
-    fn is_down_and_locked_returns_false_when_somewhat_up() {
-        let mut landing_gear = LandingGear::new();
-        run_test_bed_on(&mut landing_gear, 99.);
+        visitor.visit(self);
+    }
 
-        assert!(!landing_gear.is_down_and_locked());
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_bool(&self.nose_gear_compressed_id, self.nose_gear_compressed);
+        writer.write_bool(&self.left_gear_compressed_id, self.left_gear_compressed);
+        writer.write_bool(&self.right_gear_compressed_id, self.right_gear_compressed);
+        writer.write_bool(&self.down_and_locked_id, self.all_down_and_locked);
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(test)]
+    mod gear_leg_tests {
+        use super::*;
+        use crate::simulation::test::SimulationTestBed;
+        use std::time::Duration;
+
+        fn green_pressure() -> Pressure {
+            Pressure::new::<psi>(3000.)
+        }
+
+        fn run(
+            leg: &mut GearLeg,
+            seconds: u64,
+            door_should_open: bool,
+            gear_should_extend: bool,
+            pressure: Pressure,
+            free_fall: bool,
+        ) {
+            let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_secs(1));
+            for _ in 0..seconds {
+                test_bed.run(leg, |leg, context| {
+                    leg.update(
+                        context,
+                        door_should_open,
+                        gear_should_extend,
+                        pressure,
+                        free_fall,
+                    )
+                });
+            }
+        }
+
+        #[test]
+        fn starts_down_and_locked_with_door_closed() {
+            let leg = GearLeg::new(&mut VariableRegistry::new(), "CENTER", 0);
+
+            assert!(leg.is_downlocked());
+            assert!(leg.door_is_closed());
+        }
 
-    #[test]
-    fn is_down_and_locked_returns_true_when_fully_down() {
-        let mut landing_gear = LandingGear::new();
-        run_test_bed_on(&mut landing_gear, 100.);
+        #[test]
+        fn gear_does_not_retract_while_door_closed() {
+            let mut leg = GearLeg::new(&mut VariableRegistry::new(), "CENTER", 0);
+            let mut test_bed = SimulationTestBed::new();
+            test_bed.run(&mut leg, |leg, context| {
+                leg.update(context, false, false, green_pressure(), false)
+            });
 
-        assert!(landing_gear.is_down_and_locked());
+            assert!(leg.is_downlocked());
+        }
+
+        #[test]
+        fn door_opens_with_hydraulic_pressure() {
+            let mut leg = GearLeg::new(&mut VariableRegistry::new(), "CENTER", 0);
+            run(&mut leg, 5, true, true, green_pressure(), false);
+
+            assert!(leg.door_is_fully_open());
+        }
+
+        #[test]
+        fn gear_retracts_once_door_is_open() {
+            let mut leg = GearLeg::new(&mut VariableRegistry::new(), "CENTER", 0);
+            run(&mut leg, 5, true, true, green_pressure(), false);
+            run(&mut leg, 10, true, false, green_pressure(), false);
+
+            assert!(leg.is_uplocked());
+        }
+
+        #[test]
+        fn nothing_moves_without_hydraulic_pressure() {
+            let mut leg = GearLeg::new(&mut VariableRegistry::new(), "CENTER", 0);
+            run(&mut leg, 5, true, true, Pressure::new::<psi>(0.), false);
+
+            assert!(leg.door_is_closed());
+        }
+
+        #[test]
+        fn gear_free_falls_into_downlock_without_hydraulic_pressure() {
+            let mut leg = GearLeg::new(&mut VariableRegistry::new(), "CENTER", 0);
+            run(&mut leg, 5, true, true, green_pressure(), false);
+            run(&mut leg, 10, true, false, green_pressure(), false);
+            run(&mut leg, 15, false, false, Pressure::new::<psi>(0.), true);
+
+            assert!(leg.is_downlocked());
+            assert!(leg.door_is_fully_open());
+        }
+
+        #[test]
+        fn reads_compression_from_the_simulator() {
+            let mut leg = GearLeg::new(&mut VariableRegistry::new(), "CENTER", 0);
+            let mut test_bed = SimulationTestBed::new();
+            test_bed.write_f64("CONTACT POINT COMPRESSION:0", 50.);
+
+            test_bed.run_without_update(&mut leg);
+
+            assert!(leg.is_compressed());
+        }
+
+        #[test]
+        fn writes_its_state() {
+            let mut leg = GearLeg::new(&mut VariableRegistry::new(), "CENTER", 0);
+            let mut test_bed = SimulationTestBed::new();
+
+            test_bed.run_without_update(&mut leg);
+
+            assert!(test_bed.contains_key("GEAR_CENTER_POSITION"));
+            assert!(test_bed.contains_key("GEAR_DOOR_CENTER_POSITION"));
+        }
     }
 
-    fn run_test_bed_on(landing_gear: &mut LandingGear, position: f64) {
-        let mut test_bed = SimulationTestBed::new();
-        test_bed.write_f64(LandingGear::GEAR_CENTER_POSITION, position);
+    #[cfg(test)]
+    mod landing_gear_control_interface_unit_tests {
+        use super::*;
+        use crate::{
+            electrical::{consumption::SuppliedPower, Potential, PotentialOrigin},
+            simulation::{test::SimulationTestBed, Aircraft},
+        };
+        use uom::si::electric_potential::volt;
+
+        struct TestAircraft {
+            lgciu: LandingGearControlInterfaceUnit,
+            legs: [GearLeg; 3],
+            lever_is_down: bool,
+            is_powered: bool,
+        }
+        impl TestAircraft {
+            fn new() -> Self {
+                let mut registry = VariableRegistry::new();
+
+                Self {
+                    lgciu: LandingGearControlInterfaceUnit::new(
+                        &mut registry,
+                        1,
+                        ElectricalBusType::DirectCurrentEssential,
+                    ),
+                    legs: [
+                        GearLeg::new(&mut registry, "CENTER", 0),
+                        GearLeg::new(&mut registry, "LEFT", 1),
+                        GearLeg::new(&mut registry, "RIGHT", 2),
+                    ],
+                    lever_is_down: true,
+                    is_powered: true,
+                }
+            }
+        }
+        impl Aircraft for TestAircraft {
+            fn update_after_power_distribution(&mut self, context: &UpdateContext) {
+                let [nose, left, right] = &self.legs;
+                self.lgciu
+                    .update(context, self.lever_is_down, [nose, left, right]);
+
+                for (leg, command) in self.legs.iter_mut().zip(self.lgciu.commands().iter()) {
+                    leg.update(
+                        context,
+                        command.door_should_open(),
+                        command.gear_should_extend(),
+                        Pressure::new::<psi>(3000.),
+                        false,
+                    );
+                }
+            }
+
+            fn get_supplied_power(&mut self) -> SuppliedPower {
+                let mut supplied_power = SuppliedPower::new();
+                if self.is_powered {
+                    supplied_power.add(
+                        ElectricalBusType::DirectCurrentEssential,
+                        Potential::single(
+                            PotentialOrigin::Battery(10),
+                            ElectricPotential::new::<volt>(28.),
+                        ),
+                    );
+                }
+
+                supplied_power
+            }
+        }
+        impl SimulationElement for TestAircraft {
+            fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+                self.lgciu.accept(visitor);
+                for leg in self.legs.iter_mut() {
+                    leg.accept(visitor);
+                }
+
+                visitor.visit(self);
+            }
+        }
+
+        fn run_for(aircraft: &mut TestAircraft, test_bed: &mut SimulationTestBed, seconds: u64) {
+            for _ in 0..seconds {
+                test_bed.run_aircraft(aircraft);
+            }
+        }
+
+        fn compress_main_gear(test_bed: &mut SimulationTestBed) {
+            test_bed.write_f64("CONTACT POINT COMPRESSION:1", 50.);
+            test_bed.write_f64("CONTACT POINT COMPRESSION:2", 50.);
+        }
+
+        #[test]
+        fn gear_down_and_locked_with_lever_down() {
+            let mut aircraft = TestAircraft::new();
+            run_for(&mut aircraft, &mut SimulationTestBed::new(), 1);
+
+            assert!(aircraft.lgciu.all_down_and_locked());
+            assert!(!aircraft.lgciu.all_up_and_locked());
+        }
+
+        #[test]
+        fn gear_retracts_and_doors_close_with_lever_up() {
+            let mut aircraft = TestAircraft::new();
+            aircraft.lever_is_down = false;
+            run_for(&mut aircraft, &mut SimulationTestBed::new(), 30);
+
+            assert!(aircraft.lgciu.all_up_and_locked());
+            assert!(aircraft.legs.iter().all(|leg| leg.door_is_closed()));
+        }
+
+        #[test]
+        fn gear_extends_and_doors_close_with_lever_down_after_retraction() {
+            let mut aircraft = TestAircraft::new();
+            let mut test_bed = SimulationTestBed::new();
+            aircraft.lever_is_down = false;
+            run_for(&mut aircraft, &mut test_bed, 30);
+
+            aircraft.lever_is_down = true;
+            run_for(&mut aircraft, &mut test_bed, 30);
+
+            assert!(aircraft.lgciu.all_down_and_locked());
+            assert!(aircraft.legs.iter().all(|leg| leg.door_is_closed()));
+        }
 
-        test_bed.run_without_update(landing_gear);
+        #[test]
+        fn retraction_is_inhibited_when_main_gear_compressed() {
+            let mut aircraft = TestAircraft::new();
+            let mut test_bed = SimulationTestBed::new();
+            compress_main_gear(&mut test_bed);
+            aircraft.lever_is_down = false;
+            run_for(&mut aircraft, &mut test_bed, 30);
+
+            assert!(aircraft.lgciu.all_down_and_locked());
+        }
+
+        #[test]
+        fn provides_weight_on_wheels() {
+            let mut aircraft = TestAircraft::new();
+            let mut test_bed = SimulationTestBed::new();
+            compress_main_gear(&mut test_bed);
+            run_for(&mut aircraft, &mut test_bed, 1);
+
+            assert!(aircraft.lgciu.left_and_right_gear_compressed());
+            assert!(!aircraft.lgciu.nose_gear_compressed());
+        }
+
+        #[test]
+        fn provides_no_signals_when_unpowered() {
+            let mut aircraft = TestAircraft::new();
+            let mut test_bed = SimulationTestBed::new();
+            compress_main_gear(&mut test_bed);
+            aircraft.is_powered = false;
+            run_for(&mut aircraft, &mut test_bed, 1);
+
+            assert!(!aircraft.lgciu.is_operating());
+            assert!(!aircraft.lgciu.left_and_right_gear_compressed());
+            assert!(!aircraft.lgciu.all_down_and_locked());
+        }
+
+        #[test]
+        fn provides_no_signals_when_failed() {
+            let mut aircraft = TestAircraft::new();
+            let mut test_bed = SimulationTestBed::new();
+            compress_main_gear(&mut test_bed);
+            test_bed.activate_failure(FailureType::Lgciu(1));
+            run_for(&mut aircraft, &mut test_bed, 1);
+
+            assert!(!aircraft.lgciu.is_operating());
+            assert!(!aircraft.lgciu.left_and_right_gear_compressed());
+            assert!(!aircraft.lgciu.all_down_and_locked());
+        }
+
+        #[test]
+        fn writes_its_state() {
+            let mut aircraft = TestAircraft::new();
+            let mut test_bed = SimulationTestBed::new();
+
+            test_bed.run_aircraft(&mut aircraft);
+
+            assert!(test_bed.contains_key("LGCIU_1_LEFT_GEAR_COMPRESSED"));
+            assert!(test_bed.contains_key("LGCIU_1_GEAR_DOWN_AND_LOCKED"));
+        }
     }
 }
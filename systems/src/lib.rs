@@ -7,12 +7,16 @@
 #[macro_use]
 mod macros;
 
+pub mod air_conditioning;
 pub mod apu;
 pub mod electrical;
 pub mod engine;
+pub mod flight_warning;
+pub mod fuel;
 pub mod hydraulic;
 pub mod landing_gear;
 pub mod overhead;
 pub mod pneumatic;
+pub mod pressurization;
 pub mod shared;
 pub mod simulation;
@@ -1,25 +1,30 @@
-use crate::simulation::{SimulationElement, SimulatorReader, SimulatorWriter};
+use crate::simulation::{
+    QuantityIdentifier, SimulationElement, SimulatorReader, SimulatorWriter, VariableRegistry,
+    VariableUnit,
+};
 
 pub struct OnOffFaultPushButton {
-    is_on_id: String,
-    has_fault_id: String,
+    is_on_id: QuantityIdentifier<bool>,
+    has_fault_id: QuantityIdentifier<bool>,
 
     is_on: bool,
     has_fault: bool,
 }
 impl OnOffFaultPushButton {
-    pub fn new_on(name: &str) -> Self {
-        Self::new(name, true)
+    pub fn new_on(registry: &mut VariableRegistry, name: &str) -> Self {
+        Self::new(registry, name, true)
     }
 
-    pub fn new_off(name: &str) -> Self {
-        Self::new(name, false)
+    pub fn new_off(registry: &mut VariableRegistry, name: &str) -> Self {
+        Self::new(registry, name, false)
     }
 
-    fn new(name: &str, is_on: bool) -> Self {
+    fn new(registry: &mut VariableRegistry, name: &str, is_on: bool) -> Self {
         Self {
-            is_on_id: format!("OVHD_{}_PB_IS_ON", name),
-            has_fault_id: format!("OVHD_{}_PB_HAS_FAULT", name),
+            is_on_id: registry
+                .register_read_write(&format!("OVHD_{}_PB_IS_ON", name), VariableUnit::Bool),
+            has_fault_id: registry
+                .register_read_write(&format!("OVHD_{}_PB_HAS_FAULT", name), VariableUnit::Bool),
             is_on,
             has_fault: false,
         }
@@ -66,25 +71,29 @@ impl SimulationElement for OnOffFaultPushButton {
 }
 
 pub struct OnOffAvailablePushButton {
-    is_on_id: String,
-    is_available_id: String,
+    is_on_id: QuantityIdentifier<bool>,
+    is_available_id: QuantityIdentifier<bool>,
 
     is_on: bool,
     is_available: bool,
 }
 impl OnOffAvailablePushButton {
-    pub fn new_on(name: &str) -> Self {
-        Self::new(name, true)
+    pub fn new_on(registry: &mut VariableRegistry, name: &str) -> Self {
+        Self::new(registry, name, true)
     }
 
-    pub fn new_off(name: &str) -> Self {
-        Self::new(name, false)
+    pub fn new_off(registry: &mut VariableRegistry, name: &str) -> Self {
+        Self::new(registry, name, false)
     }
 
-    fn new(name: &str, is_on: bool) -> Self {
+    fn new(registry: &mut VariableRegistry, name: &str, is_on: bool) -> Self {
         Self {
-            is_on_id: format!("OVHD_{}_PB_IS_ON", name),
-            is_available_id: format!("OVHD_{}_PB_IS_AVAILABLE", name),
+            is_on_id: registry
+                .register_read_write(&format!("OVHD_{}_PB_IS_ON", name), VariableUnit::Bool),
+            is_available_id: registry.register_read_write(
+                &format!("OVHD_{}_PB_IS_AVAILABLE", name),
+                VariableUnit::Bool,
+            ),
             is_on,
             is_available: false,
         }
@@ -131,25 +140,27 @@ impl SimulationElement for OnOffAvailablePushButton {
 }
 
 pub struct NormalAltnFaultPushButton {
-    is_normal_id: String,
-    has_fault_id: String,
+    is_normal_id: QuantityIdentifier<bool>,
+    has_fault_id: QuantityIdentifier<bool>,
 
     is_normal: bool,
     has_fault: bool,
 }
 impl NormalAltnFaultPushButton {
-    pub fn new_normal(name: &str) -> Self {
-        Self::new(name, true)
+    pub fn new_normal(registry: &mut VariableRegistry, name: &str) -> Self {
+        Self::new(registry, name, true)
     }
 
-    pub fn new_altn(name: &str) -> Self {
-        Self::new(name, false)
+    pub fn new_altn(registry: &mut VariableRegistry, name: &str) -> Self {
+        Self::new(registry, name, false)
     }
 
-    fn new(name: &str, is_normal: bool) -> Self {
+    fn new(registry: &mut VariableRegistry, name: &str, is_normal: bool) -> Self {
         Self {
-            is_normal_id: format!("OVHD_{}_PB_IS_NORMAL", name),
-            has_fault_id: format!("OVHD_{}_PB_HAS_FAULT", name),
+            is_normal_id: registry
+                .register_read_write(&format!("OVHD_{}_PB_IS_NORMAL", name), VariableUnit::Bool),
+            has_fault_id: registry
+                .register_read_write(&format!("OVHD_{}_PB_HAS_FAULT", name), VariableUnit::Bool),
             is_normal,
             has_fault: false,
         }
@@ -192,25 +203,27 @@ impl SimulationElement for NormalAltnFaultPushButton {
 }
 
 pub struct AutoOffFaultPushButton {
-    is_auto_id: String,
-    has_fault_id: String,
+    is_auto_id: QuantityIdentifier<bool>,
+    has_fault_id: QuantityIdentifier<bool>,
 
     is_auto: bool,
     has_fault: bool,
 }
 impl AutoOffFaultPushButton {
-    pub fn new_auto(name: &str) -> Self {
-        Self::new(name, true)
+    pub fn new_auto(registry: &mut VariableRegistry, name: &str) -> Self {
+        Self::new(registry, name, true)
     }
 
-    pub fn new_off(name: &str) -> Self {
-        Self::new(name, false)
+    pub fn new_off(registry: &mut VariableRegistry, name: &str) -> Self {
+        Self::new(registry, name, false)
     }
 
-    fn new(name: &str, is_auto: bool) -> Self {
+    fn new(registry: &mut VariableRegistry, name: &str, is_auto: bool) -> Self {
         Self {
-            is_auto_id: format!("OVHD_{}_PB_IS_AUTO", name),
-            has_fault_id: format!("OVHD_{}_PB_HAS_FAULT", name),
+            is_auto_id: registry
+                .register_read_write(&format!("OVHD_{}_PB_IS_AUTO", name), VariableUnit::Bool),
+            has_fault_id: registry
+                .register_read_write(&format!("OVHD_{}_PB_HAS_FAULT", name), VariableUnit::Bool),
             is_auto,
             has_fault: false,
         }
@@ -240,7 +253,7 @@ impl AutoOffFaultPushButton {
         self.has_fault
     }
 
-    fn set_fault(&mut self, value: bool) {
+    pub fn set_fault(&mut self, value: bool) {
         self.has_fault = value;
     }
 }
@@ -257,25 +270,27 @@ impl SimulationElement for AutoOffFaultPushButton {
 }
 
 pub struct FaultReleasePushButton {
-    is_released_id: String,
-    has_fault_id: String,
+    is_released_id: QuantityIdentifier<bool>,
+    has_fault_id: QuantityIdentifier<bool>,
     is_released: bool,
     has_fault: bool,
 }
 impl FaultReleasePushButton {
     #[cfg(test)]
-    pub fn new_released(name: &str) -> Self {
-        Self::new(name, true)
+    pub fn new_released(registry: &mut VariableRegistry, name: &str) -> Self {
+        Self::new(registry, name, true)
     }
 
-    pub fn new_in(name: &str) -> Self {
-        Self::new(name, false)
+    pub fn new_in(registry: &mut VariableRegistry, name: &str) -> Self {
+        Self::new(registry, name, false)
     }
 
-    fn new(name: &str, is_released: bool) -> Self {
+    fn new(registry: &mut VariableRegistry, name: &str, is_released: bool) -> Self {
         Self {
-            is_released_id: format!("OVHD_{}_PB_IS_RELEASED", name),
-            has_fault_id: format!("OVHD_{}_PB_HAS_FAULT", name),
+            is_released_id: registry
+                .register_read_write(&format!("OVHD_{}_PB_IS_RELEASED", name), VariableUnit::Bool),
+            has_fault_id: registry
+                .register_read_write(&format!("OVHD_{}_PB_HAS_FAULT", name), VariableUnit::Bool),
             is_released,
             has_fault: false,
         }
@@ -310,13 +325,14 @@ impl SimulationElement for FaultReleasePushButton {
 }
 
 pub struct FirePushButton {
-    is_released_id: String,
+    is_released_id: QuantityIdentifier<bool>,
     is_released: bool,
 }
 impl FirePushButton {
-    pub fn new(name: &str) -> Self {
+    pub fn new(registry: &mut VariableRegistry, name: &str) -> Self {
         Self {
-            is_released_id: format!("FIRE_BUTTON_{}", name),
+            is_released_id: registry
+                .register_read_write(&format!("FIRE_BUTTON_{}", name), VariableUnit::Bool),
             is_released: false,
         }
     }
@@ -347,17 +363,17 @@ mod on_off_fault_push_button_tests {
 
     #[test]
     fn new_on_push_button_is_on() {
-        assert!(OnOffFaultPushButton::new_on("BUTTON").is_on());
+        assert!(OnOffFaultPushButton::new_on(&mut VariableRegistry::new(), "BUTTON").is_on());
     }
 
     #[test]
     fn new_off_push_button_is_off() {
-        assert!(OnOffFaultPushButton::new_off("BUTTON").is_off());
+        assert!(OnOffFaultPushButton::new_off(&mut VariableRegistry::new(), "BUTTON").is_off());
     }
 
     #[test]
     fn writes_its_state() {
-        let mut button = OnOffFaultPushButton::new_on("ELEC_GEN_1");
+        let mut button = OnOffFaultPushButton::new_on(&mut VariableRegistry::new(), "ELEC_GEN_1");
         let mut test_bed = SimulationTestBed::new();
 
         test_bed.run_without_update(&mut button);
@@ -375,17 +391,18 @@ mod on_off_available_push_button_tests {
 
     #[test]
     fn new_on_push_button_is_on() {
-        assert!(OnOffAvailablePushButton::new_on("BUTTON").is_on());
+        assert!(OnOffAvailablePushButton::new_on(&mut VariableRegistry::new(), "BUTTON").is_on());
     }
 
     #[test]
     fn new_off_push_button_is_off() {
-        assert!(OnOffAvailablePushButton::new_off("BUTTON").is_off());
+        assert!(OnOffAvailablePushButton::new_off(&mut VariableRegistry::new(), "BUTTON").is_off());
     }
 
     #[test]
     fn writes_its_state() {
-        let mut button = OnOffAvailablePushButton::new_on("ELEC_EXT_PWR");
+        let mut button =
+            OnOffAvailablePushButton::new_on(&mut VariableRegistry::new(), "ELEC_EXT_PWR");
         let mut test_bed = SimulationTestBed::new();
 
         test_bed.run_without_update(&mut button);
@@ -403,17 +420,22 @@ mod normal_altn_fault_push_button_tests {
 
     #[test]
     fn new_normal_push_button_is_normal() {
-        assert!(NormalAltnFaultPushButton::new_normal("TEST").is_normal());
+        assert!(
+            NormalAltnFaultPushButton::new_normal(&mut VariableRegistry::new(), "TEST").is_normal()
+        );
     }
 
     #[test]
     fn new_altn_push_button_is_altn() {
-        assert!(NormalAltnFaultPushButton::new_altn("TEST").is_altn());
+        assert!(
+            NormalAltnFaultPushButton::new_altn(&mut VariableRegistry::new(), "TEST").is_altn()
+        );
     }
 
     #[test]
     fn writes_its_state() {
-        let mut button = NormalAltnFaultPushButton::new_normal("ELEC_AC_ESS_FEED");
+        let mut button =
+            NormalAltnFaultPushButton::new_normal(&mut VariableRegistry::new(), "ELEC_AC_ESS_FEED");
         let mut test_bed = SimulationTestBed::new();
 
         test_bed.run_without_update(&mut button);
@@ -431,17 +453,18 @@ mod auto_off_fault_push_button_tests {
 
     #[test]
     fn new_auto_push_button_is_auto() {
-        assert!(AutoOffFaultPushButton::new_auto("TEST").is_auto());
+        assert!(AutoOffFaultPushButton::new_auto(&mut VariableRegistry::new(), "TEST").is_auto());
     }
 
     #[test]
     fn new_off_push_button_is_off() {
-        assert!(AutoOffFaultPushButton::new_off("TEST").is_off());
+        assert!(AutoOffFaultPushButton::new_off(&mut VariableRegistry::new(), "TEST").is_off());
     }
 
     #[test]
     fn writes_its_state() {
-        let mut button = AutoOffFaultPushButton::new_auto("ELEC_BUS_TIE");
+        let mut button =
+            AutoOffFaultPushButton::new_auto(&mut VariableRegistry::new(), "ELEC_BUS_TIE");
         let mut test_bed = SimulationTestBed::new();
 
         test_bed.run_without_update(&mut button);
@@ -459,21 +482,21 @@ mod fault_release_push_button_tests {
 
     #[test]
     fn new_in_is_not_released() {
-        let pb = FaultReleasePushButton::new_in("TEST");
+        let pb = FaultReleasePushButton::new_in(&mut VariableRegistry::new(), "TEST");
 
         assert_eq!(pb.is_released(), false);
     }
 
     #[test]
     fn new_released_is_released() {
-        let pb = FaultReleasePushButton::new_released("TEST");
+        let pb = FaultReleasePushButton::new_released(&mut VariableRegistry::new(), "TEST");
 
         assert_eq!(pb.is_released(), true);
     }
 
     #[test]
     fn when_set_as_released_is_released() {
-        let mut pb = FaultReleasePushButton::new_in("TEST");
+        let mut pb = FaultReleasePushButton::new_in(&mut VariableRegistry::new(), "TEST");
         pb.set_released(true);
 
         assert_eq!(pb.is_released(), true);
@@ -481,7 +504,7 @@ mod fault_release_push_button_tests {
 
     #[test]
     fn once_released_stays_released() {
-        let mut pb = FaultReleasePushButton::new_in("TEST");
+        let mut pb = FaultReleasePushButton::new_in(&mut VariableRegistry::new(), "TEST");
         pb.set_released(true);
         pb.set_released(false);
 
@@ -490,7 +513,7 @@ mod fault_release_push_button_tests {
 
     #[test]
     fn writes_its_state() {
-        let mut button = FaultReleasePushButton::new_in("IDG_1");
+        let mut button = FaultReleasePushButton::new_in(&mut VariableRegistry::new(), "IDG_1");
         let mut test_bed = SimulationTestBed::new();
 
         test_bed.run_without_update(&mut button);
@@ -508,14 +531,14 @@ mod fire_push_button_tests {
 
     #[test]
     fn new_fire_push_button_is_not_released() {
-        let pb = FirePushButton::new("TEST");
+        let pb = FirePushButton::new(&mut VariableRegistry::new(), "TEST");
 
         assert_eq!(pb.is_released(), false);
     }
 
     #[test]
     fn when_set_as_released_is_released() {
-        let mut pb = FirePushButton::new("TEST");
+        let mut pb = FirePushButton::new(&mut VariableRegistry::new(), "TEST");
         pb.set(true);
 
         assert_eq!(pb.is_released(), true);
@@ -523,7 +546,7 @@ mod fire_push_button_tests {
 
     #[test]
     fn once_released_stays_released() {
-        let mut pb = FirePushButton::new("TEST");
+        let mut pb = FirePushButton::new(&mut VariableRegistry::new(), "TEST");
         pb.set(true);
         pb.set(false);
 
@@ -532,7 +555,7 @@ mod fire_push_button_tests {
 
     #[test]
     fn writes_its_state() {
-        let mut button = FirePushButton::new("APU");
+        let mut button = FirePushButton::new(&mut VariableRegistry::new(), "APU");
         let mut test_bed = SimulationTestBed::new();
 
         test_bed.run_without_update(&mut button);
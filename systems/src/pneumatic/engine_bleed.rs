@@ -0,0 +1,239 @@
+use super::BleedAirSource;
+use crate::simulation::{
+    QuantityIdentifier, SimulationElement, SimulatorWriter, UpdateContext, VariableRegistry,
+    VariableUnit,
+};
+use uom::si::{
+    f64::*, mass_rate::kilogram_per_second, pressure::psi, ratio::percent,
+    thermodynamic_temperature::degree_celsius,
+};
+
+/// The bleed air system of a single engine. Air is normally taken from the intermediate
+/// pressure (IP) stage of the high pressure compressor. At low engine speeds the IP stage
+/// doesn't deliver sufficient pressure, and the high pressure (HP) valve opens to take air
+/// from the HP stage instead. The pressure regulating valve (PRV) limits the pressure
+/// delivered downstream, after which the precooler cools the air using fan air.
+pub struct EngineBleedAirSystem {
+    hp_valve_open_id: QuantityIdentifier<bool>,
+    pr_valve_open_id: QuantityIdentifier<bool>,
+    upstream_pressure: Pressure,
+    upstream_temperature: ThermodynamicTemperature,
+    engine_n2: Ratio,
+    hp_valve_is_open: bool,
+    pr_valve_is_open: bool,
+    supplied_flow: MassRate,
+}
+impl EngineBleedAirSystem {
+    const IP_PRESSURE_AT_FULL_N2_PSI: f64 = 60.;
+    const HP_TO_IP_PRESSURE_RATIO: f64 = 2.5;
+    /// The HP valve opens when the IP stage delivers less than this pressure.
+    const HP_VALVE_OPENING_IP_PRESSURE_PSI: f64 = 30.;
+    /// The PRV is pneumatically operated and requires this upstream pressure to open.
+    const PR_VALVE_MINIMUM_UPSTREAM_PRESSURE_PSI: f64 = 8.;
+    const PR_VALVE_REGULATED_PRESSURE_PSI: f64 = 44.;
+    const IP_TEMPERATURE_RISE_AT_FULL_N2_DEGREE_CELSIUS: f64 = 350.;
+    const HP_ADDITIONAL_TEMPERATURE_RISE_DEGREE_CELSIUS: f64 = 150.;
+    const PRECOOLER_REGULATED_TEMPERATURE_DEGREE_CELSIUS: f64 = 200.;
+    const MAX_FLOW_AT_FULL_N2_KILOGRAM_PER_SECOND: f64 = 1.5;
+
+    pub fn new(registry: &mut VariableRegistry, number: usize) -> EngineBleedAirSystem {
+        EngineBleedAirSystem {
+            hp_valve_open_id: registry.register_write(
+                &format!("PNEU_ENG_{}_HP_VALVE_OPEN", number),
+                VariableUnit::Bool,
+            ),
+            pr_valve_open_id: registry.register_write(
+                &format!("PNEU_ENG_{}_PR_VALVE_OPEN", number),
+                VariableUnit::Bool,
+            ),
+            upstream_pressure: Pressure::new::<psi>(0.),
+            upstream_temperature: ThermodynamicTemperature::new::<degree_celsius>(15.),
+            engine_n2: Ratio::new::<percent>(0.),
+            hp_valve_is_open: false,
+            pr_valve_is_open: false,
+            supplied_flow: MassRate::new::<kilogram_per_second>(0.),
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        context: &UpdateContext,
+        engine_n2: Ratio,
+        should_open_pr_valve: bool,
+    ) {
+        self.engine_n2 = engine_n2;
+
+        let n2_ratio = (engine_n2.get::<percent>() / 100.).max(0.);
+        let ip_pressure = EngineBleedAirSystem::IP_PRESSURE_AT_FULL_N2_PSI * n2_ratio.powi(2);
+        let ip_temperature = context.ambient_temperature().get::<degree_celsius>()
+            + EngineBleedAirSystem::IP_TEMPERATURE_RISE_AT_FULL_N2_DEGREE_CELSIUS * n2_ratio;
+
+        self.hp_valve_is_open = ip_pressure > 0.
+            && ip_pressure < EngineBleedAirSystem::HP_VALVE_OPENING_IP_PRESSURE_PSI;
+
+        let (upstream_pressure, upstream_temperature) = if self.hp_valve_is_open {
+            (
+                ip_pressure * EngineBleedAirSystem::HP_TO_IP_PRESSURE_RATIO,
+                ip_temperature
+                    + EngineBleedAirSystem::HP_ADDITIONAL_TEMPERATURE_RISE_DEGREE_CELSIUS,
+            )
+        } else {
+            (ip_pressure, ip_temperature)
+        };
+        self.upstream_pressure = Pressure::new::<psi>(upstream_pressure);
+        self.upstream_temperature =
+            ThermodynamicTemperature::new::<degree_celsius>(upstream_temperature);
+
+        self.pr_valve_is_open = should_open_pr_valve
+            && upstream_pressure >= EngineBleedAirSystem::PR_VALVE_MINIMUM_UPSTREAM_PRESSURE_PSI;
+    }
+
+    pub fn hp_valve_is_open(&self) -> bool {
+        self.hp_valve_is_open
+    }
+
+    pub fn pr_valve_is_open(&self) -> bool {
+        self.pr_valve_is_open
+    }
+
+    pub fn supplied_flow(&self) -> MassRate {
+        self.supplied_flow
+    }
+
+    fn precooler_outlet_temperature(&self) -> ThermodynamicTemperature {
+        let regulated_temperature = ThermodynamicTemperature::new::<degree_celsius>(
+            EngineBleedAirSystem::PRECOOLER_REGULATED_TEMPERATURE_DEGREE_CELSIUS,
+        );
+
+        if self.upstream_temperature > regulated_temperature {
+            regulated_temperature
+        } else {
+            self.upstream_temperature
+        }
+    }
+}
+impl BleedAirSource for EngineBleedAirSystem {
+    fn supply_pressure(&self) -> Pressure {
+        if self.pr_valve_is_open {
+            self.upstream_pressure.min(Pressure::new::<psi>(
+                EngineBleedAirSystem::PR_VALVE_REGULATED_PRESSURE_PSI,
+            ))
+        } else {
+            Pressure::new::<psi>(0.)
+        }
+    }
+
+    fn supply_temperature(&self) -> ThermodynamicTemperature {
+        self.precooler_outlet_temperature()
+    }
+
+    fn max_flow(&self) -> MassRate {
+        if self.pr_valve_is_open {
+            MassRate::new::<kilogram_per_second>(
+                EngineBleedAirSystem::MAX_FLOW_AT_FULL_N2_KILOGRAM_PER_SECOND
+                    * self.engine_n2.get::<percent>()
+                    / 100.,
+            )
+        } else {
+            MassRate::new::<kilogram_per_second>(0.)
+        }
+    }
+
+    fn set_supplied_flow(&mut self, flow: MassRate) {
+        self.supplied_flow = flow;
+    }
+}
+impl SimulationElement for EngineBleedAirSystem {
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_bool(&self.hp_valve_open_id, self.hp_valve_is_open);
+        writer.write_bool(&self.pr_valve_open_id, self.pr_valve_is_open);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::test::SimulationTestBed;
+
+    fn update_with(n2: f64, should_open_pr_valve: bool) -> EngineBleedAirSystem {
+        let mut bleed = EngineBleedAirSystem::new(&mut VariableRegistry::new(), 1);
+        SimulationTestBed::new().run(&mut bleed, |bleed, context| {
+            bleed.update(context, Ratio::new::<percent>(n2), should_open_pr_valve)
+        });
+
+        bleed
+    }
+
+    #[test]
+    fn supplies_nothing_when_engine_stopped() {
+        let bleed = update_with(0., true);
+
+        assert!(!bleed.pr_valve_is_open());
+        assert_eq!(bleed.supply_pressure(), Pressure::new::<psi>(0.));
+        assert_eq!(bleed.max_flow(), MassRate::new::<kilogram_per_second>(0.));
+    }
+
+    #[test]
+    fn hp_valve_opens_at_idle() {
+        let bleed = update_with(60., true);
+
+        assert!(bleed.hp_valve_is_open());
+        assert!(bleed.supply_pressure() > Pressure::new::<psi>(30.));
+    }
+
+    #[test]
+    fn hp_valve_closes_at_high_engine_speed() {
+        let bleed = update_with(90., true);
+
+        assert!(!bleed.hp_valve_is_open());
+        assert!(bleed.supply_pressure() > Pressure::new::<psi>(30.));
+    }
+
+    #[test]
+    fn pr_valve_limits_supply_pressure() {
+        let bleed = update_with(100., true);
+
+        assert_eq!(
+            bleed.supply_pressure(),
+            Pressure::new::<psi>(EngineBleedAirSystem::PR_VALVE_REGULATED_PRESSURE_PSI)
+        );
+    }
+
+    #[test]
+    fn supplies_nothing_when_pr_valve_commanded_closed() {
+        let bleed = update_with(80., false);
+
+        assert!(!bleed.pr_valve_is_open());
+        assert_eq!(bleed.supply_pressure(), Pressure::new::<psi>(0.));
+    }
+
+    #[test]
+    fn precooler_limits_supply_temperature() {
+        let bleed = update_with(100., true);
+
+        assert_eq!(
+            bleed.supply_temperature(),
+            ThermodynamicTemperature::new::<degree_celsius>(
+                EngineBleedAirSystem::PRECOOLER_REGULATED_TEMPERATURE_DEGREE_CELSIUS
+            )
+        );
+    }
+
+    #[test]
+    fn supply_temperature_above_ambient_when_running() {
+        let bleed = update_with(60., true);
+
+        assert!(bleed.supply_temperature() > ThermodynamicTemperature::new::<degree_celsius>(15.));
+    }
+
+    #[test]
+    fn writes_its_state() {
+        let mut bleed = EngineBleedAirSystem::new(&mut VariableRegistry::new(), 1);
+        let mut test_bed = SimulationTestBed::new();
+
+        test_bed.run_without_update(&mut bleed);
+
+        assert!(test_bed.contains_key("PNEU_ENG_1_HP_VALVE_OPEN"));
+        assert!(test_bed.contains_key("PNEU_ENG_1_PR_VALVE_OPEN"));
+    }
+}
@@ -1,4 +1,20 @@
-//! As we've not yet modelled pneumatic systems and some pneumatic things are needed for the APU, for now this implementation will be very simple.
+//! Bleed air is taken from the engines' compressors or the APU's load compressor. It flows
+//! through ducts towards consumers such as the packs, the engine starters and wing anti-ice.
+use crate::{
+    electrical::{consumption::PowerConsumer, ElectricalBusType},
+    simulation::{
+        Failure, FailureType, QuantityIdentifier, SimulationElement, SimulationElementVisitor,
+        SimulatorWriter, Snapshot, UpdateContext, VariableRegistry, VariableUnit,
+    },
+};
+use std::cmp::Ordering;
+use uom::si::{
+    f64::*, mass_rate::kilogram_per_second, pressure::psi, ratio::ratio,
+    thermodynamic_temperature::degree_celsius,
+};
+
+mod engine_bleed;
+pub use engine_bleed::EngineBleedAirSystem;
 
 pub trait BleedAirValveState {
     fn bleed_air_valve_is_open(&self) -> bool;
@@ -35,3 +51,608 @@ impl Default for BleedAirValve {
         Self::new()
     }
 }
+
+/// A source of air flowing into a [`BleedAirDuct`].
+pub trait BleedAirSource {
+    /// The pressure at which the source supplies air.
+    fn supply_pressure(&self) -> Pressure;
+
+    /// The temperature of the air the source supplies.
+    fn supply_temperature(&self) -> ThermodynamicTemperature;
+
+    /// The maximum mass flow the source can supply at this moment.
+    fn max_flow(&self) -> MassRate;
+
+    /// Informs the source of the flow it actually supplied into the duct.
+    fn set_supplied_flow(&mut self, flow: MassRate);
+}
+
+/// A bleed air duct. Check valves ensure air is taken from the source with the highest
+/// supply pressure first. When the consumers demand more air than the sources can
+/// supply, the duct's pressure drops.
+pub struct BleedAirDuct {
+    pressure_id: QuantityIdentifier<Pressure>,
+    temperature_id: QuantityIdentifier<ThermodynamicTemperature>,
+    pressure: Pressure,
+    temperature: ThermodynamicTemperature,
+}
+impl BleedAirDuct {
+    const PRESSURE_TIME_CONSTANT_SECONDS: f64 = 0.5;
+    const TEMPERATURE_TIME_CONSTANT_SECONDS: f64 = 10.;
+
+    pub fn new(registry: &mut VariableRegistry, id: &str) -> BleedAirDuct {
+        BleedAirDuct {
+            pressure_id: registry
+                .register_write(&format!("PNEU_{}_DUCT_PRESSURE", id), VariableUnit::Psi),
+            temperature_id: registry.register_write(
+                &format!("PNEU_{}_DUCT_TEMPERATURE", id),
+                VariableUnit::Celsius,
+            ),
+            pressure: Pressure::new::<psi>(0.),
+            temperature: ThermodynamicTemperature::new::<degree_celsius>(15.),
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        context: &UpdateContext,
+        sources: &mut [&mut dyn BleedAirSource],
+        demand: MassRate,
+    ) {
+        let mut remaining_demand = demand;
+        let mut max_flow = MassRate::new::<kilogram_per_second>(0.);
+        for source in sources.iter_mut() {
+            source.set_supplied_flow(MassRate::new::<kilogram_per_second>(0.));
+        }
+
+        let highest_pressure_source = next_supplying_source(sources, None);
+        let mut previous = None;
+        while let Some(index) = next_supplying_source(sources, previous) {
+            let flow = remaining_demand.min(sources[index].max_flow());
+            sources[index].set_supplied_flow(flow);
+            remaining_demand -= flow;
+            max_flow += sources[index].max_flow();
+            previous = Some(index);
+        }
+
+        let (target_pressure, target_temperature) = match highest_pressure_source {
+            Some(index) => {
+                let pressure = if demand > max_flow {
+                    sources[index].supply_pressure() * (max_flow / demand).get::<ratio>()
+                } else {
+                    sources[index].supply_pressure()
+                };
+
+                (pressure, sources[index].supply_temperature())
+            }
+            None => (Pressure::new::<psi>(0.), context.ambient_temperature()),
+        };
+
+        self.pressure = Pressure::new::<psi>(approach(
+            self.pressure.get::<psi>(),
+            target_pressure.get::<psi>(),
+            context,
+            BleedAirDuct::PRESSURE_TIME_CONSTANT_SECONDS,
+        ));
+        self.temperature = ThermodynamicTemperature::new::<degree_celsius>(approach(
+            self.temperature.get::<degree_celsius>(),
+            target_temperature.get::<degree_celsius>(),
+            context,
+            BleedAirDuct::TEMPERATURE_TIME_CONSTANT_SECONDS,
+        ));
+    }
+
+    /// Equalises the duct's state with the given duct, for when both
+    /// ducts are connected through an open valve.
+    pub fn equalise_with(&mut self, other: &BleedAirDuct) {
+        self.pressure = other.pressure;
+        self.temperature = other.temperature;
+    }
+
+    pub fn pressure(&self) -> Pressure {
+        self.pressure
+    }
+
+    pub fn temperature(&self) -> ThermodynamicTemperature {
+        self.temperature
+    }
+}
+impl SimulationElement for BleedAirDuct {
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_pressure(&self.pressure_id, self.pressure);
+        writer.write_temperature(&self.temperature_id, self.temperature);
+    }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        snapshot.save_f64(self.pressure_id.name(), self.pressure.get::<psi>());
+        snapshot.save_f64(
+            self.temperature_id.name(),
+            self.temperature.get::<degree_celsius>(),
+        );
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        if let Some(pressure) = snapshot.restore_f64(self.pressure_id.name()) {
+            self.pressure = Pressure::new::<psi>(pressure);
+        }
+        if let Some(temperature) = snapshot.restore_f64(self.temperature_id.name()) {
+            self.temperature = ThermodynamicTemperature::new::<degree_celsius>(temperature);
+        }
+    }
+}
+
+fn is_supplying(source: &dyn BleedAirSource) -> bool {
+    source.supply_pressure() > Pressure::new::<psi>(0.)
+        && source.max_flow() > MassRate::new::<kilogram_per_second>(0.)
+}
+
+/// Orders the sources by descending supply pressure, and sources with equal
+/// pressure by their position in the slice.
+fn supply_order(sources: &[&mut dyn BleedAirSource], a: usize, b: usize) -> Ordering {
+    sources[b]
+        .supply_pressure()
+        .get::<psi>()
+        .total_cmp(&sources[a].supply_pressure().get::<psi>())
+        .then(a.cmp(&b))
+}
+
+/// The supplying source which follows the previous source in supply order,
+/// or the first supplying source when there is no previous source.
+fn next_supplying_source(
+    sources: &[&mut dyn BleedAirSource],
+    previous: Option<usize>,
+) -> Option<usize> {
+    (0..sources.len())
+        .filter(|&index| is_supplying(&*sources[index]))
+        .filter(|&index| match previous {
+            Some(previous) => supply_order(sources, previous, index) == Ordering::Less,
+            None => true,
+        })
+        .min_by(|&a, &b| supply_order(sources, a, b))
+}
+
+/// Moves the value towards the target as a first order lag with the given time constant.
+fn approach(value: f64, target: f64, context: &UpdateContext, time_constant_seconds: f64) -> f64 {
+    value + (target - value) * (1. - (-context.delta().as_secs_f64() / time_constant_seconds).exp())
+}
+
+/// A motor operated valve connecting two bleed air ducts. When its motor is
+/// unpowered or the valve has failed, the valve remains in its last position.
+pub struct CrossBleedValve {
+    is_open_id: QuantityIdentifier<bool>,
+    is_open: bool,
+    consumer: PowerConsumer,
+    failure: Failure,
+}
+impl CrossBleedValve {
+    pub fn new(
+        registry: &mut VariableRegistry,
+        number: usize,
+        bus_type: ElectricalBusType,
+    ) -> CrossBleedValve {
+        CrossBleedValve {
+            is_open_id: registry.register_write("PNEU_XBLEED_VALVE_OPEN", VariableUnit::Bool),
+            is_open: false,
+            consumer: PowerConsumer::from(bus_type),
+            failure: Failure::new(registry, FailureType::BleedAirValve(number)),
+        }
+    }
+
+    pub fn update(&mut self, _: &UpdateContext, should_open: bool) {
+        if self.consumer.is_powered() && !self.failure.is_active() {
+            self.is_open = should_open;
+        }
+    }
+}
+impl Valve for CrossBleedValve {
+    fn is_open(&self) -> bool {
+        self.is_open
+    }
+}
+impl SimulationElement for CrossBleedValve {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.consumer.accept(visitor);
+        self.failure.accept(visitor);
+
+        visitor.visit(self);
+    }
+
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_bool(&self.is_open_id, self.is_open);
+    }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        snapshot.save_bool(self.is_open_id.name(), self.is_open);
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        if let Some(is_open) = snapshot.restore_bool(self.is_open_id.name()) {
+            self.is_open = is_open;
+        }
+    }
+}
+
+/// A valve through which a consumer, such as a pack or the wing anti-ice system,
+/// takes air from a [`BleedAirDuct`]. The valve is pneumatically operated, and thus
+/// only opens when the duct supplies sufficient pressure. A failed valve remains
+/// in its last position.
+pub struct BleedAirConsumerValve {
+    is_open_id: QuantityIdentifier<bool>,
+    is_open: bool,
+    nominal_flow: MassRate,
+    failure: Failure,
+}
+impl BleedAirConsumerValve {
+    const MINIMUM_OPENING_PRESSURE_PSI: f64 = 8.;
+
+    pub fn new(
+        registry: &mut VariableRegistry,
+        id: &str,
+        number: usize,
+        nominal_flow: MassRate,
+    ) -> BleedAirConsumerValve {
+        BleedAirConsumerValve {
+            is_open_id: registry
+                .register_write(&format!("PNEU_{}_VALVE_OPEN", id), VariableUnit::Bool),
+            is_open: false,
+            nominal_flow,
+            failure: Failure::new(registry, FailureType::BleedAirValve(number)),
+        }
+    }
+
+    pub fn update(&mut self, _: &UpdateContext, should_open: bool, duct: &BleedAirDuct) {
+        if self.failure.is_active() {
+            return;
+        }
+
+        self.is_open = should_open
+            && duct.pressure()
+                >= Pressure::new::<psi>(BleedAirConsumerValve::MINIMUM_OPENING_PRESSURE_PSI);
+    }
+
+    /// The flow the consumer takes from the duct.
+    pub fn demand(&self) -> MassRate {
+        if self.is_open {
+            self.nominal_flow
+        } else {
+            MassRate::new::<kilogram_per_second>(0.)
+        }
+    }
+}
+impl Valve for BleedAirConsumerValve {
+    fn is_open(&self) -> bool {
+        self.is_open
+    }
+}
+impl SimulationElement for BleedAirConsumerValve {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.failure.accept(visitor);
+
+        visitor.visit(self);
+    }
+
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_bool(&self.is_open_id, self.is_open);
+    }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        snapshot.save_bool(self.is_open_id.name(), self.is_open);
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        if let Some(is_open) = snapshot.restore_bool(self.is_open_id.name()) {
+            self.is_open = is_open;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(test)]
+    mod bleed_air_duct_tests {
+        use super::*;
+        use crate::simulation::{test::SimulationTestBed, Aircraft};
+        use std::time::Duration;
+
+        struct TestSource {
+            supply_pressure: Pressure,
+            max_flow: MassRate,
+            supplied_flow: MassRate,
+        }
+        impl TestSource {
+            fn new(supply_pressure: f64, max_flow: f64) -> Self {
+                Self {
+                    supply_pressure: Pressure::new::<psi>(supply_pressure),
+                    max_flow: MassRate::new::<kilogram_per_second>(max_flow),
+                    supplied_flow: MassRate::new::<kilogram_per_second>(0.),
+                }
+            }
+        }
+        impl BleedAirSource for TestSource {
+            fn supply_pressure(&self) -> Pressure {
+                self.supply_pressure
+            }
+
+            fn supply_temperature(&self) -> ThermodynamicTemperature {
+                ThermodynamicTemperature::new::<degree_celsius>(200.)
+            }
+
+            fn max_flow(&self) -> MassRate {
+                self.max_flow
+            }
+
+            fn set_supplied_flow(&mut self, flow: MassRate) {
+                self.supplied_flow = flow;
+            }
+        }
+
+        struct TestAircraft {
+            duct: BleedAirDuct,
+            high_pressure_source: TestSource,
+            low_pressure_source: TestSource,
+            demand: MassRate,
+        }
+        impl TestAircraft {
+            fn new(demand: f64) -> Self {
+                Self {
+                    duct: BleedAirDuct::new(&mut VariableRegistry::new(), "LEFT"),
+                    high_pressure_source: TestSource::new(44., 1.),
+                    low_pressure_source: TestSource::new(38., 1.),
+                    demand: MassRate::new::<kilogram_per_second>(demand),
+                }
+            }
+
+            fn without_sources(mut self) -> Self {
+                self.high_pressure_source = TestSource::new(0., 0.);
+                self.low_pressure_source = TestSource::new(0., 0.);
+                self
+            }
+        }
+        impl Aircraft for TestAircraft {
+            fn update_after_power_distribution(&mut self, context: &UpdateContext) {
+                self.duct.update(
+                    context,
+                    &mut [
+                        &mut self.low_pressure_source,
+                        &mut self.high_pressure_source,
+                    ],
+                    self.demand,
+                );
+            }
+        }
+        impl SimulationElement for TestAircraft {
+            fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+                self.duct.accept(visitor);
+
+                visitor.visit(self);
+            }
+        }
+
+        fn run_for(aircraft: &mut TestAircraft, duration: Duration) {
+            let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_millis(100));
+            for _ in 0..(duration.as_millis() / 100) {
+                test_bed.run_aircraft(aircraft);
+            }
+        }
+
+        #[test]
+        fn pressurises_to_highest_supply_pressure() {
+            let mut aircraft = TestAircraft::new(0.5);
+            run_for(&mut aircraft, Duration::from_secs(5));
+
+            assert!((aircraft.duct.pressure().get::<psi>() - 44.).abs() < 0.1);
+        }
+
+        #[test]
+        fn takes_air_from_highest_pressure_source_first() {
+            let mut aircraft = TestAircraft::new(0.5);
+            run_for(&mut aircraft, Duration::from_secs(1));
+
+            assert_eq!(
+                aircraft.high_pressure_source.supplied_flow,
+                MassRate::new::<kilogram_per_second>(0.5)
+            );
+            assert_eq!(
+                aircraft.low_pressure_source.supplied_flow,
+                MassRate::new::<kilogram_per_second>(0.)
+            );
+        }
+
+        #[test]
+        fn takes_air_from_lower_pressure_source_when_demand_exceeds_highest_pressure_source() {
+            let mut aircraft = TestAircraft::new(1.5);
+            run_for(&mut aircraft, Duration::from_secs(1));
+
+            assert_eq!(
+                aircraft.high_pressure_source.supplied_flow,
+                MassRate::new::<kilogram_per_second>(1.)
+            );
+            assert_eq!(
+                aircraft.low_pressure_source.supplied_flow,
+                MassRate::new::<kilogram_per_second>(0.5)
+            );
+        }
+
+        #[test]
+        fn pressure_drops_when_demand_exceeds_supply() {
+            let mut aircraft = TestAircraft::new(4.);
+            run_for(&mut aircraft, Duration::from_secs(5));
+
+            assert!(aircraft.duct.pressure() < Pressure::new::<psi>(30.));
+        }
+
+        #[test]
+        fn temperature_follows_supplying_source() {
+            let mut aircraft = TestAircraft::new(0.5);
+            run_for(&mut aircraft, Duration::from_secs(120));
+
+            assert!(
+                (aircraft.duct.temperature().get::<degree_celsius>() - 200.).abs() < 1.,
+                "Temperature was {}",
+                aircraft.duct.temperature().get::<degree_celsius>()
+            );
+        }
+
+        #[test]
+        fn has_no_pressure_without_sources() {
+            let mut aircraft = TestAircraft::new(0.5).without_sources();
+            run_for(&mut aircraft, Duration::from_secs(5));
+
+            assert_eq!(aircraft.duct.pressure(), Pressure::new::<psi>(0.));
+        }
+
+        #[test]
+        fn source_with_an_invalid_pressure_does_not_supply() {
+            let mut aircraft = TestAircraft::new(0.5);
+            aircraft.low_pressure_source = TestSource::new(f64::NAN, 1.);
+            run_for(&mut aircraft, Duration::from_secs(1));
+
+            assert_eq!(
+                aircraft.low_pressure_source.supplied_flow,
+                MassRate::new::<kilogram_per_second>(0.)
+            );
+            assert_eq!(
+                aircraft.high_pressure_source.supplied_flow,
+                MassRate::new::<kilogram_per_second>(0.5)
+            );
+        }
+
+        #[test]
+        fn sources_with_equal_pressure_supply_in_order() {
+            let mut aircraft = TestAircraft::new(1.5);
+            aircraft.low_pressure_source = TestSource::new(44., 1.);
+            run_for(&mut aircraft, Duration::from_secs(1));
+
+            assert_eq!(
+                aircraft.low_pressure_source.supplied_flow,
+                MassRate::new::<kilogram_per_second>(1.)
+            );
+            assert_eq!(
+                aircraft.high_pressure_source.supplied_flow,
+                MassRate::new::<kilogram_per_second>(0.5)
+            );
+        }
+
+        #[test]
+        fn is_stable_with_large_time_steps() {
+            let mut aircraft = TestAircraft::new(0.5);
+            let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_secs(100));
+            test_bed.run_aircraft(&mut aircraft);
+
+            assert!(aircraft.duct.pressure() <= Pressure::new::<psi>(44.));
+        }
+
+        #[test]
+        fn writes_its_state() {
+            let mut aircraft = TestAircraft::new(0.5);
+            let mut test_bed = SimulationTestBed::new();
+
+            test_bed.run_aircraft(&mut aircraft);
+
+            assert!(test_bed.contains_key("PNEU_LEFT_DUCT_PRESSURE"));
+            assert!(test_bed.contains_key("PNEU_LEFT_DUCT_TEMPERATURE"));
+        }
+
+        #[test]
+        fn pressure_and_temperature_are_restored_from_snapshot() {
+            let mut aircraft = TestAircraft::new(0.5);
+            run_for(&mut aircraft, Duration::from_secs(5));
+            let snapshot = Snapshot::take(&mut aircraft);
+
+            let mut restored_aircraft = TestAircraft::new(0.5);
+            snapshot.restore(&mut restored_aircraft);
+
+            assert_eq!(restored_aircraft.duct.pressure(), aircraft.duct.pressure());
+            assert_eq!(
+                restored_aircraft.duct.temperature(),
+                aircraft.duct.temperature()
+            );
+        }
+    }
+
+    #[cfg(test)]
+    mod bleed_air_consumer_valve_tests {
+        use super::*;
+        use crate::simulation::test::SimulationTestBed;
+
+        fn duct_with_pressure(pressure: f64) -> BleedAirDuct {
+            let mut duct = BleedAirDuct::new(&mut VariableRegistry::new(), "LEFT");
+            duct.pressure = Pressure::new::<psi>(pressure);
+
+            duct
+        }
+
+        fn valve() -> BleedAirConsumerValve {
+            BleedAirConsumerValve::new(
+                &mut VariableRegistry::new(),
+                "PACK_1_FLOW",
+                2,
+                MassRate::new::<kilogram_per_second>(0.4),
+            )
+        }
+
+        #[test]
+        fn opens_when_commanded_and_duct_pressurised() {
+            let mut valve = valve();
+            let duct = duct_with_pressure(40.);
+            SimulationTestBed::new().run(&mut valve, |valve, context| {
+                valve.update(context, true, &duct)
+            });
+
+            assert!(valve.is_open());
+            assert_eq!(valve.demand(), MassRate::new::<kilogram_per_second>(0.4));
+        }
+
+        #[test]
+        fn remains_closed_when_duct_unpressurised() {
+            let mut valve = valve();
+            let duct = duct_with_pressure(0.);
+            SimulationTestBed::new().run(&mut valve, |valve, context| {
+                valve.update(context, true, &duct)
+            });
+
+            assert!(!valve.is_open());
+            assert_eq!(valve.demand(), MassRate::new::<kilogram_per_second>(0.));
+        }
+
+        #[test]
+        fn remains_closed_when_not_commanded() {
+            let mut valve = valve();
+            let duct = duct_with_pressure(40.);
+            SimulationTestBed::new().run(&mut valve, |valve, context| {
+                valve.update(context, false, &duct)
+            });
+
+            assert!(!valve.is_open());
+        }
+
+        #[test]
+        fn failed_valve_remains_in_its_last_position() {
+            let mut valve = valve();
+            let duct = duct_with_pressure(40.);
+            let mut test_bed = SimulationTestBed::new();
+            test_bed.run(&mut valve, |valve, context| {
+                valve.update(context, true, &duct)
+            });
+
+            test_bed.activate_failure(FailureType::BleedAirValve(2));
+            test_bed.run(&mut valve, |valve, context| {
+                valve.update(context, false, &duct)
+            });
+
+            assert!(valve.is_open());
+        }
+
+        #[test]
+        fn writes_its_state() {
+            let mut valve = valve();
+            let mut test_bed = SimulationTestBed::new();
+
+            test_bed.run_without_update(&mut valve);
+
+            assert!(test_bed.contains_key("PNEU_PACK_1_FLOW_VALVE_OPEN"));
+        }
+    }
+}
@@ -0,0 +1,589 @@
+//! The cabin is pressurized by the air supplied by the packs. A cabin pressure
+//! controller positions the outflow valve so that the cabin altitude follows a
+//! schedule based on the aircraft's altitude. Safety valves protect the fuselage
+//! against excessive positive or negative differential pressure.
+use crate::{
+    electrical::{consumption::PowerConsumer, ElectricalBusType},
+    simulation::{
+        QuantityIdentifier, SimulationElement, SimulationElementVisitor, SimulatorWriter, Snapshot,
+        UpdateContext, VariableRegistry, VariableUnit,
+    },
+};
+use uom::si::{
+    f64::*,
+    length::{foot, meter},
+    mass_rate::kilogram_per_second,
+    power::watt,
+    pressure::{pascal, psi},
+    ratio::{percent, ratio},
+    velocity::foot_per_minute,
+};
+
+const SEA_LEVEL_PRESSURE_PASCAL: f64 = 101325.;
+const AIR_GAS_CONSTANT_JOULE_PER_KILOGRAM_KELVIN: f64 = 287.05;
+const CABIN_AIR_TEMPERATURE_KELVIN: f64 = 295.;
+
+/// Calculates the pressure at the given altitude in the International Standard Atmosphere.
+fn pressure_at_altitude(altitude: Length) -> Pressure {
+    Pressure::new::<pascal>(
+        SEA_LEVEL_PRESSURE_PASCAL * (1. - 2.25577e-5 * altitude.get::<meter>()).powf(5.25588),
+    )
+}
+
+/// Calculates the altitude at which the given pressure is found in the International Standard Atmosphere.
+fn altitude_at_pressure(pressure: Pressure) -> Length {
+    Length::new::<meter>(
+        (1. - (pressure.get::<pascal>() / SEA_LEVEL_PRESSURE_PASCAL).powf(1. / 5.25588))
+            / 2.25577e-5,
+    )
+}
+
+fn cabin_air_density(pressure: Pressure) -> f64 {
+    pressure.get::<pascal>()
+        / (AIR_GAS_CONSTANT_JOULE_PER_KILOGRAM_KELVIN * CABIN_AIR_TEMPERATURE_KELVIN)
+}
+
+/// The flow through an orifice with the given effective area. A positive differential
+/// pressure results in a positive flow out of the cabin.
+fn orifice_flow(
+    effective_area_square_meter: f64,
+    cabin_pressure: Pressure,
+    differential_pressure: Pressure,
+) -> f64 {
+    let differential_pressure = differential_pressure.get::<pascal>();
+
+    differential_pressure.signum()
+        * effective_area_square_meter
+        * (2. * cabin_air_density(cabin_pressure) * differential_pressure.abs()).sqrt()
+}
+
+/// The pressurized volume of the fuselage.
+pub struct PressurizedCabin {
+    altitude_id: QuantityIdentifier<Length>,
+    vertical_speed_id: QuantityIdentifier<Velocity>,
+    differential_pressure_id: QuantityIdentifier<Pressure>,
+    safety_valve_open_id: QuantityIdentifier<bool>,
+    pressure: Pressure,
+    ambient_pressure: Pressure,
+    vertical_speed: Velocity,
+    safety_valve_is_open: bool,
+}
+impl PressurizedCabin {
+    const VOLUME_CUBIC_METER: f64 = 139.;
+    const LEAK_EFFECTIVE_AREA_SQUARE_METER: f64 = 0.001;
+    const SAFETY_VALVE_OPENING_DIFFERENTIAL_PRESSURE_PSI: f64 = 8.6;
+    const NEGATIVE_RELIEF_DIFFERENTIAL_PRESSURE_PSI: f64 = -0.5;
+    const MAXIMUM_INTEGRATION_STEP_SECONDS: f64 = 0.1;
+
+    pub fn new(registry: &mut VariableRegistry) -> PressurizedCabin {
+        PressurizedCabin {
+            altitude_id: registry.register_write("PRESS_CABIN_ALTITUDE", VariableUnit::Feet),
+            vertical_speed_id: registry
+                .register_write("PRESS_CABIN_VS", VariableUnit::FeetPerMinute),
+            differential_pressure_id: registry
+                .register_write("PRESS_CABIN_DELTA_PRESSURE", VariableUnit::Psi),
+            safety_valve_open_id: registry
+                .register_write("PRESS_SAFETY_VALVE_OPEN", VariableUnit::Bool),
+            pressure: Pressure::new::<pascal>(SEA_LEVEL_PRESSURE_PASCAL),
+            ambient_pressure: Pressure::new::<pascal>(SEA_LEVEL_PRESSURE_PASCAL),
+            vertical_speed: Velocity::new::<foot_per_minute>(0.),
+            safety_valve_is_open: false,
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        context: &UpdateContext,
+        inflow: MassRate,
+        outflow_valve: &OutflowValve,
+    ) {
+        self.ambient_pressure = context.ambient_pressure();
+        let previous_altitude = self.altitude();
+
+        let effective_area = PressurizedCabin::LEAK_EFFECTIVE_AREA_SQUARE_METER
+            + outflow_valve.effective_area_square_meter();
+        let delta_seconds = context.delta().as_secs_f64();
+        let steps = (delta_seconds / PressurizedCabin::MAXIMUM_INTEGRATION_STEP_SECONDS).ceil();
+        for _ in 0..steps as usize {
+            self.integrate(delta_seconds / steps, inflow, effective_area);
+        }
+
+        self.relieve_through_safety_valves();
+
+        if delta_seconds > 0. {
+            self.vertical_speed = Velocity::new::<foot_per_minute>(
+                (self.altitude() - previous_altitude).get::<foot>() * 60. / delta_seconds,
+            );
+        }
+    }
+
+    fn integrate(&mut self, step_seconds: f64, inflow: MassRate, effective_area: f64) {
+        let mass_per_pascal = PressurizedCabin::mass_per_pascal();
+        let differential_pressure = self.differential_pressure().get::<pascal>();
+
+        // Air flowing through the orifices cannot flow beyond equalising the pressures.
+        let maximum_equalising_mass = differential_pressure.abs() * mass_per_pascal;
+        let outflow_mass =
+            (orifice_flow(effective_area, self.pressure, self.differential_pressure())
+                * step_seconds)
+                .max(-maximum_equalising_mass)
+                .min(maximum_equalising_mass);
+
+        let mass = self.pressure.get::<pascal>() * mass_per_pascal
+            + inflow.get::<kilogram_per_second>() * step_seconds
+            - outflow_mass;
+        self.pressure = Pressure::new::<pascal>(mass / mass_per_pascal);
+    }
+
+    fn relieve_through_safety_valves(&mut self) {
+        let maximum =
+            Pressure::new::<psi>(PressurizedCabin::SAFETY_VALVE_OPENING_DIFFERENTIAL_PRESSURE_PSI);
+        let minimum =
+            Pressure::new::<psi>(PressurizedCabin::NEGATIVE_RELIEF_DIFFERENTIAL_PRESSURE_PSI);
+
+        self.safety_valve_is_open = if self.differential_pressure() > maximum {
+            self.pressure = self.ambient_pressure + maximum;
+            true
+        } else if self.differential_pressure() < minimum {
+            self.pressure = self.ambient_pressure + minimum;
+            true
+        } else {
+            false
+        };
+    }
+
+    fn mass_per_pascal() -> f64 {
+        PressurizedCabin::VOLUME_CUBIC_METER
+            / (AIR_GAS_CONSTANT_JOULE_PER_KILOGRAM_KELVIN * CABIN_AIR_TEMPERATURE_KELVIN)
+    }
+
+    pub fn pressure(&self) -> Pressure {
+        self.pressure
+    }
+
+    pub fn altitude(&self) -> Length {
+        altitude_at_pressure(self.pressure)
+    }
+
+    pub fn vertical_speed(&self) -> Velocity {
+        self.vertical_speed
+    }
+
+    pub fn differential_pressure(&self) -> Pressure {
+        self.pressure - self.ambient_pressure
+    }
+
+    pub fn safety_valve_is_open(&self) -> bool {
+        self.safety_valve_is_open
+    }
+}
+impl SimulationElement for PressurizedCabin {
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_length(&self.altitude_id, self.altitude());
+        writer.write_velocity(&self.vertical_speed_id, self.vertical_speed);
+        writer.write_pressure(&self.differential_pressure_id, self.differential_pressure());
+        writer.write_bool(&self.safety_valve_open_id, self.safety_valve_is_open);
+    }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        snapshot.save_f64("PRESS_CABIN_PRESSURE", self.pressure.get::<pascal>());
+        snapshot.save_f64(
+            "PRESS_CABIN_AMBIENT_PRESSURE",
+            self.ambient_pressure.get::<pascal>(),
+        );
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        if let Some(pressure) = snapshot.restore_f64("PRESS_CABIN_PRESSURE") {
+            self.pressure = Pressure::new::<pascal>(pressure);
+        }
+        if let Some(ambient_pressure) = snapshot.restore_f64("PRESS_CABIN_AMBIENT_PRESSURE") {
+            self.ambient_pressure = Pressure::new::<pascal>(ambient_pressure);
+        }
+    }
+}
+
+/// The outflow valve releases air from the cabin. Its motor moves the valve
+/// towards the position commanded by the active cabin pressure controller. When
+/// no controller commands the valve, it remains in its last position.
+pub struct OutflowValve {
+    open_amount_id: QuantityIdentifier<Ratio>,
+    open_amount: Ratio,
+}
+impl OutflowValve {
+    const MAXIMUM_EFFECTIVE_AREA_SQUARE_METER: f64 = 0.03;
+    const SPEED_PERCENT_PER_SECOND: f64 = 10.;
+
+    pub fn new(registry: &mut VariableRegistry) -> OutflowValve {
+        OutflowValve {
+            open_amount_id: registry
+                .register_write("PRESS_OUTFLOW_VALVE_OPEN_PERCENTAGE", VariableUnit::Percent),
+            open_amount: Ratio::new::<percent>(100.),
+        }
+    }
+
+    pub fn update(&mut self, context: &UpdateContext, command: Option<Ratio>) {
+        if let Some(command) = command {
+            let maximum_travel = Ratio::new::<percent>(
+                OutflowValve::SPEED_PERCENT_PER_SECOND * context.delta().as_secs_f64(),
+            );
+            self.open_amount += (command - self.open_amount)
+                .max(-maximum_travel)
+                .min(maximum_travel);
+        }
+    }
+
+    pub fn open_amount(&self) -> Ratio {
+        self.open_amount
+    }
+
+    fn effective_area_square_meter(&self) -> f64 {
+        OutflowValve::MAXIMUM_EFFECTIVE_AREA_SQUARE_METER * self.open_amount.get::<ratio>()
+    }
+}
+impl SimulationElement for OutflowValve {
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write_ratio(&self.open_amount_id, self.open_amount);
+    }
+
+    fn save(&self, snapshot: &mut Snapshot) {
+        snapshot.save_f64(
+            self.open_amount_id.name(),
+            self.open_amount.get::<percent>(),
+        );
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        if let Some(open_amount) = snapshot.restore_f64(self.open_amount_id.name()) {
+            self.open_amount = Ratio::new::<percent>(open_amount);
+        }
+    }
+}
+
+/// The cabin pressure controller determines the target cabin altitude and
+/// commands the outflow valve position required to move the cabin towards it
+/// within the cabin rate limits. On ground the outflow valve is fully opened.
+pub struct CabinPressureController {
+    outflow_valve_command: Option<Ratio>,
+    consumer: PowerConsumer,
+}
+impl CabinPressureController {
+    const MAXIMUM_CABIN_ALTITUDE_FEET: f64 = 8000.;
+    const CABIN_TO_AIRCRAFT_ALTITUDE_RATIO: f64 = 0.2;
+    const MAXIMUM_DIFFERENTIAL_PRESSURE_PSI: f64 = 8.06;
+    const MAXIMUM_CLIMB_RATE_FEET_PER_MINUTE: f64 = 1000.;
+    const MAXIMUM_DESCENT_RATE_FEET_PER_MINUTE: f64 = 750.;
+    const RATE_TIME_CONSTANT_SECONDS: f64 = 10.;
+    const POWER_CONSUMPTION_WATT: f64 = 30.;
+
+    pub fn new(bus_type: ElectricalBusType) -> CabinPressureController {
+        CabinPressureController {
+            outflow_valve_command: None,
+            consumer: PowerConsumer::from(bus_type),
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        context: &UpdateContext,
+        cabin: &PressurizedCabin,
+        inflow: MassRate,
+        is_on_ground: bool,
+    ) {
+        self.outflow_valve_command = if !self.consumer.is_powered() {
+            None
+        } else if is_on_ground {
+            Some(Ratio::new::<percent>(100.))
+        } else {
+            Some(self.outflow_valve_command_in_flight(context, cabin, inflow))
+        };
+
+        self.consumer.demand(Power::new::<watt>(
+            CabinPressureController::POWER_CONSUMPTION_WATT,
+        ));
+    }
+
+    fn outflow_valve_command_in_flight(
+        &self,
+        context: &UpdateContext,
+        cabin: &PressurizedCabin,
+        inflow: MassRate,
+    ) -> Ratio {
+        let target_pressure = self.target_cabin_pressure(context);
+        let target_altitude = altitude_at_pressure(target_pressure);
+
+        let desired_vertical_speed = ((target_altitude - cabin.altitude()).get::<foot>() * 60.
+            / CabinPressureController::RATE_TIME_CONSTANT_SECONDS)
+            .clamp(
+                -CabinPressureController::MAXIMUM_DESCENT_RATE_FEET_PER_MINUTE,
+                CabinPressureController::MAXIMUM_CLIMB_RATE_FEET_PER_MINUTE,
+            );
+        let desired_pressure_rate = (pressure_at_altitude(
+            cabin.altitude() + Length::new::<foot>(desired_vertical_speed / 60.),
+        ) - cabin.pressure())
+        .get::<pascal>();
+
+        let differential_pressure = cabin.differential_pressure();
+        let leak_flow = orifice_flow(
+            PressurizedCabin::LEAK_EFFECTIVE_AREA_SQUARE_METER,
+            cabin.pressure(),
+            differential_pressure,
+        );
+        let required_outflow = inflow.get::<kilogram_per_second>()
+            - leak_flow
+            - desired_pressure_rate * PressurizedCabin::mass_per_pascal();
+
+        let fully_open_flow = orifice_flow(
+            OutflowValve::MAXIMUM_EFFECTIVE_AREA_SQUARE_METER,
+            cabin.pressure(),
+            differential_pressure,
+        );
+        if fully_open_flow > 0. {
+            Ratio::new::<ratio>((required_outflow / fully_open_flow).clamp(0., 1.))
+        } else if required_outflow > 0. {
+            Ratio::new::<percent>(100.)
+        } else {
+            Ratio::new::<percent>(0.)
+        }
+    }
+
+    fn target_cabin_pressure(&self, context: &UpdateContext) -> Pressure {
+        let ambient_pressure = context.ambient_pressure();
+        let scheduled_altitude = Length::new::<foot>(
+            (context.indicated_altitude().get::<foot>()
+                * CabinPressureController::CABIN_TO_AIRCRAFT_ALTITUDE_RATIO)
+                .min(CabinPressureController::MAXIMUM_CABIN_ALTITUDE_FEET),
+        );
+
+        pressure_at_altitude(scheduled_altitude)
+            .min(
+                ambient_pressure
+                    + Pressure::new::<psi>(
+                        CabinPressureController::MAXIMUM_DIFFERENTIAL_PRESSURE_PSI,
+                    ),
+            )
+            .max(ambient_pressure)
+    }
+
+    pub fn is_powered(&self) -> bool {
+        self.consumer.is_powered()
+    }
+
+    /// The outflow valve position commanded by the controller, if any.
+    pub fn outflow_valve_command(&self) -> Option<Ratio> {
+        self.outflow_valve_command
+    }
+}
+impl SimulationElement for CabinPressureController {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        self.consumer.accept(visitor);
+
+        visitor.visit(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        electrical::{consumption::SuppliedPower, Potential, PotentialOrigin},
+        simulation::{test::SimulationTestBed, Aircraft},
+    };
+    use uom::si::electric_potential::volt;
+
+    #[test]
+    fn pressure_at_sea_level_is_standard_pressure() {
+        assert!(
+            (pressure_at_altitude(Length::new::<foot>(0.)).get::<pascal>()
+                - SEA_LEVEL_PRESSURE_PASCAL)
+                .abs()
+                < 0.001
+        );
+    }
+
+    #[test]
+    fn altitude_at_pressure_is_inverse_of_pressure_at_altitude() {
+        let altitude = altitude_at_pressure(pressure_at_altitude(Length::new::<foot>(8000.)));
+
+        assert!((altitude.get::<foot>() - 8000.).abs() < 0.1);
+    }
+
+    struct TestAircraft {
+        controller: CabinPressureController,
+        outflow_valve: OutflowValve,
+        cabin: PressurizedCabin,
+        inflow: MassRate,
+        is_dc_powered: bool,
+    }
+    impl TestAircraft {
+        fn new() -> Self {
+            let mut registry = VariableRegistry::new();
+
+            Self {
+                controller: CabinPressureController::new(ElectricalBusType::DirectCurrent(1)),
+                outflow_valve: OutflowValve::new(&mut registry),
+                cabin: PressurizedCabin::new(&mut registry),
+                inflow: MassRate::new::<kilogram_per_second>(0.8),
+                is_dc_powered: true,
+            }
+        }
+    }
+    impl Aircraft for TestAircraft {
+        fn update_after_power_distribution(&mut self, context: &UpdateContext) {
+            self.controller
+                .update(context, &self.cabin, self.inflow, context.is_on_ground());
+            self.outflow_valve
+                .update(context, self.controller.outflow_valve_command());
+            self.cabin.update(context, self.inflow, &self.outflow_valve);
+        }
+
+        fn get_supplied_power(&mut self) -> SuppliedPower {
+            let mut supplied_power = SuppliedPower::new();
+            if self.is_dc_powered {
+                supplied_power.add(
+                    ElectricalBusType::DirectCurrent(1),
+                    Potential::single(
+                        PotentialOrigin::Battery(10),
+                        ElectricPotential::new::<volt>(28.),
+                    ),
+                );
+            }
+
+            supplied_power
+        }
+    }
+    impl SimulationElement for TestAircraft {
+        fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            self.controller.accept(visitor);
+            self.outflow_valve.accept(visitor);
+            self.cabin.accept(visitor);
+
+            visitor.visit(self);
+        }
+    }
+
+    fn run_at_altitude(
+        aircraft: &mut TestAircraft,
+        test_bed: &mut SimulationTestBed,
+        feet: f64,
+        seconds: u64,
+    ) {
+        test_bed.set_indicated_altitude(Length::new::<foot>(feet));
+        test_bed.set_ambient_pressure(pressure_at_altitude(Length::new::<foot>(feet)));
+        for _ in 0..seconds {
+            test_bed.run_aircraft(aircraft);
+        }
+    }
+
+    fn in_flight_at(aircraft: &mut TestAircraft, feet: f64) -> SimulationTestBed {
+        let mut test_bed = SimulationTestBed::new();
+        run_at_altitude(aircraft, &mut test_bed, feet, 1800);
+
+        test_bed
+    }
+
+    #[test]
+    fn outflow_valve_fully_open_on_ground() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.set_on_ground(true);
+        run_at_altitude(&mut aircraft, &mut test_bed, 0., 60);
+
+        assert_eq!(
+            aircraft.outflow_valve.open_amount(),
+            Ratio::new::<percent>(100.)
+        );
+        assert!(aircraft.cabin.altitude().get::<foot>().abs() < 100.);
+    }
+
+    #[test]
+    fn cabin_equalises_with_the_ambient_pressure_on_ground() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.set_on_ground(true);
+        test_bed.set_indicated_altitude(Length::new::<foot>(0.));
+        test_bed.set_ambient_pressure(pressure_at_altitude(Length::new::<foot>(1000.)));
+        for _ in 0..60 {
+            test_bed.run_aircraft(&mut aircraft);
+        }
+
+        assert!((aircraft.cabin.altitude().get::<foot>() - 1000.).abs() < 100.);
+    }
+
+    #[test]
+    fn cabin_altitude_follows_schedule_in_cruise() {
+        let mut aircraft = TestAircraft::new();
+        in_flight_at(&mut aircraft, 39000.);
+
+        assert!((aircraft.cabin.altitude().get::<foot>() - 8000.).abs() < 100.);
+        assert!(aircraft.cabin.differential_pressure() < Pressure::new::<psi>(8.6));
+        assert!(!aircraft.cabin.safety_valve_is_open());
+        assert!(aircraft.outflow_valve.open_amount() > Ratio::new::<percent>(0.));
+        assert!(aircraft.outflow_valve.open_amount() < Ratio::new::<percent>(100.));
+    }
+
+    #[test]
+    fn cabin_climbs_within_rate_limit() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = in_flight_at(&mut aircraft, 10000.);
+        run_at_altitude(&mut aircraft, &mut test_bed, 20000., 10);
+
+        assert!(aircraft.cabin.vertical_speed() > Velocity::new::<foot_per_minute>(0.));
+        assert!(aircraft.cabin.vertical_speed() < Velocity::new::<foot_per_minute>(1100.));
+    }
+
+    #[test]
+    fn cabin_descends_within_rate_limit() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = in_flight_at(&mut aircraft, 39000.);
+        run_at_altitude(&mut aircraft, &mut test_bed, 10000., 10);
+
+        assert!(aircraft.cabin.vertical_speed() < Velocity::new::<foot_per_minute>(0.));
+        assert!(aircraft.cabin.vertical_speed() > Velocity::new::<foot_per_minute>(-850.));
+    }
+
+    #[test]
+    fn cabin_depressurizes_without_inflow() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = in_flight_at(&mut aircraft, 39000.);
+        aircraft.inflow = MassRate::new::<kilogram_per_second>(0.);
+        run_at_altitude(&mut aircraft, &mut test_bed, 39000., 300);
+
+        assert!(aircraft.cabin.altitude() > Length::new::<foot>(10000.));
+    }
+
+    #[test]
+    fn safety_valve_opens_on_excessive_differential_pressure() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+        run_at_altitude(&mut aircraft, &mut test_bed, 39000., 1);
+
+        assert!(aircraft.cabin.safety_valve_is_open());
+        assert!(aircraft.cabin.differential_pressure() <= Pressure::new::<psi>(8.6 + 0.001));
+    }
+
+    #[test]
+    fn outflow_valve_remains_in_position_when_controller_unpowered() {
+        let mut aircraft = TestAircraft::new();
+        let mut test_bed = in_flight_at(&mut aircraft, 39000.);
+        let open_amount = aircraft.outflow_valve.open_amount();
+
+        aircraft.is_dc_powered = false;
+        test_bed.set_on_ground(true);
+        run_at_altitude(&mut aircraft, &mut test_bed, 39000., 10);
+
+        assert_eq!(aircraft.outflow_valve.open_amount(), open_amount);
+    }
+
+    #[test]
+    fn cabin_writes_its_state() {
+        let mut cabin = PressurizedCabin::new(&mut VariableRegistry::new());
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.run_without_update(&mut cabin);
+
+        assert!(test_bed.contains_key("PRESS_CABIN_ALTITUDE"));
+        assert!(test_bed.contains_key("PRESS_CABIN_VS"));
+        assert!(test_bed.contains_key("PRESS_CABIN_DELTA_PRESSURE"));
+        assert!(test_bed.contains_key("PRESS_SAFETY_VALVE_OPEN"));
+    }
+}
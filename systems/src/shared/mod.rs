@@ -1,8 +1,7 @@
 use crate::{
     electrical::{Potential, PotentialSource},
-    simulation::UpdateContext,
+    simulation::{Snapshot, UpdateContext},
 };
-use num_derive::FromPrimitive;
 use std::time::Duration;
 use uom::si::{f64::*, thermodynamic_temperature::degree_celsius};
 
@@ -20,8 +19,14 @@ pub trait AuxiliaryPowerUnitElectrical: PotentialSource + ApuStartContactorsCont
     fn output_within_normal_parameters(&self) -> bool;
 }
 
-#[derive(FromPrimitive)]
-pub(crate) enum FwcFlightPhase {
+/// The weight-on-wheels signals provided by a Landing Gear Control and Interface Unit.
+pub trait LgciuWeightOnWheels {
+    fn left_and_right_gear_compressed(&self) -> bool;
+}
+
+/// The flight phase as computed by the Flight Warning Computer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FwcFlightPhase {
     ElecPwr = 1,
     FirstEngineStarted = 2,
     FirstEngineTakeOffPower = 3,
@@ -34,6 +39,11 @@ pub(crate) enum FwcFlightPhase {
     EnginesShutdown = 10,
 }
 
+/// Provides the flight phase as computed by the Flight Warning Computer.
+pub trait FwcFlightPhaseProvider {
+    fn flight_phase(&self) -> FwcFlightPhase;
+}
+
 /// The delay logic gate delays the true result of a given expression by the given amount of time.
 /// False results are output immediately.
 pub struct DelayedTrueLogicGate {
@@ -63,6 +73,25 @@ impl DelayedTrueLogicGate {
     pub fn output(&self) -> bool {
         self.expression_result && self.delay <= self.true_duration
     }
+
+    pub fn save(&self, snapshot: &mut Snapshot, name: &str) {
+        snapshot.save_bool(
+            &format!("{}_EXPRESSION_RESULT", name),
+            self.expression_result,
+        );
+        snapshot.save_duration(&format!("{}_TRUE_DURATION", name), self.true_duration);
+    }
+
+    pub fn restore(&mut self, snapshot: &Snapshot, name: &str) {
+        if let Some(expression_result) =
+            snapshot.restore_bool(&format!("{}_EXPRESSION_RESULT", name))
+        {
+            self.expression_result = expression_result;
+        }
+        if let Some(true_duration) = snapshot.restore_duration(&format!("{}_TRUE_DURATION", name)) {
+            self.true_duration = true_duration;
+        }
+    }
 }
 
 /// Given a current and target temperature, takes a coefficient and delta to
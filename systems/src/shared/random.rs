@@ -1,19 +1,66 @@
+use std::cell::Cell;
+
 #[cfg(not(any(target_arch = "wasm32", doc)))]
 use rand::Rng;
 
+/// A seedable pseudo random number generator. Generators created with the same
+/// seed produce the same sequence of numbers, which keeps simulation runs reproducible.
+///
+/// The generator implements the SplitMix64 algorithm, which is fast and has a small
+/// state. It is not suitable for cryptographic purposes.
+///
+/// # Examples
+/// ```rust
+/// # use systems::shared::RandomNumberGenerator;
+/// let first = RandomNumberGenerator::new(42);
+/// let second = RandomNumberGenerator::new(42);
+///
+/// assert_eq!(first.next_u64(), second.next_u64());
+/// ```
+#[derive(Clone, Debug)]
+pub struct RandomNumberGenerator {
+    state: Cell<u64>,
+}
+impl RandomNumberGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: Cell::new(seed),
+        }
+    }
+
+    /// Creates a generator seeded with a number provided by the operating system.
+    pub fn from_entropy() -> Self {
+        Self::new(entropy_seed())
+    }
+
+    pub fn next_u64(&self) -> u64 {
+        let state = self.state.get().wrapping_add(0x9E37_79B9_7F4A_7C15);
+        self.state.set(state);
+
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    pub fn next_u8(&self) -> u8 {
+        (self.next_u64() >> 56) as u8
+    }
+}
+
 #[cfg(not(any(target_arch = "wasm32", doc)))]
-pub fn random_number() -> u8 {
+fn entropy_seed() -> u64 {
     let mut rng = rand::thread_rng();
 
     rng.gen()
 }
 
 #[cfg(any(target_arch = "wasm32", doc))]
-pub fn random_number() -> u8 {
-    let buf = &mut [0, 0, 0, 0];
+fn entropy_seed() -> u64 {
+    let buf = &mut [0; 8];
     unsafe { wasi_random_get(buf.as_mut_ptr(), buf.len()) };
 
-    buf[0]
+    u64::from_le_bytes(*buf)
 }
 
 #[link(wasm_import_module = "wasi_snapshot_preview1")]
@@ -22,3 +69,47 @@ extern "C" {
     #[cfg(any(target_arch = "wasm32", doc))]
     fn wasi_random_get(buf: *mut u8, buf_len: usize) -> u16;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generators_with_the_same_seed_produce_the_same_numbers() {
+        let first = RandomNumberGenerator::new(1234);
+        let second = RandomNumberGenerator::new(1234);
+
+        for _ in 0..100 {
+            assert_eq!(first.next_u64(), second.next_u64());
+        }
+    }
+
+    #[test]
+    fn generators_with_a_different_seed_produce_different_numbers() {
+        let first = RandomNumberGenerator::new(1);
+        let second = RandomNumberGenerator::new(2);
+
+        assert!((0..10).any(|_| first.next_u64() != second.next_u64()));
+    }
+
+    #[test]
+    fn cloned_generator_continues_the_same_sequence() {
+        let generator = RandomNumberGenerator::new(99);
+        generator.next_u64();
+
+        let clone = generator.clone();
+
+        assert_eq!(generator.next_u8(), clone.next_u8());
+    }
+
+    #[test]
+    fn produces_all_byte_values() {
+        let generator = RandomNumberGenerator::new(0);
+        let mut seen = [false; 256];
+        for _ in 0..10_000 {
+            seen[generator.next_u8() as usize] = true;
+        }
+
+        assert!(seen.iter().all(|&seen| seen));
+    }
+}
@@ -0,0 +1,245 @@
+use super::{
+    QuantityIdentifier, SimulationElement, SimulationElementVisitor, SimulatorReader,
+    VariableRegistry, VariableUnit,
+};
+
+/// The types of failure which can be injected into the aircraft's systems.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FailureType {
+    TransformerRectifier(usize),
+    Battery(usize),
+    EngineGenerator(usize),
+    ApuGenerator(usize),
+    FuelPump(usize),
+    HydraulicPump(usize),
+    Lgciu(usize),
+    BleedAirValve(usize),
+}
+impl FailureType {
+    /// A number uniquely identifying the failure. The first two digits
+    /// represent the ATA chapter of the failing component.
+    pub fn id(&self) -> u32 {
+        match self {
+            FailureType::TransformerRectifier(number) => 24000 + *number as u32,
+            FailureType::Battery(number) => 24100 + *number as u32,
+            FailureType::EngineGenerator(number) => 24200 + *number as u32,
+            FailureType::ApuGenerator(number) => 49000 + *number as u32,
+            FailureType::FuelPump(number) => 28000 + *number as u32,
+            FailureType::HydraulicPump(number) => 29000 + *number as u32,
+            FailureType::Lgciu(number) => 32000 + *number as u32,
+            FailureType::BleedAirValve(number) => 36000 + *number as u32,
+        }
+    }
+
+    /// The simulator variable which activates the failure while it is set.
+    pub fn is_active_id(&self) -> String {
+        format!("FAILURE_{}_IS_ACTIVE", self.id())
+    }
+}
+
+/// A failable component within a [`SimulationElement`]. The failure is active for
+/// as long as its simulator variable is set, allowing failures to be activated
+/// and cleared at runtime.
+///
+/// [`SimulationElement`]: ../trait.SimulationElement.html
+pub struct Failure {
+    failure_type: FailureType,
+    is_active_id: QuantityIdentifier<bool>,
+    is_active: bool,
+}
+impl Failure {
+    pub fn new(registry: &mut VariableRegistry, failure_type: FailureType) -> Failure {
+        Failure {
+            failure_type,
+            is_active_id: registry.register_read(&failure_type.is_active_id(), VariableUnit::Bool),
+            is_active: false,
+        }
+    }
+
+    pub fn failure_type(&self) -> FailureType {
+        self.failure_type
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.is_active
+    }
+}
+impl SimulationElement for Failure {
+    fn read(&mut self, reader: &mut SimulatorReader) {
+        self.is_active = reader.read_bool(&self.is_active_id);
+    }
+
+    fn register_failures(&self, registry: &mut FailureRegistry) {
+        registry.register(self.failure_type);
+    }
+}
+
+/// Contains the failures which can occur within an aircraft.
+///
+/// # Examples
+/// ```rust
+/// # use systems::simulation::{Failure, FailureRegistry, FailureType, SimulationElement,
+/// #    SimulationElementVisitor, VariableRegistry};
+/// struct MyTransformerRectifier {
+///     failure: Failure,
+/// }
+/// impl SimulationElement for MyTransformerRectifier {
+///     fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+///         self.failure.accept(visitor);
+///
+///         visitor.visit(self);
+///     }
+/// }
+///
+/// let mut element = MyTransformerRectifier {
+///     failure: Failure::new(&mut VariableRegistry::new(), FailureType::TransformerRectifier(1)),
+/// };
+/// let registry = FailureRegistry::collect_from(&mut element);
+/// assert_eq!(registry.find(24001), Some(FailureType::TransformerRectifier(1)));
+/// ```
+#[derive(Default)]
+pub struct FailureRegistry {
+    failure_types: Vec<FailureType>,
+}
+impl FailureRegistry {
+    pub fn new() -> FailureRegistry {
+        FailureRegistry {
+            failure_types: Vec::new(),
+        }
+    }
+
+    /// Creates a registry containing all failures declared by the given
+    /// element and its children.
+    pub fn collect_from<T: SimulationElement>(element: &mut T) -> FailureRegistry {
+        let mut visitor = FailureRegistrationVisitor {
+            registry: FailureRegistry::new(),
+        };
+        element.accept(&mut visitor);
+
+        visitor.registry
+    }
+
+    pub fn register(&mut self, failure_type: FailureType) {
+        if !self.failure_types.contains(&failure_type) {
+            self.failure_types.push(failure_type);
+            self.failure_types.sort();
+        }
+    }
+
+    pub fn failure_types(&self) -> &[FailureType] {
+        &self.failure_types
+    }
+
+    pub fn find(&self, id: u32) -> Option<FailureType> {
+        self.failure_types
+            .iter()
+            .find(|failure_type| failure_type.id() == id)
+            .copied()
+    }
+}
+
+struct FailureRegistrationVisitor {
+    registry: FailureRegistry,
+}
+impl SimulationElementVisitor for FailureRegistrationVisitor {
+    fn visit<T: SimulationElement>(&mut self, visited: &mut T) {
+        visited.register_failures(&mut self.registry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{test::SimulationTestBed, Aircraft, InMemoryReaderWriter, Simulation};
+
+    struct TestElement {
+        first: Failure,
+        second: Failure,
+    }
+    impl TestElement {
+        fn new(registry: &mut VariableRegistry) -> Self {
+            Self {
+                first: Failure::new(registry, FailureType::EngineGenerator(2)),
+                second: Failure::new(registry, FailureType::TransformerRectifier(1)),
+            }
+        }
+    }
+    impl SimulationElement for TestElement {
+        fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            self.first.accept(visitor);
+            self.second.accept(visitor);
+
+            visitor.visit(self);
+        }
+    }
+    impl Aircraft for TestElement {}
+
+    #[test]
+    fn failure_starts_inactive() {
+        let failure = Failure::new(&mut VariableRegistry::new(), FailureType::Battery(1));
+
+        assert!(!failure.is_active());
+    }
+
+    #[test]
+    fn failure_is_active_while_simulator_variable_is_set() {
+        let mut failure = Failure::new(&mut VariableRegistry::new(), FailureType::Battery(1));
+        let mut test_bed = SimulationTestBed::new();
+
+        test_bed.activate_failure(FailureType::Battery(1));
+        test_bed.run_without_update(&mut failure);
+        assert!(failure.is_active());
+
+        test_bed.deactivate_failure(FailureType::Battery(1));
+        test_bed.run_without_update(&mut failure);
+        assert!(!failure.is_active());
+    }
+
+    #[test]
+    fn activating_another_failure_does_not_affect_failure() {
+        let mut failure = Failure::new(&mut VariableRegistry::new(), FailureType::Battery(1));
+        let mut test_bed = SimulationTestBed::new();
+
+        test_bed.activate_failure(FailureType::Battery(2));
+        test_bed.run_without_update(&mut failure);
+
+        assert!(!failure.is_active());
+    }
+
+    #[test]
+    fn registry_contains_declared_failures_in_order() {
+        let registry =
+            FailureRegistry::collect_from(&mut TestElement::new(&mut VariableRegistry::new()));
+
+        assert_eq!(
+            registry.failure_types(),
+            &[
+                FailureType::TransformerRectifier(1),
+                FailureType::EngineGenerator(2)
+            ]
+        );
+    }
+
+    #[test]
+    fn registry_finds_failure_by_id() {
+        let registry =
+            FailureRegistry::collect_from(&mut TestElement::new(&mut VariableRegistry::new()));
+
+        assert_eq!(registry.find(24202), Some(FailureType::EngineGenerator(2)));
+        assert_eq!(registry.find(24101), None);
+    }
+
+    #[test]
+    fn simulation_lists_the_failures_of_its_aircraft() {
+        let mut aircraft = TestElement::new(&mut VariableRegistry::new());
+        let mut reader_writer = InMemoryReaderWriter::new();
+
+        let failures = Simulation::new(&mut aircraft, &mut reader_writer).failures();
+
+        assert_eq!(failures.failure_types().len(), 2);
+        assert_eq!(
+            failures.find(24001),
+            Some(FailureType::TransformerRectifier(1))
+        );
+    }
+}
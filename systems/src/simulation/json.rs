@@ -0,0 +1,221 @@
+use std::{collections::BTreeMap, iter::Peekable, str::Chars};
+
+/// Quotes the value as a JSON string, escaping quotes, backslashes and control characters.
+pub(crate) fn string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+
+    quoted
+}
+
+/// Formats the value as a JSON number. JSON has no representation for
+/// infinity and NaN, which are therefore written as `null`.
+pub(crate) fn number(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_owned()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct JsonError {
+    reason: String,
+}
+impl JsonError {
+    pub fn new(reason: String) -> Self {
+        Self { reason }
+    }
+
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+/// Parses the subset of JSON written by the simulation: objects containing
+/// strings and numbers.
+pub(crate) struct JsonParser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+impl<'a> JsonParser<'a> {
+    pub fn new(json: &'a str) -> Self {
+        Self {
+            chars: json.chars().peekable(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.chars.peek(), Some(c) if c.is_whitespace()) {
+            self.chars.next();
+        }
+    }
+
+    pub fn expect(&mut self, expected: char) -> Result<(), JsonError> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(JsonError::new(format!(
+                "expected '{}' but found '{}'",
+                expected, c
+            ))),
+            None => Err(JsonError::new(format!(
+                "expected '{}' but found the end",
+                expected
+            ))),
+        }
+    }
+
+    /// Consumes the separator between members. Returns false when the
+    /// closing character is found instead.
+    pub fn next_member(&mut self, closing: char) -> Result<bool, JsonError> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some(',') => Ok(true),
+            Some(c) if c == closing => Ok(false),
+            _ => Err(JsonError::new(format!("expected ',' or '{}'", closing))),
+        }
+    }
+
+    pub fn end(&mut self) -> Result<(), JsonError> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            None => Ok(()),
+            Some(_) => Err(JsonError::new(
+                "unexpected content after the end".to_owned(),
+            )),
+        }
+    }
+
+    pub fn string(&mut self) -> Result<String, JsonError> {
+        self.expect('"')?;
+        let mut value = String::new();
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(value),
+                Some('\\') => value.push(self.escaped()?),
+                Some(c) if c.is_control() => {
+                    return Err(JsonError::new(
+                        "unescaped control character in string".to_owned(),
+                    ))
+                }
+                Some(c) => value.push(c),
+                None => return Err(JsonError::new("unterminated string".to_owned())),
+            }
+        }
+    }
+
+    fn escaped(&mut self) -> Result<char, JsonError> {
+        match self.chars.next() {
+            Some(c @ '"') | Some(c @ '\\') | Some(c @ '/') => Ok(c),
+            Some('b') => Ok('\u{8}'),
+            Some('f') => Ok('\u{c}'),
+            Some('n') => Ok('\n'),
+            Some('r') => Ok('\r'),
+            Some('t') => Ok('\t'),
+            Some('u') => {
+                let code: String = (0..4).filter_map(|_| self.chars.next()).collect();
+                u32::from_str_radix(&code, 16)
+                    .ok()
+                    .and_then(std::char::from_u32)
+                    .ok_or_else(|| JsonError::new(format!("invalid escape '\\u{}'", code)))
+            }
+            _ => Err(JsonError::new("unsupported escape sequence".to_owned())),
+        }
+    }
+
+    /// Parses a number. `null` is parsed as NaN, as the simulation writes
+    /// non-finite numbers as `null`.
+    pub fn number(&mut self) -> Result<f64, JsonError> {
+        self.skip_whitespace();
+        let mut literal = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '+' || c == '.' {
+                literal.push(c);
+                self.chars.next();
+            } else {
+                break;
+            }
+        }
+
+        if literal == "null" {
+            Ok(f64::NAN)
+        } else {
+            literal
+                .parse()
+                .map_err(|_| JsonError::new(format!("invalid number '{}'", literal)))
+        }
+    }
+
+    /// Parses an object of which every member is a number.
+    pub fn numbers(&mut self) -> Result<BTreeMap<String, f64>, JsonError> {
+        let mut values = BTreeMap::new();
+        self.expect('{')?;
+
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&'}') {
+            self.chars.next();
+            return Ok(values);
+        }
+
+        loop {
+            let name = self.string()?;
+            self.expect(':')?;
+            values.insert(name, self.number()?);
+
+            if !self.next_member('}')? {
+                return Ok(values);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_quotes_and_backslashes() {
+        assert_eq!(string("A \"B\" \\ C"), "\"A \\\"B\\\" \\\\ C\"");
+    }
+
+    #[test]
+    fn escapes_control_characters() {
+        assert_eq!(string("A\tB\nC\u{1}"), "\"A\\tB\\nC\\u0001\"");
+    }
+
+    #[test]
+    fn parses_escaped_strings() {
+        let value = "A \"B\" \\ C\t\r\n\u{1}\u{7f} é";
+
+        assert_eq!(
+            JsonParser::new(&string(value)).string(),
+            Ok(value.to_owned())
+        );
+    }
+
+    #[test]
+    fn unescaped_control_character_is_rejected() {
+        assert!(JsonParser::new("\"A\nB\"").string().is_err());
+    }
+
+    #[test]
+    fn non_finite_number_is_null() {
+        assert_eq!(number(f64::INFINITY), "null");
+        assert!(JsonParser::new(&number(f64::NAN))
+            .number()
+            .unwrap()
+            .is_nan());
+    }
+}
@@ -0,0 +1,185 @@
+use super::{json, VariableDirection, VariableRegistry, VariableUnit};
+
+/// A variable read or written by the aircraft system simulation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VariableManifestEntry {
+    name: String,
+    unit: VariableUnit,
+    direction: VariableDirection,
+    owners: Vec<&'static str>,
+}
+impl VariableManifestEntry {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn unit(&self) -> VariableUnit {
+        self.unit
+    }
+
+    /// The combined direction of all registrations of the variable.
+    pub fn direction(&self) -> VariableDirection {
+        self.direction
+    }
+
+    /// The owners which registered the variable, such as the systems reading or writing it.
+    pub fn owners(&self) -> &[&'static str] {
+        &self.owners
+    }
+
+    fn to_json(&self) -> String {
+        format!(
+            "{{\"name\": {}, \"unit\": {}, \"direction\": {}, \"owners\": [{}]}}",
+            json::string(&self.name),
+            json::string(self.unit.name()),
+            json::string(self.direction.name()),
+            self.owners
+                .iter()
+                .map(|owner| json::string(owner))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+/// Contains every variable the aircraft system simulation reads from and writes to
+/// the simulator, together with the owners which registered it. As the manifest is
+/// built from the [`VariableRegistry`], it also contains the variables which are
+/// only read or written under certain conditions.
+///
+/// # Examples
+/// ```rust
+/// # use systems::simulation::{VariableDirection, VariableIdentifier, VariableManifest,
+/// #    VariableRegistry, VariableUnit};
+/// let mut registry = VariableRegistry::new();
+/// registry.with_owner("my_system", |registry| {
+///     registry.register_write::<VariableIdentifier>("MY_LIGHT_IS_ON", VariableUnit::Bool)
+/// });
+///
+/// let manifest = VariableManifest::from(&registry);
+/// let entry = manifest.find("MY_LIGHT_IS_ON").unwrap();
+/// assert_eq!(entry.direction(), VariableDirection::Write);
+/// assert_eq!(entry.owners(), ["my_system"]);
+/// ```
+/// [`VariableRegistry`]: struct.VariableRegistry.html
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VariableManifest {
+    entries: Vec<VariableManifestEntry>,
+}
+impl VariableManifest {
+    /// The entries ordered by variable name.
+    pub fn entries(&self) -> &[VariableManifestEntry] {
+        &self.entries
+    }
+
+    pub fn find(&self, name: &str) -> Option<&VariableManifestEntry> {
+        self.entries
+            .binary_search_by(|entry| entry.name.as_str().cmp(name))
+            .ok()
+            .map(|index| &self.entries[index])
+    }
+
+    /// Writes the manifest as a JSON array containing an object per entry.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("[\n");
+        for (index, entry) in self.entries.iter().enumerate() {
+            json.push_str("  ");
+            json.push_str(&entry.to_json());
+            if index + 1 < self.entries.len() {
+                json.push(',');
+            }
+            json.push('\n');
+        }
+        json.push_str("]\n");
+
+        json
+    }
+}
+impl From<&VariableRegistry> for VariableManifest {
+    fn from(registry: &VariableRegistry) -> Self {
+        let mut entries: Vec<VariableManifestEntry> = registry
+            .definitions()
+            .iter()
+            .map(|definition| VariableManifestEntry {
+                name: definition.name().to_owned(),
+                unit: definition.unit(),
+                direction: definition.direction(),
+                owners: definition.owners().to_vec(),
+            })
+            .collect();
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Self { entries }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{UpdateContext, VariableIdentifier};
+
+    fn manifest() -> VariableManifest {
+        let mut registry = VariableRegistry::new();
+        registry.with_owner("lights", |registry| {
+            registry.register_read::<VariableIdentifier>("TEST_IS_ON", VariableUnit::Bool);
+            registry.register_write::<VariableIdentifier>("TEST_IS_LIT", VariableUnit::Bool);
+        });
+        registry.with_owner("counter", |registry| {
+            registry.register_read::<VariableIdentifier>("TEST_IS_ON", VariableUnit::Bool);
+            registry
+                .register_read_write::<VariableIdentifier>("TEST_COUNTER", VariableUnit::Number);
+        });
+
+        VariableManifest::from(&registry)
+    }
+
+    #[test]
+    fn contains_the_registered_variables() {
+        let manifest = manifest();
+        let entry = manifest.find("TEST_IS_LIT").unwrap();
+
+        assert_eq!(entry.direction(), VariableDirection::Write);
+        assert_eq!(entry.unit(), VariableUnit::Bool);
+        assert_eq!(entry.owners(), ["lights"]);
+    }
+
+    #[test]
+    fn variable_registered_by_multiple_owners_lists_every_owner() {
+        assert_eq!(
+            manifest().find("TEST_IS_ON").unwrap().owners(),
+            ["lights", "counter"]
+        );
+    }
+
+    #[test]
+    fn contains_the_variables_read_by_the_update_context() {
+        let manifest = manifest();
+        let entry = manifest
+            .find(UpdateContext::INDICATED_AIRSPEED_KEY)
+            .unwrap();
+
+        assert_eq!(entry.unit(), VariableUnit::Knots);
+        assert_eq!(entry.owners(), [VariableRegistry::UPDATE_CONTEXT_OWNER]);
+    }
+
+    #[test]
+    fn entries_are_ordered_by_name() {
+        assert!(manifest()
+            .entries()
+            .windows(2)
+            .all(|pair| pair[0].name() < pair[1].name()));
+    }
+
+    #[test]
+    fn empty_manifest_is_an_empty_array() {
+        assert_eq!(VariableManifest::default().to_json(), "[\n]\n");
+    }
+
+    #[test]
+    fn json_contains_an_object_per_entry() {
+        assert!(manifest().to_json().contains(
+            "  {\"name\": \"TEST_IS_ON\", \"unit\": \"Bool\", \"direction\": \"read\", \
+             \"owners\": [\"lights\", \"counter\"]},\n"
+        ));
+    }
+}
@@ -0,0 +1,67 @@
+use super::{
+    from_bool, to_bool, SimulationElement, SimulationToSimulatorVisitor, SimulatorReaderWriter,
+    SimulatorWriter, VariableIdentifier,
+};
+use std::collections::HashMap;
+
+/// A [`SimulatorReaderWriter`] which keeps the variables in memory by their name.
+/// Variables which haven't been written read as 0.0. Use it to run a [`Simulation`]
+/// outside of the simulator, such as for scenarios and tests.
+///
+/// [`SimulatorReaderWriter`]: trait.SimulatorReaderWriter.html
+/// [`Simulation`]: struct.Simulation.html
+#[derive(Clone)]
+pub struct InMemoryReaderWriter {
+    variables: HashMap<String, f64>,
+}
+impl InMemoryReaderWriter {
+    pub fn new() -> Self {
+        Self {
+            variables: HashMap::new(),
+        }
+    }
+
+    /// Writes the state of the given element and its children into the variables,
+    /// just like a [`Simulation`] tick does after updating the element.
+    ///
+    /// [`Simulation`]: struct.Simulation.html
+    pub fn write_state_of<T: SimulationElement>(&mut self, element: &mut T) {
+        let mut writer = SimulatorWriter::new(self);
+        let mut visitor = SimulationToSimulatorVisitor::new(&mut writer);
+        element.accept(&mut visitor);
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.variables.contains_key(name)
+    }
+
+    pub fn write_bool(&mut self, name: &str, value: bool) {
+        self.write_f64(name, from_bool(value));
+    }
+
+    pub fn write_f64(&mut self, name: &str, value: f64) {
+        self.variables.insert(name.to_owned(), value);
+    }
+
+    pub fn read_bool(&self, name: &str) -> bool {
+        to_bool(self.read_f64(name))
+    }
+
+    pub fn read_f64(&self, name: &str) -> f64 {
+        *self.variables.get(name).unwrap_or(&0.)
+    }
+}
+impl SimulatorReaderWriter for InMemoryReaderWriter {
+    fn read(&mut self, identifier: &VariableIdentifier) -> f64 {
+        self.read_f64(identifier.name())
+    }
+
+    fn write(&mut self, identifier: &VariableIdentifier, value: f64) {
+        self.write_f64(identifier.name(), value);
+    }
+}
+impl Default for InMemoryReaderWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
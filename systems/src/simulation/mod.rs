@@ -1,23 +1,73 @@
 use std::time::Duration;
 
+mod failures;
+pub use failures::*;
+
+mod json;
+
+mod manifest;
+pub use manifest::*;
+
+mod memory;
+pub use memory::*;
+
+mod profiling;
+pub use profiling::{DurationPercentiles, ProfileEntry, ProfileReport};
+use profiling::{Profiler, ProfilingVisitor};
+
+mod quantities;
+pub use quantities::VariableQuantity;
+
+mod scenario;
+pub use scenario::*;
+
+mod snapshot;
+pub use snapshot::*;
+
+mod trace;
+pub use trace::*;
+
 mod update_context;
 pub use update_context::*;
 
+mod variables;
+pub use variables::*;
+
+mod write_cache;
+pub use write_cache::WriteStatistics;
+use write_cache::{CachingReaderWriter, WriteCache};
+
 pub mod test;
 
-use crate::electrical::consumption::{
-    ElectricPower, PowerConsumption, PowerConsumptionReport, SuppliedPower,
+use crate::{
+    electrical::consumption::{
+        ElectricPower, PowerConsumption, PowerConsumptionReport, SuppliedPower,
+    },
+    shared::RandomNumberGenerator,
 };
+use uom::si::f64::*;
 
 /// Trait for a type which can read and write simulator data.
 /// Using this trait implementors can abstract away the way the code
 /// interacts with the simulator. This separation of concerns is very important
 /// for keeping the majority of the code unit testable.
 pub trait SimulatorReaderWriter {
-    /// Reads a variable with the given name from the simulator.
-    fn read(&mut self, name: &str) -> f64;
-    /// Writes a variable with the given name to the simulator.
-    fn write(&mut self, name: &str, value: f64);
+    /// Called at the start of every [`Simulation`] tick, before any variable is read.
+    /// Receives the seed from which the tick's random numbers are derived.
+    ///
+    /// [`Simulation`]: struct.Simulation.html
+    fn begin_tick(&mut self, _delta: Duration, _random_seed: u64) {}
+    /// Reads the identified variable from the simulator.
+    fn read(&mut self, identifier: &VariableIdentifier) -> f64;
+    /// Writes the identified variable to the simulator.
+    fn write(&mut self, identifier: &VariableIdentifier, value: f64);
+    /// Called instead of [`write`] when the [`Simulation`] skips writing a value which
+    /// didn't change since it was last written. See [`with_change_only_writes`].
+    ///
+    /// [`write`]: #tymethod.write
+    /// [`Simulation`]: struct.Simulation.html
+    /// [`with_change_only_writes`]: struct.Simulation.html#method.with_change_only_writes
+    fn write_unchanged(&mut self, _identifier: &VariableIdentifier, _value: f64) {}
 }
 
 /// An [`Aircraft`] that can be simulated by the [`Simulation`].
@@ -75,13 +125,14 @@ pub trait SimulationElement {
     /// # Examples
     /// ```rust
     /// # use systems::simulation::{SimulationElement, SimulationElementVisitor,
-    /// #    SimulatorReader, SimulatorWriter};
+    /// #    SimulatorReader, SimulatorWriter, QuantityIdentifier};
     /// struct MySimulationElement {
+    ///     is_on_id: QuantityIdentifier<bool>,
     ///     is_on: bool,
     /// }
     /// impl SimulationElement for MySimulationElement {
     ///     fn read(&mut self, reader: &mut SimulatorReader) {
-    ///         self.is_on = reader.read_bool("MY_SIMULATOR_ELEMENT_IS_ON");
+    ///         self.is_on = reader.read_bool(&self.is_on_id);
     ///     }
     /// }
     /// ```
@@ -91,13 +142,14 @@ pub trait SimulationElement {
     /// # Examples
     /// ```rust
     /// # use systems::simulation::{SimulationElement, SimulationElementVisitor,
-    /// #    SimulatorReader, SimulatorWriter};
+    /// #    SimulatorReader, SimulatorWriter, QuantityIdentifier};
     /// struct MySimulationElement {
+    ///     is_on_id: QuantityIdentifier<bool>,
     ///     is_on: bool,
     /// }
     /// impl SimulationElement for MySimulationElement {
     ///     fn write(&self, writer: &mut SimulatorWriter) {
-    ///        writer.write_bool("MY_SIMULATOR_ELEMENT_IS_ON", self.is_on);
+    ///        writer.write_bool(&self.is_on_id, self.is_on);
     ///     }
     /// }
     /// ```
@@ -131,6 +183,23 @@ pub trait SimulationElement {
         Self: Sized,
     {
     }
+
+    /// Registers the failures which can occur within the element.
+    /// The easiest way to deal with failures is using the [`Failure`] type.
+    ///
+    /// [`Failure`]: struct.Failure.html
+    fn register_failures(&self, _registry: &mut FailureRegistry) {}
+
+    /// Saves the internal state of the element which cannot be derived from the simulator,
+    /// such that it can be restored later on. Refer to [`Snapshot`] for details.
+    ///
+    /// [`Snapshot`]: struct.Snapshot.html
+    fn save(&self, _snapshot: &mut Snapshot) {}
+
+    /// Restores the internal state of the element as saved by [`save`].
+    ///
+    /// [`save`]: #method.save
+    fn restore(&mut self, _snapshot: &Snapshot) {}
 }
 
 /// Trait for visitors that visit the aircraft's system simulation to call
@@ -168,11 +237,12 @@ pub trait SimulationElementVisitor {
 /// Basic usage is as follows:
 /// ```rust
 /// # use std::time::Duration;
-/// # use systems::simulation::{Aircraft, SimulationElement, SimulatorReaderWriter, Simulation, UpdateContext};
+/// # use systems::simulation::{Aircraft, SimulationElement, SimulatorReaderWriter, Simulation,
+/// #    UpdateContext, VariableIdentifier, VariableRegistry};
 /// # use systems::electrical::consumption::SuppliedPower;
 /// # struct MyAircraft {}
 /// # impl MyAircraft {
-/// #     fn new() -> Self {
+/// #     fn new(registry: &mut VariableRegistry) -> Self {
 /// #         Self {}
 /// #     }
 /// # }
@@ -190,11 +260,12 @@ pub trait SimulationElementVisitor {
 /// #     }
 /// # }
 /// # impl SimulatorReaderWriter for MySimulatorReaderWriter {
-/// #     fn read(&mut self, name: &str) -> f64 { 0.0 }
-/// #     fn write(&mut self, name: &str, value: f64) { }
+/// #     fn read(&mut self, identifier: &VariableIdentifier) -> f64 { 0.0 }
+/// #     fn write(&mut self, identifier: &VariableIdentifier, value: f64) { }
 /// # }
 /// // Create the Simulation only once.
-/// let mut aircraft = MyAircraft::new();
+/// let mut registry = VariableRegistry::new();
+/// let mut aircraft = MyAircraft::new(&mut registry);
 /// let mut reader_writer = MySimulatorReaderWriter::new();
 /// let mut simulation = Simulation::new(&mut aircraft, &mut reader_writer);
 /// // For each frame, call the tick function.
@@ -204,37 +275,214 @@ pub trait SimulationElementVisitor {
 pub struct Simulation<'a, T: Aircraft, U: SimulatorReaderWriter> {
     aircraft: &'a mut T,
     simulator_read_writer: &'a mut U,
+    context_variables: UpdateContextVariables,
+    random: RandomNumberGenerator,
+    max_step: Option<Duration>,
+    write_cache: WriteCache,
+    profiler: Profiler,
 }
 impl<'a, T: Aircraft, U: SimulatorReaderWriter> Simulation<'a, T, U> {
+    /// Ticks are split into no more than this number of sub-steps, such that a single
+    /// long tick, e.g. after the simulator was paused, doesn't stall the simulation.
+    /// Beyond this number the sub-steps are larger than the maximum step.
+    pub const MAX_STEPS_PER_TICK: u32 = 50;
+
     pub fn new(aircraft: &'a mut T, simulator_read_writer: &'a mut U) -> Self {
         Simulation {
             aircraft,
             simulator_read_writer,
+            context_variables: UpdateContextVariables::new(),
+            random: RandomNumberGenerator::from_entropy(),
+            max_step: None,
+            write_cache: WriteCache::disabled(),
+            profiler: Profiler::disabled(),
+        }
+    }
+
+    /// Creates a simulation whose random numbers are derived from the given seed.
+    /// Simulations created with the same seed and fed the same simulator
+    /// variables produce identical results.
+    pub fn new_with_random_seed(
+        aircraft: &'a mut T,
+        simulator_read_writer: &'a mut U,
+        random_seed: u64,
+    ) -> Self {
+        Simulation {
+            aircraft,
+            simulator_read_writer,
+            context_variables: UpdateContextVariables::new(),
+            random: RandomNumberGenerator::new(random_seed),
+            max_step: None,
+            write_cache: WriteCache::disabled(),
+            profiler: Profiler::disabled(),
         }
     }
 
+    /// Splits ticks with a delta larger than `max_step` into equally sized sub-steps
+    /// no larger than `max_step`, up to [`MAX_STEPS_PER_TICK`] sub-steps. The simulator
+    /// variables are read once before the first sub-step and written once after the last
+    /// sub-step. This keeps the behaviour of the aircraft independent of the frame rate,
+    /// e.g. when the simulator stutters.
+    ///
+    /// [`MAX_STEPS_PER_TICK`]: #associatedconstant.MAX_STEPS_PER_TICK
+    pub fn with_max_step(mut self, max_step: Duration) -> Self {
+        assert!(
+            max_step > Duration::from_secs(0),
+            "The maximum step must be larger than zero."
+        );
+        self.max_step = Some(max_step);
+
+        self
+    }
+
+    /// Only writes variables to the simulator when their value changed since it was
+    /// last written, as every write can be costly. Variables are written regardless of
+    /// changes once every `refresh_interval`, such that the simulator recovers from
+    /// something else overwriting them. Variables which the aircraft also reads, as
+    /// registered in the given registry, are always written.
+    pub fn with_change_only_writes(
+        mut self,
+        refresh_interval: Duration,
+        registry: &VariableRegistry,
+    ) -> Self {
+        self.write_cache = WriteCache::new(refresh_interval, registry);
+
+        self
+    }
+
+    /// Measures the duration of every phase of each tick, of the updates the aircraft measures
+    /// through the [`UpdateContext`] and the time each type of element spends reading from and
+    /// writing to the simulator. Once every `ticks` ticks, the percentiles of the measured
+    /// durations are aggregated into a [`ProfileReport`].
+    ///
+    /// Profiling requires a clock, which isn't available when running in the simulator.
+    ///
+    /// [`UpdateContext`]: struct.UpdateContext.html
+    /// [`ProfileReport`]: struct.ProfileReport.html
+    pub fn with_profiling(mut self, ticks: usize) -> Self {
+        assert!(
+            ticks > 0,
+            "The number of profiled ticks must be larger than zero."
+        );
+        self.profiler = Profiler::new(Some(ticks));
+
+        self
+    }
+
+    /// The report of the most recently profiled ticks. Returns `None` when profiling
+    /// isn't enabled, or when not enough ticks were profiled yet.
+    pub fn profile_report(&self) -> Option<&ProfileReport> {
+        self.profiler.report()
+    }
+
+    /// The simulator variables the simulation reads from and writes to, such that
+    /// they can be inspected and changed in between ticks.
+    pub fn simulator_reader_writer(&mut self) -> &mut U {
+        self.simulator_read_writer
+    }
+
+    /// The number of writes passed on to the simulator and the number of writes
+    /// skipped as their value didn't change, since the simulation was created.
+    pub fn write_statistics(&self) -> WriteStatistics {
+        self.write_cache.statistics()
+    }
+
     /// Execute a single run of the simulation using the specified `delta` duration
     /// as the amount of time that has passed since the previous run.
     pub fn tick(&mut self, delta: Duration) {
+        let random_seed = self.random.next_u64();
+        self.tick_with_random_seed(delta, random_seed);
+    }
+
+    /// Execute a single run of the simulation, deriving the random numbers
+    /// of the tick from the given seed.
+    fn tick_with_random_seed(&mut self, delta: Duration, random_seed: u64) {
+        let tick_started = self.profiler.start();
+        self.simulator_read_writer.begin_tick(delta, random_seed);
+
+        let started = self.profiler.start();
         let mut reader = SimulatorReader::new(self.simulator_read_writer);
-        let context = UpdateContext::from_reader(&mut reader, delta);
+        let mut context =
+            UpdateContext::from_reader(&mut reader, &self.context_variables, delta, random_seed);
+        if self.profiler.is_enabled() {
+            context.enable_profiling();
+        }
 
         let mut visitor = SimulatorToSimulationVisitor::new(&mut reader);
-        self.aircraft.accept(&mut visitor);
+        if self.profiler.is_enabled() {
+            self.aircraft
+                .accept(&mut ProfilingVisitor::new(visitor, &mut self.profiler));
+        } else {
+            self.aircraft.accept(&mut visitor);
+        }
+        self.profiler.record_phase("read", started);
 
-        self.aircraft.update_before_power_distribution(&context);
+        for step in Self::steps(context.delta(), self.max_step) {
+            context.set_delta(step);
+            let started = self.profiler.start();
+            self.aircraft.update_before_power_distribution(&context);
+            self.profiler
+                .record_phase("update before power distribution", started);
 
-        let mut electric_power = ElectricPower::from(self.aircraft.get_supplied_power(), delta);
-        electric_power.distribute_to(self.aircraft);
+            let started = self.profiler.start();
+            let mut electric_power = ElectricPower::from(self.aircraft.get_supplied_power(), step);
+            electric_power.distribute_to(self.aircraft);
+            self.profiler.record_phase("power distribution", started);
 
-        self.aircraft.update_after_power_distribution(&context);
+            let started = self.profiler.start();
+            self.aircraft.update_after_power_distribution(&context);
+            self.profiler
+                .record_phase("update after power distribution", started);
 
-        electric_power.consume_in(self.aircraft);
-        electric_power.report_consumption_to(self.aircraft);
+            let started = self.profiler.start();
+            electric_power.consume_in(self.aircraft);
+            electric_power.report_consumption_to(self.aircraft);
+            self.profiler.record_phase("power consumption", started);
+        }
+        self.profiler.record_updates(context.profiler_mut());
 
-        let mut writer = SimulatorWriter::new(self.simulator_read_writer);
+        let started = self.profiler.start();
+        self.write_cache.begin_writing(delta);
+        let mut caching_reader_writer =
+            CachingReaderWriter::new(self.simulator_read_writer, &mut self.write_cache);
+        let mut writer = SimulatorWriter::new(&mut caching_reader_writer);
         let mut visitor = SimulationToSimulatorVisitor::new(&mut writer);
-        self.aircraft.accept(&mut visitor);
+        if self.profiler.is_enabled() {
+            self.aircraft
+                .accept(&mut ProfilingVisitor::new(visitor, &mut self.profiler));
+        } else {
+            self.aircraft.accept(&mut visitor);
+        }
+        self.profiler.record_phase("write", started);
+
+        self.profiler.end_tick(tick_started);
+    }
+
+    /// The failures which can be activated within the aircraft.
+    pub fn failures(&mut self) -> FailureRegistry {
+        FailureRegistry::collect_from(self.aircraft)
+    }
+
+    /// Splits the delta into the sub-steps to execute. The last sub-step
+    /// contains any remainder of the division.
+    fn steps(delta: Duration, max_step: Option<Duration>) -> impl Iterator<Item = Duration> {
+        let count = match max_step {
+            Some(max_step) if delta > max_step => delta
+                .as_nanos()
+                .div_ceil(max_step.as_nanos())
+                .min(Self::MAX_STEPS_PER_TICK as u128)
+                as u32,
+            _ => 1,
+        };
+
+        let step = delta / count;
+        (1..=count).map(move |number| {
+            if number == count {
+                delta - step * (count - 1)
+            } else {
+                step
+            }
+        })
     }
 }
 
@@ -271,6 +519,11 @@ impl<'a> SimulationElementVisitor for SimulationToSimulatorVisitor<'a> {
 }
 
 /// Reads data from the simulator into the aircraft system simulation.
+///
+/// Quantities are read in the unit of the variable's definition, e.g. [`read_velocity`]
+/// reads a variable registered in `VariableUnit::Knots` as knots.
+///
+/// [`read_velocity`]: #method.read_velocity
 pub struct SimulatorReader<'a> {
     simulator_read_writer: &'a mut dyn SimulatorReaderWriter,
 }
@@ -281,44 +534,170 @@ impl<'a> SimulatorReader<'a> {
         }
     }
 
-    /// Reads an `f64` from the simulator.
+    /// Reads an `f64` from the simulator. Only variables without a physical
+    /// dimension, such as `VariableUnit::Number`, can be read as an `f64`.
     /// # Examples
     /// ```rust
     /// # use systems::simulation::{SimulationElement, SimulationElementVisitor,
-    /// #    SimulatorReader, SimulatorWriter};
+    /// #    SimulatorReader, SimulatorWriter, QuantityIdentifier};
     /// struct MySimulationElement {
+    ///     n_id: QuantityIdentifier<f64>,
     ///     n: f64,
     /// }
     /// impl SimulationElement for MySimulationElement {
     ///     fn read(&mut self, reader: &mut SimulatorReader) {
-    ///         self.n = reader.read_f64("MY_SIMULATOR_ELEMENT_IS_ON");
+    ///         self.n = reader.read_f64(&self.n_id);
     ///     }
     /// }
     /// ```
-    pub fn read_f64(&mut self, name: &str) -> f64 {
-        self.simulator_read_writer.read(name)
+    pub fn read_f64(&mut self, identifier: &QuantityIdentifier<f64>) -> f64 {
+        self.read_quantity(identifier)
     }
 
     /// Reads a `bool` from the simulator.
     /// # Examples
     /// ```rust
     /// # use systems::simulation::{SimulationElement, SimulationElementVisitor,
-    /// #    SimulatorReader, SimulatorWriter};
+    /// #    SimulatorReader, SimulatorWriter, QuantityIdentifier};
     /// struct MySimulationElement {
+    ///     is_on_id: QuantityIdentifier<bool>,
     ///     is_on: bool,
     /// }
     /// impl SimulationElement for MySimulationElement {
     ///     fn read(&mut self, reader: &mut SimulatorReader) {
-    ///         self.is_on = reader.read_bool("MY_SIMULATOR_ELEMENT_IS_ON");
+    ///         self.is_on = reader.read_bool(&self.is_on_id);
+    ///     }
+    /// }
+    /// ```
+    pub fn read_bool(&mut self, identifier: &QuantityIdentifier<bool>) -> bool {
+        self.read_quantity(identifier)
+    }
+
+    pub fn read_acceleration(
+        &mut self,
+        identifier: &QuantityIdentifier<Acceleration>,
+    ) -> Acceleration {
+        self.read_quantity(identifier)
+    }
+
+    pub fn read_angle(&mut self, identifier: &QuantityIdentifier<Angle>) -> Angle {
+        self.read_quantity(identifier)
+    }
+
+    pub fn read_angular_velocity(
+        &mut self,
+        identifier: &QuantityIdentifier<AngularVelocity>,
+    ) -> AngularVelocity {
+        self.read_quantity(identifier)
+    }
+
+    pub fn read_charge(
+        &mut self,
+        identifier: &QuantityIdentifier<ElectricCharge>,
+    ) -> ElectricCharge {
+        self.read_quantity(identifier)
+    }
+
+    pub fn read_current(
+        &mut self,
+        identifier: &QuantityIdentifier<ElectricCurrent>,
+    ) -> ElectricCurrent {
+        self.read_quantity(identifier)
+    }
+
+    pub fn read_potential(
+        &mut self,
+        identifier: &QuantityIdentifier<ElectricPotential>,
+    ) -> ElectricPotential {
+        self.read_quantity(identifier)
+    }
+
+    pub fn read_frequency(&mut self, identifier: &QuantityIdentifier<Frequency>) -> Frequency {
+        self.read_quantity(identifier)
+    }
+
+    pub fn read_length(&mut self, identifier: &QuantityIdentifier<Length>) -> Length {
+        self.read_quantity(identifier)
+    }
+
+    pub fn read_mass(&mut self, identifier: &QuantityIdentifier<Mass>) -> Mass {
+        self.read_quantity(identifier)
+    }
+
+    pub fn read_mass_rate(&mut self, identifier: &QuantityIdentifier<MassRate>) -> MassRate {
+        self.read_quantity(identifier)
+    }
+
+    pub fn read_pressure(&mut self, identifier: &QuantityIdentifier<Pressure>) -> Pressure {
+        self.read_quantity(identifier)
+    }
+
+    pub fn read_ratio(&mut self, identifier: &QuantityIdentifier<Ratio>) -> Ratio {
+        self.read_quantity(identifier)
+    }
+
+    pub fn read_temperature(
+        &mut self,
+        identifier: &QuantityIdentifier<ThermodynamicTemperature>,
+    ) -> ThermodynamicTemperature {
+        self.read_quantity(identifier)
+    }
+
+    /// Reads a velocity from the simulator.
+    /// # Examples
+    /// ```rust
+    /// # use systems::simulation::{QuantityIdentifier, SimulationElement, SimulatorReader,
+    /// #    VariableRegistry, VariableUnit};
+    /// # use uom::si::f64::*;
+    /// struct MySimulationElement {
+    ///     airspeed_id: QuantityIdentifier<Velocity>,
+    ///     airspeed: Velocity,
+    /// }
+    /// impl MySimulationElement {
+    ///     fn new(registry: &mut VariableRegistry) -> Self {
+    ///         Self {
+    ///             airspeed_id: registry.register_read("MY_AIRSPEED", VariableUnit::Knots),
+    ///             airspeed: Velocity::default(),
+    ///         }
+    ///     }
+    /// }
+    /// impl SimulationElement for MySimulationElement {
+    ///     fn read(&mut self, reader: &mut SimulatorReader) {
+    ///         self.airspeed = reader.read_velocity(&self.airspeed_id);
     ///     }
     /// }
     /// ```
-    pub fn read_bool(&mut self, name: &str) -> bool {
-        to_bool(self.read_f64(name))
+    pub fn read_velocity(&mut self, identifier: &QuantityIdentifier<Velocity>) -> Velocity {
+        self.read_quantity(identifier)
+    }
+
+    pub fn read_volume(&mut self, identifier: &QuantityIdentifier<Volume>) -> Volume {
+        self.read_quantity(identifier)
+    }
+
+    fn read_quantity<T: VariableQuantity>(&mut self, identifier: &QuantityIdentifier<T>) -> T {
+        let value = self.read(identifier);
+        T::from_simulator(identifier.unit(), value).expect(
+            "The registry only creates quantity identifiers for units measuring the quantity.",
+        )
+    }
+
+    fn read(&mut self, identifier: &VariableIdentifier) -> f64 {
+        debug_assert!(
+            identifier.direction().is_read(),
+            "Variable {} isn't registered for reading.",
+            identifier.name()
+        );
+        self.simulator_read_writer.read(identifier)
     }
 }
 
 /// Writes data from the aircraft system simulation into the the simulator.
+///
+/// Quantities are written in the unit of the variable's definition, e.g. [`write_temperature`]
+/// writes a variable registered in `VariableUnit::Celsius` as degrees Celsius.
+///
+/// [`write_temperature`]: #method.write_temperature
 pub struct SimulatorWriter<'a> {
     simulator_read_writer: &'a mut dyn SimulatorReaderWriter,
 }
@@ -329,45 +708,180 @@ impl<'a> SimulatorWriter<'a> {
         }
     }
 
-    /// Write an `f64` to the simulator.
+    /// Write an `f64` to the simulator. Only variables without a physical
+    /// dimension, such as `VariableUnit::Number`, can be written as an `f64`.
     /// # Examples
     /// ```rust
     /// # use systems::simulation::{SimulationElement, SimulationElementVisitor,
-    /// #    SimulatorReader, SimulatorWriter};
+    /// #    SimulatorReader, SimulatorWriter, QuantityIdentifier};
     /// struct MySimulationElement {
+    ///     n_id: QuantityIdentifier<f64>,
     ///     n: f64,
     /// }
     /// impl SimulationElement for MySimulationElement {
     ///     fn write(&self, writer: &mut SimulatorWriter) {
-    ///        writer.write_f64("MY_SIMULATOR_ELEMENT_N", self.n);
+    ///        writer.write_f64(&self.n_id, self.n);
     ///     }
     /// }
     /// ```
-    pub fn write_f64(&mut self, name: &str, value: f64) {
-        self.simulator_read_writer.write(name, value);
+    pub fn write_f64(&mut self, identifier: &QuantityIdentifier<f64>, value: f64) {
+        self.write_quantity(identifier, value);
     }
 
     /// Write a `bool` to the simulator.
     /// # Examples
     /// ```rust
     /// # use systems::simulation::{SimulationElement, SimulationElementVisitor,
-    /// #    SimulatorReader, SimulatorWriter};
+    /// #    SimulatorReader, SimulatorWriter, QuantityIdentifier};
     /// struct MySimulationElement {
+    ///     is_on_id: QuantityIdentifier<bool>,
     ///     is_on: bool,
     /// }
     /// impl SimulationElement for MySimulationElement {
     ///     fn write(&self, writer: &mut SimulatorWriter) {
-    ///        writer.write_bool("MY_SIMULATOR_ELEMENT_IS_ON", self.is_on);
+    ///        writer.write_bool(&self.is_on_id, self.is_on);
+    ///     }
+    /// }
+    /// ```
+    pub fn write_bool(&mut self, identifier: &QuantityIdentifier<bool>, value: bool) {
+        self.write_quantity(identifier, value);
+    }
+
+    pub fn write_acceleration(
+        &mut self,
+        identifier: &QuantityIdentifier<Acceleration>,
+        value: Acceleration,
+    ) {
+        self.write_quantity(identifier, value);
+    }
+
+    pub fn write_angle(&mut self, identifier: &QuantityIdentifier<Angle>, value: Angle) {
+        self.write_quantity(identifier, value);
+    }
+
+    pub fn write_angular_velocity(
+        &mut self,
+        identifier: &QuantityIdentifier<AngularVelocity>,
+        value: AngularVelocity,
+    ) {
+        self.write_quantity(identifier, value);
+    }
+
+    pub fn write_charge(
+        &mut self,
+        identifier: &QuantityIdentifier<ElectricCharge>,
+        value: ElectricCharge,
+    ) {
+        self.write_quantity(identifier, value);
+    }
+
+    pub fn write_current(
+        &mut self,
+        identifier: &QuantityIdentifier<ElectricCurrent>,
+        value: ElectricCurrent,
+    ) {
+        self.write_quantity(identifier, value);
+    }
+
+    /// Write an electric potential to the simulator.
+    /// # Examples
+    /// ```rust
+    /// # use systems::simulation::{QuantityIdentifier, SimulationElement, SimulatorWriter,
+    /// #    VariableRegistry, VariableUnit};
+    /// # use uom::si::f64::*;
+    /// struct MySimulationElement {
+    ///     potential_id: QuantityIdentifier<ElectricPotential>,
+    ///     potential: ElectricPotential,
+    /// }
+    /// impl MySimulationElement {
+    ///     fn new(registry: &mut VariableRegistry) -> Self {
+    ///         Self {
+    ///             potential_id: registry.register_write("MY_POTENTIAL", VariableUnit::Volts),
+    ///             potential: ElectricPotential::default(),
+    ///         }
+    ///     }
+    /// }
+    /// impl SimulationElement for MySimulationElement {
+    ///     fn write(&self, writer: &mut SimulatorWriter) {
+    ///        writer.write_potential(&self.potential_id, self.potential);
     ///     }
     /// }
     /// ```
-    pub fn write_bool(&mut self, name: &str, value: bool) {
-        self.simulator_read_writer.write(name, from_bool(value));
+    pub fn write_potential(
+        &mut self,
+        identifier: &QuantityIdentifier<ElectricPotential>,
+        value: ElectricPotential,
+    ) {
+        self.write_quantity(identifier, value);
+    }
+
+    pub fn write_frequency(
+        &mut self,
+        identifier: &QuantityIdentifier<Frequency>,
+        value: Frequency,
+    ) {
+        self.write_quantity(identifier, value);
+    }
+
+    pub fn write_length(&mut self, identifier: &QuantityIdentifier<Length>, value: Length) {
+        self.write_quantity(identifier, value);
+    }
+
+    pub fn write_mass(&mut self, identifier: &QuantityIdentifier<Mass>, value: Mass) {
+        self.write_quantity(identifier, value);
+    }
+
+    pub fn write_mass_rate(&mut self, identifier: &QuantityIdentifier<MassRate>, value: MassRate) {
+        self.write_quantity(identifier, value);
+    }
+
+    pub fn write_pressure(&mut self, identifier: &QuantityIdentifier<Pressure>, value: Pressure) {
+        self.write_quantity(identifier, value);
+    }
+
+    pub fn write_ratio(&mut self, identifier: &QuantityIdentifier<Ratio>, value: Ratio) {
+        self.write_quantity(identifier, value);
+    }
+
+    pub fn write_temperature(
+        &mut self,
+        identifier: &QuantityIdentifier<ThermodynamicTemperature>,
+        value: ThermodynamicTemperature,
+    ) {
+        self.write_quantity(identifier, value);
+    }
+
+    pub fn write_velocity(&mut self, identifier: &QuantityIdentifier<Velocity>, value: Velocity) {
+        self.write_quantity(identifier, value);
+    }
+
+    pub fn write_volume(&mut self, identifier: &QuantityIdentifier<Volume>, value: Volume) {
+        self.write_quantity(identifier, value);
+    }
+
+    fn write_quantity<T: VariableQuantity>(
+        &mut self,
+        identifier: &QuantityIdentifier<T>,
+        value: T,
+    ) {
+        let value = value.to_simulator(identifier.unit()).expect(
+            "The registry only creates quantity identifiers for units measuring the quantity.",
+        );
+        self.write(identifier, value);
+    }
+
+    fn write(&mut self, identifier: &VariableIdentifier, value: f64) {
+        debug_assert!(
+            identifier.direction().is_written(),
+            "Variable {} isn't registered for writing.",
+            identifier.name()
+        );
+        self.simulator_read_writer.write(identifier, value);
     }
 }
 
 /// Converts a given `f64` representing a boolean value in the simulator into an actual `bool` value.
-fn to_bool(value: f64) -> bool {
+pub(crate) fn to_bool(value: f64) -> bool {
     (value - 1.).abs() < f64::EPSILON
 }
 
@@ -379,3 +893,405 @@ pub(crate) fn from_bool(value: bool) -> f64 {
         0.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{test::SimulationTestBed, InMemoryReaderWriter};
+    use ntest::assert_about_eq;
+    use std::any::type_name;
+    use uom::si::{
+        acceleration::foot_per_second_squared,
+        angle::degree,
+        length::foot,
+        pressure::inch_of_mercury,
+        velocity::{foot_per_minute, knot},
+    };
+
+    struct TestAircraft {
+        steps_id: QuantityIdentifier<f64>,
+        reads: usize,
+        deltas: Vec<Duration>,
+        powered_deltas: Vec<Duration>,
+        random_numbers: Vec<u8>,
+    }
+    impl TestAircraft {
+        fn new() -> Self {
+            Self::with_registry(&mut VariableRegistry::new())
+        }
+
+        fn with_registry(registry: &mut VariableRegistry) -> Self {
+            Self {
+                steps_id: registry.register_write("TEST_STEPS", VariableUnit::Number),
+                reads: 0,
+                deltas: Vec::new(),
+                powered_deltas: Vec::new(),
+                random_numbers: Vec::new(),
+            }
+        }
+    }
+    impl Aircraft for TestAircraft {
+        fn update_before_power_distribution(&mut self, context: &UpdateContext) {
+            context.profile("test", || {
+                self.deltas.push(context.delta());
+                self.random_numbers.push(context.random_number());
+            });
+        }
+
+        fn update_after_power_distribution(&mut self, context: &UpdateContext) {
+            self.powered_deltas.push(context.delta());
+        }
+    }
+    impl SimulationElement for TestAircraft {
+        fn read(&mut self, _: &mut SimulatorReader) {
+            self.reads += 1;
+        }
+
+        fn write(&self, writer: &mut SimulatorWriter) {
+            writer.write_f64(&self.steps_id, self.deltas.len() as f64);
+        }
+    }
+
+    fn tick(delta: Duration, max_step: Option<Duration>) -> (TestAircraft, InMemoryReaderWriter) {
+        tick_at_rate(delta, max_step, 1.)
+    }
+
+    fn tick_at_rate(
+        delta: Duration,
+        max_step: Option<Duration>,
+        simulation_rate: f64,
+    ) -> (TestAircraft, InMemoryReaderWriter) {
+        let mut aircraft = TestAircraft::new();
+        let mut reader_writer = InMemoryReaderWriter::new();
+        reader_writer.write_f64(UpdateContext::SIMULATION_RATE_KEY, simulation_rate);
+        let mut simulation = Simulation::new_with_random_seed(&mut aircraft, &mut reader_writer, 0);
+        if let Some(max_step) = max_step {
+            simulation = simulation.with_max_step(max_step);
+        }
+
+        simulation.tick(delta);
+
+        (aircraft, reader_writer)
+    }
+
+    #[test]
+    fn without_max_step_ticks_in_a_single_step() {
+        let (aircraft, _) = tick(Duration::from_secs(2), None);
+
+        assert_eq!(aircraft.deltas, vec![Duration::from_secs(2)]);
+    }
+
+    #[test]
+    fn delta_within_max_step_ticks_in_a_single_step() {
+        let (aircraft, _) = tick(Duration::from_millis(100), Some(Duration::from_millis(100)));
+
+        assert_eq!(aircraft.deltas, vec![Duration::from_millis(100)]);
+    }
+
+    #[test]
+    fn delta_beyond_max_step_is_split_into_equal_steps() {
+        let (aircraft, _) = tick(Duration::from_secs(2), Some(Duration::from_millis(300)));
+
+        assert_eq!(aircraft.deltas.len(), 7);
+        assert!(aircraft
+            .deltas
+            .iter()
+            .all(|delta| *delta <= Duration::from_millis(300)));
+        assert_eq!(aircraft.deltas, aircraft.powered_deltas);
+    }
+
+    #[test]
+    fn steps_add_up_to_delta() {
+        let (aircraft, _) = tick(
+            Duration::from_nanos(1_000_000_001),
+            Some(Duration::from_millis(300)),
+        );
+
+        assert_eq!(
+            aircraft.deltas.iter().sum::<Duration>(),
+            Duration::from_nanos(1_000_000_001)
+        );
+    }
+
+    #[test]
+    fn number_of_steps_is_capped() {
+        let (aircraft, _) = tick(Duration::from_secs(3600), Some(Duration::from_millis(1)));
+
+        assert_eq!(
+            aircraft.deltas.len(),
+            Simulation::<TestAircraft, InMemoryReaderWriter>::MAX_STEPS_PER_TICK as usize
+        );
+        assert_eq!(
+            aircraft.deltas.iter().sum::<Duration>(),
+            Duration::from_secs(3600)
+        );
+    }
+
+    #[test]
+    fn random_numbers_do_not_depend_on_how_a_tick_is_split() {
+        let (without_steps, _) = tick(Duration::from_millis(100), None);
+        let (two_steps, _) = tick(Duration::from_millis(100), Some(Duration::from_millis(50)));
+        let (four_steps, _) = tick(Duration::from_millis(100), Some(Duration::from_millis(25)));
+
+        assert_eq!(
+            without_steps.random_numbers[..],
+            two_steps.random_numbers[..1]
+        );
+        assert_eq!(two_steps.random_numbers[..], four_steps.random_numbers[..2]);
+    }
+
+    #[test]
+    fn reads_once_and_writes_once_after_the_last_step() {
+        let (aircraft, reader_writer) =
+            tick(Duration::from_secs(2), Some(Duration::from_millis(500)));
+
+        assert_eq!(aircraft.reads, 1);
+        assert_eq!(reader_writer.read_f64("TEST_STEPS"), 4.);
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_max_step_is_rejected() {
+        tick(Duration::from_secs(1), Some(Duration::from_secs(0)));
+    }
+
+    #[test]
+    fn delta_is_scaled_by_the_simulation_rate() {
+        let (aircraft, _) = tick_at_rate(Duration::from_millis(100), None, 4.);
+
+        assert_eq!(aircraft.deltas, vec![Duration::from_millis(400)]);
+    }
+
+    #[test]
+    fn missing_simulation_rate_is_treated_as_real_time() {
+        let (aircraft, _) = tick_at_rate(Duration::from_millis(100), None, 0.);
+
+        assert_eq!(aircraft.deltas, vec![Duration::from_millis(100)]);
+    }
+
+    #[test]
+    fn increased_simulation_rate_is_split_into_steps_no_larger_than_max_step() {
+        let (aircraft, _) = tick_at_rate(
+            Duration::from_millis(100),
+            Some(Duration::from_millis(100)),
+            4.,
+        );
+
+        assert_eq!(aircraft.deltas, vec![Duration::from_millis(100); 4]);
+    }
+
+    #[test]
+    fn write_statistics_count_the_writes_of_every_tick() {
+        let mut registry = VariableRegistry::new();
+        let mut aircraft = TestAircraft::with_registry(&mut registry);
+        let mut reader_writer = InMemoryReaderWriter::new();
+        let mut simulation = Simulation::new(&mut aircraft, &mut reader_writer)
+            .with_change_only_writes(Duration::from_secs(1), &registry);
+
+        simulation.tick(Duration::from_millis(100));
+        simulation.tick(Duration::from_millis(100));
+
+        // The number of steps written by the aircraft changes every tick.
+        assert_eq!(simulation.write_statistics().written(), 2);
+        assert_eq!(simulation.write_statistics().skipped(), 0);
+    }
+
+    #[test]
+    fn profiling_reports_phases_updates_and_elements_once_every_given_number_of_ticks() {
+        let mut aircraft = TestAircraft::new();
+        let mut reader_writer = InMemoryReaderWriter::new();
+        let mut simulation = Simulation::new(&mut aircraft, &mut reader_writer)
+            .with_max_step(Duration::from_millis(50))
+            .with_profiling(3);
+
+        simulation.tick(Duration::from_millis(100));
+        simulation.tick(Duration::from_millis(100));
+        assert!(simulation.profile_report().is_none());
+
+        simulation.tick(Duration::from_millis(100));
+        let report = simulation.profile_report().unwrap();
+        assert_eq!(report.ticks(), 3);
+        assert_eq!(
+            report
+                .phases()
+                .iter()
+                .map(|phase| phase.name())
+                .collect::<Vec<_>>(),
+            vec![
+                "read",
+                "update before power distribution",
+                "power distribution",
+                "update after power distribution",
+                "power consumption",
+                "write",
+                "tick"
+            ]
+        );
+        assert!(report.update("test").is_some());
+        assert!(report.element(type_name::<TestAircraft>()).is_some());
+    }
+
+    #[test]
+    fn without_profiling_there_is_no_report() {
+        let mut aircraft = TestAircraft::new();
+        let mut reader_writer = InMemoryReaderWriter::new();
+        let mut simulation = Simulation::new(&mut aircraft, &mut reader_writer);
+        simulation.tick(Duration::from_millis(100));
+
+        assert!(simulation.profile_report().is_none());
+    }
+
+    #[derive(Default)]
+    struct ContextRecorder {
+        values: Vec<f64>,
+    }
+    impl SimulationElement for ContextRecorder {}
+
+    fn record_context(
+        test_bed: &mut SimulationTestBed,
+        record_fn: fn(&UpdateContext) -> Vec<f64>,
+    ) -> Vec<f64> {
+        let mut recorder = ContextRecorder::default();
+        test_bed.run(&mut recorder, |recorder, context| {
+            recorder.values = record_fn(context)
+        });
+
+        recorder.values
+    }
+
+    #[test]
+    fn context_provides_flight_data_set_on_the_test_bed() {
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.set_true_airspeed(Velocity::new::<knot>(450.));
+        test_bed.set_mach_number(0.78);
+        test_bed.set_ground_speed(Velocity::new::<knot>(480.));
+        test_bed.set_pressure_altitude(Length::new::<foot>(37000.));
+        test_bed.set_radio_altitude(Length::new::<foot>(2500.));
+        test_bed.set_ambient_pressure(Pressure::new::<inch_of_mercury>(6.47));
+        test_bed.set_load_factor(1.3);
+
+        let values = record_context(&mut test_bed, |context| {
+            vec![
+                context.true_airspeed().get::<knot>(),
+                context.mach_number(),
+                context.ground_speed().get::<knot>(),
+                context.pressure_altitude().get::<foot>(),
+                context.radio_altitude().get::<foot>(),
+                context.ambient_pressure().get::<inch_of_mercury>(),
+                context.load_factor(),
+            ]
+        });
+
+        assert_eq!(values, vec![450., 0.78, 480., 37000., 2500., 6.47, 1.3]);
+    }
+
+    #[test]
+    fn context_provides_accelerations_set_on_the_test_bed() {
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.set_accelerations(
+            Acceleration::new::<foot_per_second_squared>(3.),
+            Acceleration::new::<foot_per_second_squared>(-1.),
+            Acceleration::new::<foot_per_second_squared>(9.),
+        );
+
+        let values = record_context(&mut test_bed, |context| {
+            vec![
+                context
+                    .longitudinal_acceleration()
+                    .get::<foot_per_second_squared>(),
+                context
+                    .lateral_acceleration()
+                    .get::<foot_per_second_squared>(),
+                context
+                    .vertical_acceleration()
+                    .get::<foot_per_second_squared>(),
+            ]
+        });
+
+        assert_eq!(values, vec![3., -1., 9.]);
+    }
+
+    #[test]
+    fn nose_up_and_right_wing_down_are_positive_pitch_and_bank() {
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.write_f64(UpdateContext::PITCH_KEY, -5.);
+        test_bed.write_f64(UpdateContext::BANK_KEY, -20.);
+
+        let values = record_context(&mut test_bed, |context| {
+            vec![
+                context.pitch().get::<degree>(),
+                context.bank().get::<degree>(),
+            ]
+        });
+
+        assert_about_eq!(values[0], 5.);
+        assert_about_eq!(values[1], 20.);
+    }
+
+    #[test]
+    fn pitch_and_bank_set_on_the_test_bed_are_provided_unchanged() {
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.set_pitch(Angle::new::<degree>(15.));
+        test_bed.set_bank(Angle::new::<degree>(-25.));
+
+        let values = record_context(&mut test_bed, |context| {
+            vec![
+                context.pitch().get::<degree>(),
+                context.bank().get::<degree>(),
+            ]
+        });
+
+        assert_about_eq!(values[0], 15.);
+        assert_about_eq!(values[1], -25.);
+    }
+
+    #[test]
+    fn reads_a_quantity_in_the_unit_of_the_variable() {
+        let identifier =
+            VariableRegistry::new().register_read("TEST_SPEED", VariableUnit::FeetPerMinute);
+        let mut reader_writer = InMemoryReaderWriter::new();
+        reader_writer.write_f64("TEST_SPEED", 600.);
+
+        let speed = SimulatorReader::new(&mut reader_writer).read_velocity(&identifier);
+
+        assert_about_eq!(
+            speed.get::<knot>(),
+            Velocity::new::<foot_per_minute>(600.).get::<knot>()
+        );
+    }
+
+    #[test]
+    fn writes_a_quantity_in_the_unit_of_the_variable() {
+        let identifier =
+            VariableRegistry::new().register_write("TEST_PRESSURE", VariableUnit::InchesOfMercury);
+        let mut reader_writer = InMemoryReaderWriter::new();
+
+        SimulatorWriter::new(&mut reader_writer)
+            .write_pressure(&identifier, Pressure::new::<inch_of_mercury>(29.92));
+
+        assert_about_eq!(reader_writer.read_f64("TEST_PRESSURE"), 29.92);
+    }
+
+    #[test]
+    #[should_panic]
+    fn registering_a_quantity_for_reading_in_a_unit_of_another_quantity_panics() {
+        let _: QuantityIdentifier<Mass> =
+            VariableRegistry::new().register_read("TEST_SPEED", VariableUnit::Knots);
+    }
+
+    #[test]
+    #[should_panic]
+    fn registering_a_quantity_for_writing_in_a_unit_of_another_quantity_panics() {
+        let _: QuantityIdentifier<ElectricPotential> =
+            VariableRegistry::new().register_write("TEST_TEMPERATURE", VariableUnit::Celsius);
+    }
+
+    #[test]
+    #[should_panic]
+    fn reading_a_variable_with_a_physical_dimension_as_f64_panics() {
+        let identifier = VariableRegistry::new().register_read("TEST_SPEED", VariableUnit::Knots);
+        let mut reader_writer = InMemoryReaderWriter::new();
+
+        SimulatorReader::new(&mut reader_writer).read_f64(&identifier);
+    }
+}
@@ -0,0 +1,509 @@
+use super::{SimulationElement, SimulationElementVisitor};
+use std::{
+    any::type_name,
+    cell::RefCell,
+    collections::BTreeMap,
+    fmt,
+    time::{Duration, Instant},
+};
+
+/// Percentiles of the durations measured for a phase or element over the profiled ticks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DurationPercentiles {
+    p50: Duration,
+    p90: Duration,
+    p99: Duration,
+    max: Duration,
+}
+impl DurationPercentiles {
+    fn from(mut durations: Vec<Duration>) -> Self {
+        durations.sort_unstable();
+
+        Self {
+            p50: percentile(&durations, 50),
+            p90: percentile(&durations, 90),
+            p99: percentile(&durations, 99),
+            max: durations.last().copied().unwrap_or_default(),
+        }
+    }
+
+    pub fn p50(&self) -> Duration {
+        self.p50
+    }
+
+    pub fn p90(&self) -> Duration {
+        self.p90
+    }
+
+    pub fn p99(&self) -> Duration {
+        self.p99
+    }
+
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+}
+
+/// The nearest rank percentile of the sorted durations.
+fn percentile(sorted: &[Duration], percentile: usize) -> Duration {
+    if sorted.is_empty() {
+        return Duration::default();
+    }
+
+    let rank = (percentile * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/// The percentiles of a phase of the simulation tick, of an update or of an element.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProfileEntry {
+    name: String,
+    percentiles: DurationPercentiles,
+}
+impl ProfileEntry {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn percentiles(&self) -> DurationPercentiles {
+        self.percentiles
+    }
+}
+
+/// The durations of the phases of the simulation tick, of the aircraft's updates and
+/// of its elements, aggregated over a number of ticks.
+///
+/// Update durations are the time spent in the updates the aircraft measures through the
+/// [`UpdateContext`], summed over the sub-steps of a tick. Element durations are the time
+/// an element spends reading from and writing to the simulator within a tick, summed over
+/// all elements of the same type.
+///
+/// [`UpdateContext`]: struct.UpdateContext.html
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProfileReport {
+    ticks: usize,
+    phases: Vec<ProfileEntry>,
+    updates: Vec<ProfileEntry>,
+    elements: Vec<ProfileEntry>,
+}
+impl ProfileReport {
+    /// The number of ticks over which the percentiles are aggregated.
+    pub fn ticks(&self) -> usize {
+        self.ticks
+    }
+
+    /// The phases in the order in which they are executed, followed by the entire tick.
+    pub fn phases(&self) -> &[ProfileEntry] {
+        &self.phases
+    }
+
+    /// The updates ordered by their name.
+    pub fn updates(&self) -> &[ProfileEntry] {
+        &self.updates
+    }
+
+    /// The elements ordered by their type name.
+    pub fn elements(&self) -> &[ProfileEntry] {
+        &self.elements
+    }
+
+    pub fn phase(&self, name: &str) -> Option<&ProfileEntry> {
+        self.phases.iter().find(|entry| entry.name == name)
+    }
+
+    pub fn update(&self, name: &str) -> Option<&ProfileEntry> {
+        self.updates.iter().find(|entry| entry.name == name)
+    }
+
+    pub fn element(&self, name: &str) -> Option<&ProfileEntry> {
+        self.elements.iter().find(|entry| entry.name == name)
+    }
+}
+impl fmt::Display for ProfileReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self
+            .phases
+            .iter()
+            .chain(self.updates.iter())
+            .chain(self.elements.iter())
+            .map(|entry| entry.name.len())
+            .max()
+            .unwrap_or_default();
+
+        writeln!(
+            f,
+            "profile of {} ticks (durations in microseconds)",
+            self.ticks
+        )?;
+        for (title, entries) in &[
+            ("phase", &self.phases),
+            ("update", &self.updates),
+            ("element", &self.elements),
+        ] {
+            writeln!(
+                f,
+                "{:<width$} {:>10} {:>10} {:>10} {:>10}",
+                title,
+                "p50",
+                "p90",
+                "p99",
+                "max",
+                width = width
+            )?;
+            for entry in entries.iter() {
+                let percentiles = entry.percentiles;
+                writeln!(
+                    f,
+                    "{:<width$} {:>10.1} {:>10.1} {:>10.1} {:>10.1}",
+                    entry.name,
+                    micros(percentiles.p50),
+                    micros(percentiles.p90),
+                    micros(percentiles.p99),
+                    micros(percentiles.max),
+                    width = width
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn micros(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1_000_000.
+}
+
+/// Measures the durations of the phases of the simulation tick, of the aircraft's updates
+/// and of its elements. A report is created once every given number of ticks.
+///
+/// A disabled profiler doesn't measure anything, as the clock isn't available on
+/// every platform the simulation runs on.
+pub(super) struct Profiler {
+    ticks_per_report: Option<usize>,
+    ticks: usize,
+    tick_phases: Vec<(&'static str, Duration)>,
+    tick_updates: BTreeMap<&'static str, Duration>,
+    tick_elements: BTreeMap<&'static str, Duration>,
+    phases: Vec<(&'static str, Vec<Duration>)>,
+    updates: BTreeMap<&'static str, Vec<Duration>>,
+    elements: BTreeMap<&'static str, Vec<Duration>>,
+    report: Option<ProfileReport>,
+}
+impl Profiler {
+    pub const TICK: &'static str = "tick";
+
+    pub fn disabled() -> Self {
+        Self::new(None)
+    }
+
+    pub fn new(ticks_per_report: Option<usize>) -> Self {
+        Self {
+            ticks_per_report,
+            ticks: 0,
+            tick_phases: Vec::new(),
+            tick_updates: BTreeMap::new(),
+            tick_elements: BTreeMap::new(),
+            phases: Vec::new(),
+            updates: BTreeMap::new(),
+            elements: BTreeMap::new(),
+            report: None,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.ticks_per_report.is_some()
+    }
+
+    /// Starts a measurement. Returns `None` when the profiler is disabled.
+    pub fn start(&self) -> Option<Instant> {
+        if self.is_enabled() {
+            Some(Instant::now())
+        } else {
+            None
+        }
+    }
+
+    /// Adds the time passed since the start of the measurement to the phase.
+    /// Phases executed multiple times within a tick are summed.
+    pub fn record_phase(&mut self, name: &'static str, started: Option<Instant>) {
+        if let Some(started) = started {
+            self.add_phase(name, started.elapsed());
+        }
+    }
+
+    fn add_phase(&mut self, name: &'static str, elapsed: Duration) {
+        match self
+            .tick_phases
+            .iter_mut()
+            .find(|(phase, _)| *phase == name)
+        {
+            Some((_, duration)) => *duration += elapsed,
+            None => self.tick_phases.push((name, elapsed)),
+        }
+    }
+
+    /// Adds the durations measured by the [`UpdateProfiler`] to the tick's updates.
+    ///
+    /// [`UpdateProfiler`]: struct.UpdateProfiler.html
+    pub fn record_updates(&mut self, updates: &mut UpdateProfiler) {
+        for (name, elapsed) in std::mem::take(updates.durations.get_mut()) {
+            *self.tick_updates.entry(name).or_default() += elapsed;
+        }
+    }
+
+    fn record_element(&mut self, name: &'static str, elapsed: Duration) {
+        *self.tick_elements.entry(name).or_default() += elapsed;
+    }
+
+    /// Ends the tick which started at the given measurement,
+    /// creating a report when enough ticks were profiled.
+    pub fn end_tick(&mut self, started: Option<Instant>) {
+        self.record_phase(Profiler::TICK, started);
+        let ticks_per_report = match self.ticks_per_report {
+            Some(ticks_per_report) => ticks_per_report,
+            None => return,
+        };
+
+        for (name, duration) in self.tick_phases.drain(..) {
+            match self.phases.iter_mut().find(|(phase, _)| *phase == name) {
+                Some((_, durations)) => durations.push(duration),
+                None => self.phases.push((name, vec![duration])),
+            }
+        }
+        for (name, duration) in std::mem::take(&mut self.tick_updates) {
+            self.updates.entry(name).or_default().push(duration);
+        }
+        for (name, duration) in std::mem::take(&mut self.tick_elements) {
+            self.elements.entry(name).or_default().push(duration);
+        }
+
+        self.ticks += 1;
+        if self.ticks >= ticks_per_report {
+            self.report = Some(self.create_report());
+        }
+    }
+
+    /// The report of the most recently profiled ticks.
+    pub fn report(&self) -> Option<&ProfileReport> {
+        self.report.as_ref()
+    }
+
+    fn create_report(&mut self) -> ProfileReport {
+        // An update or element which isn't measured in every tick took no time in the other ticks.
+        let ticks = self.ticks;
+        let entry = |name: &str, mut durations: Vec<Duration>| {
+            durations.resize(ticks, Duration::default());
+            ProfileEntry {
+                name: name.to_owned(),
+                percentiles: DurationPercentiles::from(durations),
+            }
+        };
+
+        let report = ProfileReport {
+            ticks,
+            phases: self
+                .phases
+                .drain(..)
+                .map(|(name, durations)| entry(name, durations))
+                .collect(),
+            updates: std::mem::take(&mut self.updates)
+                .into_iter()
+                .map(|(name, durations)| entry(name, durations))
+                .collect(),
+            elements: std::mem::take(&mut self.elements)
+                .into_iter()
+                .map(|(name, durations)| entry(name, durations))
+                .collect(),
+        };
+        self.ticks = 0;
+
+        report
+    }
+}
+
+/// Measures the durations of the updates of the aircraft's systems on behalf of the
+/// [`UpdateContext`]. As the context is shared by reference, the durations are kept in a cell.
+///
+/// [`UpdateContext`]: struct.UpdateContext.html
+#[derive(Debug, Default)]
+pub(super) struct UpdateProfiler {
+    is_enabled: bool,
+    durations: RefCell<BTreeMap<&'static str, Duration>>,
+}
+impl UpdateProfiler {
+    pub fn enabled() -> Self {
+        Self {
+            is_enabled: true,
+            ..Self::default()
+        }
+    }
+
+    /// Starts a measurement. Returns `None` when the profiler is disabled.
+    pub fn start(&self) -> Option<Instant> {
+        if self.is_enabled {
+            Some(Instant::now())
+        } else {
+            None
+        }
+    }
+
+    /// Adds the time passed since the start of the measurement to the update.
+    pub fn record(&self, name: &'static str, started: Option<Instant>) {
+        if let Some(started) = started {
+            *self.durations.borrow_mut().entry(name).or_default() += started.elapsed();
+        }
+    }
+}
+
+/// Wraps a visitor in order to measure the time spent visiting each type of element.
+pub(super) struct ProfilingVisitor<'a, T: SimulationElementVisitor> {
+    visitor: T,
+    profiler: &'a mut Profiler,
+}
+impl<'a, T: SimulationElementVisitor> ProfilingVisitor<'a, T> {
+    pub fn new(visitor: T, profiler: &'a mut Profiler) -> Self {
+        Self { visitor, profiler }
+    }
+}
+impl<T: SimulationElementVisitor> SimulationElementVisitor for ProfilingVisitor<'_, T> {
+    fn visit<U: SimulationElement>(&mut self, visited: &mut U) {
+        let started = Instant::now();
+        self.visitor.visit(visited);
+        self.profiler
+            .record_element(type_name::<U>(), started.elapsed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(values: &[u64]) -> Vec<Duration> {
+        values
+            .iter()
+            .map(|value| Duration::from_millis(*value))
+            .collect()
+    }
+
+    #[test]
+    fn percentiles_use_the_nearest_rank() {
+        let percentiles = DurationPercentiles::from(millis(&(1..=100).rev().collect::<Vec<_>>()));
+
+        assert_eq!(percentiles.p50(), Duration::from_millis(50));
+        assert_eq!(percentiles.p90(), Duration::from_millis(90));
+        assert_eq!(percentiles.p99(), Duration::from_millis(99));
+        assert_eq!(percentiles.max(), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn percentiles_of_a_single_duration_are_that_duration() {
+        let percentiles = DurationPercentiles::from(millis(&[7]));
+
+        assert_eq!(percentiles.p50(), Duration::from_millis(7));
+        assert_eq!(percentiles.p99(), Duration::from_millis(7));
+    }
+
+    #[test]
+    fn disabled_profiler_does_not_measure() {
+        let mut profiler = Profiler::disabled();
+        let started = profiler.start();
+        profiler.end_tick(started);
+
+        assert!(started.is_none());
+        assert!(profiler.report().is_none());
+    }
+
+    #[test]
+    fn reports_once_every_given_number_of_ticks() {
+        let mut profiler = Profiler::new(Some(2));
+
+        profiler.end_tick(profiler.start());
+        assert!(profiler.report().is_none());
+
+        profiler.end_tick(profiler.start());
+        assert_eq!(profiler.report().unwrap().ticks(), 2);
+    }
+
+    #[test]
+    fn phases_executed_multiple_times_within_a_tick_are_summed() {
+        let mut profiler = Profiler::new(Some(1));
+        profiler.add_phase("update", Duration::from_millis(2));
+        profiler.add_phase("update", Duration::from_millis(3));
+        profiler.end_tick(profiler.start());
+
+        let report = profiler.report().unwrap();
+        assert_eq!(report.phases().len(), 2);
+        assert_eq!(
+            report.phase("update").unwrap().percentiles().max(),
+            Duration::from_millis(5)
+        );
+        assert_eq!(report.phases()[1].name(), Profiler::TICK);
+    }
+
+    #[test]
+    fn updates_measured_multiple_times_within_a_tick_are_summed() {
+        let mut profiler = Profiler::new(Some(2));
+        let mut updates = UpdateProfiler::enabled();
+        updates
+            .durations
+            .get_mut()
+            .insert("electrical", Duration::from_millis(2));
+        profiler.record_updates(&mut updates);
+        updates
+            .durations
+            .get_mut()
+            .insert("electrical", Duration::from_millis(3));
+        profiler.record_updates(&mut updates);
+        profiler.end_tick(profiler.start());
+        profiler.end_tick(profiler.start());
+
+        let percentiles = profiler
+            .report()
+            .unwrap()
+            .update("electrical")
+            .unwrap()
+            .percentiles();
+        assert_eq!(percentiles.p50(), Duration::from_millis(0));
+        assert_eq!(percentiles.max(), Duration::from_millis(5));
+    }
+
+    #[test]
+    fn disabled_update_profiler_does_not_measure() {
+        let updates = UpdateProfiler::default();
+        let started = updates.start();
+        updates.record("electrical", started);
+
+        assert!(started.is_none());
+        assert!(updates.durations.borrow().is_empty());
+    }
+
+    #[test]
+    fn elements_missing_from_a_tick_took_no_time_in_that_tick() {
+        let mut profiler = Profiler::new(Some(2));
+        profiler.record_element("element", Duration::from_millis(5));
+        profiler.end_tick(profiler.start());
+        profiler.end_tick(profiler.start());
+
+        let percentiles = profiler
+            .report()
+            .unwrap()
+            .element("element")
+            .unwrap()
+            .percentiles();
+        assert_eq!(percentiles.p50(), Duration::from_millis(0));
+        assert_eq!(percentiles.max(), Duration::from_millis(5));
+    }
+
+    #[test]
+    fn report_lists_phases_and_elements() {
+        let mut profiler = Profiler::new(Some(1));
+        profiler.record_element("my_element", Duration::from_micros(1500));
+        profiler.end_tick(profiler.start());
+
+        let report = profiler.report().unwrap().to_string();
+        assert!(report.starts_with("profile of 1 ticks"));
+        assert!(report.contains("update"));
+        assert!(report.contains("my_element"));
+        assert!(report.contains("1500.0"));
+    }
+}
@@ -0,0 +1,152 @@
+use super::{from_bool, to_bool, VariableUnit};
+use uom::si::{
+    acceleration::foot_per_second_squared,
+    angle::degree,
+    angular_velocity::revolution_per_minute,
+    electric_charge::ampere_hour,
+    electric_current::ampere,
+    electric_potential::volt,
+    f64::*,
+    frequency::hertz,
+    length::foot,
+    mass::pound,
+    mass_rate::pound_per_hour,
+    pressure::{inch_of_mercury, psi},
+    ratio::percent,
+    thermodynamic_temperature::degree_celsius,
+    velocity::{foot_per_minute, knot},
+    volume::gallon,
+};
+
+/// A quantity which is read from or written to the simulator in the unit
+/// of the variable's definition.
+pub trait VariableQuantity: Sized {
+    /// The name of the quantity, for use in error messages.
+    const NAME: &'static str;
+
+    /// Whether the unit measures this quantity.
+    fn is_measured_in(unit: VariableUnit) -> bool {
+        Self::from_simulator(unit, 0.).is_some()
+    }
+
+    /// Converts the simulator value into the quantity. Returns `None` when
+    /// the unit doesn't measure this quantity.
+    fn from_simulator(unit: VariableUnit, value: f64) -> Option<Self>;
+
+    /// Converts the quantity into the simulator value. Returns `None` when
+    /// the unit doesn't measure this quantity.
+    fn to_simulator(&self, unit: VariableUnit) -> Option<f64>;
+}
+
+macro_rules! variable_quantity {
+    ($quantity: ty, $name: literal, $($unit: ident => $uom_unit: ty),+) => {
+        impl VariableQuantity for $quantity {
+            const NAME: &'static str = $name;
+
+            fn from_simulator(unit: VariableUnit, value: f64) -> Option<Self> {
+                match unit {
+                    $(VariableUnit::$unit => Some(<$quantity>::new::<$uom_unit>(value)),)+
+                    _ => None,
+                }
+            }
+
+            fn to_simulator(&self, unit: VariableUnit) -> Option<f64> {
+                match unit {
+                    $(VariableUnit::$unit => Some(self.get::<$uom_unit>()),)+
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+impl VariableQuantity for bool {
+    const NAME: &'static str = "bool";
+
+    fn from_simulator(unit: VariableUnit, value: f64) -> Option<Self> {
+        match unit {
+            VariableUnit::Bool => Some(to_bool(value)),
+            _ => None,
+        }
+    }
+
+    fn to_simulator(&self, unit: VariableUnit) -> Option<f64> {
+        match unit {
+            VariableUnit::Bool => Some(from_bool(*self)),
+            _ => None,
+        }
+    }
+}
+
+/// Variables without a physical dimension are read and written as a plain `f64`.
+impl VariableQuantity for f64 {
+    const NAME: &'static str = "dimensionless number";
+
+    fn from_simulator(unit: VariableUnit, value: f64) -> Option<Self> {
+        if unit.is_dimensionless() {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    fn to_simulator(&self, unit: VariableUnit) -> Option<f64> {
+        if unit.is_dimensionless() {
+            Some(*self)
+        } else {
+            None
+        }
+    }
+}
+
+variable_quantity!(Acceleration, "acceleration", FeetPerSecondSquared => foot_per_second_squared);
+variable_quantity!(Angle, "angle", Degrees => degree);
+variable_quantity!(AngularVelocity, "angular velocity", Rpm => revolution_per_minute);
+variable_quantity!(ElectricCharge, "electric charge", AmpereHours => ampere_hour);
+variable_quantity!(ElectricCurrent, "electric current", Amperes => ampere);
+variable_quantity!(ElectricPotential, "electric potential", Volts => volt);
+variable_quantity!(Frequency, "frequency", Hertz => hertz);
+variable_quantity!(Length, "length", Feet => foot);
+variable_quantity!(Mass, "mass", Pounds => pound);
+variable_quantity!(MassRate, "mass rate", PoundsPerHour => pound_per_hour);
+variable_quantity!(Pressure, "pressure", Psi => psi, InchesOfMercury => inch_of_mercury);
+variable_quantity!(Ratio, "ratio", Percent => percent);
+variable_quantity!(ThermodynamicTemperature, "temperature", Celsius => degree_celsius);
+variable_quantity!(Velocity, "velocity", Knots => knot, FeetPerMinute => foot_per_minute);
+variable_quantity!(Volume, "volume", Gallons => gallon);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ntest::assert_about_eq;
+
+    #[test]
+    fn converts_from_the_unit_of_the_variable() {
+        assert_about_eq!(
+            Velocity::from_simulator(VariableUnit::FeetPerMinute, 60.)
+                .unwrap()
+                .get::<foot_per_minute>(),
+            60.
+        );
+    }
+
+    #[test]
+    fn converts_to_the_unit_of_the_variable() {
+        let pressure = Pressure::new::<psi>(14.7);
+
+        assert_about_eq!(
+            pressure
+                .to_simulator(VariableUnit::InchesOfMercury)
+                .unwrap(),
+            pressure.get::<inch_of_mercury>()
+        );
+    }
+
+    #[test]
+    fn unit_of_another_quantity_does_not_convert() {
+        assert!(Mass::from_simulator(VariableUnit::Knots, 1.).is_none());
+        assert!(Mass::new::<pound>(1.)
+            .to_simulator(VariableUnit::Gallons)
+            .is_none());
+    }
+}
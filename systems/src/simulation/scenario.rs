@@ -0,0 +1,677 @@
+use super::{Aircraft, InMemoryReaderWriter, Simulation};
+use std::{fmt, str::FromStr, time::Duration};
+
+/// A scripted run of the simulation. The scenario sets simulator variables at
+/// given moments in time, names the variables which are to be output after every tick
+/// and contains the values variables are expected to have at given moments in time.
+///
+/// Scenarios are written in a line based text format. Empty lines and lines
+/// starting with `#` are ignored. Times and durations are in seconds.
+/// Variables set at time 0 are the initial state of the scenario.
+/// An expected value can be followed by `+-` and a tolerance.
+///
+/// ```text
+/// # Run for two minutes in steps of 50 milliseconds.
+/// duration 120
+/// delta 0.05
+/// seed 1
+/// output ELEC_DC_BAT_BUS_IS_POWERED
+/// at 0 1 OVHD_ELEC_BAT_10_PB_IS_AUTO
+/// at 10 60 TURB ENG CORRECTED N2:1
+/// expect 60 1 ELEC_AC_1_BUS_IS_POWERED
+/// expect 60 115+-5 ELEC_ENG_GEN_1_POTENTIAL
+/// ```
+///
+/// # Examples
+/// ```rust
+/// # use systems::simulation::{Aircraft, Scenario, SimulationElement, SimulatorReader,
+/// #    SimulatorWriter, QuantityIdentifier, VariableRegistry, VariableUnit};
+/// struct MyAircraft {
+///     pb_is_on_id: QuantityIdentifier<bool>,
+///     light_is_on_id: QuantityIdentifier<bool>,
+///     is_on: bool,
+/// }
+/// impl MyAircraft {
+///     fn new(registry: &mut VariableRegistry) -> Self {
+///         Self {
+///             pb_is_on_id: registry.register_read("MY_PB_IS_ON", VariableUnit::Bool),
+///             light_is_on_id: registry.register_write("MY_LIGHT_IS_ON", VariableUnit::Bool),
+///             is_on: false,
+///         }
+///     }
+/// }
+/// impl Aircraft for MyAircraft {}
+/// impl SimulationElement for MyAircraft {
+///     fn read(&mut self, reader: &mut SimulatorReader) {
+///         self.is_on = reader.read_bool(&self.pb_is_on_id);
+///     }
+///
+///     fn write(&self, writer: &mut SimulatorWriter) {
+///         writer.write_bool(&self.light_is_on_id, self.is_on);
+///     }
+/// }
+///
+/// let scenario: Scenario = "duration 2\nat 1 1 MY_PB_IS_ON\nexpect 2 1 MY_LIGHT_IS_ON"
+///     .parse()
+///     .unwrap();
+/// let mut registry = VariableRegistry::new();
+/// let failures = scenario.run(&mut MyAircraft::new(&mut registry), |_, _| {});
+/// assert!(failures.is_empty());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scenario {
+    duration: Duration,
+    delta: Duration,
+    random_seed: u64,
+    outputs: Vec<String>,
+    events: Vec<ScenarioEvent>,
+    expectations: Vec<ScenarioExpectation>,
+}
+impl Scenario {
+    const DEFAULT_DELTA_MILLIS: u64 = 50;
+
+    /// Runs the aircraft through the scenario, keeping the simulator variables in memory.
+    /// Before the first tick the variables are initialised with the state of the aircraft,
+    /// such that e.g. push buttons start in their default position. The variables set at
+    /// a given time are written before the first tick which starts at or after that time.
+    ///
+    /// After every tick `on_tick` is called with the elapsed time and the values of the
+    /// scenario's outputs. Expectations are verified after the first tick which ends
+    /// at or after their time. Returns the expectations which weren't met, including
+    /// those of variables which were never written.
+    pub fn run<T: Aircraft, U: FnMut(Duration, &[f64])>(
+        &self,
+        aircraft: &mut T,
+        mut on_tick: U,
+    ) -> Vec<ScenarioFailure> {
+        let mut reader_writer = InMemoryReaderWriter::new();
+        reader_writer.write_state_of(aircraft);
+
+        let mut simulation =
+            Simulation::new_with_random_seed(aircraft, &mut reader_writer, self.random_seed);
+        let mut events = self.events.iter().peekable();
+        let mut expectations = self.expectations.iter().peekable();
+        let mut failures = Vec::new();
+        let mut elapsed = Duration::from_secs(0);
+
+        while elapsed < self.duration {
+            while let Some(event) = events.next_if(|event| event.at <= elapsed) {
+                simulation
+                    .simulator_reader_writer()
+                    .write_f64(&event.name, event.value);
+            }
+
+            let delta = self.delta.min(self.duration - elapsed);
+            simulation.tick(delta);
+            elapsed += delta;
+
+            let variables = simulation.simulator_reader_writer();
+            let outputs: Vec<f64> = self
+                .outputs
+                .iter()
+                .map(|name| variables.read_f64(name))
+                .collect();
+            on_tick(elapsed, &outputs);
+
+            while let Some(expectation) =
+                expectations.next_if(|expectation| expectation.at <= elapsed)
+            {
+                if !variables.contains_key(&expectation.name) {
+                    failures.push(ScenarioFailure::new(
+                        expectation.clone(),
+                        ScenarioOutcome::NotWritten,
+                    ));
+                    continue;
+                }
+
+                let actual = variables.read_f64(&expectation.name);
+                if !expectation.is_met_by(actual) {
+                    failures.push(ScenarioFailure::new(
+                        expectation.clone(),
+                        ScenarioOutcome::Value(actual),
+                    ));
+                }
+            }
+        }
+
+        failures.extend(
+            expectations.map(|expectation| {
+                ScenarioFailure::new(expectation.clone(), ScenarioOutcome::Ended)
+            }),
+        );
+
+        failures
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn random_seed(&self) -> u64 {
+        self.random_seed
+    }
+
+    pub fn outputs(&self) -> &[String] {
+        &self.outputs
+    }
+
+    /// The events of the scenario, ordered by the time at which they occur.
+    pub fn events(&self) -> &[ScenarioEvent] {
+        &self.events
+    }
+
+    /// The expectations of the scenario, ordered by the time at which they are verified.
+    pub fn expectations(&self) -> &[ScenarioExpectation] {
+        &self.expectations
+    }
+}
+impl Default for Scenario {
+    fn default() -> Self {
+        Self {
+            duration: Duration::from_secs(0),
+            delta: Duration::from_millis(Scenario::DEFAULT_DELTA_MILLIS),
+            random_seed: 0,
+            outputs: Vec::new(),
+            events: Vec::new(),
+            expectations: Vec::new(),
+        }
+    }
+}
+impl FromStr for Scenario {
+    type Err = ScenarioError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut scenario = Scenario::default();
+        for (index, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = line.splitn(2, ' ');
+            match (parts.next(), parts.next().map(str::trim)) {
+                (Some("duration"), Some(seconds)) => {
+                    scenario.duration = parse_seconds(seconds, index, "invalid duration")?
+                }
+                (Some("delta"), Some(seconds)) => {
+                    scenario.delta = parse_seconds(seconds, index, "invalid delta")?;
+                    if scenario.delta == Duration::from_secs(0) {
+                        return Err(ScenarioError::new(index, "delta must be positive"));
+                    }
+                }
+                (Some("seed"), Some(seed)) => {
+                    scenario.random_seed = seed
+                        .parse()
+                        .map_err(|_| ScenarioError::new(index, "invalid seed"))?
+                }
+                (Some("output"), Some(name)) => scenario.outputs.push(name.to_owned()),
+                (Some("at"), Some(event)) => {
+                    scenario.events.push(ScenarioEvent::parse(event, index)?)
+                }
+                (Some("expect"), Some(expectation)) => scenario
+                    .expectations
+                    .push(ScenarioExpectation::parse(expectation, index)?),
+                _ => return Err(ScenarioError::new(index, "unknown line")),
+            }
+        }
+
+        // A stable sort keeps events occurring at the same time in the order they were written.
+        scenario.events.sort_by_key(|event| event.at);
+        scenario
+            .expectations
+            .sort_by_key(|expectation| expectation.at);
+
+        Ok(scenario)
+    }
+}
+
+/// Sets a simulator variable to a value at the given time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScenarioEvent {
+    at: Duration,
+    name: String,
+    value: f64,
+}
+impl ScenarioEvent {
+    fn parse(event: &str, line_index: usize) -> Result<Self, ScenarioError> {
+        let mut parts = event.splitn(3, ' ');
+        match (parts.next(), parts.next(), parts.next().map(str::trim)) {
+            (Some(at), Some(value), Some(name)) if !name.is_empty() => Ok(Self {
+                at: parse_seconds(at, line_index, "invalid time")?,
+                value: value
+                    .parse()
+                    .map_err(|_| ScenarioError::new(line_index, "invalid value"))?,
+                name: name.to_owned(),
+            }),
+            _ => Err(ScenarioError::new(
+                line_index,
+                "expected 'at <seconds> <value> <name>'",
+            )),
+        }
+    }
+
+    pub fn at(&self) -> Duration {
+        self.at
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+}
+
+/// Expects a simulator variable to have a value within the tolerance at the given time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScenarioExpectation {
+    at: Duration,
+    name: String,
+    value: f64,
+    tolerance: f64,
+}
+impl ScenarioExpectation {
+    fn parse(expectation: &str, line_index: usize) -> Result<Self, ScenarioError> {
+        let mut parts = expectation.splitn(3, ' ');
+        match (parts.next(), parts.next(), parts.next().map(str::trim)) {
+            (Some(at), Some(value), Some(name)) if !name.is_empty() => {
+                let (value, tolerance) = match value.find("+-") {
+                    Some(index) => (&value[..index], &value[index + 2..]),
+                    None => (value, "0"),
+                };
+
+                Ok(Self {
+                    at: parse_seconds(at, line_index, "invalid time")?,
+                    value: value
+                        .parse()
+                        .map_err(|_| ScenarioError::new(line_index, "invalid value"))?,
+                    tolerance: match tolerance.parse::<f64>() {
+                        Ok(tolerance) if tolerance >= 0. => tolerance,
+                        _ => return Err(ScenarioError::new(line_index, "invalid tolerance")),
+                    },
+                    name: name.to_owned(),
+                })
+            }
+            _ => Err(ScenarioError::new(
+                line_index,
+                "expected 'expect <seconds> <value>[+-<tolerance>] <name>'",
+            )),
+        }
+    }
+
+    pub fn at(&self) -> Duration {
+        self.at
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    pub fn tolerance(&self) -> f64 {
+        self.tolerance
+    }
+
+    pub fn is_met_by(&self, actual: f64) -> bool {
+        (actual - self.value).abs() <= self.tolerance
+    }
+}
+
+/// An expectation which wasn't met while running a [`Scenario`].
+///
+/// [`Scenario`]: struct.Scenario.html
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScenarioFailure {
+    expectation: ScenarioExpectation,
+    outcome: ScenarioOutcome,
+}
+impl ScenarioFailure {
+    fn new(expectation: ScenarioExpectation, outcome: ScenarioOutcome) -> Self {
+        Self {
+            expectation,
+            outcome,
+        }
+    }
+
+    pub fn expectation(&self) -> &ScenarioExpectation {
+        &self.expectation
+    }
+
+    /// The actual value, or `None` when the variable was never written
+    /// or the scenario ended before the expectation's time.
+    pub fn actual(&self) -> Option<f64> {
+        match self.outcome {
+            ScenarioOutcome::Value(actual) => Some(actual),
+            _ => None,
+        }
+    }
+
+    /// Whether the expected variable was never written, e.g. as its name is misspelled.
+    pub fn is_not_written(&self) -> bool {
+        self.outcome == ScenarioOutcome::NotWritten
+    }
+}
+impl fmt::Display for ScenarioFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let expectation = &self.expectation;
+        write!(
+            f,
+            "at {}s expected {} to be {}",
+            expectation.at.as_secs_f64(),
+            expectation.name,
+            expectation.value
+        )?;
+        if expectation.tolerance > 0. {
+            write!(f, " +- {}", expectation.tolerance)?;
+        }
+
+        match self.outcome {
+            ScenarioOutcome::Value(actual) => write!(f, ", but it was {}", actual),
+            ScenarioOutcome::NotWritten => write!(f, ", but it was never written"),
+            ScenarioOutcome::Ended => write!(f, ", but the scenario ended before"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ScenarioOutcome {
+    Value(f64),
+    NotWritten,
+    Ended,
+}
+
+fn parse_seconds(
+    seconds: &str,
+    line_index: usize,
+    reason: &'static str,
+) -> Result<Duration, ScenarioError> {
+    match seconds.parse::<f64>() {
+        // Rounding to whole nanoseconds ensures e.g. 0.05 seconds add up to exactly 1 second.
+        Ok(seconds) if seconds.is_finite() && seconds >= 0. => {
+            Ok(Duration::from_nanos((seconds * 1e9).round() as u64))
+        }
+        _ => Err(ScenarioError::new(line_index, reason)),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScenarioError {
+    line: usize,
+    reason: &'static str,
+}
+impl ScenarioError {
+    fn new(line_index: usize, reason: &'static str) -> Self {
+        Self {
+            line: line_index + 1,
+            reason,
+        }
+    }
+}
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "malformed scenario at line {}: {}",
+            self.line, self.reason
+        )
+    }
+}
+impl std::error::Error for ScenarioError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{
+        QuantityIdentifier, SimulationElement, SimulatorReader, SimulatorWriter, VariableRegistry,
+        VariableUnit,
+    };
+
+    struct TestAircraft {
+        n_id: QuantityIdentifier<f64>,
+        double_n_id: QuantityIdentifier<f64>,
+        n: f64,
+    }
+    impl TestAircraft {
+        fn new() -> Self {
+            let mut registry = VariableRegistry::new();
+            Self {
+                n_id: registry.register_read_write("TEST N", VariableUnit::Number),
+                double_n_id: registry.register_write("TEST_DOUBLE_N", VariableUnit::Number),
+                n: 1.,
+            }
+        }
+    }
+    impl Aircraft for TestAircraft {}
+    impl SimulationElement for TestAircraft {
+        fn read(&mut self, reader: &mut SimulatorReader) {
+            self.n = reader.read_f64(&self.n_id);
+        }
+
+        fn write(&self, writer: &mut SimulatorWriter) {
+            writer.write_f64(&self.n_id, self.n);
+            writer.write_f64(&self.double_n_id, self.n * 2.);
+        }
+    }
+
+    fn run(scenario: &str) -> (Vec<(Duration, Vec<f64>)>, Vec<ScenarioFailure>) {
+        let scenario: Scenario = scenario.parse().unwrap();
+        let mut ticks = Vec::new();
+        let failures = scenario.run(&mut TestAircraft::new(), |elapsed, outputs| {
+            ticks.push((elapsed, outputs.to_vec()))
+        });
+
+        (ticks, failures)
+    }
+
+    #[test]
+    fn parses_settings() {
+        let scenario: Scenario = "duration 120\ndelta 0.1\nseed 7\n".parse().unwrap();
+
+        assert_eq!(scenario.duration(), Duration::from_secs(120));
+        assert_eq!(scenario.delta(), Duration::from_millis(100));
+        assert_eq!(scenario.random_seed(), 7);
+    }
+
+    #[test]
+    fn has_defaults() {
+        let scenario: Scenario = "".parse().unwrap();
+
+        assert_eq!(scenario.duration(), Duration::from_secs(0));
+        assert_eq!(scenario.delta(), Duration::from_millis(50));
+        assert!(scenario.outputs().is_empty());
+    }
+
+    #[test]
+    fn ignores_comments_and_empty_lines() {
+        let scenario: Scenario = "# A comment\n\n   \nduration 1".parse().unwrap();
+
+        assert_eq!(scenario.duration(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn parses_outputs_with_spaces_in_order() {
+        let scenario: Scenario = "output B\noutput TURB ENG N1:1\n".parse().unwrap();
+
+        assert_eq!(scenario.outputs(), &["B", "TURB ENG N1:1"]);
+    }
+
+    #[test]
+    fn parses_events_with_spaces_in_name() {
+        let scenario: Scenario = "at 2.5 60 TURB ENG CORRECTED N2:1".parse().unwrap();
+
+        let event = &scenario.events()[0];
+        assert_eq!(event.at(), Duration::from_millis(2500));
+        assert_eq!(event.value(), 60.);
+        assert_eq!(event.name(), "TURB ENG CORRECTED N2:1");
+    }
+
+    #[test]
+    fn orders_events_by_time_keeping_written_order_for_equal_times() {
+        let scenario: Scenario = "at 5 1 C\nat 0 1 A\nat 5 1 D\nat 1 1 B".parse().unwrap();
+
+        let names: Vec<&str> = scenario.events().iter().map(|event| event.name()).collect();
+        assert_eq!(names, ["A", "B", "C", "D"]);
+    }
+
+    #[test]
+    fn rejects_unknown_lines() {
+        assert_eq!(
+            "duration 1\nfly 10".parse::<Scenario>(),
+            Err(ScenarioError::new(1, "unknown line"))
+        );
+    }
+
+    #[test]
+    fn rejects_events_without_a_name() {
+        assert_eq!(
+            "at 1 2".parse::<Scenario>(),
+            Err(ScenarioError::new(
+                0,
+                "expected 'at <seconds> <value> <name>'"
+            ))
+        );
+    }
+
+    #[test]
+    fn rejects_negative_times() {
+        assert_eq!(
+            "at -1 2 A".parse::<Scenario>(),
+            Err(ScenarioError::new(0, "invalid time"))
+        );
+    }
+
+    #[test]
+    fn rejects_zero_delta() {
+        assert_eq!(
+            "delta 0".parse::<Scenario>(),
+            Err(ScenarioError::new(0, "delta must be positive"))
+        );
+    }
+
+    #[test]
+    fn parses_expectations() {
+        let scenario: Scenario = "expect 10 115 ELEC_AC_1_BUS_POTENTIAL".parse().unwrap();
+
+        let expectation = &scenario.expectations()[0];
+        assert_eq!(expectation.at(), Duration::from_secs(10));
+        assert_eq!(expectation.value(), 115.);
+        assert_eq!(expectation.tolerance(), 0.);
+        assert_eq!(expectation.name(), "ELEC_AC_1_BUS_POTENTIAL");
+    }
+
+    #[test]
+    fn parses_expectations_with_tolerance() {
+        let scenario: Scenario = "expect 10 -115.5+-0.5 A B".parse().unwrap();
+
+        let expectation = &scenario.expectations()[0];
+        assert_eq!(expectation.value(), -115.5);
+        assert_eq!(expectation.tolerance(), 0.5);
+        assert_eq!(expectation.name(), "A B");
+    }
+
+    #[test]
+    fn rejects_negative_tolerance() {
+        assert_eq!(
+            "expect 1 2+--1 A".parse::<Scenario>(),
+            Err(ScenarioError::new(0, "invalid tolerance"))
+        );
+    }
+
+    #[test]
+    fn expectation_is_met_within_tolerance() {
+        let expectation = ScenarioExpectation::parse("1 10+-1 A", 0).unwrap();
+
+        assert!(expectation.is_met_by(9.));
+        assert!(expectation.is_met_by(11.));
+        assert!(!expectation.is_met_by(11.1));
+        assert!(!expectation.is_met_by(f64::NAN));
+    }
+
+    #[test]
+    fn runs_until_duration_calling_on_tick_with_outputs() {
+        let (ticks, _) = run("duration 1\ndelta 0.5\noutput TEST_DOUBLE_N");
+
+        assert_eq!(
+            ticks,
+            vec![
+                (Duration::from_millis(500), vec![2.]),
+                (Duration::from_secs(1), vec![2.])
+            ]
+        );
+    }
+
+    #[test]
+    fn last_tick_does_not_exceed_duration() {
+        let (ticks, _) = run("duration 0.75\ndelta 0.5");
+
+        assert_eq!(ticks.last().unwrap().0, Duration::from_millis(750));
+    }
+
+    #[test]
+    fn applies_events_before_the_tick_which_starts_at_their_time() {
+        let (ticks, _) =
+            run("duration 1.5\ndelta 0.5\noutput TEST_DOUBLE_N\nat 0.5 2 TEST N\nat 0.7 4 TEST N");
+
+        let outputs: Vec<f64> = ticks.iter().map(|(_, outputs)| outputs[0]).collect();
+        assert_eq!(outputs, [2., 4., 8.]);
+    }
+
+    #[test]
+    fn small_deltas_add_up_to_event_times() {
+        let (ticks, _) = run("duration 1.05\ndelta 0.05\noutput TEST_DOUBLE_N\nat 1 2 TEST N");
+
+        assert_eq!(ticks.last().unwrap().1, vec![4.]);
+    }
+
+    #[test]
+    fn met_expectations_do_not_fail() {
+        let (_, failures) = run("duration 2\nat 1 3 TEST N\nexpect 2 6 TEST_DOUBLE_N");
+
+        assert!(failures.is_empty());
+    }
+
+    #[test]
+    fn unmet_expectations_fail_with_the_actual_value() {
+        let (_, failures) = run("duration 2\nexpect 1 6 TEST_DOUBLE_N");
+
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].actual(), Some(2.));
+        assert_eq!(
+            failures[0].to_string(),
+            "at 1s expected TEST_DOUBLE_N to be 6, but it was 2"
+        );
+    }
+
+    #[test]
+    fn expectations_of_variables_which_were_never_written_fail() {
+        let (_, failures) = run("duration 1\nexpect 1 0 TEST_DOUBEL_N");
+
+        assert_eq!(failures.len(), 1);
+        assert!(failures[0].is_not_written());
+        assert_eq!(failures[0].actual(), None);
+        assert_eq!(
+            failures[0].to_string(),
+            "at 1s expected TEST_DOUBEL_N to be 0, but it was never written"
+        );
+    }
+
+    #[test]
+    fn expectations_after_the_end_fail_without_a_value() {
+        let (_, failures) = run("duration 1\nexpect 2 2+-0.5 TEST_DOUBLE_N");
+
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].actual(), None);
+        assert_eq!(
+            failures[0].to_string(),
+            "at 2s expected TEST_DOUBLE_N to be 2 +- 0.5, but the scenario ended before"
+        );
+    }
+}
@@ -0,0 +1,321 @@
+use super::{
+    json::{self, JsonError, JsonParser},
+    SimulationElement, SimulationElementVisitor,
+};
+use std::{collections::BTreeMap, fmt, time::Duration};
+
+/// Contains the internal state of the aircraft's system simulation at a moment in time.
+/// A snapshot can be restored into an aircraft of the same type, resuming the simulation
+/// from that moment. Elements store their state under unique names, much like they
+/// write their state to the simulator.
+///
+/// # Examples
+/// ```rust
+/// # use systems::simulation::{SimulationElement, Snapshot};
+/// struct MyBattery {
+///     charge: f64,
+/// }
+/// impl SimulationElement for MyBattery {
+///     fn save(&self, snapshot: &mut Snapshot) {
+///         snapshot.save_f64("MY_BATTERY_CHARGE", self.charge);
+///     }
+///
+///     fn restore(&mut self, snapshot: &Snapshot) {
+///         if let Some(charge) = snapshot.restore_f64("MY_BATTERY_CHARGE") {
+///             self.charge = charge;
+///         }
+///     }
+/// }
+///
+/// let snapshot = Snapshot::take(&mut MyBattery { charge: 12.5 });
+/// let json = snapshot.to_json();
+///
+/// let mut battery = MyBattery { charge: 0. };
+/// Snapshot::from_json(&json).unwrap().restore(&mut battery);
+/// assert_eq!(battery.charge, 12.5);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Snapshot {
+    values: BTreeMap<String, f64>,
+}
+impl Snapshot {
+    /// The version of the snapshot format. Increment this whenever a change
+    /// to the simulation makes earlier snapshots incompatible.
+    pub const VERSION: u32 = 1;
+
+    pub fn new() -> Snapshot {
+        Snapshot {
+            values: BTreeMap::new(),
+        }
+    }
+
+    /// Takes a snapshot of the given element and its children.
+    pub fn take<T: SimulationElement>(element: &mut T) -> Snapshot {
+        let mut visitor = SaveVisitor {
+            snapshot: Snapshot::new(),
+        };
+        element.accept(&mut visitor);
+
+        visitor.snapshot
+    }
+
+    /// Restores the state contained in the snapshot into the given element and its children.
+    pub fn restore<T: SimulationElement>(&self, element: &mut T) {
+        let mut visitor = RestoreVisitor { snapshot: self };
+        element.accept(&mut visitor);
+    }
+
+    pub fn save_f64(&mut self, name: &str, value: f64) {
+        self.values.insert(name.to_owned(), value);
+    }
+
+    pub fn save_bool(&mut self, name: &str, value: bool) {
+        self.save_f64(name, if value { 1. } else { 0. });
+    }
+
+    pub fn save_duration(&mut self, name: &str, value: Duration) {
+        self.save_f64(name, value.as_secs_f64());
+    }
+
+    /// Restores an `f64` from the snapshot. Returns `None` when the snapshot
+    /// doesn't contain the given name, in which case elements keep their current state.
+    pub fn restore_f64(&self, name: &str) -> Option<f64> {
+        self.values.get(name).copied()
+    }
+
+    pub fn restore_bool(&self, name: &str) -> Option<bool> {
+        self.restore_f64(name).map(|value| value > 0.5)
+    }
+
+    pub fn restore_duration(&self, name: &str) -> Option<Duration> {
+        // Rounding to whole nanoseconds ensures durations survive the conversion to seconds.
+        self.restore_f64(name)
+            .map(|seconds| Duration::from_nanos((seconds.max(0.) * 1e9).round() as u64))
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.values.contains_key(name)
+    }
+
+    /// Serialises the snapshot into a JSON document of the form
+    /// `{"version":1,"state":{"NAME":0.5}}`.
+    pub fn to_json(&self) -> String {
+        let state = self
+            .values
+            .iter()
+            .map(|(name, value)| format!("{}:{}", json::string(name), json::number(*value)))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        format!(
+            "{{\"version\":{},\"state\":{{{}}}}}",
+            Snapshot::VERSION,
+            state
+        )
+    }
+
+    /// Deserialises a snapshot from a JSON document created by [`to_json`].
+    ///
+    /// [`to_json`]: #method.to_json
+    pub fn from_json(json: &str) -> Result<Snapshot, SnapshotError> {
+        let mut parser = JsonParser::new(json);
+        let mut version = None;
+        let mut values = None;
+
+        parser.expect('{')?;
+        loop {
+            let key = parser.string()?;
+            parser.expect(':')?;
+            match key.as_str() {
+                "version" => version = Some(parser.number()?),
+                "state" => values = Some(parser.numbers()?),
+                _ => return Err(SnapshotError::Malformed(format!("unknown key '{}'", key))),
+            }
+
+            if !parser.next_member('}')? {
+                break;
+            }
+        }
+        parser.end()?;
+
+        match version {
+            Some(version) if (version - Snapshot::VERSION as f64).abs() < f64::EPSILON => {
+                Ok(Snapshot {
+                    values: values.unwrap_or_default(),
+                })
+            }
+            Some(version) => Err(SnapshotError::UnsupportedVersion(version as u32)),
+            None => Err(SnapshotError::Malformed("missing version".to_owned())),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SnapshotError {
+    UnsupportedVersion(u32),
+    Malformed(String),
+}
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::UnsupportedVersion(version) => write!(
+                f,
+                "snapshot version {} is not supported, expected version {}",
+                version,
+                Snapshot::VERSION
+            ),
+            SnapshotError::Malformed(reason) => write!(f, "malformed snapshot: {}", reason),
+        }
+    }
+}
+impl std::error::Error for SnapshotError {}
+impl From<JsonError> for SnapshotError {
+    fn from(error: JsonError) -> Self {
+        SnapshotError::Malformed(error.reason().to_owned())
+    }
+}
+
+struct SaveVisitor {
+    snapshot: Snapshot,
+}
+impl SimulationElementVisitor for SaveVisitor {
+    fn visit<T: SimulationElement>(&mut self, visited: &mut T) {
+        visited.save(&mut self.snapshot);
+    }
+}
+
+struct RestoreVisitor<'a> {
+    snapshot: &'a Snapshot,
+}
+impl SimulationElementVisitor for RestoreVisitor<'_> {
+    fn visit<T: SimulationElement>(&mut self, visited: &mut T) {
+        visited.restore(self.snapshot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestElement {
+        value: f64,
+        is_on: bool,
+        duration: Duration,
+    }
+    impl TestElement {
+        fn new(value: f64, is_on: bool, duration: Duration) -> Self {
+            Self {
+                value,
+                is_on,
+                duration,
+            }
+        }
+    }
+    impl SimulationElement for TestElement {
+        fn save(&self, snapshot: &mut Snapshot) {
+            snapshot.save_f64("VALUE", self.value);
+            snapshot.save_bool("IS_ON", self.is_on);
+            snapshot.save_duration("DURATION", self.duration);
+        }
+
+        fn restore(&mut self, snapshot: &Snapshot) {
+            if let Some(value) = snapshot.restore_f64("VALUE") {
+                self.value = value;
+            }
+            if let Some(is_on) = snapshot.restore_bool("IS_ON") {
+                self.is_on = is_on;
+            }
+            if let Some(duration) = snapshot.restore_duration("DURATION") {
+                self.duration = duration;
+            }
+        }
+    }
+
+    #[test]
+    fn restores_taken_state() {
+        let snapshot = Snapshot::take(&mut TestElement::new(
+            -12.25,
+            true,
+            Duration::from_millis(1500),
+        ));
+
+        let mut element = TestElement::new(0., false, Duration::from_secs(0));
+        snapshot.restore(&mut element);
+
+        assert_eq!(element.value, -12.25);
+        assert!(element.is_on);
+        assert_eq!(element.duration, Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn json_round_trip_is_lossless() {
+        let snapshot = Snapshot::take(&mut TestElement::new(
+            0.1 + 0.2,
+            true,
+            Duration::from_nanos(123_456_789),
+        ));
+
+        assert_eq!(Snapshot::from_json(&snapshot.to_json()), Ok(snapshot));
+    }
+
+    #[test]
+    fn json_contains_version() {
+        let json = Snapshot::new().to_json();
+
+        assert_eq!(json, "{\"version\":1,\"state\":{}}");
+    }
+
+    #[test]
+    fn json_with_whitespace_is_accepted() {
+        let snapshot =
+            Snapshot::from_json("{ \"version\": 1,\n \"state\": { \"A\": 1e3, \"B\": -2 } }")
+                .unwrap();
+
+        assert_eq!(snapshot.restore_f64("A"), Some(1000.));
+        assert_eq!(snapshot.restore_f64("B"), Some(-2.));
+    }
+
+    #[test]
+    fn unsupported_version_is_rejected() {
+        assert_eq!(
+            Snapshot::from_json("{\"version\":2,\"state\":{}}"),
+            Err(SnapshotError::UnsupportedVersion(2))
+        );
+    }
+
+    #[test]
+    fn malformed_json_is_rejected() {
+        assert!(matches!(
+            Snapshot::from_json("{\"version\":1,\"state\":{\"A\":}}"),
+            Err(SnapshotError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn missing_value_is_not_restored() {
+        let snapshot = Snapshot::new();
+
+        assert_eq!(snapshot.restore_f64("UNKNOWN"), None);
+        assert_eq!(snapshot.restore_bool("UNKNOWN"), None);
+    }
+
+    #[test]
+    fn element_missing_from_the_snapshot_keeps_its_state() {
+        let mut element = TestElement::new(3., true, Duration::from_secs(2));
+        Snapshot::new().restore(&mut element);
+
+        assert_eq!(element.value, 3.);
+        assert!(element.is_on);
+        assert_eq!(element.duration, Duration::from_secs(2));
+    }
+
+    #[test]
+    fn json_round_trip_escapes_control_characters() {
+        let mut snapshot = Snapshot::new();
+        snapshot.save_f64("A\n\"B\"\u{1}", 1.);
+
+        let json = snapshot.to_json();
+        assert!(!json.contains('\n'));
+        assert_eq!(Snapshot::from_json(&json), Ok(snapshot));
+    }
+}
@@ -1,38 +1,71 @@
-use std::{collections::HashMap, time::Duration};
-use uom::si::{f64::*, length::foot, thermodynamic_temperature::degree_celsius, velocity::knot};
+use std::{rc::Rc, time::Duration};
+use uom::si::{
+    acceleration::foot_per_second_squared,
+    angle::degree,
+    f64::*,
+    length::foot,
+    pressure::inch_of_mercury,
+    thermodynamic_temperature::degree_celsius,
+    velocity::{foot_per_minute, knot},
+};
 
-use crate::electrical::consumption::SuppliedPower;
+use crate::{electrical::consumption::SuppliedPower, shared::RandomNumberGenerator};
 
 use super::{
-    from_bool, to_bool, Aircraft, Simulation, SimulationElement, SimulationElementVisitor,
-    SimulationToSimulatorVisitor, SimulatorReaderWriter, SimulatorWriter, UpdateContext,
+    Aircraft, FailureType, InMemoryReaderWriter, Simulation, SimulationElement,
+    SimulationElementVisitor, UpdateContext,
 };
 
 /// The simulation test bed handles the testing of [`Aircraft`] and [`SimulationElement`]
 /// by running a full simulation tick on them.
 ///
+/// The random numbers provided to the elements are derived from a fixed seed,
+/// such that every run of a test produces the same results.
+///
 /// [`Aircraft`]: ../trait.Aircraft.html
 /// [`SimulationElement`]: ../trait.SimulationElement.html
 pub struct SimulationTestBed {
-    reader_writer: TestReaderWriter,
-    get_supplied_power_fn: Box<dyn Fn() -> SuppliedPower>,
+    reader_writer: InMemoryReaderWriter,
+    get_supplied_power_fn: Rc<dyn Fn() -> SuppliedPower>,
     delta: Duration,
+    max_step: Option<Duration>,
+    random: RandomNumberGenerator,
 }
 impl SimulationTestBed {
+    const DEFAULT_RANDOM_SEED: u64 = 0;
+
     pub fn new() -> Self {
         Self::new_with_delta(Duration::from_secs(1))
     }
 
     pub fn new_with_delta(delta: Duration) -> Self {
         let mut test_bed = Self {
-            reader_writer: TestReaderWriter::new(),
-            get_supplied_power_fn: Box::new(SuppliedPower::new),
+            reader_writer: InMemoryReaderWriter::new(),
+            get_supplied_power_fn: Rc::new(SuppliedPower::new),
             delta,
+            max_step: None,
+            random: RandomNumberGenerator::new(SimulationTestBed::DEFAULT_RANDOM_SEED),
         };
 
+        test_bed.set_simulation_rate(1.);
         test_bed.set_indicated_airspeed(Velocity::new::<knot>(250.));
+        test_bed.set_true_airspeed(Velocity::new::<knot>(270.));
+        test_bed.set_mach_number(0.41);
+        test_bed.set_ground_speed(Velocity::new::<knot>(270.));
         test_bed.set_indicated_altitude(Length::new::<foot>(5000.));
+        test_bed.set_pressure_altitude(Length::new::<foot>(5000.));
+        test_bed.set_radio_altitude(Length::new::<foot>(5000.));
+        test_bed.set_vertical_speed(Velocity::new::<foot_per_minute>(0.));
         test_bed.set_ambient_temperature(ThermodynamicTemperature::new::<degree_celsius>(0.));
+        test_bed.set_ambient_pressure(Pressure::new::<inch_of_mercury>(24.89));
+        test_bed.set_pitch(Angle::new::<degree>(0.));
+        test_bed.set_bank(Angle::new::<degree>(0.));
+        test_bed.set_accelerations(
+            Acceleration::new::<foot_per_second_squared>(0.),
+            Acceleration::new::<foot_per_second_squared>(0.),
+            Acceleration::new::<foot_per_second_squared>(0.),
+        );
+        test_bed.set_load_factor(1.);
         test_bed.set_on_ground(false);
 
         test_bed
@@ -44,10 +77,7 @@ impl SimulationTestBed {
     /// variables. If this is a problem for your test, then use this function.
     pub fn seeded_with<T: SimulationElement>(element: &mut T) -> Self {
         let mut test_bed = Self::new();
-
-        let mut writer = SimulatorWriter::new(&mut test_bed.reader_writer);
-        let mut visitor = SimulationToSimulatorVisitor::new(&mut writer);
-        element.accept(&mut visitor);
+        test_bed.reader_writer.write_state_of(element);
 
         test_bed
     }
@@ -57,8 +87,12 @@ impl SimulationTestBed {
     /// [`Aircraft`]: ../trait.Aircraft.html
     /// [`Simulation`]: ../struct.Simulation.html
     pub fn run_aircraft<T: Aircraft>(&mut self, aircraft: &mut T) {
-        let mut simulation = Simulation::new(aircraft, &mut self.reader_writer);
-        simulation.tick(self.delta);
+        let mut simulation = Simulation::new_with_random_seed(aircraft, &mut self.reader_writer, 0);
+        if let Some(max_step) = self.max_step {
+            simulation = simulation.with_max_step(max_step);
+        }
+
+        simulation.tick_with_random_seed(self.delta, self.random.next_u64());
     }
 
     /// Runs a single [`Simulation`] tick on the provided [`SimulationElement`], executing
@@ -123,17 +157,32 @@ impl SimulationTestBed {
         let mut aircraft = TestAircraft::new(
             element,
             update_fn,
-            (self.get_supplied_power_fn)(),
+            Rc::clone(&self.get_supplied_power_fn),
             before_power_distribution,
         );
 
         self.run_aircraft(&mut aircraft);
     }
 
+    /// Sets the seed from which the random numbers of subsequent ticks are derived.
+    pub fn set_random_seed(&mut self, seed: u64) {
+        self.random = RandomNumberGenerator::new(seed);
+    }
+
     pub fn set_delta(&mut self, delta: Duration) {
         self.delta = delta;
     }
 
+    /// Splits ticks into sub-steps no larger than the given maximum step.
+    pub fn set_max_step(&mut self, max_step: Duration) {
+        self.max_step = Some(max_step);
+    }
+
+    pub fn set_simulation_rate(&mut self, simulation_rate: f64) {
+        self.reader_writer
+            .write_f64(UpdateContext::SIMULATION_RATE_KEY, simulation_rate);
+    }
+
     pub fn set_indicated_airspeed(&mut self, indicated_airspeed: Velocity) {
         self.reader_writer.write_f64(
             UpdateContext::INDICATED_AIRSPEED_KEY,
@@ -141,6 +190,23 @@ impl SimulationTestBed {
         );
     }
 
+    pub fn set_true_airspeed(&mut self, true_airspeed: Velocity) {
+        self.reader_writer.write_f64(
+            UpdateContext::TRUE_AIRSPEED_KEY,
+            true_airspeed.get::<knot>(),
+        );
+    }
+
+    pub fn set_mach_number(&mut self, mach_number: f64) {
+        self.reader_writer
+            .write_f64(UpdateContext::MACH_NUMBER_KEY, mach_number);
+    }
+
+    pub fn set_ground_speed(&mut self, ground_speed: Velocity) {
+        self.reader_writer
+            .write_f64(UpdateContext::GROUND_SPEED_KEY, ground_speed.get::<knot>());
+    }
+
     pub fn set_indicated_altitude(&mut self, indicated_altitude: Length) {
         self.reader_writer.write_f64(
             UpdateContext::INDICATED_ALTITUDE_KEY,
@@ -148,6 +214,27 @@ impl SimulationTestBed {
         );
     }
 
+    pub fn set_pressure_altitude(&mut self, pressure_altitude: Length) {
+        self.reader_writer.write_f64(
+            UpdateContext::PRESSURE_ALTITUDE_KEY,
+            pressure_altitude.get::<foot>(),
+        );
+    }
+
+    pub fn set_radio_altitude(&mut self, radio_altitude: Length) {
+        self.reader_writer.write_f64(
+            UpdateContext::RADIO_ALTITUDE_KEY,
+            radio_altitude.get::<foot>(),
+        );
+    }
+
+    pub fn set_vertical_speed(&mut self, vertical_speed: Velocity) {
+        self.reader_writer.write_f64(
+            UpdateContext::VERTICAL_SPEED_KEY,
+            vertical_speed.get::<foot_per_minute>(),
+        );
+    }
+
     pub fn set_ambient_temperature(&mut self, ambient_temperature: ThermodynamicTemperature) {
         self.reader_writer.write_f64(
             UpdateContext::AMBIENT_TEMPERATURE_KEY,
@@ -155,16 +242,72 @@ impl SimulationTestBed {
         );
     }
 
+    pub fn set_ambient_pressure(&mut self, ambient_pressure: Pressure) {
+        self.reader_writer.write_f64(
+            UpdateContext::AMBIENT_PRESSURE_KEY,
+            ambient_pressure.get::<inch_of_mercury>(),
+        );
+    }
+
+    /// Sets the pitch, which is positive when the nose is above the horizon.
+    pub fn set_pitch(&mut self, pitch: Angle) {
+        // The simulator's pitch is positive when the nose is below the horizon.
+        self.reader_writer
+            .write_f64(UpdateContext::PITCH_KEY, -pitch.get::<degree>());
+    }
+
+    /// Sets the bank, which is positive when the right wing is below the horizon.
+    pub fn set_bank(&mut self, bank: Angle) {
+        // The simulator's bank is positive when the left wing is below the horizon.
+        self.reader_writer
+            .write_f64(UpdateContext::BANK_KEY, -bank.get::<degree>());
+    }
+
+    pub fn set_accelerations(
+        &mut self,
+        longitudinal: Acceleration,
+        lateral: Acceleration,
+        vertical: Acceleration,
+    ) {
+        self.reader_writer.write_f64(
+            UpdateContext::LONGITUDINAL_ACCELERATION_KEY,
+            longitudinal.get::<foot_per_second_squared>(),
+        );
+        self.reader_writer.write_f64(
+            UpdateContext::LATERAL_ACCELERATION_KEY,
+            lateral.get::<foot_per_second_squared>(),
+        );
+        self.reader_writer.write_f64(
+            UpdateContext::VERTICAL_ACCELERATION_KEY,
+            vertical.get::<foot_per_second_squared>(),
+        );
+    }
+
+    pub fn set_load_factor(&mut self, load_factor: f64) {
+        self.reader_writer
+            .write_f64(UpdateContext::LOAD_FACTOR_KEY, load_factor);
+    }
+
     pub fn set_on_ground(&mut self, on_ground: bool) {
         self.reader_writer
             .write_bool(UpdateContext::IS_ON_GROUND_KEY, on_ground);
     }
 
+    pub fn activate_failure(&mut self, failure_type: FailureType) {
+        self.reader_writer
+            .write_bool(&failure_type.is_active_id(), true);
+    }
+
+    pub fn deactivate_failure(&mut self, failure_type: FailureType) {
+        self.reader_writer
+            .write_bool(&failure_type.is_active_id(), false);
+    }
+
     pub fn supplied_power_fn<T: Fn() -> SuppliedPower + 'static>(
         mut self,
         supplied_power_fn: T,
     ) -> Self {
-        self.get_supplied_power_fn = Box::new(supplied_power_fn);
+        self.get_supplied_power_fn = Rc::new(supplied_power_fn);
         self
     }
 
@@ -197,20 +340,20 @@ impl Default for SimulationTestBed {
 struct TestAircraft<'a, T: SimulationElement, U: Fn(&mut T, &UpdateContext)> {
     element: &'a mut T,
     update_fn: U,
-    supplied_power: Option<SuppliedPower>,
+    get_supplied_power_fn: Rc<dyn Fn() -> SuppliedPower>,
     update_before_power_distribution: bool,
 }
 impl<'a, T: SimulationElement, U: Fn(&mut T, &UpdateContext)> TestAircraft<'a, T, U> {
     fn new(
         element: &'a mut T,
         update_fn: U,
-        supplied_power: SuppliedPower,
+        get_supplied_power_fn: Rc<dyn Fn() -> SuppliedPower>,
         update_before_power_distribution: bool,
     ) -> Self {
         Self {
             element,
             update_fn,
-            supplied_power: Some(supplied_power),
+            get_supplied_power_fn,
             update_before_power_distribution,
         }
     }
@@ -229,59 +372,14 @@ impl<'a, T: SimulationElement, U: Fn(&mut T, &UpdateContext)> Aircraft for TestA
     }
 
     fn get_supplied_power(&mut self) -> SuppliedPower {
-        self.supplied_power.take().unwrap()
+        (self.get_supplied_power_fn)()
     }
 }
 impl<'a, T: SimulationElement, U: Fn(&mut T, &UpdateContext)> SimulationElement
     for TestAircraft<'a, T, U>
 {
     fn accept<W: SimulationElementVisitor>(&mut self, visitor: &mut W) {
-        visitor.visit(self.element);
-    }
-}
-
-struct TestReaderWriter {
-    variables: HashMap<String, f64>,
-}
-impl TestReaderWriter {
-    fn new() -> Self {
-        Self {
-            variables: HashMap::new(),
-        }
-    }
-
-    fn contains_key(&self, name: &str) -> bool {
-        self.variables.contains_key(name)
-    }
-
-    fn write_bool(&mut self, name: &str, value: bool) {
-        self.write(name, from_bool(value));
-    }
-
-    fn write_f64(&mut self, name: &str, value: f64) {
-        self.write(name, value);
-    }
-
-    fn read_bool(&mut self, name: &str) -> bool {
-        to_bool(self.read(name))
-    }
-
-    fn read_f64(&mut self, name: &str) -> f64 {
-        self.read(name)
-    }
-}
-impl SimulatorReaderWriter for TestReaderWriter {
-    fn read(&mut self, name: &str) -> f64 {
-        *self.variables.get(name).unwrap_or(&0.)
-    }
-
-    fn write(&mut self, name: &str, value: f64) {
-        self.variables.insert(name.to_owned(), value);
-    }
-}
-impl Default for TestReaderWriter {
-    fn default() -> Self {
-        Self::new()
+        self.element.accept(visitor);
     }
 }
 
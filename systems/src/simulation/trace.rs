@@ -0,0 +1,708 @@
+use super::{Aircraft, Simulation, SimulatorReaderWriter, VariableIdentifier};
+use std::{collections::HashMap, fmt, str::FromStr, time::Duration};
+
+/// The reads and writes of a single simulation tick.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulatorTraceTick {
+    delta: Duration,
+    random_seed: u64,
+    reads: Vec<(String, f64)>,
+    writes: Vec<(String, f64)>,
+}
+impl SimulatorTraceTick {
+    fn new(delta: Duration, random_seed: u64) -> Self {
+        Self {
+            delta,
+            random_seed,
+            reads: Vec::new(),
+            writes: Vec::new(),
+        }
+    }
+
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn random_seed(&self) -> u64 {
+        self.random_seed
+    }
+
+    pub fn reads(&self) -> &[(String, f64)] {
+        &self.reads
+    }
+
+    pub fn writes(&self) -> &[(String, f64)] {
+        &self.writes
+    }
+}
+
+/// Contains every simulator variable read and written by the [`Simulation`] during
+/// a number of ticks. A trace recorded in the simulator can be replayed on any machine
+/// to reproduce the behaviour of the aircraft's systems.
+///
+/// The trace is stored in a line based text format. Each tick starts with a `tick`
+/// line containing the delta in nanoseconds and the random seed of the tick, followed
+/// by `r` (read) and `w` (write) lines containing the value and name of the variable.
+///
+/// # Examples
+/// ```rust
+/// # use std::time::Duration;
+/// # use systems::simulation::{Aircraft, RecordingSimulatorReaderWriter, SimulationElement,
+/// #    SimulatorReader, SimulatorTrace, SimulatorWriter, Simulation, InMemoryReaderWriter,
+/// #    QuantityIdentifier, VariableRegistry, VariableUnit};
+/// struct MyAircraft {
+///     pb_is_on_id: QuantityIdentifier<bool>,
+///     light_is_on_id: QuantityIdentifier<bool>,
+///     is_on: bool,
+/// }
+/// impl MyAircraft {
+///     fn new(registry: &mut VariableRegistry) -> Self {
+///         Self {
+///             pb_is_on_id: registry.register_read("MY_PB_IS_ON", VariableUnit::Bool),
+///             light_is_on_id: registry.register_write("MY_LIGHT_IS_ON", VariableUnit::Bool),
+///             is_on: false,
+///         }
+///     }
+/// }
+/// impl Aircraft for MyAircraft {}
+/// impl SimulationElement for MyAircraft {
+///     fn read(&mut self, reader: &mut SimulatorReader) {
+///         self.is_on = reader.read_bool(&self.pb_is_on_id);
+///     }
+///
+///     fn write(&self, writer: &mut SimulatorWriter) {
+///         writer.write_bool(&self.light_is_on_id, self.is_on);
+///     }
+/// }
+///
+/// let mut registry = VariableRegistry::new();
+/// let mut recorder = RecordingSimulatorReaderWriter::new(InMemoryReaderWriter::new());
+/// let mut aircraft = MyAircraft::new(&mut registry);
+/// Simulation::new(&mut aircraft, &mut recorder).tick(Duration::from_millis(50));
+///
+/// let trace: SimulatorTrace = recorder.into_trace().to_string().parse().unwrap();
+/// assert!(trace.replay(&mut MyAircraft::new(&mut registry)).is_empty());
+/// ```
+/// [`Simulation`]: struct.Simulation.html
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SimulatorTrace {
+    ticks: Vec<SimulatorTraceTick>,
+}
+impl SimulatorTrace {
+    /// The version of the trace format.
+    pub const VERSION: u32 = 2;
+
+    pub fn new() -> Self {
+        Self { ticks: Vec::new() }
+    }
+
+    pub fn ticks(&self) -> &[SimulatorTraceTick] {
+        &self.ticks
+    }
+
+    /// Runs the given aircraft through every tick of the trace, feeding it the recorded
+    /// reads and random seeds. Returns the writes which differ from those recorded in the trace.
+    /// An empty result indicates the aircraft behaves exactly as it did while recording.
+    pub fn replay<T: Aircraft>(&self, aircraft: &mut T) -> Vec<SimulatorTraceDivergence> {
+        self.replay_with(aircraft, None)
+    }
+
+    /// Replays the trace like [`replay`] does, splitting ticks into sub-steps no larger
+    /// than `max_step`. Use this for traces recorded by a simulation with the same maximum step.
+    ///
+    /// [`replay`]: #method.replay
+    pub fn replay_with_max_step<T: Aircraft>(
+        &self,
+        aircraft: &mut T,
+        max_step: Duration,
+    ) -> Vec<SimulatorTraceDivergence> {
+        self.replay_with(aircraft, Some(max_step))
+    }
+
+    fn replay_with<T: Aircraft>(
+        &self,
+        aircraft: &mut T,
+        max_step: Option<Duration>,
+    ) -> Vec<SimulatorTraceDivergence> {
+        let mut reader_writer = ReplaySimulatorReaderWriter::new(self);
+        let mut simulation = Simulation::new_with_random_seed(aircraft, &mut reader_writer, 0);
+        if let Some(max_step) = max_step {
+            simulation = simulation.with_max_step(max_step);
+        }
+
+        for tick in &self.ticks {
+            simulation.tick_with_random_seed(tick.delta(), tick.random_seed());
+        }
+
+        reader_writer.into_divergences()
+    }
+
+    fn begin_tick(&mut self, delta: Duration, random_seed: u64) {
+        self.ticks.push(SimulatorTraceTick::new(delta, random_seed));
+    }
+
+    fn record_read(&mut self, name: &str, value: f64) {
+        if let Some(tick) = self.ticks.last_mut() {
+            tick.reads.push((name.to_owned(), value));
+        }
+    }
+
+    fn record_write(&mut self, name: &str, value: f64) {
+        if let Some(tick) = self.ticks.last_mut() {
+            tick.writes.push((name.to_owned(), value));
+        }
+    }
+}
+impl fmt::Display for SimulatorTrace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "version {}", SimulatorTrace::VERSION)?;
+        for tick in &self.ticks {
+            writeln!(f, "tick {} {}", tick.delta.as_nanos(), tick.random_seed)?;
+            for (name, value) in &tick.reads {
+                writeln!(f, "r {} {}", value, name)?;
+            }
+            for (name, value) in &tick.writes {
+                writeln!(f, "w {} {}", value, name)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+impl FromStr for SimulatorTrace {
+    type Err = SimulatorTraceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().enumerate().filter(|(_, line)| !line.is_empty());
+        match lines.next() {
+            Some((_, line)) if line == format!("version {}", SimulatorTrace::VERSION) => {}
+            Some((number, _)) => {
+                return Err(SimulatorTraceError::new(number, "unsupported version"))
+            }
+            None => return Err(SimulatorTraceError::new(0, "missing version")),
+        }
+
+        let mut trace = SimulatorTrace::new();
+        for (number, line) in lines {
+            let mut parts = line.splitn(3, ' ');
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some("tick"), Some(nanos), Some(random_seed)) => trace.begin_tick(
+                    Duration::from_nanos(
+                        nanos
+                            .parse()
+                            .map_err(|_| SimulatorTraceError::new(number, "invalid delta"))?,
+                    ),
+                    random_seed
+                        .parse()
+                        .map_err(|_| SimulatorTraceError::new(number, "invalid random seed"))?,
+                ),
+                (Some(kind @ "r"), Some(value), Some(name))
+                | (Some(kind @ "w"), Some(value), Some(name)) => {
+                    let value = value
+                        .parse()
+                        .map_err(|_| SimulatorTraceError::new(number, "invalid value"))?;
+                    let tick = trace
+                        .ticks
+                        .last_mut()
+                        .ok_or_else(|| SimulatorTraceError::new(number, "missing tick"))?;
+
+                    if kind == "r" {
+                        tick.reads.push((name.to_owned(), value));
+                    } else {
+                        tick.writes.push((name.to_owned(), value));
+                    }
+                }
+                _ => return Err(SimulatorTraceError::new(number, "unknown line")),
+            }
+        }
+
+        Ok(trace)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulatorTraceError {
+    line: usize,
+    reason: &'static str,
+}
+impl SimulatorTraceError {
+    fn new(line_index: usize, reason: &'static str) -> Self {
+        Self {
+            line: line_index + 1,
+            reason,
+        }
+    }
+}
+impl fmt::Display for SimulatorTraceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "malformed trace at line {}: {}", self.line, self.reason)
+    }
+}
+impl std::error::Error for SimulatorTraceError {}
+
+/// A write which differs between the recorded trace and its replay.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulatorTraceDivergence {
+    tick: usize,
+    name: String,
+    recorded: Option<f64>,
+    replayed: f64,
+}
+impl SimulatorTraceDivergence {
+    /// The zero based index of the tick in which the divergence occurred.
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The recorded value, or `None` when the variable wasn't written while recording.
+    pub fn recorded(&self) -> Option<f64> {
+        self.recorded
+    }
+
+    pub fn replayed(&self) -> f64 {
+        self.replayed
+    }
+}
+impl fmt::Display for SimulatorTraceDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.recorded {
+            Some(recorded) => write!(
+                f,
+                "tick {}: {} was {} but is now {}",
+                self.tick, self.name, recorded, self.replayed
+            ),
+            None => write!(
+                f,
+                "tick {}: {} was not written but is now {}",
+                self.tick, self.name, self.replayed
+            ),
+        }
+    }
+}
+
+/// Wraps a [`SimulatorReaderWriter`] to record every read and write into a [`SimulatorTrace`].
+/// Recording can be paused, in which case reads and writes are only passed on.
+///
+/// Writes which the [`Simulation`] skips as their value didn't change are recorded as well,
+/// as a replay writes every value.
+///
+/// [`SimulatorReaderWriter`]: trait.SimulatorReaderWriter.html
+/// [`SimulatorTrace`]: struct.SimulatorTrace.html
+/// [`Simulation`]: struct.Simulation.html
+pub struct RecordingSimulatorReaderWriter<T: SimulatorReaderWriter> {
+    reader_writer: T,
+    trace: SimulatorTrace,
+    is_recording: bool,
+    is_recording_tick: bool,
+}
+impl<T: SimulatorReaderWriter> RecordingSimulatorReaderWriter<T> {
+    pub fn new(reader_writer: T) -> Self {
+        Self {
+            reader_writer,
+            trace: SimulatorTrace::new(),
+            is_recording: true,
+            is_recording_tick: false,
+        }
+    }
+
+    /// Creates a recorder which doesn't record until recording is resumed.
+    pub fn paused(reader_writer: T) -> Self {
+        Self {
+            is_recording: false,
+            ..Self::new(reader_writer)
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.is_recording
+    }
+
+    /// Pauses or resumes recording. Takes effect from the next tick onwards.
+    pub fn set_recording(&mut self, is_recording: bool) {
+        self.is_recording = is_recording;
+    }
+
+    pub fn trace(&self) -> &SimulatorTrace {
+        &self.trace
+    }
+
+    /// Removes the recorded ticks from the recorder, such that
+    /// recording can continue with an empty trace.
+    pub fn take_trace(&mut self) -> SimulatorTrace {
+        std::mem::take(&mut self.trace)
+    }
+
+    pub fn into_trace(self) -> SimulatorTrace {
+        self.trace
+    }
+}
+impl<T: SimulatorReaderWriter> SimulatorReaderWriter for RecordingSimulatorReaderWriter<T> {
+    fn begin_tick(&mut self, delta: Duration, random_seed: u64) {
+        self.reader_writer.begin_tick(delta, random_seed);
+        self.is_recording_tick = self.is_recording;
+        if self.is_recording_tick {
+            self.trace.begin_tick(delta, random_seed);
+        }
+    }
+
+    fn read(&mut self, identifier: &VariableIdentifier) -> f64 {
+        let value = self.reader_writer.read(identifier);
+        if self.is_recording_tick {
+            self.trace.record_read(identifier.name(), value);
+        }
+
+        value
+    }
+
+    fn write(&mut self, identifier: &VariableIdentifier, value: f64) {
+        if self.is_recording_tick {
+            self.trace.record_write(identifier.name(), value);
+        }
+        self.reader_writer.write(identifier, value);
+    }
+
+    fn write_unchanged(&mut self, identifier: &VariableIdentifier, value: f64) {
+        if self.is_recording_tick {
+            self.trace.record_write(identifier.name(), value);
+        }
+        self.reader_writer.write_unchanged(identifier, value);
+    }
+}
+
+/// Feeds the reads of a recorded [`SimulatorTrace`] back into the [`Simulation`],
+/// while comparing the written values to those recorded.
+/// Variables which weren't read while recording read as 0.0.
+///
+/// [`SimulatorTrace`]: struct.SimulatorTrace.html
+/// [`Simulation`]: struct.Simulation.html
+pub struct ReplaySimulatorReaderWriter<'a> {
+    trace: &'a SimulatorTrace,
+    tick: Option<usize>,
+    reads: HashMap<&'a str, f64>,
+    writes: HashMap<&'a str, f64>,
+    divergences: Vec<SimulatorTraceDivergence>,
+}
+impl<'a> ReplaySimulatorReaderWriter<'a> {
+    pub fn new(trace: &'a SimulatorTrace) -> Self {
+        Self {
+            trace,
+            tick: None,
+            reads: HashMap::new(),
+            writes: HashMap::new(),
+            divergences: Vec::new(),
+        }
+    }
+
+    pub fn divergences(&self) -> &[SimulatorTraceDivergence] {
+        &self.divergences
+    }
+
+    pub fn into_divergences(self) -> Vec<SimulatorTraceDivergence> {
+        self.divergences
+    }
+}
+impl<'a> SimulatorReaderWriter for ReplaySimulatorReaderWriter<'a> {
+    fn begin_tick(&mut self, _: Duration, _: u64) {
+        let index = self.tick.map_or(0, |tick| tick + 1);
+        self.tick = Some(index);
+
+        let tick = self.trace.ticks.get(index);
+        self.reads = tick.map_or_else(HashMap::new, |tick| {
+            tick.reads
+                .iter()
+                .map(|(name, value)| (name.as_str(), *value))
+                .collect()
+        });
+        self.writes = tick.map_or_else(HashMap::new, |tick| {
+            tick.writes
+                .iter()
+                .map(|(name, value)| (name.as_str(), *value))
+                .collect()
+        });
+    }
+
+    fn read(&mut self, identifier: &VariableIdentifier) -> f64 {
+        *self.reads.get(identifier.name()).unwrap_or(&0.)
+    }
+
+    fn write(&mut self, identifier: &VariableIdentifier, value: f64) {
+        let name = identifier.name();
+        let recorded = self.writes.get(name).copied();
+        let is_equal = matches!(recorded, Some(recorded)
+            if recorded == value || (recorded.is_nan() && value.is_nan()));
+        if !is_equal {
+            self.divergences.push(SimulatorTraceDivergence {
+                tick: self.tick.unwrap_or(0),
+                name: name.to_owned(),
+                recorded,
+                replayed: value,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{
+        InMemoryReaderWriter, QuantityIdentifier, SimulationElement, SimulatorReader,
+        SimulatorWriter, UpdateContext, VariableRegistry, VariableUnit,
+    };
+
+    struct TestAircraft {
+        n_id: QuantityIdentifier<f64>,
+        double_n_id: QuantityIdentifier<f64>,
+        elapsed_id: QuantityIdentifier<f64>,
+        random_number_id: QuantityIdentifier<f64>,
+        n: f64,
+        elapsed: f64,
+        offset: f64,
+        random_number: u8,
+    }
+    impl TestAircraft {
+        fn new() -> Self {
+            Self::with_registry(&mut VariableRegistry::new())
+        }
+
+        fn with_registry(registry: &mut VariableRegistry) -> Self {
+            Self {
+                n_id: registry.register_read("TEST N", VariableUnit::Number),
+                double_n_id: registry.register_write("TEST_DOUBLE_N", VariableUnit::Number),
+                elapsed_id: registry.register_write("TEST_ELAPSED", VariableUnit::Number),
+                random_number_id: registry
+                    .register_write("TEST_RANDOM_NUMBER", VariableUnit::Number),
+                n: 0.,
+                elapsed: 0.,
+                offset: 0.,
+                random_number: 0,
+            }
+        }
+
+        fn with_offset(offset: f64) -> Self {
+            Self {
+                offset,
+                ..Self::new()
+            }
+        }
+    }
+    impl Aircraft for TestAircraft {
+        fn update_before_power_distribution(&mut self, context: &UpdateContext) {
+            self.elapsed += context.delta().as_secs_f64();
+            self.random_number = context.random_number();
+        }
+    }
+    impl SimulationElement for TestAircraft {
+        fn read(&mut self, reader: &mut SimulatorReader) {
+            self.n = reader.read_f64(&self.n_id);
+        }
+
+        fn write(&self, writer: &mut SimulatorWriter) {
+            writer.write_f64(&self.double_n_id, self.n * 2. + self.offset);
+            writer.write_f64(&self.elapsed_id, self.elapsed);
+            writer.write_f64(&self.random_number_id, self.random_number as f64);
+        }
+    }
+
+    fn record(ticks: &[(f64, Duration)]) -> SimulatorTrace {
+        let mut recorder = RecordingSimulatorReaderWriter::new(InMemoryReaderWriter::new());
+        let mut aircraft = TestAircraft::new();
+        let n_id = VariableRegistry::new().register_write("TEST N", VariableUnit::Number);
+        for (n, delta) in ticks {
+            recorder.write(&n_id, *n);
+            Simulation::new(&mut aircraft, &mut recorder).tick(*delta);
+        }
+
+        recorder.into_trace()
+    }
+
+    fn find(values: &[(String, f64)], name: &str) -> Option<f64> {
+        values
+            .iter()
+            .find(|(value_name, _)| value_name == name)
+            .map(|(_, value)| *value)
+    }
+
+    #[test]
+    fn records_delta_reads_and_writes_per_tick() {
+        let trace = record(&[
+            (1., Duration::from_millis(50)),
+            (2., Duration::from_millis(100)),
+        ]);
+
+        assert_eq!(trace.ticks().len(), 2);
+        assert_eq!(trace.ticks()[1].delta(), Duration::from_millis(100));
+        assert_eq!(find(trace.ticks()[1].reads(), "TEST N"), Some(2.));
+        assert_eq!(find(trace.ticks()[1].writes(), "TEST_DOUBLE_N"), Some(4.));
+    }
+
+    #[test]
+    fn does_not_record_while_paused() {
+        let mut recorder = RecordingSimulatorReaderWriter::paused(InMemoryReaderWriter::new());
+        let mut aircraft = TestAircraft::new();
+        Simulation::new(&mut aircraft, &mut recorder).tick(Duration::from_millis(50));
+        recorder.set_recording(true);
+        Simulation::new(&mut aircraft, &mut recorder).tick(Duration::from_millis(100));
+        recorder.set_recording(false);
+        Simulation::new(&mut aircraft, &mut recorder).tick(Duration::from_millis(150));
+
+        let trace = recorder.into_trace();
+        assert_eq!(trace.ticks().len(), 1);
+        assert_eq!(trace.ticks()[0].delta(), Duration::from_millis(100));
+        assert!(!trace.ticks()[0].writes().is_empty());
+    }
+
+    #[test]
+    fn does_not_record_outside_of_ticks() {
+        let trace = record(&[]);
+
+        assert!(trace.ticks().is_empty());
+    }
+
+    #[test]
+    fn replay_of_same_aircraft_has_no_divergences() {
+        let trace = record(&[
+            (1., Duration::from_millis(50)),
+            (2., Duration::from_millis(100)),
+        ]);
+
+        assert!(trace.replay(&mut TestAircraft::new()).is_empty());
+    }
+
+    #[test]
+    fn replay_of_recording_with_change_only_writes_has_no_divergences() {
+        let mut registry = VariableRegistry::new();
+        let mut aircraft = TestAircraft::with_registry(&mut registry);
+        let mut reader_writer = InMemoryReaderWriter::new();
+        reader_writer.write_f64("TEST N", 1.);
+        let mut recorder = RecordingSimulatorReaderWriter::new(reader_writer);
+        let mut simulation = Simulation::new(&mut aircraft, &mut recorder)
+            .with_change_only_writes(Duration::from_secs(10), &registry);
+        for _ in 0..3 {
+            simulation.tick(Duration::from_millis(50));
+        }
+        assert!(simulation.write_statistics().skipped() > 0);
+
+        let trace = recorder.into_trace();
+        assert!(trace
+            .ticks()
+            .iter()
+            .all(|tick| find(tick.writes(), "TEST_DOUBLE_N") == Some(2.)));
+        assert!(trace.replay(&mut TestAircraft::new()).is_empty());
+    }
+
+    #[test]
+    fn records_random_seed_per_tick() {
+        let mut recorder = RecordingSimulatorReaderWriter::new(InMemoryReaderWriter::new());
+        Simulation::new_with_random_seed(&mut TestAircraft::new(), &mut recorder, 1)
+            .tick(Duration::from_millis(50));
+        Simulation::new_with_random_seed(&mut TestAircraft::new(), &mut recorder, 2)
+            .tick(Duration::from_millis(50));
+
+        let trace = recorder.into_trace();
+        assert_ne!(
+            trace.ticks()[0].random_seed(),
+            trace.ticks()[1].random_seed()
+        );
+    }
+
+    #[test]
+    fn replay_reproduces_random_numbers() {
+        let trace = record(&[
+            (1., Duration::from_millis(50)),
+            (1., Duration::from_millis(50)),
+            (1., Duration::from_millis(50)),
+        ]);
+
+        let mut aircraft = TestAircraft::new();
+        assert!(trace.replay(&mut aircraft).is_empty());
+        assert_eq!(
+            find(trace.ticks()[2].writes(), "TEST_RANDOM_NUMBER"),
+            Some(aircraft.random_number as f64)
+        );
+    }
+
+    #[test]
+    fn replay_with_max_step_reproduces_sub_stepped_recording() {
+        let mut recorder = RecordingSimulatorReaderWriter::new(InMemoryReaderWriter::new());
+        let mut aircraft = TestAircraft::new();
+        Simulation::new(&mut aircraft, &mut recorder)
+            .with_max_step(Duration::from_millis(20))
+            .tick(Duration::from_millis(50));
+        let trace = recorder.into_trace();
+
+        assert!(trace
+            .replay_with_max_step(&mut TestAircraft::new(), Duration::from_millis(20))
+            .is_empty());
+    }
+
+    #[test]
+    fn replay_feeds_recorded_reads_and_deltas() {
+        let trace = record(&[
+            (1., Duration::from_millis(50)),
+            (3., Duration::from_millis(100)),
+        ]);
+
+        let mut aircraft = TestAircraft::new();
+        trace.replay(&mut aircraft);
+
+        assert_eq!(aircraft.n, 3.);
+        assert!((aircraft.elapsed - 0.15).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn replay_of_changed_aircraft_reports_divergences() {
+        let trace = record(&[(1., Duration::from_millis(50))]);
+
+        let divergences = trace.replay(&mut TestAircraft::with_offset(1.));
+
+        assert_eq!(divergences.len(), 1);
+        assert_eq!(divergences[0].tick(), 0);
+        assert_eq!(divergences[0].name(), "TEST_DOUBLE_N");
+        assert_eq!(divergences[0].recorded(), Some(2.));
+        assert_eq!(divergences[0].replayed(), 3.);
+    }
+
+    #[test]
+    fn text_round_trip_is_lossless() {
+        let trace = record(&[
+            (0.1 + 0.2, Duration::from_nanos(16_666_667)),
+            (-1e-300, Duration::from_millis(100)),
+        ]);
+
+        assert_eq!(trace.to_string().parse(), Ok(trace));
+    }
+
+    #[test]
+    fn replay_treats_not_a_number_as_equal() {
+        let trace = record(&[(f64::NAN, Duration::from_millis(50))]);
+
+        assert!(trace.replay(&mut TestAircraft::new()).is_empty());
+    }
+
+    #[test]
+    fn text_contains_names_with_spaces() {
+        let trace = record(&[(1., Duration::from_millis(50))]);
+
+        assert!(trace.to_string().contains("\nr 1 TEST N\n"));
+    }
+
+    #[test]
+    fn unsupported_version_is_rejected() {
+        assert_eq!(
+            "version 1\n".parse::<SimulatorTrace>(),
+            Err(SimulatorTraceError::new(0, "unsupported version"))
+        );
+    }
+
+    #[test]
+    fn read_before_tick_is_rejected() {
+        assert_eq!(
+            "version 2\nr 1 TEST N\n".parse::<SimulatorTrace>(),
+            Err(SimulatorTraceError::new(1, "missing tick"))
+        );
+    }
+}
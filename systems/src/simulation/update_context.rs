@@ -1,57 +1,234 @@
 use std::time::Duration;
-use uom::si::{f64::*, length::foot, thermodynamic_temperature::degree_celsius, velocity::knot};
+use uom::si::f64::*;
 
-use super::SimulatorReader;
+use super::{
+    profiling::UpdateProfiler, QuantityIdentifier, SimulatorReader, VariableRegistry, VariableUnit,
+};
+use crate::shared::RandomNumberGenerator;
 
 /// Provides data unowned by any system in the aircraft system simulation
 /// for the purpose of handling a simulation tick.
+///
+/// The delta is expressed in simulated time. When the simulator runs at an increased
+/// simulation rate, the delta is the frame's duration multiplied by that rate.
+/// Elements which shouldn't be accelerated can use the [`simulation_rate`] to
+/// scale or clamp their behaviour.
+///
+/// The pitch is positive when the aircraft's nose is above the horizon and the bank
+/// is positive when the right wing is below the horizon. Accelerations are expressed
+/// in the aircraft's body axes.
+///
+/// When the [`Simulation`] is profiling, the aircraft can measure the duration of
+/// its updates through the context.
+///
+/// [`simulation_rate`]: #method.simulation_rate
+/// [`Simulation`]: struct.Simulation.html
 #[derive(Debug)]
 pub struct UpdateContext {
     delta: Duration,
+    simulation_rate: f64,
     indicated_airspeed: Velocity,
+    true_airspeed: Velocity,
+    mach_number: f64,
+    ground_speed: Velocity,
     indicated_altitude: Length,
+    pressure_altitude: Length,
+    radio_altitude: Length,
+    vertical_speed: Velocity,
     ambient_temperature: ThermodynamicTemperature,
+    ambient_pressure: Pressure,
+    pitch: Angle,
+    bank: Angle,
+    longitudinal_acceleration: Acceleration,
+    lateral_acceleration: Acceleration,
+    vertical_acceleration: Acceleration,
+    load_factor: f64,
     is_on_ground: bool,
+    random: RandomNumberGenerator,
+    profiler: UpdateProfiler,
 }
 impl UpdateContext {
+    pub(crate) const AMBIENT_PRESSURE_KEY: &'static str = "AMBIENT PRESSURE";
     pub(crate) const AMBIENT_TEMPERATURE_KEY: &'static str = "AMBIENT TEMPERATURE";
+    pub(crate) const BANK_KEY: &'static str = "PLANE BANK DEGREES";
+    pub(crate) const GROUND_SPEED_KEY: &'static str = "GROUND VELOCITY";
     pub(crate) const INDICATED_AIRSPEED_KEY: &'static str = "AIRSPEED INDICATED";
     pub(crate) const INDICATED_ALTITUDE_KEY: &'static str = "INDICATED ALTITUDE";
     pub(crate) const IS_ON_GROUND_KEY: &'static str = "SIM ON GROUND";
+    pub(crate) const LATERAL_ACCELERATION_KEY: &'static str = "ACCELERATION BODY X";
+    pub(crate) const LOAD_FACTOR_KEY: &'static str = "G FORCE";
+    pub(crate) const LONGITUDINAL_ACCELERATION_KEY: &'static str = "ACCELERATION BODY Z";
+    pub(crate) const MACH_NUMBER_KEY: &'static str = "AIRSPEED MACH";
+    pub(crate) const PITCH_KEY: &'static str = "PLANE PITCH DEGREES";
+    pub(crate) const PRESSURE_ALTITUDE_KEY: &'static str = "PRESSURE ALTITUDE";
+    pub(crate) const RADIO_ALTITUDE_KEY: &'static str = "RADIO HEIGHT";
+    pub(crate) const SIMULATION_RATE_KEY: &'static str = "SIMULATION RATE";
+    pub(crate) const TRUE_AIRSPEED_KEY: &'static str = "AIRSPEED TRUE";
+    pub(crate) const VERTICAL_ACCELERATION_KEY: &'static str = "ACCELERATION BODY Y";
+    pub(crate) const VERTICAL_SPEED_KEY: &'static str = "VERTICAL SPEED";
 
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         delta: Duration,
+        simulation_rate: f64,
         indicated_airspeed: Velocity,
+        true_airspeed: Velocity,
+        mach_number: f64,
+        ground_speed: Velocity,
         indicated_altitude: Length,
+        pressure_altitude: Length,
+        radio_altitude: Length,
+        vertical_speed: Velocity,
         ambient_temperature: ThermodynamicTemperature,
+        ambient_pressure: Pressure,
+        pitch: Angle,
+        bank: Angle,
+        longitudinal_acceleration: Acceleration,
+        lateral_acceleration: Acceleration,
+        vertical_acceleration: Acceleration,
+        load_factor: f64,
         is_on_ground: bool,
+        random_seed: u64,
     ) -> UpdateContext {
         UpdateContext {
             delta,
+            simulation_rate,
             indicated_airspeed,
+            true_airspeed,
+            mach_number,
+            ground_speed,
             indicated_altitude,
+            pressure_altitude,
+            radio_altitude,
+            vertical_speed,
             ambient_temperature,
+            ambient_pressure,
+            pitch,
+            bank,
+            longitudinal_acceleration,
+            lateral_acceleration,
+            vertical_acceleration,
+            load_factor,
             is_on_ground,
+            random: RandomNumberGenerator::new(random_seed),
+            profiler: UpdateProfiler::default(),
         }
     }
 
-    /// Creates a context based on the data that was read from the simulator.
-    pub(super) fn from_reader(reader: &mut SimulatorReader, delta_time: Duration) -> UpdateContext {
-        UpdateContext {
-            ambient_temperature: ThermodynamicTemperature::new::<degree_celsius>(
-                reader.read_f64(UpdateContext::AMBIENT_TEMPERATURE_KEY),
+    /// Registers the variables from which the context is created.
+    pub(super) fn register_variables(registry: &mut VariableRegistry) -> UpdateContextVariables {
+        UpdateContextVariables {
+            ambient_pressure: registry.register_read(
+                UpdateContext::AMBIENT_PRESSURE_KEY,
+                VariableUnit::InchesOfMercury,
+            ),
+            ambient_temperature: registry.register_read(
+                UpdateContext::AMBIENT_TEMPERATURE_KEY,
+                VariableUnit::Celsius,
+            ),
+            bank: registry.register_read(UpdateContext::BANK_KEY, VariableUnit::Degrees),
+            ground_speed: registry
+                .register_read(UpdateContext::GROUND_SPEED_KEY, VariableUnit::Knots),
+            indicated_airspeed: registry
+                .register_read(UpdateContext::INDICATED_AIRSPEED_KEY, VariableUnit::Knots),
+            indicated_altitude: registry
+                .register_read(UpdateContext::INDICATED_ALTITUDE_KEY, VariableUnit::Feet),
+            is_on_ground: registry
+                .register_read(UpdateContext::IS_ON_GROUND_KEY, VariableUnit::Bool),
+            lateral_acceleration: registry.register_read(
+                UpdateContext::LATERAL_ACCELERATION_KEY,
+                VariableUnit::FeetPerSecondSquared,
             ),
-            indicated_airspeed: Velocity::new::<knot>(
-                reader.read_f64(UpdateContext::INDICATED_AIRSPEED_KEY),
+            load_factor: registry
+                .register_read(UpdateContext::LOAD_FACTOR_KEY, VariableUnit::GForce),
+            longitudinal_acceleration: registry.register_read(
+                UpdateContext::LONGITUDINAL_ACCELERATION_KEY,
+                VariableUnit::FeetPerSecondSquared,
             ),
-            indicated_altitude: Length::new::<foot>(
-                reader.read_f64(UpdateContext::INDICATED_ALTITUDE_KEY),
+            mach_number: registry.register_read(UpdateContext::MACH_NUMBER_KEY, VariableUnit::Mach),
+            pitch: registry.register_read(UpdateContext::PITCH_KEY, VariableUnit::Degrees),
+            pressure_altitude: registry
+                .register_read(UpdateContext::PRESSURE_ALTITUDE_KEY, VariableUnit::Feet),
+            radio_altitude: registry
+                .register_read(UpdateContext::RADIO_ALTITUDE_KEY, VariableUnit::Feet),
+            simulation_rate: registry
+                .register_read(UpdateContext::SIMULATION_RATE_KEY, VariableUnit::Number),
+            true_airspeed: registry
+                .register_read(UpdateContext::TRUE_AIRSPEED_KEY, VariableUnit::Knots),
+            vertical_acceleration: registry.register_read(
+                UpdateContext::VERTICAL_ACCELERATION_KEY,
+                VariableUnit::FeetPerSecondSquared,
             ),
-            is_on_ground: reader.read_bool(UpdateContext::IS_ON_GROUND_KEY),
-            delta: delta_time,
+            vertical_speed: registry.register_read(
+                UpdateContext::VERTICAL_SPEED_KEY,
+                VariableUnit::FeetPerMinute,
+            ),
+        }
+    }
+
+    /// Creates a context based on the data that was read from the simulator.
+    /// The given delta is the real time which passed, which is scaled by the simulation rate.
+    pub(super) fn from_reader(
+        reader: &mut SimulatorReader,
+        variables: &UpdateContextVariables,
+        delta_time: Duration,
+        random_seed: u64,
+    ) -> UpdateContext {
+        let simulation_rate =
+            UpdateContext::simulation_rate_from(reader.read_f64(&variables.simulation_rate));
+
+        UpdateContext {
+            ambient_temperature: reader.read_temperature(&variables.ambient_temperature),
+            ambient_pressure: reader.read_pressure(&variables.ambient_pressure),
+            indicated_airspeed: reader.read_velocity(&variables.indicated_airspeed),
+            true_airspeed: reader.read_velocity(&variables.true_airspeed),
+            mach_number: reader.read_f64(&variables.mach_number),
+            ground_speed: reader.read_velocity(&variables.ground_speed),
+            indicated_altitude: reader.read_length(&variables.indicated_altitude),
+            pressure_altitude: reader.read_length(&variables.pressure_altitude),
+            radio_altitude: reader.read_length(&variables.radio_altitude),
+            vertical_speed: reader.read_velocity(&variables.vertical_speed),
+            // The simulator's pitch is positive when the nose is below the horizon and
+            // its bank is positive when the left wing is below the horizon.
+            pitch: -reader.read_angle(&variables.pitch),
+            bank: -reader.read_angle(&variables.bank),
+            longitudinal_acceleration: reader
+                .read_acceleration(&variables.longitudinal_acceleration),
+            lateral_acceleration: reader.read_acceleration(&variables.lateral_acceleration),
+            vertical_acceleration: reader.read_acceleration(&variables.vertical_acceleration),
+            load_factor: reader.read_f64(&variables.load_factor),
+            is_on_ground: reader.read_bool(&variables.is_on_ground),
+            delta: delta_time.mul_f64(simulation_rate),
+            simulation_rate,
+            random: RandomNumberGenerator::new(random_seed),
+            profiler: UpdateProfiler::default(),
+        }
+    }
+
+    /// Simulators which don't provide a simulation rate run in real time.
+    fn simulation_rate_from(value: f64) -> f64 {
+        if value.is_finite() && value > 0. {
+            value
+        } else {
+            1.
         }
     }
 
+    /// Sets the delta of the part of the tick which is executed next. The random numbers
+    /// continue where the previous part left off, such that the random numbers drawn within
+    /// a tick don't depend on how the tick is split.
+    pub(super) fn set_delta(&mut self, delta: Duration) {
+        self.delta = delta;
+    }
+
+    pub(super) fn enable_profiling(&mut self) {
+        self.profiler = UpdateProfiler::enabled();
+    }
+
+    pub(super) fn profiler_mut(&mut self) -> &mut UpdateProfiler {
+        &mut self.profiler
+    }
+
     pub fn is_in_flight(&self) -> bool {
         !self.is_on_ground
     }
@@ -60,19 +237,132 @@ impl UpdateContext {
         self.delta
     }
 
+    /// The rate at which simulated time passes compared to real time.
+    /// For example, 4.0 when the simulator runs four times as fast as real time.
+    pub fn simulation_rate(&self) -> f64 {
+        self.simulation_rate
+    }
+
     pub fn indicated_airspeed(&self) -> Velocity {
         self.indicated_airspeed
     }
 
+    pub fn true_airspeed(&self) -> Velocity {
+        self.true_airspeed
+    }
+
+    pub fn mach_number(&self) -> f64 {
+        self.mach_number
+    }
+
+    pub fn ground_speed(&self) -> Velocity {
+        self.ground_speed
+    }
+
     pub fn indicated_altitude(&self) -> Length {
         self.indicated_altitude
     }
 
+    /// The altitude based on the standard pressure setting of 29.92 inHg.
+    pub fn pressure_altitude(&self) -> Length {
+        self.pressure_altitude
+    }
+
+    /// The height of the aircraft above the terrain below it.
+    pub fn radio_altitude(&self) -> Length {
+        self.radio_altitude
+    }
+
+    pub fn vertical_speed(&self) -> Velocity {
+        self.vertical_speed
+    }
+
     pub fn ambient_temperature(&self) -> ThermodynamicTemperature {
         self.ambient_temperature
     }
 
+    pub fn ambient_pressure(&self) -> Pressure {
+        self.ambient_pressure
+    }
+
+    pub fn pitch(&self) -> Angle {
+        self.pitch
+    }
+
+    pub fn bank(&self) -> Angle {
+        self.bank
+    }
+
+    pub fn longitudinal_acceleration(&self) -> Acceleration {
+        self.longitudinal_acceleration
+    }
+
+    pub fn lateral_acceleration(&self) -> Acceleration {
+        self.lateral_acceleration
+    }
+
+    pub fn vertical_acceleration(&self) -> Acceleration {
+        self.vertical_acceleration
+    }
+
+    /// The ratio of the lift to the aircraft's weight, 1.0 in level flight.
+    pub fn load_factor(&self) -> f64 {
+        self.load_factor
+    }
+
     pub fn is_on_ground(&self) -> bool {
         self.is_on_ground
     }
+
+    /// Returns a random number. The numbers are derived from the seed of the
+    /// tick, such that a tick with the same seed produces the same numbers.
+    pub fn random_number(&self) -> u8 {
+        self.random.next_u8()
+    }
+
+    /// Runs the given update, adding its duration to the named update such that it
+    /// is included in the [`ProfileReport`]. Nothing is measured when the simulation
+    /// isn't profiling.
+    ///
+    /// [`ProfileReport`]: struct.ProfileReport.html
+    pub fn profile<T>(&self, name: &'static str, update: impl FnOnce() -> T) -> T {
+        let started = self.profiler.start();
+        let result = update();
+        self.profiler.record(name, started);
+
+        result
+    }
+}
+
+/// The identifiers of the variables from which the [`UpdateContext`] is created.
+///
+/// [`UpdateContext`]: struct.UpdateContext.html
+pub(super) struct UpdateContextVariables {
+    ambient_pressure: QuantityIdentifier<Pressure>,
+    ambient_temperature: QuantityIdentifier<ThermodynamicTemperature>,
+    bank: QuantityIdentifier<Angle>,
+    ground_speed: QuantityIdentifier<Velocity>,
+    indicated_airspeed: QuantityIdentifier<Velocity>,
+    indicated_altitude: QuantityIdentifier<Length>,
+    is_on_ground: QuantityIdentifier<bool>,
+    lateral_acceleration: QuantityIdentifier<Acceleration>,
+    load_factor: QuantityIdentifier<f64>,
+    longitudinal_acceleration: QuantityIdentifier<Acceleration>,
+    mach_number: QuantityIdentifier<f64>,
+    pitch: QuantityIdentifier<Angle>,
+    pressure_altitude: QuantityIdentifier<Length>,
+    radio_altitude: QuantityIdentifier<Length>,
+    simulation_rate: QuantityIdentifier<f64>,
+    true_airspeed: QuantityIdentifier<Velocity>,
+    vertical_acceleration: QuantityIdentifier<Acceleration>,
+    vertical_speed: QuantityIdentifier<Velocity>,
+}
+impl UpdateContextVariables {
+    /// The identifiers are the same in every [`VariableRegistry`], as
+    /// each registry registers these variables first.
+    ///
+    /// [`VariableRegistry`]: struct.VariableRegistry.html
+    pub(super) fn new() -> Self {
+        UpdateContext::register_variables(&mut VariableRegistry::new())
+    }
 }
@@ -0,0 +1,645 @@
+use super::{UpdateContext, VariableQuantity};
+use std::{collections::HashMap, fmt, marker::PhantomData, ops::Deref, rc::Rc};
+
+/// The unit in which the simulator provides or expects the value of a variable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VariableUnit {
+    Bool,
+    Number,
+    Percent,
+    Degrees,
+    Celsius,
+    Feet,
+    FeetPerMinute,
+    FeetPerSecondSquared,
+    Knots,
+    Mach,
+    GForce,
+    Pounds,
+    PoundsPerHour,
+    Gallons,
+    Rpm,
+    Psi,
+    InchesOfMercury,
+    Volts,
+    Amperes,
+    AmpereHours,
+    Hertz,
+}
+impl VariableUnit {
+    /// The name of the unit as known by the simulator.
+    pub fn name(&self) -> &'static str {
+        match self {
+            VariableUnit::Bool => "Bool",
+            VariableUnit::Number => "Number",
+            VariableUnit::Percent => "Percent",
+            VariableUnit::Degrees => "Degrees",
+            VariableUnit::Celsius => "Celsius",
+            VariableUnit::Feet => "Feet",
+            VariableUnit::FeetPerMinute => "Feet per minute",
+            VariableUnit::FeetPerSecondSquared => "Feet per second squared",
+            VariableUnit::Knots => "Knots",
+            VariableUnit::Mach => "Mach",
+            VariableUnit::GForce => "GForce",
+            VariableUnit::Pounds => "Pounds",
+            VariableUnit::PoundsPerHour => "Pounds per hour",
+            VariableUnit::Gallons => "Gallons",
+            VariableUnit::Rpm => "RPM",
+            VariableUnit::Psi => "PSI",
+            VariableUnit::InchesOfMercury => "inHg",
+            VariableUnit::Volts => "Volts",
+            VariableUnit::Amperes => "Amperes",
+            VariableUnit::AmpereHours => "Ampere hours",
+            VariableUnit::Hertz => "Hertz",
+        }
+    }
+
+    /// Whether the unit measures a value without a physical dimension,
+    /// which is read and written as a plain `f64`.
+    pub fn is_dimensionless(&self) -> bool {
+        matches!(
+            self,
+            VariableUnit::Number | VariableUnit::Mach | VariableUnit::GForce
+        )
+    }
+}
+
+/// Whether the aircraft system simulation reads a variable from or writes
+/// a variable to the simulator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VariableDirection {
+    Read,
+    Write,
+    ReadWrite,
+}
+impl VariableDirection {
+    pub fn is_read(&self) -> bool {
+        matches!(self, VariableDirection::Read | VariableDirection::ReadWrite)
+    }
+
+    pub fn is_written(&self) -> bool {
+        matches!(
+            self,
+            VariableDirection::Write | VariableDirection::ReadWrite
+        )
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            VariableDirection::Read => "read",
+            VariableDirection::Write => "write",
+            VariableDirection::ReadWrite => "readwrite",
+        }
+    }
+
+    pub(super) fn combine(self, other: VariableDirection) -> VariableDirection {
+        if self == other {
+            self
+        } else {
+            VariableDirection::ReadWrite
+        }
+    }
+}
+
+/// A handle to a simulator variable, as returned by the [`VariableRegistry`].
+/// Handles of the same variable share the same index, which simulator implementations
+/// can use to look up the variable without hashing its name.
+///
+/// [`VariableRegistry`]: struct.VariableRegistry.html
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VariableIdentifier {
+    index: usize,
+    name: Rc<str>,
+    unit: VariableUnit,
+    direction: VariableDirection,
+}
+impl VariableIdentifier {
+    /// The zero based index of the variable within the registry which registered it.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn unit(&self) -> VariableUnit {
+        self.unit
+    }
+
+    pub fn direction(&self) -> VariableDirection {
+        self.direction
+    }
+}
+
+/// A handle to a simulator variable which measures a quantity, such as a `Velocity`,
+/// a `bool` or a dimensionless `f64`. The [`VariableRegistry`] only hands out the handle
+/// when the variable's unit measures the quantity, such that the quantity can always be
+/// read and written in that unit.
+///
+/// [`VariableRegistry`]: struct.VariableRegistry.html
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuantityIdentifier<T> {
+    identifier: VariableIdentifier,
+    quantity: PhantomData<T>,
+}
+impl<T> Deref for QuantityIdentifier<T> {
+    type Target = VariableIdentifier;
+
+    fn deref(&self) -> &VariableIdentifier {
+        &self.identifier
+    }
+}
+
+/// A handle which the [`VariableRegistry`] returns when registering a variable.
+///
+/// [`VariableRegistry`]: struct.VariableRegistry.html
+pub trait VariableHandle: Sized {
+    /// Returns the name of the quantity the handle reads and writes, when the unit
+    /// doesn't measure that quantity.
+    fn check_unit(unit: VariableUnit) -> Result<(), &'static str>;
+
+    fn from_identifier(identifier: VariableIdentifier) -> Self;
+}
+impl VariableHandle for VariableIdentifier {
+    fn check_unit(_: VariableUnit) -> Result<(), &'static str> {
+        Ok(())
+    }
+
+    fn from_identifier(identifier: VariableIdentifier) -> Self {
+        identifier
+    }
+}
+impl<T: VariableQuantity> VariableHandle for QuantityIdentifier<T> {
+    fn check_unit(unit: VariableUnit) -> Result<(), &'static str> {
+        if T::is_measured_in(unit) {
+            Ok(())
+        } else {
+            Err(T::NAME)
+        }
+    }
+
+    fn from_identifier(identifier: VariableIdentifier) -> Self {
+        Self {
+            identifier,
+            quantity: PhantomData,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum VariableRegistrationError {
+    /// The variable is already registered with another unit.
+    DifferentUnit {
+        name: String,
+        registered: VariableUnit,
+        requested: VariableUnit,
+    },
+    /// The unit doesn't measure the quantity of the requested handle.
+    UnitMismatch {
+        name: String,
+        unit: VariableUnit,
+        quantity: &'static str,
+    },
+}
+impl fmt::Display for VariableRegistrationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VariableRegistrationError::DifferentUnit {
+                name,
+                registered,
+                requested,
+            } => write!(
+                f,
+                "variable {} is registered with unit {} and cannot be registered with unit {}",
+                name,
+                registered.name(),
+                requested.name()
+            ),
+            VariableRegistrationError::UnitMismatch {
+                name,
+                unit,
+                quantity,
+            } => write!(
+                f,
+                "variable {} has unit {}, which doesn't measure a {}",
+                name,
+                unit.name(),
+                quantity
+            ),
+        }
+    }
+}
+impl std::error::Error for VariableRegistrationError {}
+
+/// A variable registered in the [`VariableRegistry`].
+///
+/// [`VariableRegistry`]: struct.VariableRegistry.html
+#[derive(Clone, Debug, PartialEq)]
+pub struct VariableDefinition {
+    name: Rc<str>,
+    unit: VariableUnit,
+    direction: VariableDirection,
+    owners: Vec<&'static str>,
+}
+impl VariableDefinition {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn unit(&self) -> VariableUnit {
+        self.unit
+    }
+
+    /// The combined direction of all registrations of the variable.
+    pub fn direction(&self) -> VariableDirection {
+        self.direction
+    }
+
+    /// The owners which registered the variable, in the order in which they registered it.
+    pub fn owners(&self) -> &[&'static str] {
+        &self.owners
+    }
+}
+
+/// Contains every variable the aircraft system simulation reads from and writes to
+/// the simulator. Elements register their variables once when they are created,
+/// and use the returned [`VariableIdentifier`] for reading and writing.
+///
+/// Every registry starts out with the variables read by the [`UpdateContext`],
+/// such that those variables have the same index in any registry.
+///
+/// Each variable is attributed to the owners which registered it, such as the
+/// system it belongs to. Refer to [`with_owner`] for setting the owner.
+///
+/// # Examples
+/// ```rust
+/// # use systems::simulation::{QuantityIdentifier, SimulationElement, SimulatorReader,
+/// #    VariableRegistry, VariableUnit};
+/// struct MySimulationElement {
+///     is_on_id: QuantityIdentifier<bool>,
+///     is_on: bool,
+/// }
+/// impl MySimulationElement {
+///     fn new(registry: &mut VariableRegistry) -> Self {
+///         Self {
+///             is_on_id: registry.register_read("MY_SIMULATOR_ELEMENT_IS_ON", VariableUnit::Bool),
+///             is_on: false,
+///         }
+///     }
+/// }
+/// impl SimulationElement for MySimulationElement {
+///     fn read(&mut self, reader: &mut SimulatorReader) {
+///         self.is_on = reader.read_bool(&self.is_on_id);
+///     }
+/// }
+///
+/// let mut registry = VariableRegistry::new();
+/// MySimulationElement::new(&mut registry);
+///
+/// let definition = registry.find("MY_SIMULATOR_ELEMENT_IS_ON").unwrap();
+/// assert_eq!(definition.unit(), VariableUnit::Bool);
+/// ```
+/// [`VariableIdentifier`]: struct.VariableIdentifier.html
+/// [`UpdateContext`]: struct.UpdateContext.html
+/// [`with_owner`]: #method.with_owner
+pub struct VariableRegistry {
+    definitions: Vec<VariableDefinition>,
+    indices: HashMap<Rc<str>, usize>,
+    owner: &'static str,
+}
+impl VariableRegistry {
+    /// The owner of the variables read by the [`UpdateContext`].
+    ///
+    /// [`UpdateContext`]: struct.UpdateContext.html
+    pub const UPDATE_CONTEXT_OWNER: &'static str = "update_context";
+
+    /// The owner of variables registered outside of [`with_owner`].
+    ///
+    /// [`with_owner`]: #method.with_owner
+    pub const DEFAULT_OWNER: &'static str = "aircraft";
+
+    pub fn new() -> Self {
+        let mut registry = Self {
+            definitions: Vec::new(),
+            indices: HashMap::new(),
+            owner: VariableRegistry::DEFAULT_OWNER,
+        };
+        registry.with_owner(VariableRegistry::UPDATE_CONTEXT_OWNER, |registry| {
+            UpdateContext::register_variables(registry)
+        });
+
+        registry
+    }
+
+    /// Attributes the variables registered by `register` to the given owner,
+    /// e.g. the name of the system being created.
+    pub fn with_owner<T, U: FnOnce(&mut VariableRegistry) -> T>(
+        &mut self,
+        owner: &'static str,
+        register: U,
+    ) -> T {
+        let previous_owner = std::mem::replace(&mut self.owner, owner);
+        let result = register(self);
+        self.owner = previous_owner;
+
+        result
+    }
+
+    /// Registers a variable which is read from the simulator.
+    pub fn register_read<T: VariableHandle>(&mut self, name: &str, unit: VariableUnit) -> T {
+        self.register(name, unit, VariableDirection::Read)
+    }
+
+    /// Registers a variable which is written to the simulator.
+    pub fn register_write<T: VariableHandle>(&mut self, name: &str, unit: VariableUnit) -> T {
+        self.register(name, unit, VariableDirection::Write)
+    }
+
+    /// Registers a variable which is both read from and written to the simulator.
+    pub fn register_read_write<T: VariableHandle>(&mut self, name: &str, unit: VariableUnit) -> T {
+        self.register(name, unit, VariableDirection::ReadWrite)
+    }
+
+    /// Registers the variable with the given name, like [`try_register`] does.
+    ///
+    /// # Panics
+    /// Panics when the variable cannot be registered, as a mistake in registering
+    /// a variable is a mistake in the aircraft's code.
+    ///
+    /// [`try_register`]: #method.try_register
+    pub fn register<T: VariableHandle>(
+        &mut self,
+        name: &str,
+        unit: VariableUnit,
+        direction: VariableDirection,
+    ) -> T {
+        self.try_register(name, unit, direction)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Registers the variable with the given name. Registering a variable which
+    /// is already registered returns a handle with the same index.
+    ///
+    /// Returns an error when the variable is already registered with a different unit,
+    /// or when the unit doesn't measure the quantity of the requested handle.
+    pub fn try_register<T: VariableHandle>(
+        &mut self,
+        name: &str,
+        unit: VariableUnit,
+        direction: VariableDirection,
+    ) -> Result<T, VariableRegistrationError> {
+        T::check_unit(unit).map_err(|quantity| VariableRegistrationError::UnitMismatch {
+            name: name.to_owned(),
+            unit,
+            quantity,
+        })?;
+
+        let index = match self.indices.get(name) {
+            Some(&index) => {
+                let definition = &mut self.definitions[index];
+                if definition.unit != unit {
+                    return Err(VariableRegistrationError::DifferentUnit {
+                        name: name.to_owned(),
+                        registered: definition.unit,
+                        requested: unit,
+                    });
+                }
+                definition.direction = definition.direction.combine(direction);
+                if !definition.owners.contains(&self.owner) {
+                    definition.owners.push(self.owner);
+                }
+
+                index
+            }
+            None => {
+                let index = self.definitions.len();
+                let name: Rc<str> = Rc::from(name);
+                self.definitions.push(VariableDefinition {
+                    name: Rc::clone(&name),
+                    unit,
+                    direction,
+                    owners: vec![self.owner],
+                });
+                self.indices.insert(name, index);
+
+                index
+            }
+        };
+
+        Ok(T::from_identifier(VariableIdentifier {
+            index,
+            name: Rc::clone(&self.definitions[index].name),
+            unit,
+            direction,
+        }))
+    }
+
+    /// The registered variables, ordered by their index.
+    pub fn definitions(&self) -> &[VariableDefinition] {
+        &self.definitions
+    }
+
+    pub fn find(&self, name: &str) -> Option<&VariableDefinition> {
+        self.indices
+            .get(name)
+            .map(|&index| &self.definitions[index])
+    }
+}
+impl Default for VariableRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uom::si::f64::*;
+
+    #[test]
+    fn registers_a_variable_once() {
+        let mut registry = VariableRegistry::new();
+        let count = registry.definitions().len();
+
+        let first: VariableIdentifier = registry.register_read("TEST", VariableUnit::Number);
+        let second: VariableIdentifier = registry.register_read("TEST", VariableUnit::Number);
+
+        assert_eq!(first, second);
+        assert_eq!(registry.definitions().len(), count + 1);
+        assert_eq!(registry.definitions()[first.index()].name(), "TEST");
+    }
+
+    #[test]
+    fn different_variables_have_different_indices() {
+        let mut registry = VariableRegistry::new();
+
+        let first: VariableIdentifier = registry.register_read("FIRST", VariableUnit::Number);
+        let second: VariableIdentifier = registry.register_read("SECOND", VariableUnit::Number);
+
+        assert_ne!(first.index(), second.index());
+    }
+
+    #[test]
+    fn handle_has_the_direction_it_was_registered_with() {
+        let mut registry = VariableRegistry::new();
+
+        let read: VariableIdentifier = registry.register_read("TEST", VariableUnit::Bool);
+        let write: VariableIdentifier = registry.register_write("TEST", VariableUnit::Bool);
+
+        assert_eq!(read.index(), write.index());
+        assert_eq!(read.direction(), VariableDirection::Read);
+        assert_eq!(write.direction(), VariableDirection::Write);
+    }
+
+    #[test]
+    fn definition_combines_the_directions_of_all_registrations() {
+        let mut registry = VariableRegistry::new();
+
+        registry.register_read::<VariableIdentifier>("TEST", VariableUnit::Bool);
+        registry.register_write::<VariableIdentifier>("TEST", VariableUnit::Bool);
+
+        assert_eq!(
+            registry.find("TEST").unwrap().direction(),
+            VariableDirection::ReadWrite
+        );
+    }
+
+    #[test]
+    fn definition_contains_the_owners_of_all_registrations() {
+        let mut registry = VariableRegistry::new();
+
+        registry.register_read::<VariableIdentifier>("TEST", VariableUnit::Bool);
+        registry.with_owner("electrical", |registry| {
+            registry.register_write::<VariableIdentifier>("TEST", VariableUnit::Bool);
+            registry.register_write::<VariableIdentifier>("TEST", VariableUnit::Bool);
+        });
+
+        assert_eq!(
+            registry.find("TEST").unwrap().owners(),
+            [VariableRegistry::DEFAULT_OWNER, "electrical"]
+        );
+    }
+
+    #[test]
+    fn update_context_variables_are_owned_by_the_update_context() {
+        let registry = VariableRegistry::new();
+
+        assert!(registry
+            .definitions()
+            .iter()
+            .all(|definition| definition.owners() == [VariableRegistry::UPDATE_CONTEXT_OWNER]));
+    }
+
+    #[test]
+    fn quantity_handle_is_registered_in_the_given_unit() {
+        let mut registry = VariableRegistry::new();
+
+        let identifier: QuantityIdentifier<Velocity> =
+            registry.register_read("TEST", VariableUnit::Knots);
+
+        assert_eq!(identifier.unit(), VariableUnit::Knots);
+        assert_eq!(registry.definitions()[identifier.index()].name(), "TEST");
+    }
+
+    #[test]
+    fn registering_a_quantity_in_a_unit_which_does_not_measure_it_fails() {
+        let mut registry = VariableRegistry::new();
+        let count = registry.definitions().len();
+
+        let result: Result<QuantityIdentifier<Velocity>, _> =
+            registry.try_register("TEST", VariableUnit::Feet, VariableDirection::Read);
+
+        assert_eq!(
+            result,
+            Err(VariableRegistrationError::UnitMismatch {
+                name: "TEST".to_owned(),
+                unit: VariableUnit::Feet,
+                quantity: Velocity::NAME,
+            })
+        );
+        assert_eq!(registry.definitions().len(), count);
+    }
+
+    #[test]
+    fn registering_a_bool_in_a_unit_other_than_bool_fails() {
+        let mut registry = VariableRegistry::new();
+
+        let result: Result<QuantityIdentifier<bool>, _> =
+            registry.try_register("TEST", VariableUnit::Percent, VariableDirection::Read);
+
+        assert_eq!(
+            result,
+            Err(VariableRegistrationError::UnitMismatch {
+                name: "TEST".to_owned(),
+                unit: VariableUnit::Percent,
+                quantity: bool::NAME,
+            })
+        );
+    }
+
+    #[test]
+    fn registering_a_dimensionless_f64_in_a_unit_with_a_dimension_fails() {
+        let mut registry = VariableRegistry::new();
+
+        let result: Result<QuantityIdentifier<f64>, _> =
+            registry.try_register("TEST", VariableUnit::Feet, VariableDirection::Write);
+
+        assert_eq!(
+            result,
+            Err(VariableRegistrationError::UnitMismatch {
+                name: "TEST".to_owned(),
+                unit: VariableUnit::Feet,
+                quantity: f64::NAME,
+            })
+        );
+    }
+
+    #[test]
+    fn registering_a_variable_with_a_different_unit_fails() {
+        let mut registry = VariableRegistry::new();
+        registry.register_read::<VariableIdentifier>("TEST", VariableUnit::Knots);
+
+        let result: Result<VariableIdentifier, _> =
+            registry.try_register("TEST", VariableUnit::Feet, VariableDirection::Write);
+
+        assert_eq!(
+            result,
+            Err(VariableRegistrationError::DifferentUnit {
+                name: "TEST".to_owned(),
+                registered: VariableUnit::Knots,
+                requested: VariableUnit::Feet,
+            })
+        );
+        assert_eq!(
+            registry.find("TEST").unwrap().direction(),
+            VariableDirection::Read
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn registering_a_variable_with_a_different_unit_panics() {
+        let mut registry = VariableRegistry::new();
+
+        registry.register_read::<VariableIdentifier>("TEST", VariableUnit::Knots);
+        registry.register_read::<VariableIdentifier>("TEST", VariableUnit::Feet);
+    }
+
+    #[test]
+    fn update_context_variables_have_the_same_index_in_every_registry() {
+        let first = VariableRegistry::new();
+        let mut second = VariableRegistry::new();
+        second.register_read::<VariableIdentifier>("TEST", VariableUnit::Number);
+
+        assert_eq!(
+            first.definitions(),
+            &second.definitions()[..first.definitions().len()]
+        );
+    }
+
+    #[test]
+    fn unknown_variable_is_not_found() {
+        assert!(VariableRegistry::new().find("UNKNOWN").is_none());
+    }
+}
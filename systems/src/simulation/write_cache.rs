@@ -0,0 +1,321 @@
+use super::{SimulatorReaderWriter, VariableDirection, VariableIdentifier, VariableRegistry};
+use std::time::Duration;
+
+/// The number of writes the aircraft system simulation passed on to the simulator,
+/// and the number of writes it skipped because the value didn't change.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WriteStatistics {
+    written: u64,
+    skipped: u64,
+}
+impl WriteStatistics {
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+
+    pub fn total(&self) -> u64 {
+        self.written + self.skipped
+    }
+}
+
+/// Remembers the values last written to the simulator, such that writes of
+/// unchanged values can be skipped. All values are written again once every refresh
+/// interval, in case something else in the simulator changed them in the meantime.
+///
+/// Without a refresh interval every write is passed on.
+pub(super) struct WriteCache {
+    refresh_interval: Option<Duration>,
+    since_refresh: Duration,
+    is_refreshing: bool,
+    /// Whether the variable is also read, indexed by the index of its variable identifier.
+    is_read: Vec<bool>,
+    /// The values last written, indexed by the index of their variable identifier.
+    values: Vec<Option<f64>>,
+    statistics: WriteStatistics,
+}
+impl WriteCache {
+    pub fn disabled() -> Self {
+        Self {
+            refresh_interval: None,
+            since_refresh: Duration::from_secs(0),
+            is_refreshing: false,
+            is_read: Vec::new(),
+            values: Vec::new(),
+            statistics: WriteStatistics::default(),
+        }
+    }
+
+    /// Creates a cache for the variables of the registry. A variable is also read when
+    /// any of its registrations reads it, regardless of the handle used for writing it.
+    pub fn new(refresh_interval: Duration, registry: &VariableRegistry) -> Self {
+        Self {
+            refresh_interval: Some(refresh_interval),
+            is_read: registry
+                .definitions()
+                .iter()
+                .map(|definition| definition.direction() == VariableDirection::ReadWrite)
+                .collect(),
+            ..Self::disabled()
+        }
+    }
+
+    /// Called before the simulation writes the results of a tick with the given delta.
+    pub fn begin_writing(&mut self, delta: Duration) {
+        if let Some(refresh_interval) = self.refresh_interval {
+            self.since_refresh += delta;
+            self.is_refreshing = self.since_refresh >= refresh_interval;
+            if self.is_refreshing {
+                self.since_refresh = Duration::from_secs(0);
+            }
+        }
+    }
+
+    pub fn statistics(&self) -> WriteStatistics {
+        self.statistics
+    }
+
+    /// Returns whether the value is to be written to the simulator.
+    /// Variables which are also read from the simulator are always written,
+    /// as the simulator is expected to change them. The same goes for variables
+    /// unknown to the registry.
+    fn should_write(&mut self, identifier: &VariableIdentifier, value: f64) -> bool {
+        let should_write = self.refresh_interval.is_none()
+            || self.is_refreshing
+            || self
+                .is_read
+                .get(identifier.index())
+                .copied()
+                .unwrap_or(true)
+            || !self.is_unchanged(identifier, value);
+
+        if should_write {
+            self.statistics.written += 1;
+            self.remember(identifier, value);
+        } else {
+            self.statistics.skipped += 1;
+        }
+
+        should_write
+    }
+
+    fn is_unchanged(&self, identifier: &VariableIdentifier, value: f64) -> bool {
+        // Compares the bits, such that a NaN is unchanged when it is written again.
+        matches!(
+            self.values.get(identifier.index()),
+            Some(Some(last)) if last.to_bits() == value.to_bits()
+        )
+    }
+
+    fn remember(&mut self, identifier: &VariableIdentifier, value: f64) {
+        if self.refresh_interval.is_none() {
+            return;
+        }
+
+        let index = identifier.index();
+        if self.values.len() <= index {
+            self.values.resize(index + 1, None);
+        }
+
+        self.values[index] = Some(value);
+    }
+}
+
+/// Passes writes on to the simulator when the [`WriteCache`] permits it, and
+/// passes the skipped writes on as unchanged writes. Reads are always passed on.
+///
+/// [`WriteCache`]: struct.WriteCache.html
+pub(super) struct CachingReaderWriter<'a> {
+    simulator_read_writer: &'a mut dyn SimulatorReaderWriter,
+    cache: &'a mut WriteCache,
+}
+impl<'a> CachingReaderWriter<'a> {
+    pub fn new(
+        simulator_read_writer: &'a mut dyn SimulatorReaderWriter,
+        cache: &'a mut WriteCache,
+    ) -> Self {
+        Self {
+            simulator_read_writer,
+            cache,
+        }
+    }
+}
+impl SimulatorReaderWriter for CachingReaderWriter<'_> {
+    fn read(&mut self, identifier: &VariableIdentifier) -> f64 {
+        self.simulator_read_writer.read(identifier)
+    }
+
+    fn write(&mut self, identifier: &VariableIdentifier, value: f64) {
+        if self.cache.should_write(identifier, value) {
+            self.simulator_read_writer.write(identifier, value);
+        } else {
+            self.simulator_read_writer
+                .write_unchanged(identifier, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{InMemoryReaderWriter, VariableUnit};
+
+    #[derive(Default)]
+    struct TestWriter {
+        writes: Vec<f64>,
+        unchanged_writes: Vec<f64>,
+    }
+    impl SimulatorReaderWriter for TestWriter {
+        fn read(&mut self, _: &VariableIdentifier) -> f64 {
+            0.
+        }
+
+        fn write(&mut self, _: &VariableIdentifier, value: f64) {
+            self.writes.push(value);
+        }
+
+        fn write_unchanged(&mut self, _: &VariableIdentifier, value: f64) {
+            self.unchanged_writes.push(value);
+        }
+    }
+
+    fn write_ticks_to(
+        writer: &mut TestWriter,
+        cache: &mut WriteCache,
+        identifier: &VariableIdentifier,
+        values: &[f64],
+    ) {
+        for value in values {
+            cache.begin_writing(Duration::from_millis(100));
+            CachingReaderWriter::new(writer, cache).write(identifier, *value);
+        }
+    }
+
+    fn write_ticks(
+        cache: &mut WriteCache,
+        identifier: &VariableIdentifier,
+        values: &[f64],
+    ) -> Vec<f64> {
+        let mut writer = TestWriter::default();
+        write_ticks_to(&mut writer, cache, identifier, values);
+
+        writer.writes
+    }
+
+    fn write_variable(refresh_interval: Duration) -> (WriteCache, VariableIdentifier) {
+        let mut registry = VariableRegistry::new();
+        let identifier = registry.register_write("TEST", VariableUnit::Number);
+
+        (WriteCache::new(refresh_interval, &registry), identifier)
+    }
+
+    #[test]
+    fn without_refresh_interval_writes_every_value() {
+        let (_, identifier) = write_variable(Duration::from_secs(10));
+        let mut cache = WriteCache::disabled();
+
+        assert_eq!(
+            write_ticks(&mut cache, &identifier, &[1., 1., 1.]),
+            vec![1., 1., 1.]
+        );
+    }
+
+    #[test]
+    fn skips_unchanged_values() {
+        let (mut cache, identifier) = write_variable(Duration::from_secs(10));
+
+        assert_eq!(
+            write_ticks(&mut cache, &identifier, &[1., 1., 2., 2., 1.]),
+            vec![1., 2., 1.]
+        );
+    }
+
+    #[test]
+    fn passes_skipped_values_on_as_unchanged_writes() {
+        let (mut cache, identifier) = write_variable(Duration::from_secs(10));
+        let mut writer = TestWriter::default();
+
+        write_ticks_to(&mut writer, &mut cache, &identifier, &[1., 1., 2., 2.]);
+
+        assert_eq!(writer.unchanged_writes, vec![1., 2.]);
+    }
+
+    #[test]
+    fn writes_unchanged_values_once_every_refresh_interval() {
+        let (mut cache, identifier) = write_variable(Duration::from_millis(300));
+
+        assert_eq!(write_ticks(&mut cache, &identifier, &[1.; 7]).len(), 3);
+    }
+
+    #[test]
+    fn always_writes_variables_which_are_also_read() {
+        let mut registry = VariableRegistry::new();
+        let identifier = registry.register_read_write("TEST", VariableUnit::Number);
+        let mut cache = WriteCache::new(Duration::from_secs(10), &registry);
+
+        assert_eq!(
+            write_ticks(&mut cache, &identifier, &[1., 1.]),
+            vec![1., 1.]
+        );
+    }
+
+    #[test]
+    fn always_writes_variables_which_another_registration_reads() {
+        let mut registry = VariableRegistry::new();
+        let identifier: VariableIdentifier = registry.register_write("TEST", VariableUnit::Number);
+        registry.register_read::<VariableIdentifier>("TEST", VariableUnit::Number);
+        let mut cache = WriteCache::new(Duration::from_secs(10), &registry);
+
+        assert_eq!(
+            write_ticks(&mut cache, &identifier, &[1., 1.]),
+            vec![1., 1.]
+        );
+    }
+
+    #[test]
+    fn always_writes_variables_unknown_to_the_registry() {
+        let (_, identifier) = write_variable(Duration::from_secs(10));
+        let mut cache = WriteCache::new(Duration::from_secs(10), &VariableRegistry::new());
+
+        assert_eq!(
+            write_ticks(&mut cache, &identifier, &[1., 1.]),
+            vec![1., 1.]
+        );
+    }
+
+    #[test]
+    fn unchanged_nan_is_skipped() {
+        let (mut cache, identifier) = write_variable(Duration::from_secs(10));
+
+        assert_eq!(
+            write_ticks(&mut cache, &identifier, &[f64::NAN, f64::NAN]).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn counts_written_and_skipped_values() {
+        let (mut cache, identifier) = write_variable(Duration::from_secs(10));
+        write_ticks(&mut cache, &identifier, &[1., 1., 2.]);
+
+        assert_eq!(cache.statistics().written(), 2);
+        assert_eq!(cache.statistics().skipped(), 1);
+        assert_eq!(cache.statistics().total(), 3);
+    }
+
+    #[test]
+    fn reads_are_passed_on() {
+        let identifier = VariableRegistry::new().register_read("TEST", VariableUnit::Number);
+        let mut reader_writer = InMemoryReaderWriter::new();
+        reader_writer.write_f64("TEST", 5.);
+        let mut cache = WriteCache::new(Duration::from_secs(10), &VariableRegistry::new());
+
+        assert_eq!(
+            CachingReaderWriter::new(&mut reader_writer, &mut cache).read(&identifier),
+            5.
+        );
+    }
+}